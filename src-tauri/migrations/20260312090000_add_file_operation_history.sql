@@ -0,0 +1,68 @@
+-- FileOperationEngine history, undo/redo stacks, transactions and version
+-- snapshots, so undo survives restarts. Timestamps are unix milliseconds.
+CREATE TABLE IF NOT EXISTS file_operations (
+    id TEXT PRIMARY KEY,
+    description TEXT NOT NULL,
+    workspace_id TEXT,
+    task_id TEXT,
+    run_id TEXT,
+    transaction_id TEXT,
+    changes_json TEXT NOT NULL,
+    versions_json TEXT NOT NULL DEFAULT '[]',
+    undoable INTEGER NOT NULL DEFAULT 0, -- still offered by basic undo
+    status TEXT NOT NULL DEFAULT 'applied', -- 'applied', 'undone'
+    created_at BIGINT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_file_operations_created_at
+    ON file_operations(created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_file_operations_workspace
+    ON file_operations(workspace_id, created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_file_operations_task_id
+    ON file_operations(task_id);
+CREATE INDEX IF NOT EXISTS idx_file_operations_run_id
+    ON file_operations(run_id);
+
+CREATE TABLE IF NOT EXISTS file_history_stacks (
+    stack TEXT NOT NULL, -- 'undo', 'redo'
+    stack_key TEXT NOT NULL,
+    position INTEGER NOT NULL,
+    operation_id TEXT NOT NULL,
+    can_redo INTEGER NOT NULL DEFAULT 1,
+    PRIMARY KEY(stack, stack_key, position)
+);
+
+CREATE INDEX IF NOT EXISTS idx_file_history_stacks_operation
+    ON file_history_stacks(operation_id);
+
+CREATE TABLE IF NOT EXISTS file_transactions (
+    id TEXT PRIMARY KEY,
+    description TEXT NOT NULL,
+    state TEXT NOT NULL, -- 'active', 'committed', 'rolled_back', 'failed'
+    workspace_id TEXT,
+    task_id TEXT,
+    run_id TEXT,
+    operations_json TEXT NOT NULL,
+    snapshots_json TEXT NOT NULL,
+    start_time BIGINT NOT NULL,
+    end_time BIGINT
+);
+
+CREATE INDEX IF NOT EXISTS idx_file_transactions_run_id
+    ON file_transactions(run_id);
+CREATE INDEX IF NOT EXISTS idx_file_transactions_end_time
+    ON file_transactions(end_time);
+
+CREATE TABLE IF NOT EXISTS file_versions (
+    id TEXT PRIMARY KEY,
+    file_path TEXT NOT NULL,
+    version_number INTEGER NOT NULL,
+    version_path TEXT NOT NULL,
+    content_hash TEXT NOT NULL,
+    size BIGINT NOT NULL,
+    description TEXT NOT NULL,
+    created_at BIGINT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_file_versions_file_path
+    ON file_versions(file_path, version_number);
@@ -0,0 +1,12 @@
+-- Model token usage per agent and UTC day (YYYY-MM-DD), enforced against
+-- AirlockRateLimits.max_tokens_per_day across runs and restarts.
+CREATE TABLE IF NOT EXISTS agent_token_usage (
+    agent_id TEXT NOT NULL,
+    day TEXT NOT NULL,
+    prompt_tokens BIGINT NOT NULL DEFAULT 0,
+    completion_tokens BIGINT NOT NULL DEFAULT 0,
+    total_tokens BIGINT NOT NULL DEFAULT 0,
+    requests BIGINT NOT NULL DEFAULT 0,
+    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY(agent_id, day)
+);
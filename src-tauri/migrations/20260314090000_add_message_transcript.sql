@@ -0,0 +1,6 @@
+-- Full AgentMessage transcript for chat history: multimodal content parts,
+-- assistant tool calls and tool result linkage. `content` keeps the
+-- plain-text view used by the chat UI and title generation.
+ALTER TABLE messages ADD COLUMN content_json TEXT;
+ALTER TABLE messages ADD COLUMN tool_calls_json TEXT;
+ALTER TABLE messages ADD COLUMN tool_call_id TEXT;
@@ -0,0 +1,27 @@
+-- Rows replaced by a compaction summary, kept so the summary can be audited
+-- or expanded back into its source. `generation` is the chat's compaction
+-- count at archive time.
+CREATE TABLE IF NOT EXISTS message_archive (
+    archive_id INTEGER PRIMARY KEY AUTOINCREMENT,
+    chat_id TEXT NOT NULL,
+    generation INTEGER NOT NULL,
+    summary_message_id TEXT NOT NULL,
+    message_id TEXT NOT NULL,
+    role TEXT NOT NULL,
+    content TEXT NOT NULL,
+    content_json TEXT,
+    tool_calls_json TEXT,
+    tool_call_id TEXT,
+    created_at DATETIME,
+    archived_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY(chat_id) REFERENCES chats(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_message_archive_summary
+    ON message_archive(chat_id, summary_message_id);
+CREATE INDEX IF NOT EXISTS idx_message_archive_generation
+    ON message_archive(chat_id, generation);
+
+-- Fork lineage: the chat and message a forked chat copied its prefix from.
+ALTER TABLE chats ADD COLUMN parent_chat_id TEXT;
+ALTER TABLE chats ADD COLUMN forked_from_message_id TEXT;
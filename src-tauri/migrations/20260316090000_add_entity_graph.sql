@@ -0,0 +1,29 @@
+-- Entity knowledge graph: typed attribute facts on agent_entities plus relations.
+-- attribute '' marks a free-form note that never contradicts other notes.
+ALTER TABLE agent_entities ADD COLUMN attribute TEXT NOT NULL DEFAULT '';
+ALTER TABLE agent_entities ADD COLUMN entity_type TEXT;
+ALTER TABLE agent_entities ADD COLUMN source TEXT;
+ALTER TABLE agent_entities ADD COLUMN observations INTEGER NOT NULL DEFAULT 1;
+-- 'active' or 'contested' (a lower-confidence rival value for the same attribute)
+ALTER TABLE agent_entities ADD COLUMN status TEXT NOT NULL DEFAULT 'active';
+
+UPDATE agent_entities SET entity_key = lower(trim(entity_key));
+
+CREATE INDEX IF NOT EXISTS idx_entities_workspace_key_attribute
+    ON agent_entities(workspace_id, entity_key, attribute);
+
+CREATE TABLE IF NOT EXISTS agent_entity_relations (
+    id TEXT PRIMARY KEY,
+    workspace_id TEXT NOT NULL,
+    subject_key TEXT NOT NULL,
+    relation TEXT NOT NULL,
+    object_key TEXT NOT NULL,
+    confidence REAL NOT NULL DEFAULT 0.5,
+    observations INTEGER NOT NULL DEFAULT 1,
+    source TEXT,
+    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (workspace_id, subject_key, relation, object_key)
+);
+
+CREATE INDEX IF NOT EXISTS idx_entity_relations_object
+    ON agent_entity_relations(workspace_id, object_key);
@@ -1,3 +1,4 @@
+use crate::ai::agent::context_window::ContextWindow;
 use crate::ai::agent::runtime::AgentMessage;
 
 pub struct ContextBudget;
@@ -16,12 +17,16 @@ impl ContextBudget {
         let mut keepers = Vec::new();
         let mut overflowed = false;
 
-        // Iterate backwards to keep the most recent messages
-        for msg in messages.iter().rev() {
-            let msg_len = msg.content.as_text().len();
-            if current_chars + msg_len <= max_chars {
-                current_chars += msg_len;
-                keepers.push(msg.clone());
+        // Iterate backwards to keep the most recent messages. Tool-call groups
+        // (assistant + tool results) are kept or dropped as a unit.
+        for group in ContextWindow::tool_call_groups(messages).into_iter().rev() {
+            let group_len: usize = messages[group.clone()]
+                .iter()
+                .map(|msg| msg.content.as_text().len())
+                .sum();
+            if current_chars + group_len <= max_chars {
+                current_chars += group_len;
+                keepers.push(&messages[group]);
             } else {
                 overflowed = true;
                 break;
@@ -29,7 +34,7 @@ impl ContextBudget {
         }
 
         // Restore chronological order
-        keepers.reverse();
+        let keepers: Vec<AgentMessage> = keepers.into_iter().rev().flatten().cloned().collect();
 
         (keepers, overflowed)
     }
@@ -45,8 +50,13 @@ impl ContextBudget {
         let mut recovered = Vec::new();
         recovered.push(messages[0].clone());
 
-        let tail = &messages[messages.len() - 9..];
-        recovered.extend_from_slice(tail);
+        // Start the tail on a group boundary so no tool result loses its call.
+        let tail_start = messages.len() - 9;
+        for group in ContextWindow::tool_call_groups(&messages[1..]) {
+            if group.start + 1 >= tail_start {
+                recovered.extend_from_slice(&messages[group.start + 1..group.end + 1]);
+            }
+        }
 
         recovered
     }
@@ -1,7 +1,12 @@
 // Context Window Manager — Sliding window to prevent unbounded history growth.
 // Enforces max_tokens limit by evicting oldest non-system messages when exceeded.
+// An assistant message carrying `tool_calls` and its `role: "tool"` replies are
+// evicted together so providers never see a half-finished tool exchange.
 
 use crate::ai::agent::runtime::AgentMessage;
+use crate::ai::specs::AgentSpec;
+use std::ops::Range;
+use std::sync::Arc;
 
 /// Estimated average tokens per character for English text.
 /// Conservative: 1 token ≈ 4 chars for most models.
@@ -11,9 +16,47 @@ const CHARS_PER_TOKEN: usize = 4;
 const DEFAULT_MAX_TOKENS: usize = 120_000;
 const SEMANTIC_CONTEXT_BUDGET_DIVISOR: usize = 5; // 20%
 
+/// Per-message overhead for role/metadata framing.
+const MESSAGE_OVERHEAD_TOKENS: usize = 4;
+/// Per-tool-call overhead for id/type framing around name + arguments.
+const TOOL_CALL_OVERHEAD_TOKENS: usize = 8;
+
+/// Counts tokens for context budgeting. Implement this to plug in a
+/// model-specific tokenizer in place of the character heuristic.
+pub trait Tokenizer: Send + Sync {
+    fn count_tokens(&self, text: &str) -> usize;
+}
+
+/// Character-count heuristic (1 token ≈ `chars_per_token` chars).
+#[derive(Debug, Clone, Copy)]
+pub struct CharHeuristicTokenizer {
+    chars_per_token: usize,
+}
+
+impl CharHeuristicTokenizer {
+    pub fn new(chars_per_token: usize) -> Self {
+        Self {
+            chars_per_token: chars_per_token.max(1),
+        }
+    }
+}
+
+impl Default for CharHeuristicTokenizer {
+    fn default() -> Self {
+        Self::new(CHARS_PER_TOKEN)
+    }
+}
+
+impl Tokenizer for CharHeuristicTokenizer {
+    fn count_tokens(&self, text: &str) -> usize {
+        text.len() / self.chars_per_token
+    }
+}
+
 /// Manages the agent's context window to keep history within token limits.
 pub struct ContextWindow {
     max_tokens: usize,
+    tokenizer: Arc<dyn Tokenizer>,
 }
 
 impl ContextWindow {
@@ -25,24 +68,59 @@ impl ContextWindow {
             } else {
                 max_tokens
             },
+            tokenizer: Arc::new(CharHeuristicTokenizer::default()),
+        }
+    }
+
+    /// Window for an agent: its configured token limit, counted with the
+    /// spec's `chars_per_token` when set.
+    pub fn for_spec(spec: &AgentSpec) -> Self {
+        let window = Self::new(spec.memory_config.effective_max_tokens() as usize);
+        match spec.memory_config.retrieval.chars_per_token {
+            Some(chars) => {
+                window.with_tokenizer(Arc::new(CharHeuristicTokenizer::new(chars as usize)))
+            }
+            None => window,
         }
     }
 
-    /// Estimate the token count for a single message.
-    fn estimate_tokens(msg: &AgentMessage) -> usize {
-        let text_len = msg.content.as_text().len();
-        // tool_calls add overhead: ~50 tokens per call for function name + args
-        let tool_overhead = msg
+    /// Replace the default character heuristic with a custom tokenizer.
+    pub fn with_tokenizer(mut self, tokenizer: Arc<dyn Tokenizer>) -> Self {
+        self.tokenizer = tokenizer;
+        self
+    }
+
+    /// Tokenizer used for this window's budgeting.
+    pub fn tokenizer(&self) -> &dyn Tokenizer {
+        self.tokenizer.as_ref()
+    }
+
+    /// Count the tokens for a single message.
+    fn message_tokens(tokenizer: &dyn Tokenizer, msg: &AgentMessage) -> usize {
+        let text_tokens = tokenizer.count_tokens(&msg.content.as_text());
+        let tool_tokens: usize = msg
             .tool_calls
             .as_ref()
-            .map(|calls| calls.len() * 50)
+            .map(|calls| {
+                calls
+                    .iter()
+                    .map(|call| {
+                        tokenizer.count_tokens(&call.function.name)
+                            + tokenizer.count_tokens(&call.function.arguments)
+                            + TOOL_CALL_OVERHEAD_TOKENS
+                    })
+                    .sum()
+            })
             .unwrap_or(0);
-        (text_len / CHARS_PER_TOKEN) + tool_overhead + 4 // +4 for role/metadata overhead
+        text_tokens + tool_tokens + MESSAGE_OVERHEAD_TOKENS
     }
 
-    /// Estimate the total token count for a message list.
-    pub fn estimate_total_tokens(messages: &[AgentMessage]) -> usize {
-        messages.iter().map(Self::estimate_tokens).sum()
+    /// Count the tokens for a message list using this window's tokenizer.
+    pub fn count_total_tokens(&self, messages: &[AgentMessage]) -> usize {
+        messages
+            .iter()
+            .map(|msg| Self::message_tokens(self.tokenizer.as_ref(), msg))
+            .sum()
     }
 
     pub fn semantic_context_budget_tokens(&self) -> usize {
@@ -54,22 +132,70 @@ impl ContextWindow {
             return String::new();
         }
 
-        let max_chars = token_budget.saturating_mul(CHARS_PER_TOKEN);
-        if text.chars().count() <= max_chars {
+        if self.tokenizer.count_tokens(text) <= token_budget {
             return text.to_string();
         }
 
-        let truncated: String = text.chars().take(max_chars.saturating_sub(3)).collect();
-        format!("{}...", truncated)
+        // Binary search the longest char prefix (plus ellipsis) that fits the budget.
+        let chars: Vec<char> = text.chars().collect();
+        let (mut lo, mut hi) = (0usize, chars.len());
+        while lo < hi {
+            let mid = (lo + hi).div_ceil(2);
+            let candidate: String = chars[..mid].iter().collect();
+            // Reserve one token for the ellipsis.
+            if self.tokenizer.count_tokens(&candidate) < token_budget {
+                lo = mid;
+            } else {
+                hi = mid - 1;
+            }
+        }
+
+        let truncated: String = chars[..lo].iter().collect();
+        format!("{}...", truncated.trim_end())
+    }
+
+    /// Split non-system history into atomic eviction groups.
+    ///
+    /// An assistant message with `tool_calls` absorbs the `role: "tool"` replies
+    /// that follow it. Tool replies with no owning assistant message belong to
+    /// no group, so callers that rebuild history from groups drop them.
+    pub(crate) fn tool_call_groups(messages: &[AgentMessage]) -> Vec<Range<usize>> {
+        let mut groups = Vec::new();
+        let mut i = 0;
+
+        while i < messages.len() {
+            let msg = &messages[i];
+            if msg.role == "tool" {
+                i += 1;
+                continue;
+            }
+
+            let start = i;
+            i += 1;
+            let has_tool_calls = msg.role == "assistant"
+                && msg
+                    .tool_calls
+                    .as_ref()
+                    .is_some_and(|calls| !calls.is_empty());
+            if has_tool_calls {
+                while i < messages.len() && messages[i].role == "tool" {
+                    i += 1;
+                }
+            }
+            groups.push(start..i);
+        }
+
+        groups
     }
 
     /// Trim the history to fit within the max_tokens limit.
-    /// Preserves system messages (always kept) and the latest messages.
-    /// Removes oldest non-system messages first.
+    /// Preserves system messages and the first user turn (always kept), then
+    /// keeps the most recent groups that fit. Assistant tool-call messages and
+    /// their tool results are kept or evicted together.
     ///
     /// Returns the trimmed messages vector.
     pub fn trim_history(&self, messages: Vec<AgentMessage>) -> Vec<AgentMessage> {
-        let total = Self::estimate_total_tokens(&messages);
+        let total = self.count_total_tokens(&messages);
 
         if total <= self.max_tokens {
             return messages;
@@ -87,31 +213,45 @@ impl ContextWindow {
             }
         }
 
-        // System messages token budget
-        let system_tokens: usize = system_msgs.iter().map(Self::estimate_tokens).sum();
-        let available_tokens = self.max_tokens.saturating_sub(system_tokens);
+        // Pin the first user turn: it carries the task the agent is working on.
+        let pinned = match non_system.first() {
+            Some(msg) if msg.role == "user" => Some(non_system.remove(0)),
+            _ => None,
+        };
+
+        // System + pinned token budget
+        let reserved_tokens = self.count_total_tokens(&system_msgs)
+            + pinned
+                .as_ref()
+                .map(|msg| Self::message_tokens(self.tokenizer.as_ref(), msg))
+                .unwrap_or(0);
+        let available_tokens = self.max_tokens.saturating_sub(reserved_tokens);
 
-        // Keep as many recent non-system messages as fit in the budget
-        let mut kept: Vec<AgentMessage> = Vec::new();
+        // Keep as many recent groups as fit in the budget
+        let groups = Self::tool_call_groups(&non_system);
+        let mut kept_from = non_system.len();
         let mut used_tokens: usize = 0;
 
-        // Iterate from newest to oldest, keeping messages that fit
-        for msg in non_system.into_iter().rev() {
-            let msg_tokens = Self::estimate_tokens(&msg);
-            if used_tokens + msg_tokens <= available_tokens {
-                kept.push(msg);
-                used_tokens += msg_tokens;
+        // Iterate from newest to oldest, keeping whole groups that fit
+        for group in groups.iter().rev() {
+            let group_tokens = self.count_total_tokens(&non_system[group.clone()]);
+            if used_tokens + group_tokens <= available_tokens {
+                kept_from = group.start;
+                used_tokens += group_tokens;
             } else {
                 break; // Stop keeping once we can't fit more
             }
         }
 
-        // Reverse to restore chronological order
-        kept.reverse();
-
-        // Combine: system messages first, then trimmed history
+        // Combine: system messages first, then the pinned turn, then kept groups.
+        // Orphaned tool results inside the kept range are dropped.
+        let kept_groups = Self::tool_call_groups(&non_system[kept_from..]);
         let mut result = system_msgs;
-        result.extend(kept);
+        result.extend(pinned);
+        let tail: Vec<AgentMessage> = non_system.drain(kept_from..).collect();
+        for group in kept_groups {
+            result.extend_from_slice(&tail[group]);
+        }
         result
     }
 }
@@ -130,6 +270,57 @@ mod tests {
         }
     }
 
+    fn make_tool_call_msg(ids: &[&str]) -> AgentMessage {
+        AgentMessage {
+            role: "assistant".to_string(),
+            content: AgentContent::text(""),
+            tool_calls: Some(
+                ids.iter()
+                    .map(|id| crate::ai::provider_types::ToolCall {
+                        id: id.to_string(),
+                        r#type: "function".to_string(),
+                        extra_content: None,
+                        function: crate::ai::provider_types::FunctionCall {
+                            name: "read_file".to_string(),
+                            arguments: r#"{"path":"src/main.rs"}"#.to_string(),
+                        },
+                    })
+                    .collect(),
+            ),
+            tool_call_id: None,
+        }
+    }
+
+    fn make_tool_result(id: &str, text: &str) -> AgentMessage {
+        AgentMessage {
+            role: "tool".to_string(),
+            content: AgentContent::text(text),
+            tool_calls: None,
+            tool_call_id: Some(id.to_string()),
+        }
+    }
+
+    /// Every tool result must follow the assistant message that requested it.
+    fn assert_tool_pairs_intact(messages: &[AgentMessage]) {
+        let mut open_ids: Vec<String> = Vec::new();
+        for msg in messages {
+            match msg.role.as_str() {
+                "tool" => {
+                    let id = msg.tool_call_id.clone().unwrap();
+                    assert!(open_ids.contains(&id), "orphaned tool result {}", id);
+                }
+                _ => {
+                    open_ids = msg
+                        .tool_calls
+                        .iter()
+                        .flatten()
+                        .map(|call| call.id.clone())
+                        .collect();
+                }
+            }
+        }
+    }
+
     #[test]
     fn test_no_trim_under_limit() {
         let cw = ContextWindow::new(10_000);
@@ -144,13 +335,13 @@ mod tests {
 
     #[test]
     fn test_trim_evicts_oldest() {
-        // Very small window — only ~100 tokens
-        let cw = ContextWindow::new(100);
+        // Small window — fits system, the pinned first user turn and one more message
+        let cw = ContextWindow::new(150);
         let msgs = vec![
             make_msg("system", "System prompt"),
-            make_msg("user", &"A".repeat(200)),      // ~50 tokens
-            make_msg("assistant", &"B".repeat(200)), // ~50 tokens
-            make_msg("user", &"C".repeat(200)),      // ~50 tokens — this should be kept
+            make_msg("user", &"A".repeat(200)), // ~50 tokens — pinned first turn
+            make_msg("assistant", &"B".repeat(200)), // ~50 tokens — evicted
+            make_msg("user", &"C".repeat(200)), // ~50 tokens — evicted
             make_msg("assistant", &"D".repeat(200)), // ~50 tokens — this should be kept
         ];
         let trimmed = cw.trim_history(msgs);
@@ -171,4 +362,118 @@ mod tests {
         let trimmed = cw.trim_history(msgs);
         assert!(trimmed.iter().any(|m| m.role == "system"));
     }
+
+    #[test]
+    fn test_first_user_turn_pinned() {
+        let cw = ContextWindow::new(150);
+        let msgs = vec![
+            make_msg("system", "System prompt"),
+            make_msg("user", "Refactor the parser"),
+            make_msg("assistant", &"B".repeat(400)),
+            make_msg("user", &"C".repeat(200)),
+            make_msg("assistant", "Done"),
+        ];
+        let trimmed = cw.trim_history(msgs);
+        assert_eq!(trimmed[1].content.as_text(), "Refactor the parser");
+        assert_eq!(trimmed.last().unwrap().content.as_text(), "Done");
+        assert!(trimmed
+            .iter()
+            .all(|m| m.content.as_text() != "B".repeat(400)));
+    }
+
+    #[test]
+    fn test_tool_call_group_evicted_atomically() {
+        // The tool results alone would fit, but not together with their
+        // assistant message; the whole exchange must go.
+        let cw = ContextWindow::new(160);
+        let msgs = vec![
+            make_msg("system", "System prompt"),
+            make_msg("user", "Read the files"),
+            make_tool_call_msg(&["call_1", "call_2"]),
+            make_tool_result("call_1", &"x".repeat(160)),
+            make_tool_result("call_2", &"y".repeat(160)),
+            make_msg("assistant", &"z".repeat(200)),
+        ];
+        let trimmed = cw.trim_history(msgs);
+        assert_tool_pairs_intact(&trimmed);
+        assert!(trimmed.iter().all(|m| m.role != "tool"));
+        assert_eq!(trimmed.last().unwrap().content.as_text(), "z".repeat(200));
+    }
+
+    #[test]
+    fn test_tool_call_group_kept_whole() {
+        let cw = ContextWindow::new(200);
+        let msgs = vec![
+            make_msg("system", "System prompt"),
+            make_msg("user", "Read the files"),
+            make_msg("assistant", &"old".repeat(200)),
+            make_tool_call_msg(&["call_1", "call_2"]),
+            make_tool_result("call_1", "fn main() {}"),
+            make_tool_result("call_2", "mod parser;"),
+        ];
+        let trimmed = cw.trim_history(msgs);
+        assert_tool_pairs_intact(&trimmed);
+        assert_eq!(trimmed.iter().filter(|m| m.role == "tool").count(), 2);
+        assert!(trimmed
+            .iter()
+            .all(|m| m.content.as_text() != "old".repeat(200)));
+    }
+
+    #[test]
+    fn test_orphaned_tool_results_dropped() {
+        let cw = ContextWindow::new(60);
+        let msgs = vec![
+            make_msg("system", "System prompt"),
+            make_msg("user", "Go"),
+            make_msg("assistant", &"A".repeat(400)),
+            make_msg("assistant", "Short"),
+            make_tool_result("call_stale", "stale output"),
+            make_msg("assistant", "Next"),
+        ];
+        let trimmed = cw.trim_history(msgs);
+        assert_tool_pairs_intact(&trimmed);
+        assert!(trimmed.iter().all(|m| m.role != "tool"));
+        assert_eq!(trimmed.last().unwrap().content.as_text(), "Next");
+    }
+
+    #[test]
+    fn test_custom_tokenizer() {
+        struct WordTokenizer;
+        impl Tokenizer for WordTokenizer {
+            fn count_tokens(&self, text: &str) -> usize {
+                text.split_whitespace().count()
+            }
+        }
+
+        let cw = ContextWindow::new(1_000).with_tokenizer(Arc::new(WordTokenizer));
+        let msgs = vec![make_msg("user", "one two three")];
+        assert_eq!(cw.count_total_tokens(&msgs), 3 + MESSAGE_OVERHEAD_TOKENS);
+        assert_eq!(
+            cw.truncate_text_for_tokens("one two three four", 3),
+            "one two..."
+        );
+    }
+
+    #[test]
+    fn test_for_spec_uses_configured_chars_per_token() {
+        use crate::ai::specs::{AgentSkills, AgentSoul};
+
+        let mut spec = AgentSpec {
+            id: "tokenizer-test".to_string(),
+            version: "3.0.0".to_string(),
+            soul: AgentSoul::default(),
+            skills: AgentSkills::default(),
+            airlock: Default::default(),
+            memory_config: Default::default(),
+            connectors: Default::default(),
+            runtime: Default::default(),
+            signature: None,
+        };
+        let cw = ContextWindow::for_spec(&spec);
+        assert_eq!(cw.tokenizer().count_tokens("abcdefgh"), 2);
+
+        spec.memory_config.retrieval.chars_per_token = Some(2);
+        let cw = ContextWindow::for_spec(&spec);
+        assert_eq!(cw.tokenizer().count_tokens("abcdefgh"), 4);
+    }
 }
@@ -0,0 +1,660 @@
+//! Entity knowledge graph over `agent_entities` and `agent_entity_relations`.
+//!
+//! Facts are (entity, attribute, value) triples scoped to a memory workspace.
+//! Observing a known fact again merges confidence as a noisy-OR. A different
+//! value for the same named attribute is a contradiction: the higher-confidence
+//! value (newest on a tie) stays active and its rivals are kept as contested.
+//! Facts with an empty attribute are free-form notes and never contradict.
+
+use serde::{Deserialize, Serialize};
+use sqlx::{Pool, Sqlite, SqliteConnection};
+
+/// Repeated observations never make a fact certain.
+const MAX_CONFIDENCE: f32 = 0.99;
+/// Cap on entities whose facts are pulled in for one prompt.
+const MAX_MENTIONED_ENTITIES: usize = 16;
+
+const FACT_COLUMNS: &str =
+    "id, entity_key, entity_type, attribute, entity_value, confidence, observations, status, source";
+const RELATION_COLUMNS: &str =
+    "subject_key, relation, object_key, confidence, observations, source";
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum FactStatus {
+    Active,
+    /// A lower-confidence value for an attribute that has an active value.
+    Contested,
+}
+
+impl FactStatus {
+    fn from_db(status: &str) -> Self {
+        match status {
+            "contested" => FactStatus::Contested,
+            _ => FactStatus::Active,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct EntityFact {
+    pub id: String,
+    pub entity: String,
+    pub entity_type: Option<String>,
+    pub attribute: String,
+    pub value: String,
+    pub confidence: f32,
+    pub observations: i64,
+    pub status: FactStatus,
+    pub source: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct EntityRelation {
+    pub subject: String,
+    pub relation: String,
+    pub object: String,
+    pub confidence: f32,
+    pub observations: i64,
+    pub source: Option<String>,
+}
+
+/// Everything known about one entity.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct EntityProfile {
+    pub entity: String,
+    pub entity_type: Option<String>,
+    pub facts: Vec<EntityFact>,
+    pub relations: Vec<EntityRelation>,
+}
+
+/// A fact to record. `attribute` may be empty for a free-form note.
+#[derive(Debug, Clone, Default)]
+pub struct FactInput {
+    pub entity: String,
+    pub attribute: String,
+    pub value: String,
+    pub entity_type: Option<String>,
+    pub confidence: f32,
+    pub source: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct FactOutcome {
+    pub fact: EntityFact,
+    /// The value was already known and its confidence was raised.
+    pub reinforced: bool,
+    /// Other values recorded for the same attribute.
+    pub conflicts: Vec<EntityFact>,
+}
+
+/// Active facts and relations for the entities mentioned in a piece of text.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EntityContext {
+    pub facts: Vec<EntityFact>,
+    pub relations: Vec<EntityRelation>,
+}
+
+impl EntityContext {
+    pub fn is_empty(&self) -> bool {
+        self.facts.is_empty() && self.relations.is_empty()
+    }
+
+    /// One line per fact or relation, for the system prompt.
+    pub fn render(&self) -> String {
+        let mut lines = Vec::with_capacity(self.facts.len() + self.relations.len());
+        for fact in &self.facts {
+            let subject = if fact.attribute.is_empty() {
+                fact.entity.clone()
+            } else {
+                format!("{}.{}", fact.entity, fact.attribute)
+            };
+            lines.push(format!(
+                "- {}: {} (confidence {:.2})",
+                subject, fact.value, fact.confidence
+            ));
+        }
+        for relation in &self.relations {
+            lines.push(format!(
+                "- {} {} {} (confidence {:.2})",
+                relation.subject, relation.relation, relation.object, relation.confidence
+            ));
+        }
+        lines.join("\n")
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct FactRow {
+    id: String,
+    entity_key: String,
+    entity_type: Option<String>,
+    attribute: String,
+    entity_value: String,
+    confidence: f64,
+    observations: i64,
+    status: String,
+    source: Option<String>,
+}
+
+impl From<FactRow> for EntityFact {
+    fn from(row: FactRow) -> Self {
+        Self {
+            id: row.id,
+            entity: row.entity_key,
+            entity_type: row.entity_type,
+            attribute: row.attribute,
+            value: row.entity_value,
+            confidence: row.confidence as f32,
+            observations: row.observations,
+            status: FactStatus::from_db(&row.status),
+            source: row.source,
+        }
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct RelationRow {
+    subject_key: String,
+    relation: String,
+    object_key: String,
+    confidence: f64,
+    observations: i64,
+    source: Option<String>,
+}
+
+impl From<RelationRow> for EntityRelation {
+    fn from(row: RelationRow) -> Self {
+        Self {
+            subject: row.subject_key,
+            relation: row.relation,
+            object: row.object_key,
+            confidence: row.confidence as f32,
+            observations: row.observations,
+            source: row.source,
+        }
+    }
+}
+
+/// Canonical entity key: trimmed, lowercase, single-spaced.
+pub fn normalize_entity_key(name: &str) -> String {
+    name.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+/// Canonical attribute or relation name: lowercase snake_case.
+fn normalize_label(label: &str) -> String {
+    label
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join("_")
+        .to_lowercase()
+}
+
+fn merge_confidence(current: f32, observed: f32) -> f32 {
+    (1.0 - (1.0 - current) * (1.0 - observed)).min(MAX_CONFIDENCE)
+}
+
+/// Whether `key` occurs in `text` as a whole word or phrase.
+fn mentions(text: &str, key: &str) -> bool {
+    text.match_indices(key).any(|(start, matched)| {
+        let before = text[..start].chars().next_back();
+        let after = text[start + matched.len()..].chars().next();
+        !before.is_some_and(char::is_alphanumeric) && !after.is_some_and(char::is_alphanumeric)
+    })
+}
+
+#[derive(Debug, Clone)]
+pub struct EntityGraph {
+    db: Pool<Sqlite>,
+}
+
+impl EntityGraph {
+    pub fn new(db: Pool<Sqlite>) -> Self {
+        Self { db }
+    }
+
+    /// Record a fact, merging it with what is already known about the entity.
+    pub async fn remember_fact(
+        &self,
+        workspace_id: &str,
+        input: FactInput,
+    ) -> Result<FactOutcome, sqlx::Error> {
+        let entity = normalize_entity_key(&input.entity);
+        let attribute = normalize_label(&input.attribute);
+        let value = input.value.trim().to_string();
+        if entity.is_empty() || value.is_empty() {
+            return Err(sqlx::Error::Protocol(
+                "a fact needs an entity and a value".to_string(),
+            ));
+        }
+        let confidence = input.confidence.clamp(0.0, MAX_CONFIDENCE);
+        let entity_type = input
+            .entity_type
+            .as_deref()
+            .map(normalize_label)
+            .filter(|t| !t.is_empty());
+
+        let mut tx = self.db.begin().await?;
+        let existing = Self::attribute_facts(&mut tx, workspace_id, &entity, &attribute).await?;
+        let known = existing
+            .iter()
+            .find(|fact| fact.value.to_lowercase() == value.to_lowercase());
+
+        let (fact_id, reinforced) = match known {
+            Some(fact) => {
+                sqlx::query(
+                    "UPDATE agent_entities
+                     SET confidence = ?, observations = observations + 1,
+                         entity_type = COALESCE(?, entity_type), source = COALESCE(?, source),
+                         updated_at = CURRENT_TIMESTAMP
+                     WHERE id = ?",
+                )
+                .bind(merge_confidence(fact.confidence, confidence))
+                .bind(&entity_type)
+                .bind(&input.source)
+                .bind(&fact.id)
+                .execute(&mut *tx)
+                .await?;
+                (fact.id.clone(), true)
+            }
+            None => {
+                let id = uuid::Uuid::new_v4().to_string();
+                sqlx::query(
+                    "INSERT INTO agent_entities
+                     (id, workspace_id, entity_key, entity_value, confidence, attribute, entity_type, source)
+                     VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+                )
+                .bind(&id)
+                .bind(workspace_id)
+                .bind(&entity)
+                .bind(&value)
+                .bind(confidence)
+                .bind(&attribute)
+                .bind(&entity_type)
+                .bind(&input.source)
+                .execute(&mut *tx)
+                .await?;
+                (id, false)
+            }
+        };
+
+        if !attribute.is_empty() {
+            // The strongest value wins; ties go to the most recently recorded one.
+            sqlx::query(
+                "UPDATE agent_entities
+                 SET status = CASE WHEN id = (
+                         SELECT id FROM agent_entities
+                         WHERE workspace_id = ? AND entity_key = ? AND attribute = ?
+                         ORDER BY confidence DESC, rowid DESC LIMIT 1
+                     ) THEN 'active' ELSE 'contested' END
+                 WHERE workspace_id = ? AND entity_key = ? AND attribute = ?",
+            )
+            .bind(workspace_id)
+            .bind(&entity)
+            .bind(&attribute)
+            .bind(workspace_id)
+            .bind(&entity)
+            .bind(&attribute)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        let facts = Self::attribute_facts(&mut tx, workspace_id, &entity, &attribute).await?;
+        tx.commit().await?;
+
+        let (mut own, others): (Vec<_>, Vec<_>) =
+            facts.into_iter().partition(|fact| fact.id == fact_id);
+        let fact = own.pop().ok_or(sqlx::Error::RowNotFound)?;
+        let conflicts = if attribute.is_empty() {
+            Vec::new()
+        } else {
+            others
+        };
+        Ok(FactOutcome {
+            fact,
+            reinforced,
+            conflicts,
+        })
+    }
+
+    /// Record that `subject` stands in `relation` to `object`.
+    pub async fn relate(
+        &self,
+        workspace_id: &str,
+        subject: &str,
+        relation: &str,
+        object: &str,
+        confidence: f32,
+        source: Option<&str>,
+    ) -> Result<EntityRelation, sqlx::Error> {
+        let subject = normalize_entity_key(subject);
+        let relation = normalize_label(relation);
+        let object = normalize_entity_key(object);
+        if subject.is_empty() || relation.is_empty() || object.is_empty() {
+            return Err(sqlx::Error::Protocol(
+                "a relation needs a subject, a relation name and an object".to_string(),
+            ));
+        }
+
+        sqlx::query(
+            "INSERT INTO agent_entity_relations
+             (id, workspace_id, subject_key, relation, object_key, confidence, source)
+             VALUES (?, ?, ?, ?, ?, ?, ?)
+             ON CONFLICT(workspace_id, subject_key, relation, object_key) DO UPDATE SET
+                confidence = MIN(?, 1 - (1 - confidence) * (1 - excluded.confidence)),
+                observations = observations + 1,
+                source = COALESCE(excluded.source, source),
+                updated_at = CURRENT_TIMESTAMP",
+        )
+        .bind(uuid::Uuid::new_v4().to_string())
+        .bind(workspace_id)
+        .bind(&subject)
+        .bind(&relation)
+        .bind(&object)
+        .bind(confidence.clamp(0.0, MAX_CONFIDENCE))
+        .bind(source)
+        .bind(MAX_CONFIDENCE)
+        .execute(&self.db)
+        .await?;
+
+        sqlx::query_as::<_, RelationRow>(&format!(
+            "SELECT {} FROM agent_entity_relations
+             WHERE workspace_id = ? AND subject_key = ? AND relation = ? AND object_key = ?",
+            RELATION_COLUMNS
+        ))
+        .bind(workspace_id)
+        .bind(&subject)
+        .bind(&relation)
+        .bind(&object)
+        .fetch_one(&self.db)
+        .await
+        .map(EntityRelation::from)
+    }
+
+    /// All facts and relations for `entity`, or `None` if it is unknown.
+    pub async fn recall_entity(
+        &self,
+        workspace_id: &str,
+        entity: &str,
+    ) -> Result<Option<EntityProfile>, sqlx::Error> {
+        let entity = normalize_entity_key(entity);
+        let facts: Vec<EntityFact> = sqlx::query_as::<_, FactRow>(&format!(
+            "SELECT {} FROM agent_entities
+             WHERE workspace_id = ? AND entity_key = ?
+             ORDER BY status = 'contested', attribute, confidence DESC",
+            FACT_COLUMNS
+        ))
+        .bind(workspace_id)
+        .bind(&entity)
+        .fetch_all(&self.db)
+        .await?
+        .into_iter()
+        .map(EntityFact::from)
+        .collect();
+        let relations = self.list_related(workspace_id, &entity, None, 50).await?;
+
+        if facts.is_empty() && relations.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(EntityProfile {
+            entity_type: facts.iter().find_map(|fact| fact.entity_type.clone()),
+            entity,
+            facts,
+            relations,
+        }))
+    }
+
+    /// Relations in either direction involving `entity`, strongest first.
+    pub async fn list_related(
+        &self,
+        workspace_id: &str,
+        entity: &str,
+        relation: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<EntityRelation>, sqlx::Error> {
+        let entity = normalize_entity_key(entity);
+        let relation = relation.map(normalize_label).filter(|r| !r.is_empty());
+        let rows = sqlx::query_as::<_, RelationRow>(&format!(
+            "SELECT {} FROM agent_entity_relations
+             WHERE workspace_id = ? AND (subject_key = ? OR object_key = ?)
+               AND (? IS NULL OR relation = ?)
+             ORDER BY confidence DESC, observations DESC
+             LIMIT ?",
+            RELATION_COLUMNS
+        ))
+        .bind(workspace_id)
+        .bind(&entity)
+        .bind(&entity)
+        .bind(&relation)
+        .bind(&relation)
+        .bind(limit as i64)
+        .fetch_all(&self.db)
+        .await?;
+        Ok(rows.into_iter().map(EntityRelation::from).collect())
+    }
+
+    /// Active facts and relations for known entities named in `text`,
+    /// strongest first and at most `limit` of each.
+    pub async fn relevant_context(
+        &self,
+        workspace_id: &str,
+        text: &str,
+        limit: usize,
+    ) -> Result<EntityContext, sqlx::Error> {
+        let haystack = normalize_entity_key(text);
+        let keys: Vec<(String,)> = sqlx::query_as(
+            "SELECT DISTINCT entity_key FROM agent_entities WHERE workspace_id = ?
+             UNION
+             SELECT subject_key FROM agent_entity_relations WHERE workspace_id = ?",
+        )
+        .bind(workspace_id)
+        .bind(workspace_id)
+        .fetch_all(&self.db)
+        .await?;
+        let mentioned: Vec<String> = keys
+            .into_iter()
+            .map(|(key,)| key)
+            .filter(|key| mentions(&haystack, key))
+            .take(MAX_MENTIONED_ENTITIES)
+            .collect();
+
+        let mut context = EntityContext::default();
+        for key in &mentioned {
+            let facts = sqlx::query_as::<_, FactRow>(&format!(
+                "SELECT {} FROM agent_entities
+                 WHERE workspace_id = ? AND entity_key = ? AND status = 'active'
+                 ORDER BY confidence DESC LIMIT ?",
+                FACT_COLUMNS
+            ))
+            .bind(workspace_id)
+            .bind(key)
+            .bind(limit as i64)
+            .fetch_all(&self.db)
+            .await?;
+            context
+                .facts
+                .extend(facts.into_iter().map(EntityFact::from));
+
+            let relations = sqlx::query_as::<_, RelationRow>(&format!(
+                "SELECT {} FROM agent_entity_relations
+                 WHERE workspace_id = ? AND subject_key = ?
+                 ORDER BY confidence DESC LIMIT ?",
+                RELATION_COLUMNS
+            ))
+            .bind(workspace_id)
+            .bind(key)
+            .bind(limit as i64)
+            .fetch_all(&self.db)
+            .await?;
+            context
+                .relations
+                .extend(relations.into_iter().map(EntityRelation::from));
+        }
+
+        context
+            .facts
+            .sort_by(|a, b| b.confidence.total_cmp(&a.confidence));
+        context.facts.truncate(limit);
+        context
+            .relations
+            .sort_by(|a, b| b.confidence.total_cmp(&a.confidence));
+        context.relations.truncate(limit);
+        Ok(context)
+    }
+
+    async fn attribute_facts(
+        conn: &mut SqliteConnection,
+        workspace_id: &str,
+        entity: &str,
+        attribute: &str,
+    ) -> Result<Vec<EntityFact>, sqlx::Error> {
+        let rows = sqlx::query_as::<_, FactRow>(&format!(
+            "SELECT {} FROM agent_entities
+             WHERE workspace_id = ? AND entity_key = ? AND attribute = ?
+             ORDER BY confidence DESC, rowid DESC",
+            FACT_COLUMNS
+        ))
+        .bind(workspace_id)
+        .bind(entity)
+        .bind(attribute)
+        .fetch_all(conn)
+        .await?;
+        Ok(rows.into_iter().map(EntityFact::from).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serial_test::serial;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    async fn graph() -> EntityGraph {
+        // Initialize libSQL's C state before sqlx, as in verification_test.
+        let _ = libsql::Builder::new_local(":memory:").build().await;
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+        EntityGraph::new(pool)
+    }
+
+    fn fact(entity: &str, attribute: &str, value: &str, confidence: f32) -> FactInput {
+        FactInput {
+            entity: entity.to_string(),
+            attribute: attribute.to_string(),
+            value: value.to_string(),
+            confidence,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_mentions_requires_word_boundaries() {
+        assert!(mentions("what does alice own?", "alice"));
+        assert!(mentions("deploy rainy cowork today", "rainy cowork"));
+        assert!(!mentions("malice aforethought", "alice"));
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_repeated_fact_merges_confidence_and_rival_is_contested() {
+        let graph = graph().await;
+
+        let first = graph
+            .remember_fact("ws", fact("Alice", "Role", "Staff Engineer", 0.5))
+            .await
+            .unwrap();
+        assert!(!first.reinforced);
+        assert_eq!(first.fact.entity, "alice");
+        assert_eq!(first.fact.attribute, "role");
+
+        let again = graph
+            .remember_fact("ws", fact("alice", "role", "staff engineer", 0.5))
+            .await
+            .unwrap();
+        assert!(again.reinforced);
+        assert_eq!(again.fact.observations, 2);
+        assert!((again.fact.confidence - 0.75).abs() < 1e-6);
+
+        let rival = graph
+            .remember_fact("ws", fact("alice", "role", "Manager", 0.6))
+            .await
+            .unwrap();
+        assert_eq!(rival.fact.status, FactStatus::Contested);
+        assert_eq!(rival.conflicts.len(), 1);
+        assert_eq!(rival.conflicts[0].status, FactStatus::Active);
+
+        // Notes never contradict, and other workspaces see nothing.
+        graph
+            .remember_fact("ws", fact("alice", "", "Prefers async reviews", 0.5))
+            .await
+            .unwrap();
+        let note = graph
+            .remember_fact("ws", fact("alice", "", "Lives in Lisbon", 0.5))
+            .await
+            .unwrap();
+        assert!(note.conflicts.is_empty());
+        assert!(graph
+            .recall_entity("other", "alice")
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_relations_and_prompt_context() {
+        let graph = graph().await;
+        graph
+            .remember_fact("ws", fact("Rainy Cowork", "language", "Rust", 0.9))
+            .await
+            .unwrap();
+        graph
+            .relate("ws", "Alice", "maintains", "Rainy Cowork", 0.7, None)
+            .await
+            .unwrap();
+        let merged = graph
+            .relate("ws", "alice", "Maintains", "rainy cowork", 0.5, None)
+            .await
+            .unwrap();
+        assert_eq!(merged.observations, 2);
+
+        let related = graph
+            .list_related("ws", "rainy cowork", Some("maintains"), 10)
+            .await
+            .unwrap();
+        assert_eq!(related.len(), 1);
+        assert_eq!(related[0].subject, "alice");
+
+        let profile = graph
+            .recall_entity("ws", "RAINY  cowork")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(profile.facts.len(), 1);
+        assert_eq!(profile.relations.len(), 1);
+
+        let context = graph
+            .relevant_context("ws", "Who maintains Rainy Cowork?", 8)
+            .await
+            .unwrap();
+        assert_eq!(context.facts.len(), 1);
+        assert!(context.render().contains("rainy cowork.language: Rust"));
+        let context = graph
+            .relevant_context("ws", "What should alice work on?", 8)
+            .await
+            .unwrap();
+        assert_eq!(context.relations.len(), 1);
+        assert!(graph
+            .relevant_context("ws", "unrelated question", 8)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+}
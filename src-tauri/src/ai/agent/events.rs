@@ -1,5 +1,6 @@
 use super::protocol::{SpecialistRole, SpecialistStatus, SupervisorPlan};
 use crate::ai::provider_types::ToolCall;
+use crate::services::tool_output::ToolOutputStream;
 use serde::Serialize;
 
 #[derive(Clone, Debug, Serialize)]
@@ -52,6 +53,12 @@ pub enum AgentEvent {
         id: String,
         result: String,
     },
+    /// Output a tool has produced while still running; `id` is the tool call's.
+    ToolOutputChunk {
+        id: String,
+        stream: ToolOutputStream,
+        text: String,
+    },
     Error(String),
     MemoryStored(String),
     SupervisorPlanCreated(SupervisorPlan),
@@ -60,4 +67,14 @@ pub enum AgentEvent {
     SpecialistCompleted(SpecialistCompletedPayload),
     SpecialistFailed(SpecialistFailedPayload),
     SupervisorSummary(SupervisorSummaryPayload),
+    /// Final event of a cancelled run; lists operations aborted mid-flight.
+    RunCancelled {
+        interrupted: Vec<String>,
+    },
+    /// Untrusted tool output matched injection detectors; `flags` names them.
+    PromptInjectionDetected {
+        tool_call_id: String,
+        tool: String,
+        flags: Vec<String>,
+    },
 }
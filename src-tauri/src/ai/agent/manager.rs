@@ -1,13 +1,38 @@
+use crate::ai::agent::runtime::{AgentContent, AgentMessage};
+use crate::ai::provider_types::ToolCall;
 use crate::ai::specs::manifest::AgentSpec;
 use crate::commands::memory::MemoryManagerState;
 use crate::db::Database;
 use serde::{Deserialize, Serialize};
-use sqlx::{Pool, Sqlite};
+use sqlx::{Pool, Sqlite, SqliteConnection};
 use std::sync::Arc;
 use tauri::State;
 
 pub const DEFAULT_LONG_CHAT_SCOPE_ID: &str = "global:long_chat:v1";
 
+/// A `messages` row as copied between the live history, the archive and forks.
+#[derive(sqlx::FromRow)]
+struct StoredMessageRow {
+    id: String,
+    role: String,
+    content: String,
+    content_json: Option<String>,
+    tool_calls_json: Option<String>,
+    tool_call_id: Option<String>,
+    created_at: Option<String>,
+}
+
+type HistoryWindowRow = (
+    i64,
+    String,
+    String,
+    String,
+    String,
+    String,
+    Option<String>,
+    Option<String>,
+);
+
 #[derive(Clone, Serialize, Deserialize, sqlx::FromRow)]
 pub struct AgentEntity {
     pub id: String,
@@ -32,6 +57,10 @@ pub struct ChatHistoryMessageDto {
     pub content: String,
     pub created_at: String,
     pub cursor_rowid: i64,
+    #[serde(default)]
+    pub tool_calls: Option<Vec<ToolCall>>,
+    #[serde(default)]
+    pub tool_call_id: Option<String>,
 }
 
 #[derive(Clone, Serialize, Deserialize)]
@@ -71,6 +100,20 @@ pub struct ChatSessionDto {
     pub updated_at: String,
     pub message_count: i64,
     pub last_message_at: Option<String>,
+    pub parent_chat_id: Option<String>,
+    pub forked_from_message_id: Option<String>,
+}
+
+#[derive(Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct ArchivedChatMessageDto {
+    pub id: String,
+    pub chat_scope_id: String,
+    pub summary_message_id: String,
+    pub generation: i64,
+    pub role: String,
+    pub content: String,
+    pub created_at: Option<String>,
+    pub archived_at: String,
 }
 
 impl AgentManager {
@@ -120,12 +163,96 @@ impl AgentManager {
         Ok(id)
     }
 
+    /// Persist a complete runtime message: multimodal parts, assistant tool
+    /// calls and the tool result linkage. `content` keeps the plain-text view.
+    pub async fn save_agent_message(
+        &self,
+        chat_id: &str,
+        message: &AgentMessage,
+    ) -> Result<String, sqlx::Error> {
+        let id = uuid::Uuid::new_v4().to_string();
+        let content_json = match &message.content {
+            AgentContent::Parts(_) => serde_json::to_string(&message.content).ok(),
+            AgentContent::Text(_) => None,
+        };
+        let tool_calls_json = message
+            .tool_calls
+            .as_ref()
+            .filter(|calls| !calls.is_empty())
+            .and_then(|calls| serde_json::to_string(calls).ok());
+
+        sqlx::query(
+            "INSERT INTO messages (id, chat_id, role, content, content_json, tool_calls_json, tool_call_id)
+             VALUES (?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(&id)
+        .bind(chat_id)
+        .bind(&message.role)
+        .bind(message.content.as_text())
+        .bind(content_json)
+        .bind(tool_calls_json)
+        .bind(&message.tool_call_id)
+        .execute(&*self.db)
+        .await?;
+
+        Ok(id)
+    }
+
+    /// Full transcript for a chat as `(id, message)` pairs, oldest first.
+    /// Rows written by `save_message` load as plain text messages.
+    pub async fn get_agent_history(
+        &self,
+        chat_id: &str,
+    ) -> Result<Vec<(String, AgentMessage)>, sqlx::Error> {
+        let rows = sqlx::query_as::<
+            _,
+            (
+                String,
+                String,
+                String,
+                Option<String>,
+                Option<String>,
+                Option<String>,
+            ),
+        >(
+            "SELECT id, role, content, content_json, tool_calls_json, tool_call_id
+             FROM messages
+             WHERE chat_id = ?
+             ORDER BY created_at ASC, rowid ASC",
+        )
+        .bind(chat_id)
+        .fetch_all(&*self.db)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(
+                |(id, role, content, content_json, tool_calls_json, tool_call_id)| {
+                    let content = content_json
+                        .and_then(|json| serde_json::from_str::<AgentContent>(&json).ok())
+                        .unwrap_or(AgentContent::Text(content));
+                    let tool_calls = tool_calls_json
+                        .and_then(|json| serde_json::from_str::<Vec<ToolCall>>(&json).ok());
+                    (
+                        id,
+                        AgentMessage {
+                            role,
+                            content,
+                            tool_calls,
+                            tool_call_id,
+                        },
+                    )
+                },
+            )
+            .collect())
+    }
+
     pub async fn get_history(
         &self,
         chat_id: &str,
     ) -> Result<Vec<(String, String, String)>, sqlx::Error> {
         let messages = sqlx::query_as::<_, (String, String, String)>(
-            "SELECT id, role, content FROM messages WHERE chat_id = ? ORDER BY created_at ASC",
+            "SELECT id, role, content FROM messages WHERE chat_id = ? ORDER BY created_at ASC, rowid ASC",
         )
         .bind(chat_id)
         .fetch_all(&*self.db)
@@ -141,8 +268,8 @@ impl AgentManager {
     ) -> Result<ChatHistoryWindowDto, sqlx::Error> {
         let safe_limit = limit.clamp(1, 200) as i64;
         let rows = if let Some(cursor) = cursor_rowid {
-            sqlx::query_as::<_, (i64, String, String, String, String, String)>(
-                "SELECT rowid, id, chat_id, role, content, created_at
+            sqlx::query_as::<_, HistoryWindowRow>(
+                "SELECT rowid, id, chat_id, role, content, created_at, tool_calls_json, tool_call_id
                  FROM messages
                  WHERE chat_id = ? AND rowid < ?
                  ORDER BY rowid DESC
@@ -154,8 +281,8 @@ impl AgentManager {
             .fetch_all(&*self.db)
             .await?
         } else {
-            sqlx::query_as::<_, (i64, String, String, String, String, String)>(
-                "SELECT rowid, id, chat_id, role, content, created_at
+            sqlx::query_as::<_, HistoryWindowRow>(
+                "SELECT rowid, id, chat_id, role, content, created_at, tool_calls_json, tool_call_id
                  FROM messages
                  WHERE chat_id = ?
                  ORDER BY rowid DESC
@@ -183,14 +310,30 @@ impl AgentManager {
 
         let mut messages: Vec<ChatHistoryMessageDto> = rows
             .into_iter()
-            .map(|(cursor_rowid, id, chat_id, role, content, created_at)| ChatHistoryMessageDto {
-                id,
-                chat_scope_id: chat_id,
-                role,
-                content,
-                created_at,
-                cursor_rowid,
-            })
+            .map(
+                |(
+                    cursor_rowid,
+                    id,
+                    chat_id,
+                    role,
+                    content,
+                    created_at,
+                    tool_calls_json,
+                    tool_call_id,
+                )| {
+                    ChatHistoryMessageDto {
+                        id,
+                        chat_scope_id: chat_id,
+                        role,
+                        content,
+                        created_at,
+                        cursor_rowid,
+                        tool_calls: tool_calls_json
+                            .and_then(|json| serde_json::from_str(&json).ok()),
+                        tool_call_id,
+                    }
+                },
+            )
             .collect();
         messages.reverse();
 
@@ -208,11 +351,21 @@ impl AgentManager {
             .execute(&*self.db)
             .await?;
 
+        sqlx::query("DELETE FROM message_archive WHERE chat_id = ?")
+            .bind(chat_id)
+            .execute(&*self.db)
+            .await?;
+
         sqlx::query("DELETE FROM agent_entities WHERE workspace_id = ?")
             .bind(chat_id)
             .execute(&*self.db)
             .await?;
 
+        sqlx::query("DELETE FROM agent_entity_relations WHERE workspace_id = ?")
+            .bind(chat_id)
+            .execute(&*self.db)
+            .await?;
+
         sqlx::query("DELETE FROM chat_compaction_state WHERE chat_id = ?")
             .bind(chat_id)
             .execute(&*self.db)
@@ -260,8 +413,8 @@ impl AgentManager {
     ) -> Result<Option<ChatCompactionStateDto>, sqlx::Error> {
         let mut tx = self.db.begin().await?;
 
-        let messages = sqlx::query_as::<_, (i64, String)>(
-            "SELECT rowid, id FROM messages WHERE chat_id = ? ORDER BY rowid ASC",
+        let messages = sqlx::query_as::<_, (i64, String, Option<String>)>(
+            "SELECT rowid, id, created_at FROM messages WHERE chat_id = ? ORDER BY rowid ASC",
         )
         .bind(chat_id)
         .fetch_all(&mut *tx)
@@ -274,28 +427,52 @@ impl AgentManager {
 
         let source_message_count = messages.len();
         let delete_count = source_message_count - keep_recent_count;
-        let to_delete: Vec<String> = messages
-            .iter()
-            .take(delete_count)
-            .map(|(_, id)| id.clone())
-            .collect();
+        let (generation,): (i64,) = sqlx::query_as(
+            "SELECT COALESCE(MAX(compaction_count), 0) + 1 FROM chat_compaction_state WHERE chat_id = ?",
+        )
+        .bind(chat_id)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        // Archive rather than drop the compacted rows so the summary can be
+        // expanded back into them later.
+        let summary_id = uuid::Uuid::new_v4().to_string();
+        for (_, id, _) in messages.iter().take(delete_count) {
+            sqlx::query(
+                "INSERT INTO message_archive (
+                    chat_id, generation, summary_message_id, message_id, role, content,
+                    content_json, tool_calls_json, tool_call_id, created_at
+                )
+                SELECT chat_id, ?, ?, id, role, content, content_json, tool_calls_json,
+                    tool_call_id, created_at
+                FROM messages WHERE id = ?",
+            )
+            .bind(generation)
+            .bind(&summary_id)
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
 
-        for id in to_delete {
             sqlx::query("DELETE FROM messages WHERE id = ?")
                 .bind(id)
                 .execute(&mut *tx)
                 .await?;
         }
 
-        let summary_id = uuid::Uuid::new_v4().to_string();
+        // The summary takes the last compacted row's slot so it stays ahead of
+        // the kept messages in both rowid and created_at order.
+        let (last_rowid, _, last_created_at) = &messages[delete_count - 1];
         sqlx::query(
-            "INSERT INTO messages (id, chat_id, role, content) VALUES (?, ?, 'system', ?)",
+            "INSERT INTO messages (rowid, id, chat_id, role, content, created_at)
+             VALUES (?, ?, ?, 'system', ?, COALESCE(?, CURRENT_TIMESTAMP))",
         )
-            .bind(summary_id)
-            .bind(chat_id)
-            .bind(format!("SESSION COMPACTION SUMMARY:\n{}", summary_content))
-            .execute(&mut *tx)
-            .await?;
+        .bind(last_rowid)
+        .bind(&summary_id)
+        .bind(chat_id)
+        .bind(format!("SESSION COMPACTION SUMMARY:\n{}", summary_content))
+        .bind(last_created_at)
+        .execute(&mut *tx)
+        .await?;
 
         sqlx::query(
             "INSERT INTO chat_compaction_state (
@@ -333,6 +510,254 @@ impl AgentManager {
         self.get_latest_chat_compaction(chat_id).await
     }
 
+    /// Rows archived behind a compaction summary, in their original order.
+    pub async fn get_archived_messages(
+        &self,
+        chat_id: &str,
+        summary_message_id: &str,
+    ) -> Result<Vec<ArchivedChatMessageDto>, sqlx::Error> {
+        sqlx::query_as::<_, ArchivedChatMessageDto>(
+            "SELECT
+                message_id AS id,
+                chat_id AS chat_scope_id,
+                summary_message_id,
+                generation,
+                role,
+                content,
+                created_at,
+                archived_at
+             FROM message_archive
+             WHERE chat_id = ? AND summary_message_id = ?
+             ORDER BY archive_id ASC",
+        )
+        .bind(chat_id)
+        .bind(summary_message_id)
+        .fetch_all(&*self.db)
+        .await
+    }
+
+    /// Replace a compaction summary with the rows it archived. Returns the
+    /// number of restored messages; 0 if `summary_message_id` archived nothing.
+    pub async fn expand_compaction_summary(
+        &self,
+        chat_id: &str,
+        summary_message_id: &str,
+    ) -> Result<usize, sqlx::Error> {
+        let mut tx = self.db.begin().await?;
+
+        let archived = sqlx::query_as::<_, StoredMessageRow>(
+            "SELECT message_id AS id, role, content, content_json, tool_calls_json, tool_call_id, created_at
+             FROM message_archive
+             WHERE chat_id = ? AND summary_message_id = ?
+             ORDER BY archive_id ASC",
+        )
+        .bind(chat_id)
+        .bind(summary_message_id)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        let summary_rowid =
+            sqlx::query_as::<_, (i64,)>("SELECT rowid FROM messages WHERE chat_id = ? AND id = ?")
+                .bind(chat_id)
+                .bind(summary_message_id)
+                .fetch_optional(&mut *tx)
+                .await?;
+
+        let Some((summary_rowid,)) = summary_rowid.filter(|_| !archived.is_empty()) else {
+            tx.rollback().await?;
+            return Ok(0);
+        };
+
+        // Rowids order the history, so rewrite everything from the summary on.
+        let following = sqlx::query_as::<_, StoredMessageRow>(
+            "SELECT id, role, content, content_json, tool_calls_json, tool_call_id, created_at
+             FROM messages
+             WHERE chat_id = ? AND rowid > ?
+             ORDER BY rowid ASC",
+        )
+        .bind(chat_id)
+        .bind(summary_rowid)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        sqlx::query("DELETE FROM messages WHERE chat_id = ? AND rowid >= ?")
+            .bind(chat_id)
+            .bind(summary_rowid)
+            .execute(&mut *tx)
+            .await?;
+
+        for row in archived.iter().chain(following.iter()) {
+            insert_stored_message(&mut tx, chat_id, &row.id, row).await?;
+        }
+
+        sqlx::query("DELETE FROM message_archive WHERE chat_id = ? AND summary_message_id = ?")
+            .bind(chat_id)
+            .bind(summary_message_id)
+            .execute(&mut *tx)
+            .await?;
+
+        // The state described the summary just expanded. Point it at the
+        // newest summary still in the history (a rolling compaction restores
+        // the one before it), or clear it. The compaction count is kept so
+        // later generations stay unique.
+        let remaining = sqlx::query_as::<_, (String, i64)>(
+            "SELECT m.content, COUNT(*)
+             FROM messages m
+             JOIN message_archive a ON a.chat_id = m.chat_id AND a.summary_message_id = m.id
+             WHERE m.chat_id = ?
+             GROUP BY m.id
+             ORDER BY MAX(a.generation) DESC
+             LIMIT 1",
+        )
+        .bind(chat_id)
+        .fetch_optional(&mut *tx)
+        .await?;
+        let (summary_content, source_message_count) = remaining
+            .map(|(content, count)| {
+                let summary = content
+                    .strip_prefix("SESSION COMPACTION SUMMARY:\n")
+                    .map(str::to_string)
+                    .unwrap_or(content);
+                (summary, count)
+            })
+            .unwrap_or_default();
+        sqlx::query(
+            "UPDATE chat_compaction_state SET
+                summary_content = ?,
+                source_message_count = ?,
+                source_estimated_tokens = 0,
+                kept_recent_count = 0,
+                updated_at = CURRENT_TIMESTAMP
+             WHERE chat_id = ?",
+        )
+        .bind(summary_content)
+        .bind(source_message_count)
+        .bind(chat_id)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(archived.len())
+    }
+
+    /// Start a new chat whose history is a copy of `chat_id` up to and
+    /// including `message_id`. Archives behind copied summaries are copied
+    /// too, so the fork can still expand them. Returns the new chat id.
+    pub async fn fork_chat(
+        &self,
+        chat_id: &str,
+        message_id: &str,
+        title: Option<&str>,
+    ) -> Result<String, sqlx::Error> {
+        let mut tx = self.db.begin().await?;
+
+        let (cutoff_rowid,) =
+            sqlx::query_as::<_, (i64,)>("SELECT rowid FROM messages WHERE chat_id = ? AND id = ?")
+                .bind(chat_id)
+                .bind(message_id)
+                .fetch_optional(&mut *tx)
+                .await?
+                .ok_or(sqlx::Error::RowNotFound)?;
+
+        let fork_id = uuid::Uuid::new_v4().to_string();
+        sqlx::query(
+            "INSERT INTO chats (id, agent_id, title, parent_chat_id, forked_from_message_id)
+             SELECT ?, agent_id, ?, id, ? FROM chats WHERE id = ?",
+        )
+        .bind(&fork_id)
+        .bind(title)
+        .bind(message_id)
+        .bind(chat_id)
+        .execute(&mut *tx)
+        .await?;
+
+        let prefix = sqlx::query_as::<_, StoredMessageRow>(
+            "SELECT id, role, content, content_json, tool_calls_json, tool_call_id, created_at
+             FROM messages
+             WHERE chat_id = ? AND rowid <= ?
+             ORDER BY rowid ASC",
+        )
+        .bind(chat_id)
+        .bind(cutoff_rowid)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        let archived_summaries: std::collections::HashSet<String> = sqlx::query_as::<_, (String,)>(
+            "SELECT DISTINCT summary_message_id FROM message_archive WHERE chat_id = ?",
+        )
+        .bind(chat_id)
+        .fetch_all(&mut *tx)
+        .await?
+        .into_iter()
+        .map(|(id,)| id)
+        .collect();
+
+        // (source summary id, fork summary id) whose archives still need copying.
+        let mut pending = Vec::new();
+        for row in &prefix {
+            let new_id = uuid::Uuid::new_v4().to_string();
+            insert_stored_message(&mut tx, &fork_id, &new_id, row).await?;
+            if archived_summaries.contains(&row.id) {
+                pending.push((row.id.clone(), new_id));
+            }
+        }
+
+        while let Some((source_summary_id, fork_summary_id)) = pending.pop() {
+            let rows = sqlx::query_as::<_, (String,)>(
+                "SELECT message_id FROM message_archive
+                 WHERE chat_id = ? AND summary_message_id = ?
+                 ORDER BY archive_id ASC",
+            )
+            .bind(chat_id)
+            .bind(&source_summary_id)
+            .fetch_all(&mut *tx)
+            .await?;
+
+            for (source_message_id,) in rows {
+                let new_id = uuid::Uuid::new_v4().to_string();
+                sqlx::query(
+                    "INSERT INTO message_archive (
+                        chat_id, generation, summary_message_id, message_id, role, content,
+                        content_json, tool_calls_json, tool_call_id, created_at, archived_at
+                    )
+                    SELECT ?, generation, ?, ?, role, content, content_json, tool_calls_json,
+                        tool_call_id, created_at, archived_at
+                    FROM message_archive
+                    WHERE chat_id = ? AND summary_message_id = ? AND message_id = ?",
+                )
+                .bind(&fork_id)
+                .bind(&fork_summary_id)
+                .bind(&new_id)
+                .bind(chat_id)
+                .bind(&source_summary_id)
+                .bind(&source_message_id)
+                .execute(&mut *tx)
+                .await?;
+                if archived_summaries.contains(&source_message_id) {
+                    pending.push((source_message_id, new_id));
+                }
+            }
+        }
+
+        // Carry the compaction count over so later generations stay unique.
+        sqlx::query(
+            "INSERT INTO chat_compaction_state (
+                chat_id, summary_content, source_message_count, source_estimated_tokens,
+                kept_recent_count, compression_model, compaction_count, compressed_at, updated_at
+            )
+            SELECT ?, summary_content, source_message_count, source_estimated_tokens,
+                kept_recent_count, compression_model, compaction_count, compressed_at, updated_at
+            FROM chat_compaction_state WHERE chat_id = ?",
+        )
+        .bind(&fork_id)
+        .bind(chat_id)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(fork_id)
+    }
+
     pub async fn get_latest_chat_compaction(
         &self,
         chat_id: &str,
@@ -412,7 +837,9 @@ impl AgentManager {
                 chats.created_at,
                 chats.updated_at,
                 COUNT(messages.id) AS message_count,
-                MAX(messages.created_at) AS last_message_at
+                MAX(messages.created_at) AS last_message_at,
+                chats.parent_chat_id,
+                chats.forked_from_message_id
              FROM chats
              LEFT JOIN messages ON messages.chat_id = chats.id
              WHERE chats.id = ?
@@ -517,6 +944,29 @@ impl AgentManager {
     }
 }
 
+async fn insert_stored_message(
+    conn: &mut SqliteConnection,
+    chat_id: &str,
+    id: &str,
+    row: &StoredMessageRow,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO messages (id, chat_id, role, content, content_json, tool_calls_json, tool_call_id, created_at)
+         VALUES (?, ?, ?, ?, ?, ?, ?, COALESCE(?, CURRENT_TIMESTAMP))",
+    )
+    .bind(id)
+    .bind(chat_id)
+    .bind(&row.role)
+    .bind(&row.content)
+    .bind(&row.content_json)
+    .bind(&row.tool_calls_json)
+    .bind(&row.tool_call_id)
+    .bind(&row.created_at)
+    .execute(conn)
+    .await?;
+    Ok(())
+}
+
 // Commands to be exposed to Frontend
 #[tauri::command]
 pub async fn save_agent_to_db(
@@ -610,6 +1060,55 @@ pub async fn compact_session_cmd(
         .map_err(|e| e.to_string())
 }
 
+#[tauri::command]
+pub async fn get_compacted_messages(
+    state: State<'_, AgentManager>,
+    chat_scope_id: String,
+    summary_message_id: String,
+) -> Result<Vec<ArchivedChatMessageDto>, String> {
+    state
+        .get_archived_messages(&chat_scope_id, &summary_message_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn expand_chat_compaction(
+    state: State<'_, AgentManager>,
+    chat_scope_id: String,
+    summary_message_id: String,
+) -> Result<usize, String> {
+    state
+        .expand_compaction_summary(&chat_scope_id, &summary_message_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn fork_chat_session(
+    state: State<'_, AgentManager>,
+    chat_scope_id: String,
+    message_id: String,
+    title: Option<String>,
+) -> Result<ChatSessionDto, String> {
+    let fork_id = state
+        .fork_chat(&chat_scope_id, &message_id, title.as_deref())
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::RowNotFound => format!(
+                "Message '{}' was not found in chat '{}'",
+                message_id, chat_scope_id
+            ),
+            other => other.to_string(),
+        })?;
+
+    state
+        .get_chat_session(&fork_id)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Chat session '{}' was not found after fork", fork_id))
+}
+
 #[tauri::command]
 pub async fn get_chat_compaction_state(
     state: State<'_, AgentManager>,
@@ -649,3 +1148,261 @@ pub async fn get_chat_history_window(
         .await
         .map_err(|e| e.to_string())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai::agent::runtime::{AgentContentPart, AgentImageUrl};
+    use crate::ai::provider_types::FunctionCall;
+    use serial_test::serial;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    async fn manager() -> AgentManager {
+        // Initialize libSQL's C state before sqlx, as in verification_test.
+        let _ = libsql::Builder::new_local(":memory:").build().await;
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+        let manager = AgentManager::new(pool);
+        manager
+            .ensure_chat_session("chat", "Rainy Agent")
+            .await
+            .unwrap();
+        manager
+    }
+
+    async fn contents(manager: &AgentManager, chat_id: &str) -> Vec<String> {
+        manager
+            .get_history(chat_id)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|(_, _, content)| content)
+            .collect()
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_agent_messages_round_trip_tool_calls_and_parts() {
+        let manager = manager().await;
+
+        manager
+            .save_message("chat", "user", "Describe logo.png")
+            .await
+            .unwrap();
+        let transcript = vec![
+            AgentMessage {
+                role: "assistant".to_string(),
+                content: AgentContent::text(""),
+                tool_calls: Some(vec![ToolCall {
+                    id: "call_1".to_string(),
+                    r#type: "function".to_string(),
+                    extra_content: None,
+                    function: FunctionCall {
+                        name: "read_file".to_string(),
+                        arguments: r#"{"path":"logo.png"}"#.to_string(),
+                    },
+                }]),
+                tool_call_id: None,
+            },
+            AgentMessage {
+                role: "tool".to_string(),
+                content: AgentContent::Parts(vec![
+                    AgentContentPart::Text {
+                        text: "logo.png".to_string(),
+                    },
+                    AgentContentPart::ImageUrl {
+                        image_url: AgentImageUrl {
+                            url: "data:image/png;base64,AAAA".to_string(),
+                            detail: Some("auto".to_string()),
+                        },
+                    },
+                ]),
+                tool_calls: None,
+                tool_call_id: Some("call_1".to_string()),
+            },
+            AgentMessage {
+                role: "assistant".to_string(),
+                content: AgentContent::text("A blue cloud."),
+                tool_calls: None,
+                tool_call_id: None,
+            },
+        ];
+        for message in &transcript {
+            manager.save_agent_message("chat", message).await.unwrap();
+        }
+
+        let history = manager.get_agent_history("chat").await.unwrap();
+        let roles: Vec<&str> = history.iter().map(|(_, m)| m.role.as_str()).collect();
+        assert_eq!(roles, vec!["user", "assistant", "tool", "assistant"]);
+
+        let call = &history[1].1.tool_calls.as_ref().unwrap()[0];
+        assert_eq!(call.id, "call_1");
+        assert_eq!(call.function.name, "read_file");
+
+        let tool_result = &history[2].1;
+        assert_eq!(tool_result.tool_call_id.as_deref(), Some("call_1"));
+        assert!(tool_result.content.has_image());
+        assert_eq!(tool_result.content.as_text(), "logo.png [IMAGE]");
+
+        // The UI window sees the plain-text view plus the tool linkage.
+        let window = manager.get_history_window("chat", None, 10).await.unwrap();
+        assert_eq!(window.messages[2].content, "logo.png [IMAGE]");
+        assert_eq!(window.messages[2].tool_call_id.as_deref(), Some("call_1"));
+        assert_eq!(
+            window.messages[1].tool_calls.as_ref().map(Vec::len),
+            Some(1)
+        );
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_compaction_archives_and_expands_in_place() {
+        let manager = manager().await;
+        for i in 1..=5 {
+            manager
+                .save_message("chat", "user", &format!("m{}", i))
+                .await
+                .unwrap();
+        }
+
+        let state = manager
+            .compact_session_with_rolling_summary("chat", "summary", 2, 0, "test")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(state.compaction_count, 1);
+        let history = manager.get_history("chat").await.unwrap();
+        let summary_id = history[0].0.clone();
+        assert_eq!(
+            contents(&manager, "chat").await,
+            vec!["SESSION COMPACTION SUMMARY:\nsummary", "m4", "m5"]
+        );
+
+        let archived = manager
+            .get_archived_messages("chat", &summary_id)
+            .await
+            .unwrap();
+        let archived: Vec<(&str, i64)> = archived
+            .iter()
+            .map(|m| (m.content.as_str(), m.generation))
+            .collect();
+        assert_eq!(archived, vec![("m1", 1), ("m2", 1), ("m3", 1)]);
+
+        assert_eq!(
+            manager
+                .expand_compaction_summary("chat", &summary_id)
+                .await
+                .unwrap(),
+            3
+        );
+        assert_eq!(
+            contents(&manager, "chat").await,
+            vec!["m1", "m2", "m3", "m4", "m5"]
+        );
+        let window = manager.get_history_window("chat", None, 10).await.unwrap();
+        let window: Vec<&str> = window.messages.iter().map(|m| m.content.as_str()).collect();
+        assert_eq!(window, vec!["m1", "m2", "m3", "m4", "m5"]);
+        let state = manager
+            .get_latest_chat_compaction("chat")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(state.summary_content, "");
+        assert_eq!(state.source_message_count, 0);
+        assert_eq!(state.compaction_count, 1);
+        assert_eq!(
+            manager
+                .expand_compaction_summary("chat", &summary_id)
+                .await
+                .unwrap(),
+            0
+        );
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_expanding_rolling_summary_restores_previous_state() {
+        let manager = manager().await;
+        for i in 1..=4 {
+            manager
+                .save_message("chat", "user", &format!("m{}", i))
+                .await
+                .unwrap();
+        }
+        manager
+            .compact_session_with_rolling_summary("chat", "first", 2, 0, "test")
+            .await
+            .unwrap();
+        manager.save_message("chat", "user", "m5").await.unwrap();
+        manager
+            .compact_session_with_rolling_summary("chat", "second", 1, 0, "test")
+            .await
+            .unwrap();
+        let summary_id = manager.get_history("chat").await.unwrap()[0].0.clone();
+
+        manager
+            .expand_compaction_summary("chat", &summary_id)
+            .await
+            .unwrap();
+        let state = manager
+            .get_latest_chat_compaction("chat")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(state.summary_content, "first");
+        assert_eq!(state.source_message_count, 2);
+        assert_eq!(state.compaction_count, 2);
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_fork_copies_prefix_and_compacted_archive() {
+        let manager = manager().await;
+        for i in 1..=4 {
+            manager
+                .save_message("chat", "user", &format!("m{}", i))
+                .await
+                .unwrap();
+        }
+        manager
+            .compact_session_with_rolling_summary("chat", "summary", 2, 0, "test")
+            .await
+            .unwrap();
+        let history = manager.get_history("chat").await.unwrap();
+        let fork_point = history[1].0.clone();
+
+        let fork_id = manager
+            .fork_chat("chat", &fork_point, Some("Alternative"))
+            .await
+            .unwrap();
+        let session = manager.get_chat_session(&fork_id).await.unwrap().unwrap();
+        assert_eq!(session.title.as_deref(), Some("Alternative"));
+        assert_eq!(session.parent_chat_id.as_deref(), Some("chat"));
+        assert_eq!(session.forked_from_message_id, Some(fork_point));
+
+        manager
+            .save_message(&fork_id, "user", "branch")
+            .await
+            .unwrap();
+        let fork_summary_id = manager.get_history(&fork_id).await.unwrap()[0].0.clone();
+        assert_ne!(fork_summary_id, history[0].0);
+        manager
+            .expand_compaction_summary(&fork_id, &fork_summary_id)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            contents(&manager, &fork_id).await,
+            vec!["m1", "m2", "m3", "branch"]
+        );
+        assert_eq!(
+            contents(&manager, "chat").await,
+            vec!["SESSION COMPACTION SUMMARY:\nsummary", "m3", "m4"]
+        );
+        assert!(manager.fork_chat("chat", "missing", None).await.is_err());
+    }
+}
@@ -1,3 +1,4 @@
+use crate::ai::agent::entity_graph::{EntityContext, EntityGraph, FactInput};
 use crate::services::memory_vault::MemorySensitivity;
 use chrono::{TimeZone, Utc};
 use reqwest::Client;
@@ -23,7 +24,7 @@ pub struct MemoryEntry {
 #[derive(Debug, Clone)]
 pub struct AgentMemory {
     workspace_id: String,
-    db: Arc<sqlx::SqlitePool>,
+    entity_graph: EntityGraph,
     manager: Arc<crate::services::MemoryManager>,
     #[allow(dead_code)]
     http_client: Client,
@@ -63,7 +64,7 @@ impl AgentMemory {
 
         let memory = Self {
             workspace_id: workspace_id.to_string(),
-            db: Arc::new(pool),
+            entity_graph: EntityGraph::new(pool),
             manager,
             http_client: Client::builder()
                 .user_agent("Rainy-MaTE-Agent/1.0")
@@ -79,6 +80,21 @@ impl AgentMemory {
         self.manager.clone()
     }
 
+    /// Known facts about entities mentioned in `text`; empty if the graph is unavailable.
+    pub async fn entity_context(&self, text: &str, limit: usize) -> EntityContext {
+        self.entity_graph
+            .relevant_context(&self.workspace_id, text, limit)
+            .await
+            .unwrap_or_else(|e| {
+                tracing::warn!(
+                    "Failed to load entity facts for {}: {}",
+                    self.workspace_id,
+                    e
+                );
+                EntityContext::default()
+            })
+    }
+
     pub async fn store(
         &self,
         content: String,
@@ -118,17 +134,27 @@ impl AgentMemory {
         if let (Some(entity_key), Some(entity_value)) =
             (metadata.get("entity_key"), metadata.get("entity_value"))
         {
-            let _ = sqlx::query(
-                "INSERT INTO agent_entities (id, workspace_id, entity_key, entity_value, confidence)
-                 VALUES (?, ?, ?, ?, ?)",
-            )
-            .bind(uuid::Uuid::new_v4().to_string())
-            .bind(&self.workspace_id)
-            .bind(entity_key)
-            .bind(entity_value)
-            .bind(0.5_f32)
-            .execute(&*self.db)
-            .await;
+            let fact = FactInput {
+                entity: entity_key.clone(),
+                attribute: metadata
+                    .get("entity_attribute")
+                    .cloned()
+                    .unwrap_or_default(),
+                value: entity_value.clone(),
+                entity_type: metadata.get("entity_type").cloned(),
+                confidence: metadata
+                    .get("entity_confidence")
+                    .and_then(|c| c.parse().ok())
+                    .unwrap_or(0.5),
+                source: Some(source),
+            };
+            if let Err(e) = self
+                .entity_graph
+                .remember_fact(&self.workspace_id, fact)
+                .await
+            {
+                tracing::warn!("Failed to record entity fact: {}", e);
+            }
         }
     }
 
@@ -139,17 +165,14 @@ impl AgentMemory {
             .await
             .unwrap_or_default()
             .into_iter()
-            .map(|row| {
-                let importance = 0.5;
-                MemoryEntry {
-                    id: row.id,
-                    content: row.content,
-                    source: derive_source_from_tags(&row.tags),
-                    timestamp: row.timestamp.timestamp(),
-                    metadata: HashMap::new(),
-                    importance,
-                    sensitivity: MemorySensitivity::Internal,
-                }
+            .map(|row| MemoryEntry {
+                id: row.id,
+                content: row.content,
+                source: derive_source_from_tags(&row.tags),
+                timestamp: row.timestamp.timestamp(),
+                metadata: HashMap::new(),
+                importance: row.importance,
+                sensitivity: MemorySensitivity::Internal,
             })
             .collect()
     }
@@ -1,10 +1,12 @@
 pub mod context_budget;
 pub mod context_window;
+pub mod entity_graph;
 pub mod error;
 pub mod events;
 pub mod manager;
 pub mod memory;
 pub mod protocol;
+pub mod rate_limiter;
 pub mod runtime;
 pub mod runtime_registry;
 pub mod specialist;
@@ -1,3 +1,4 @@
+use crate::services::tool_output::ToolOutputStream;
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
@@ -95,4 +96,10 @@ pub enum SupervisorMessage {
         role: SpecialistRole,
         error: String,
     },
+    /// Output of a specialist's running tool, passed through to the run's events.
+    ToolOutputChunk {
+        id: String,
+        stream: ToolOutputStream,
+        text: String,
+    },
 }
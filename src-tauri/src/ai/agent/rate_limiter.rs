@@ -0,0 +1,242 @@
+//! Runtime enforcement of `AirlockRateLimits`.
+//!
+//! Requests are counted per agent over a sliding one-minute window in memory.
+//! Token usage is accumulated per agent and UTC day in `agent_token_usage`, so
+//! the daily budget holds across runs, cloud-dispatched jobs and restarts.
+
+use crate::ai::provider_types::TokenUsage;
+use chrono::{NaiveDate, Utc};
+use sqlx::{Pool, Sqlite};
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+const WINDOW: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, Copy)]
+struct DailyUsage {
+    day: NaiveDate,
+    total_tokens: u64,
+}
+
+/// Shared by every runtime in the process; keyed by agent spec id.
+#[derive(Debug, Default)]
+pub struct AgentRateLimiter {
+    windows: Mutex<HashMap<String, VecDeque<Instant>>>,
+    daily: tokio::sync::Mutex<HashMap<String, DailyUsage>>,
+    db: Option<Pool<Sqlite>>,
+}
+
+impl AgentRateLimiter {
+    /// Without a pool, daily usage only lives as long as the process.
+    pub fn new(db: Option<Pool<Sqlite>>) -> Self {
+        Self {
+            db,
+            ..Self::default()
+        }
+    }
+
+    /// Take one slot in the agent's per-minute window. On a full window,
+    /// returns how long until the oldest slot frees up.
+    pub fn try_acquire(&self, agent_id: &str, max_per_minute: u32) -> Result<(), Duration> {
+        self.try_acquire_at(agent_id, max_per_minute, Instant::now())
+    }
+
+    fn try_acquire_at(
+        &self,
+        agent_id: &str,
+        max_per_minute: u32,
+        now: Instant,
+    ) -> Result<(), Duration> {
+        if max_per_minute == 0 {
+            return Ok(());
+        }
+
+        let mut windows = self.windows.lock().unwrap_or_else(|e| e.into_inner());
+        let window = windows.entry(agent_id.to_string()).or_default();
+        while window
+            .front()
+            .is_some_and(|at| now.duration_since(*at) >= WINDOW)
+        {
+            window.pop_front();
+        }
+
+        match window.front() {
+            Some(oldest) if window.len() >= max_per_minute as usize => {
+                Err(WINDOW.saturating_sub(now.duration_since(*oldest)))
+            }
+            _ => {
+                window.push_back(now);
+                Ok(())
+            }
+        }
+    }
+
+    /// Fails with a user-facing message once the agent has spent its token
+    /// budget for the current UTC day.
+    pub async fn check_daily_budget(
+        &self,
+        agent_id: &str,
+        max_tokens_per_day: u64,
+    ) -> Result<(), String> {
+        if max_tokens_per_day == 0 {
+            return Ok(());
+        }
+        let used = self.tokens_used_today(agent_id).await;
+        if used >= max_tokens_per_day {
+            return Err(format!(
+                "Daily token budget exhausted for agent '{}': {} of {} tokens used today (UTC). The budget resets at midnight UTC.",
+                agent_id, used, max_tokens_per_day
+            ));
+        }
+        Ok(())
+    }
+
+    pub async fn tokens_used_today(&self, agent_id: &str) -> u64 {
+        let today = Utc::now().date_naive();
+        let mut daily = self.daily.lock().await;
+        self.cached_or_load(&mut daily, agent_id, today).await
+    }
+
+    /// Add one model response's usage to today's total.
+    pub async fn record_usage(&self, agent_id: &str, usage: &TokenUsage) {
+        let total = u64::from(usage.total_tokens)
+            .max(u64::from(usage.prompt_tokens) + u64::from(usage.completion_tokens));
+        let today = Utc::now().date_naive();
+
+        let mut daily = self.daily.lock().await;
+        let used = self.cached_or_load(&mut daily, agent_id, today).await;
+        daily.insert(
+            agent_id.to_string(),
+            DailyUsage {
+                day: today,
+                total_tokens: used + total,
+            },
+        );
+        drop(daily);
+
+        let Some(db) = self.db.as_ref() else {
+            return;
+        };
+        let result = sqlx::query(
+            "INSERT INTO agent_token_usage (agent_id, day, prompt_tokens, completion_tokens, total_tokens, requests)
+             VALUES (?, ?, ?, ?, ?, 1)
+             ON CONFLICT(agent_id, day) DO UPDATE SET
+                prompt_tokens = prompt_tokens + excluded.prompt_tokens,
+                completion_tokens = completion_tokens + excluded.completion_tokens,
+                total_tokens = total_tokens + excluded.total_tokens,
+                requests = requests + 1,
+                updated_at = CURRENT_TIMESTAMP",
+        )
+        .bind(agent_id)
+        .bind(today.to_string())
+        .bind(i64::from(usage.prompt_tokens))
+        .bind(i64::from(usage.completion_tokens))
+        .bind(total as i64)
+        .execute(db)
+        .await;
+        if let Err(e) = result {
+            tracing::warn!(
+                "Failed to persist token usage for agent {}: {}",
+                agent_id,
+                e
+            );
+        }
+    }
+
+    async fn cached_or_load(
+        &self,
+        daily: &mut HashMap<String, DailyUsage>,
+        agent_id: &str,
+        today: NaiveDate,
+    ) -> u64 {
+        if let Some(usage) = daily.get(agent_id).filter(|usage| usage.day == today) {
+            return usage.total_tokens;
+        }
+
+        let total_tokens = match self.db.as_ref() {
+            Some(db) => sqlx::query_as::<_, (i64,)>(
+                "SELECT total_tokens FROM agent_token_usage WHERE agent_id = ? AND day = ?",
+            )
+            .bind(agent_id)
+            .bind(today.to_string())
+            .fetch_optional(db)
+            .await
+            .unwrap_or_else(|e| {
+                tracing::warn!("Failed to load token usage for agent {}: {}", agent_id, e);
+                None
+            })
+            .map_or(0, |(total,)| total.max(0) as u64),
+            None => 0,
+        };
+        daily.insert(
+            agent_id.to_string(),
+            DailyUsage {
+                day: today,
+                total_tokens,
+            },
+        );
+        total_tokens
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serial_test::serial;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    #[test]
+    fn test_sliding_window_limits_requests_per_agent() {
+        let limiter = AgentRateLimiter::new(None);
+        let start = Instant::now();
+
+        assert!(limiter.try_acquire_at("a", 2, start).is_ok());
+        assert!(limiter
+            .try_acquire_at("a", 2, start + Duration::from_secs(10))
+            .is_ok());
+        let wait = limiter
+            .try_acquire_at("a", 2, start + Duration::from_secs(20))
+            .unwrap_err();
+        assert_eq!(wait, Duration::from_secs(40));
+
+        // Other agents and unlimited specs are unaffected.
+        assert!(limiter.try_acquire_at("b", 2, start).is_ok());
+        assert!(limiter.try_acquire_at("a", 0, start).is_ok());
+
+        // The oldest slot has expired a minute later.
+        assert!(limiter.try_acquire_at("a", 2, start + WINDOW).is_ok());
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_daily_usage_persists_across_limiters() {
+        // Initialize libSQL's C state before sqlx, as in verification_test.
+        let _ = libsql::Builder::new_local(":memory:").build().await;
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+
+        let limiter = AgentRateLimiter::new(Some(pool.clone()));
+        limiter
+            .record_usage("agent", &TokenUsage::new(600, 200))
+            .await;
+        assert!(limiter.check_daily_budget("agent", 1_000).await.is_ok());
+        limiter
+            .record_usage("agent", &TokenUsage::new(150, 50))
+            .await;
+
+        let restarted = AgentRateLimiter::new(Some(pool));
+        assert_eq!(restarted.tokens_used_today("agent").await, 1_000);
+        let err = restarted
+            .check_daily_budget("agent", 1_000)
+            .await
+            .unwrap_err();
+        assert!(err.contains("Daily token budget exhausted"));
+        assert!(restarted.check_daily_budget("agent", 0).await.is_ok());
+        assert!(restarted.check_daily_budget("other", 1_000).await.is_ok());
+    }
+}
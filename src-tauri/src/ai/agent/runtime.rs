@@ -3,6 +3,7 @@
 use crate::ai::agent::context_window::ContextWindow;
 use crate::ai::agent::events::AgentEvent;
 use crate::ai::agent::memory::AgentMemory;
+use crate::ai::agent::rate_limiter::AgentRateLimiter;
 use crate::ai::agent::runtime_registry::RuntimeRegistry;
 use crate::ai::agent::supervisor::SupervisorAgent;
 use crate::ai::agent::workflow::{ActStep, AgentState, ThinkStep, Workflow};
@@ -23,6 +24,8 @@ pub struct RuntimeOptions {
     pub custom_system_prompt: Option<String>,
     pub streaming_enabled: Option<bool>,
     pub reasoning_effort: Option<String>,
+    /// Identifies the run for file checkpoints (see `FileOperationEngine::revert_run`)
+    pub run_id: Option<String>,
 }
 
 /// The core runtime that orchestrates the agent's thinking process
@@ -35,6 +38,7 @@ pub struct AgentRuntime {
     airlock_service: Arc<Option<crate::services::airlock::AirlockService>>,
     kill_switch: Option<AgentKillSwitch>,
     runtime_registry: Option<Arc<RuntimeRegistry>>,
+    rate_limiter: Option<Arc<AgentRateLimiter>>,
     history: Arc<Mutex<Vec<AgentMessage>>>,
 }
 
@@ -172,16 +176,28 @@ impl AgentRuntime {
             airlock_service,
             kill_switch,
             runtime_registry,
+            rate_limiter: None,
             history: Arc::new(Mutex::new(Vec::new())),
         }
     }
 
+    /// Enforce the spec's `AirlockRateLimits` through a shared limiter.
+    pub fn with_rate_limiter(mut self, rate_limiter: Option<Arc<AgentRateLimiter>>) -> Self {
+        self.rate_limiter = rate_limiter;
+        self
+    }
+
     /// Replace in-memory history for this runtime instance.
     pub async fn set_history(&self, messages: Vec<AgentMessage>) {
         let mut hist = self.history.lock().await;
         *hist = messages;
     }
 
+    /// Snapshot of in-memory history, including every message appended by runs.
+    pub async fn history(&self) -> Vec<AgentMessage> {
+        self.history.lock().await.clone()
+    }
+
     async fn generate_system_prompt(&self, skills: &SkillExecutor) -> String {
         // If a custom system prompt is provided (e.g. from Cloud/ATM), use it directly.
         if let Some(custom) = &self.options.custom_system_prompt {
@@ -352,6 +368,57 @@ Rules:
         block
     }
 
+    /// Knowledge-file chunks cited by file name and line range, sanitized like
+    /// workspace memory. Shares half of the semantic context budget.
+    fn build_knowledge_context_block(
+        &self,
+        context_window: &ContextWindow,
+        result: &crate::services::memory::SemanticSearchResult,
+    ) -> String {
+        if result.entries.is_empty() {
+            return String::new();
+        }
+
+        let mut remaining_chars = (context_window.semantic_context_budget_tokens() / 2) * 4;
+        let header = "\n\n--- REFERENCE KNOWLEDGE (agent knowledge files) ---\n";
+        let mut block = header.to_string();
+        remaining_chars = remaining_chars.saturating_sub(header.len());
+
+        for (i, entry) in result.entries.iter().enumerate() {
+            if remaining_chars < 32 {
+                break;
+            }
+            let tag = |key: &str| {
+                entry
+                    .tags
+                    .iter()
+                    .find_map(|t| t.strip_prefix(key).map(str::to_string))
+            };
+            let file = tag("knowledge_file_name:").unwrap_or_else(|| "knowledge".to_string());
+            let citation = match tag("lines:") {
+                Some(lines) => format!("[{}] {} (lines {})\n", i + 1, file, lines),
+                None => format!("[{}] {}\n", i + 1, file),
+            };
+            let sanitized = crate::ai::agent::prompt_guard::sanitize_memory_context(&entry.content);
+            let content_budget_tokens =
+                (remaining_chars.saturating_sub(citation.len()) / 4).saturating_sub(8);
+            let content =
+                context_window.truncate_text_for_tokens(&sanitized.text, content_budget_tokens);
+            let item = format!("{}{}\n", citation, content);
+            if item.len() > remaining_chars {
+                break;
+            }
+            block.push_str(&item);
+            remaining_chars = remaining_chars.saturating_sub(item.len());
+        }
+
+        let end = "----------------------------------------------\n";
+        if end.len() <= remaining_chars {
+            block.push_str(end);
+        }
+        block
+    }
+
     /// Primary entry point: Run a workflow/turn
     pub async fn run<F>(&self, input: &str, on_event: F) -> Result<String, String>
     where
@@ -367,6 +434,7 @@ Rules:
                 airlock_service: self.airlock_service.clone(),
                 kill_switch: self.kill_switch.clone(),
                 runtime_registry: self.runtime_registry.clone(),
+                rate_limiter: self.rate_limiter.clone(),
             };
             return supervisor.run(input, on_event).await;
         }
@@ -386,6 +454,8 @@ Rules:
             self.airlock_service.clone(),
             self.kill_switch.clone(),
         );
+        state.run_id = self.options.run_id.clone();
+        state.rate_limiter = self.rate_limiter.clone();
 
         // Add System Message to State
         state.messages.push(AgentMessage {
@@ -395,8 +465,22 @@ Rules:
             tool_call_id: None,
         });
 
-        let context_window =
-            ContextWindow::new(self.spec.memory_config.effective_max_tokens() as usize);
+        let context_window = ContextWindow::for_spec(&self.spec);
+
+        // Keep the workspace index warm for semantic_search_workspace
+        if self
+            .spec
+            .airlock
+            .is_tool_allowed("semantic_search_workspace")
+        {
+            self.skills
+                .warm_workspace_index(
+                    &self.options.workspace_id,
+                    &self.options.allowed_paths.clone().unwrap_or_default(),
+                    &self.spec.airlock.scopes.blocked_paths,
+                )
+                .await;
+        }
 
         // --- SEMANTIC RETRIEVAL (Hive Mind Seed) ---
         // Retrieve relevant context from the encrypted memory vault using the user input
@@ -405,8 +489,18 @@ Rules:
         let mut appended_context = String::new();
         let mut retrieval_mode = "unavailable".to_string();
         let mm = self.memory.manager();
+        mm.set_retention_policy(
+            &self.options.workspace_id,
+            self.spec.memory_config.retention_policy(),
+        )
+        .await;
         if let Ok(mut result) = mm
-            .search_semantic_detailed(&self.options.workspace_id, input, 5)
+            .search_with_options(
+                &self.options.workspace_id,
+                input,
+                5,
+                &self.spec.memory_config.search_options(),
+            )
             .await
         {
             if !result.confidential_entry_ids.is_empty() {
@@ -426,6 +520,8 @@ Rules:
                             tool_access_policy: None,
                             tool_access_policy_version: None,
                             tool_access_policy_hash: None,
+                            command_policy: None,
+                            run_id: None,
                         },
                         status: crate::models::neural::CommandStatus::Pending,
                         priority: crate::models::neural::CommandPriority::Normal,
@@ -463,6 +559,8 @@ Rules:
             retrieval_mode = match result.mode {
                 crate::services::memory::SemanticRetrievalMode::Ann => "ann",
                 crate::services::memory::SemanticRetrievalMode::Exact => "exact",
+                crate::services::memory::SemanticRetrievalMode::Hybrid => "hybrid",
+                crate::services::memory::SemanticRetrievalMode::Lexical => "lexical",
                 crate::services::memory::SemanticRetrievalMode::LexicalFallback => {
                     "lexical_fallback"
                 }
@@ -489,12 +587,42 @@ Rules:
                 .collect();
             appended_context = self.build_semantic_context_block(&context_window, &sanitized_result);
         }
+
+        // The agent's own reference corpus, searched only within its knowledge partition.
+        let knowledge = &self.spec.memory_config.knowledge;
+        if knowledge.enabled && !knowledge.indexed_files.is_empty() {
+            match mm
+                .search_with_options(
+                    &crate::services::knowledge_base::knowledge_partition(&self.spec.id),
+                    input,
+                    5,
+                    &self.spec.memory_config.search_options(),
+                )
+                .await
+            {
+                Ok(result) => appended_context
+                    .push_str(&self.build_knowledge_context_block(&context_window, &result)),
+                Err(e) => tracing::warn!("Knowledge retrieval failed for {}: {}", self.spec.id, e),
+            }
+        }
+
+        // Stable facts about entities named in the input, from the entity graph.
+        let entity_context = self.memory.entity_context(input, 12).await;
+        if !entity_context.is_empty() {
+            let facts =
+                crate::ai::agent::prompt_guard::sanitize_memory_context(&entity_context.render());
+            appended_context.push_str(&format!(
+                "\n\n--- KNOWN FACTS ABOUT MENTIONED ENTITIES ---\n{}\n----------------------------------------------\n",
+                facts.text
+            ));
+        }
+        let embedding_profile = mm.embedding_profile().await.label();
         on_event(AgentEvent::Status(format!(
             "RAG_TELEMETRY:{}",
             serde_json::json!({
                 "history_source": "persisted_long_chat",
                 "retrieval_mode": retrieval_mode,
-                "embedding_profile": crate::services::memory_vault::types::EMBEDDING_MODEL,
+                "embedding_profile": embedding_profile,
             })
             .to_string()
         )));
@@ -630,6 +758,8 @@ Rules:
                         tool_access_policy: None,
                         tool_access_policy_version: None,
                         tool_access_policy_hash: None,
+                        command_policy: None,
+                        run_id: None,
                     },
                     status: crate::models::neural::CommandStatus::Pending,
                     priority: crate::models::neural::CommandPriority::Normal,
@@ -1,5 +1,6 @@
 use super::protocol::{SpecialistAssignment, SpecialistOutcome, SpecialistRole, SpecialistStatus, SupervisorMessage};
 use crate::ai::agent::memory::AgentMemory;
+use crate::ai::agent::rate_limiter::AgentRateLimiter;
 use crate::ai::agent::runtime::{AgentRuntime, RuntimeOptions};
 use crate::ai::router::IntelligentRouter;
 use crate::ai::specs::manifest::{AgentSpec, RuntimeMode};
@@ -16,6 +17,7 @@ pub struct SpecialistAgent {
     memory: Arc<AgentMemory>,
     airlock_service: Arc<Option<AirlockService>>,
     kill_switch: Option<AgentKillSwitch>,
+    rate_limiter: Option<Arc<AgentRateLimiter>>,
 }
 
 impl SpecialistAgent {
@@ -38,9 +40,15 @@ impl SpecialistAgent {
             memory,
             airlock_service,
             kill_switch,
+            rate_limiter: None,
         }
     }
 
+    pub fn with_rate_limiter(mut self, rate_limiter: Option<Arc<AgentRateLimiter>>) -> Self {
+        self.rate_limiter = rate_limiter;
+        self
+    }
+
     pub fn allowed_tools(role: &SpecialistRole) -> &'static [&'static str] {
         match role {
             SpecialistRole::Research => &[
@@ -62,6 +70,7 @@ impl SpecialistAgent {
                 "read_file",
                 "read_many_files",
                 "search_files",
+                "semantic_search_workspace",
                 "ingest_document",
             ],
             SpecialistRole::Executor => &[
@@ -73,9 +82,12 @@ impl SpecialistAgent {
                 "file_exists",
                 "get_file_info",
                 "search_files",
+                "semantic_search_workspace",
                 "mkdir",
                 "write_file",
                 "append_file",
+                "edit_file",
+                "apply_patch",
                 "move_file",
                 "delete_file",
                 "git_status",
@@ -84,6 +96,11 @@ impl SpecialistAgent {
                 "git_show",
                 "git_branch_list",
                 "execute_command",
+                "start_process",
+                "read_process_output",
+                "write_process_stdin",
+                "list_processes",
+                "stop_process",
             ],
             SpecialistRole::Verifier => &[
                 "read_file",
@@ -94,6 +111,7 @@ impl SpecialistAgent {
                 "file_exists",
                 "get_file_info",
                 "search_files",
+                "semantic_search_workspace",
                 "git_status",
                 "git_diff",
                 "git_log",
@@ -164,7 +182,8 @@ impl SpecialistAgent {
             self.airlock_service.clone(),
             self.kill_switch.clone(),
             None,
-        );
+        )
+        .with_rate_limiter(self.rate_limiter.clone());
 
         let _ = tx
             .send(SupervisorMessage::SpecialistStarted {
@@ -184,7 +203,16 @@ impl SpecialistAgent {
                     super::events::AgentEvent::ToolCall(ref call) => {
                         if matches!(
                             call.function.name.as_str(),
-                            "write_file" | "append_file" | "mkdir" | "move_file" | "delete_file" | "execute_command"
+                            "write_file"
+                                | "append_file"
+                                | "edit_file"
+                                | "apply_patch"
+                                | "mkdir"
+                                | "move_file"
+                                | "delete_file"
+                                | "execute_command"
+                                | "start_process"
+                                | "write_process_stdin"
                         ) {
                             if let Ok(mut flag) = tool_flag.lock() {
                                 *flag = true;
@@ -214,6 +242,10 @@ impl SpecialistAgent {
                             active_tool: None,
                         });
                     }
+                    super::events::AgentEvent::ToolOutputChunk { id, stream, text } => {
+                        let _ = callback_tx
+                            .try_send(SupervisorMessage::ToolOutputChunk { id, stream, text });
+                    }
                     _ => {}
                 }
             })
@@ -242,6 +274,8 @@ mod tests {
         let tools = SpecialistAgent::allowed_tools(&SpecialistRole::Verifier);
         assert!(!tools.contains(&"write_file"));
         assert!(!tools.contains(&"append_file"));
+        assert!(!tools.contains(&"edit_file"));
+        assert!(!tools.contains(&"apply_patch"));
         assert!(!tools.contains(&"delete_file"));
         assert!(!tools.contains(&"execute_command"));
     }
@@ -251,6 +285,8 @@ mod tests {
         let tools = SpecialistAgent::allowed_tools(&SpecialistRole::Executor);
         assert!(tools.contains(&"write_file"));
         assert!(tools.contains(&"append_file"));
+        assert!(tools.contains(&"edit_file"));
+        assert!(tools.contains(&"apply_patch"));
         assert!(tools.contains(&"delete_file"));
         assert!(tools.contains(&"execute_command"));
     }
@@ -6,6 +6,7 @@ use super::protocol::{
     SpecialistAssignment, SpecialistOutcome, SpecialistRole, SpecialistStatus, SupervisorMessage,
     SupervisorPlan,
 };
+use super::rate_limiter::AgentRateLimiter;
 use super::runtime::RuntimeOptions;
 use super::runtime_registry::RuntimeRegistry;
 use super::specialist::SpecialistAgent;
@@ -26,6 +27,7 @@ pub struct SupervisorAgent {
     pub airlock_service: Arc<Option<AirlockService>>,
     pub kill_switch: Option<AgentKillSwitch>,
     pub runtime_registry: Option<Arc<RuntimeRegistry>>,
+    pub rate_limiter: Option<Arc<AgentRateLimiter>>,
 }
 
 impl SupervisorAgent {
@@ -368,6 +370,9 @@ impl SupervisorAgent {
                         error,
                     }));
                 }
+                SupervisorMessage::ToolOutputChunk { id, stream, text } => {
+                    on_event(AgentEvent::ToolOutputChunk { id, stream, text });
+                }
             }
         }
     }
@@ -441,7 +446,8 @@ impl SupervisorAgent {
                 self.memory.clone(),
                 self.airlock_service.clone(),
                 self.kill_switch.clone(),
-            );
+            )
+            .with_rate_limiter(self.rate_limiter.clone());
             let specialist_input =
                 Self::build_specialist_input(input, &assignment, &completed_outcomes);
 
@@ -542,7 +548,8 @@ impl SupervisorAgent {
                     self.memory.clone(),
                     self.airlock_service.clone(),
                     self.kill_switch.clone(),
-                );
+                )
+                .with_rate_limiter(self.rate_limiter.clone());
                 let verifier_input =
                     Self::build_specialist_input(input, &assignment, &completed_outcomes);
                 match verifier
@@ -608,6 +615,7 @@ mod tests {
             mode: RuntimeMode::Supervisor,
             max_specialists,
             verification_required,
+            ..Default::default()
         }
     }
 
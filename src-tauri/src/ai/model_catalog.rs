@@ -42,9 +42,20 @@ pub fn is_explicit_gemini_model(model: &str) -> bool {
     model.starts_with("gemini:")
 }
 
+pub fn is_explicit_local_model(model: &str) -> bool {
+    model.starts_with("ollama:") || model.starts_with("local:")
+}
+
+pub fn strip_local_prefix(model: &str) -> &str {
+    model
+        .strip_prefix("ollama:")
+        .or_else(|| model.strip_prefix("local:"))
+        .unwrap_or(model)
+}
+
 pub fn is_rainy_catalog_slug(model: &str) -> bool {
     let normalized = normalize_model_slug(model);
-    normalized.contains('/') && !normalized.starts_with("ollama:")
+    normalized.contains('/') && !is_explicit_local_model(normalized)
 }
 
 pub fn requires_rainy_provider(model: &str) -> bool {
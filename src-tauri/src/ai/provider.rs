@@ -66,6 +66,8 @@ impl AIProviderManager {
         match provider {
             "rainy_api" => &["rainy_api", "rainyapi"],
             "gemini" => &["gemini", "gemini_byok"],
+            "brave_search" => &["brave_search"],
+            "tavily" => &["tavily"],
             _ => &[],
         }
     }
@@ -74,6 +76,8 @@ impl AIProviderManager {
         match provider {
             "rainy_api" => &["RAINY_API_KEY"],
             "gemini" => &["GEMINI_API_KEY"],
+            "brave_search" => &["BRAVE_SEARCH_API_KEY"],
+            "tavily" => &["TAVILY_API_KEY"],
             _ => &[],
         }
     }
@@ -543,6 +547,7 @@ impl AIProviderManager {
                                                     thought,
                                                     is_final: false,
                                                     finish_reason: None,
+                                                    tool_calls: None,
                                                 };
                                             token_callback(chunk_data);
                                             full_response.push_str(&content);
@@ -473,6 +473,9 @@ pub struct StreamingChunk {
     pub is_final: bool,
     /// Finish reason (if final)
     pub finish_reason: Option<String>,
+    /// Tool calls assembled from the stream (only set on the final chunk)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_calls: Option<Vec<ToolCall>>,
 }
 
 /// Provider error
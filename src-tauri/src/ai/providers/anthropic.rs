@@ -4,12 +4,14 @@
 use crate::ai::provider_trait::{AIProvider, AIProviderFactory};
 use crate::ai::provider_types::{
     AIError, ChatCompletionRequest, ChatCompletionResponse, ChatMessage, EmbeddingRequest,
-    EmbeddingResponse, ProviderCapabilities, ProviderConfig, ProviderHealth, ProviderId,
-    ProviderResult, ProviderType, StreamingCallback, StreamingChunk, TokenUsage,
+    EmbeddingResponse, FunctionCall, ProviderCapabilities, ProviderConfig, ProviderHealth,
+    ProviderId, ProviderResult, ProviderType, StreamingCallback, StreamingChunk, TokenUsage, Tool,
+    ToolCall, ToolChoice,
 };
 use async_trait::async_trait;
 use futures::StreamExt;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::sync::Arc;
 
 /// Anthropic API base URL
@@ -33,7 +35,36 @@ pub struct AnthropicProvider {
 #[derive(Debug, Serialize, Deserialize, Clone)]
 struct AnthropicMessage {
     role: String,
-    content: String,
+    content: Vec<AnthropicContentBlock>,
+}
+
+/// Anthropic content block (request and response)
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum AnthropicContentBlock {
+    Text {
+        text: String,
+    },
+    ToolUse {
+        id: String,
+        name: String,
+        input: serde_json::Value,
+    },
+    ToolResult {
+        tool_use_id: String,
+        content: String,
+    },
+    /// Block types we do not consume (e.g. thinking)
+    #[serde(other)]
+    Unsupported,
+}
+
+/// Anthropic tool definition
+#[derive(Debug, Serialize)]
+struct AnthropicTool {
+    name: String,
+    description: String,
+    input_schema: serde_json::Value,
 }
 
 /// Anthropic API request body
@@ -52,6 +83,10 @@ struct AnthropicChatRequest {
     #[serde(skip_serializing_if = "Option::is_none")]
     system: Option<String>,
     stream: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tools: Option<Vec<AnthropicTool>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tool_choice: Option<serde_json::Value>,
 }
 
 /// Anthropic API response
@@ -63,12 +98,6 @@ struct AnthropicChatResponse {
     usage: AnthropicUsage,
 }
 
-/// Anthropic content block
-#[derive(Debug, Deserialize)]
-struct AnthropicContentBlock {
-    text: Option<String>,
-}
-
 /// Anthropic token usage
 #[derive(Debug, Deserialize)]
 struct AnthropicUsage {
@@ -103,13 +132,142 @@ struct AnthropicErrorDetail {
     message: String,
 }
 
+/// `content_block_start` streaming event payload
+#[derive(Debug, Deserialize)]
+struct ContentBlockStart {
+    index: usize,
+    content_block: AnthropicContentBlock,
+}
+
+/// `content_block_delta` streaming event payload
+#[derive(Debug, Deserialize)]
+struct ContentBlockDeltaEvent {
+    index: usize,
+    delta: ContentBlockDelta,
+}
+
 /// Content block delta for streaming
 #[derive(Debug, Deserialize)]
-#[allow(dead_code)]
-struct ContentBlockDelta {
-    #[serde(rename = "type")]
-    delta_type: String,
-    text: String,
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ContentBlockDelta {
+    TextDelta {
+        text: String,
+    },
+    InputJsonDelta {
+        partial_json: String,
+    },
+    #[serde(other)]
+    Other,
+}
+
+/// `message_delta` streaming event payload
+#[derive(Debug, Deserialize)]
+struct MessageDeltaEvent {
+    delta: MessageDelta,
+}
+
+/// Message-level delta (carries the stop reason)
+#[derive(Debug, Deserialize)]
+struct MessageDelta {
+    #[serde(default)]
+    stop_reason: Option<String>,
+}
+
+/// Accumulates streamed `tool_use` blocks and the stop reason
+#[derive(Debug, Default)]
+struct AnthropicStreamState {
+    tool_uses: BTreeMap<usize, ToolCall>,
+    stop_reason: Option<String>,
+}
+
+impl AnthropicStreamState {
+    /// Apply one SSE event, returning any text that should be forwarded
+    fn apply(&mut self, event: AnthropicStreamEvent) -> ProviderResult<Option<String>> {
+        match event.event_type.as_str() {
+            "content_block_start" => {
+                if let Ok(start) = serde_json::from_value::<ContentBlockStart>(event.data) {
+                    if let AnthropicContentBlock::ToolUse { id, name, .. } = start.content_block {
+                        // Input arrives incrementally as `input_json_delta` fragments.
+                        self.tool_uses.insert(
+                            start.index,
+                            ToolCall {
+                                id,
+                                r#type: "function".to_string(),
+                                extra_content: None,
+                                function: FunctionCall {
+                                    name,
+                                    arguments: String::new(),
+                                },
+                            },
+                        );
+                    }
+                }
+                Ok(None)
+            }
+            "content_block_delta" => {
+                let Ok(delta) = serde_json::from_value::<ContentBlockDeltaEvent>(event.data) else {
+                    return Ok(None);
+                };
+                match delta.delta {
+                    ContentBlockDelta::TextDelta { text } => Ok(Some(text)),
+                    ContentBlockDelta::InputJsonDelta { partial_json } => {
+                        if let Some(call) = self.tool_uses.get_mut(&delta.index) {
+                            call.function.arguments.push_str(&partial_json);
+                        }
+                        Ok(None)
+                    }
+                    ContentBlockDelta::Other => Ok(None),
+                }
+            }
+            "message_delta" => {
+                if let Ok(message) = serde_json::from_value::<MessageDeltaEvent>(event.data) {
+                    if message.delta.stop_reason.is_some() {
+                        self.stop_reason = message.delta.stop_reason;
+                    }
+                }
+                Ok(None)
+            }
+            "error" => {
+                let message = event
+                    .data
+                    .get("error")
+                    .and_then(|e| e.get("message"))
+                    .and_then(|m| m.as_str())
+                    .unwrap_or("unknown stream error")
+                    .to_string();
+                Err(AIError::APIError(format!(
+                    "Anthropic stream error: {}",
+                    message
+                )))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// Build the final chunk carrying the finish reason and any tool calls
+    fn finish(self) -> StreamingChunk {
+        let tool_calls: Vec<ToolCall> = self
+            .tool_uses
+            .into_values()
+            .map(|mut call| {
+                if call.function.arguments.trim().is_empty() {
+                    call.function.arguments = "{}".to_string();
+                }
+                call
+            })
+            .collect();
+        StreamingChunk {
+            content: String::new(),
+            thought: None,
+            is_final: true,
+            finish_reason: Some(AnthropicProvider::map_stop_reason(self.stop_reason)),
+            tool_calls: if tool_calls.is_empty() {
+                None
+            } else {
+                Some(tool_calls)
+            },
+        }
+    }
 }
 
 impl AnthropicProvider {
@@ -138,37 +296,189 @@ impl AnthropicProvider {
         })
     }
 
-    /// Get the HTTP client
-
-    /// Convert chat messages to Anthropic format
+    /// Convert chat messages to Anthropic format.
+    ///
+    /// Assistant tool calls become `tool_use` blocks and `tool` messages become
+    /// `tool_result` blocks on a user turn. Consecutive turns with the same role
+    /// are merged so parallel tool results land in a single user message.
     fn convert_messages(messages: &[ChatMessage]) -> (Option<String>, Vec<AnthropicMessage>) {
         let mut system_message = None;
-        let mut anthropic_messages = Vec::new();
+        let mut anthropic_messages: Vec<AnthropicMessage> = Vec::new();
 
         for msg in messages {
-            match msg.role.as_str() {
+            let (role, blocks) = match msg.role.as_str() {
                 "system" => {
                     system_message = Some(msg.text());
+                    continue;
                 }
-                "user" | "assistant" => {
-                    anthropic_messages.push(AnthropicMessage {
-                        role: msg.role.clone(),
-                        content: msg.text(),
-                    });
+                "assistant" => {
+                    let mut blocks = Self::text_blocks(msg.text());
+                    for call in msg.tool_calls.iter().flatten() {
+                        let input =
+                            serde_json::from_str::<serde_json::Value>(&call.function.arguments)
+                                .ok()
+                                .filter(|value| value.is_object())
+                                .unwrap_or_else(|| serde_json::json!({}));
+                        blocks.push(AnthropicContentBlock::ToolUse {
+                            id: call.id.clone(),
+                            name: call.function.name.clone(),
+                            input,
+                        });
+                    }
+                    ("assistant", blocks)
                 }
-                _ => {
-                    // Default to user for unknown roles
-                    anthropic_messages.push(AnthropicMessage {
-                        role: "user".to_string(),
+                "tool" => (
+                    "user",
+                    vec![AnthropicContentBlock::ToolResult {
+                        tool_use_id: msg.tool_call_id.clone().unwrap_or_default(),
                         content: msg.text(),
-                    });
-                }
+                    }],
+                ),
+                // User and unknown roles are sent as user turns
+                _ => ("user", Self::text_blocks(msg.text())),
+            };
+
+            if blocks.is_empty() {
+                continue;
+            }
+
+            match anthropic_messages.last_mut() {
+                Some(last) if last.role == role => last.content.extend(blocks),
+                _ => anthropic_messages.push(AnthropicMessage {
+                    role: role.to_string(),
+                    content: blocks,
+                }),
             }
         }
 
         (system_message, anthropic_messages)
     }
 
+    /// Anthropic rejects empty text blocks, so omit them entirely
+    fn text_blocks(text: String) -> Vec<AnthropicContentBlock> {
+        if text.is_empty() {
+            Vec::new()
+        } else {
+            vec![AnthropicContentBlock::Text { text }]
+        }
+    }
+
+    /// Map provider-neutral tool definitions to Anthropic's format
+    fn convert_tools(
+        request: &ChatCompletionRequest,
+    ) -> (Option<Vec<AnthropicTool>>, Option<serde_json::Value>) {
+        let Some(tools) = request.tools.as_ref().filter(|tools| !tools.is_empty()) else {
+            return (None, None);
+        };
+
+        let tools = tools
+            .iter()
+            .map(|tool: &Tool| AnthropicTool {
+                name: tool.function.name.clone(),
+                description: tool.function.description.clone(),
+                input_schema: tool.function.parameters.clone(),
+            })
+            .collect();
+
+        let tool_choice = request.tool_choice.as_ref().map(|choice| match choice {
+            ToolChoice::None => serde_json::json!({ "type": "none" }),
+            ToolChoice::Auto => serde_json::json!({ "type": "auto" }),
+            ToolChoice::Tool(tool) => serde_json::json!({
+                "type": "tool",
+                "name": tool.function.name,
+            }),
+        });
+
+        (Some(tools), tool_choice)
+    }
+
+    /// Build the Anthropic request body
+    fn build_request(
+        request: &ChatCompletionRequest,
+        stream: bool,
+    ) -> ProviderResult<AnthropicChatRequest> {
+        let (system, messages) = Self::convert_messages(&request.messages);
+
+        if messages.is_empty() {
+            return Err(AIError::InvalidRequest(
+                "At least one message is required".to_string(),
+            ));
+        }
+
+        let (tools, tool_choice) = Self::convert_tools(request);
+
+        Ok(AnthropicChatRequest {
+            model: request.model.clone(),
+            messages,
+            max_tokens: request
+                .max_tokens
+                .or_else(|| Some(Self::default_max_tokens(&request.model))),
+            temperature: request.temperature,
+            top_p: request.top_p,
+            stop_sequences: request.stop.clone(),
+            system,
+            stream,
+            tools,
+            tool_choice,
+        })
+    }
+
+    /// Normalize Anthropic stop reasons to the OpenAI-style values used by the router
+    fn map_stop_reason(stop_reason: Option<String>) -> String {
+        match stop_reason.as_deref() {
+            Some("tool_use") => "tool_calls".to_string(),
+            Some(reason) => reason.to_string(),
+            None => "stop".to_string(),
+        }
+    }
+
+    /// Extract text and tool calls from a non-streaming response
+    fn parse_response(
+        chat_response: AnthropicChatResponse,
+    ) -> ProviderResult<ChatCompletionResponse> {
+        let mut content = String::new();
+        let mut tool_calls = Vec::new();
+
+        for block in chat_response.content {
+            match block {
+                AnthropicContentBlock::Text { text } => content.push_str(&text),
+                AnthropicContentBlock::ToolUse { id, name, input } => tool_calls.push(ToolCall {
+                    id,
+                    r#type: "function".to_string(),
+                    extra_content: None,
+                    function: FunctionCall {
+                        name,
+                        arguments: input.to_string(),
+                    },
+                }),
+                AnthropicContentBlock::ToolResult { .. } | AnthropicContentBlock::Unsupported => {}
+            }
+        }
+
+        if content.is_empty() && tool_calls.is_empty() {
+            return Err(AIError::APIError(
+                "Empty response from Anthropic".to_string(),
+            ));
+        }
+
+        Ok(ChatCompletionResponse {
+            content: Some(content),
+            tool_calls: if tool_calls.is_empty() {
+                None
+            } else {
+                Some(tool_calls)
+            },
+            model: chat_response.model,
+            usage: TokenUsage {
+                prompt_tokens: chat_response.usage.input_tokens,
+                completion_tokens: chat_response.usage.output_tokens,
+                total_tokens: chat_response.usage.input_tokens + chat_response.usage.output_tokens,
+            },
+            finish_reason: Self::map_stop_reason(chat_response.stop_reason),
+            provider_metadata: None,
+        })
+    }
+
     /// Map Anthropic error to AIError
     fn map_error(status: reqwest::StatusCode, error: AnthropicError) -> AIError {
         match status {
@@ -247,26 +557,7 @@ impl AIProvider for AnthropicProvider {
         &self,
         request: ChatCompletionRequest,
     ) -> ProviderResult<ChatCompletionResponse> {
-        let (system, messages) = Self::convert_messages(&request.messages);
-
-        if messages.is_empty() {
-            return Err(AIError::InvalidRequest(
-                "At least one message is required".to_string(),
-            ));
-        }
-
-        let anthropic_request = AnthropicChatRequest {
-            model: request.model.clone(),
-            messages,
-            max_tokens: request
-                .max_tokens
-                .or_else(|| Some(Self::default_max_tokens(&request.model))),
-            temperature: request.temperature,
-            top_p: request.top_p,
-            stop_sequences: request.stop,
-            system,
-            stream: false,
-        };
+        let anthropic_request = Self::build_request(&request, false)?;
 
         let response = self
             .client
@@ -294,34 +585,7 @@ impl AIProvider for AnthropicProvider {
             .await
             .map_err(|e| AIError::APIError(format!("Failed to parse response: {}", e)))?;
 
-        // Extract text content from content blocks
-        let content = chat_response
-            .content
-            .iter()
-            .filter_map(|block| block.text.clone())
-            .collect::<Vec<_>>()
-            .join("");
-
-        if content.is_empty() {
-            return Err(AIError::APIError(
-                "Empty response from Anthropic".to_string(),
-            ));
-        }
-
-        Ok(ChatCompletionResponse {
-            content: Some(content),
-            tool_calls: None,
-            model: chat_response.model,
-            usage: TokenUsage {
-                prompt_tokens: chat_response.usage.input_tokens,
-                completion_tokens: chat_response.usage.output_tokens,
-                total_tokens: chat_response.usage.input_tokens + chat_response.usage.output_tokens,
-            },
-            finish_reason: chat_response
-                .stop_reason
-                .unwrap_or_else(|| "stop".to_string()),
-            provider_metadata: None,
-        })
+        Self::parse_response(chat_response)
     }
 
     async fn complete_stream(
@@ -329,26 +593,7 @@ impl AIProvider for AnthropicProvider {
         request: ChatCompletionRequest,
         callback: StreamingCallback,
     ) -> ProviderResult<()> {
-        let (system, messages) = Self::convert_messages(&request.messages);
-
-        if messages.is_empty() {
-            return Err(AIError::InvalidRequest(
-                "At least one message is required".to_string(),
-            ));
-        }
-
-        let anthropic_request = AnthropicChatRequest {
-            model: request.model.clone(),
-            messages,
-            max_tokens: request
-                .max_tokens
-                .or_else(|| Some(Self::default_max_tokens(&request.model))),
-            temperature: request.temperature,
-            top_p: request.top_p,
-            stop_sequences: request.stop,
-            system,
-            stream: true,
-        };
+        let anthropic_request = Self::build_request(&request, true)?;
 
         let response = self
             .client
@@ -373,6 +618,7 @@ impl AIProvider for AnthropicProvider {
 
         let mut stream = response.bytes_stream();
         let mut buffer = String::new();
+        let mut state = AnthropicStreamState::default();
 
         while let Some(chunk) = stream.next().await {
             let chunk = chunk.map_err(|e| AIError::NetworkError(format!("Stream error: {}", e)))?;
@@ -384,39 +630,27 @@ impl AIProvider for AnthropicProvider {
                 let line = buffer.drain(..=pos).collect::<String>();
                 let line = line.trim();
 
-                if line.starts_with("data: ") {
-                    let data = &line[6..];
-
+                if let Some(data) = line.strip_prefix("data: ") {
                     if let Ok(event) = serde_json::from_str::<AnthropicStreamEvent>(data) {
-                        match event.event_type.as_str() {
-                            "content_block_delta" => {
-                                if let Ok(delta) =
-                                    serde_json::from_value::<ContentBlockDelta>(event.data)
-                                {
-                                    callback(StreamingChunk {
-                                        content: delta.text,
-                                        thought: None,
-                                        is_final: false,
-                                        finish_reason: None,
-                                    });
-                                }
-                            }
-                            "message_stop" => {
-                                callback(StreamingChunk {
-                                    content: String::new(),
-                                    thought: None,
-                                    is_final: true,
-                                    finish_reason: Some("stop".to_string()),
-                                });
-                                return Ok(());
-                            }
-                            _ => {}
+                        if event.event_type == "message_stop" {
+                            callback(state.finish());
+                            return Ok(());
+                        }
+                        if let Some(text) = state.apply(event)? {
+                            callback(StreamingChunk {
+                                content: text,
+                                thought: None,
+                                is_final: false,
+                                finish_reason: None,
+                                tool_calls: None,
+                            });
                         }
                     }
                 }
             }
         }
 
+        callback(state.finish());
         Ok(())
     }
 
@@ -514,6 +748,125 @@ mod tests {
         assert_eq!(anthropic_messages.len(), 1);
     }
 
+    #[test]
+    fn test_convert_tool_messages() {
+        let call = |id: &str| ToolCall {
+            id: id.to_string(),
+            r#type: "function".to_string(),
+            extra_content: None,
+            function: FunctionCall {
+                name: "read_file".to_string(),
+                arguments: r#"{"path":"a.txt"}"#.to_string(),
+            },
+        };
+        let tool_result = |id: &str| ChatMessage {
+            role: "tool".to_string(),
+            content: "contents".into(),
+            name: None,
+            tool_calls: None,
+            tool_call_id: Some(id.to_string()),
+        };
+        let messages = vec![
+            ChatMessage::user("Read both files"),
+            ChatMessage {
+                role: "assistant".to_string(),
+                content: "".into(),
+                name: None,
+                tool_calls: Some(vec![call("toolu_1"), call("toolu_2")]),
+                tool_call_id: None,
+            },
+            tool_result("toolu_1"),
+            tool_result("toolu_2"),
+        ];
+
+        let (_, converted) = AnthropicProvider::convert_messages(&messages);
+        assert_eq!(converted.len(), 3);
+
+        let value = serde_json::to_value(&converted).unwrap();
+        assert_eq!(value[1]["role"], "assistant");
+        assert_eq!(value[1]["content"].as_array().unwrap().len(), 2);
+        assert_eq!(value[1]["content"][0]["type"], "tool_use");
+        assert_eq!(value[1]["content"][0]["input"]["path"], "a.txt");
+        assert_eq!(value[2]["role"], "user");
+        assert_eq!(value[2]["content"][0]["type"], "tool_result");
+        assert_eq!(value[2]["content"][1]["tool_use_id"], "toolu_2");
+    }
+
+    #[test]
+    fn test_build_request_maps_tools() {
+        let request = ChatCompletionRequest {
+            messages: vec![ChatMessage::user("Hello")],
+            model: "claude-3-5-sonnet-20241022".to_string(),
+            tools: Some(vec![Tool {
+                r#type: "function".to_string(),
+                function: crate::ai::provider_types::FunctionDefinition {
+                    name: "web_search".to_string(),
+                    description: "Search the web".to_string(),
+                    parameters: serde_json::json!({ "type": "object", "properties": {} }),
+                },
+            }]),
+            tool_choice: Some(ToolChoice::Auto),
+            ..Default::default()
+        };
+
+        let body = serde_json::to_value(AnthropicProvider::build_request(&request, false).unwrap())
+            .unwrap();
+        assert_eq!(body["tools"][0]["name"], "web_search");
+        assert_eq!(body["tools"][0]["input_schema"]["type"], "object");
+        assert_eq!(body["tool_choice"]["type"], "auto");
+    }
+
+    #[test]
+    fn test_parse_tool_use_response() {
+        let response: AnthropicChatResponse = serde_json::from_value(serde_json::json!({
+            "content": [
+                { "type": "thinking", "thinking": "..." },
+                { "type": "text", "text": "Let me check." },
+                { "type": "tool_use", "id": "toolu_1", "name": "read_file", "input": { "path": "a" } }
+            ],
+            "model": "claude-3-5-sonnet-20241022",
+            "stop_reason": "tool_use",
+            "usage": { "input_tokens": 10, "output_tokens": 5 }
+        }))
+        .unwrap();
+
+        let parsed = AnthropicProvider::parse_response(response).unwrap();
+        assert_eq!(parsed.content.as_deref(), Some("Let me check."));
+        assert_eq!(parsed.finish_reason, "tool_calls");
+        let calls = parsed.tool_calls.unwrap();
+        assert_eq!(calls[0].id, "toolu_1");
+        assert_eq!(calls[0].function.arguments, r#"{"path":"a"}"#);
+    }
+
+    #[test]
+    fn test_stream_state_assembles_tool_use() {
+        let events = [
+            r#"{"type":"content_block_start","index":0,"content_block":{"type":"text","text":""}}"#,
+            r#"{"type":"content_block_delta","index":0,"delta":{"type":"text_delta","text":"Hi"}}"#,
+            r#"{"type":"content_block_start","index":1,"content_block":{"type":"tool_use","id":"toolu_9","name":"web_search","input":{}}}"#,
+            r#"{"type":"content_block_delta","index":1,"delta":{"type":"input_json_delta","partial_json":"{\"query\":"}}"#,
+            r#"{"type":"content_block_delta","index":1,"delta":{"type":"input_json_delta","partial_json":"\"rust\"}"}}"#,
+            r#"{"type":"message_delta","delta":{"stop_reason":"tool_use"}}"#,
+        ];
+
+        let mut state = AnthropicStreamState::default();
+        let mut text = String::new();
+        for event in events {
+            let event: AnthropicStreamEvent = serde_json::from_str(event).unwrap();
+            if let Some(chunk) = state.apply(event).unwrap() {
+                text.push_str(&chunk);
+            }
+        }
+
+        assert_eq!(text, "Hi");
+        let final_chunk = state.finish();
+        assert!(final_chunk.is_final);
+        assert_eq!(final_chunk.finish_reason.as_deref(), Some("tool_calls"));
+        let calls = final_chunk.tool_calls.unwrap();
+        assert_eq!(calls[0].id, "toolu_9");
+        assert_eq!(calls[0].function.arguments, r#"{"query":"rust"}"#);
+    }
+
     #[test]
     fn test_available_models() {
         let models = AnthropicProvider::available_models();
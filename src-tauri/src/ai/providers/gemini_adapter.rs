@@ -807,6 +807,7 @@ impl AIProvider for GeminiProviderAdapter {
                             thought: None,
                             is_final: true,
                             finish_reason: Some("stop".to_string()),
+                            tool_calls: None,
                         });
                         return Ok(());
                     }
@@ -835,6 +836,7 @@ impl AIProvider for GeminiProviderAdapter {
                                         thought: None,
                                         is_final,
                                         finish_reason: candidate.finish_reason,
+                                        tool_calls: None,
                                     });
                                 }
                             }
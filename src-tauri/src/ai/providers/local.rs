@@ -0,0 +1,827 @@
+// Local Provider (Ollama / OpenAI-compatible)
+// Runs agents against a self-hosted `/v1/chat/completions` endpoint such as
+// Ollama, llama.cpp server, LM Studio or vLLM. No API key is required.
+
+use crate::ai::model_catalog::strip_local_prefix;
+use crate::ai::provider_trait::{AIProvider, AIProviderFactory};
+use crate::ai::provider_types::{
+    AIError, ChatCompletionRequest, ChatCompletionResponse, ChatMessage, EmbeddingRequest,
+    EmbeddingResponse, MessageContent, ProviderCapabilities, ProviderConfig, ProviderHealth,
+    ProviderId, ProviderResult, ProviderType, StreamingCallback, StreamingChunk, TokenUsage, Tool,
+    ToolCall,
+};
+use crate::ai::providers::openai::{
+    map_tools, outbound_content, outbound_tool_calls, OpenAIToolCallDelta, ToolCallAccumulator,
+};
+use async_trait::async_trait;
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+/// Default base URL (Ollama's OpenAI-compatible API)
+pub const LOCAL_API_BASE: &str = "http://localhost:11434/v1";
+
+/// Provider ID used when a local model is auto-registered for a run
+pub const LOCAL_PROVIDER_ID: &str = "local_llm";
+
+/// Local OpenAI-compatible provider
+pub struct LocalProvider {
+    /// Provider configuration
+    config: ProviderConfig,
+    /// HTTP client
+    client: reqwest::Client,
+    /// Optional bearer token (most local servers ignore it)
+    api_key: Option<String>,
+    /// Base URL, e.g. `http://localhost:11434/v1`
+    base_url: String,
+    /// Models discovered through `/models`
+    cached_models: RwLock<Option<Vec<String>>>,
+}
+
+/// Chat request body
+#[derive(Debug, Serialize)]
+struct LocalChatRequest {
+    model: String,
+    messages: Vec<LocalMessage>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    temperature: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_tokens: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    top_p: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stop: Option<Vec<String>>,
+    stream: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tools: Option<Vec<Tool>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tool_choice: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    response_format: Option<serde_json::Value>,
+}
+
+/// Message format
+#[derive(Debug, Serialize, Deserialize)]
+struct LocalMessage {
+    role: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    content: Option<MessageContent>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    tool_calls: Option<Vec<ToolCall>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    tool_call_id: Option<String>,
+}
+
+/// Chat response
+#[derive(Debug, Deserialize)]
+struct LocalChatResponse {
+    #[serde(default)]
+    model: String,
+    choices: Vec<LocalChoice>,
+    #[serde(default)]
+    usage: Option<LocalUsage>,
+}
+
+/// Response choice
+#[derive(Debug, Deserialize)]
+struct LocalChoice {
+    message: LocalMessage,
+    #[serde(default)]
+    finish_reason: Option<String>,
+}
+
+/// Token usage (optional on many local servers)
+#[derive(Debug, Deserialize)]
+struct LocalUsage {
+    #[serde(default)]
+    prompt_tokens: u32,
+    #[serde(default)]
+    completion_tokens: u32,
+}
+
+/// Streaming chunk
+#[derive(Debug, Deserialize)]
+struct LocalStreamChunk {
+    #[serde(default)]
+    choices: Vec<LocalStreamChoice>,
+}
+
+/// Streaming choice
+#[derive(Debug, Deserialize)]
+struct LocalStreamChoice {
+    delta: LocalDelta,
+    #[serde(default)]
+    finish_reason: Option<String>,
+}
+
+/// Streaming delta
+#[derive(Debug, Deserialize)]
+struct LocalDelta {
+    #[serde(default)]
+    content: Option<String>,
+    #[serde(default)]
+    tool_calls: Option<Vec<OpenAIToolCallDelta>>,
+}
+
+/// Embedding request
+#[derive(Debug, Serialize)]
+struct LocalEmbeddingRequest {
+    model: String,
+    input: String,
+}
+
+/// Embedding response
+#[derive(Debug, Deserialize)]
+struct LocalEmbeddingResponse {
+    data: Vec<LocalEmbeddingData>,
+    #[serde(default)]
+    model: String,
+    #[serde(default)]
+    usage: Option<LocalUsage>,
+}
+
+/// Embedding vector
+#[derive(Debug, Deserialize)]
+struct LocalEmbeddingData {
+    embedding: Vec<f32>,
+}
+
+/// `/models` listing
+#[derive(Debug, Deserialize)]
+struct LocalModelList {
+    data: Vec<LocalModelEntry>,
+}
+
+/// `/models` entry
+#[derive(Debug, Deserialize)]
+struct LocalModelEntry {
+    id: String,
+}
+
+impl LocalProvider {
+    /// Create a new local provider
+    pub fn new(config: ProviderConfig) -> ProviderResult<Self> {
+        let base_url = config
+            .base_url
+            .clone()
+            .filter(|url| !url.trim().is_empty())
+            .unwrap_or_else(|| LOCAL_API_BASE.to_string())
+            .trim_end_matches('/')
+            .to_string();
+
+        let client = reqwest::Client::builder()
+            .timeout(std::time::Duration::from_secs(config.timeout))
+            .build()
+            .map_err(|e| AIError::Configuration(format!("Failed to create HTTP client: {}", e)))?;
+
+        Ok(Self {
+            api_key: config.api_key.clone().filter(|key| !key.is_empty()),
+            config,
+            client,
+            base_url,
+            cached_models: RwLock::new(None),
+        })
+    }
+
+    /// Attach the bearer token when one is configured
+    fn authorize(&self, builder: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        match self.api_key.as_deref() {
+            Some(key) => builder.bearer_auth(key),
+            None => builder,
+        }
+    }
+
+    /// Resolve the model name sent on the wire (`ollama:llama3.1` -> `llama3.1`)
+    fn resolve_model(&self, model: &str) -> String {
+        let model = if model.is_empty() || model == "default" {
+            self.config.model.as_str()
+        } else {
+            model
+        };
+        strip_local_prefix(model).to_string()
+    }
+
+    /// Convert chat messages to the OpenAI-compatible format
+    fn convert_messages(messages: &[ChatMessage]) -> Vec<LocalMessage> {
+        messages
+            .iter()
+            .map(|msg| LocalMessage {
+                role: msg.role.clone(),
+                content: outbound_content(msg),
+                name: msg.name.clone(),
+                tool_calls: outbound_tool_calls(msg.tool_calls.as_ref()),
+                tool_call_id: msg.tool_call_id.clone(),
+            })
+            .collect()
+    }
+
+    /// Build the request body
+    fn build_request(&self, request: &ChatCompletionRequest, stream: bool) -> LocalChatRequest {
+        let (tools, tool_choice) = map_tools(request);
+        LocalChatRequest {
+            model: self.resolve_model(&request.model),
+            messages: Self::convert_messages(&request.messages),
+            temperature: request.temperature,
+            max_tokens: request.max_tokens,
+            top_p: request.top_p,
+            stop: request.stop.clone(),
+            stream,
+            tools,
+            tool_choice,
+            response_format: request
+                .json_mode
+                .then(|| serde_json::json!({ "type": "json_object" })),
+        }
+    }
+
+    /// Map a non-success HTTP response to AIError
+    async fn map_error_response(response: reqwest::Response) -> AIError {
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+        let message = serde_json::from_str::<serde_json::Value>(&body)
+            .ok()
+            .and_then(|v| {
+                v.get("error")
+                    .and_then(|e| e.get("message").or(Some(e)))
+                    .and_then(|m| m.as_str().map(|s| s.to_string()))
+            })
+            .unwrap_or(body);
+
+        match status {
+            reqwest::StatusCode::UNAUTHORIZED | reqwest::StatusCode::FORBIDDEN => {
+                AIError::Authentication(message)
+            }
+            reqwest::StatusCode::TOO_MANY_REQUESTS => AIError::RateLimit(message),
+            reqwest::StatusCode::NOT_FOUND => AIError::ModelNotFound(message),
+            reqwest::StatusCode::BAD_REQUEST => AIError::InvalidRequest(message),
+            _ => AIError::APIError(format!("Local provider error {}: {}", status, message)),
+        }
+    }
+
+    /// Map a transport error, calling out the common "server not running" case
+    fn map_send_error(&self, error: reqwest::Error) -> AIError {
+        if error.is_connect() {
+            AIError::NetworkError(format!(
+                "Local model server unreachable at {}: {}",
+                self.base_url, error
+            ))
+        } else if error.is_timeout() {
+            AIError::Timeout(format!("Local model server timed out: {}", error))
+        } else {
+            AIError::NetworkError(format!("Request failed: {}", error))
+        }
+    }
+
+    /// Query `/models` and refresh the cache
+    async fn discover_models(&self) -> ProviderResult<Vec<String>> {
+        let response = self
+            .authorize(self.client.get(format!("{}/models", self.base_url)))
+            .send()
+            .await
+            .map_err(|e| self.map_send_error(e))?;
+
+        if !response.status().is_success() {
+            return Err(Self::map_error_response(response).await);
+        }
+
+        let list: LocalModelList = response
+            .json()
+            .await
+            .map_err(|e| AIError::APIError(format!("Failed to parse model list: {}", e)))?;
+        let models: Vec<String> = list.data.into_iter().map(|m| m.id).collect();
+
+        *self.cached_models.write().await = Some(models.clone());
+        Ok(models)
+    }
+}
+
+#[async_trait]
+impl AIProvider for LocalProvider {
+    fn id(&self) -> &ProviderId {
+        &self.config.id
+    }
+
+    fn provider_type(&self) -> ProviderType {
+        ProviderType::Local
+    }
+
+    async fn capabilities(&self) -> ProviderResult<ProviderCapabilities> {
+        let models = match self.cached_models.read().await.clone() {
+            Some(models) => models,
+            None => self
+                .discover_models()
+                .await
+                .unwrap_or_else(|_| vec![self.resolve_model(&self.config.model)]),
+        };
+
+        Ok(ProviderCapabilities {
+            chat_completions: true,
+            embeddings: true,
+            streaming: true,
+            function_calling: true,
+            vision: false, // Depends on the loaded model
+            web_search: false,
+            max_context_tokens: 32768,
+            max_output_tokens: 4096,
+            models,
+        })
+    }
+
+    async fn health_check(&self) -> ProviderResult<ProviderHealth> {
+        match self.discover_models().await {
+            Ok(_) => Ok(ProviderHealth::Healthy),
+            Err(AIError::RateLimit(_)) => Ok(ProviderHealth::Degraded),
+            Err(_) => Ok(ProviderHealth::Unhealthy),
+        }
+    }
+
+    async fn complete(
+        &self,
+        request: ChatCompletionRequest,
+    ) -> ProviderResult<ChatCompletionResponse> {
+        let body = self.build_request(&request, false);
+
+        let response = self
+            .authorize(
+                self.client
+                    .post(format!("{}/chat/completions", self.base_url)),
+            )
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| self.map_send_error(e))?;
+
+        if !response.status().is_success() {
+            return Err(Self::map_error_response(response).await);
+        }
+
+        let chat_response: LocalChatResponse = response
+            .json()
+            .await
+            .map_err(|e| AIError::APIError(format!("Failed to parse response: {}", e)))?;
+
+        let choice = chat_response
+            .choices
+            .into_iter()
+            .next()
+            .ok_or_else(|| AIError::APIError("No response choices".to_string()))?;
+
+        let usage = chat_response
+            .usage
+            .map(|u| TokenUsage::new(u.prompt_tokens, u.completion_tokens))
+            .unwrap_or_else(|| TokenUsage::new(0, 0));
+
+        Ok(ChatCompletionResponse {
+            content: choice.message.content.as_ref().map(|c| c.text()),
+            tool_calls: choice.message.tool_calls.filter(|calls| !calls.is_empty()),
+            model: if chat_response.model.is_empty() {
+                body.model
+            } else {
+                chat_response.model
+            },
+            usage,
+            finish_reason: choice.finish_reason.unwrap_or_else(|| "stop".to_string()),
+            provider_metadata: None,
+        })
+    }
+
+    async fn complete_stream(
+        &self,
+        request: ChatCompletionRequest,
+        callback: StreamingCallback,
+    ) -> ProviderResult<()> {
+        let body = self.build_request(&request, true);
+
+        let response = self
+            .authorize(
+                self.client
+                    .post(format!("{}/chat/completions", self.base_url)),
+            )
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| self.map_send_error(e))?;
+
+        if !response.status().is_success() {
+            return Err(Self::map_error_response(response).await);
+        }
+
+        let mut stream = response.bytes_stream();
+        let mut buffer = String::new();
+        let mut tool_calls = ToolCallAccumulator::default();
+        let mut finish_reason: Option<String> = None;
+
+        while let Some(chunk) = stream.next().await {
+            let chunk = chunk.map_err(|e| AIError::NetworkError(format!("Stream error: {}", e)))?;
+            buffer.push_str(&String::from_utf8_lossy(&chunk));
+
+            // Process SSE lines
+            while let Some(pos) = buffer.find('\n') {
+                let line = buffer.drain(..=pos).collect::<String>();
+                let Some(data) = line.trim().strip_prefix("data: ") else {
+                    continue;
+                };
+
+                if data == "[DONE]" {
+                    callback(StreamingChunk {
+                        content: String::new(),
+                        thought: None,
+                        is_final: true,
+                        finish_reason: Some(finish_reason.unwrap_or_else(|| "stop".to_string())),
+                        tool_calls: tool_calls.finish(),
+                    });
+                    return Ok(());
+                }
+
+                if let Ok(chunk_data) = serde_json::from_str::<LocalStreamChunk>(data) {
+                    let Some(choice) = chunk_data.choices.first() else {
+                        continue;
+                    };
+                    if let Some(deltas) = choice.delta.tool_calls.as_ref() {
+                        tool_calls.push(deltas);
+                    }
+                    if choice.finish_reason.is_some() {
+                        finish_reason = choice.finish_reason.clone();
+                    }
+                    if let Some(content) = choice.delta.content.as_ref().filter(|c| !c.is_empty()) {
+                        callback(StreamingChunk {
+                            content: content.clone(),
+                            thought: None,
+                            is_final: false,
+                            finish_reason: None,
+                            tool_calls: None,
+                        });
+                    }
+                }
+            }
+        }
+
+        callback(StreamingChunk {
+            content: String::new(),
+            thought: None,
+            is_final: true,
+            finish_reason: Some(finish_reason.unwrap_or_else(|| "stop".to_string())),
+            tool_calls: tool_calls.finish(),
+        });
+
+        Ok(())
+    }
+
+    async fn embed(&self, request: EmbeddingRequest) -> ProviderResult<EmbeddingResponse> {
+        let body = LocalEmbeddingRequest {
+            model: self.resolve_model(&request.model),
+            input: request.input,
+        };
+
+        let response = self
+            .authorize(self.client.post(format!("{}/embeddings", self.base_url)))
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| self.map_send_error(e))?;
+
+        if !response.status().is_success() {
+            return Err(Self::map_error_response(response).await);
+        }
+
+        let embedding_response: LocalEmbeddingResponse = response
+            .json()
+            .await
+            .map_err(|e| AIError::APIError(format!("Failed to parse response: {}", e)))?;
+
+        let data = embedding_response
+            .data
+            .into_iter()
+            .next()
+            .ok_or_else(|| AIError::APIError("No embedding data".to_string()))?;
+
+        Ok(EmbeddingResponse {
+            embedding: data.embedding,
+            model: if embedding_response.model.is_empty() {
+                body.model
+            } else {
+                embedding_response.model
+            },
+            usage: embedding_response
+                .usage
+                .map(|u| TokenUsage::new(u.prompt_tokens, 0))
+                .unwrap_or_else(|| TokenUsage::new(0, 0)),
+        })
+    }
+
+    fn default_model(&self) -> &str {
+        strip_local_prefix(&self.config.model)
+    }
+
+    async fn available_models(&self) -> ProviderResult<Vec<String>> {
+        self.discover_models().await
+    }
+
+    fn config(&self) -> &ProviderConfig {
+        &self.config
+    }
+}
+
+/// Local provider factory
+pub struct LocalProviderFactory;
+
+#[async_trait]
+impl AIProviderFactory for LocalProviderFactory {
+    async fn create(config: ProviderConfig) -> ProviderResult<Arc<dyn AIProvider>> {
+        Self::validate_config(&config)?;
+        Ok(Arc::new(LocalProvider::new(config)?))
+    }
+
+    fn validate_config(config: &ProviderConfig) -> ProviderResult<()> {
+        if config.model.is_empty() {
+            return Err(AIError::InvalidRequest("Model is required".to_string()));
+        }
+
+        if let Some(base_url) = config.base_url.as_deref().filter(|u| !u.trim().is_empty()) {
+            let parsed = url::Url::parse(base_url).map_err(|e| {
+                AIError::Configuration(format!("Invalid base URL '{}': {}", base_url, e))
+            })?;
+            if !matches!(parsed.scheme(), "http" | "https") {
+                return Err(AIError::Configuration(format!(
+                    "Unsupported base URL scheme '{}'. Use http or https.",
+                    parsed.scheme()
+                )));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai::provider_trait::ProviderWithStats;
+    use crate::ai::router::IntelligentRouter;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    /// Minimal OpenAI-compatible stand-in server. Each request is answered
+    /// from `routes` by path; unknown paths get a 404.
+    async fn spawn_stand_in(routes: Vec<(&'static str, &'static str, String)>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            loop {
+                let Ok((mut socket, _)) = listener.accept().await else {
+                    break;
+                };
+                let routes = routes.clone();
+                tokio::spawn(async move {
+                    let mut raw = Vec::new();
+                    let mut buf = [0u8; 4096];
+                    // Read headers, then the body announced by Content-Length.
+                    loop {
+                        let n = socket.read(&mut buf).await.unwrap_or(0);
+                        if n == 0 {
+                            return;
+                        }
+                        raw.extend_from_slice(&buf[..n]);
+                        let text = String::from_utf8_lossy(&raw).to_string();
+                        if let Some(header_end) = text.find("\r\n\r\n") {
+                            let content_length = text[..header_end]
+                                .lines()
+                                .find_map(|l| {
+                                    l.to_ascii_lowercase()
+                                        .strip_prefix("content-length:")
+                                        .map(|v| v.trim().parse::<usize>().unwrap_or(0))
+                                })
+                                .unwrap_or(0);
+                            if raw.len() >= header_end + 4 + content_length {
+                                break;
+                            }
+                        }
+                    }
+
+                    let request = String::from_utf8_lossy(&raw).to_string();
+                    let path = request.split_whitespace().nth(1).unwrap_or("/").to_string();
+                    let (status, content_type, body) = routes
+                        .iter()
+                        .find(|(route, _, _)| *route == path)
+                        .map(|(_, ct, body)| ("200 OK", *ct, body.clone()))
+                        .unwrap_or(("404 Not Found", "application/json", "{}".to_string()));
+
+                    let response = format!(
+                        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                        status,
+                        content_type,
+                        body.len(),
+                        body
+                    );
+                    let _ = socket.write_all(response.as_bytes()).await;
+                    let _ = socket.shutdown().await;
+                });
+            }
+        });
+
+        format!("http://{}/v1", addr)
+    }
+
+    fn local_config(base_url: String) -> ProviderConfig {
+        ProviderConfig {
+            id: ProviderId::new(LOCAL_PROVIDER_ID),
+            provider_type: ProviderType::Local,
+            api_key: None,
+            base_url: Some(base_url),
+            model: "ollama:llama3.1".to_string(),
+            timeout: 10,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_validate_config() {
+        let mut config = local_config("http://localhost:11434/v1".to_string());
+        assert!(LocalProviderFactory::validate_config(&config).is_ok());
+
+        config.base_url = Some("ftp://localhost".to_string());
+        assert!(LocalProviderFactory::validate_config(&config).is_err());
+
+        config.base_url = None;
+        config.model = String::new();
+        assert!(LocalProviderFactory::validate_config(&config).is_err());
+    }
+
+    #[test]
+    fn test_build_request_strips_prefix_and_maps_tools() {
+        let provider = LocalProvider::new(local_config(LOCAL_API_BASE.to_string())).unwrap();
+        let request = ChatCompletionRequest {
+            model: "ollama:qwen2.5".to_string(),
+            messages: vec![ChatMessage::user("hi")],
+            tools: Some(vec![Tool {
+                r#type: "function".to_string(),
+                function: crate::ai::provider_types::FunctionDefinition {
+                    name: "read_file".to_string(),
+                    description: "Read a file".to_string(),
+                    parameters: serde_json::json!({ "type": "object" }),
+                },
+            }]),
+            tool_choice: Some(crate::ai::provider_types::ToolChoice::Auto),
+            json_mode: true,
+            ..Default::default()
+        };
+
+        let body = serde_json::to_value(provider.build_request(&request, false)).unwrap();
+        assert_eq!(body["model"], "qwen2.5");
+        assert_eq!(body["tools"][0]["function"]["name"], "read_file");
+        assert_eq!(body["tool_choice"], "auto");
+        assert_eq!(body["response_format"]["type"], "json_object");
+
+        let default_model = ChatCompletionRequest {
+            model: "default".to_string(),
+            ..request
+        };
+        assert_eq!(
+            provider.build_request(&default_model, false).model,
+            "llama3.1"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_complete_and_discover_against_stand_in() {
+        let base_url = spawn_stand_in(vec![
+            (
+                "/v1/models",
+                "application/json",
+                r#"{"object":"list","data":[{"id":"llama3.1"},{"id":"nomic-embed-text"}]}"#
+                    .to_string(),
+            ),
+            (
+                "/v1/chat/completions",
+                "application/json",
+                r#"{"model":"llama3.1","choices":[{"message":{"role":"assistant","content":null,"tool_calls":[{"id":"call_0","type":"function","function":{"name":"read_file","arguments":"{\"path\":\"a\"}"}}]},"finish_reason":"tool_calls"}]}"#
+                    .to_string(),
+            ),
+            (
+                "/v1/embeddings",
+                "application/json",
+                r#"{"data":[{"embedding":[0.1,0.2,0.3]}],"model":"nomic-embed-text"}"#.to_string(),
+            ),
+        ])
+        .await;
+
+        let provider = LocalProviderFactory::create(local_config(base_url))
+            .await
+            .unwrap();
+
+        let models = provider.available_models().await.unwrap();
+        assert_eq!(models, vec!["llama3.1", "nomic-embed-text"]);
+        assert_eq!(
+            provider.health_check().await.unwrap(),
+            ProviderHealth::Healthy
+        );
+
+        let response = provider
+            .complete(ChatCompletionRequest {
+                model: "ollama:llama3.1".to_string(),
+                messages: vec![ChatMessage::user("read a")],
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(response.finish_reason, "tool_calls");
+        assert_eq!(response.tool_calls.unwrap()[0].function.name, "read_file");
+
+        let embedding = provider
+            .embed(EmbeddingRequest {
+                input: "hello".to_string(),
+                model: "nomic-embed-text".to_string(),
+            })
+            .await
+            .unwrap();
+        assert_eq!(embedding.embedding.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_stream_against_stand_in() {
+        let sse = [
+            r#"data: {"choices":[{"delta":{"content":"Hel"}}]}"#,
+            r#"data: {"choices":[{"delta":{"content":"lo"}}]}"#,
+            r#"data: {"choices":[{"delta":{},"finish_reason":"stop"}]}"#,
+            "data: [DONE]",
+        ]
+        .iter()
+        .map(|line| format!("{}\n\n", line))
+        .collect::<String>();
+        let base_url =
+            spawn_stand_in(vec![("/v1/chat/completions", "text/event-stream", sse)]).await;
+
+        let provider = LocalProvider::new(local_config(base_url)).unwrap();
+        let collected = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let sink = Arc::clone(&collected);
+        provider
+            .complete_stream(
+                ChatCompletionRequest {
+                    model: "ollama:llama3.1".to_string(),
+                    messages: vec![ChatMessage::user("hi")],
+                    stream: true,
+                    ..Default::default()
+                },
+                Arc::new(move |chunk: StreamingChunk| sink.lock().unwrap().push(chunk)),
+            )
+            .await
+            .unwrap();
+
+        let chunks = collected.lock().unwrap();
+        let text: String = chunks.iter().map(|c| c.content.as_str()).collect();
+        assert_eq!(text, "Hello");
+        assert!(chunks.last().unwrap().is_final);
+    }
+
+    #[tokio::test]
+    async fn test_router_pins_local_models() {
+        let local = spawn_stand_in(vec![(
+            "/v1/chat/completions",
+            "application/json",
+            r#"{"model":"llama3.1","choices":[{"message":{"role":"assistant","content":"pong"},"finish_reason":"stop"}]}"#
+                .to_string(),
+        )])
+        .await;
+
+        // A cloud provider registered first must not receive local model requests.
+        let cloud = crate::ai::providers::OpenAIProviderFactory::create(ProviderConfig {
+            id: ProviderId::new("openai"),
+            provider_type: ProviderType::OpenAI,
+            api_key: Some("sk-test".to_string()),
+            base_url: Some("http://127.0.0.1:9/v1".to_string()),
+            model: "gpt-4o".to_string(),
+            timeout: 5,
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+
+        let mut router = IntelligentRouter::default();
+        router.add_provider(Arc::new(ProviderWithStats::new(cloud)));
+
+        let request = ChatCompletionRequest {
+            model: "ollama:llama3.1".to_string(),
+            messages: vec![ChatMessage::user("ping")],
+            ..Default::default()
+        };
+        match router.complete(request.clone()).await {
+            Err(AIError::ProviderNotFound(message)) => assert!(message.contains("Local")),
+            other => panic!(
+                "expected pinned local provider error, got {:?}",
+                other.map(|r| r.content)
+            ),
+        }
+
+        router.add_provider(Arc::new(ProviderWithStats::new(
+            LocalProviderFactory::create(local_config(local))
+                .await
+                .unwrap(),
+        )));
+        let response = router.complete(request).await.unwrap();
+        assert_eq!(response.content.as_deref(), Some("pong"));
+    }
+}
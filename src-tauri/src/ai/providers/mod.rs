@@ -3,6 +3,7 @@
 
 pub mod anthropic;
 pub mod gemini_adapter;
+pub mod local;
 pub mod moonshot;
 pub mod openai;
 pub mod rainy_sdk;
@@ -11,6 +12,7 @@ pub mod xai;
 // Only re-export factories (which are used for registration)
 pub use anthropic::AnthropicProviderFactory;
 pub use gemini_adapter::{GeminiProviderAdapter, GeminiProviderFactory};
+pub use local::LocalProviderFactory;
 pub use moonshot::MoonshotProviderFactory;
 pub use openai::OpenAIProviderFactory;
 pub use rainy_sdk::RainySDKProviderFactory;
@@ -21,3 +23,4 @@ pub use xai::XAIProviderFactory;
 // - openai::OpenAIProvider
 // - anthropic::AnthropicProvider
 // - xai::XAIProvider
+// - local::LocalProvider
@@ -341,6 +341,7 @@ impl AIProvider for MoonshotProvider {
                             thought: None,
                             is_final: true,
                             finish_reason: Some("stop".to_string()),
+                            tool_calls: None,
                         });
                         return Ok(());
                     }
@@ -353,6 +354,7 @@ impl AIProvider for MoonshotProvider {
                                     thought: None, // Kimi puts thought in content usually? Or maybe separate field?
                                     is_final: choice.finish_reason.is_some(),
                                     finish_reason: choice.finish_reason.clone(),
+                                    tool_calls: None,
                                 });
                             }
                         }
@@ -5,12 +5,14 @@ use crate::ai::provider_trait::{AIProvider, AIProviderFactory};
 use crate::ai::provider_types::MessageContent;
 use crate::ai::provider_types::{
     AIError, ChatCompletionRequest, ChatCompletionResponse, ChatMessage, EmbeddingRequest,
-    EmbeddingResponse, ProviderCapabilities, ProviderConfig, ProviderHealth, ProviderId,
-    ProviderResult, ProviderType, StreamingCallback, StreamingChunk, TokenUsage,
+    EmbeddingResponse, FunctionCall, ProviderCapabilities, ProviderConfig, ProviderHealth,
+    ProviderId, ProviderResult, ProviderType, StreamingCallback, StreamingChunk, TokenUsage, Tool,
+    ToolCall, ToolChoice,
 };
 use async_trait::async_trait;
 use futures::StreamExt;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::sync::Arc;
 
 /// OpenAI API base URL
@@ -46,16 +48,24 @@ struct OpenAIChatRequest {
     #[serde(skip_serializing_if = "Option::is_none")]
     stop: Option<Vec<String>>,
     stream: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tools: Option<Vec<Tool>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tool_choice: Option<serde_json::Value>,
 }
 
 /// OpenAI message format
 #[derive(Debug, Serialize, Deserialize)]
 struct OpenAIMessage {
     role: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     content: Option<MessageContent>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    tool_calls: Option<Vec<ToolCall>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    tool_call_id: Option<String>,
 }
 
 /// OpenAI API response
@@ -120,6 +130,148 @@ struct OpenAIStreamChoice {
 struct OpenAIDelta {
     #[serde(default)]
     content: Option<String>,
+    #[serde(default)]
+    tool_calls: Option<Vec<OpenAIToolCallDelta>>,
+}
+
+/// Partial tool call emitted inside a streaming delta.
+/// Fragments sharing the same `index` belong to the same call.
+#[derive(Debug, Clone, Deserialize)]
+pub struct OpenAIToolCallDelta {
+    #[serde(default)]
+    pub index: usize,
+    #[serde(default)]
+    pub id: Option<String>,
+    #[serde(default, rename = "type")]
+    pub call_type: Option<String>,
+    #[serde(default)]
+    pub function: Option<OpenAIFunctionDelta>,
+}
+
+/// Partial function payload of a streaming tool call
+#[derive(Debug, Clone, Deserialize)]
+pub struct OpenAIFunctionDelta {
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub arguments: Option<String>,
+}
+
+/// Reassembles streamed tool call fragments into complete `ToolCall`s
+#[derive(Debug, Default)]
+pub(crate) struct ToolCallAccumulator {
+    calls: BTreeMap<usize, ToolCall>,
+}
+
+impl ToolCallAccumulator {
+    /// Merge a batch of delta fragments into the accumulated calls
+    pub fn push(&mut self, deltas: &[OpenAIToolCallDelta]) {
+        for delta in deltas {
+            let entry = self.calls.entry(delta.index).or_insert_with(|| ToolCall {
+                id: String::new(),
+                r#type: "function".to_string(),
+                extra_content: None,
+                function: FunctionCall {
+                    name: String::new(),
+                    arguments: String::new(),
+                },
+            });
+            if let Some(id) = delta.id.as_ref().filter(|id| !id.is_empty()) {
+                entry.id = id.clone();
+            }
+            if let Some(call_type) = delta.call_type.as_ref() {
+                entry.r#type = call_type.clone();
+            }
+            if let Some(function) = delta.function.as_ref() {
+                if let Some(name) = function.name.as_ref() {
+                    entry.function.name.push_str(name);
+                }
+                if let Some(arguments) = function.arguments.as_ref() {
+                    entry.function.arguments.push_str(arguments);
+                }
+            }
+        }
+    }
+
+    /// Finish accumulation, returning the calls in stream order (None if empty)
+    pub fn finish(self) -> Option<Vec<ToolCall>> {
+        let calls: Vec<ToolCall> = self
+            .calls
+            .into_values()
+            .filter(|call| !call.function.name.is_empty())
+            .enumerate()
+            .map(|(idx, mut call)| {
+                if call.id.is_empty() {
+                    call.id = format!("call_{}", idx);
+                }
+                if call.function.arguments.trim().is_empty() {
+                    call.function.arguments = "{}".to_string();
+                }
+                call
+            })
+            .collect();
+        if calls.is_empty() {
+            None
+        } else {
+            Some(calls)
+        }
+    }
+}
+
+/// Map the provider-neutral tool choice to the OpenAI wire format.
+/// `ToolChoice` is an untagged enum whose unit variants serialize to `null`,
+/// so it cannot be forwarded as-is.
+pub(crate) fn map_tool_choice(tool_choice: Option<&ToolChoice>) -> Option<serde_json::Value> {
+    match tool_choice? {
+        ToolChoice::None => Some(serde_json::json!("none")),
+        ToolChoice::Auto => Some(serde_json::json!("auto")),
+        ToolChoice::Tool(tool) => Some(serde_json::json!({
+            "type": "function",
+            "function": { "name": tool.function.name },
+        })),
+    }
+}
+
+/// Tools and tool choice for an OpenAI-compatible request.
+/// `tool_choice` is only valid alongside a non-empty tool list.
+pub(crate) fn map_tools(
+    request: &ChatCompletionRequest,
+) -> (Option<Vec<Tool>>, Option<serde_json::Value>) {
+    match request.tools.as_ref().filter(|tools| !tools.is_empty()) {
+        Some(tools) => (
+            Some(tools.clone()),
+            map_tool_choice(request.tool_choice.as_ref()),
+        ),
+        None => (None, None),
+    }
+}
+
+/// Strip provider-specific metadata (e.g. Gemini thought signatures) from
+/// tool calls before echoing them back to an OpenAI-compatible endpoint.
+pub(crate) fn outbound_tool_calls(calls: Option<&Vec<ToolCall>>) -> Option<Vec<ToolCall>> {
+    calls.filter(|calls| !calls.is_empty()).map(|calls| {
+        calls
+            .iter()
+            .map(|call| ToolCall {
+                extra_content: None,
+                ..call.clone()
+            })
+            .collect()
+    })
+}
+
+/// Content for an outbound OpenAI-compatible message.
+/// Assistant tool-call turns carry no text, and tool results are text-only.
+pub(crate) fn outbound_content(msg: &ChatMessage) -> Option<MessageContent> {
+    let has_tool_calls = msg
+        .tool_calls
+        .as_ref()
+        .is_some_and(|calls| !calls.is_empty());
+    match &msg.content {
+        MessageContent::Text(text) if text.is_empty() && has_tool_calls => None,
+        MessageContent::Parts(_) if msg.role == "tool" => Some(MessageContent::Text(msg.text())),
+        content => Some(content.clone()),
+    }
 }
 
 /// OpenAI error response
@@ -160,20 +312,38 @@ impl OpenAIProvider {
         })
     }
 
-    /// Get the HTTP client
-
     /// Convert chat messages to OpenAI format
     fn convert_messages(messages: &[ChatMessage]) -> Vec<OpenAIMessage> {
         messages
             .iter()
             .map(|msg| OpenAIMessage {
                 role: msg.role.clone(),
-                content: Some(msg.content.clone()),
+                content: outbound_content(msg),
                 name: msg.name.clone(),
+                tool_calls: outbound_tool_calls(msg.tool_calls.as_ref()),
+                tool_call_id: msg.tool_call_id.clone(),
             })
             .collect()
     }
 
+    /// Build the OpenAI request body
+    fn build_request(request: &ChatCompletionRequest, stream: bool) -> OpenAIChatRequest {
+        let (tools, tool_choice) = map_tools(request);
+        OpenAIChatRequest {
+            model: request.model.clone(),
+            messages: Self::convert_messages(&request.messages),
+            temperature: request.temperature,
+            max_tokens: request.max_tokens,
+            top_p: request.top_p,
+            frequency_penalty: request.frequency_penalty,
+            presence_penalty: request.presence_penalty,
+            stop: request.stop.clone(),
+            stream,
+            tools,
+            tool_choice,
+        }
+    }
+
     /// Map OpenAI error to AIError
     fn map_error(status: reqwest::StatusCode, error: OpenAIError) -> AIError {
         match status {
@@ -250,17 +420,7 @@ impl AIProvider for OpenAIProvider {
         &self,
         request: ChatCompletionRequest,
     ) -> ProviderResult<ChatCompletionResponse> {
-        let openai_request = OpenAIChatRequest {
-            model: request.model.clone(),
-            messages: Self::convert_messages(&request.messages),
-            temperature: request.temperature,
-            max_tokens: request.max_tokens,
-            top_p: request.top_p,
-            frequency_penalty: request.frequency_penalty,
-            presence_penalty: request.presence_penalty,
-            stop: request.stop,
-            stream: false,
-        };
+        let openai_request = Self::build_request(&request, false);
 
         let response = self
             .client
@@ -298,7 +458,7 @@ impl AIProvider for OpenAIProvider {
                 .content
                 .as_ref()
                 .map(|c: &MessageContent| c.text()),
-            tool_calls: None,
+            tool_calls: choice.message.tool_calls.filter(|calls| !calls.is_empty()),
             model: chat_response.model,
             usage: TokenUsage {
                 prompt_tokens: chat_response.usage.prompt_tokens,
@@ -315,17 +475,7 @@ impl AIProvider for OpenAIProvider {
         request: ChatCompletionRequest,
         callback: StreamingCallback,
     ) -> ProviderResult<()> {
-        let openai_request = OpenAIChatRequest {
-            model: request.model.clone(),
-            messages: Self::convert_messages(&request.messages),
-            temperature: request.temperature,
-            max_tokens: request.max_tokens,
-            top_p: request.top_p,
-            frequency_penalty: request.frequency_penalty,
-            presence_penalty: request.presence_penalty,
-            stop: request.stop,
-            stream: true,
-        };
+        let openai_request = Self::build_request(&request, true);
 
         let response = self
             .client
@@ -348,6 +498,8 @@ impl AIProvider for OpenAIProvider {
 
         let mut stream = response.bytes_stream();
         let mut buffer = String::new();
+        let mut tool_calls = ToolCallAccumulator::default();
+        let mut finish_reason: Option<String> = None;
 
         while let Some(chunk) = stream.next().await {
             let chunk = chunk.map_err(|e| AIError::NetworkError(format!("Stream error: {}", e)))?;
@@ -359,27 +511,37 @@ impl AIProvider for OpenAIProvider {
                 let line = buffer.drain(..=pos).collect::<String>();
                 let line = line.trim();
 
-                if line.starts_with("data: ") {
-                    let data = &line[6..];
-
+                if let Some(data) = line.strip_prefix("data: ") {
                     if data == "[DONE]" {
                         callback(StreamingChunk {
                             content: String::new(),
                             thought: None,
                             is_final: true,
-                            finish_reason: Some("stop".to_string()),
+                            finish_reason: Some(
+                                finish_reason.unwrap_or_else(|| "stop".to_string()),
+                            ),
+                            tool_calls: tool_calls.finish(),
                         });
                         return Ok(());
                     }
 
                     if let Ok(chunk_data) = serde_json::from_str::<OpenAIStreamChunk>(data) {
                         if let Some(choice) = chunk_data.choices.first() {
-                            if let Some(content) = &choice.delta.content {
+                            if let Some(deltas) = &choice.delta.tool_calls {
+                                tool_calls.push(deltas);
+                            }
+                            if choice.finish_reason.is_some() {
+                                finish_reason = choice.finish_reason.clone();
+                            }
+                            if let Some(content) =
+                                choice.delta.content.as_ref().filter(|c| !c.is_empty())
+                            {
                                 callback(StreamingChunk {
                                     content: content.clone(),
                                     thought: None,
-                                    is_final: choice.finish_reason.is_some(),
-                                    finish_reason: choice.finish_reason.clone(),
+                                    is_final: false,
+                                    finish_reason: None,
+                                    tool_calls: None,
                                 });
                             }
                         }
@@ -388,6 +550,15 @@ impl AIProvider for OpenAIProvider {
             }
         }
 
+        // Stream closed without [DONE]; still deliver what was assembled.
+        callback(StreamingChunk {
+            content: String::new(),
+            thought: None,
+            is_final: true,
+            finish_reason: Some(finish_reason.unwrap_or_else(|| "stop".to_string())),
+            tool_calls: tool_calls.finish(),
+        });
+
         Ok(())
     }
 
@@ -505,6 +676,126 @@ mod tests {
         assert_eq!(openai_messages[2].role, "assistant");
     }
 
+    #[test]
+    fn test_convert_tool_round_trip_messages() {
+        let call = ToolCall {
+            id: "call_1".to_string(),
+            r#type: "function".to_string(),
+            extra_content: Some(serde_json::json!({ "google": { "thought_signature": "sig" } })),
+            function: FunctionCall {
+                name: "read_file".to_string(),
+                arguments: r#"{"path":"a.txt"}"#.to_string(),
+            },
+        };
+        let messages = vec![
+            ChatMessage {
+                role: "assistant".to_string(),
+                content: "".into(),
+                name: None,
+                tool_calls: Some(vec![call]),
+                tool_call_id: None,
+            },
+            ChatMessage {
+                role: "tool".to_string(),
+                content: "file contents".into(),
+                name: None,
+                tool_calls: None,
+                tool_call_id: Some("call_1".to_string()),
+            },
+        ];
+
+        let value = serde_json::to_value(OpenAIProvider::convert_messages(&messages)).unwrap();
+        assert!(value[0].get("content").is_none());
+        assert_eq!(value[0]["tool_calls"][0]["id"], "call_1");
+        assert_eq!(value[0]["tool_calls"][0]["type"], "function");
+        assert_eq!(value[0]["tool_calls"][0]["function"]["name"], "read_file");
+        assert!(value[0]["tool_calls"][0].get("extra_content").is_none());
+        assert_eq!(value[1]["role"], "tool");
+        assert_eq!(value[1]["tool_call_id"], "call_1");
+        assert_eq!(value[1]["content"], "file contents");
+    }
+
+    #[test]
+    fn test_build_request_maps_tools() {
+        let request = ChatCompletionRequest {
+            messages: vec![ChatMessage::user("Hello")],
+            model: "gpt-4o".to_string(),
+            tools: Some(vec![Tool {
+                r#type: "function".to_string(),
+                function: crate::ai::provider_types::FunctionDefinition {
+                    name: "web_search".to_string(),
+                    description: "Search the web".to_string(),
+                    parameters: serde_json::json!({ "type": "object", "properties": {} }),
+                },
+            }]),
+            tool_choice: Some(ToolChoice::Auto),
+            ..Default::default()
+        };
+
+        let value = serde_json::to_value(OpenAIProvider::build_request(&request, false)).unwrap();
+        assert_eq!(value["tools"][0]["function"]["name"], "web_search");
+        assert_eq!(value["tool_choice"], "auto");
+
+        let no_tools = ChatCompletionRequest {
+            tools: None,
+            ..request
+        };
+        let value = serde_json::to_value(OpenAIProvider::build_request(&no_tools, false)).unwrap();
+        assert!(value.get("tools").is_none());
+        assert!(value.get("tool_choice").is_none());
+    }
+
+    #[test]
+    fn test_parse_tool_call_response() {
+        let body = serde_json::json!({
+            "model": "gpt-4o",
+            "choices": [{
+                "message": {
+                    "role": "assistant",
+                    "content": null,
+                    "tool_calls": [{
+                        "id": "call_abc",
+                        "type": "function",
+                        "function": { "name": "read_file", "arguments": "{\"path\":\"a\"}" }
+                    }]
+                },
+                "finish_reason": "tool_calls"
+            }],
+            "usage": { "prompt_tokens": 1, "completion_tokens": 2, "total_tokens": 3 }
+        });
+
+        let response: OpenAIChatResponse = serde_json::from_value(body).unwrap();
+        let message = &response.choices[0].message;
+        assert!(message.content.is_none());
+        let calls = message.tool_calls.as_ref().unwrap();
+        assert_eq!(calls[0].id, "call_abc");
+        assert_eq!(calls[0].function.name, "read_file");
+    }
+
+    #[test]
+    fn test_tool_call_accumulator() {
+        let fragments = [
+            r#"[{"index":0,"id":"call_a","type":"function","function":{"name":"read_","arguments":""}}]"#,
+            r#"[{"index":0,"function":{"name":"file","arguments":"{\"path\":"}}]"#,
+            r#"[{"index":1,"id":"call_b","function":{"name":"web_search","arguments":"{}"}}]"#,
+            r#"[{"index":0,"function":{"arguments":"\"a.txt\"}"}}]"#,
+        ];
+
+        let mut accumulator = ToolCallAccumulator::default();
+        for fragment in fragments {
+            let deltas: Vec<OpenAIToolCallDelta> = serde_json::from_str(fragment).unwrap();
+            accumulator.push(&deltas);
+        }
+
+        let calls = accumulator.finish().unwrap();
+        assert_eq!(calls.len(), 2);
+        assert_eq!(calls[0].id, "call_a");
+        assert_eq!(calls[0].function.name, "read_file");
+        assert_eq!(calls[0].function.arguments, r#"{"path":"a.txt"}"#);
+        assert_eq!(calls[1].id, "call_b");
+        assert!(ToolCallAccumulator::default().finish().is_none());
+    }
+
     #[test]
     fn test_available_models() {
         let models = OpenAIProvider::available_models();
@@ -759,6 +759,7 @@ impl AIProvider for RainySDKProvider {
                                     thought,
                                     is_final,
                                     finish_reason,
+                                    tool_calls: None,
                                 });
                             }
                         }
@@ -780,6 +781,7 @@ impl AIProvider for RainySDKProvider {
                     thought: None,
                     is_final: true,
                     finish_reason: Some(response.finish_reason),
+                    tool_calls: response.tool_calls,
                 });
                 Ok(())
             }
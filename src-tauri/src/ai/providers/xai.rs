@@ -1,7 +1,10 @@
 // xAI Provider for Grok Models
 // Direct integration with xAI's Grok API using OpenAI-compatible endpoints
 
-use crate::ai::provider_types::MessageContent;
+use crate::ai::provider_types::{MessageContent, Tool, ToolCall};
+use crate::ai::providers::openai::{
+    map_tools, outbound_content, outbound_tool_calls, OpenAIToolCallDelta, ToolCallAccumulator,
+};
 use crate::ai::{
     AIError, AIProvider, AIProviderFactory, ChatCompletionRequest, ChatCompletionResponse,
     ChatMessage, EmbeddingRequest, EmbeddingResponse, ProviderCapabilities, ProviderConfig,
@@ -181,7 +184,8 @@ impl AIProvider for XAIProvider {
         request: ChatCompletionRequest,
         callback: StreamingCallback,
     ) -> ProviderResult<()> {
-        let request_body = XAIChatRequest::from(request.clone());
+        let mut request_body = XAIChatRequest::from(request);
+        request_body.stream = true;
         let request_builder = self
             .client
             .post(&format!("{}/chat/completions", self.base_url()))
@@ -230,40 +234,64 @@ impl XAIProvider {
             .map_err(|e| AIError::NetworkError(e.to_string()))?;
 
         let mut stream = response.bytes_stream();
+        // SSE events can straddle network chunks (tool call arguments often do),
+        // so buffer until a full line is available.
+        let mut buffer = String::new();
+        let mut tool_calls = ToolCallAccumulator::default();
+        let mut finish_reason: Option<String> = None;
 
         while let Some(chunk) = stream.next().await {
             let chunk = chunk.map_err(|e| AIError::NetworkError(e.to_string()))?;
+            buffer.push_str(&String::from_utf8_lossy(&chunk));
+
+            while let Some(pos) = buffer.find('\n') {
+                let line = buffer.drain(..=pos).collect::<String>();
+                let Some(data) = line.trim().strip_prefix("data: ") else {
+                    continue;
+                };
+                if data == "[DONE]" {
+                    callback(StreamingChunk {
+                        content: String::new(),
+                        thought: None,
+                        is_final: true,
+                        finish_reason: Some(finish_reason.unwrap_or_else(|| "stop".to_string())),
+                        tool_calls: tool_calls.finish(),
+                    });
+                    return Ok(());
+                }
 
-            let text = String::from_utf8_lossy(&chunk);
-            let lines: Vec<&str> = text.split('\n').filter(|l| !l.is_empty()).collect();
-
-            for line in lines {
-                if line.starts_with("data: ") {
-                    let data = &line[6..];
-                    if data == "[DONE]" {
-                        return Ok(());
+                // Parse SSE data
+                if let Ok(chunk) = serde_json::from_str::<XAIStreamingChunk>(data) {
+                    let Some(choice) = chunk.choices.first() else {
+                        continue;
+                    };
+                    if let Some(deltas) = choice.delta.tool_calls.as_ref() {
+                        tool_calls.push(deltas);
                     }
-
-                    // Parse SSE data
-                    if let Ok(chunk) = serde_json::from_str::<XAIStreamingChunk>(data) {
-                        if let Some(delta) =
-                            chunk.choices.first().and_then(|c| c.delta.content.clone())
-                        {
-                            let _ = callback(StreamingChunk {
-                                content: delta,
-                                thought: None,
-                                is_final: false,
-                                finish_reason: chunk
-                                    .choices
-                                    .first()
-                                    .and_then(|c| c.finish_reason.clone()),
-                            });
-                        }
+                    if choice.finish_reason.is_some() {
+                        finish_reason = choice.finish_reason.clone();
+                    }
+                    if let Some(delta) = choice.delta.content.clone().filter(|c| !c.is_empty()) {
+                        callback(StreamingChunk {
+                            content: delta,
+                            thought: None,
+                            is_final: false,
+                            finish_reason: None,
+                            tool_calls: None,
+                        });
                     }
                 }
             }
         }
 
+        callback(StreamingChunk {
+            content: String::new(),
+            thought: None,
+            is_final: true,
+            finish_reason: Some(finish_reason.unwrap_or_else(|| "stop".to_string())),
+            tool_calls: tool_calls.finish(),
+        });
+
         Ok(())
     }
 }
@@ -286,10 +314,15 @@ pub struct XAIChatRequest {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub stop: Option<Vec<String>>,
     pub stream: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tools: Option<Vec<Tool>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool_choice: Option<serde_json::Value>,
 }
 
 impl From<ChatCompletionRequest> for XAIChatRequest {
     fn from(req: ChatCompletionRequest) -> Self {
+        let (tools, tool_choice) = map_tools(&req);
         Self {
             model: req.model.clone(),
             messages: req.messages.into_iter().map(|m| m.into()).collect(),
@@ -300,6 +333,8 @@ impl From<ChatCompletionRequest> for XAIChatRequest {
             presence_penalty: req.presence_penalty,
             stop: req.stop,
             stream: false,
+            tools,
+            tool_choice,
         }
     }
 }
@@ -308,18 +343,24 @@ impl From<ChatCompletionRequest> for XAIChatRequest {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct XAIChatMessage {
     pub role: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub content: Option<MessageContent>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_calls: Option<Vec<ToolCall>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_call_id: Option<String>,
 }
 
 impl From<ChatMessage> for XAIChatMessage {
     fn from(msg: ChatMessage) -> Self {
         Self {
+            content: outbound_content(&msg),
+            tool_calls: outbound_tool_calls(msg.tool_calls.as_ref()),
             role: msg.role,
-            content: Some(msg.content),
             name: msg.name,
+            tool_call_id: msg.tool_call_id,
         }
     }
 }
@@ -358,9 +399,15 @@ impl XAIChatResponse {
             .and_then(|c| c.finish_reason.clone())
             .unwrap_or_else(|| "stop".to_string());
 
+        let tool_calls = self
+            .choices
+            .first()
+            .and_then(|c| c.message.tool_calls.clone())
+            .filter(|calls| !calls.is_empty());
+
         ChatCompletionResponse {
             content: Some(content),
-            tool_calls: None,
+            tool_calls,
             model: self.model.clone(),
             usage,
             finish_reason,
@@ -402,7 +449,10 @@ pub struct XAIStreamingChoice {
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct XAIDelta {
+    #[serde(default)]
     pub content: Option<String>,
+    #[serde(default)]
+    pub tool_calls: Option<Vec<OpenAIToolCallDelta>>,
 }
 
 /// xAI Provider Factory
@@ -463,6 +513,7 @@ mod tests {
             tools: None,
             tool_choice: None,
             json_mode: false,
+            reasoning_effort: None,
         };
 
         let xai_request = XAIChatRequest::from(request);
@@ -490,6 +541,77 @@ mod tests {
         assert_eq!(xai_request.temperature, Some(0.7));
         assert_eq!(xai_request.max_tokens, Some(100));
         assert!(!xai_request.stream);
+        assert!(xai_request.tools.is_none());
+        assert!(xai_request.tool_choice.is_none());
+    }
+
+    #[test]
+    fn test_xai_tool_calls_round_trip() {
+        let request = ChatCompletionRequest {
+            model: "grok-4".to_string(),
+            messages: vec![
+                ChatMessage::user("Find it"),
+                ChatMessage {
+                    role: "assistant".to_string(),
+                    content: "".into(),
+                    name: None,
+                    tool_calls: Some(vec![ToolCall {
+                        id: "call_1".to_string(),
+                        r#type: "function".to_string(),
+                        extra_content: None,
+                        function: crate::ai::provider_types::FunctionCall {
+                            name: "web_search".to_string(),
+                            arguments: r#"{"query":"rust"}"#.to_string(),
+                        },
+                    }]),
+                    tool_call_id: None,
+                },
+                ChatMessage {
+                    role: "tool".to_string(),
+                    content: "results".into(),
+                    name: None,
+                    tool_calls: None,
+                    tool_call_id: Some("call_1".to_string()),
+                },
+            ],
+            tools: Some(vec![Tool {
+                r#type: "function".to_string(),
+                function: crate::ai::provider_types::FunctionDefinition {
+                    name: "web_search".to_string(),
+                    description: "Search".to_string(),
+                    parameters: serde_json::json!({ "type": "object" }),
+                },
+            }]),
+            tool_choice: Some(crate::ai::provider_types::ToolChoice::Auto),
+            ..Default::default()
+        };
+
+        let body = serde_json::to_value(XAIChatRequest::from(request)).unwrap();
+        assert_eq!(body["tools"][0]["function"]["name"], "web_search");
+        assert_eq!(body["tool_choice"], "auto");
+        assert!(body["messages"][1].get("content").is_none());
+        assert_eq!(body["messages"][1]["tool_calls"][0]["id"], "call_1");
+        assert_eq!(body["messages"][2]["tool_call_id"], "call_1");
+
+        let response: XAIChatResponse = serde_json::from_value(serde_json::json!({
+            "model": "grok-4",
+            "choices": [{
+                "message": {
+                    "role": "assistant",
+                    "content": null,
+                    "tool_calls": [{
+                        "id": "call_2",
+                        "type": "function",
+                        "function": { "name": "read_file", "arguments": "{}" }
+                    }]
+                },
+                "finish_reason": "tool_calls"
+            }]
+        }))
+        .unwrap();
+        let completion = response.to_completion_response();
+        assert_eq!(completion.finish_reason, "tool_calls");
+        assert_eq!(completion.tool_calls.unwrap()[0].function.name, "read_file");
     }
 
     #[test]
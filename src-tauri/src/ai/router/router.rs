@@ -4,7 +4,7 @@
 use crate::ai::provider_trait::ProviderWithStats;
 use crate::ai::provider_types::{
     AIError, ChatCompletionRequest, ChatCompletionResponse, EmbeddingRequest, EmbeddingResponse,
-    ProviderId, ProviderResult, StreamingCallback,
+    ProviderId, ProviderResult, ProviderType, StreamingCallback,
 };
 use crate::ai::router::fallback_chain::FallbackStrategy;
 use crate::ai::router::load_balancer::LoadBalancingStrategy;
@@ -59,6 +59,13 @@ pub struct IntelligentRouter {
 
 impl IntelligentRouter {
     fn pinned_provider_error(model: &str) -> Option<AIError> {
+        if crate::ai::model_catalog::is_explicit_local_model(model) {
+            return Some(AIError::ProviderNotFound(format!(
+                "Local provider unavailable for model '{}'. Register 'local_llm' and ensure the local model server URL is configured.",
+                model
+            )));
+        }
+
         if crate::ai::model_catalog::requires_rainy_provider(model) {
             return Some(AIError::ProviderNotFound(format!(
                 "Rainy provider unavailable for model '{}'. Register 'rainy_api' and ensure a Rainy API key is configured.",
@@ -346,6 +353,13 @@ impl IntelligentRouter {
         let all_providers = self.load_balancer.providers();
 
         // Explicit provider prefixes are authoritative and deterministic.
+        if crate::ai::model_catalog::is_explicit_local_model(&model) {
+            return all_providers
+                .iter()
+                .find(|p| p.provider().provider_type() == ProviderType::Local)
+                .cloned();
+        }
+
         if crate::ai::model_catalog::requires_rainy_provider(&model) {
             return all_providers
                 .iter()
@@ -1,6 +1,9 @@
 use super::security::AgentSignature;
 use super::skills::AgentSkills;
 use super::soul::AgentSoul;
+use crate::services::command_policy::CommandPolicy;
+use crate::services::memory::{RetrievalStrategy, SearchOptions};
+use crate::services::memory_vault::RetentionPolicy;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
@@ -45,6 +48,10 @@ pub struct RuntimeConfig {
     pub max_specialists: u8,
     #[serde(default = "default_verification_required")]
     pub verification_required: bool,
+    /// Maximum number of read-only (Airlock Safe) tool calls from one model
+    /// turn that may run concurrently.
+    #[serde(default = "default_max_parallel_tools")]
+    pub max_parallel_tools: u8,
 }
 
 fn default_max_specialists() -> u8 {
@@ -55,12 +62,17 @@ fn default_verification_required() -> bool {
     true
 }
 
+fn default_max_parallel_tools() -> u8 {
+    crate::ai::agent::workflow::DEFAULT_MAX_PARALLEL_TOOLS
+}
+
 impl Default for RuntimeConfig {
     fn default() -> Self {
         Self {
             mode: RuntimeMode::Single,
             max_specialists: default_max_specialists(),
             verification_required: default_verification_required(),
+            max_parallel_tools: default_max_parallel_tools(),
         }
     }
 }
@@ -82,6 +94,15 @@ pub struct AirlockConfig {
 
     #[serde(default)]
     pub rate_limits: AirlockRateLimits,
+
+    /// Once a run has read untrusted tool output (web pages, MCP, WASM skills),
+    /// treat further Sensitive tool calls as Dangerous so they need approval.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub escalate_after_untrusted_content: bool,
+
+    /// Rules for `execute_command`; narrows the workspace's command policy.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub command_policy: Option<CommandPolicy>,
 }
 
 impl Default for AirlockConfig {
@@ -91,6 +112,8 @@ impl Default for AirlockConfig {
             tool_levels: HashMap::new(),
             scopes: AirlockScopes::default(),
             rate_limits: AirlockRateLimits::default(),
+            escalate_after_untrusted_content: false,
+            command_policy: None,
         }
     }
 }
@@ -158,12 +181,28 @@ impl Default for AirlockScopes {
     }
 }
 
+/// Per-agent limits enforced by the runtime. `0` means unlimited.
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct AirlockRateLimits {
+    /// Model requests plus tool calls, over a sliding one-minute window.
     #[serde(default)]
     pub max_requests_per_minute: u32,
+    /// Total model tokens per UTC day, shared by every run of the agent.
     #[serde(default)]
     pub max_tokens_per_day: u64,
+    #[serde(default)]
+    pub on_limit: RateLimitAction,
+}
+
+/// What a run does when it hits `max_requests_per_minute`. An exhausted
+/// daily token budget always fails the run.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum RateLimitAction {
+    /// Back off until the window has room again.
+    #[default]
+    Wait,
+    Fail,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -179,6 +218,9 @@ pub struct MemoryConfig {
     #[serde(default)]
     pub knowledge: KnowledgeConfig,
 
+    #[serde(default, skip_serializing_if = "LifecycleConfig::is_default")]
+    pub lifecycle: LifecycleConfig,
+
     // Backward compat: accept flat fields from old specs on disk
     #[serde(default)]
     pub retention_days: Option<u32>,
@@ -190,6 +232,13 @@ pub struct MemoryConfig {
 pub struct RetrievalConfig {
     pub retention_days: u32,
     pub max_tokens: u32,
+    /// Re-score retrieved memories on query-term coverage before ranking.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub rerank: bool,
+    /// Characters per token when budgeting the context window, for models
+    /// whose tokenizer is denser or sparser than the default of 4.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chars_per_token: Option<u32>,
 }
 
 impl Default for RetrievalConfig {
@@ -197,10 +246,54 @@ impl Default for RetrievalConfig {
         Self {
             retention_days: 30,
             max_tokens: 32000,
+            rerank: false,
+            chars_per_token: None,
         }
     }
 }
 
+/// Background maintenance and ranking of the agent's long-term memory.
+/// Expiry uses `effective_retention_days`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LifecycleConfig {
+    pub maintenance_enabled: bool,
+    /// Entries at or above this importance (0-1) outlive the retention window.
+    pub keep_importance_above: f32,
+    /// Cosine similarity at which near-duplicate memories are merged; 1.0 disables.
+    pub dedup_similarity: f32,
+    pub importance_weight: f32,
+    pub recency_weight: f32,
+    pub recency_half_life_days: u32,
+}
+
+impl Default for LifecycleConfig {
+    fn default() -> Self {
+        let policy = RetentionPolicy::default();
+        Self {
+            maintenance_enabled: policy.maintenance_enabled,
+            keep_importance_above: policy.keep_importance_above,
+            dedup_similarity: policy.dedup_similarity,
+            importance_weight: policy.importance_weight,
+            recency_weight: policy.recency_weight,
+            recency_half_life_days: policy.recency_half_life_days,
+        }
+    }
+}
+
+impl LifecycleConfig {
+    fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+// Fields added to the signed parts of a spec (airlock and memory config) are
+// left out while they hold their default, so specs signed before the field
+// existed keep the same content hash.
+fn is_false(value: &bool) -> bool {
+    !value
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PersistenceConfig {
     pub cross_session: bool,
@@ -254,6 +347,28 @@ impl MemoryConfig {
     pub fn effective_max_tokens(&self) -> u32 {
         self.max_tokens.unwrap_or(self.retrieval.max_tokens)
     }
+
+    /// Lifecycle settings for the memory vault, with weights clamped to [0, 1].
+    pub fn retention_policy(&self) -> RetentionPolicy {
+        let lifecycle = &self.lifecycle;
+        RetentionPolicy {
+            retention_days: self.effective_retention_days(),
+            keep_importance_above: lifecycle.keep_importance_above.clamp(0.0, 1.0),
+            dedup_similarity: lifecycle.dedup_similarity.clamp(0.0, 1.0),
+            importance_weight: lifecycle.importance_weight.clamp(0.0, 1.0),
+            recency_weight: lifecycle.recency_weight.clamp(0.0, 1.0),
+            recency_half_life_days: lifecycle.recency_half_life_days.max(1),
+            maintenance_enabled: lifecycle.maintenance_enabled,
+        }
+    }
+
+    /// How long-term memory is searched for this agent.
+    pub fn search_options(&self) -> SearchOptions {
+        SearchOptions {
+            strategy: RetrievalStrategy::from_spec(&self.strategy),
+            rerank: self.retrieval.rerank,
+        }
+    }
 }
 
 impl Default for MemoryConfig {
@@ -263,6 +378,7 @@ impl Default for MemoryConfig {
             retrieval: RetrievalConfig::default(),
             persistence: PersistenceConfig::default(),
             knowledge: KnowledgeConfig::default(),
+            lifecycle: LifecycleConfig::default(),
             retention_days: None,
             max_tokens: None,
         }
@@ -1,8 +1,16 @@
+use super::manifest::AgentSpec;
+use crate::ai::keychain::KeychainManager;
+use crate::services::manifest_signing::canonicalize;
+use crate::services::skill_installer::verify_ed25519_signature;
+use ed25519_dalek::{Signer, SigningKey};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+const SPEC_SIGNING_KEY_ID: &str = "agent_spec_signing_key_v1";
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AgentSignature {
-    // Ed25519 signature of the hash (soul + skills + memory config)
+    // Ed25519 signature of the hash (soul + skills + airlock + memory config)
     pub signature: String,
     // The public key ID that signed this package
     pub signer_id: String,
@@ -14,6 +22,343 @@ pub struct AgentSignature {
     pub signed_at: i64,
 }
 
+/// What to do with a spec whose signature is missing, invalid or from an
+/// untrusted signer when it is loaded or run.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum SpecSignaturePolicy {
+    /// Refuse to load or run the spec.
+    Require,
+    /// Load it and log a warning.
+    #[default]
+    Warn,
+    /// Skip verification entirely.
+    Ignore,
+}
+
+/// A public key whose spec signatures are accepted.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct TrustedSigner {
+    pub signer_id: String,
+    pub public_key_hex: String,
+    #[serde(default)]
+    pub label: String,
+}
+
+/// Outcome of checking a spec against the signer keyring.
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum SpecTrust {
+    Verified {
+        signer_id: String,
+    },
+    Unsigned,
+    UntrustedSigner {
+        signer_id: String,
+    },
+    Invalid {
+        reason: String,
+    },
+    /// Verification was skipped by policy.
+    Skipped,
+}
+
+impl SpecTrust {
+    pub fn is_verified(&self) -> bool {
+        matches!(self, SpecTrust::Verified { .. })
+    }
+
+    fn describe(&self) -> String {
+        match self {
+            SpecTrust::Verified { signer_id } => format!("signed by trusted signer {}", signer_id),
+            SpecTrust::Unsigned => "is not signed".to_string(),
+            SpecTrust::UntrustedSigner { signer_id } => {
+                format!("is signed by untrusted signer {}", signer_id)
+            }
+            SpecTrust::Invalid { reason } => format!("has an invalid signature: {}", reason),
+            SpecTrust::Skipped => "was not verified".to_string(),
+        }
+    }
+}
+
 impl AgentSignature {
-    // Methods removed to avoid dead code warnings
+    /// Sign the spec's soul, skills, airlock and memory config.
+    pub fn sign(spec: &AgentSpec, signing_key: &SigningKey, origin_device_id: &str) -> Self {
+        let public_key_hex = hex::encode(signing_key.verifying_key().as_bytes());
+        let mut signature = Self {
+            signature: String::new(),
+            signer_id: signer_id_for(&public_key_hex),
+            capabilities_hash: capabilities_hash(spec),
+            origin_device_id: origin_device_id.to_string(),
+            signed_at: chrono::Utc::now().timestamp(),
+        };
+        let payload = signature.signing_payload(spec);
+        signature.signature = hex::encode(signing_key.sign(payload.as_bytes()).to_bytes());
+        signature
+    }
+
+    /// Check this signature against `spec` and the signer's public key.
+    pub fn verify(&self, spec: &AgentSpec, public_key_hex: &str) -> Result<(), String> {
+        if signer_id_for(public_key_hex) != self.signer_id {
+            return Err(format!(
+                "public key does not belong to signer {}",
+                self.signer_id
+            ));
+        }
+        if capabilities_hash(spec) != self.capabilities_hash {
+            return Err("skills changed since the spec was signed".to_string());
+        }
+        let payload = self.signing_payload(spec);
+        if !verify_ed25519_signature(payload.as_bytes(), &self.signature, public_key_hex) {
+            return Err("signature does not match spec contents".to_string());
+        }
+        Ok(())
+    }
+
+    /// Canonical message covered by the signature: the spec's identity and
+    /// content hash plus this signature's own metadata.
+    fn signing_payload(&self, spec: &AgentSpec) -> String {
+        canonicalize(&serde_json::json!({
+            "spec_id": spec.id,
+            "version": spec.version,
+            "content_hash": content_hash(spec),
+            "capabilities_hash": self.capabilities_hash,
+            "signer_id": self.signer_id,
+            "origin_device_id": self.origin_device_id,
+            "signed_at": self.signed_at,
+        }))
+    }
+}
+
+/// SHA-256 over the canonical JSON of soul, skills, airlock and memory config.
+/// Fields added to these after signing shipped must be skipped while they
+/// hold their default, or every existing signature stops verifying.
+pub fn content_hash(spec: &AgentSpec) -> String {
+    sha256_hex(&canonicalize(&serde_json::json!({
+        "soul": spec.soul,
+        "skills": spec.skills,
+        "airlock": spec.airlock,
+        "memory_config": spec.memory_config,
+    })))
+}
+
+/// SHA-256 over the canonical JSON of the spec's skills.
+pub fn capabilities_hash(spec: &AgentSpec) -> String {
+    sha256_hex(&canonicalize(
+        &serde_json::to_value(&spec.skills).unwrap_or_default(),
+    ))
+}
+
+/// Key id for a hex-encoded Ed25519 public key: the first 16 hex chars of
+/// its SHA-256.
+pub fn signer_id_for(public_key_hex: &str) -> String {
+    let bytes = hex::decode(public_key_hex.trim()).unwrap_or_default();
+    hex::encode(Sha256::digest(bytes))[..16].to_string()
+}
+
+/// Check `spec` against the trusted signers. Does not apply the policy.
+pub fn check_spec_signature(spec: &AgentSpec, trusted: &[TrustedSigner]) -> SpecTrust {
+    let Some(signature) = spec.signature.as_ref() else {
+        return SpecTrust::Unsigned;
+    };
+    let Some(signer) = trusted
+        .iter()
+        .find(|signer| signer.signer_id == signature.signer_id)
+    else {
+        return SpecTrust::UntrustedSigner {
+            signer_id: signature.signer_id.clone(),
+        };
+    };
+    match signature.verify(spec, &signer.public_key_hex) {
+        Ok(()) => SpecTrust::Verified {
+            signer_id: signer.signer_id.clone(),
+        },
+        Err(reason) => SpecTrust::Invalid { reason },
+    }
+}
+
+/// Apply `policy` to a spec about to be loaded or run. Errors under
+/// `Require` unless the spec verifies; under `Warn` the problem is logged.
+pub fn enforce_spec_signature(
+    spec: &AgentSpec,
+    policy: SpecSignaturePolicy,
+    trusted: &[TrustedSigner],
+) -> Result<SpecTrust, String> {
+    if policy == SpecSignaturePolicy::Ignore {
+        return Ok(SpecTrust::Skipped);
+    }
+
+    let trust = check_spec_signature(spec, trusted);
+    if trust.is_verified() {
+        return Ok(trust);
+    }
+    let message = format!("Agent spec '{}' {}", spec.id, trust.describe());
+    if policy == SpecSignaturePolicy::Require {
+        return Err(format!(
+            "{}. Signature policy requires a valid signature from a trusted signer.",
+            message
+        ));
+    }
+    tracing::warn!("{}", message);
+    Ok(trust)
+}
+
+/// This device's spec signing key, created and stored in the keychain on
+/// first use.
+pub fn local_signing_key() -> Result<SigningKey, String> {
+    let keychain = KeychainManager::new();
+    if let Some(encoded) = keychain.get_key(SPEC_SIGNING_KEY_ID)? {
+        let bytes: [u8; 32] = hex::decode(encoded.trim())
+            .ok()
+            .and_then(|bytes| bytes.try_into().ok())
+            .ok_or_else(|| "Invalid spec signing key in keychain".to_string())?;
+        return Ok(SigningKey::from_bytes(&bytes));
+    }
+
+    let signing_key = SigningKey::generate(&mut rand::rngs::OsRng);
+    keychain.store_key(SPEC_SIGNING_KEY_ID, &hex::encode(signing_key.to_bytes()))?;
+    Ok(signing_key)
+}
+
+/// The trusted-signer entry for a signing key.
+pub fn trusted_signer_for(signing_key: &SigningKey, label: &str) -> TrustedSigner {
+    let public_key_hex = hex::encode(signing_key.verifying_key().as_bytes());
+    TrustedSigner {
+        signer_id: signer_id_for(&public_key_hex),
+        public_key_hex,
+        label: label.to_string(),
+    }
+}
+
+fn sha256_hex(payload: &str) -> String {
+    hex::encode(Sha256::digest(payload.as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai::specs::skills::AgentSkills;
+    use crate::ai::specs::soul::AgentSoul;
+
+    fn spec() -> AgentSpec {
+        AgentSpec {
+            id: "signed-agent".to_string(),
+            version: "3.0.0".to_string(),
+            soul: AgentSoul {
+                name: "Signed Agent".to_string(),
+                soul_content: "Be careful.".to_string(),
+                ..Default::default()
+            },
+            skills: AgentSkills::default(),
+            airlock: Default::default(),
+            memory_config: Default::default(),
+            connectors: Default::default(),
+            runtime: Default::default(),
+            signature: None,
+        }
+    }
+
+    #[test]
+    fn test_signed_spec_verifies_and_detects_tampering() {
+        let key = SigningKey::generate(&mut rand::rngs::OsRng);
+        let trusted = vec![trusted_signer_for(&key, "test")];
+
+        let mut spec = spec();
+        spec.signature = Some(AgentSignature::sign(&spec, &key, "device-1"));
+        assert_eq!(
+            check_spec_signature(&spec, &trusted),
+            SpecTrust::Verified {
+                signer_id: trusted[0].signer_id.clone()
+            }
+        );
+
+        // Round-trips through JSON like a stored or downloaded spec.
+        let reloaded: AgentSpec =
+            serde_json::from_str(&serde_json::to_string(&spec).unwrap()).unwrap();
+        assert!(check_spec_signature(&reloaded, &trusted).is_verified());
+
+        let mut escalated = spec.clone();
+        escalated.airlock.tool_policy.deny.clear();
+        escalated.airlock.scopes.allowed_paths.push("/".to_string());
+        assert!(matches!(
+            check_spec_signature(&escalated, &trusted),
+            SpecTrust::Invalid { .. }
+        ));
+
+        let other = SigningKey::generate(&mut rand::rngs::OsRng);
+        let untrusted = vec![trusted_signer_for(&other, "other")];
+        assert!(matches!(
+            check_spec_signature(&spec, &untrusted),
+            SpecTrust::UntrustedSigner { .. }
+        ));
+    }
+
+    #[test]
+    fn test_fields_added_after_signing_keep_old_signatures_valid() {
+        let key = SigningKey::generate(&mut rand::rngs::OsRng);
+        let trusted = vec![trusted_signer_for(&key, "test")];
+        let mut spec = spec();
+
+        // Sign the way a build without these fields did: its content hash
+        // never saw them.
+        let mut airlock = serde_json::to_value(&spec.airlock).unwrap();
+        airlock
+            .as_object_mut()
+            .unwrap()
+            .remove("escalate_after_untrusted_content");
+        let mut memory_config = serde_json::to_value(&spec.memory_config).unwrap();
+        let memory = memory_config.as_object_mut().unwrap();
+        memory.remove("lifecycle");
+        memory["retrieval"]
+            .as_object_mut()
+            .unwrap()
+            .remove("rerank");
+        let old_content_hash = sha256_hex(&canonicalize(&serde_json::json!({
+            "soul": spec.soul,
+            "skills": spec.skills,
+            "airlock": airlock,
+            "memory_config": memory_config,
+        })));
+        let mut signature = AgentSignature {
+            signature: String::new(),
+            signer_id: trusted[0].signer_id.clone(),
+            capabilities_hash: capabilities_hash(&spec),
+            origin_device_id: "device-1".to_string(),
+            signed_at: 1_700_000_000,
+        };
+        let payload = canonicalize(&serde_json::json!({
+            "spec_id": spec.id,
+            "version": spec.version,
+            "content_hash": old_content_hash,
+            "capabilities_hash": signature.capabilities_hash,
+            "signer_id": signature.signer_id,
+            "origin_device_id": signature.origin_device_id,
+            "signed_at": signature.signed_at,
+        }));
+        signature.signature = hex::encode(key.sign(payload.as_bytes()).to_bytes());
+        spec.signature = Some(signature);
+        assert!(check_spec_signature(&spec, &trusted).is_verified());
+
+        // Turning one of them on is a change the signature has to cover.
+        spec.memory_config.retrieval.rerank = true;
+        assert!(matches!(
+            check_spec_signature(&spec, &trusted),
+            SpecTrust::Invalid { .. }
+        ));
+    }
+
+    #[test]
+    fn test_policy_enforcement() {
+        let unsigned = spec();
+        assert!(enforce_spec_signature(&unsigned, SpecSignaturePolicy::Require, &[]).is_err());
+        assert_eq!(
+            enforce_spec_signature(&unsigned, SpecSignaturePolicy::Warn, &[]),
+            Ok(SpecTrust::Unsigned)
+        );
+        assert_eq!(
+            enforce_spec_signature(&unsigned, SpecSignaturePolicy::Ignore, &[]),
+            Ok(SpecTrust::Skipped)
+        );
+    }
 }
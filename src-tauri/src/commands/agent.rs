@@ -1,3 +1,4 @@
+use crate::ai::agent::rate_limiter::AgentRateLimiter;
 use crate::ai::agent::runtime::{AgentContent, AgentMessage, AgentRuntime, RuntimeOptions};
 use crate::ai::agent::runtime_registry::RuntimeRegistry;
 use crate::ai::specs::AgentSpec;
@@ -8,7 +9,10 @@ use crate::ai::{
     keychain::KeychainManager,
     provider_trait::{AIProviderFactory, ProviderWithStats},
     provider_types::{ChatCompletionRequest, ChatMessage, ProviderConfig, ProviderId, ProviderType},
-    providers::{GeminiProviderFactory, RainySDKProviderFactory},
+    providers::{
+        local::LOCAL_PROVIDER_ID, GeminiProviderFactory, LocalProviderFactory,
+        RainySDKProviderFactory,
+    },
 };
 use crate::commands::ai_providers::ProviderRegistryState;
 use crate::commands::airlock::AirlockServiceState;
@@ -173,24 +177,94 @@ fn truncate_text(input: &str, max_chars: usize) -> String {
     format!("{}\n\n[TRUNCATED]", out)
 }
 
-fn build_runtime_history(rows: Vec<(String, String, String)>) -> Vec<AgentMessage> {
-    rows
+fn build_runtime_history(rows: Vec<(String, AgentMessage)>) -> Vec<AgentMessage> {
+    let messages = rows
         .into_iter()
-        .filter_map(|(_, role, content)| {
-            if role != "user" && role != "assistant" && role != "system" {
+        .filter_map(|(_, mut message)| {
+            if !matches!(
+                message.role.as_str(),
+                "user" | "assistant" | "system" | "tool"
+            ) {
                 return None;
             }
-            Some(AgentMessage {
-                role,
-                content: AgentContent::text(truncate_text(&content, MAX_HISTORY_MESSAGE_CHARS)),
-                tool_calls: None,
-                tool_call_id: None,
-            })
+            if let AgentContent::Text(text) = &message.content {
+                message.content =
+                    AgentContent::text(truncate_text(text, MAX_HISTORY_MESSAGE_CHARS));
+            }
+            Some(message)
+        })
+        .collect();
+    repair_tool_call_pairs(messages)
+}
+
+/// Providers reject tool results without a preceding call and calls without
+/// results. Compaction can cut a turn in half, so drop the unmatched side.
+fn repair_tool_call_pairs(messages: Vec<AgentMessage>) -> Vec<AgentMessage> {
+    let mut answered = std::collections::HashSet::new();
+    let mut requested = std::collections::HashSet::new();
+    for message in &messages {
+        if let Some(calls) = &message.tool_calls {
+            requested.extend(calls.iter().map(|call| call.id.clone()));
+        }
+        if let Some(id) = &message.tool_call_id {
+            if requested.contains(id) {
+                answered.insert(id.clone());
+            }
+        }
+    }
+
+    messages
+        .into_iter()
+        .filter_map(|mut message| {
+            if message.role == "tool" {
+                return message
+                    .tool_call_id
+                    .as_ref()
+                    .is_some_and(|id| answered.contains(id))
+                    .then_some(message);
+            }
+            if let Some(calls) = message.tool_calls.take() {
+                let calls: Vec<_> = calls
+                    .into_iter()
+                    .filter(|call| answered.contains(&call.id))
+                    .collect();
+                if calls.is_empty() && message.content.as_text().trim().is_empty() {
+                    return None;
+                }
+                message.tool_calls = (!calls.is_empty()).then_some(calls);
+            }
+            Some(message)
         })
         .collect()
 }
 
-fn estimate_history_tokens(rows: &[(String, String, String)], prompt: &str) -> usize {
+/// Messages the runtime appended during this turn, with the returned
+/// response standing in for an empty or missing final answer.
+fn finalize_turn_transcript(
+    mut transcript: Vec<AgentMessage>,
+    response: &str,
+) -> Vec<AgentMessage> {
+    match transcript.last_mut() {
+        Some(last) if last.role == "assistant" && last.tool_calls.is_none() => {
+            if last.content.as_text().trim().is_empty() {
+                last.content = AgentContent::text(response);
+            }
+        }
+        _ => transcript.push(AgentMessage {
+            role: "assistant".to_string(),
+            content: AgentContent::text(response),
+            tool_calls: None,
+            tool_call_id: None,
+        }),
+    }
+    transcript
+}
+
+fn estimate_history_tokens(
+    rows: &[(String, AgentMessage)],
+    prompt: &str,
+    context_window: &ContextWindow,
+) -> usize {
     let mut messages = build_runtime_history(rows.to_vec());
     messages.push(AgentMessage {
         role: "user".to_string(),
@@ -198,11 +272,11 @@ fn estimate_history_tokens(rows: &[(String, String, String)], prompt: &str) -> u
         tool_calls: None,
         tool_call_id: None,
     });
-    ContextWindow::estimate_total_tokens(&messages)
+    context_window.count_total_tokens(&messages)
 }
 
 fn build_compaction_transcript(
-    rows: &[(String, String, String)],
+    rows: &[(String, AgentMessage)],
     keep_recent_count: usize,
 ) -> Option<(String, usize)> {
     if rows.len() <= keep_recent_count + 2 {
@@ -216,16 +290,32 @@ fn build_compaction_transcript(
     let to_summarize = &rows[..split_index];
     let mut transcript = String::new();
 
-    for (_, role, content) in to_summarize {
-        if role == "system" && content.starts_with("SESSION COMPACTION SUMMARY:") {
+    for (_, message) in to_summarize {
+        let content = message.content.as_text();
+        if message.role == "system" && content.starts_with("SESSION COMPACTION SUMMARY:") {
             continue;
         }
-        let role_label = match role.as_str() {
+        let role_label = match message.role.as_str() {
             "user" => "USER",
             "assistant" => "ASSISTANT",
+            "tool" => "TOOL RESULT",
             _ => "SYSTEM",
         };
-        let line = format!("{}: {}\n", role_label, truncate_text(content, 1200));
+        let mut line = String::new();
+        if !content.trim().is_empty() {
+            line.push_str(&format!(
+                "{}: {}\n",
+                role_label,
+                truncate_text(&content, 1200)
+            ));
+        }
+        for call in message.tool_calls.iter().flatten() {
+            line.push_str(&format!(
+                "TOOL CALL: {}({})\n",
+                call.function.name,
+                truncate_text(&call.function.arguments, 400)
+            ));
+        }
         if transcript.len() + line.len() > MAX_COMPACTION_TRANSCRIPT_CHARS {
             break;
         }
@@ -318,13 +408,14 @@ async fn maybe_compact_chat_history(
     chat_id: &str,
     model_id: &str,
     prompt: &str,
+    context_window: &ContextWindow,
 ) -> Result<Option<ChatCompactionStateDto>, String> {
     let history_rows = agent_manager
-        .get_history(chat_id)
+        .get_agent_history(chat_id)
         .await
         .map_err(|e| format!("Failed to load chat history for compaction: {}", e))?;
 
-    let estimated_tokens = estimate_history_tokens(&history_rows, prompt);
+    let estimated_tokens = estimate_history_tokens(&history_rows, prompt, context_window);
     if estimated_tokens < AUTO_COMPACTION_TRIGGER_TOKENS {
         return Ok(None);
     }
@@ -363,6 +454,7 @@ fn default_instructions(workspace_id: &str) -> String {
         
         CAPABILITIES:
         - You can read, write, list, and search files in the workspace.
+        - To change part of an existing file, prefer `edit_file` (search/replace) or `apply_patch` (unified diff) over rewriting it with `write_file`.
         - **MULTIMODAL: You can SEE images.** If you use `read_file` on an image, you will receive its visual content.
         - You can plan multi-step tasks.
         - You may use shell tools only when available through the provided tools.
@@ -389,6 +481,10 @@ async fn ensure_provider_ready_for_model(
     registry: &ProviderRegistryState,
     router: &IntelligentRouterState,
 ) -> Result<(), String> {
+    if crate::ai::model_catalog::is_explicit_local_model(model_id) {
+        return ensure_local_provider_ready(model_id, registry, router).await;
+    }
+
     let keychain = KeychainManager::new();
     let normalized_model = crate::ai::model_catalog::normalize_model_slug(model_id).to_string();
 
@@ -478,6 +574,62 @@ async fn ensure_provider_ready_for_model(
     Ok(())
 }
 
+/// Register the local OpenAI-compatible provider (Ollama, llama.cpp, LM Studio)
+/// against the base URL from user settings. No API key is required.
+async fn ensure_local_provider_ready(
+    model_id: &str,
+    registry: &ProviderRegistryState,
+    router: &IntelligentRouterState,
+) -> Result<(), String> {
+    let provider_id = ProviderId::new(LOCAL_PROVIDER_ID);
+
+    if registry.0.get(&provider_id).is_err() {
+        let base_url = crate::services::settings::SettingsManager::new()
+            .get_local_llm_base_url()
+            .to_string();
+
+        let config = ProviderConfig {
+            id: provider_id.clone(),
+            provider_type: ProviderType::Local,
+            api_key: None,
+            base_url: Some(base_url),
+            model: model_id.to_string(),
+            params: std::collections::HashMap::new(),
+            enabled: true,
+            priority: 30,
+            rate_limit: None,
+            timeout: 300,
+        };
+
+        let provider = <LocalProviderFactory as AIProviderFactory>::create(config)
+            .await
+            .map_err(|e| format!("Failed to initialize local provider: {}", e))?;
+
+        registry
+            .0
+            .register(provider)
+            .map_err(|e| format!("Failed to register provider '{}': {}", provider_id, e))?;
+    }
+
+    let mut router_guard = router.0.write().await;
+    let already_present = router_guard
+        .get_all_providers()
+        .iter()
+        .any(|p| p.provider().id() == &provider_id);
+
+    if !already_present {
+        let provider = registry.0.get(&provider_id).map_err(|e| {
+            format!(
+                "Provider '{}' not available after registration: {}",
+                provider_id, e
+            )
+        })?;
+        router_guard.add_provider(Arc::new(ProviderWithStats::new(provider.provider.clone())));
+    }
+
+    Ok(())
+}
+
 #[tauri::command]
 pub async fn run_agent_workflow(
     app_handle: tauri::AppHandle,
@@ -544,6 +696,8 @@ pub async fn run_agent_workflow(
     // 1. Initialize Runtime (Ephemeral for now, persistent later)
     // 1. Initialize Runtime (Ephemeral for now, persistent later)
     let spec = if let Some(spec_id) = agent_spec_id {
+        // Stored specs must satisfy the signature policy before they run.
+        let settings_manager = crate::services::settings::SettingsManager::new();
         // Try DB first, then fall back to file-based spec storage
         let db_spec = match agent_manager.get_agent_spec(&spec_id).await {
             Ok(Some(s)) => Some(s),
@@ -559,7 +713,10 @@ pub async fn run_agent_workflow(
 
         // Fallback: try loading from agent_specs/ JSON files (canonical source from AgentBuilder)
         let spec = match db_spec {
-            Some(s) => s,
+            Some(s) => {
+                settings_manager.verify_agent_spec(&s)?;
+                s
+            }
             None => {
                 let app_data_dir = app_handle
                     .path()
@@ -572,8 +729,10 @@ pub async fn run_agent_workflow(
                 if spec_path.exists() {
                     let body = std::fs::read_to_string(&spec_path)
                         .map_err(|e| format!("Failed to read spec file: {}", e))?;
-                    serde_json::from_str(&body)
-                        .map_err(|e| format!("Invalid agent spec JSON: {}", e))?
+                    let file_spec: AgentSpec = serde_json::from_str(&body)
+                        .map_err(|e| format!("Invalid agent spec JSON: {}", e))?;
+                    settings_manager.verify_agent_spec(&file_spec)?;
+                    file_spec
                 } else {
                     eprintln!(
                         "[AgentWorkflow] Spec {} not found in DB or files, falling back to default",
@@ -651,6 +810,7 @@ pub async fn run_agent_workflow(
         reasoning_effort: crate::ai::agent::prompt_guard::validate_reasoning_effort(
             reasoning_effort.as_deref()
         ),
+        run_id: Some(run_id.clone()),
     };
 
     // Initialize Persistent Memory
@@ -683,6 +843,11 @@ pub async fn run_agent_workflow(
         airlock_service,
         Some(run_kill_switch),
         Some(runtime_registry.inner().clone()),
+    )
+    .with_rate_limiter(
+        app_handle
+            .try_state::<Arc<AgentRateLimiter>>()
+            .map(|limiter| limiter.inner().clone()),
     );
 
     // Load persisted conversation history into runtime so local Native Runtime
@@ -693,6 +858,7 @@ pub async fn run_agent_workflow(
         &chat_id,
         &model_id,
         &prompt,
+        &ContextWindow::for_spec(&runtime.spec),
     )
     .await?;
 
@@ -720,20 +886,21 @@ pub async fn run_agent_workflow(
     }
 
     let history_rows = agent_manager
-        .get_history(&chat_id)
+        .get_agent_history(&chat_id)
         .await
         .map_err(|e| format!("Failed to load chat history: {}", e))?;
-    runtime
-        .set_history(build_runtime_history(history_rows))
-        .await;
+    let history = build_runtime_history(history_rows);
+    let prior_history_len = history.len();
+    runtime.set_history(history).await;
 
     // 2. Run Workflow with Persistence
+    let default_embedding_profile = memory_manager.0.embedding_profile().await.label();
     let _ = agent_manager
         .upsert_chat_runtime_telemetry(
             &chat_id,
             "persisted_long_chat",
             "unavailable",
-            crate::services::memory_vault::types::EMBEDDING_MODEL,
+            &default_embedding_profile,
         )
         .await;
 
@@ -777,7 +944,7 @@ pub async fn run_agent_workflow(
                         let embedding_profile = value
                             .get("embedding_profile")
                             .and_then(|v| v.as_str())
-                            .unwrap_or(crate::services::memory_vault::types::EMBEDDING_MODEL)
+                            .unwrap_or(default_embedding_profile.as_str())
                             .to_string();
                         let manager = agent_manager_clone.clone();
                         let chat_id = chat_id_for_events.clone();
@@ -798,13 +965,24 @@ pub async fn run_agent_workflow(
         .await;
 
     run_control.unregister_run(&run_id).await;
+    skills.finish_run_checkpoint(&run_id).await;
+    skills.stop_run_processes(&run_id).await;
     let response = response_result?;
 
-    // Persist final assistant response only (avoid noisy intermediate event spam).
-    let _ = agent_manager
-        .save_message(&chat_id, "assistant", &response)
+    // Persist the turn's full transcript (tool calls, tool results and the final
+    // answer), skipping the delimiter-wrapped copy of the prompt saved above.
+    let new_messages: Vec<AgentMessage> = runtime
+        .history()
         .await
-        .map_err(|e| format!("Failed to save assistant message: {}", e))?;
+        .into_iter()
+        .skip(prior_history_len + 1)
+        .collect();
+    for message in finalize_turn_transcript(new_messages, &response) {
+        agent_manager
+            .save_agent_message(&chat_id, &message)
+            .await
+            .map_err(|e| format!("Failed to save assistant message: {}", e))?;
+    }
 
     Ok(RunAgentWorkflowResponse { run_id, response })
 }
@@ -965,7 +1143,33 @@ pub async fn ensure_chat_title(
 
 #[cfg(test)]
 mod tests {
-    use super::{build_fallback_chat_title, is_placeholder_chat_title, sanitize_chat_title};
+    use super::{
+        build_fallback_chat_title, build_runtime_history, finalize_turn_transcript,
+        is_placeholder_chat_title, sanitize_chat_title,
+    };
+    use crate::ai::agent::runtime::{AgentContent, AgentMessage};
+    use crate::ai::provider_types::{FunctionCall, ToolCall};
+
+    fn message(role: &str, content: &str) -> AgentMessage {
+        AgentMessage {
+            role: role.to_string(),
+            content: AgentContent::text(content),
+            tool_calls: None,
+            tool_call_id: None,
+        }
+    }
+
+    fn tool_call(id: &str) -> ToolCall {
+        ToolCall {
+            id: id.to_string(),
+            r#type: "function".to_string(),
+            extra_content: None,
+            function: FunctionCall {
+                name: "read_file".to_string(),
+                arguments: "{}".to_string(),
+            },
+        }
+    }
 
     #[test]
     fn placeholder_titles_are_detected() {
@@ -985,4 +1189,61 @@ mod tests {
         let title = sanitize_chat_title("\"\"", "Create the new sidebar system");
         assert_eq!(title, "Create the new sidebar system");
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn runtime_history_keeps_tool_trace_and_drops_unmatched_halves() {
+        let orphan_result = AgentMessage {
+            tool_call_id: Some("call_cut".to_string()),
+            ..message("tool", "result of a compacted call")
+        };
+        let answered_call = AgentMessage {
+            tool_calls: Some(vec![tool_call("call_1"), tool_call("call_lost")]),
+            ..message("assistant", "")
+        };
+        let result = AgentMessage {
+            tool_call_id: Some("call_1".to_string()),
+            ..message("tool", "file contents")
+        };
+        let unanswered_call = AgentMessage {
+            tool_calls: Some(vec![tool_call("call_2")]),
+            ..message("assistant", "")
+        };
+        let rows = vec![
+            orphan_result,
+            message("user", "read it"),
+            answered_call,
+            result,
+            unanswered_call,
+            message("assistant", "done"),
+        ]
+        .into_iter()
+        .enumerate()
+        .map(|(i, m)| (i.to_string(), m))
+        .collect();
+
+        let history = build_runtime_history(rows);
+        let roles: Vec<&str> = history.iter().map(|m| m.role.as_str()).collect();
+        assert_eq!(roles, vec!["user", "assistant", "tool", "assistant"]);
+        let calls = history[1].tool_calls.as_ref().unwrap();
+        assert_eq!(calls.len(), 1);
+        assert_eq!(calls[0].id, "call_1");
+        assert_eq!(history[2].tool_call_id.as_deref(), Some("call_1"));
+    }
+
+    #[test]
+    fn turn_transcript_always_ends_with_the_response() {
+        let transcript = finalize_turn_transcript(vec![message("assistant", "")], "fallback");
+        assert_eq!(transcript.len(), 1);
+        assert_eq!(transcript[0].content.as_text(), "fallback");
+
+        let transcript = finalize_turn_transcript(
+            vec![AgentMessage {
+                tool_calls: Some(vec![tool_call("call_1")]),
+                ..message("assistant", "")
+            }],
+            "stopped",
+        );
+        assert_eq!(transcript.len(), 2);
+        assert_eq!(transcript[1].content.as_text(), "stopped");
+    }
+}
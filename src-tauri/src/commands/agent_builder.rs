@@ -1,7 +1,14 @@
+use crate::ai::specs::security::{
+    check_spec_signature, local_signing_key, trusted_signer_for, AgentSignature, SpecTrust,
+};
 use crate::ai::specs::AgentSpec;
+use crate::services::knowledge_base::KnowledgeBase;
+use crate::services::settings::SettingsManager;
 use crate::services::ATMClient;
 use std::path::PathBuf;
+use std::sync::Arc;
 use tauri::{AppHandle, Manager, State};
+use tokio::sync::Mutex;
 
 fn specs_dir(app_handle: &AppHandle) -> Result<PathBuf, String> {
     let app_dir = app_handle
@@ -12,7 +19,7 @@ fn specs_dir(app_handle: &AppHandle) -> Result<PathBuf, String> {
 }
 
 #[tauri::command]
-pub async fn save_agent_spec(app_handle: AppHandle, spec: AgentSpec) -> Result<String, String> {
+pub async fn save_agent_spec(app_handle: AppHandle, mut spec: AgentSpec) -> Result<String, String> {
     if spec.id.trim().is_empty() {
         return Err("Agent spec id is required".to_string());
     }
@@ -21,6 +28,17 @@ pub async fn save_agent_spec(app_handle: AppHandle, spec: AgentSpec) -> Result<S
         return Err("Agent name is required".to_string());
     }
 
+    // Bring the agent's knowledge partition in line with the file list and
+    // save the refreshed entries. Signed specs keep theirs, since the
+    // signature covers them.
+    if let Some(knowledge_base) = app_handle.try_state::<Arc<KnowledgeBase>>() {
+        let knowledge = &mut spec.memory_config.knowledge;
+        let files = knowledge_base.sync(&spec.id, knowledge).await;
+        if spec.signature.is_none() {
+            knowledge.indexed_files = files;
+        }
+    }
+
     let dir = specs_dir(&app_handle)?;
     std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create spec dir: {}", e))?;
 
@@ -81,3 +99,37 @@ pub async fn deploy_agent_spec(
     save_agent_spec(app_handle, spec.clone()).await?;
     client.deploy_agent(spec).await
 }
+
+/// Sign a spec with this device's key. The key is added to the trusted
+/// signers so locally signed specs pass a `require` policy.
+#[tauri::command]
+pub async fn sign_agent_spec(
+    settings: State<'_, Arc<Mutex<SettingsManager>>>,
+    mut spec: AgentSpec,
+) -> Result<AgentSpec, String> {
+    let signing_key = local_signing_key()?;
+    let origin_device_id = std::env::var("HOSTNAME")
+        .or_else(|_| std::env::var("COMPUTERNAME"))
+        .unwrap_or_else(|_| "unknown-host".to_string());
+
+    spec.signature = Some(AgentSignature::sign(&spec, &signing_key, &origin_device_id));
+    settings
+        .lock()
+        .await
+        .upsert_trusted_spec_signer(trusted_signer_for(&signing_key, "This device"))?;
+    Ok(spec)
+}
+
+/// Check a spec's signature against the trusted signers without applying
+/// the load policy.
+#[tauri::command]
+pub async fn verify_agent_spec_signature(
+    settings: State<'_, Arc<Mutex<SettingsManager>>>,
+    spec: AgentSpec,
+) -> Result<SpecTrust, String> {
+    let settings = settings.lock().await;
+    Ok(check_spec_signature(
+        &spec,
+        settings.get_trusted_spec_signers(),
+    ))
+}
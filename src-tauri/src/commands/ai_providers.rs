@@ -3,7 +3,7 @@
 
 use crate::ai::provider_trait::AIProviderFactory;
 use crate::ai::providers::{
-    AnthropicProviderFactory, GeminiProviderFactory, OpenAIProviderFactory,
+    AnthropicProviderFactory, GeminiProviderFactory, LocalProviderFactory, OpenAIProviderFactory,
     RainySDKProviderFactory, XAIProviderFactory,
 };
 use crate::ai::{
@@ -232,6 +232,13 @@ pub async fn register_provider(
                 .await
                 .map_err(|e| format!("Failed to create provider: {}", e))?
         }
+        ProviderType::Local => {
+            <LocalProviderFactory as AIProviderFactory>::validate_config(&config)
+                .map_err(|e| format!("Invalid config: {}", e))?;
+            <LocalProviderFactory as AIProviderFactory>::create(config)
+                .await
+                .map_err(|e| format!("Failed to create provider: {}", e))?
+        }
 
         _ => {
             return Err(format!(
@@ -105,6 +105,7 @@ pub async fn import_atm_marketplace_agent(
     marketplace_id: String,
 ) -> Result<crate::services::atm_client::MarketplaceAgentSpecResponse, String> {
     let imported = client.get_marketplace_agent_spec(marketplace_id).await?;
+    crate::services::settings::SettingsManager::new().verify_agent_spec(&imported.spec)?;
     crate::commands::agent_builder::save_agent_spec(app_handle, imported.spec.clone()).await?;
     Ok(imported)
 }
@@ -3,8 +3,9 @@
 // Part of Phase 2: Enhanced Tauri Commands
 
 use crate::services::file_operations::{
-    ConflictStrategy, FileOpChange, FileOperationEngine, FileVersion, FileVersionInfo,
-    MoveOperation, OrganizeResult, OrganizeStrategy, RenamePattern, RenamePreview, Transaction,
+    ConflictStrategy, FileOpChange, FileOperationEngine, FileOperationEntry, FileOperationQuery,
+    FileVersion, FileVersionInfo, HistoryGcReport, MoveOperation, OrganizeResult, OrganizeStrategy,
+    RenamePattern, RenamePreview, RetentionPolicy, RunRevertPreview, Transaction,
     WorkspaceAnalysis,
 };
 use std::sync::Arc;
@@ -181,14 +182,15 @@ pub async fn restore_file_version(
 
 // ============ Transaction Commands ============
 
-/// Start a new transaction
+/// Start a new transaction, optionally on behalf of a task
 #[tauri::command]
 pub async fn begin_file_transaction(
     description: String,
+    task_id: Option<String>,
     state: State<'_, Arc<FileOperationEngine>>,
 ) -> Result<String, String> {
     state
-        .begin_transaction(&description)
+        .begin_transaction(&description, task_id)
         .await
         .map_err(|e| e.to_string())
 }
@@ -226,6 +228,29 @@ pub async fn get_file_transaction(
     Ok(state.get_transaction(&transaction_id))
 }
 
+// ============ Agent Run Checkpoint Commands ============
+
+/// Diff of everything an agent run changed, against its pre-run checkpoint
+#[tauri::command]
+pub async fn preview_agent_run_revert(
+    run_id: String,
+    state: State<'_, Arc<FileOperationEngine>>,
+) -> Result<RunRevertPreview, String> {
+    state
+        .preview_run_revert(&run_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Restore the workspace to its state before an agent run
+#[tauri::command]
+pub async fn revert_agent_run(
+    run_id: String,
+    state: State<'_, Arc<FileOperationEngine>>,
+) -> Result<Vec<FileOpChange>, String> {
+    state.revert_run(&run_id).await.map_err(|e| e.to_string())
+}
+
 // ============ Enhanced Undo/Redo Commands ============
 
 /// Enhanced undo operation
@@ -260,6 +285,36 @@ pub async fn list_enhanced_file_operations(
         .collect())
 }
 
+// ============ History Commands ============
+
+/// Browse persisted file operation history by workspace, time range, task or run
+#[tauri::command]
+pub async fn query_file_operations(
+    query: FileOperationQuery,
+    state: State<'_, Arc<FileOperationEngine>>,
+) -> Result<Vec<FileOperationEntry>, String> {
+    state
+        .query_operations(&query)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Prune old history and delete orphaned version files and trash entries
+#[tauri::command]
+pub async fn collect_file_history_garbage(
+    max_age_days: Option<u32>,
+    state: State<'_, Arc<FileOperationEngine>>,
+) -> Result<HistoryGcReport, String> {
+    let mut policy = RetentionPolicy::default();
+    if let Some(max_age_days) = max_age_days {
+        policy.max_age_days = max_age_days;
+    }
+    state
+        .collect_garbage(&policy)
+        .await
+        .map_err(|e| e.to_string())
+}
+
 /// Set workspace context for file operations
 #[tauri::command]
 pub async fn set_file_ops_workspace(
@@ -3,10 +3,15 @@
 //! This module provides Tauri commands that expose the memory system to the frontend.
 //! All commands are thread-safe and use the MemoryManager for operations.
 
+use crate::ai::specs::manifest::KnowledgeConfig;
+use crate::services::knowledge_base::KnowledgeBase;
 use crate::services::memory::MemoryEntry;
 use crate::services::memory::MemoryManager;
+use crate::services::memory::{RetrievalStrategy, SearchOptions};
+use crate::services::memory_vault::MaintenanceReport;
 use serde::{Deserialize, Serialize};
-use std::path::{Path, PathBuf};
+use std::path::PathBuf;
+use std::sync::Arc;
 use tauri::State;
 
 /// State wrapper for MemoryManager
@@ -49,30 +54,6 @@ pub enum MemoryStrategy {
     Hybrid,
 }
 
-fn split_text_into_chunks(content: &str, chunk_size: usize, overlap: usize) -> Vec<String> {
-    if content.trim().is_empty() {
-        return Vec::new();
-    }
-
-    let chars: Vec<char> = content.chars().collect();
-    let mut chunks = Vec::new();
-    let mut start = 0usize;
-
-    while start < chars.len() {
-        let end = (start + chunk_size).min(chars.len());
-        let chunk: String = chars[start..end].iter().collect();
-        if !chunk.trim().is_empty() {
-            chunks.push(chunk);
-        }
-        if end == chars.len() {
-            break;
-        }
-        start = end.saturating_sub(overlap);
-    }
-
-    chunks
-}
-
 fn compute_text_score(query: &str, content: &str) -> f32 {
     let query_tokens: Vec<String> = query
         .to_lowercase()
@@ -94,13 +75,6 @@ fn compute_text_score(query: &str, content: &str) -> f32 {
     hits as f32 / query_tokens.len() as f32
 }
 
-fn file_name_from_path(path: &Path) -> String {
-    path.file_name()
-        .and_then(|name| name.to_str())
-        .unwrap_or("knowledge-file")
-        .to_string()
-}
-
 /// Store an entry in memory
 ///
 /// Stores the entry in both short-term and long-term memory.
@@ -137,6 +111,7 @@ pub async fn store_memory(
         embedding: None,
         timestamp: chrono::Utc::now(),
         tags,
+        importance: 0.5,
     };
 
     manager.0.store(entry).await.map_err(|e| e.to_string())?;
@@ -353,6 +328,59 @@ pub async fn delete_memory(
     manager.0.delete(&id).await.map_err(|e| e.to_string())
 }
 
+/// Pin or unpin a memory entry
+///
+/// Pinned entries score full importance, rank higher in search and are never
+/// expired or merged away by maintenance.
+///
+/// # Example
+///
+/// ```typescript
+/// import { invoke } from '@tauri-apps/api/core';
+///
+/// await invoke('pin_memory', { id: 'entry-id', pinned: true });
+/// ```
+#[tauri::command]
+pub async fn pin_memory(
+    manager: State<'_, MemoryManagerState>,
+    id: String,
+    pinned: bool,
+) -> Result<(), String> {
+    let found = manager
+        .0
+        .set_pinned(&id, pinned)
+        .await
+        .map_err(|e| e.to_string())?;
+    if !found {
+        return Err(format!("Memory entry not found: {}", id));
+    }
+    Ok(())
+}
+
+/// Run memory maintenance for a workspace now
+///
+/// Rescores importance, expires entries past retention and merges
+/// near-duplicates using the workspace's agent retention policy.
+///
+/// # Example
+///
+/// ```typescript
+/// import { invoke } from '@tauri-apps/api/core';
+///
+/// const report = await invoke('run_memory_maintenance', { workspaceId: 'ws' });
+/// ```
+#[tauri::command]
+pub async fn run_memory_maintenance(
+    manager: State<'_, MemoryManagerState>,
+    workspace_id: String,
+) -> Result<MaintenanceReport, String> {
+    manager
+        .0
+        .run_maintenance(&workspace_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
 /// Get short-term memory size
 ///
 /// Returns the current number of entries in short-term memory.
@@ -405,10 +433,24 @@ pub async fn is_short_term_memory_empty(
     Ok(manager.0.is_short_term_empty().await)
 }
 
+/// Index a knowledge file into the agent's knowledge partition
+///
+/// Re-indexing an unchanged file (same content hash) is a no-op that returns
+/// the existing entry.
+///
+/// # Example
+///
+/// ```typescript
+/// import { invoke } from '@tauri-apps/api/core';
+///
+/// const result = await invoke('index_knowledge_file', {
+///   agentId: 'agent-id',
+///   filePath: '/docs/handbook.pdf'
+/// });
+/// ```
 #[tauri::command]
 pub async fn index_knowledge_file(
-    _app_handle: tauri::AppHandle,
-    manager: State<'_, MemoryManagerState>,
+    knowledge_base: State<'_, Arc<KnowledgeBase>>,
     agent_id: String,
     file_path: String,
 ) -> Result<KnowledgeIndexResult, String> {
@@ -421,53 +463,52 @@ pub async fn index_knowledge_file(
         return Err(format!("File does not exist: {}", file_path));
     }
 
-    let bytes = std::fs::read(&path).map_err(|e| format!("Failed to read file: {}", e))?;
-    let text = String::from_utf8_lossy(&bytes).to_string();
-    let chunks = split_text_into_chunks(&text, 1200, 150);
-    if chunks.is_empty() {
-        return Err("No indexable text content found in file".to_string());
-    }
-
-    let indexed_at = chrono::Utc::now().timestamp();
-
+    let outcome = knowledge_base.index_file(&agent_id, &file_path).await?;
     let file = KnowledgeFile {
-        id: uuid::Uuid::new_v4().to_string(),
-        name: file_name_from_path(&path),
-        path: file_path.clone(),
-        size_bytes: bytes.len() as u64,
-        indexed_at,
-        chunk_count: chunks.len(),
+        id: outcome.file.id,
+        name: outcome.file.name,
+        path: outcome.file.path,
+        size_bytes: outcome.file.size_bytes,
+        indexed_at: outcome.file.indexed_at as i64,
+        chunk_count: outcome.file.chunk_count as usize,
     };
 
-    for (idx, chunk) in chunks.iter().enumerate() {
-        let entry = MemoryEntry {
-            id: uuid::Uuid::new_v4().to_string(),
-            content: chunk.clone(),
-            embedding: None,
-            timestamp: chrono::Utc::now(),
-            tags: vec![
-                format!("agent:{}", agent_id),
-                "knowledge".to_string(),
-                format!("knowledge_file:{}", file.id),
-                format!("knowledge_file_name:{}", file.name),
-                format!("knowledge_file_path:{}", file.path),
-                format!("workspace:{}", agent_id),
-                format!("chunk:{}", idx),
-            ],
-        };
-        manager.0.store(entry).await.map_err(|e| e.to_string())?;
-    }
-
     Ok(KnowledgeIndexResult {
-        file: file.clone(),
         chunks_indexed: file.chunk_count,
+        file,
     })
 }
 
+/// Re-index an agent's knowledge files and watch them for changes
+///
+/// Indexes new or changed files, drops files no longer listed and returns
+/// the files with current chunk counts.
+///
+/// # Example
+///
+/// ```typescript
+/// import { invoke } from '@tauri-apps/api/core';
+///
+/// const files = await invoke('sync_agent_knowledge', {
+///   agentId: 'agent-id',
+///   knowledge: spec.memory_config.knowledge
+/// });
+/// ```
+#[tauri::command]
+pub async fn sync_agent_knowledge(
+    knowledge_base: State<'_, Arc<KnowledgeBase>>,
+    agent_id: String,
+    knowledge: KnowledgeConfig,
+) -> Result<Vec<crate::ai::specs::manifest::KnowledgeFile>, String> {
+    if agent_id.trim().is_empty() {
+        return Err("agent_id is required".to_string());
+    }
+    Ok(knowledge_base.sync(&agent_id, &knowledge).await)
+}
+
 #[tauri::command]
 pub async fn query_agent_memory(
-    _app_handle: tauri::AppHandle,
-    manager: State<'_, MemoryManagerState>,
+    knowledge_base: State<'_, Arc<KnowledgeBase>>,
     agent_id: String,
     query: String,
     strategy: Option<MemoryStrategy>,
@@ -481,28 +522,24 @@ pub async fn query_agent_memory(
     }
 
     let max_results = limit.unwrap_or(8).max(1).min(50);
-    let selected_strategy = strategy.unwrap_or(MemoryStrategy::Hybrid);
-    let entries = manager
-        .0
-        .query_workspace_memory(&agent_id, &query, max_results * 5)
-        .await
-        .map_err(|e| e.to_string())?;
+    let options = SearchOptions {
+        strategy: match strategy.unwrap_or(MemoryStrategy::Hybrid) {
+            MemoryStrategy::Vector => RetrievalStrategy::Vector,
+            MemoryStrategy::SimpleBuffer => RetrievalStrategy::Lexical,
+            MemoryStrategy::Hybrid => RetrievalStrategy::Hybrid,
+        },
+        rerank: false,
+    };
+    let result = knowledge_base
+        .search(&agent_id, &query, max_results, &options)
+        .await?;
 
-    let mut results: Vec<MemoryResult> = entries
+    // Entries arrive ranked; the score is only a coverage hint for display.
+    Ok(result
+        .entries
         .into_iter()
-        .filter(|entry| {
-            entry.tags.iter().any(|tag| tag == "knowledge")
-                && entry
-                    .tags
-                    .iter()
-                    .any(|tag| tag == &format!("agent:{}", agent_id))
-        })
         .map(|entry| {
-            let score = match selected_strategy {
-                MemoryStrategy::SimpleBuffer => compute_text_score(&query, &entry.content) * 0.7 + 0.3,
-                MemoryStrategy::Vector | MemoryStrategy::Hybrid => compute_text_score(&query, &entry.content),
-            };
-
+            let score = compute_text_score(&query, &entry.content);
             let file_id = extract_tag_value(&entry.tags, "knowledge_file");
             let file_name = extract_tag_value(&entry.tags, "knowledge_file_name");
             let file_path = extract_tag_value(&entry.tags, "knowledge_file_path");
@@ -516,11 +553,7 @@ pub async fn query_agent_memory(
                 score,
             }
         })
-        .collect();
-
-    results.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal));
-    results.truncate(max_results);
-    Ok(results)
+        .collect())
 }
 
 fn extract_tag_value(tags: &[String], key: &str) -> Option<String> {
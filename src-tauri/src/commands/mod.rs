@@ -17,6 +17,7 @@ pub mod image;
 pub mod memory;
 pub mod mcp;
 pub mod neural;
+pub mod process;
 
 pub mod research;
 pub mod router;
@@ -42,6 +43,7 @@ pub use image::*;
 pub use memory::*;
 pub use mcp::*;
 pub use neural::*;
+pub use process::*;
 
 pub use router::*;
 pub use settings::*;
@@ -0,0 +1,24 @@
+// Rainy Cowork - Agent Process Commands
+// Tauri commands to inspect and stop processes agents started with start_process
+
+use crate::services::process_manager::{ProcessInfo, ProcessManager};
+use tauri::State;
+
+/// List agent processes, optionally only those of one workspace
+#[tauri::command]
+pub async fn list_agent_processes(
+    workspace_id: Option<String>,
+    state: State<'_, ProcessManager>,
+) -> Result<Vec<ProcessInfo>, String> {
+    Ok(state.list(workspace_id.as_deref()).await)
+}
+
+/// Stop an agent process and its process group
+#[tauri::command]
+pub async fn stop_agent_process(
+    workspace_id: String,
+    process_id: String,
+    state: State<'_, ProcessManager>,
+) -> Result<ProcessInfo, String> {
+    state.stop(&workspace_id, &process_id).await
+}
@@ -1,8 +1,19 @@
 // Rainy Cowork - Settings Commands
 // Tauri commands for user settings and model selection
 
+use crate::services::memory_vault::EmbeddingProfile;
 use crate::services::settings::{ModelOption, SettingsManager, UserProfile, UserSettings};
 use crate::ai::provider::AIProviderManager;
+use crate::ai::provider_types::ProviderId;
+use crate::ai::providers::local::LOCAL_PROVIDER_ID;
+use crate::ai::specs::security::{signer_id_for, SpecSignaturePolicy, TrustedSigner};
+use crate::commands::ai_providers::ProviderRegistryState;
+use crate::services::command_policy::CommandPolicy;
+use crate::services::command_sandbox::{self, SandboxConfig};
+use crate::services::skill_executor::SkillExecutor;
+use crate::services::web_search::WebSearchConfig;
+use crate::commands::memory::MemoryManagerState;
+use crate::commands::router::IntelligentRouterState;
 use std::sync::Arc;
 use tauri::State;
 use tokio::sync::Mutex;
@@ -83,14 +94,16 @@ pub async fn get_embedder_provider(
     Ok(settings.get_embedder_provider().to_string())
 }
 
-/// Set embedder provider
+/// Set embedder provider (`gemini`, `openai` or `local`)
 #[tauri::command]
 pub async fn set_embedder_provider(
     provider: String,
     settings: State<'_, Arc<Mutex<SettingsManager>>>,
+    memory: State<'_, MemoryManagerState>,
 ) -> Result<(), String> {
-    let mut settings = settings.lock().await;
-    settings.set_embedder_provider(provider)
+    settings.lock().await.set_embedder_provider(provider)?;
+    memory.0.reload_embedder().await;
+    Ok(())
 }
 
 /// Get embedder model
@@ -107,9 +120,236 @@ pub async fn get_embedder_model(
 pub async fn set_embedder_model(
     model: String,
     settings: State<'_, Arc<Mutex<SettingsManager>>>,
+    memory: State<'_, MemoryManagerState>,
+) -> Result<(), String> {
+    settings.lock().await.set_embedder_model(model)?;
+    memory.0.reload_embedder().await;
+    Ok(())
+}
+
+/// Get OpenAI-compatible embeddings base URL
+#[tauri::command]
+pub async fn get_embedder_base_url(
+    settings: State<'_, Arc<Mutex<SettingsManager>>>,
+) -> Result<String, String> {
+    let settings = settings.lock().await;
+    Ok(settings.get_embedder_base_url().to_string())
+}
+
+/// Set OpenAI-compatible embeddings base URL
+#[tauri::command]
+pub async fn set_embedder_base_url(
+    base_url: String,
+    settings: State<'_, Arc<Mutex<SettingsManager>>>,
+    memory: State<'_, MemoryManagerState>,
+) -> Result<(), String> {
+    settings.lock().await.set_embedder_base_url(base_url)?;
+    memory.0.reload_embedder().await;
+    Ok(())
+}
+
+/// Set embedding vector size; `None` uses the model's known size
+#[tauri::command]
+pub async fn set_embedder_dimensions(
+    dimensions: Option<usize>,
+    settings: State<'_, Arc<Mutex<SettingsManager>>>,
+    memory: State<'_, MemoryManagerState>,
+) -> Result<(), String> {
+    settings.lock().await.set_embedder_dimensions(dimensions)?;
+    memory.0.reload_embedder().await;
+    Ok(())
+}
+
+/// Get the embedding profile memories are currently embedded with
+#[tauri::command]
+pub async fn get_embedding_profile(
+    memory: State<'_, MemoryManagerState>,
+) -> Result<EmbeddingProfile, String> {
+    Ok(memory.0.embedding_profile().await)
+}
+
+/// Get local model server base URL
+#[tauri::command]
+pub async fn get_local_llm_base_url(
+    settings: State<'_, Arc<Mutex<SettingsManager>>>,
+) -> Result<String, String> {
+    let settings = settings.lock().await;
+    Ok(settings.get_local_llm_base_url().to_string())
+}
+
+/// Set local model server base URL
+///
+/// Drops the registered local provider so the next run reconnects to the new URL.
+#[tauri::command]
+pub async fn set_local_llm_base_url(
+    base_url: String,
+    settings: State<'_, Arc<Mutex<SettingsManager>>>,
+    registry: State<'_, ProviderRegistryState>,
+    router: State<'_, IntelligentRouterState>,
+) -> Result<(), String> {
+    settings.lock().await.set_local_llm_base_url(base_url)?;
+
+    let provider_id = ProviderId::new(LOCAL_PROVIDER_ID);
+    registry.0.unregister(&provider_id).ok();
+    router.0.write().await.remove_provider(&provider_id);
+    Ok(())
+}
+
+/// Get which backend the `web_search` tool queries
+#[tauri::command]
+pub async fn get_web_search_config(
+    settings: State<'_, Arc<Mutex<SettingsManager>>>,
+) -> Result<WebSearchConfig, String> {
+    let settings = settings.lock().await;
+    Ok(settings.get_web_search_config())
+}
+
+/// Set which backend the `web_search` tool queries
+#[tauri::command]
+pub async fn set_web_search_config(
+    config: WebSearchConfig,
+    settings: State<'_, Arc<Mutex<SettingsManager>>>,
+) -> Result<(), String> {
+    let mut settings = settings.lock().await;
+    settings.set_web_search_config(config)
+}
+
+/// Get how agent spec signatures are enforced
+#[tauri::command]
+pub async fn get_spec_signature_policy(
+    settings: State<'_, Arc<Mutex<SettingsManager>>>,
+) -> Result<SpecSignaturePolicy, String> {
+    let settings = settings.lock().await;
+    Ok(settings.get_spec_signature_policy())
+}
+
+/// Set how agent spec signatures are enforced
+#[tauri::command]
+pub async fn set_spec_signature_policy(
+    policy: SpecSignaturePolicy,
+    settings: State<'_, Arc<Mutex<SettingsManager>>>,
+) -> Result<(), String> {
+    let mut settings = settings.lock().await;
+    settings.set_spec_signature_policy(policy)
+}
+
+/// Get the workspace's `execute_command` policy; `None` means the built-in allowlist
+#[tauri::command]
+pub async fn get_workspace_command_policy(
+    workspace_id: String,
+    settings: State<'_, Arc<Mutex<SettingsManager>>>,
+) -> Result<Option<CommandPolicy>, String> {
+    let settings = settings.lock().await;
+    Ok(settings.get_workspace_command_policy(&workspace_id))
+}
+
+/// Set the workspace's `execute_command` policy; `None` restores the built-in allowlist
+#[tauri::command]
+pub async fn set_workspace_command_policy(
+    workspace_id: String,
+    policy: Option<CommandPolicy>,
+    settings: State<'_, Arc<Mutex<SettingsManager>>>,
+) -> Result<(), String> {
+    let mut settings = settings.lock().await;
+    settings.set_workspace_command_policy(&workspace_id, policy)
+}
+
+/// Get the workspace's `execute_command` sandbox; `None` means unsandboxed
+#[tauri::command]
+pub async fn get_workspace_sandbox(
+    workspace_id: String,
+    settings: State<'_, Arc<Mutex<SettingsManager>>>,
+) -> Result<Option<SandboxConfig>, String> {
+    let settings = settings.lock().await;
+    Ok(settings.get_workspace_sandbox(&workspace_id))
+}
+
+/// Set the workspace's `execute_command` sandbox. Fails when the selected
+/// backend isn't supported by this OS or kernel.
+#[tauri::command]
+pub async fn set_workspace_sandbox(
+    workspace_id: String,
+    sandbox: Option<SandboxConfig>,
+    settings: State<'_, Arc<Mutex<SettingsManager>>>,
+) -> Result<(), String> {
+    if let Some(sandbox) = sandbox.as_ref() {
+        command_sandbox::check_support(sandbox)?;
+    }
+    let mut settings = settings.lock().await;
+    settings.set_workspace_sandbox(&workspace_id, sandbox)
+}
+
+/// Whether the workspace's files are indexed for `semantic_search_workspace`
+#[tauri::command]
+pub async fn get_workspace_indexed(
+    workspace_id: String,
+    settings: State<'_, Arc<Mutex<SettingsManager>>>,
+) -> Result<bool, String> {
+    let settings = settings.lock().await;
+    Ok(settings.is_workspace_indexed(&workspace_id))
+}
+
+/// Opt the workspace in or out of background indexing. Indexed file contents
+/// are sent to the configured embedder; opting out deletes the index.
+#[tauri::command]
+pub async fn set_workspace_indexed(
+    workspace_id: String,
+    enabled: bool,
+    settings: State<'_, Arc<Mutex<SettingsManager>>>,
+    skills: State<'_, Arc<SkillExecutor>>,
+) -> Result<(), String> {
+    settings
+        .lock()
+        .await
+        .set_workspace_indexed(&workspace_id, enabled)?;
+    if !enabled {
+        skills.drop_workspace_index(&workspace_id).await;
+    }
+    Ok(())
+}
+
+/// List public keys trusted to sign agent specs
+#[tauri::command]
+pub async fn list_trusted_spec_signers(
+    settings: State<'_, Arc<Mutex<SettingsManager>>>,
+) -> Result<Vec<TrustedSigner>, String> {
+    let settings = settings.lock().await;
+    Ok(settings.get_trusted_spec_signers().to_vec())
+}
+
+/// Trust a hex-encoded Ed25519 public key for agent spec signatures
+#[tauri::command]
+pub async fn add_trusted_spec_signer(
+    public_key_hex: String,
+    label: Option<String>,
+    settings: State<'_, Arc<Mutex<SettingsManager>>>,
+) -> Result<TrustedSigner, String> {
+    let public_key_hex = public_key_hex.trim().to_ascii_lowercase();
+    let key_bytes: [u8; 32] = hex::decode(&public_key_hex)
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| "Public key must be 32 bytes of hex".to_string())?;
+    ed25519_dalek::VerifyingKey::from_bytes(&key_bytes)
+        .map_err(|e| format!("Invalid Ed25519 public key: {}", e))?;
+
+    let signer = TrustedSigner {
+        signer_id: signer_id_for(&public_key_hex),
+        public_key_hex,
+        label: label.unwrap_or_default(),
+    };
+    let mut settings = settings.lock().await;
+    settings.upsert_trusted_spec_signer(signer.clone())?;
+    Ok(signer)
+}
+
+/// Stop trusting an agent spec signer
+#[tauri::command]
+pub async fn remove_trusted_spec_signer(
+    signer_id: String,
+    settings: State<'_, Arc<Mutex<SettingsManager>>>,
 ) -> Result<(), String> {
     let mut settings = settings.lock().await;
-    settings.set_embedder_model(model)
+    settings.remove_trusted_spec_signer(&signer_id)
 }
 
 /// Get available models based on user's plan
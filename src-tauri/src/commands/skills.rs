@@ -55,6 +55,8 @@ pub async fn execute_skill(
             tool_access_policy: None,
             tool_access_policy_version: None,
             tool_access_policy_hash: None,
+            command_policy: None,
+            run_id: None,
         },
         priority: CommandPriority::Normal,
         status: CommandStatus::Pending,
@@ -14,9 +14,10 @@ use crate::db::Database;
 use ai::{AIProviderManager, IntelligentRouter, ProviderRegistry};
 use services::{
     ATMClient, AgentLibraryService, AgentRunControl, BrowserController, CommandPoller,
-    DocumentService, FileManager, FileOperationEngine, FolderManager, ImageService, LLMClient,
-    ManagedResearchService, MemoryManager, NeuralService, NodeAuthenticator, SettingsManager,
-    SkillExecutor, SocketClient, WorkflowRecorderService, WorkspaceManager,
+    DocumentService, FileManager, FileOperationEngine, FolderManager, ImageService, KnowledgeBase,
+    LLMClient, ManagedResearchService, MemoryManager, NeuralService, NodeAuthenticator,
+    ProcessManager, SettingsManager, SkillExecutor, SocketClient, WorkflowRecorderService,
+    WorkspaceIndexer, WorkspaceManager,
 };
 use std::sync::Arc;
 use tokio::sync::{Mutex, RwLock};
@@ -30,7 +31,9 @@ pub fn run() {
     let provider_registry = Arc::new(ProviderRegistry::new());
 
     // Initialize task manager with Arc clone (needs its own reference)
-    let task_manager = Arc::new(services::task_manager::TaskManager::new(ai_provider.clone()));
+    let task_manager = Arc::new(services::task_manager::TaskManager::new(
+        ai_provider.clone(),
+    ));
 
     // Initialize file manager
     let file_manager = Arc::new(FileManager::new());
@@ -312,6 +315,23 @@ pub fn run() {
             // Manage memory manager state
             app.manage(commands::memory::MemoryManagerState(memory_manager.clone()));
 
+            // Per-agent knowledge bases; resume indexing and watching saved agents' files
+            let knowledge_base = Arc::new(KnowledgeBase::new(memory_manager.clone(), &app_data_dir));
+            app.manage(knowledge_base.clone());
+            let specs_handle = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                let specs = commands::agent_builder::list_agent_specs(specs_handle)
+                    .await
+                    .unwrap_or_default();
+                for spec in specs {
+                    if spec.memory_config.knowledge.enabled {
+                        knowledge_base
+                            .sync(&spec.id, &spec.memory_config.knowledge)
+                            .await;
+                    }
+                }
+            });
+
             // Inject MemoryManager into SkillExecutor (Late Binding)
             {
                 let se = app.state::<Arc<SkillExecutor>>();
@@ -321,6 +341,43 @@ pub fn run() {
                 });
             }
 
+            // Incremental semantic index of workspace roots, started lazily per workspace
+            {
+                let se = app.state::<Arc<SkillExecutor>>();
+                let indexer = WorkspaceIndexer::new(memory_manager.clone(), &app_data_dir);
+                tauri::async_runtime::block_on(async move {
+                    se.set_workspace_indexer(indexer).await;
+                });
+            }
+
+            // Inject FileOperationEngine so agent file mutations are checkpointed per run
+            {
+                let se = app.state::<Arc<SkillExecutor>>();
+                let ops = app.state::<Arc<FileOperationEngine>>().inner().clone();
+                tauri::async_runtime::block_on(async move {
+                    se.set_file_operation_engine(ops).await;
+                });
+            }
+
+            // Long-running agent processes (dev servers, watchers, REPLs), owned by their run
+            {
+                let process_manager = ProcessManager::new().with_events(app.handle().clone());
+                app.manage(process_manager.clone());
+                let se = app.state::<Arc<SkillExecutor>>();
+                tauri::async_runtime::block_on(async move {
+                    se.set_process_manager(process_manager).await;
+                });
+            }
+
+            // Tool handlers read command policy, sandbox and web search settings per call
+            {
+                let settings = app.state::<Arc<Mutex<SettingsManager>>>().inner().clone();
+                let se = app.state::<Arc<SkillExecutor>>();
+                tauri::async_runtime::block_on(async move {
+                    se.set_settings_manager(settings).await;
+                });
+            }
+
             // Initialize Airlock Service with app handle
             let airlock = AirlockService::new(app.handle().clone());
             let airlock_for_poller = airlock.clone();
@@ -339,6 +396,47 @@ pub fn run() {
             let agent_manager = AgentManager::new(db.pool.clone());
             app.manage(agent_manager.clone());
 
+            // Back the agent's entity knowledge graph tools with the app database
+            {
+                let se = app.state::<Arc<SkillExecutor>>();
+                let graph = crate::ai::agent::entity_graph::EntityGraph::new(db.pool.clone());
+                tauri::async_runtime::block_on(async move {
+                    se.set_entity_graph(graph).await;
+                });
+            }
+
+            // Shared limiter enforcing AgentSpec rate limits and daily token budgets
+            let rate_limiter = Arc::new(crate::ai::agent::rate_limiter::AgentRateLimiter::new(
+                Some(db.pool.clone()),
+            ));
+            app.manage(rate_limiter.clone());
+
+            // Restore file operation history and persist it from here on
+            {
+                let ops = app.state::<Arc<FileOperationEngine>>().inner().clone();
+                let attached =
+                    tauri::async_runtime::block_on(ops.attach_history_store(db.pool.clone()));
+                match attached {
+                    Ok(()) => {
+                        tauri::async_runtime::spawn(async move {
+                            let policy =
+                                crate::services::file_operations::RetentionPolicy::default();
+                            match ops.collect_garbage(&policy).await {
+                                Ok(report) => tracing::info!(
+                                    "File history GC removed {} operations, {} versions, {} trash entries ({} bytes)",
+                                    report.operations_removed,
+                                    report.versions_removed,
+                                    report.trash_entries_removed,
+                                    report.bytes_freed
+                                ),
+                                Err(e) => tracing::warn!("File history GC failed: {}", e),
+                            }
+                        });
+                    }
+                    Err(e) => tracing::error!("Failed to load file operation history: {}", e),
+                }
+            }
+
             // Initialize Persistent Scheduler
             let persistent_scheduler = std::sync::Arc::new(
                 crate::services::persistent_scheduler::PersistentScheduler::new(
@@ -370,6 +468,7 @@ pub fn run() {
             let agent_manager_for_poller = Arc::new(agent_manager);
             let runtime_registry_for_poller = runtime_registry.clone();
             let memory_manager_for_poller = memory_manager.clone();
+            let rate_limiter_for_poller = rate_limiter.clone();
 
             tauri::async_runtime::spawn(async move {
                 // Inject Airlock service
@@ -383,6 +482,7 @@ pub fn run() {
                         agent_manager_for_poller,
                         runtime_registry_for_poller,
                         memory_manager_for_poller,
+                        rate_limiter_for_poller,
                     )
                     .await;
 
@@ -524,10 +624,14 @@ pub fn run() {
             commands::commit_file_transaction,
             commands::rollback_file_transaction,
             commands::get_file_transaction,
+            commands::preview_agent_run_revert,
+            commands::revert_agent_run,
             // Enhanced undo/redo commands
             commands::undo_file_operation_enhanced,
             commands::redo_file_operation,
             commands::list_enhanced_file_operations,
+            commands::query_file_operations,
+            commands::collect_file_history_garbage,
             commands::set_file_ops_workspace,
             // Memory commands (NEW - Memory System)
             commands::store_memory,
@@ -538,9 +642,12 @@ pub fn run() {
             commands::get_memory_stats,
             commands::get_memory_by_id,
             commands::delete_memory,
+            commands::pin_memory,
+            commands::run_memory_maintenance,
             commands::get_short_term_memory_size,
             commands::is_short_term_memory_empty,
             commands::index_knowledge_file,
+            commands::sync_agent_knowledge,
             commands::query_agent_memory,
             // Settings commands
             commands::get_user_settings,
@@ -550,6 +657,27 @@ pub fn run() {
             commands::set_embedder_provider,
             commands::get_embedder_model,
             commands::set_embedder_model,
+            commands::get_embedder_base_url,
+            commands::set_embedder_base_url,
+            commands::set_embedder_dimensions,
+            commands::get_embedding_profile,
+            commands::get_local_llm_base_url,
+            commands::set_local_llm_base_url,
+            commands::get_web_search_config,
+            commands::set_web_search_config,
+            commands::get_spec_signature_policy,
+            commands::set_spec_signature_policy,
+            commands::get_workspace_command_policy,
+            commands::set_workspace_command_policy,
+            commands::get_workspace_sandbox,
+            commands::set_workspace_sandbox,
+            commands::get_workspace_indexed,
+            commands::set_workspace_indexed,
+            commands::list_agent_processes,
+            commands::stop_agent_process,
+            commands::list_trusted_spec_signers,
+            commands::add_trusted_spec_signer,
+            commands::remove_trusted_spec_signer,
             commands::set_theme,
             commands::set_notifications,
             commands::get_user_profile,
@@ -675,6 +803,8 @@ pub fn run() {
             commands::load_agent_spec,
             commands::list_agent_specs,
             commands::deploy_agent_spec,
+            commands::sign_agent_spec,
+            commands::verify_agent_spec_signature,
             // Agent Persistence (Phase 3)
             manager::save_agent_to_db,
             manager::load_agents_from_db,
@@ -686,6 +816,9 @@ pub fn run() {
             manager::get_chat_runtime_telemetry,
             manager::clear_chat_history,
             manager::compact_session_cmd,
+            manager::get_compacted_messages,
+            manager::expand_chat_compaction,
+            manager::fork_chat_session,
             crate::services::mcp_http::handle_mcp_request,
             commands::list_mcp_servers,
             commands::upsert_mcp_server,
@@ -1,3 +1,4 @@
+use crate::services::command_policy::CommandPolicy;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
@@ -157,6 +158,12 @@ pub struct RainyPayload {
     /// Optional SHA-256 hash of canonicalized tool policy.
     #[serde(default)]
     pub tool_access_policy_hash: Option<String>,
+    /// Agent's `execute_command` rules; can only narrow the workspace's command policy.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub command_policy: Option<CommandPolicy>,
+    /// Agent run issuing this command; file mutations are checkpointed under it.
+    #[serde(default)]
+    pub run_id: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
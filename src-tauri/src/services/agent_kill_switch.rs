@@ -1,9 +1,13 @@
+use std::future::Future;
 use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, Ordering};
+use tokio::sync::Notify;
 
 #[derive(Clone, Debug, Default)]
 pub struct AgentKillSwitch {
     triggered: Arc<AtomicBool>,
+    notify: Arc<Notify>,
+    interrupted: Arc<std::sync::Mutex<Vec<String>>>,
 }
 
 impl AgentKillSwitch {
@@ -13,13 +17,99 @@ impl AgentKillSwitch {
 
     pub fn trigger(&self) {
         self.triggered.store(true, Ordering::Relaxed);
+        self.notify.notify_waiters();
     }
 
     pub fn clear(&self) {
         self.triggered.store(false, Ordering::Relaxed);
+        self.interrupted
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clear();
     }
 
     pub fn is_triggered(&self) -> bool {
         self.triggered.load(Ordering::Relaxed)
     }
+
+    /// Resolves once the switch is triggered.
+    pub async fn cancelled(&self) {
+        loop {
+            // Register interest before checking the flag so a trigger between
+            // the check and the await is not missed.
+            let notified = self.notify.notified();
+            if self.is_triggered() {
+                return;
+            }
+            notified.await;
+        }
+    }
+
+    /// Drive `future` until it completes or the switch is triggered. On
+    /// trigger the future is dropped (aborting in-flight I/O, killing
+    /// `kill_on_drop` children) and `what` is recorded as interrupted.
+    pub async fn run_cancellable<F: Future>(
+        &self,
+        what: impl Into<String>,
+        future: F,
+    ) -> Option<F::Output> {
+        tokio::select! {
+            biased;
+            _ = self.cancelled() => {
+                self.interrupted
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .push(what.into());
+                None
+            }
+            output = future => Some(output),
+        }
+    }
+
+    /// Operations that were aborted mid-flight since the last `clear`.
+    pub fn interrupted(&self) -> Vec<String> {
+        self.interrupted
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_run_cancellable_aborts_in_flight_future() {
+        let switch = AgentKillSwitch::new();
+        let trigger = switch.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            trigger.trigger();
+        });
+
+        let result = switch
+            .run_cancellable(
+                "slow completion",
+                tokio::time::sleep(Duration::from_secs(30)),
+            )
+            .await;
+
+        assert!(result.is_none());
+        assert_eq!(switch.interrupted(), vec!["slow completion".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_run_cancellable_completes_when_not_triggered() {
+        let switch = AgentKillSwitch::new();
+        assert_eq!(switch.run_cancellable("fast", async { 7 }).await, Some(7));
+        assert!(switch.interrupted().is_empty());
+
+        switch.trigger();
+        assert!(switch.run_cancellable("late", async { 7 }).await.is_none());
+        switch.clear();
+        assert!(!switch.is_triggered());
+        assert!(switch.interrupted().is_empty());
+    }
 }
@@ -10,13 +10,18 @@
 //! - **Level 2 (Dangerous)**: Execution operations - requires explicit approval
 
 use crate::models::neural::{AirlockLevel, QueuedCommand};
+use crate::services::command_policy::{
+    command_from_params, evaluate_command, is_command_tool, CommandDecision, CommandMatch,
+    CommandPolicy,
+};
+use crate::services::settings::SettingsManager;
 use crate::services::ThirdPartySkillRegistry;
 use crate::services::tool_policy::get_tool_policy;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
-use tauri::{AppHandle, Emitter};
+use tauri::{AppHandle, Emitter, Manager};
 use tokio::sync::{oneshot, Mutex};
 
 // Used when emitting approval request events to frontend
@@ -28,6 +33,9 @@ pub struct ApprovalRequest {
     pub payload_summary: String,
     pub airlock_level: AirlockLevel,
     pub timestamp: i64,
+    /// Command policy rule(s) that allowed an `execute_command` or `start_process` call.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub command_rule: Option<String>,
 }
 
 /// Result of an approval request
@@ -97,18 +105,49 @@ impl AirlockService {
             .unwrap_or(false)
     }
 
-    fn effective_airlock_level(command: &QueuedCommand) -> AirlockLevel {
+    /// `execute_command` and `start_process` calls are classified by the
+    /// command policy rather than the tool's blanket level. `None` for every
+    /// other tool.
+    async fn command_decision(&self, command: &QueuedCommand) -> Option<CommandDecision> {
+        if !Self::infer_tool_name(command).is_some_and(|tool| is_command_tool(&tool)) {
+            return None;
+        }
+        let (binary, args) = command_from_params(command.payload.params.as_ref());
+        let workspace_id = command.workspace_id.clone().unwrap_or_default();
+        let shared = self
+            .app
+            .try_state::<Arc<Mutex<SettingsManager>>>()
+            .map(|state| state.inner().clone());
+        let workspace = SettingsManager::read_shared(shared.as_ref(), move |settings| {
+            CommandPolicy::for_workspace(settings, &workspace_id)
+        })
+        .await;
+        Some(evaluate_command(
+            &workspace,
+            command.payload.command_policy.as_ref(),
+            &binary,
+            &args,
+        ))
+    }
+
+    fn effective_airlock_level(
+        command: &QueuedCommand,
+        command_match: Option<&CommandMatch>,
+    ) -> AirlockLevel {
         if Self::is_agent_run_bootstrap(command) {
             return AirlockLevel::Safe;
         }
         let declared = command.airlock_level;
-        let policy_level = Self::infer_tool_name(command)
-            .and_then(|tool| {
-                get_tool_policy(&tool)
-                    .map(|policy| policy.airlock_level)
-                    .or_else(|| Self::third_party_tool_level(&tool))
-            })
-            .unwrap_or(AirlockLevel::Dangerous);
+        let policy_level = match command_match {
+            Some(matched) => matched.airlock_level,
+            None => Self::infer_tool_name(command)
+                .and_then(|tool| {
+                    get_tool_policy(&tool)
+                        .map(|policy| policy.airlock_level)
+                        .or_else(|| Self::third_party_tool_level(&tool))
+                })
+                .unwrap_or(AirlockLevel::Dangerous),
+        };
 
         if policy_level > declared {
             policy_level
@@ -192,7 +231,18 @@ impl AirlockService {
             return Ok(false);
         }
 
-        let effective_level = Self::effective_airlock_level(command);
+        let command_decision = self.command_decision(command).await;
+        let command_match = match &command_decision {
+            Some(CommandDecision::Deny { reason }) => {
+                tracing::warn!("Airlock: Denying command {}: {}", command.id, reason);
+                return Ok(false);
+            }
+            Some(CommandDecision::Allow(matched)) => Some(matched),
+            None => None,
+        };
+        let command_rule = command_match.map(CommandMatch::describe);
+
+        let effective_level = Self::effective_airlock_level(command, command_match);
         if effective_level != command.airlock_level {
             tracing::warn!(
                 "Airlock: Escalating command {} level from {:?} to {:?} based on tool policy",
@@ -221,7 +271,8 @@ impl AirlockService {
                         "Airlock: SENSITIVE command {} requires notification",
                         command.id
                     );
-                    self.request_approval(command, effective_level, false).await
+                    self.request_approval(command, effective_level, command_rule, false)
+                        .await
                 }
             }
             AirlockLevel::Dangerous => {
@@ -238,7 +289,8 @@ impl AirlockService {
                     "Airlock: DANGEROUS command {} requires explicit approval",
                     command.id
                 );
-                self.request_approval(command, effective_level, false).await
+                self.request_approval(command, effective_level, command_rule, false)
+                    .await
             }
         }
     }
@@ -254,6 +306,7 @@ impl AirlockService {
         &self,
         command: &QueuedCommand,
         effective_level: AirlockLevel,
+        command_rule: Option<String>,
         allow_on_timeout: bool,
     ) -> Result<bool, String> {
         let request = ApprovalRequest {
@@ -262,6 +315,7 @@ impl AirlockService {
             payload_summary: serde_json::to_string(&command.payload).unwrap_or_default(),
             airlock_level: effective_level,
             timestamp: chrono::Utc::now().timestamp_millis(),
+            command_rule,
         };
 
         let (tx, rx) = oneshot::channel::<ApprovalResult>();
@@ -374,6 +428,7 @@ mod tests {
             payload_summary: "{\"path\":\"/tmp/x\"}".to_string(),
             airlock_level: AirlockLevel::Sensitive,
             timestamp,
+            command_rule: None,
         }
     }
 
@@ -434,6 +489,8 @@ mod tests {
                 }),
                 tool_access_policy_version: None,
                 tool_access_policy_hash: None,
+                command_policy: None,
+                run_id: None,
             },
             priority: CommandPriority::Normal,
             status: CommandStatus::Pending,
@@ -445,7 +502,7 @@ mod tests {
             completed_at: None,
         };
 
-        let level = AirlockService::effective_airlock_level(&command);
+        let level = AirlockService::effective_airlock_level(&command, None);
         assert_eq!(level, AirlockLevel::Dangerous);
     }
 
@@ -480,6 +537,8 @@ mod tests {
                 }),
                 tool_access_policy_version: None,
                 tool_access_policy_hash: None,
+                command_policy: None,
+                run_id: None,
             },
             priority: CommandPriority::Normal,
             status: CommandStatus::Pending,
@@ -507,7 +566,7 @@ mod tests {
             let declared = level_from_u8(rng.gen_range(0u8..=2u8));
             let tool = tools[rng.gen_range(0..tools.len())];
             let command = make_command_with_tool(tool, declared);
-            let effective = AirlockService::effective_airlock_level(&command);
+            let effective = AirlockService::effective_airlock_level(&command, None);
             assert!(
                 effective >= declared,
                 "effective={:?} declared={:?} tool={}",
@@ -535,7 +594,7 @@ mod tests {
 
             let method = format!("unknown_{}", suffix);
             let command = make_command_with_tool(&method, declared);
-            let effective = AirlockService::effective_airlock_level(&command);
+            let effective = AirlockService::effective_airlock_level(&command, None);
             assert_eq!(
                 effective,
                 AirlockLevel::Dangerous,
@@ -573,7 +632,7 @@ mod tests {
         command.payload.method = Some("run".to_string());
         assert!(AirlockService::is_agent_run_bootstrap(&command));
         assert_eq!(
-            AirlockService::effective_airlock_level(&command),
+            AirlockService::effective_airlock_level(&command, None),
             AirlockLevel::Safe
         );
     }
@@ -376,6 +376,23 @@ impl BrowserController {
             .map_err(|e| format!("Failed to parse result: {}", e))
     }
 
+    /// Stop any in-progress page load. Best effort: used when a navigation is
+    /// interrupted by run cancellation, so failures are ignored.
+    pub async fn stop_loading(&self) {
+        let stop = async {
+            let browser_lock = self.browser.lock().await;
+            let Some(browser) = browser_lock.as_ref() else {
+                return;
+            };
+            if let Ok(pages) = browser.pages().await {
+                if let Some(page) = pages.last() {
+                    let _ = page.evaluate("window.stop()").await;
+                }
+            }
+        };
+        let _ = tokio::time::timeout(std::time::Duration::from_secs(2), stop).await;
+    }
+
     /// Close the browser gracefully
     #[allow(dead_code)] // @RESERVED - will be used for cleanup
     pub async fn close(&self) {
@@ -0,0 +1,414 @@
+//! Shell command policy for `execute_command`
+//!
+//! A workspace policy (stored in settings) is the baseline; without one the
+//! built-in allowlist applies. An agent spec may carry its own policy, which
+//! can only narrow the workspace's: a command must be allowed by both, and
+//! the stricter Airlock level wins.
+//!
+//! Within a policy any matching deny rule wins; otherwise the first matching
+//! allow rule (in list order) decides the Airlock level. Commands no allow
+//! rule matches are denied.
+
+use crate::models::neural::AirlockLevel;
+use crate::services::settings::SettingsManager;
+use serde::{Deserialize, Serialize};
+
+/// Always inherited when an env allowlist is set, so binaries still resolve.
+const ALWAYS_INHERITED_ENV: &[&str] = &["PATH"];
+/// Allowed by the built-in policy, which applies when a workspace has none.
+const BUILTIN_BINARIES: &[&str] = &[
+    "npm", "pnpm", "bun", "cargo", "git", "ls", "grep", "echo", "cat",
+];
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CommandRuleAction {
+    Allow,
+    Deny,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CommandRule {
+    /// Shown in approval requests and denials; derived from the rule when empty.
+    #[serde(default)]
+    pub id: String,
+    /// Binary name or glob (`python*`). Names without `/` never match a
+    /// command given as a path.
+    pub binary: String,
+    /// Globs the leading arguments must match, in order (`["push"]`).
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// Globs that must each match some argument anywhere (`["--force*"]`).
+    #[serde(default)]
+    pub args_contain: Vec<String>,
+    pub action: CommandRuleAction,
+    /// Approval level for commands this rule allows (default: Dangerous).
+    #[serde(default)]
+    pub airlock_level: Option<AirlockLevel>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct CommandPolicy {
+    #[serde(default)]
+    pub rules: Vec<CommandRule>,
+    /// Environment variables commands inherit and may set. `None` inherits
+    /// the app's environment and accepts no overrides.
+    #[serde(default)]
+    pub env_allowlist: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CommandMatch {
+    /// Labels of the rules that allowed the command, workspace first.
+    pub rules: Vec<String>,
+    pub airlock_level: AirlockLevel,
+    pub env_allowlist: Option<Vec<String>>,
+}
+
+impl CommandMatch {
+    pub fn describe(&self) -> String {
+        self.rules.join(" + ")
+    }
+
+    /// Whether `name` may be set by the caller.
+    pub fn allows_env(&self, name: &str) -> bool {
+        self.env_allowlist
+            .as_ref()
+            .is_some_and(|list| list.iter().any(|allowed| allowed == name))
+    }
+
+    /// Variables to inherit from the app's environment; `None` inherits all.
+    pub fn inherited_env(&self) -> Option<Vec<String>> {
+        self.env_allowlist.as_ref().map(|list| {
+            let mut names = list.clone();
+            for name in ALWAYS_INHERITED_ENV {
+                if !names.iter().any(|n| n == name) {
+                    names.push(name.to_string());
+                }
+            }
+            names
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CommandDecision {
+    Allow(CommandMatch),
+    Deny { reason: String },
+}
+
+impl CommandRule {
+    pub fn label(&self) -> String {
+        if !self.id.trim().is_empty() {
+            return self.id.clone();
+        }
+        let mut parts = vec![self.binary.clone()];
+        parts.extend(self.args.iter().cloned());
+        if !self.args_contain.is_empty() {
+            parts.push(format!("[{}]", self.args_contain.join(", ")));
+        }
+        let action = match self.action {
+            CommandRuleAction::Allow => "allow",
+            CommandRuleAction::Deny => "deny",
+        };
+        format!("{} `{}`", action, parts.join(" "))
+    }
+
+    fn matches(&self, binary: &str, args: &[String]) -> bool {
+        if binary.contains('/') && !self.binary.contains('/') {
+            return false;
+        }
+        if !glob_matches(&self.binary, binary) || args.len() < self.args.len() {
+            return false;
+        }
+        self.args
+            .iter()
+            .zip(args)
+            .all(|(pattern, arg)| glob_matches(pattern, arg))
+            && self
+                .args_contain
+                .iter()
+                .all(|pattern| args.iter().any(|arg| glob_matches(pattern, arg)))
+    }
+}
+
+impl CommandPolicy {
+    /// Allow the binaries `execute_command` has always accepted, with approval.
+    pub fn builtin() -> Self {
+        Self {
+            rules: BUILTIN_BINARIES
+                .iter()
+                .map(|binary| CommandRule {
+                    id: format!("builtin: {}", binary),
+                    binary: binary.to_string(),
+                    args: Vec::new(),
+                    args_contain: Vec::new(),
+                    action: CommandRuleAction::Allow,
+                    airlock_level: None,
+                })
+                .collect(),
+            env_allowlist: None,
+        }
+    }
+
+    /// The workspace's stored policy, or the built-in one.
+    pub fn for_workspace(settings: &SettingsManager, workspace_id: &str) -> Self {
+        settings
+            .get_workspace_command_policy(workspace_id)
+            .unwrap_or_else(Self::builtin)
+    }
+
+    pub fn evaluate(&self, binary: &str, args: &[String]) -> CommandDecision {
+        let matching = self
+            .rules
+            .iter()
+            .filter(|rule| rule.matches(binary, args))
+            .collect::<Vec<_>>();
+        if let Some(deny) = matching
+            .iter()
+            .find(|rule| rule.action == CommandRuleAction::Deny)
+        {
+            return CommandDecision::Deny {
+                reason: format!("Command denied by rule {}", deny.label()),
+            };
+        }
+        match matching
+            .iter()
+            .find(|rule| rule.action == CommandRuleAction::Allow)
+        {
+            Some(allow) => CommandDecision::Allow(CommandMatch {
+                rules: vec![allow.label()],
+                airlock_level: allow.airlock_level.unwrap_or(AirlockLevel::Dangerous),
+                env_allowlist: self.env_allowlist.clone(),
+            }),
+            None => CommandDecision::Deny {
+                reason: format!("Command '{}' is not allowed by the command policy", binary),
+            },
+        }
+    }
+}
+
+/// Evaluate a command against the workspace policy and, when present, the
+/// agent's. The agent policy can deny or raise the level, never lower it.
+pub fn evaluate_command(
+    workspace: &CommandPolicy,
+    agent: Option<&CommandPolicy>,
+    binary: &str,
+    args: &[String],
+) -> CommandDecision {
+    let mut matched = match workspace.evaluate(binary, args) {
+        CommandDecision::Allow(matched) => matched,
+        denied => return denied,
+    };
+    let Some(agent) = agent else {
+        return CommandDecision::Allow(matched);
+    };
+    match agent.evaluate(binary, args) {
+        CommandDecision::Deny { reason } => CommandDecision::Deny {
+            reason: format!("{} (agent policy)", reason),
+        },
+        CommandDecision::Allow(agent_match) => {
+            if agent_match.airlock_level > matched.airlock_level {
+                matched.airlock_level = agent_match.airlock_level;
+            }
+            matched.rules.extend(agent_match.rules);
+            matched.env_allowlist = match (matched.env_allowlist, agent_match.env_allowlist) {
+                (Some(workspace_env), Some(agent_env)) => Some(
+                    workspace_env
+                        .into_iter()
+                        .filter(|name| agent_env.contains(name))
+                        .collect(),
+                ),
+                (workspace_env, agent_env) => workspace_env.or(agent_env),
+            };
+            CommandDecision::Allow(matched)
+        }
+    }
+}
+
+/// Tools whose call runs a command line and is classified by the command policy.
+pub fn is_command_tool(tool: &str) -> bool {
+    matches!(tool, "execute_command" | "start_process")
+}
+
+/// Binary and arguments of an `execute_command` call's params.
+pub fn command_from_params(params: Option<&serde_json::Value>) -> (String, Vec<String>) {
+    let binary = params
+        .and_then(|p| p.get("command"))
+        .and_then(|v| v.as_str())
+        .unwrap_or_default()
+        .to_string();
+    let args = params
+        .and_then(|p| p.get("args"))
+        .and_then(|v| v.as_array())
+        .map(|args| {
+            args.iter()
+                .filter_map(|arg| arg.as_str().map(str::to_string))
+                .collect()
+        })
+        .unwrap_or_default();
+    (binary, args)
+}
+
+/// `*` matches any run of characters, `?` exactly one.
+fn glob_matches(pattern: &str, text: &str) -> bool {
+    let pattern = pattern.chars().collect::<Vec<_>>();
+    let text = text.chars().collect::<Vec<_>>();
+    let (mut p, mut t) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, t));
+                p += 1;
+            }
+            Some(c) if *c == '?' || *c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match backtrack {
+                Some((star, matched)) => {
+                    p = star + 1;
+                    t = matched + 1;
+                    backtrack = Some((star, matched + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    fn rule(
+        binary: &str,
+        leading: &[&str],
+        contain: &[&str],
+        action: CommandRuleAction,
+    ) -> CommandRule {
+        CommandRule {
+            id: String::new(),
+            binary: binary.to_string(),
+            args: args(leading),
+            args_contain: args(contain),
+            action,
+            airlock_level: None,
+        }
+    }
+
+    #[test]
+    fn builtin_policy_keeps_the_original_allowlist() {
+        let policy = CommandPolicy::builtin();
+        for cmd in BUILTIN_BINARIES {
+            assert!(matches!(
+                policy.evaluate(cmd, &[]),
+                CommandDecision::Allow(_)
+            ));
+        }
+        for cmd in [
+            "rm",
+            "curl",
+            "wget",
+            "kill",
+            "mv",
+            "cp",
+            "node",
+            "/usr/bin/git",
+        ] {
+            assert!(matches!(
+                policy.evaluate(cmd, &[]),
+                CommandDecision::Deny { .. }
+            ));
+        }
+    }
+
+    #[test]
+    fn deny_rules_override_allow_rules_on_arguments() {
+        let mut status = rule("git", &["status"], &[], CommandRuleAction::Allow);
+        status.airlock_level = Some(AirlockLevel::Safe);
+        let policy = CommandPolicy {
+            rules: vec![
+                status,
+                rule("git", &[], &[], CommandRuleAction::Allow),
+                rule("git", &["push"], &["--force*"], CommandRuleAction::Deny),
+                rule("python*", &["-m", "pytest"], &[], CommandRuleAction::Allow),
+            ],
+            env_allowlist: None,
+        };
+
+        let CommandDecision::Allow(matched) = policy.evaluate("git", &args(&["status", "-s"]))
+        else {
+            panic!("git status should be allowed");
+        };
+        assert_eq!(matched.airlock_level, AirlockLevel::Safe);
+        assert_eq!(matched.describe(), "allow `git status`");
+
+        assert!(matches!(
+            policy.evaluate("git", &args(&["push", "origin", "--force-with-lease"])),
+            CommandDecision::Deny { .. }
+        ));
+        assert!(matches!(
+            policy.evaluate("git", &args(&["push", "origin"])),
+            CommandDecision::Allow(_)
+        ));
+        assert!(matches!(
+            policy.evaluate("python3", &args(&["-m", "pytest", "-q"])),
+            CommandDecision::Allow(_)
+        ));
+        assert!(matches!(
+            policy.evaluate("python3", &args(&["-c", "print(1)"])),
+            CommandDecision::Deny { .. }
+        ));
+    }
+
+    #[test]
+    fn agent_policy_only_narrows_the_workspace_policy() {
+        let mut workspace = CommandPolicy {
+            rules: vec![rule("npm", &[], &[], CommandRuleAction::Allow)],
+            env_allowlist: Some(args(&["NODE_ENV", "CI"])),
+        };
+        workspace.rules[0].airlock_level = Some(AirlockLevel::Sensitive);
+        let mut agent = CommandPolicy {
+            rules: vec![
+                rule("npm", &["publish"], &[], CommandRuleAction::Deny),
+                rule("npm", &[], &[], CommandRuleAction::Allow),
+                rule("make", &[], &[], CommandRuleAction::Allow),
+            ],
+            env_allowlist: Some(args(&["CI", "LD_PRELOAD"])),
+        };
+        agent.rules[1].airlock_level = Some(AirlockLevel::Safe);
+
+        let CommandDecision::Allow(matched) =
+            evaluate_command(&workspace, Some(&agent), "npm", &args(&["test"]))
+        else {
+            panic!("npm test should be allowed");
+        };
+        assert_eq!(matched.airlock_level, AirlockLevel::Sensitive);
+        assert!(matched.allows_env("CI"));
+        assert!(!matched.allows_env("LD_PRELOAD"));
+        assert!(matches!(
+            evaluate_command(&workspace, Some(&agent), "npm", &args(&["publish"])),
+            CommandDecision::Deny { .. }
+        ));
+        assert!(matches!(
+            evaluate_command(&workspace, Some(&agent), "make", &[]),
+            CommandDecision::Deny { .. }
+        ));
+    }
+
+    #[test]
+    fn glob_matching_handles_wildcards() {
+        assert!(glob_matches("--force*", "--force-with-lease"));
+        assert!(glob_matches("py*n?", "python3"));
+        assert!(glob_matches("*", ""));
+        assert!(!glob_matches("-f", "-fu"));
+        assert!(!glob_matches("py*n", "python3"));
+    }
+}
@@ -1,8 +1,10 @@
 use crate::ai::agent::memory::AgentMemory;
 use crate::ai::agent::events::AgentEvent;
+use crate::ai::agent::rate_limiter::AgentRateLimiter;
 use crate::ai::agent::runtime::AgentRuntime;
 use crate::ai::agent::runtime_registry::RuntimeRegistry;
 use crate::ai::router::IntelligentRouter;
+use crate::ai::specs::security::SpecSignaturePolicy;
 use crate::models::neural::CommandResult;
 use crate::services::airlock::AirlockService;
 use crate::services::agent_kill_switch::AgentKillSwitch;
@@ -13,6 +15,7 @@ use crate::services::neural_service::NeuralService;
 use crate::services::settings::SettingsManager;
 use crate::services::skill_executor::SkillExecutor;
 use crate::services::tool_manifest::build_skill_manifest_from_runtime;
+use crate::services::tool_output::{ToolOutputSink, ToolOutputStream};
 use crate::services::MemoryManager;
 use rand::Rng;
 use std::path::PathBuf;
@@ -28,6 +31,8 @@ const MAX_PROGRESS_PREVIEW_CHARS: usize = 300;
 const AGENT_PROGRESS_CHANNEL_CAPACITY: usize = 128;
 const AGENT_PROGRESS_MIN_INTERVAL: Duration = Duration::from_millis(250);
 const AGENT_PROGRESS_MAX_SUPPRESSED: u32 = 12;
+const TOOL_OUTPUT_PROGRESS_INTERVAL: Duration = Duration::from_secs(1);
+const MAX_TOOL_OUTPUT_PROGRESS_BYTES: usize = 8 * 1024;
 const DEFAULT_REMOTE_AGENT_MAX_STEPS: usize = 80;
 const MIN_REMOTE_AGENT_MAX_STEPS: usize = 4;
 const MAX_REMOTE_AGENT_MAX_STEPS: usize = 200;
@@ -104,6 +109,15 @@ fn map_agent_event(event: &AgentEvent) -> (String, serde_json::Value) {
                 "resultPreview": progress_preview(result),
             }),
         ),
+        AgentEvent::ToolOutputChunk { id, stream, text } => (
+            "Tool output".to_string(),
+            serde_json::json!({
+                "type": "tool_output",
+                "toolCallId": id,
+                "stream": stream,
+                "text": progress_preview(text),
+            }),
+        ),
         AgentEvent::Error(text) => (
             "Agent error".to_string(),
             serde_json::json!({
@@ -178,6 +192,26 @@ fn map_agent_event(event: &AgentEvent) -> (String, serde_json::Value) {
                 "summary": payload.summary,
             }),
         ),
+        AgentEvent::RunCancelled { interrupted } => (
+            "Run cancelled".to_string(),
+            serde_json::json!({
+                "type": "run_cancelled",
+                "interrupted": interrupted,
+            }),
+        ),
+        AgentEvent::PromptInjectionDetected {
+            tool_call_id,
+            tool,
+            flags,
+        } => (
+            format!("Prompt injection detected: {}", tool),
+            serde_json::json!({
+                "type": "prompt_injection_detected",
+                "toolCallId": tool_call_id,
+                "toolName": tool,
+                "flags": flags,
+            }),
+        ),
     }
 }
 
@@ -234,6 +268,76 @@ async fn report_agent_progress_event(
         .await;
 }
 
+/// Output of a running tool on its way to Cloud; `tool_call_id` is set for
+/// tools an agent run called.
+struct ToolOutputProgress {
+    tool_call_id: Option<String>,
+    stream: ToolOutputStream,
+    text: String,
+}
+
+/// Forward tool output to Cloud as command progress until every sender is
+/// dropped. Output is batched into one report per interval; a batch larger
+/// than `MAX_TOOL_OUTPUT_PROGRESS_BYTES` keeps its most recent output.
+async fn report_tool_output(
+    neural_service: NeuralService,
+    command_id: String,
+    mut output_rx: mpsc::UnboundedReceiver<ToolOutputProgress>,
+) {
+    while let Some(first) = output_rx.recv().await {
+        sleep(TOOL_OUTPUT_PROGRESS_INTERVAL).await;
+        let mut batch = vec![first];
+        while let Ok(next) = output_rx.try_recv() {
+            match batch.last_mut() {
+                Some(last)
+                    if last.tool_call_id == next.tool_call_id && last.stream == next.stream =>
+                {
+                    last.text.push_str(&next.text);
+                }
+                _ => batch.push(next),
+            }
+        }
+
+        let mut budget = MAX_TOOL_OUTPUT_PROGRESS_BYTES;
+        let mut dropped_bytes = 0usize;
+        let mut chunks = Vec::new();
+        for progress in batch.into_iter().rev() {
+            let text = if progress.text.len() <= budget {
+                progress.text
+            } else {
+                let mut cut = progress.text.len() - budget;
+                while !progress.text.is_char_boundary(cut) {
+                    cut += 1;
+                }
+                dropped_bytes += cut;
+                progress.text[cut..].to_string()
+            };
+            budget -= text.len();
+            if !text.is_empty() {
+                chunks.push(serde_json::json!({
+                    "toolCallId": progress.tool_call_id,
+                    "stream": progress.stream,
+                    "text": text,
+                }));
+            }
+        }
+        chunks.reverse();
+
+        let _ = neural_service
+            .report_command_progress(
+                &command_id,
+                "info",
+                "Tool output",
+                Some(serde_json::json!({
+                    "type": "tool_output",
+                    "chunks": chunks,
+                    "droppedBytes": dropped_bytes,
+                })),
+            )
+            .await;
+    }
+}
+
 use crate::ai::agent::manager::AgentManager;
 
 /// Context needed to create AgentRuntime instances on-demand
@@ -243,6 +347,7 @@ pub struct AgentRuntimeContext {
     pub agent_manager: Arc<AgentManager>,
     pub runtime_registry: Arc<RuntimeRegistry>,
     pub memory_manager: Arc<MemoryManager>,
+    pub rate_limiter: Arc<AgentRateLimiter>,
 }
 
 #[derive(Clone)]
@@ -283,6 +388,7 @@ impl CommandPoller {
 
     pub async fn arm_kill_switch(&self, reason: &str) {
         self.kill_switch.trigger();
+        self.skill_executor.stop_all_processes().await;
         self.audit_emitter
             .enqueue(FleetAuditEvent {
                 action_type: "fleet.kill_switch.armed".to_string(),
@@ -313,6 +419,7 @@ impl CommandPoller {
         agent_manager: Arc<AgentManager>,
         runtime_registry: Arc<RuntimeRegistry>,
         memory_manager: Arc<MemoryManager>,
+        rate_limiter: Arc<AgentRateLimiter>,
     ) {
         let mut lock = self.agent_context.write().await;
         *lock = Some(AgentRuntimeContext {
@@ -321,6 +428,7 @@ impl CommandPoller {
             agent_manager,
             runtime_registry,
             memory_manager,
+            rate_limiter,
         });
     }
 
@@ -862,7 +970,49 @@ impl CommandPoller {
 
                     // Create AgentRuntime on-demand
                     let context_lock = self.agent_context.read().await;
-                    if let Some(ctx) = context_lock.as_ref() {
+
+                    // Try to load spec from DB if agentId is present
+                    let loaded_spec = match (context_lock.as_ref(), &agent_id) {
+                        (Some(ctx), Some(id)) => ctx
+                            .agent_manager
+                            .get_agent_spec(id)
+                            .await
+                            .unwrap_or_else(|e| {
+                                eprintln!(
+                                    "[CommandPoller] Failed to load agent spec {}: {}",
+                                    id, e
+                                );
+                                None
+                            }),
+                        _ => None,
+                    };
+                    // Stored specs must satisfy the signature policy before they run.
+                    // Under `Require` the unsigned fallback spec built from the cloud
+                    // prompt below is refused too.
+                    let spec_rejection = {
+                        let settings = SettingsManager::new();
+                        match loaded_spec.as_ref() {
+                            Some(spec) => settings.verify_agent_spec(spec).err(),
+                            None if settings.get_spec_signature_policy()
+                                == SpecSignaturePolicy::Require =>
+                            {
+                                Some(
+                                    "No stored agent spec for this run. Signature policy requires a valid signature from a trusted signer."
+                                        .to_string(),
+                                )
+                            }
+                            None => None,
+                        }
+                    };
+
+                    if let Some(reason) = spec_rejection {
+                        CommandResult {
+                            success: false,
+                            output: None,
+                            error: Some(reason),
+                            exit_code: Some(1),
+                        }
+                    } else if let Some(ctx) = context_lock.as_ref() {
                         // Create memory for this workspace
                         let memory = Arc::new(
                             AgentMemory::new(
@@ -873,22 +1023,6 @@ impl CommandPoller {
                             .await,
                         );
 
-                        // Try to load spec from DB if agentId is present
-                        let loaded_spec = if let Some(id) = &agent_id {
-                            ctx.agent_manager
-                                .get_agent_spec(id)
-                                .await
-                                .unwrap_or_else(|e| {
-                                    eprintln!(
-                                        "[CommandPoller] Failed to load agent spec {}: {}",
-                                        id, e
-                                    );
-                                    None
-                                })
-                        } else {
-                            None
-                        };
-
                         use crate::ai::agent::runtime::RuntimeOptions;
                         use crate::ai::specs::manifest::AgentSpec;
                         use crate::ai::specs::skills::AgentSkills;
@@ -912,6 +1046,7 @@ impl CommandPoller {
                             // even if we load a local (potentially stale) spec.
                             custom_system_prompt: agent_system_prompt.clone(),
                             streaming_enabled: Some(false),
+                            run_id: Some(command.id.clone()),
                         };
 
                         // Create config
@@ -1003,7 +1138,8 @@ GUIDELINES:
                             Arc::new(airlock),
                             Some(self.kill_switch.clone()),
                             Some(ctx.runtime_registry.clone()),
-                        );
+                        )
+                        .with_rate_limiter(Some(ctx.rate_limiter.clone()));
 
                         // Run the agent with bounded event streaming to avoid ATM overload under heavy loops.
                         let neural_service = self.neural_service.clone();
@@ -1048,12 +1184,29 @@ GUIDELINES:
                             }
                         });
 
+                        let (output_tx, output_rx) = mpsc::unbounded_channel();
+                        let output_reporter = tokio::spawn(report_tool_output(
+                            neural_service.clone(),
+                            command_id.clone(),
+                            output_rx,
+                        ));
+
                         let callback_tx = progress_tx.clone();
                         let callback_dropped_events = dropped_events.clone();
                         let audit_emitter = self.audit_emitter.clone();
                         let audit_agent_id = agent_id.clone();
                         match runtime
                             .run(prompt, move |event| {
+                                // Tool output has its own batched channel so a
+                                // chatty build can't crowd out status events.
+                                if let AgentEvent::ToolOutputChunk { id, stream, text } = event {
+                                    let _ = output_tx.send(ToolOutputProgress {
+                                        tool_call_id: Some(id),
+                                        stream,
+                                        text,
+                                    });
+                                    return;
+                                }
                                 println!("[Agent Event] {:?}", event);
                                 let (message, data) = map_agent_event(&event);
                                 if callback_tx.try_send((message, data)).is_err() {
@@ -1140,12 +1293,20 @@ GUIDELINES:
                         {
                             Ok(response) => {
                                 drop(progress_tx);
+                                self.skill_executor.finish_run_checkpoint(&command.id).await;
+                                self.skill_executor.stop_run_processes(&command.id).await;
                                 if let Err(e) = reporter_handle.await {
                                     eprintln!(
                                         "[CommandPoller] Progress reporter join error for {}: {}",
                                         command.id, e
                                     );
                                 }
+                                if let Err(e) = output_reporter.await {
+                                    eprintln!(
+                                        "[CommandPoller] Tool output reporter join error for {}: {}",
+                                        command.id, e
+                                    );
+                                }
                                 CommandResult {
                                     success: true,
                                     output: Some(response),
@@ -1155,12 +1316,20 @@ GUIDELINES:
                             }
                             Err(e) => {
                                 drop(progress_tx);
+                                self.skill_executor.finish_run_checkpoint(&command.id).await;
+                                self.skill_executor.stop_run_processes(&command.id).await;
                                 if let Err(join_err) = reporter_handle.await {
                                     eprintln!(
                                         "[CommandPoller] Progress reporter join error for {}: {}",
                                         command.id, join_err
                                     );
                                 }
+                                if let Err(join_err) = output_reporter.await {
+                                    eprintln!(
+                                        "[CommandPoller] Tool output reporter join error for {}: {}",
+                                        command.id, join_err
+                                    );
+                                }
                                 CommandResult {
                                     success: false,
                                     output: None,
@@ -1191,7 +1360,30 @@ GUIDELINES:
             }
         } else {
             // Standard skill execution
-            self.skill_executor.execute(&command_for_execution).await
+            let (output_tx, output_rx) = mpsc::unbounded_channel();
+            let output_reporter = tokio::spawn(report_tool_output(
+                self.neural_service.clone(),
+                command.id.clone(),
+                output_rx,
+            ));
+            let sink: ToolOutputSink = Arc::new(move |stream, text| {
+                let _ = output_tx.send(ToolOutputProgress {
+                    tool_call_id: None,
+                    stream,
+                    text,
+                });
+            });
+            let result = self
+                .skill_executor
+                .execute_streaming(&command_for_execution, Some(sink))
+                .await;
+            if let Err(e) = output_reporter.await {
+                eprintln!(
+                    "[CommandPoller] Tool output reporter join error for {}: {}",
+                    command.id, e
+                );
+            }
+            result
         };
 
         if result.success {
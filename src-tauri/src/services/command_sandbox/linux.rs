@@ -0,0 +1,420 @@
+//! Linux backend: rlimits, a private user + network namespace, Landlock and a
+//! seccomp filter, entered by the forked child just before `exec`.
+//!
+//! Everything the child needs (the Landlock ruleset fd, the BPF program, the
+//! id-map contents) is built in the parent, so `enter` only makes raw syscalls.
+
+use super::{carve_out, SandboxConfig};
+use std::ffi::CString;
+use std::io;
+use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::{Path, PathBuf};
+
+const LANDLOCK_CREATE_RULESET_VERSION: libc::c_uint = 1;
+const LANDLOCK_RULE_PATH_BENEATH: libc::c_int = 1;
+
+const ACCESS_EXECUTE: u64 = 1 << 0;
+const ACCESS_WRITE_FILE: u64 = 1 << 1;
+const ACCESS_READ_FILE: u64 = 1 << 2;
+const ACCESS_READ_DIR: u64 = 1 << 3;
+const ACCESS_TRUNCATE: u64 = 1 << 14;
+const ACCESS_IOCTL_DEV: u64 = 1 << 15;
+const READ_ACCESS: u64 = ACCESS_EXECUTE | ACCESS_READ_FILE | ACCESS_READ_DIR;
+/// Rights that can be granted on a file rather than a directory.
+const FILE_ACCESS: u64 =
+    ACCESS_EXECUTE | ACCESS_WRITE_FILE | ACCESS_READ_FILE | ACCESS_TRUNCATE | ACCESS_IOCTL_DEV;
+
+/// Read-only locations toolchains need (binaries, libraries, config, procfs).
+const SYSTEM_READ_PATHS: &[&str] = &[
+    "/usr", "/bin", "/sbin", "/lib", "/lib32", "/lib64", "/etc", "/opt", "/nix", "/proc", "/sys",
+    "/run",
+];
+/// Writable scratch locations (`/dev` for `/dev/null`, ttys and `/dev/shm`).
+const SYSTEM_WRITE_PATHS: &[&str] = &["/tmp", "/dev"];
+
+#[cfg(target_arch = "x86_64")]
+const AUDIT_ARCH: Option<u32> = Some(0xC000_003E);
+#[cfg(target_arch = "aarch64")]
+const AUDIT_ARCH: Option<u32> = Some(0xC000_00B7);
+#[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+const AUDIT_ARCH: Option<u32> = None;
+
+/// Syscalls that fail with EPERM inside the sandbox: escaping or inspecting
+/// other processes, kernel and mount administration, and re-namespacing.
+#[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+const DENIED_SYSCALLS: &[libc::c_long] = &[
+    libc::SYS_ptrace,
+    libc::SYS_process_vm_readv,
+    libc::SYS_process_vm_writev,
+    libc::SYS_mount,
+    libc::SYS_umount2,
+    libc::SYS_pivot_root,
+    libc::SYS_chroot,
+    libc::SYS_setns,
+    libc::SYS_unshare,
+    libc::SYS_swapon,
+    libc::SYS_swapoff,
+    libc::SYS_reboot,
+    libc::SYS_kexec_load,
+    libc::SYS_kexec_file_load,
+    libc::SYS_init_module,
+    libc::SYS_finit_module,
+    libc::SYS_delete_module,
+    libc::SYS_bpf,
+    libc::SYS_perf_event_open,
+    libc::SYS_keyctl,
+    libc::SYS_add_key,
+    libc::SYS_request_key,
+    libc::SYS_userfaultfd,
+    libc::SYS_open_by_handle_at,
+    libc::SYS_acct,
+    libc::SYS_settimeofday,
+    libc::SYS_clock_settime,
+];
+#[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+const DENIED_SYSCALLS: &[libc::c_long] = &[];
+
+#[cfg(target_env = "gnu")]
+type RlimitResource = libc::__rlimit_resource_t;
+#[cfg(not(target_env = "gnu"))]
+type RlimitResource = libc::c_int;
+
+#[repr(C)]
+struct RulesetAttr {
+    handled_access_fs: u64,
+}
+
+#[repr(C, packed)]
+struct PathBeneathAttr {
+    allowed_access: u64,
+    parent_fd: libc::c_int,
+}
+
+/// Files written after `unshare` to map the caller's ids into the new user
+/// namespace, so file ownership looks unchanged to tools like git.
+struct IdMaps {
+    setgroups: CString,
+    uid_map: CString,
+    gid_map: CString,
+    uid_line: Vec<u8>,
+    gid_line: Vec<u8>,
+}
+
+pub(super) struct Confinement {
+    ruleset: OwnedFd,
+    filter: Vec<libc::sock_filter>,
+    rlimits: Vec<(RlimitResource, u64)>,
+    id_maps: Option<IdMaps>,
+}
+
+impl Confinement {
+    pub(super) fn prepare(
+        config: &SandboxConfig,
+        read_paths: &[PathBuf],
+        write_paths: &[PathBuf],
+        blocked_paths: &[PathBuf],
+    ) -> Result<Self, String> {
+        let abi = check_support(config)?;
+        let handled = handled_access(abi);
+        let ruleset = create_ruleset(handled)?;
+        let system = |paths: &[&str]| {
+            paths
+                .iter()
+                .flat_map(|path| carve_out(Path::new(path), blocked_paths))
+                .collect::<Vec<_>>()
+        };
+        for path in system(SYSTEM_READ_PATHS).iter().chain(read_paths) {
+            add_rule(&ruleset, path, READ_ACCESS)?;
+        }
+        for path in system(SYSTEM_WRITE_PATHS).iter().chain(write_paths) {
+            add_rule(&ruleset, path, handled)?;
+        }
+
+        let rlimits = [
+            (libc::RLIMIT_CPU, config.max_cpu_seconds),
+            (
+                libc::RLIMIT_AS,
+                config.max_memory_mb.saturating_mul(1024 * 1024),
+            ),
+            (libc::RLIMIT_NPROC, config.max_processes),
+        ]
+        .into_iter()
+        .filter(|(_, limit)| *limit > 0)
+        .collect();
+
+        let id_maps = if config.allow_network {
+            None
+        } else {
+            // SAFETY: getuid/getgid cannot fail.
+            let (uid, gid) = unsafe { (libc::getuid(), libc::getgid()) };
+            Some(IdMaps {
+                setgroups: CString::new("/proc/self/setgroups").unwrap(),
+                uid_map: CString::new("/proc/self/uid_map").unwrap(),
+                gid_map: CString::new("/proc/self/gid_map").unwrap(),
+                uid_line: format!("{} {} 1", uid, uid).into_bytes(),
+                gid_line: format!("{} {} 1", gid, gid).into_bytes(),
+            })
+        };
+
+        Ok(Self {
+            ruleset,
+            filter: seccomp_filter(),
+            rlimits,
+            id_maps,
+        })
+    }
+
+    /// Confine the calling process. Runs in the forked child, so it must not
+    /// allocate; errors carry only the errno.
+    pub(super) fn enter(&self) -> io::Result<()> {
+        // SAFETY: every call below is a plain syscall on memory owned by
+        // `self`, which outlives the call.
+        unsafe {
+            for (resource, limit) in &self.rlimits {
+                let rlimit = libc::rlimit {
+                    rlim_cur: *limit as libc::rlim_t,
+                    rlim_max: *limit as libc::rlim_t,
+                };
+                check(libc::setrlimit(*resource, &rlimit))?;
+            }
+
+            if let Some(maps) = &self.id_maps {
+                check(libc::unshare(libc::CLONE_NEWUSER | libc::CLONE_NEWNET))?;
+                write_file(&maps.setgroups, b"deny")?;
+                write_file(&maps.uid_map, &maps.uid_line)?;
+                write_file(&maps.gid_map, &maps.gid_line)?;
+            }
+
+            check(libc::prctl(libc::PR_SET_NO_NEW_PRIVS, 1, 0, 0, 0))?;
+            check(libc::syscall(
+                libc::SYS_landlock_restrict_self,
+                self.ruleset.as_raw_fd(),
+                0,
+            ) as libc::c_int)?;
+
+            let program = libc::sock_fprog {
+                len: self.filter.len() as libc::c_ushort,
+                filter: self.filter.as_ptr() as *mut libc::sock_filter,
+            };
+            check(libc::prctl(
+                libc::PR_SET_SECCOMP,
+                libc::SECCOMP_MODE_FILTER,
+                &program as *const libc::sock_fprog,
+            ))?;
+        }
+        Ok(())
+    }
+}
+
+/// Probe the kernel for everything `config` needs; returns the Landlock ABI.
+pub(super) fn check_support(config: &SandboxConfig) -> Result<i64, String> {
+    // SAFETY: the version query takes no attribute pointer.
+    let abi = unsafe {
+        libc::syscall(
+            libc::SYS_landlock_create_ruleset,
+            std::ptr::null::<RulesetAttr>(),
+            0usize,
+            LANDLOCK_CREATE_RULESET_VERSION,
+        )
+    };
+    if abi < 1 {
+        let error = io::Error::last_os_error();
+        return Err(match error.raw_os_error() {
+            Some(libc::ENOSYS) => {
+                "This kernel does not support Landlock (Linux 5.13 or newer is required)"
+                    .to_string()
+            }
+            Some(libc::EOPNOTSUPP) => {
+                "Landlock is disabled on this system; add `landlock` to the kernel's `lsm=` boot parameter"
+                    .to_string()
+            }
+            _ => format!("Landlock is unavailable: {}", error),
+        });
+    }
+
+    if AUDIT_ARCH.is_none() {
+        return Err(
+            "The sandbox's seccomp filter does not support this CPU architecture".to_string(),
+        );
+    }
+    // SAFETY: PR_GET_SECCOMP takes no arguments.
+    if unsafe { libc::prctl(libc::PR_GET_SECCOMP) } < 0 {
+        return Err("This kernel was built without seccomp support".to_string());
+    }
+
+    if !config.allow_network {
+        check_user_namespaces()?;
+    }
+    Ok(abi)
+}
+
+/// Network isolation needs an unprivileged user namespace to create the
+/// network namespace in.
+fn check_user_namespaces() -> Result<(), String> {
+    if !Path::new("/proc/self/ns/user").exists() {
+        return Err(
+            "This kernel does not support user namespaces, which network isolation requires"
+                .to_string(),
+        );
+    }
+    let knobs = [
+        ("/proc/sys/user/max_user_namespaces", "0"),
+        ("/proc/sys/kernel/unprivileged_userns_clone", "0"),
+        (
+            "/proc/sys/kernel/apparmor_restrict_unprivileged_userns",
+            "1",
+        ),
+    ];
+    for (knob, disabled) in knobs {
+        if std::fs::read_to_string(knob).is_ok_and(|value| value.trim() == disabled) {
+            return Err(format!(
+                "Unprivileged user namespaces, which network isolation requires, are disabled by {} (or allow network access for this workspace)",
+                knob
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Every filesystem right the kernel's Landlock ABI knows about.
+fn handled_access(abi: i64) -> u64 {
+    match abi {
+        1 => (1 << 13) - 1,
+        2 => (1 << 14) - 1,
+        3 | 4 => (1 << 15) - 1,
+        _ => (1 << 16) - 1,
+    }
+}
+
+fn create_ruleset(handled_access_fs: u64) -> Result<OwnedFd, String> {
+    let attr = RulesetAttr { handled_access_fs };
+    // SAFETY: `attr` is a valid ruleset attribute of the size passed.
+    let fd = unsafe {
+        libc::syscall(
+            libc::SYS_landlock_create_ruleset,
+            &attr as *const RulesetAttr,
+            std::mem::size_of::<RulesetAttr>(),
+            0,
+        )
+    };
+    if fd < 0 {
+        return Err(format!(
+            "Failed to create Landlock ruleset: {}",
+            io::Error::last_os_error()
+        ));
+    }
+    // SAFETY: the syscall returned a new fd we exclusively own.
+    Ok(unsafe { OwnedFd::from_raw_fd(fd as libc::c_int) })
+}
+
+/// Grant `access` beneath `path`; missing paths are skipped.
+fn add_rule(ruleset: &OwnedFd, path: &Path, access: u64) -> Result<(), String> {
+    let file = match std::fs::File::options()
+        .read(true)
+        .custom_flags(libc::O_PATH)
+        .open(path)
+    {
+        Ok(file) => file,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => {
+            return Err(format!(
+                "Cannot open sandbox path {}: {}",
+                path.display(),
+                e
+            ))
+        }
+    };
+    let is_dir = file.metadata().map(|m| m.is_dir()).unwrap_or(false);
+    let attr = PathBeneathAttr {
+        allowed_access: if is_dir { access } else { access & FILE_ACCESS },
+        parent_fd: file.as_raw_fd(),
+    };
+    // SAFETY: `attr` points at a valid path-beneath rule and `file` is open.
+    let result = unsafe {
+        libc::syscall(
+            libc::SYS_landlock_add_rule,
+            ruleset.as_raw_fd(),
+            LANDLOCK_RULE_PATH_BENEATH,
+            &attr as *const PathBeneathAttr,
+            0,
+        )
+    };
+    if result < 0 {
+        return Err(format!(
+            "Failed to add sandbox rule for {}: {}",
+            path.display(),
+            io::Error::last_os_error()
+        ));
+    }
+    Ok(())
+}
+
+/// Kill on a foreign architecture, fail the denied syscalls with EPERM and
+/// allow everything else.
+fn seccomp_filter() -> Vec<libc::sock_filter> {
+    const LOAD: u32 = libc::BPF_LD | libc::BPF_W | libc::BPF_ABS;
+    const JEQ: u32 = libc::BPF_JMP | libc::BPF_JEQ | libc::BPF_K;
+    const RET: u32 = libc::BPF_RET | libc::BPF_K;
+    // Offsets into `struct seccomp_data`.
+    const NR_OFFSET: u32 = 0;
+    const ARCH_OFFSET: u32 = 4;
+    let deny = libc::SECCOMP_RET_ERRNO | libc::EPERM as u32;
+
+    let mut filter = vec![
+        statement(LOAD, ARCH_OFFSET),
+        jump(JEQ, AUDIT_ARCH.unwrap_or(0), 1, 0),
+        statement(RET, libc::SECCOMP_RET_KILL_PROCESS),
+        statement(LOAD, NR_OFFSET),
+    ];
+    // x32 syscalls reach the same handlers under numbers with this bit set.
+    #[cfg(target_arch = "x86_64")]
+    {
+        filter.push(jump(
+            libc::BPF_JMP | libc::BPF_JGE | libc::BPF_K,
+            0x4000_0000,
+            0,
+            1,
+        ));
+        filter.push(statement(RET, deny));
+    }
+    for nr in DENIED_SYSCALLS {
+        filter.push(jump(JEQ, *nr as u32, 0, 1));
+        filter.push(statement(RET, deny));
+    }
+    filter.push(statement(RET, libc::SECCOMP_RET_ALLOW));
+    filter
+}
+
+fn statement(code: u32, k: u32) -> libc::sock_filter {
+    jump(code, k, 0, 0)
+}
+
+fn jump(code: u32, k: u32, jt: u8, jf: u8) -> libc::sock_filter {
+    libc::sock_filter {
+        code: code as u16,
+        jt,
+        jf,
+        k,
+    }
+}
+
+fn check(result: libc::c_int) -> io::Result<()> {
+    if result < 0 {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(())
+    }
+}
+
+unsafe fn write_file(path: &CString, contents: &[u8]) -> io::Result<()> {
+    let fd = libc::open(path.as_ptr(), libc::O_WRONLY | libc::O_CLOEXEC);
+    check(fd)?;
+    let written = libc::write(fd, contents.as_ptr().cast(), contents.len());
+    let error = io::Error::last_os_error();
+    libc::close(fd);
+    if written != contents.len() as isize {
+        return Err(error);
+    }
+    Ok(())
+}
@@ -0,0 +1,315 @@
+//! OS-level confinement for `execute_command`
+//!
+//! The command policy decides which commands may run; the sandbox limits what
+//! an allowed command, and any build or lifecycle script it starts, can reach.
+//! It is opt-in per workspace. The only backend today is Linux (Landlock,
+//! seccomp, a private network namespace and rlimits); selecting it on a kernel
+//! that lacks one of those fails with an error instead of running unconfined.
+
+#[cfg(target_os = "linux")]
+mod linux;
+
+use crate::services::skill_executor::SkillExecutor;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+#[cfg(not(target_os = "linux"))]
+const LINUX_ONLY: &str = "The Linux sandbox is only available on Linux";
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SandboxBackend {
+    /// Commands run with the app's privileges.
+    #[default]
+    None,
+    Linux,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct SandboxConfig {
+    pub backend: SandboxBackend,
+    /// Keep network access; otherwise commands run in an empty network namespace.
+    pub allow_network: bool,
+    /// Readable (and executable) paths besides the system directories. `~/` is
+    /// expanded; missing paths are skipped.
+    pub read_paths: Vec<String>,
+    /// Writable paths besides the workspace, e.g. package manager caches.
+    pub write_paths: Vec<String>,
+    /// CPU seconds per process; `0` is unlimited.
+    pub max_cpu_seconds: u64,
+    /// Address space per process in MiB; `0` is unlimited.
+    pub max_memory_mb: u64,
+    /// Processes the user may own (counted by the kernel across the user's
+    /// processes, not just the command's); `0` is unlimited.
+    pub max_processes: u64,
+}
+
+impl Default for SandboxConfig {
+    fn default() -> Self {
+        Self {
+            backend: SandboxBackend::None,
+            allow_network: false,
+            read_paths: vec![
+                "~/.rustup".to_string(),
+                "~/.nvm".to_string(),
+                "~/.bun".to_string(),
+                "~/.local/bin".to_string(),
+                "~/.gitconfig".to_string(),
+            ],
+            write_paths: vec![
+                "~/.cargo".to_string(),
+                "~/.npm".to_string(),
+                "~/.cache".to_string(),
+            ],
+            max_cpu_seconds: 600,
+            max_memory_mb: 8192,
+            max_processes: 4096,
+        }
+    }
+}
+
+/// A confinement prepared for one command, applied when it is spawned.
+pub struct CommandSandbox {
+    #[cfg(target_os = "linux")]
+    confinement: linux::Confinement,
+}
+
+impl CommandSandbox {
+    /// Prepare a workspace's sandbox, or `None` when `config` selects none.
+    /// `writable_roots` are the workspace directories the command may modify;
+    /// nothing under `blocked_paths` is made accessible.
+    pub fn prepare(
+        config: &SandboxConfig,
+        writable_roots: &[PathBuf],
+        blocked_paths: &[String],
+    ) -> Result<Option<Self>, String> {
+        if config.backend == SandboxBackend::None {
+            return Ok(None);
+        }
+
+        // Relative entries are blocked under every writable root, as in the
+        // file tools.
+        let blocked = blocked_paths
+            .iter()
+            .map(|path| expand_home(path).to_string_lossy().into_owned())
+            .collect::<Vec<_>>();
+        let blocked = SkillExecutor::absolute_blocked_paths(&blocked, writable_roots);
+        let read = config
+            .read_paths
+            .iter()
+            .flat_map(|path| carve_out(&expand_home(path), &blocked))
+            .collect::<Vec<_>>();
+        let write = writable_roots
+            .iter()
+            .cloned()
+            .chain(config.write_paths.iter().map(|path| expand_home(path)))
+            .flat_map(|path| carve_out(&path, &blocked))
+            .collect::<Vec<_>>();
+
+        #[cfg(target_os = "linux")]
+        {
+            Ok(Some(Self {
+                confinement: linux::Confinement::prepare(config, &read, &write, &blocked)?,
+            }))
+        }
+        #[cfg(not(target_os = "linux"))]
+        {
+            let _ = (read, write, blocked);
+            Err(LINUX_ONLY.to_string())
+        }
+    }
+
+    /// Install the confinement in `command`'s child process before it execs.
+    pub fn apply(self, command: &mut tokio::process::Command) {
+        #[cfg(target_os = "linux")]
+        {
+            let confinement = self.confinement;
+            // SAFETY: `enter` only makes raw syscalls on data prepared in the
+            // parent; it neither allocates nor takes locks.
+            unsafe {
+                command.pre_exec(move || confinement.enter());
+            }
+        }
+        #[cfg(not(target_os = "linux"))]
+        let _ = command;
+    }
+}
+
+/// Whether `config`'s backend can run here, with the reason when it can't.
+pub fn check_support(config: &SandboxConfig) -> Result<(), String> {
+    match config.backend {
+        SandboxBackend::None => Ok(()),
+        #[cfg(target_os = "linux")]
+        SandboxBackend::Linux => linux::check_support(config).map(|_| ()),
+        #[cfg(not(target_os = "linux"))]
+        SandboxBackend::Linux => Err(LINUX_ONLY.to_string()),
+    }
+}
+
+fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), dirs::home_dir()) {
+        (Some(rest), Some(home)) => home.join(rest),
+        _ => PathBuf::from(path),
+    }
+}
+
+/// Split `root` into the paths that cover everything under it except the
+/// blocked ones: Landlock can only grant access, so a blocked subdirectory is
+/// excluded by granting its siblings (and its ancestors' siblings) instead.
+/// Entries created later directly inside a split directory stay inaccessible.
+fn carve_out(root: &Path, blocked: &[PathBuf]) -> Vec<PathBuf> {
+    if blocked.iter().any(|b| root.starts_with(b)) {
+        return Vec::new();
+    }
+    if !blocked.iter().any(|b| b.starts_with(root)) {
+        return vec![root.to_path_buf()];
+    }
+    let Ok(entries) = std::fs::read_dir(root) else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .flat_map(|entry| carve_out(&entry.path(), blocked))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn carve_out_excludes_blocked_subtrees() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        for sub in ["src", "secrets/keys", "secrets/public"] {
+            std::fs::create_dir_all(root.join(sub)).unwrap();
+        }
+        std::fs::write(root.join("Cargo.toml"), "").unwrap();
+
+        let mut paths = carve_out(root, &[root.join("secrets/keys")]);
+        paths.sort();
+        assert_eq!(
+            paths,
+            vec![
+                root.join("Cargo.toml"),
+                root.join("secrets/public"),
+                root.join("src"),
+            ]
+        );
+        assert_eq!(carve_out(root, &[]), vec![root.to_path_buf()]);
+        assert!(carve_out(&root.join("secrets/keys"), &[root.join("secrets")]).is_empty());
+    }
+
+    #[cfg(target_os = "linux")]
+    async fn run_sandboxed(
+        config: &SandboxConfig,
+        workspace: &Path,
+        blocked_paths: &[String],
+        program: &str,
+        args: &[&str],
+    ) -> std::process::Output {
+        let sandbox = CommandSandbox::prepare(config, &[workspace.to_path_buf()], blocked_paths)
+            .unwrap()
+            .unwrap();
+        let mut command = tokio::process::Command::new(program);
+        command.args(args).current_dir(workspace);
+        sandbox.apply(&mut command);
+        command.output().await.unwrap()
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn linux_sandbox_confines_files_and_network() {
+        let config = SandboxConfig {
+            backend: SandboxBackend::Linux,
+            read_paths: Vec::new(),
+            write_paths: Vec::new(),
+            ..SandboxConfig::default()
+        };
+        if let Err(reason) = check_support(&config) {
+            println!("Skipping sandbox test: {}", reason);
+            return;
+        }
+
+        // Not under /tmp, which sandboxed commands may always write to.
+        let root = tempfile::tempdir_in(env!("CARGO_MANIFEST_DIR")).unwrap();
+        let workspace = root.path().join("workspace");
+        std::fs::create_dir(&workspace).unwrap();
+        std::fs::write(workspace.join("notes.txt"), "notes").unwrap();
+        let secret = root.path().join("secret.txt");
+        std::fs::write(&secret, "secret").unwrap();
+
+        let inside = workspace.join("notes.txt");
+        let output =
+            run_sandboxed(&config, &workspace, &[], "cat", &[inside.to_str().unwrap()]).await;
+        assert!(output.status.success());
+        assert_eq!(output.stdout, b"notes");
+
+        let output =
+            run_sandboxed(&config, &workspace, &[], "cat", &[secret.to_str().unwrap()]).await;
+        assert!(!output.status.success());
+        assert!(output.stdout.is_empty());
+
+        let escape = root.path().join("escape.txt");
+        let script = format!("echo x > '{}'", escape.display());
+        let output = run_sandboxed(&config, &workspace, &[], "sh", &["-c", &script]).await;
+        assert!(!output.status.success());
+        assert!(!escape.exists());
+
+        // The listener is reachable from the host's network namespace only.
+        if Path::new("/bin/bash").exists() {
+            let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+            let port = listener.local_addr().unwrap().port();
+            let script = format!("exec 3<>/dev/tcp/127.0.0.1/{}", port);
+            let output =
+                run_sandboxed(&config, &workspace, &[], "/bin/bash", &["-c", &script]).await;
+            assert!(!output.status.success());
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn linux_sandbox_resolves_relative_blocked_paths_in_the_workspace() {
+        let config = SandboxConfig {
+            backend: SandboxBackend::Linux,
+            read_paths: Vec::new(),
+            write_paths: Vec::new(),
+            ..SandboxConfig::default()
+        };
+        if let Err(reason) = check_support(&config) {
+            println!("Skipping sandbox test: {}", reason);
+            return;
+        }
+
+        let root = tempfile::tempdir_in(env!("CARGO_MANIFEST_DIR")).unwrap();
+        let workspace = root.path().to_path_buf();
+        std::fs::create_dir(workspace.join("private")).unwrap();
+        std::fs::write(workspace.join("private/key.txt"), "secret").unwrap();
+        std::fs::write(workspace.join("notes.txt"), "notes").unwrap();
+        let blocked = vec!["private".to_string()];
+
+        let notes = workspace.join("notes.txt");
+        let output = run_sandboxed(
+            &config,
+            &workspace,
+            &blocked,
+            "cat",
+            &[notes.to_str().unwrap()],
+        )
+        .await;
+        assert!(output.status.success());
+
+        let key = workspace.join("private/key.txt");
+        let output = run_sandboxed(
+            &config,
+            &workspace,
+            &blocked,
+            "cat",
+            &[key.to_str().unwrap()],
+        )
+        .await;
+        assert!(!output.status.success());
+        assert!(output.stdout.is_empty());
+    }
+}
@@ -1,7 +1,20 @@
-use crate::services::memory_vault::profiles::FALLBACK_EMBEDDING_PROFILE;
-use reqwest::Client;
-use serde::{Deserialize, Serialize};
-use serde_json::Value;
+mod gemini;
+mod local;
+mod openai_compat;
+
+pub use gemini::GeminiEmbedder;
+pub use local::LocalEmbedder;
+pub use openai_compat::OpenAiCompatibleEmbedder;
+
+use crate::services::memory_vault::profiles::{
+    EmbeddingProfile, GEMINI_EMBEDDING_001, GEMINI_PROVIDER, LOCAL_PROVIDER,
+    OPENAI_COMPATIBLE_PROVIDER, VECTOR_COLUMN_DIM,
+};
+use crate::services::settings::SettingsManager;
+use async_trait::async_trait;
+use std::sync::Arc;
+
+const DEFAULT_OPENAI_EMBEDDING_MODEL: &str = "text-embedding-3-small";
 
 #[derive(Debug, Clone, Copy)]
 pub enum EmbeddingTaskType {
@@ -18,282 +31,166 @@ impl EmbeddingTaskType {
     }
 }
 
-#[derive(Debug, Serialize)]
-#[serde(rename_all = "camelCase")]
-struct GeminiEmbeddingRequest {
-    model: String,
-    content: GeminiContent,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    task_type: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    output_dimensionality: Option<u32>,
-}
-
-#[derive(Debug, Serialize)]
-struct GeminiContent {
-    parts: Vec<GeminiPart>,
-}
-
-#[derive(Debug, Serialize)]
-struct GeminiPart {
-    text: String,
-}
-
-#[derive(Debug, Deserialize)]
-struct GeminiEmbeddingResponse {
-    embedding: GeminiEmbeddingData,
-}
-
-#[derive(Debug, Serialize)]
-#[serde(rename_all = "camelCase")]
-struct GeminiBatchEmbeddingRequest {
-    requests: Vec<GeminiEmbeddingRequest>,
-}
-
-#[derive(Debug, Deserialize)]
-struct GeminiEmbeddingData {
-    values: Vec<f32>,
-}
-
-#[derive(Debug)]
-pub struct EmbedderService {
-    client: Client,
-    provider: String,
-    api_key: String,
-    model: String,
-}
-
-impl EmbedderService {
-    pub fn new(provider: String, api_key: String, model: Option<String>) -> Self {
-        let normalized_provider = match provider.trim().to_lowercase().as_str() {
-            "g" | "google" | "gemini" => "gemini".to_string(),
-            // Step 3 HIVE MIND SEED production path is Gemini-only for memory embeddings.
-            _ => "gemini".to_string(),
-        };
+/// Turns text into vectors for one embedding profile.
+#[async_trait]
+pub trait Embedder: Send + Sync + std::fmt::Debug {
+    fn profile(&self) -> &EmbeddingProfile;
 
-        let default_model = crate::services::memory_vault::types::EMBEDDING_MODEL.to_string();
-
-        let selected_model = model.unwrap_or(default_model);
-        let normalized_model = match selected_model.trim() {
-            "gemini-embedding-2-preview" | "gemini-embedding-001" => selected_model,
-            "text-embedding-004"
-            | "embedding-001"
-            | "embedding-gecko-001"
-            | "gemini-embedding-exp"
-            | "gemini-embedding-exp-03-07" => "gemini-embedding-001".to_string(),
-            _ => crate::services::memory_vault::types::EMBEDDING_MODEL.to_string(),
-        };
-
-        Self {
-            client: Client::new(),
-            provider: normalized_provider,
-            api_key,
-            model: normalized_model,
-        }
-    }
-
-    pub async fn embed_text_with_task(
+    /// One vector per input text, each `profile().dim` long.
+    async fn embed_batch(
         &self,
-        text: &str,
+        texts: &[String],
         task_type: EmbeddingTaskType,
-    ) -> Result<Vec<f32>, String> {
-        if self.api_key.is_empty() {
-            return Err(format!(
-                "Missing embedding API key for provider: {}",
-                self.provider
-            ));
-        }
-
-        match self.embed_gemini(text, &self.model, task_type).await {
-            Ok(v) => Ok(v),
-            Err(primary_error) => {
-                if self.model == FALLBACK_EMBEDDING_PROFILE.model {
-                    return Err(primary_error);
-                }
+    ) -> Result<Vec<Vec<f32>>, String>;
 
-                self.embed_gemini(text, FALLBACK_EMBEDDING_PROFILE.model, task_type)
-                    .await
-                    .map_err(|fallback_error| {
-                        format!(
-                            "Embedding failed for '{}' and fallback '{}': {} | {}",
-                            self.model, FALLBACK_EMBEDDING_PROFILE.model, primary_error, fallback_error
-                        )
-                    })
-            }
-        }
+    async fn embed(&self, text: &str, task_type: EmbeddingTaskType) -> Result<Vec<f32>, String> {
+        self.embed_batch(&[text.to_string()], task_type)
+            .await?
+            .pop()
+            .ok_or_else(|| format!("{} returned no embedding", self.profile().label()))
     }
 
-    pub async fn embed_text_for_model_with_task_strict(
-        &self,
-        text: &str,
-        model: &str,
-        task_type: EmbeddingTaskType,
-    ) -> Result<Vec<f32>, String> {
-        if self.api_key.is_empty() {
-            return Err(format!(
-                "Missing embedding API key for provider: {}",
-                self.provider
-            ));
-        }
-        self.embed_gemini(text, model, task_type).await
+    /// A second profile embedded alongside this one during ingestion, so
+    /// search can fall back to it.
+    fn fallback(&self) -> Option<Arc<dyn Embedder>> {
+        None
     }
+}
 
-    pub async fn embed_texts_for_model_with_task(
-        &self,
-        texts: &[String],
-        model: &str,
-        task_type: EmbeddingTaskType,
-    ) -> Result<Vec<Vec<f32>>, String> {
-        if texts.is_empty() {
-            return Ok(Vec::new());
-        }
-        if self.api_key.is_empty() {
-            return Err(format!(
-                "Missing embedding API key for provider: {}",
-                self.provider
-            ));
-        }
-        self.embed_gemini_batch(texts, model, task_type).await
+/// Canonical provider id for a settings value, or `None` if unsupported.
+pub fn normalize_embedder_provider(provider: &str) -> Option<&'static str> {
+    match provider.trim().to_lowercase().as_str() {
+        "g" | "google" | "gemini" => Some(GEMINI_PROVIDER),
+        "openai" | "openai-compatible" | "openai_compatible" => Some(OPENAI_COMPATIBLE_PROVIDER),
+        "local" | "offline" => Some(LOCAL_PROVIDER),
+        _ => None,
     }
+}
 
-    async fn embed_gemini(
-        &self,
-        text: &str,
-        model: &str,
-        task_type: EmbeddingTaskType,
-    ) -> Result<Vec<f32>, String> {
-        let req_body = GeminiEmbeddingRequest {
-            model: format!("models/{}", model),
-            content: GeminiContent {
-                parts: vec![GeminiPart {
-                    text: text.to_string(),
-                }],
-            },
-            task_type: Some(task_type.as_api_value().to_string()),
-            output_dimensionality: Some(crate::services::memory_vault::types::EMBEDDING_DIM as u32),
-        };
-
-        let url = format!(
-            "https://generativelanguage.googleapis.com/v1beta/models/{}:embedContent",
-            model
-        );
-
-        let res = self
-            .client
-            .post(&url)
-            .header("x-goog-api-key", &self.api_key)
-            .json(&req_body)
-            .send()
-            .await
-            .map_err(|e| format!("Gemini embedding request failed: {}", e))?;
-
-        if !res.status().is_success() {
-            let status = res.status();
-            let text_err = res.text().await.unwrap_or_default();
-            return Err(format!(
-                "Gemini embedding API error: {} - {}",
-                status, text_err
-            ));
-        }
-
-        let parsed: GeminiEmbeddingResponse = res
-            .json()
-            .await
-            .map_err(|e| format!("Parsing Gemini embedding response failed: {}", e))?;
-
-        Ok(parsed.embedding.values)
+/// Model a provider switches to when the current model belongs elsewhere.
+pub fn default_embedder_model(provider: &str) -> &'static str {
+    match normalize_embedder_provider(provider) {
+        Some(OPENAI_COMPATIBLE_PROVIDER) => DEFAULT_OPENAI_EMBEDDING_MODEL,
+        Some(LOCAL_PROVIDER) => crate::services::memory_vault::profiles::LOCAL_HASH_EMBEDDING,
+        _ => crate::services::memory_vault::profiles::GEMINI_EMBEDDING_2_PREVIEW,
     }
+}
 
-    async fn embed_gemini_batch(
-        &self,
-        texts: &[String],
-        model: &str,
-        task_type: EmbeddingTaskType,
-    ) -> Result<Vec<Vec<f32>>, String> {
-        let requests = texts
-            .iter()
-            .map(|text| GeminiEmbeddingRequest {
-                model: format!("models/{}", model),
-                content: GeminiContent {
-                    parts: vec![GeminiPart {
-                        text: text.to_string(),
-                    }],
-                },
-                task_type: Some(task_type.as_api_value().to_string()),
-                output_dimensionality: Some(crate::services::memory_vault::types::EMBEDDING_DIM as u32),
-            })
-            .collect::<Vec<_>>();
-
-        let req_body = GeminiBatchEmbeddingRequest { requests };
-
-        let url = format!(
-            "https://generativelanguage.googleapis.com/v1beta/models/{}:batchEmbedContents",
-            model
-        );
-
-        let res = self
-            .client
-            .post(&url)
-            .header("x-goog-api-key", &self.api_key)
-            .json(&req_body)
-            .send()
-            .await
-            .map_err(|e| format!("Gemini batch embedding request failed: {}", e))?;
-
-        if !res.status().is_success() {
-            let status = res.status();
-            let text_err = res.text().await.unwrap_or_default();
-            return Err(format!(
-                "Gemini batch embedding API error: {} - {}",
-                status, text_err
-            ));
-        }
-
-        let value: Value = res
-            .json()
-            .await
-            .map_err(|e| format!("Parsing Gemini batch embedding response failed: {}", e))?;
+fn known_openai_dim(model: &str) -> Option<usize> {
+    match model {
+        "text-embedding-3-small" | "text-embedding-ada-002" => Some(1536),
+        "text-embedding-3-large" => Some(3072),
+        "nomic-embed-text" => Some(768),
+        "mxbai-embed-large" => Some(1024),
+        "all-minilm" => Some(384),
+        _ => None,
+    }
+}
 
-        let mut embeddings = Vec::new();
-        if let Some(items) = value.get("embeddings").and_then(|v| v.as_array()) {
-            for item in items {
-                if let Some(vals) = parse_embedding_values(item) {
-                    embeddings.push(vals);
-                }
-            }
-        } else if let Some(items) = value.get("responses").and_then(|v| v.as_array()) {
-            for item in items {
-                if let Some(vals) = item.get("embedding").and_then(parse_embedding_values) {
-                    embeddings.push(vals);
-                }
+/// The embedding profile selected in settings.
+pub fn embedding_profile_from_settings(
+    settings: &SettingsManager,
+) -> Result<EmbeddingProfile, String> {
+    let provider_raw = settings.get_embedder_provider();
+    let model = settings.get_embedder_model().trim();
+    let profile = match normalize_embedder_provider(provider_raw) {
+        Some(GEMINI_PROVIDER) => {
+            if model == GEMINI_EMBEDDING_001 {
+                EmbeddingProfile::gemini_fallback()
+            } else {
+                EmbeddingProfile::gemini()
             }
         }
-
-        if embeddings.len() != texts.len() {
-            return Err(format!(
-                "Gemini batch embedding size mismatch: expected {}, got {}",
-                texts.len(),
-                embeddings.len()
-            ));
+        Some(OPENAI_COMPATIBLE_PROVIDER) => {
+            let model = if model.is_empty() || model.starts_with("gemini-") {
+                DEFAULT_OPENAI_EMBEDDING_MODEL
+            } else {
+                model
+            };
+            let dim = settings
+                .get_embedder_dimensions()
+                .or_else(|| known_openai_dim(model))
+                .ok_or_else(|| {
+                    format!(
+                        "Unknown vector size for embedding model '{}'; set embedder dimensions",
+                        model
+                    )
+                })?;
+            EmbeddingProfile::new(OPENAI_COMPATIBLE_PROVIDER, model, dim)
         }
+        Some(_) => EmbeddingProfile::local(),
+        None => return Err(format!("Unsupported embedding provider '{}'", provider_raw)),
+    };
 
-        Ok(embeddings)
+    if profile.dim == 0 || profile.dim > VECTOR_COLUMN_DIM {
+        return Err(format!(
+            "Embedding size {} for '{}' is outside 1..={}",
+            profile.dim,
+            profile.label(),
+            VECTOR_COLUMN_DIM
+        ));
     }
+    Ok(profile)
 }
 
-fn parse_embedding_values(value: &Value) -> Option<Vec<f32>> {
-    let values = if let Some(v) = value.get("values") {
-        v
-    } else if let Some(v) = value.get("embedding").and_then(|emb| emb.get("values")) {
-        v
-    } else {
-        return None;
+/// Build the embedder selected in settings. `Ok(None)` when the provider
+/// needs an API key that isn't configured.
+pub fn embedder_from_settings(
+    settings: &SettingsManager,
+) -> Result<Option<Arc<dyn Embedder>>, String> {
+    let profile = embedding_profile_from_settings(settings)?;
+    let keychain = crate::ai::keychain::KeychainManager::new();
+    let embedder: Arc<dyn Embedder> = match profile.provider.as_str() {
+        GEMINI_PROVIDER => {
+            let api_key = keychain
+                .get_key(GEMINI_PROVIDER)
+                .or_else(|_| keychain.get_key(settings.get_embedder_provider()))
+                .unwrap_or_default()
+                .unwrap_or_default();
+            if api_key.trim().is_empty() {
+                return Ok(None);
+            }
+            Arc::new(GeminiEmbedder::new(api_key, profile))
+        }
+        OPENAI_COMPATIBLE_PROVIDER => {
+            // Local servers (Ollama, LM Studio) accept requests without a key.
+            let api_key = keychain
+                .get_key(OPENAI_COMPATIBLE_PROVIDER)
+                .unwrap_or_default()
+                .unwrap_or_default();
+            let explicit_dim = settings.get_embedder_dimensions().is_some();
+            Arc::new(OpenAiCompatibleEmbedder::new(
+                settings.get_embedder_base_url(),
+                api_key,
+                profile,
+                explicit_dim,
+            ))
+        }
+        _ => Arc::new(LocalEmbedder::new(profile.dim)),
     };
+    Ok(Some(embedder))
+}
 
-    let arr = values.as_array()?;
-    let mut out = Vec::with_capacity(arr.len());
-    for n in arr {
-        out.push(n.as_f64()? as f32);
+fn ensure_dims(
+    profile: &EmbeddingProfile,
+    expected: usize,
+    vectors: Vec<Vec<f32>>,
+) -> Result<Vec<Vec<f32>>, String> {
+    if vectors.len() != expected {
+        return Err(format!(
+            "{} returned {} embeddings for {} inputs",
+            profile.label(),
+            vectors.len(),
+            expected
+        ));
+    }
+    if let Some(bad) = vectors.iter().find(|v| v.len() != profile.dim) {
+        return Err(format!(
+            "{} returned a {}-dimensional embedding (expected {})",
+            profile.label(),
+            bad.len(),
+            profile.dim
+        ));
     }
-    Some(out)
+    Ok(vectors)
 }
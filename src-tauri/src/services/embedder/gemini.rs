@@ -0,0 +1,222 @@
+use super::{ensure_dims, Embedder, EmbeddingTaskType};
+use crate::services::memory_vault::profiles::EmbeddingProfile;
+use async_trait::async_trait;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::sync::Arc;
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct GeminiEmbeddingRequest {
+    model: String,
+    content: GeminiContent,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    task_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    output_dimensionality: Option<u32>,
+}
+
+#[derive(Debug, Serialize)]
+struct GeminiContent {
+    parts: Vec<GeminiPart>,
+}
+
+#[derive(Debug, Serialize)]
+struct GeminiPart {
+    text: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct GeminiEmbeddingResponse {
+    embedding: GeminiEmbeddingData,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct GeminiBatchEmbeddingRequest {
+    requests: Vec<GeminiEmbeddingRequest>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GeminiEmbeddingData {
+    values: Vec<f32>,
+}
+
+/// Gemini `embedContent` / `batchEmbedContents`.
+#[derive(Debug, Clone)]
+pub struct GeminiEmbedder {
+    client: Client,
+    api_key: String,
+    profile: EmbeddingProfile,
+}
+
+impl GeminiEmbedder {
+    pub fn new(api_key: String, profile: EmbeddingProfile) -> Self {
+        Self {
+            client: Client::new(),
+            api_key,
+            profile,
+        }
+    }
+
+    fn request(&self, text: &str, task_type: EmbeddingTaskType) -> GeminiEmbeddingRequest {
+        GeminiEmbeddingRequest {
+            model: format!("models/{}", self.profile.model),
+            content: GeminiContent {
+                parts: vec![GeminiPart {
+                    text: text.to_string(),
+                }],
+            },
+            task_type: Some(task_type.as_api_value().to_string()),
+            output_dimensionality: Some(self.profile.dim as u32),
+        }
+    }
+
+    async fn embed_gemini(
+        &self,
+        text: &str,
+        task_type: EmbeddingTaskType,
+    ) -> Result<Vec<f32>, String> {
+        let url = format!(
+            "https://generativelanguage.googleapis.com/v1beta/models/{}:embedContent",
+            self.profile.model
+        );
+
+        let res = self
+            .client
+            .post(&url)
+            .header("x-goog-api-key", &self.api_key)
+            .json(&self.request(text, task_type))
+            .send()
+            .await
+            .map_err(|e| format!("Gemini embedding request failed: {}", e))?;
+
+        if !res.status().is_success() {
+            let status = res.status();
+            let text_err = res.text().await.unwrap_or_default();
+            return Err(format!(
+                "Gemini embedding API error: {} - {}",
+                status, text_err
+            ));
+        }
+
+        let parsed: GeminiEmbeddingResponse = res
+            .json()
+            .await
+            .map_err(|e| format!("Parsing Gemini embedding response failed: {}", e))?;
+
+        Ok(parsed.embedding.values)
+    }
+
+    async fn embed_gemini_batch(
+        &self,
+        texts: &[String],
+        task_type: EmbeddingTaskType,
+    ) -> Result<Vec<Vec<f32>>, String> {
+        let req_body = GeminiBatchEmbeddingRequest {
+            requests: texts
+                .iter()
+                .map(|text| self.request(text, task_type))
+                .collect(),
+        };
+
+        let url = format!(
+            "https://generativelanguage.googleapis.com/v1beta/models/{}:batchEmbedContents",
+            self.profile.model
+        );
+
+        let res = self
+            .client
+            .post(&url)
+            .header("x-goog-api-key", &self.api_key)
+            .json(&req_body)
+            .send()
+            .await
+            .map_err(|e| format!("Gemini batch embedding request failed: {}", e))?;
+
+        if !res.status().is_success() {
+            let status = res.status();
+            let text_err = res.text().await.unwrap_or_default();
+            return Err(format!(
+                "Gemini batch embedding API error: {} - {}",
+                status, text_err
+            ));
+        }
+
+        let value: Value = res
+            .json()
+            .await
+            .map_err(|e| format!("Parsing Gemini batch embedding response failed: {}", e))?;
+
+        let mut embeddings = Vec::new();
+        if let Some(items) = value.get("embeddings").and_then(|v| v.as_array()) {
+            for item in items {
+                if let Some(vals) = parse_embedding_values(item) {
+                    embeddings.push(vals);
+                }
+            }
+        } else if let Some(items) = value.get("responses").and_then(|v| v.as_array()) {
+            for item in items {
+                if let Some(vals) = item.get("embedding").and_then(parse_embedding_values) {
+                    embeddings.push(vals);
+                }
+            }
+        }
+
+        Ok(embeddings)
+    }
+}
+
+#[async_trait]
+impl Embedder for GeminiEmbedder {
+    fn profile(&self) -> &EmbeddingProfile {
+        &self.profile
+    }
+
+    async fn embed_batch(
+        &self,
+        texts: &[String],
+        task_type: EmbeddingTaskType,
+    ) -> Result<Vec<Vec<f32>>, String> {
+        if texts.is_empty() {
+            return Ok(Vec::new());
+        }
+        let vectors = self.embed_gemini_batch(texts, task_type).await?;
+        ensure_dims(&self.profile, texts.len(), vectors)
+    }
+
+    async fn embed(&self, text: &str, task_type: EmbeddingTaskType) -> Result<Vec<f32>, String> {
+        let vector = self.embed_gemini(text, task_type).await?;
+        ensure_dims(&self.profile, 1, vec![vector]).map(|mut v| v.remove(0))
+    }
+
+    fn fallback(&self) -> Option<Arc<dyn Embedder>> {
+        let fallback = EmbeddingProfile::gemini_fallback();
+        if fallback == self.profile {
+            return None;
+        }
+        Some(Arc::new(Self {
+            client: self.client.clone(),
+            api_key: self.api_key.clone(),
+            profile: fallback,
+        }))
+    }
+}
+
+fn parse_embedding_values(value: &Value) -> Option<Vec<f32>> {
+    let values = if let Some(v) = value.get("values") {
+        v
+    } else if let Some(v) = value.get("embedding").and_then(|emb| emb.get("values")) {
+        v
+    } else {
+        return None;
+    };
+
+    let arr = values.as_array()?;
+    let mut out = Vec::with_capacity(arr.len());
+    for n in arr {
+        out.push(n.as_f64()? as f32);
+    }
+    Some(out)
+}
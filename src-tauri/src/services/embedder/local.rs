@@ -0,0 +1,111 @@
+use super::{Embedder, EmbeddingTaskType};
+use crate::services::memory_vault::profiles::{
+    EmbeddingProfile, LOCAL_HASH_EMBEDDING, LOCAL_PROVIDER,
+};
+use async_trait::async_trait;
+
+const WORD_WEIGHT: f32 = 1.0;
+const TRIGRAM_WEIGHT: f32 = 0.5;
+
+/// Offline embedder: signed feature hashing of words and character trigrams,
+/// L2-normalized. Deterministic across runs and platforms, so stored vectors
+/// stay comparable without re-embedding. Captures lexical overlap, not meaning.
+#[derive(Debug, Clone)]
+pub struct LocalEmbedder {
+    profile: EmbeddingProfile,
+}
+
+impl LocalEmbedder {
+    pub fn new(dim: usize) -> Self {
+        Self {
+            profile: EmbeddingProfile::new(LOCAL_PROVIDER, LOCAL_HASH_EMBEDDING, dim.max(1)),
+        }
+    }
+
+    pub fn embed_text(&self, text: &str) -> Vec<f32> {
+        let dim = self.profile.dim;
+        let mut vector = vec![0.0f32; dim];
+        let lowered = text.to_lowercase();
+        for word in lowered
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|w| !w.is_empty())
+        {
+            add_feature(&mut vector, word.as_bytes(), WORD_WEIGHT);
+
+            let padded = format!("#{}#", word).chars().collect::<Vec<_>>();
+            for window in padded.windows(3) {
+                let trigram = window.iter().collect::<String>();
+                add_feature(&mut vector, trigram.as_bytes(), TRIGRAM_WEIGHT);
+            }
+        }
+
+        let norm = vector.iter().map(|v| v * v).sum::<f32>().sqrt();
+        if norm > 0.0 {
+            for v in &mut vector {
+                *v /= norm;
+            }
+        }
+        vector
+    }
+}
+
+fn add_feature(vector: &mut [f32], feature: &[u8], weight: f32) {
+    let hash = fnv1a(feature);
+    let index = (hash % vector.len() as u64) as usize;
+    let sign = if hash >> 63 == 0 { 1.0 } else { -1.0 };
+    vector[index] += sign * weight;
+}
+
+/// 64-bit FNV-1a; unlike `DefaultHasher` its output is fixed forever.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in bytes {
+        hash ^= u64::from(*byte);
+        hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+    }
+    hash
+}
+
+#[async_trait]
+impl Embedder for LocalEmbedder {
+    fn profile(&self) -> &EmbeddingProfile {
+        &self.profile
+    }
+
+    async fn embed_batch(
+        &self,
+        texts: &[String],
+        _task_type: EmbeddingTaskType,
+    ) -> Result<Vec<Vec<f32>>, String> {
+        Ok(texts.iter().map(|text| self.embed_text(text)).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::memory_vault::lifecycle::cosine_similarity;
+
+    #[test]
+    fn test_local_embeddings_are_deterministic_and_normalized() {
+        let embedder = LocalEmbedder::new(256);
+        let a = embedder.embed_text("Quarterly revenue report for Acme");
+        assert_eq!(
+            a,
+            LocalEmbedder::new(256).embed_text("Quarterly revenue report for Acme")
+        );
+        assert_eq!(a.len(), 256);
+        let norm = a.iter().map(|v| v * v).sum::<f32>().sqrt();
+        assert!((norm - 1.0).abs() < 1e-5);
+        assert!(embedder.embed_text("").iter().all(|v| *v == 0.0));
+    }
+
+    #[test]
+    fn test_local_embeddings_rank_related_text_closer() {
+        let embedder = LocalEmbedder::new(1024);
+        let query = embedder.embed_text("acme revenue report");
+        let related = embedder.embed_text("The Acme quarterly revenue reports are ready");
+        let unrelated = embedder.embed_text("Bake the bread at 220 degrees");
+        assert!(cosine_similarity(&query, &related) > cosine_similarity(&query, &unrelated) + 0.2);
+    }
+}
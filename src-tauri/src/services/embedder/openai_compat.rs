@@ -0,0 +1,264 @@
+use super::{ensure_dims, Embedder, EmbeddingTaskType};
+use crate::services::memory_vault::profiles::EmbeddingProfile;
+use async_trait::async_trait;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize)]
+struct EmbeddingsRequest<'a> {
+    model: &'a str,
+    input: &'a [String],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    dimensions: Option<usize>,
+}
+
+#[derive(Debug, Deserialize)]
+struct EmbeddingsResponse {
+    data: Vec<EmbeddingItem>,
+}
+
+#[derive(Debug, Deserialize)]
+struct EmbeddingItem {
+    #[serde(default)]
+    index: usize,
+    embedding: Vec<f32>,
+}
+
+/// Any server exposing OpenAI's `POST /v1/embeddings`: OpenAI itself,
+/// Ollama, LM Studio, vLLM and similar.
+#[derive(Debug, Clone)]
+pub struct OpenAiCompatibleEmbedder {
+    client: Client,
+    base_url: String,
+    api_key: String,
+    profile: EmbeddingProfile,
+    /// Send `dimensions` so models that support shortening return `profile.dim`.
+    request_dimensions: bool,
+}
+
+impl OpenAiCompatibleEmbedder {
+    pub fn new(
+        base_url: &str,
+        api_key: String,
+        profile: EmbeddingProfile,
+        request_dimensions: bool,
+    ) -> Self {
+        Self {
+            client: Client::new(),
+            base_url: base_url.trim().trim_end_matches('/').to_string(),
+            api_key,
+            profile,
+            request_dimensions,
+        }
+    }
+}
+
+#[async_trait]
+impl Embedder for OpenAiCompatibleEmbedder {
+    fn profile(&self) -> &EmbeddingProfile {
+        &self.profile
+    }
+
+    async fn embed_batch(
+        &self,
+        texts: &[String],
+        _task_type: EmbeddingTaskType,
+    ) -> Result<Vec<Vec<f32>>, String> {
+        if texts.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let body = EmbeddingsRequest {
+            model: &self.profile.model,
+            input: texts,
+            dimensions: self.request_dimensions.then_some(self.profile.dim),
+        };
+        let mut request = self
+            .client
+            .post(format!("{}/embeddings", self.base_url))
+            .json(&body);
+        if !self.api_key.trim().is_empty() {
+            request = request.bearer_auth(&self.api_key);
+        }
+
+        let res = request
+            .send()
+            .await
+            .map_err(|e| format!("Embedding request to {} failed: {}", self.base_url, e))?;
+        if !res.status().is_success() {
+            let status = res.status();
+            let text_err = res.text().await.unwrap_or_default();
+            return Err(format!("Embedding API error: {} - {}", status, text_err));
+        }
+
+        let mut parsed: EmbeddingsResponse = res
+            .json()
+            .await
+            .map_err(|e| format!("Parsing embedding response failed: {}", e))?;
+        parsed.data.sort_by_key(|item| item.index);
+
+        ensure_dims(
+            &self.profile,
+            texts.len(),
+            parsed.data.into_iter().map(|item| item.embedding).collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    /// Stand-in embeddings server: answers every request with `body` and
+    /// records the raw requests it received.
+    async fn stand_in(body: String) -> (String, Arc<Mutex<Vec<String>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base_url = format!("http://{}/v1", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let log = requests.clone();
+        tokio::spawn(async move {
+            loop {
+                let Ok((mut socket, _)) = listener.accept().await else {
+                    return;
+                };
+                let mut raw = Vec::new();
+                let mut buf = [0u8; 4096];
+                // Read headers, then the body announced by Content-Length.
+                loop {
+                    let n = socket.read(&mut buf).await.unwrap_or(0);
+                    if n == 0 {
+                        break;
+                    }
+                    raw.extend_from_slice(&buf[..n]);
+                    let Some(head_end) = raw.windows(4).position(|w| w == b"\r\n\r\n") else {
+                        continue;
+                    };
+                    let body_len = String::from_utf8_lossy(&raw[..head_end])
+                        .lines()
+                        .find_map(|l| {
+                            let (name, value) = l.split_once(':')?;
+                            name.eq_ignore_ascii_case("content-length")
+                                .then(|| value.trim().parse::<usize>().ok())?
+                        })
+                        .unwrap_or(0);
+                    if raw.len() >= head_end + 4 + body_len {
+                        break;
+                    }
+                }
+                log.lock()
+                    .unwrap()
+                    .push(String::from_utf8_lossy(&raw).to_string());
+
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                );
+                let _ = socket.write_all(response.as_bytes()).await;
+                let _ = socket.shutdown().await;
+            }
+        });
+        (base_url, requests)
+    }
+
+    fn texts() -> Vec<String> {
+        vec!["first".to_string(), "second".to_string()]
+    }
+
+    #[tokio::test]
+    async fn embeddings_are_requested_and_returned_in_input_order() {
+        let body = serde_json::json!({
+            "data": [
+                { "index": 1, "embedding": [0.0, 1.0, 0.0] },
+                { "index": 0, "embedding": [1.0, 0.0, 0.0] }
+            ]
+        });
+        let (base_url, requests) = stand_in(body.to_string()).await;
+        let embedder = OpenAiCompatibleEmbedder::new(
+            &format!("{}/", base_url),
+            "sk-test".to_string(),
+            EmbeddingProfile::new("openai", "text-embedding-3-small", 3),
+            true,
+        );
+
+        let vectors = embedder
+            .embed_batch(&texts(), EmbeddingTaskType::RetrievalDocument)
+            .await
+            .unwrap();
+        assert_eq!(vectors, vec![vec![1.0, 0.0, 0.0], vec![0.0, 1.0, 0.0]]);
+
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests.len(), 1);
+        let (head, sent) = requests[0].split_once("\r\n\r\n").unwrap();
+        assert!(head.starts_with("POST /v1/embeddings "));
+        assert!(head
+            .lines()
+            .any(|l| l.eq_ignore_ascii_case("authorization: Bearer sk-test")));
+        let sent: serde_json::Value = serde_json::from_str(sent).unwrap();
+        assert_eq!(
+            sent,
+            serde_json::json!({
+                "model": "text-embedding-3-small",
+                "input": ["first", "second"],
+                "dimensions": 3
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn keyless_requests_omit_auth_and_dimensions() {
+        let body = serde_json::json!({
+            "data": [
+                { "index": 0, "embedding": [1.0, 0.0] },
+                { "index": 1, "embedding": [0.0, 1.0] }
+            ]
+        });
+        let (base_url, requests) = stand_in(body.to_string()).await;
+        let embedder = OpenAiCompatibleEmbedder::new(
+            &base_url,
+            String::new(),
+            EmbeddingProfile::new("ollama", "nomic-embed-text", 2),
+            false,
+        );
+
+        embedder
+            .embed_batch(&texts(), EmbeddingTaskType::RetrievalQuery)
+            .await
+            .unwrap();
+
+        let requests = requests.lock().unwrap();
+        let (head, sent) = requests[0].split_once("\r\n\r\n").unwrap();
+        assert!(!head.to_ascii_lowercase().contains("authorization:"));
+        let sent: serde_json::Value = serde_json::from_str(sent).unwrap();
+        assert!(sent.get("dimensions").is_none());
+    }
+
+    #[tokio::test]
+    async fn mismatched_dimensions_are_rejected() {
+        let body = serde_json::json!({
+            "data": [
+                { "index": 0, "embedding": [1.0, 0.0, 0.0] },
+                { "index": 1, "embedding": [0.0, 1.0] }
+            ]
+        });
+        let (base_url, _requests) = stand_in(body.to_string()).await;
+        let embedder = OpenAiCompatibleEmbedder::new(
+            &base_url,
+            String::new(),
+            EmbeddingProfile::new("ollama", "nomic-embed-text", 3),
+            false,
+        );
+
+        let err = embedder
+            .embed_batch(&texts(), EmbeddingTaskType::RetrievalDocument)
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err,
+            "ollama:nomic-embed-text returned a 2-dimensional embedding (expected 3)"
+        );
+    }
+}
@@ -6,17 +6,22 @@ use chrono::{DateTime, Utc};
 use dashmap::DashMap;
 // rayon is available for future parallel processing optimizations
 use serde::{Deserialize, Serialize};
-use std::collections::{HashMap, VecDeque};
+use sqlx::{Pool, Sqlite};
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use thiserror::Error;
 use tokio::fs;
-use tokio::sync::Mutex;
+use tokio::sync::{Mutex, RwLock};
 use uuid::Uuid;
 
 // Import workspace types
 use crate::services::workspace::Workspace;
 
+mod history_store;
+
+use history_store::FileHistoryStore;
+
 // ============ Error Types ============
 
 #[derive(Debug, Error)]
@@ -36,6 +41,8 @@ pub enum FileOpError {
     Cancelled,
     #[error("Conflict: {0}")]
     Conflict(String),
+    #[error("History store error: {0}")]
+    HistoryStore(#[from] sqlx::Error),
 }
 
 pub type FileOpResult<T> = Result<T, FileOpError>;
@@ -253,6 +260,106 @@ pub struct Transaction {
     pub end_time: Option<DateTime<Utc>>,
     pub operations: Vec<FileOpChange>,
     pub snapshots: Vec<FileVersion>, // Pre-transaction snapshots
+    #[serde(default)]
+    pub origin: OperationOrigin,
+}
+
+/// How a path differs from its state before an agent run
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RunFileStatus {
+    Created,
+    Modified,
+    Deleted,
+    Unchanged,
+}
+
+/// One path touched by an agent run, with a diff against its pre-run content
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunFileChange {
+    pub path: String,
+    pub status: RunFileStatus,
+    pub diff: Option<String>,
+}
+
+/// Everything reverting an agent run would undo
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunRevertPreview {
+    pub run_id: String,
+    pub transaction_id: String,
+    pub state: TransactionState,
+    pub changes: Vec<RunFileChange>,
+}
+
+// ============ History Persistence Types ============
+
+/// What an operation was performed on behalf of
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct OperationOrigin {
+    pub workspace_id: Option<String>,
+    pub task_id: Option<String>,
+    pub run_id: Option<String>,
+}
+
+/// Whether a recorded operation is still in effect
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum OperationStatus {
+    Applied,
+    Undone,
+}
+
+/// Filter for browsing persisted operation history; unset fields match all
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileOperationQuery {
+    pub workspace_id: Option<String>,
+    pub task_id: Option<String>,
+    pub run_id: Option<String>,
+    pub since: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
+    pub limit: Option<u32>,
+}
+
+/// A persisted operation as returned by history queries
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileOperationEntry {
+    pub id: String,
+    pub description: String,
+    pub timestamp: DateTime<Utc>,
+    pub origin: OperationOrigin,
+    pub transaction_id: Option<String>,
+    pub status: OperationStatus,
+    pub undoable: bool,
+    pub changes: Vec<FileOpChange>,
+}
+
+/// How long history, versions and trash entries are kept
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RetentionPolicy {
+    pub max_age_days: u32,
+}
+
+impl Default for RetentionPolicy {
+    fn default() -> Self {
+        Self { max_age_days: 30 }
+    }
+}
+
+/// What a history garbage collection pass removed
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryGcReport {
+    pub operations_removed: u64,
+    pub transactions_removed: u64,
+    pub versions_removed: u64,
+    pub trash_entries_removed: u64,
+    pub bytes_freed: u64,
 }
 
 /// Enhanced operation record with versioning support
@@ -263,9 +370,8 @@ pub struct EnhancedOperationRecord {
     pub timestamp: DateTime<Utc>,
     pub changes: Vec<FileOpChange>,
     pub transaction_id: Option<String>,
-    /// Reserved for future versioning feature
-    #[allow(dead_code)]
     pub versions_created: Vec<FileVersion>,
+    pub origin: OperationOrigin,
 }
 
 /// Undo/Redo stack entry
@@ -288,24 +394,35 @@ pub struct OperationRecord {
 
 // ============ File Operations Engine ============
 
+/// Stack names used when persisting `undo_stack` / `redo_stack`
+const UNDO_STACK: &str = "undo";
+const REDO_STACK: &str = "redo";
+
 /// Core engine for file operations with parallel processing
 pub struct FileOperationEngine {
     /// Operation history for undo support
     history: DashMap<String, OperationRecord>,
     /// Enhanced operation history with versioning
     enhanced_history: DashMap<String, EnhancedOperationRecord>,
-    /// Undo/Redo stacks - Reserved for future undo feature
-    #[allow(dead_code)]
+    /// Undo/Redo stacks, keyed by scope ("global")
     undo_stack: DashMap<String, VecDeque<HistoryEntry>>,
     redo_stack: DashMap<String, VecDeque<HistoryEntry>>,
     /// Active transactions
     transactions: DashMap<String, Transaction>,
+    /// Agent run id -> checkpoint transaction id
+    run_checkpoints: DashMap<String, String>,
     /// File versions storage
     versions_dir: PathBuf,
     /// Trash directory for safe deletes
     trash_dir: PathBuf,
     /// Current workspace context (interior mutability for shared state)
     workspace: Arc<Mutex<Option<Workspace>>>,
+    /// SQLite write-through for history, set once the database is open
+    history_store: RwLock<Option<FileHistoryStore>>,
+    /// Held while a delete moves files into the trash and records it, and
+    /// while garbage collection sweeps the trash, so a sweep never sees a
+    /// trashed file before its operation is recorded
+    trash_lock: Mutex<()>,
 }
 
 impl FileOperationEngine {
@@ -313,7 +430,10 @@ impl FileOperationEngine {
         let base_dir = dirs::data_local_dir()
             .unwrap_or_else(|| PathBuf::from("/tmp"))
             .join("rainy-cowork");
+        Self::with_base_dir(base_dir)
+    }
 
+    pub(crate) fn with_base_dir(base_dir: PathBuf) -> Self {
         let trash_dir = base_dir.join("trash");
         let versions_dir = base_dir.join("versions");
 
@@ -323,9 +443,12 @@ impl FileOperationEngine {
             undo_stack: DashMap::new(),
             redo_stack: DashMap::new(),
             transactions: DashMap::new(),
+            run_checkpoints: DashMap::new(),
             versions_dir,
             trash_dir,
             workspace: Arc::new(Mutex::new(None)),
+            history_store: RwLock::new(None),
+            trash_lock: Mutex::new(()),
         }
     }
 
@@ -483,7 +606,7 @@ impl FileOperationEngine {
 
         // Record in history
         if !changes.is_empty() {
-            self.record_operation("Move files", changes.clone());
+            self.record_operation("Move files", changes.clone()).await;
         }
 
         Ok(changes)
@@ -645,7 +768,7 @@ impl FileOperationEngine {
                 }
             }
             if !changes.is_empty() {
-                self.record_operation("Batch rename", changes);
+                self.record_operation("Batch rename", changes).await;
             }
         }
 
@@ -655,6 +778,7 @@ impl FileOperationEngine {
     /// Safe delete - moves files to trash with workspace validation
     pub async fn safe_delete(&self, paths: Vec<String>) -> FileOpResult<Vec<FileOpChange>> {
         self.init().await?;
+        let _trash = self.trash_lock.lock().await;
         let mut changes = Vec::new();
         let mut errors = Vec::new();
 
@@ -704,7 +828,7 @@ impl FileOperationEngine {
         }
 
         if !changes.is_empty() {
-            self.record_operation("Delete files", changes.clone());
+            self.record_operation("Delete files", changes.clone()).await;
         }
 
         Ok(changes)
@@ -811,7 +935,8 @@ impl FileOperationEngine {
 
         // Record in history if not dry run
         if !dry_run && !result.changes.is_empty() {
-            self.record_operation("Organize folder", result.changes.clone());
+            self.record_operation("Organize folder", result.changes.clone())
+                .await;
         }
 
         Ok(result)
@@ -1121,6 +1246,7 @@ impl FileOperationEngine {
             size,
             version_path: version_path.to_string_lossy().to_string(),
         };
+        self.persist_version(&version).await;
 
         Ok(version)
     }
@@ -1152,6 +1278,21 @@ impl FileOperationEngine {
             }
         }
 
+        // Recorded versions carry stable ids and metadata; prefer them over
+        // what the file names alone tell us
+        if let Some(store) = self.history_store().await {
+            let recorded = store.load_versions(Some(file_path)).await?;
+            versions.retain(|scanned| {
+                !recorded
+                    .iter()
+                    .any(|version| version.version_path == scanned.version_path)
+            });
+            for version in recorded {
+                max_version = max_version.max(version.version_number);
+                versions.push(version);
+            }
+        }
+
         // Sort versions by version number
         versions.sort_by(|a, b| a.version_number.cmp(&b.version_number));
 
@@ -1249,8 +1390,12 @@ impl FileOperationEngine {
 
     // ============ Transaction Support ============
 
-    /// Start a new transaction
-    pub async fn begin_transaction(&self, description: &str) -> FileOpResult<String> {
+    /// Start a new transaction, optionally on behalf of a task
+    pub async fn begin_transaction(
+        &self,
+        description: &str,
+        task_id: Option<String>,
+    ) -> FileOpResult<String> {
         let transaction_id = Uuid::new_v4().to_string();
 
         let transaction = Transaction {
@@ -1261,10 +1406,15 @@ impl FileOperationEngine {
             end_time: None,
             operations: Vec::new(),
             snapshots: Vec::new(),
+            origin: OperationOrigin {
+                task_id,
+                ..self.current_origin().await
+            },
         };
 
         self.transactions
             .insert(transaction_id.clone(), transaction);
+        self.persist_transaction(&transaction_id).await;
         Ok(transaction_id)
     }
 
@@ -1288,6 +1438,8 @@ impl FileOperationEngine {
         }
 
         transaction.operations.push(operation);
+        drop(transaction);
+        self.persist_transaction(transaction_id).await;
         Ok(())
     }
 
@@ -1315,15 +1467,22 @@ impl FileOperationEngine {
             changes: transaction.operations.clone(),
             transaction_id: Some(transaction_id.to_string()),
             versions_created: transaction.snapshots.clone(),
+            origin: transaction.origin.clone(),
         };
 
-        self.enhanced_history.insert(record.id.clone(), record);
+        self.enhanced_history
+            .insert(record.id.clone(), record.clone());
 
         // Update transaction state
         transaction.state = TransactionState::Committed;
         transaction.end_time = Some(Utc::now());
+        let operations = transaction.operations.clone();
+        drop(transaction);
 
-        Ok(transaction.operations.clone())
+        self.persist_operation(&record, false, OperationStatus::Applied)
+            .await;
+        self.persist_transaction(transaction_id).await;
+        Ok(operations)
     }
 
     /// Rollback a transaction
@@ -1400,6 +1559,8 @@ impl FileOperationEngine {
         // Update transaction state
         transaction.state = TransactionState::RolledBack;
         transaction.end_time = Some(Utc::now());
+        drop(transaction);
+        self.persist_transaction(transaction_id).await;
 
         Ok(rollback_changes)
     }
@@ -1409,6 +1570,285 @@ impl FileOperationEngine {
         self.transactions.get(transaction_id).map(|t| t.clone())
     }
 
+    // ============ Agent Run Checkpoints ============
+
+    /// Checkpoint transaction for an agent run, opened on first use
+    pub async fn begin_run_checkpoint(&self, run_id: &str) -> FileOpResult<String> {
+        let origin = OperationOrigin {
+            run_id: Some(run_id.to_string()),
+            ..self.current_origin().await
+        };
+        let mut opened = false;
+        let transaction_id = self
+            .run_checkpoints
+            .entry(run_id.to_string())
+            .or_insert_with(|| {
+                opened = true;
+                let transaction_id = Uuid::new_v4().to_string();
+                self.transactions.insert(
+                    transaction_id.clone(),
+                    Transaction {
+                        id: transaction_id.clone(),
+                        description: format!("Agent run {}", run_id),
+                        state: TransactionState::Active,
+                        start_time: Utc::now(),
+                        end_time: None,
+                        operations: Vec::new(),
+                        snapshots: Vec::new(),
+                        origin,
+                    },
+                );
+                transaction_id
+            })
+            .clone();
+        if opened {
+            self.persist_transaction(&transaction_id).await;
+        }
+        Ok(transaction_id)
+    }
+
+    /// Record the pre-run state of `path` before a run first changes it.
+    /// Files are snapshotted, directories snapshot every file beneath them,
+    /// and paths that do not exist yet are recorded so a revert removes them.
+    pub async fn checkpoint_path(&self, run_id: &str, path: &Path) -> FileOpResult<()> {
+        let transaction_id = self.begin_run_checkpoint(run_id).await?;
+        if self
+            .transactions
+            .get(&transaction_id)
+            .is_some_and(|t| t.state != TransactionState::Active)
+        {
+            return Err(FileOpError::Conflict(format!(
+                "Checkpoint for run {} is closed",
+                run_id
+            )));
+        }
+        fs::create_dir_all(&self.versions_dir).await?;
+
+        // For a path that does not exist yet, record its topmost missing
+        // ancestor so directories created along the way are removed too.
+        let mut path = path;
+        if !path.exists() {
+            while let Some(parent) = path.parent() {
+                if parent.exists() {
+                    break;
+                }
+                path = parent;
+            }
+        }
+
+        let targets: Vec<PathBuf> = if path.is_dir() {
+            walkdir::WalkDir::new(path)
+                .into_iter()
+                .filter_map(|entry| entry.ok())
+                .filter(|entry| entry.file_type().is_file())
+                .map(|entry| entry.into_path())
+                .collect()
+        } else {
+            vec![path.to_path_buf()]
+        };
+
+        let mut result = Ok(());
+        for target in targets {
+            let target_str = target.to_string_lossy().to_string();
+            if self.run_checkpoint_covers(&transaction_id, &target) {
+                continue;
+            }
+
+            if target.exists() {
+                let snapshot = match self
+                    .create_version_snapshot(&target_str, &format!("Before agent run {}", run_id))
+                    .await
+                {
+                    Ok(snapshot) => snapshot,
+                    Err(e) => {
+                        result = Err(e);
+                        break;
+                    }
+                };
+                if let Some(mut transaction) = self.transactions.get_mut(&transaction_id) {
+                    transaction.snapshots.push(snapshot);
+                }
+            } else if let Some(mut transaction) = self.transactions.get_mut(&transaction_id) {
+                transaction.operations.push(FileOpChange {
+                    id: Uuid::new_v4().to_string(),
+                    operation: FileOpType::Create,
+                    source_path: target_str,
+                    dest_path: None,
+                    timestamp: Utc::now(),
+                    reversible: true,
+                });
+            }
+        }
+
+        // Persist whatever was captured, even when a later snapshot failed
+        self.persist_transaction(&transaction_id).await;
+        result
+    }
+
+    /// True when the run already holds the pre-run state of `path`, either as
+    /// a snapshot or because an ancestor did not exist before the run.
+    fn run_checkpoint_covers(&self, transaction_id: &str, path: &Path) -> bool {
+        let Some(transaction) = self.transactions.get(transaction_id) else {
+            return false;
+        };
+        transaction
+            .snapshots
+            .iter()
+            .any(|snapshot| Path::new(&snapshot.file_path) == path)
+            || transaction
+                .operations
+                .iter()
+                .any(|op| op.operation == FileOpType::Create && path.starts_with(&op.source_path))
+    }
+
+    /// Close a run's checkpoint. Runs that changed nothing leave no record.
+    pub async fn finish_run_checkpoint(&self, run_id: &str) -> FileOpResult<()> {
+        let Some(transaction_id) = self.run_checkpoints.get(run_id).map(|id| id.clone()) else {
+            return Ok(());
+        };
+        let untouched = self
+            .transactions
+            .get(&transaction_id)
+            .is_some_and(|t| t.snapshots.is_empty() && t.operations.is_empty());
+        if untouched {
+            self.transactions.remove(&transaction_id);
+            self.run_checkpoints.remove(run_id);
+            if let Some(store) = self.history_store().await {
+                store.delete_transaction(&transaction_id).await?;
+            }
+            return Ok(());
+        }
+        self.commit_transaction(&transaction_id).await.map(|_| ())
+    }
+
+    fn run_transaction(&self, run_id: &str) -> FileOpResult<Transaction> {
+        self.run_checkpoints
+            .get(run_id)
+            .and_then(|id| self.get_transaction(&id))
+            .ok_or_else(|| FileOpError::NotFound(format!("No checkpoint for run {}", run_id)))
+    }
+
+    /// Diff every path a run touched against its pre-run content
+    pub async fn preview_run_revert(&self, run_id: &str) -> FileOpResult<RunRevertPreview> {
+        let transaction = self.run_transaction(run_id)?;
+        let mut changes = Vec::new();
+
+        for op in transaction
+            .operations
+            .iter()
+            .filter(|op| op.operation == FileOpType::Create)
+        {
+            let path = Path::new(&op.source_path);
+            let (status, diff) = if path.is_dir() {
+                (RunFileStatus::Created, None)
+            } else if path.is_file() {
+                let current = fs::read(path).await?;
+                (
+                    RunFileStatus::Created,
+                    Some(diff_contents(&op.source_path, b"", &current)),
+                )
+            } else {
+                (RunFileStatus::Unchanged, None)
+            };
+            changes.push(RunFileChange {
+                path: op.source_path.clone(),
+                status,
+                diff,
+            });
+        }
+
+        for snapshot in &transaction.snapshots {
+            let original = fs::read(&snapshot.version_path).await?;
+            let (status, diff) = match fs::read(&snapshot.file_path).await {
+                Ok(current) if current == original => (RunFileStatus::Unchanged, None),
+                Ok(current) => (
+                    RunFileStatus::Modified,
+                    Some(diff_contents(&snapshot.file_path, &original, &current)),
+                ),
+                Err(_) => (
+                    RunFileStatus::Deleted,
+                    Some(diff_contents(&snapshot.file_path, &original, b"")),
+                ),
+            };
+            changes.push(RunFileChange {
+                path: snapshot.file_path.clone(),
+                status,
+                diff,
+            });
+        }
+
+        Ok(RunRevertPreview {
+            run_id: run_id.to_string(),
+            transaction_id: transaction.id,
+            state: transaction.state,
+            changes,
+        })
+    }
+
+    /// Restore every path a run touched to its pre-run state
+    pub async fn revert_run(&self, run_id: &str) -> FileOpResult<Vec<FileOpChange>> {
+        let transaction = self.run_transaction(run_id)?;
+        if !matches!(
+            transaction.state,
+            TransactionState::Active | TransactionState::Committed
+        ) {
+            return Err(FileOpError::Conflict(format!(
+                "Run {} has already been reverted",
+                run_id
+            )));
+        }
+
+        let mut changes = Vec::new();
+
+        // Remove what the run created first; snapshots never live beneath them.
+        for op in transaction
+            .operations
+            .iter()
+            .rev()
+            .filter(|op| op.operation == FileOpType::Create)
+        {
+            let path = Path::new(&op.source_path);
+            if path.is_dir() {
+                fs::remove_dir_all(path).await?;
+            } else if path.exists() {
+                fs::remove_file(path).await?;
+            } else {
+                continue;
+            }
+            changes.push(FileOpChange {
+                id: Uuid::new_v4().to_string(),
+                operation: FileOpType::Delete,
+                source_path: op.source_path.clone(),
+                dest_path: None,
+                timestamp: Utc::now(),
+                reversible: false,
+            });
+        }
+
+        for snapshot in &transaction.snapshots {
+            if let Some(parent) = Path::new(&snapshot.file_path).parent() {
+                fs::create_dir_all(parent).await?;
+            }
+            fs::copy(&snapshot.version_path, &snapshot.file_path).await?;
+            changes.push(FileOpChange {
+                id: Uuid::new_v4().to_string(),
+                operation: FileOpType::Create,
+                source_path: snapshot.version_path.clone(),
+                dest_path: Some(snapshot.file_path.clone()),
+                timestamp: Utc::now(),
+                reversible: false,
+            });
+        }
+
+        if let Some(mut transaction) = self.transactions.get_mut(&transaction.id) {
+            transaction.state = TransactionState::RolledBack;
+            transaction.end_time = Some(Utc::now());
+        }
+        self.persist_transaction(&transaction.id).await;
+
+        Ok(changes)
+    }
+
     // ============ Enhanced Undo/Redo Support ============
 
     /// Enhanced undo with full operation history
@@ -1420,6 +1860,8 @@ impl FileOperationEngine {
             .ok_or_else(|| {
                 FileOpError::NotFound(format!("Operation not found: {}", operation_id))
             })?;
+        // Undone in either ledger means undone in both
+        self.history.remove(operation_id);
 
         let mut undo_changes = Vec::new();
 
@@ -1483,11 +1925,12 @@ impl FileOperationEngine {
         };
 
         // For simplicity, we'll use a global redo stack
-        let mut redo_stack = self
-            .redo_stack
+        self.redo_stack
             .entry("global".to_string())
-            .or_insert_with(VecDeque::new);
-        redo_stack.push_back(history_entry);
+            .or_insert_with(VecDeque::new)
+            .push_back(history_entry);
+        self.persist_stack(REDO_STACK, "global").await;
+        self.persist_ledgers(operation_id).await;
 
         Ok(undo_changes)
     }
@@ -1562,6 +2005,9 @@ impl FileOperationEngine {
             history_entry.operation.id.clone(),
             history_entry.operation.clone(),
         );
+        drop(redo_stack);
+        self.persist_stack(REDO_STACK, "global").await;
+        self.persist_ledgers(&history_entry.operation.id).await;
 
         Ok(redo_changes)
     }
@@ -1584,24 +2030,27 @@ impl FileOperationEngine {
     // ============ Undo Support ============
 
     /// Record an operation for undo support
-    fn record_operation(&self, description: &str, changes: Vec<FileOpChange>) {
+    async fn record_operation(&self, description: &str, changes: Vec<FileOpChange>) {
         let record = OperationRecord {
             id: Uuid::new_v4().to_string(),
             changes: changes.clone(),
             timestamp: Utc::now(),
             description: description.to_string(),
         };
-        self.history.insert(record.id.clone(), record);
 
-        // Also record in enhanced history
+        // Also record in enhanced history, under the same id
         let enhanced_record = EnhancedOperationRecord {
-            id: Uuid::new_v4().to_string(),
+            id: record.id.clone(),
             description: description.to_string(),
-            timestamp: Utc::now(),
+            timestamp: record.timestamp,
             changes,
             transaction_id: None,
             versions_created: Vec::new(),
+            origin: self.current_origin().await,
         };
+        self.history.insert(record.id.clone(), record);
+        self.persist_operation(&enhanced_record, true, OperationStatus::Applied)
+            .await;
         self.enhanced_history
             .insert(enhanced_record.id.clone(), enhanced_record);
     }
@@ -1615,6 +2064,7 @@ impl FileOperationEngine {
             .ok_or_else(|| {
                 FileOpError::NotFound(format!("Operation not found: {}", operation_id))
             })?;
+        self.enhanced_history.remove(operation_id);
 
         let mut undo_changes = Vec::new();
 
@@ -1655,6 +2105,7 @@ impl FileOperationEngine {
                 _ => {}
             }
         }
+        self.persist_ledgers(operation_id).await;
 
         Ok(undo_changes)
     }
@@ -1666,6 +2117,420 @@ impl FileOperationEngine {
             .map(|r| (r.id.clone(), r.description.clone(), r.timestamp))
             .collect()
     }
+
+    // ============ History Persistence ============
+
+    /// Persist history to `pool` from now on and restore what earlier
+    /// sessions recorded. Checkpoints of runs that never finished are closed
+    /// so those runs can still be reverted.
+    pub async fn attach_history_store(&self, pool: Pool<Sqlite>) -> FileOpResult<()> {
+        let store = FileHistoryStore::new(pool);
+
+        let mut records = HashMap::new();
+        for stored in store.load_operations().await? {
+            let record = stored.record;
+            if stored.undoable {
+                self.history.insert(
+                    record.id.clone(),
+                    OperationRecord {
+                        id: record.id.clone(),
+                        changes: record.changes.clone(),
+                        timestamp: record.timestamp,
+                        description: record.description.clone(),
+                    },
+                );
+            }
+            if stored.status == OperationStatus::Applied {
+                self.enhanced_history
+                    .insert(record.id.clone(), record.clone());
+            }
+            records.insert(record.id.clone(), record);
+        }
+
+        for entry in store.load_stacks().await? {
+            let Some(operation) = records.get(&entry.operation_id) else {
+                continue;
+            };
+            self.stack(&entry.stack)
+                .entry(entry.stack_key)
+                .or_default()
+                .push_back(HistoryEntry {
+                    operation: operation.clone(),
+                    can_redo: entry.can_redo,
+                });
+        }
+
+        for mut transaction in store.load_transactions().await? {
+            if transaction.state == TransactionState::Active {
+                if transaction.operations.is_empty() && transaction.snapshots.is_empty() {
+                    store.delete_transaction(&transaction.id).await?;
+                    continue;
+                }
+                transaction.state = if transaction.origin.run_id.is_some() {
+                    TransactionState::Committed
+                } else {
+                    TransactionState::Failed
+                };
+                transaction.end_time = Some(Utc::now());
+                store.upsert_transaction(&transaction).await?;
+            }
+            if let Some(run_id) = &transaction.origin.run_id {
+                self.run_checkpoints
+                    .insert(run_id.clone(), transaction.id.clone());
+            }
+            self.transactions
+                .insert(transaction.id.clone(), transaction);
+        }
+
+        *self.history_store.write().await = Some(store);
+        Ok(())
+    }
+
+    /// Browse persisted operations, newest first
+    pub async fn query_operations(
+        &self,
+        query: &FileOperationQuery,
+    ) -> FileOpResult<Vec<FileOperationEntry>> {
+        let store = self.require_history_store().await?;
+        Ok(store.query_operations(query).await?)
+    }
+
+    /// Drop history older than the retention window, then delete version
+    /// files and trash entries that nothing references any more. Unreferenced
+    /// version files inside the window are kept since they may predate the
+    /// history store. Trash entries are matched against recorded deletes, so
+    /// run this while no file operations are in flight (startup does).
+    pub async fn collect_garbage(&self, policy: &RetentionPolicy) -> FileOpResult<HistoryGcReport> {
+        let store = self.require_history_store().await?;
+        let _trash = self.trash_lock.lock().await;
+        let cutoff = Utc::now() - chrono::Duration::days(i64::from(policy.max_age_days));
+        let mut report = HistoryGcReport {
+            operations_removed: store.prune_operations(cutoff).await?,
+            transactions_removed: store.prune_transactions(cutoff).await?,
+            ..Default::default()
+        };
+
+        self.history.retain(|_, record| record.timestamp >= cutoff);
+        self.enhanced_history
+            .retain(|_, record| record.timestamp >= cutoff);
+        for stacks in [&self.undo_stack, &self.redo_stack] {
+            for mut stack in stacks.iter_mut() {
+                stack.retain(|entry| entry.operation.timestamp >= cutoff);
+            }
+        }
+        self.transactions.retain(|_, transaction| {
+            transaction.state == TransactionState::Active
+                || transaction.end_time.unwrap_or(transaction.start_time) >= cutoff
+        });
+        self.run_checkpoints
+            .retain(|_, transaction_id| self.transactions.contains_key(transaction_id));
+
+        // Everything still reachable from retained history
+        let operations = store.load_operations().await?;
+        let transactions = store.load_transactions().await?;
+        let mut referenced_versions: HashSet<PathBuf> = transactions
+            .iter()
+            .flat_map(|transaction| transaction.snapshots.iter())
+            .chain(
+                operations
+                    .iter()
+                    .flat_map(|stored| stored.record.versions_created.iter()),
+            )
+            .map(|version| PathBuf::from(&version.version_path))
+            .collect();
+        let referenced_trash: HashSet<PathBuf> = operations
+            .iter()
+            .flat_map(|stored| stored.record.changes.iter())
+            .chain(
+                transactions
+                    .iter()
+                    .flat_map(|transaction| transaction.operations.iter()),
+            )
+            .filter(|change| change.operation == FileOpType::Delete)
+            .filter_map(|change| change.dest_path.as_ref().map(PathBuf::from))
+            .collect();
+
+        for version in store.load_versions(None).await? {
+            let path = PathBuf::from(&version.version_path);
+            let expired = version.timestamp < cutoff && !referenced_versions.contains(&path);
+            if !path.exists() || expired {
+                if path.exists() {
+                    fs::remove_file(&path).await?;
+                    report.bytes_freed += version.size;
+                }
+                store.delete_version(&version.id).await?;
+                report.versions_removed += 1;
+            } else {
+                referenced_versions.insert(path);
+            }
+        }
+
+        if self.versions_dir.exists() {
+            let mut entries = fs::read_dir(&self.versions_dir).await?;
+            while let Some(entry) = entries.next_entry().await? {
+                let path = entry.path();
+                let metadata = entry.metadata().await?;
+                let modified: DateTime<Utc> = metadata.modified()?.into();
+                if metadata.is_file() && modified < cutoff && !referenced_versions.contains(&path) {
+                    fs::remove_file(&path).await?;
+                    report.versions_removed += 1;
+                    report.bytes_freed += metadata.len();
+                }
+            }
+        }
+
+        if self.trash_dir.exists() {
+            let mut entries = fs::read_dir(&self.trash_dir).await?;
+            while let Some(entry) = entries.next_entry().await? {
+                let path = entry.path();
+                // A file keeps its own mtime when trashed; deletes in flight
+                // are covered by `trash_lock`, recorded ones by `referenced_trash`.
+                let modified: DateTime<Utc> = entry.metadata().await?.modified()?.into();
+                if modified >= cutoff || referenced_trash.contains(&path) {
+                    continue;
+                }
+                let size = disk_usage(&path);
+                if entry.file_type().await?.is_dir() {
+                    fs::remove_dir_all(&path).await?;
+                } else {
+                    fs::remove_file(&path).await?;
+                }
+                report.trash_entries_removed += 1;
+                report.bytes_freed += size;
+            }
+        }
+
+        Ok(report)
+    }
+
+    async fn history_store(&self) -> Option<FileHistoryStore> {
+        self.history_store.read().await.clone()
+    }
+
+    async fn require_history_store(&self) -> FileOpResult<FileHistoryStore> {
+        self.history_store().await.ok_or_else(|| {
+            FileOpError::Conflict("File operation history is not persisted".to_string())
+        })
+    }
+
+    fn stack(&self, stack: &str) -> &DashMap<String, VecDeque<HistoryEntry>> {
+        if stack == UNDO_STACK {
+            &self.undo_stack
+        } else {
+            &self.redo_stack
+        }
+    }
+
+    async fn current_origin(&self) -> OperationOrigin {
+        OperationOrigin {
+            workspace_id: self.workspace.lock().await.as_ref().map(|w| w.id.clone()),
+            ..Default::default()
+        }
+    }
+
+    // Write-through failures are logged, not returned: by the time they run
+    // the file operation itself has already happened.
+
+    async fn persist_operation(
+        &self,
+        record: &EnhancedOperationRecord,
+        undoable: bool,
+        status: OperationStatus,
+    ) {
+        let Some(store) = self.history_store().await else {
+            return;
+        };
+        if let Err(e) = store.upsert_operation(record, undoable, status).await {
+            tracing::warn!("Failed to persist file operation {}: {}", record.id, e);
+        }
+    }
+
+    /// Sync an operation's ledger flags with the in-memory maps
+    async fn persist_ledgers(&self, operation_id: &str) {
+        let Some(store) = self.history_store().await else {
+            return;
+        };
+        let status = if self.enhanced_history.contains_key(operation_id) {
+            OperationStatus::Applied
+        } else {
+            OperationStatus::Undone
+        };
+        let undoable = self.history.contains_key(operation_id);
+        if let Err(e) = store
+            .set_operation_ledgers(operation_id, undoable, status)
+            .await
+        {
+            tracing::warn!("Failed to persist file operation {}: {}", operation_id, e);
+        }
+    }
+
+    async fn persist_stack(&self, stack: &str, stack_key: &str) {
+        let Some(store) = self.history_store().await else {
+            return;
+        };
+        let entries: Vec<(String, bool)> = self
+            .stack(stack)
+            .get(stack_key)
+            .map(|entries| {
+                entries
+                    .iter()
+                    .map(|entry| (entry.operation.id.clone(), entry.can_redo))
+                    .collect()
+            })
+            .unwrap_or_default();
+        if let Err(e) = store.replace_stack(stack, stack_key, &entries).await {
+            tracing::warn!("Failed to persist {} stack '{}': {}", stack, stack_key, e);
+        }
+    }
+
+    async fn persist_transaction(&self, transaction_id: &str) {
+        let Some(store) = self.history_store().await else {
+            return;
+        };
+        let Some(transaction) = self.get_transaction(transaction_id) else {
+            return;
+        };
+        if let Err(e) = store.upsert_transaction(&transaction).await {
+            tracing::warn!(
+                "Failed to persist file transaction {}: {}",
+                transaction_id,
+                e
+            );
+        }
+    }
+
+    async fn persist_version(&self, version: &FileVersion) {
+        let Some(store) = self.history_store().await else {
+            return;
+        };
+        if let Err(e) = store.insert_version(version).await {
+            tracing::warn!("Failed to persist file version {}: {}", version.id, e);
+        }
+    }
+}
+
+/// Bytes used by a file, or by everything beneath a directory
+fn disk_usage(path: &Path) -> u64 {
+    walkdir::WalkDir::new(path)
+        .into_iter()
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| entry.metadata().ok())
+        .filter(|metadata| metadata.is_file())
+        .map(|metadata| metadata.len())
+        .sum()
+}
+
+/// Above this many LCS cells a diff is summarized instead of computed.
+const MAX_DIFF_CELLS: usize = 4_000_000;
+const DIFF_CONTEXT_LINES: usize = 3;
+
+fn diff_contents(path: &str, old: &[u8], new: &[u8]) -> String {
+    match (std::str::from_utf8(old), std::str::from_utf8(new)) {
+        (Ok(old), Ok(new)) => unified_diff(path, old, new),
+        _ => format!(
+            "Binary file {} changed ({} -> {} bytes)",
+            path,
+            old.len(),
+            new.len()
+        ),
+    }
+}
+
+/// Line-based unified diff (LCS) with three lines of context.
+fn unified_diff(path: &str, old: &str, new: &str) -> String {
+    let a: Vec<&str> = old.lines().collect();
+    let b: Vec<&str> = new.lines().collect();
+    let mut out = format!("--- {}\n+++ {}\n", path, path);
+
+    let prefix = a.iter().zip(&b).take_while(|(x, y)| x == y).count();
+    let suffix = a[prefix..]
+        .iter()
+        .rev()
+        .zip(b[prefix..].iter().rev())
+        .take_while(|(x, y)| x == y)
+        .count();
+    let (mid_a, mid_b) = (&a[prefix..a.len() - suffix], &b[prefix..b.len() - suffix]);
+
+    if mid_a.len().saturating_mul(mid_b.len()) > MAX_DIFF_CELLS {
+        out.push_str(&format!(
+            "@@ too large to diff: {} -> {} lines @@\n",
+            a.len(),
+            b.len()
+        ));
+        return out;
+    }
+
+    // ops: (' ' | '-' | '+', line)
+    let mut ops: Vec<(char, &str)> = a[..prefix].iter().map(|line| (' ', *line)).collect();
+    let (n, m) = (mid_a.len(), mid_b.len());
+    let mut lcs = vec![0u32; (n + 1) * (m + 1)];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lcs[i * (m + 1) + j] = if mid_a[i] == mid_b[j] {
+                lcs[(i + 1) * (m + 1) + j + 1] + 1
+            } else {
+                lcs[(i + 1) * (m + 1) + j].max(lcs[i * (m + 1) + j + 1])
+            };
+        }
+    }
+    let (mut i, mut j) = (0, 0);
+    while i < n || j < m {
+        if i < n && j < m && mid_a[i] == mid_b[j] {
+            ops.push((' ', mid_a[i]));
+            i += 1;
+            j += 1;
+        } else if i < n && (j == m || lcs[(i + 1) * (m + 1) + j] >= lcs[i * (m + 1) + j + 1]) {
+            ops.push(('-', mid_a[i]));
+            i += 1;
+        } else {
+            ops.push(('+', mid_b[j]));
+            j += 1;
+        }
+    }
+    ops.extend(a[a.len() - suffix..].iter().map(|line| (' ', *line)));
+
+    // Group changes into hunks with surrounding context.
+    let changed: Vec<usize> = (0..ops.len()).filter(|&k| ops[k].0 != ' ').collect();
+    let mut ranges: Vec<(usize, usize)> = Vec::new();
+    for k in changed {
+        let start = k.saturating_sub(DIFF_CONTEXT_LINES);
+        let end = (k + DIFF_CONTEXT_LINES + 1).min(ops.len());
+        match ranges.last_mut() {
+            Some(last) if start <= last.1 => last.1 = end,
+            _ => ranges.push((start, end)),
+        }
+    }
+
+    for (start, end) in ranges {
+        let before = &ops[..start];
+        let old_start = before.iter().filter(|(op, _)| *op != '+').count() + 1;
+        let new_start = before.iter().filter(|(op, _)| *op != '-').count() + 1;
+        let hunk = &ops[start..end];
+        let old_len = hunk.iter().filter(|(op, _)| *op != '+').count();
+        let new_len = hunk.iter().filter(|(op, _)| *op != '-').count();
+        out.push_str(&format!(
+            "@@ -{},{} +{},{} @@\n",
+            if old_len == 0 {
+                old_start - 1
+            } else {
+                old_start
+            },
+            old_len,
+            if new_len == 0 {
+                new_start - 1
+            } else {
+                new_start
+            },
+            new_len
+        ));
+        for (op, line) in hunk {
+            out.push(*op);
+            out.push_str(line);
+            out.push('\n');
+        }
+    }
+
+    out
 }
 
 impl Default for FileOperationEngine {
@@ -1677,6 +2542,7 @@ impl Default for FileOperationEngine {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use serial_test::serial;
 
     #[test]
     fn test_get_type_folder() {
@@ -1730,6 +2596,271 @@ mod tests {
         assert_eq!(version.description, "Test version");
     }
 
+    #[test]
+    fn test_unified_diff_hunks() {
+        let old = "a\nb\nc\nd\ne\nf\ng\nh\n";
+        let new = "a\nb\nC\nd\ne\nf\ng\nh\ni\n";
+        let diff = unified_diff("/w/f.txt", old, new);
+        assert_eq!(
+            diff,
+            "--- /w/f.txt\n+++ /w/f.txt\n@@ -1,8 +1,9 @@\n a\n b\n-c\n+C\n d\n e\n f\n g\n h\n+i\n"
+        );
+        assert_eq!(
+            unified_diff("/w/new.txt", "", "x\n"),
+            "--- /w/new.txt\n+++ /w/new.txt\n@@ -0,0 +1,1 @@\n+x\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_run_checkpoint_revert_restores_pre_run_state() {
+        let data = tempfile::tempdir().unwrap();
+        let workspace = tempfile::tempdir().unwrap();
+        let engine = FileOperationEngine::with_base_dir(data.path().to_path_buf());
+
+        let edited = workspace.path().join("a.txt");
+        let deleted = workspace.path().join("b.txt");
+        let created = workspace.path().join("new/c.txt");
+        std::fs::write(&edited, "one\n").unwrap();
+        std::fs::write(&deleted, "keep\n").unwrap();
+
+        engine.checkpoint_path("run-1", &edited).await.unwrap();
+        std::fs::write(&edited, "two\n").unwrap();
+        engine.checkpoint_path("run-1", &edited).await.unwrap();
+        engine.checkpoint_path("run-1", &created).await.unwrap();
+        std::fs::create_dir_all(created.parent().unwrap()).unwrap();
+        std::fs::write(&created, "fresh\n").unwrap();
+        engine.checkpoint_path("run-1", &deleted).await.unwrap();
+        std::fs::remove_file(&deleted).unwrap();
+        engine.finish_run_checkpoint("run-1").await.unwrap();
+
+        let preview = engine.preview_run_revert("run-1").await.unwrap();
+        assert_eq!(preview.state, TransactionState::Committed);
+        let statuses: Vec<RunFileStatus> = preview.changes.iter().map(|c| c.status).collect();
+        assert_eq!(
+            statuses,
+            vec![
+                RunFileStatus::Created,
+                RunFileStatus::Modified,
+                RunFileStatus::Deleted
+            ]
+        );
+        assert!(preview.changes[1]
+            .diff
+            .as_deref()
+            .is_some_and(|d| d.contains("-one\n+two\n")));
+
+        engine.revert_run("run-1").await.unwrap();
+        assert_eq!(std::fs::read_to_string(&edited).unwrap(), "one\n");
+        assert_eq!(std::fs::read_to_string(&deleted).unwrap(), "keep\n");
+        assert!(!workspace.path().join("new").exists());
+        assert!(engine.revert_run("run-1").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_run_without_changes_leaves_no_checkpoint() {
+        let data = tempfile::tempdir().unwrap();
+        let engine = FileOperationEngine::with_base_dir(data.path().to_path_buf());
+        engine.begin_run_checkpoint("idle").await.unwrap();
+        engine.finish_run_checkpoint("idle").await.unwrap();
+        assert!(engine.preview_run_revert("idle").await.is_err());
+    }
+
+    async fn history_pool() -> Pool<Sqlite> {
+        // Initialize libSQL's C state before sqlx, as in verification_test.
+        let _ = libsql::Builder::new_local(":memory:").build().await;
+        let pool = sqlx::sqlite::SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+        pool
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_history_survives_restart() {
+        let data = tempfile::tempdir().unwrap();
+        let workspace = tempfile::tempdir().unwrap();
+        let pool = history_pool().await;
+        let file = workspace.path().join("notes.txt");
+        std::fs::write(&file, "before\n").unwrap();
+
+        let engine = FileOperationEngine::with_base_dir(data.path().to_path_buf());
+        engine.attach_history_store(pool.clone()).await.unwrap();
+        engine.checkpoint_path("run-7", &file).await.unwrap();
+        std::fs::write(&file, "after\n").unwrap();
+        engine.finish_run_checkpoint("run-7").await.unwrap();
+        drop(engine);
+
+        let restarted = FileOperationEngine::with_base_dir(data.path().to_path_buf());
+        restarted.attach_history_store(pool).await.unwrap();
+
+        let by_run = restarted
+            .query_operations(&FileOperationQuery {
+                run_id: Some("run-7".to_string()),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(by_run.len(), 1);
+        assert_eq!(by_run[0].status, OperationStatus::Applied);
+        assert_eq!(restarted.list_enhanced_operations().len(), 1);
+
+        let future = restarted
+            .query_operations(&FileOperationQuery {
+                since: Some(Utc::now() + chrono::Duration::hours(1)),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert!(future.is_empty());
+
+        let versions = restarted
+            .get_file_version_info(&file.to_string_lossy())
+            .await
+            .unwrap();
+        assert_eq!(versions.total_versions, 1);
+        assert_eq!(versions.versions[0].description, "Before agent run run-7");
+
+        restarted.revert_run("run-7").await.unwrap();
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), "before\n");
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_garbage_collection_removes_orphans() {
+        let data = tempfile::tempdir().unwrap();
+        let workspace = tempfile::tempdir().unwrap();
+        let engine = FileOperationEngine::with_base_dir(data.path().to_path_buf());
+        engine.init().await.unwrap();
+        engine
+            .attach_history_store(history_pool().await)
+            .await
+            .unwrap();
+
+        let file = workspace.path().join("kept.txt");
+        std::fs::write(&file, "keep\n").unwrap();
+        engine.checkpoint_path("run-1", &file).await.unwrap();
+        std::fs::write(&file, "changed\n").unwrap();
+        engine.finish_run_checkpoint("run-1").await.unwrap();
+
+        let stray_trash = engine.trash_dir.join("orphan_file.txt");
+        std::fs::write(&stray_trash, "gone").unwrap();
+        std::fs::File::options()
+            .write(true)
+            .open(&stray_trash)
+            .unwrap()
+            .set_modified(
+                std::time::SystemTime::now() - std::time::Duration::from_secs(90 * 86_400),
+            )
+            .unwrap();
+        let fresh_trash = engine.trash_dir.join("fresh_orphan.txt");
+        std::fs::write(&fresh_trash, "recent").unwrap();
+        let fresh_version = engine
+            .versions_dir
+            .join("fresh.txt_v001_20260101_000000.backup");
+        std::fs::write(&fresh_version, "fresh").unwrap();
+        let stale_version = engine
+            .versions_dir
+            .join("stale.txt_v001_20200101_000000.backup");
+        std::fs::write(&stale_version, "stale").unwrap();
+        std::fs::File::options()
+            .write(true)
+            .open(&stale_version)
+            .unwrap()
+            .set_modified(
+                std::time::SystemTime::now() - std::time::Duration::from_secs(90 * 86_400),
+            )
+            .unwrap();
+
+        let report = engine
+            .collect_garbage(&RetentionPolicy::default())
+            .await
+            .unwrap();
+        assert_eq!(report.operations_removed, 0);
+        assert_eq!(report.trash_entries_removed, 1);
+        assert_eq!(report.versions_removed, 1);
+        assert!(!stray_trash.exists());
+        assert!(fresh_trash.exists());
+        assert!(!stale_version.exists());
+        assert!(fresh_version.exists());
+
+        engine.revert_run("run-1").await.unwrap();
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), "keep\n");
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_garbage_collection_keeps_undoable_deletes() {
+        let data = tempfile::tempdir().unwrap();
+        let workspace = tempfile::tempdir().unwrap();
+        let engine = FileOperationEngine::with_base_dir(data.path().to_path_buf());
+        engine.init().await.unwrap();
+        engine
+            .attach_history_store(history_pool().await)
+            .await
+            .unwrap();
+        engine
+            .set_workspace(Workspace {
+                id: "ws-gc".to_string(),
+                name: "GC".to_string(),
+                allowed_paths: vec![workspace.path().to_string_lossy().to_string()],
+                permissions: crate::services::workspace::WorkspacePermissions {
+                    can_read: true,
+                    can_write: true,
+                    can_execute: false,
+                    can_delete: true,
+                    can_create_agents: false,
+                },
+                permission_overrides: Vec::new(),
+                agents: Vec::new(),
+                memory: crate::services::workspace::WorkspaceMemory {
+                    max_size: 0,
+                    current_size: 0,
+                    retention_policy: "fifo".to_string(),
+                },
+                settings: crate::services::workspace::WorkspaceSettings {
+                    theme: "default".to_string(),
+                    language: "en".to_string(),
+                    auto_save: false,
+                    notifications_enabled: false,
+                },
+            })
+            .await;
+
+        // An old file keeps its mtime when moved to the trash
+        let file = workspace.path().join("old.txt");
+        std::fs::write(&file, "precious\n").unwrap();
+        std::fs::File::options()
+            .write(true)
+            .open(&file)
+            .unwrap()
+            .set_modified(
+                std::time::SystemTime::now() - std::time::Duration::from_secs(90 * 86_400),
+            )
+            .unwrap();
+
+        let path = file.to_string_lossy().to_string();
+        let (deleted, report) = tokio::join!(
+            engine.safe_delete(vec![path]),
+            engine.collect_garbage(&RetentionPolicy::default())
+        );
+        assert_eq!(deleted.unwrap().len(), 1);
+        assert_eq!(report.unwrap().trash_entries_removed, 0);
+        assert!(!file.exists());
+
+        let report = engine
+            .collect_garbage(&RetentionPolicy::default())
+            .await
+            .unwrap();
+        assert_eq!(report.trash_entries_removed, 0);
+
+        let (operation_id, _, _) = engine.list_operations().pop().unwrap();
+        engine.undo_operation(&operation_id).await.unwrap();
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), "precious\n");
+    }
+
     #[test]
     fn test_file_operation_types() {
         assert_eq!(FileOpType::Move as u8, 0);
@@ -0,0 +1,505 @@
+//! SQLite persistence for `FileOperationEngine` history.
+//!
+//! The engine's maps stay the working copy; every change is written through
+//! to these tables and `FileOperationEngine::attach_history_store` rebuilds
+//! the maps from them on startup.
+
+use super::{
+    EnhancedOperationRecord, FileOpChange, FileOperationEntry, FileOperationQuery, FileVersion,
+    OperationOrigin, OperationStatus, Transaction, TransactionState,
+};
+use chrono::{DateTime, Utc};
+use serde::{de::DeserializeOwned, Serialize};
+use sqlx::{Pool, QueryBuilder, Sqlite};
+
+/// Most rows a single history query returns
+const MAX_QUERY_LIMIT: u32 = 1000;
+const DEFAULT_QUERY_LIMIT: u32 = 100;
+
+const OPERATION_COLUMNS: &str = "id, description, workspace_id, task_id, run_id, transaction_id, \
+     changes_json, versions_json, undoable, status, created_at";
+
+type OperationRow = (
+    String,
+    String,
+    Option<String>,
+    Option<String>,
+    Option<String>,
+    Option<String>,
+    String,
+    String,
+    bool,
+    String,
+    i64,
+);
+
+type TransactionRow = (
+    String,
+    String,
+    String,
+    Option<String>,
+    Option<String>,
+    Option<String>,
+    String,
+    String,
+    i64,
+    Option<i64>,
+);
+
+type VersionRow = (String, String, i64, String, String, i64, String, i64);
+
+/// A persisted operation together with the ledgers it is still listed in
+pub(super) struct StoredOperation {
+    pub record: EnhancedOperationRecord,
+    /// Listed in the basic `history` map
+    pub undoable: bool,
+    pub status: OperationStatus,
+}
+
+/// One entry of a persisted undo/redo stack, bottom first
+pub(super) struct StoredStackEntry {
+    pub stack: String,
+    pub stack_key: String,
+    pub operation_id: String,
+    pub can_redo: bool,
+}
+
+#[derive(Clone)]
+pub(super) struct FileHistoryStore {
+    db: Pool<Sqlite>,
+}
+
+impl FileHistoryStore {
+    pub fn new(db: Pool<Sqlite>) -> Self {
+        Self { db }
+    }
+
+    // ============ Operations ============
+
+    pub async fn upsert_operation(
+        &self,
+        record: &EnhancedOperationRecord,
+        undoable: bool,
+        status: OperationStatus,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "INSERT INTO file_operations (id, description, workspace_id, task_id, run_id, transaction_id,
+                changes_json, versions_json, undoable, status, created_at)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+             ON CONFLICT(id) DO UPDATE SET
+                changes_json = excluded.changes_json,
+                versions_json = excluded.versions_json,
+                undoable = excluded.undoable,
+                status = excluded.status",
+        )
+        .bind(&record.id)
+        .bind(&record.description)
+        .bind(&record.origin.workspace_id)
+        .bind(&record.origin.task_id)
+        .bind(&record.origin.run_id)
+        .bind(&record.transaction_id)
+        .bind(to_json(&record.changes)?)
+        .bind(to_json(&record.versions_created)?)
+        .bind(undoable)
+        .bind(status.as_str())
+        .bind(record.timestamp.timestamp_millis())
+        .execute(&self.db)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn set_operation_ledgers(
+        &self,
+        operation_id: &str,
+        undoable: bool,
+        status: OperationStatus,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query("UPDATE file_operations SET undoable = ?, status = ? WHERE id = ?")
+            .bind(undoable)
+            .bind(status.as_str())
+            .bind(operation_id)
+            .execute(&self.db)
+            .await?;
+        Ok(())
+    }
+
+    /// Every persisted operation, oldest first
+    pub async fn load_operations(&self) -> Result<Vec<StoredOperation>, sqlx::Error> {
+        let rows = sqlx::query_as::<_, OperationRow>(&format!(
+            "SELECT {} FROM file_operations ORDER BY created_at ASC",
+            OPERATION_COLUMNS
+        ))
+        .fetch_all(&self.db)
+        .await?;
+        rows.into_iter().map(stored_operation).collect()
+    }
+
+    /// Operations matching `query`, newest first
+    pub async fn query_operations(
+        &self,
+        query: &FileOperationQuery,
+    ) -> Result<Vec<FileOperationEntry>, sqlx::Error> {
+        let mut builder = QueryBuilder::<Sqlite>::new(format!(
+            "SELECT {} FROM file_operations WHERE 1 = 1",
+            OPERATION_COLUMNS
+        ));
+        if let Some(workspace_id) = &query.workspace_id {
+            builder
+                .push(" AND workspace_id = ")
+                .push_bind(workspace_id.clone());
+        }
+        if let Some(task_id) = &query.task_id {
+            builder.push(" AND task_id = ").push_bind(task_id.clone());
+        }
+        if let Some(run_id) = &query.run_id {
+            builder.push(" AND run_id = ").push_bind(run_id.clone());
+        }
+        if let Some(since) = query.since {
+            builder
+                .push(" AND created_at >= ")
+                .push_bind(since.timestamp_millis());
+        }
+        if let Some(until) = query.until {
+            builder
+                .push(" AND created_at < ")
+                .push_bind(until.timestamp_millis());
+        }
+        let limit = query
+            .limit
+            .unwrap_or(DEFAULT_QUERY_LIMIT)
+            .clamp(1, MAX_QUERY_LIMIT);
+        builder
+            .push(" ORDER BY created_at DESC LIMIT ")
+            .push_bind(i64::from(limit));
+
+        let rows: Vec<OperationRow> = builder.build_query_as().fetch_all(&self.db).await?;
+        rows.into_iter()
+            .map(|row| {
+                let stored = stored_operation(row)?;
+                Ok(FileOperationEntry {
+                    id: stored.record.id,
+                    description: stored.record.description,
+                    timestamp: stored.record.timestamp,
+                    origin: stored.record.origin,
+                    transaction_id: stored.record.transaction_id,
+                    status: stored.status,
+                    undoable: stored.undoable,
+                    changes: stored.record.changes,
+                })
+            })
+            .collect()
+    }
+
+    /// Drop operations recorded before `cutoff`, with their stack entries
+    pub async fn prune_operations(&self, cutoff: DateTime<Utc>) -> Result<u64, sqlx::Error> {
+        let cutoff = cutoff.timestamp_millis();
+        let mut tx = self.db.begin().await?;
+        sqlx::query(
+            "DELETE FROM file_history_stacks WHERE operation_id IN
+                (SELECT id FROM file_operations WHERE created_at < ?)",
+        )
+        .bind(cutoff)
+        .execute(&mut *tx)
+        .await?;
+        let removed = sqlx::query("DELETE FROM file_operations WHERE created_at < ?")
+            .bind(cutoff)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+        tx.commit().await?;
+        Ok(removed)
+    }
+
+    // ============ Undo/Redo Stacks ============
+
+    /// Replace one stack with `entries` (operation id, can_redo), bottom first
+    pub async fn replace_stack(
+        &self,
+        stack: &str,
+        stack_key: &str,
+        entries: &[(String, bool)],
+    ) -> Result<(), sqlx::Error> {
+        let mut tx = self.db.begin().await?;
+        sqlx::query("DELETE FROM file_history_stacks WHERE stack = ? AND stack_key = ?")
+            .bind(stack)
+            .bind(stack_key)
+            .execute(&mut *tx)
+            .await?;
+        for (position, (operation_id, can_redo)) in entries.iter().enumerate() {
+            sqlx::query(
+                "INSERT INTO file_history_stacks (stack, stack_key, position, operation_id, can_redo)
+                 VALUES (?, ?, ?, ?, ?)",
+            )
+            .bind(stack)
+            .bind(stack_key)
+            .bind(position as i64)
+            .bind(operation_id)
+            .bind(*can_redo)
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    pub async fn load_stacks(&self) -> Result<Vec<StoredStackEntry>, sqlx::Error> {
+        let rows = sqlx::query_as::<_, (String, String, String, bool)>(
+            "SELECT stack, stack_key, operation_id, can_redo FROM file_history_stacks
+             ORDER BY stack, stack_key, position ASC",
+        )
+        .fetch_all(&self.db)
+        .await?;
+        Ok(rows
+            .into_iter()
+            .map(
+                |(stack, stack_key, operation_id, can_redo)| StoredStackEntry {
+                    stack,
+                    stack_key,
+                    operation_id,
+                    can_redo,
+                },
+            )
+            .collect())
+    }
+
+    // ============ Transactions ============
+
+    pub async fn upsert_transaction(&self, transaction: &Transaction) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "INSERT INTO file_transactions (id, description, state, workspace_id, task_id, run_id,
+                operations_json, snapshots_json, start_time, end_time)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+             ON CONFLICT(id) DO UPDATE SET
+                state = excluded.state,
+                operations_json = excluded.operations_json,
+                snapshots_json = excluded.snapshots_json,
+                end_time = excluded.end_time",
+        )
+        .bind(&transaction.id)
+        .bind(&transaction.description)
+        .bind(transaction_state_str(&transaction.state))
+        .bind(&transaction.origin.workspace_id)
+        .bind(&transaction.origin.task_id)
+        .bind(&transaction.origin.run_id)
+        .bind(to_json(&transaction.operations)?)
+        .bind(to_json(&transaction.snapshots)?)
+        .bind(transaction.start_time.timestamp_millis())
+        .bind(transaction.end_time.map(|t| t.timestamp_millis()))
+        .execute(&self.db)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn delete_transaction(&self, transaction_id: &str) -> Result<(), sqlx::Error> {
+        sqlx::query("DELETE FROM file_transactions WHERE id = ?")
+            .bind(transaction_id)
+            .execute(&self.db)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn load_transactions(&self) -> Result<Vec<Transaction>, sqlx::Error> {
+        let rows = sqlx::query_as::<_, TransactionRow>(
+            "SELECT id, description, state, workspace_id, task_id, run_id,
+                operations_json, snapshots_json, start_time, end_time
+             FROM file_transactions ORDER BY start_time ASC",
+        )
+        .fetch_all(&self.db)
+        .await?;
+        rows.into_iter()
+            .map(
+                |(
+                    id,
+                    description,
+                    state,
+                    workspace_id,
+                    task_id,
+                    run_id,
+                    operations_json,
+                    snapshots_json,
+                    start_time,
+                    end_time,
+                )| {
+                    Ok(Transaction {
+                        id,
+                        description,
+                        state: parse_transaction_state(&state)?,
+                        start_time: from_millis(start_time),
+                        end_time: end_time.map(from_millis),
+                        operations: from_json(&operations_json)?,
+                        snapshots: from_json(&snapshots_json)?,
+                        origin: OperationOrigin {
+                            workspace_id,
+                            task_id,
+                            run_id,
+                        },
+                    })
+                },
+            )
+            .collect()
+    }
+
+    /// Drop closed transactions that ended before `cutoff`
+    pub async fn prune_transactions(&self, cutoff: DateTime<Utc>) -> Result<u64, sqlx::Error> {
+        let removed = sqlx::query(
+            "DELETE FROM file_transactions
+             WHERE state != 'active' AND COALESCE(end_time, start_time) < ?",
+        )
+        .bind(cutoff.timestamp_millis())
+        .execute(&self.db)
+        .await?
+        .rows_affected();
+        Ok(removed)
+    }
+
+    // ============ Versions ============
+
+    pub async fn insert_version(&self, version: &FileVersion) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "INSERT OR REPLACE INTO file_versions (id, file_path, version_number, version_path,
+                content_hash, size, description, created_at)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(&version.id)
+        .bind(&version.file_path)
+        .bind(i64::from(version.version_number))
+        .bind(&version.version_path)
+        .bind(&version.content_hash)
+        .bind(version.size as i64)
+        .bind(&version.description)
+        .bind(version.timestamp.timestamp_millis())
+        .execute(&self.db)
+        .await?;
+        Ok(())
+    }
+
+    /// Versions of one file, oldest first; `None` lists every version
+    pub async fn load_versions(
+        &self,
+        file_path: Option<&str>,
+    ) -> Result<Vec<FileVersion>, sqlx::Error> {
+        let mut builder = QueryBuilder::<Sqlite>::new(
+            "SELECT id, file_path, version_number, version_path, content_hash, size, description,
+                created_at FROM file_versions",
+        );
+        if let Some(file_path) = file_path {
+            builder
+                .push(" WHERE file_path = ")
+                .push_bind(file_path.to_string());
+        }
+        builder.push(" ORDER BY version_number ASC, created_at ASC");
+
+        let rows: Vec<VersionRow> = builder.build_query_as().fetch_all(&self.db).await?;
+        Ok(rows
+            .into_iter()
+            .map(
+                |(
+                    id,
+                    file_path,
+                    version_number,
+                    version_path,
+                    content_hash,
+                    size,
+                    description,
+                    created_at,
+                )| FileVersion {
+                    id,
+                    file_path,
+                    version_number: version_number as u32,
+                    timestamp: from_millis(created_at),
+                    description,
+                    content_hash,
+                    size: size as u64,
+                    version_path,
+                },
+            )
+            .collect())
+    }
+
+    pub async fn delete_version(&self, version_id: &str) -> Result<(), sqlx::Error> {
+        sqlx::query("DELETE FROM file_versions WHERE id = ?")
+            .bind(version_id)
+            .execute(&self.db)
+            .await?;
+        Ok(())
+    }
+}
+
+impl OperationStatus {
+    fn as_str(self) -> &'static str {
+        match self {
+            OperationStatus::Applied => "applied",
+            OperationStatus::Undone => "undone",
+        }
+    }
+}
+
+fn stored_operation(row: OperationRow) -> Result<StoredOperation, sqlx::Error> {
+    let (
+        id,
+        description,
+        workspace_id,
+        task_id,
+        run_id,
+        transaction_id,
+        changes_json,
+        versions_json,
+        undoable,
+        status,
+        created_at,
+    ) = row;
+    let changes: Vec<FileOpChange> = from_json(&changes_json)?;
+    Ok(StoredOperation {
+        record: EnhancedOperationRecord {
+            id,
+            description,
+            timestamp: from_millis(created_at),
+            changes,
+            transaction_id,
+            versions_created: from_json(&versions_json)?,
+            origin: OperationOrigin {
+                workspace_id,
+                task_id,
+                run_id,
+            },
+        },
+        undoable,
+        status: match status.as_str() {
+            "undone" => OperationStatus::Undone,
+            _ => OperationStatus::Applied,
+        },
+    })
+}
+
+fn transaction_state_str(state: &TransactionState) -> &'static str {
+    match state {
+        TransactionState::Active => "active",
+        TransactionState::Committed => "committed",
+        TransactionState::RolledBack => "rolled_back",
+        TransactionState::Failed => "failed",
+    }
+}
+
+fn parse_transaction_state(state: &str) -> Result<TransactionState, sqlx::Error> {
+    match state {
+        "active" => Ok(TransactionState::Active),
+        "committed" => Ok(TransactionState::Committed),
+        "rolled_back" => Ok(TransactionState::RolledBack),
+        "failed" => Ok(TransactionState::Failed),
+        other => Err(sqlx::Error::Decode(
+            format!("unknown transaction state '{}'", other).into(),
+        )),
+    }
+}
+
+fn from_millis(millis: i64) -> DateTime<Utc> {
+    DateTime::from_timestamp_millis(millis).unwrap_or_default()
+}
+
+fn to_json<T: Serialize>(value: &T) -> Result<String, sqlx::Error> {
+    serde_json::to_string(value).map_err(|e| sqlx::Error::Encode(Box::new(e)))
+}
+
+fn from_json<T: DeserializeOwned>(json: &str) -> Result<T, sqlx::Error> {
+    serde_json::from_str(json).map_err(|e| sqlx::Error::Decode(Box::new(e)))
+}
@@ -0,0 +1,512 @@
+//! Per-agent knowledge bases
+//!
+//! Indexes the files listed in an agent spec's `KnowledgeConfig` into a vault
+//! partition of their own (`knowledge:<agent_id>`), re-indexes a file only
+//! when its content hash changes, and watches the files so edits are picked
+//! up without re-saving the spec.
+
+use crate::ai::specs::manifest::{KnowledgeConfig, KnowledgeFile};
+use crate::services::memory::{ChunkOptions, MemoryManager, SearchOptions, SemanticSearchResult};
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{mpsc, Mutex, OwnedMutexGuard};
+
+const STATE_FILE: &str = "knowledge_index.json";
+const MAX_KNOWLEDGE_FILE_BYTES: u64 = 25 * 1024 * 1024;
+/// Editors often write a file in several steps; wait for them to settle.
+const WATCH_DEBOUNCE_MS: u64 = 750;
+
+/// Vault partition holding an agent's knowledge files.
+pub fn knowledge_partition(agent_id: &str) -> String {
+    format!("knowledge:{}", agent_id)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct IndexedFile {
+    id: String,
+    content_hash: String,
+    size_bytes: u64,
+    indexed_at: u64,
+    chunk_count: u32,
+}
+
+/// agent id -> file path -> what was last indexed.
+type IndexState = HashMap<String, HashMap<String, IndexedFile>>;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KnowledgeIndexOutcome {
+    pub file: KnowledgeFile,
+    /// False when the content hash matched the last index and nothing was re-ingested.
+    pub reindexed: bool,
+    pub warnings: Vec<String>,
+}
+
+#[derive(Clone)]
+pub struct KnowledgeBase {
+    manager: Arc<MemoryManager>,
+    state_path: PathBuf,
+    state: Arc<Mutex<IndexState>>,
+    /// One lock per agent so a sync, a watcher re-index and an explicit
+    /// index of the same file never interleave their delete and ingest.
+    agent_locks: Arc<Mutex<HashMap<String, Arc<Mutex<()>>>>>,
+    /// Dropping a watcher closes its event channel, which ends its re-index task.
+    watchers: Arc<Mutex<HashMap<String, RecommendedWatcher>>>,
+}
+
+impl KnowledgeBase {
+    pub fn new(manager: Arc<MemoryManager>, app_data_dir: &Path) -> Self {
+        let state_path = app_data_dir.join(STATE_FILE);
+        let state = std::fs::read_to_string(&state_path)
+            .ok()
+            .and_then(|body| serde_json::from_str(&body).ok())
+            .unwrap_or_default();
+        Self {
+            manager,
+            state_path,
+            state: Arc::new(Mutex::new(state)),
+            agent_locks: Arc::new(Mutex::new(HashMap::new())),
+            watchers: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    async fn lock_agent(&self, agent_id: &str) -> OwnedMutexGuard<()> {
+        let lock = self
+            .agent_locks
+            .lock()
+            .await
+            .entry(agent_id.to_string())
+            .or_default()
+            .clone();
+        lock.lock_owned().await
+    }
+
+    /// Index one file into the agent's partition. Unchanged files (same
+    /// content hash) are not re-ingested.
+    pub async fn index_file(
+        &self,
+        agent_id: &str,
+        path: &str,
+    ) -> Result<KnowledgeIndexOutcome, String> {
+        let _guard = self.lock_agent(agent_id).await;
+        self.index_file_locked(agent_id, path).await
+    }
+
+    async fn index_file_locked(
+        &self,
+        agent_id: &str,
+        path: &str,
+    ) -> Result<KnowledgeIndexOutcome, String> {
+        let bytes = read_knowledge_bytes(Path::new(path)).await?;
+        let content_hash = hex::encode(Sha256::digest(&bytes));
+        let size_bytes = bytes.len() as u64;
+
+        let previous = self
+            .state
+            .lock()
+            .await
+            .get(agent_id)
+            .and_then(|files| files.get(path))
+            .cloned();
+        if let Some(previous) = previous.as_ref() {
+            if previous.content_hash == content_hash {
+                return Ok(KnowledgeIndexOutcome {
+                    file: knowledge_file(path, previous),
+                    reindexed: false,
+                    warnings: Vec::new(),
+                });
+            }
+        }
+
+        let text = extract_text(path, bytes).await?;
+        if text.trim().is_empty() {
+            return Err(format!("No indexable text content found in {}", path));
+        }
+
+        let partition = knowledge_partition(agent_id);
+        let file_id = previous
+            .as_ref()
+            .map(|p| p.id.clone())
+            .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+        let name = file_name(path);
+        let tags = vec![
+            "knowledge".to_string(),
+            format!("agent:{}", agent_id),
+            format!("knowledge_file:{}", file_id),
+            format!("knowledge_file_name:{}", name),
+            format!("knowledge_file_path:{}", path),
+        ];
+
+        self.manager
+            .delete_source(&partition, path)
+            .await
+            .map_err(|e| e.to_string())?;
+        let result = self
+            .manager
+            .ingest_text_detailed(
+                &partition,
+                path,
+                &text,
+                Some(tags),
+                &ChunkOptions::default(),
+            )
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let indexed = IndexedFile {
+            id: file_id,
+            content_hash,
+            size_bytes,
+            indexed_at: chrono::Utc::now().timestamp().max(0) as u64,
+            chunk_count: result.chunks_ingested as u32,
+        };
+        let file = knowledge_file(path, &indexed);
+        {
+            let mut state = self.state.lock().await;
+            state
+                .entry(agent_id.to_string())
+                .or_default()
+                .insert(path.to_string(), indexed);
+            self.save_state(&state).await;
+        }
+
+        Ok(KnowledgeIndexOutcome {
+            file,
+            reindexed: true,
+            warnings: result.warnings,
+        })
+    }
+
+    /// Drop a file's chunks from the agent's partition.
+    pub async fn remove_file(&self, agent_id: &str, path: &str) -> Result<(), String> {
+        let _guard = self.lock_agent(agent_id).await;
+        self.remove_file_locked(agent_id, path).await
+    }
+
+    async fn remove_file_locked(&self, agent_id: &str, path: &str) -> Result<(), String> {
+        self.manager
+            .delete_source(&knowledge_partition(agent_id), path)
+            .await
+            .map_err(|e| e.to_string())?;
+        let mut state = self.state.lock().await;
+        if let Some(files) = state.get_mut(agent_id) {
+            if files.remove(path).is_some() {
+                self.save_state(&state).await;
+            }
+        }
+        Ok(())
+    }
+
+    /// Bring the agent's partition in line with its spec: re-index changed
+    /// files, drop files no longer listed, and (re)start watching. Only files
+    /// first added through [`index_file`](Self::index_file) are read, so a
+    /// spec from elsewhere cannot pull arbitrary local files into the prompt.
+    /// Returns the listed files with current chunk counts; files that are
+    /// skipped or fail to index keep their previous entry.
+    pub async fn sync(&self, agent_id: &str, config: &KnowledgeConfig) -> Vec<KnowledgeFile> {
+        let _guard = self.lock_agent(agent_id).await;
+        let listed = config
+            .indexed_files
+            .iter()
+            .map(|f| f.path.clone())
+            .collect::<HashSet<_>>();
+        let known = self
+            .state
+            .lock()
+            .await
+            .get(agent_id)
+            .map(|files| files.keys().cloned().collect::<HashSet<_>>())
+            .unwrap_or_default();
+
+        let stale = known.difference(&listed).cloned().collect::<Vec<_>>();
+        for path in stale {
+            if let Err(e) = self.remove_file_locked(agent_id, &path).await {
+                tracing::warn!("Failed to drop knowledge file {}: {}", path, e);
+            }
+        }
+
+        let mut files = Vec::with_capacity(config.indexed_files.len());
+        for file in &config.indexed_files {
+            if !known.contains(&file.path) {
+                tracing::warn!(
+                    "Not indexing knowledge file {} for {}: it was not added through the app",
+                    file.path,
+                    agent_id
+                );
+                files.push(file.clone());
+                continue;
+            }
+            match self.index_file_locked(agent_id, &file.path).await {
+                Ok(outcome) => files.push(outcome.file),
+                Err(e) => {
+                    tracing::warn!("Failed to index knowledge file {}: {}", file.path, e);
+                    files.push(file.clone());
+                }
+            }
+        }
+
+        if config.enabled {
+            self.watch(agent_id, listed.intersection(&known).cloned().collect())
+                .await;
+        } else {
+            self.unwatch(agent_id).await;
+        }
+        files
+    }
+
+    /// Search only this agent's knowledge.
+    pub async fn search(
+        &self,
+        agent_id: &str,
+        query: &str,
+        limit: usize,
+        options: &SearchOptions,
+    ) -> Result<SemanticSearchResult, String> {
+        self.manager
+            .search_with_options(&knowledge_partition(agent_id), query, limit, options)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    pub async fn unwatch(&self, agent_id: &str) {
+        self.watchers.lock().await.remove(agent_id);
+    }
+
+    /// Watch the files' parent directories (so atomic-rename saves are seen)
+    /// and re-index listed files when they change.
+    async fn watch(&self, agent_id: &str, paths: Vec<String>) {
+        // Spec paths keyed by their resolved form, which is what the watcher reports.
+        let tracked = paths
+            .into_iter()
+            .map(|path| (canonical_path(Path::new(&path)), path))
+            .collect::<HashMap<_, _>>();
+        if tracked.is_empty() {
+            self.unwatch(agent_id).await;
+            return;
+        }
+
+        let (tx, mut rx) = mpsc::unbounded_channel::<PathBuf>();
+        let mut watcher =
+            match notify::recommended_watcher(move |res: notify::Result<notify::Event>| {
+                if let Ok(event) = res {
+                    if event.kind.is_create() || event.kind.is_modify() || event.kind.is_remove() {
+                        for path in event.paths {
+                            let _ = tx.send(path);
+                        }
+                    }
+                }
+            }) {
+                Ok(watcher) => watcher,
+                Err(e) => {
+                    tracing::warn!("Failed to watch knowledge files for {}: {}", agent_id, e);
+                    return;
+                }
+            };
+
+        let dirs = tracked
+            .values()
+            .filter_map(|path| Path::new(path).parent().map(Path::to_path_buf))
+            .collect::<HashSet<_>>();
+        for dir in dirs {
+            if let Err(e) = watcher.watch(&dir, RecursiveMode::NonRecursive) {
+                tracing::warn!("Failed to watch {}: {}", dir.display(), e);
+            }
+        }
+        self.watchers
+            .lock()
+            .await
+            .insert(agent_id.to_string(), watcher);
+
+        let knowledge = self.clone();
+        let agent_id = agent_id.to_string();
+        tokio::spawn(async move {
+            while let Some(first) = rx.recv().await {
+                let mut changed = HashSet::from([first]);
+                tokio::time::sleep(Duration::from_millis(WATCH_DEBOUNCE_MS)).await;
+                while let Ok(path) = rx.try_recv() {
+                    changed.insert(path);
+                }
+
+                for changed_path in changed {
+                    let Some(path) = tracked.get(&canonical_path(&changed_path)) else {
+                        continue;
+                    };
+                    let result = if Path::new(path).exists() {
+                        knowledge.index_file(&agent_id, path).await.map(|_| ())
+                    } else {
+                        knowledge.remove_file(&agent_id, path).await
+                    };
+                    if let Err(e) = result {
+                        tracing::warn!("Failed to re-index knowledge file {}: {}", path, e);
+                    }
+                }
+            }
+        });
+    }
+
+    async fn save_state(&self, state: &IndexState) {
+        let body = match serde_json::to_string_pretty(state) {
+            Ok(body) => body,
+            Err(e) => {
+                tracing::warn!("Failed to serialize knowledge index state: {}", e);
+                return;
+            }
+        };
+        if let Err(e) = tokio::fs::write(&self.state_path, body).await {
+            tracing::warn!("Failed to persist knowledge index state: {}", e);
+        }
+    }
+}
+
+async fn read_knowledge_bytes(path: &Path) -> Result<Vec<u8>, String> {
+    let metadata = tokio::fs::metadata(path)
+        .await
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    if metadata.len() > MAX_KNOWLEDGE_FILE_BYTES {
+        return Err(format!(
+            "File too large for indexing ({} bytes > {} bytes limit)",
+            metadata.len(),
+            MAX_KNOWLEDGE_FILE_BYTES
+        ));
+    }
+    tokio::fs::read(path)
+        .await
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))
+}
+
+/// Text of a knowledge or workspace file; PDFs go through `pdf-extract`.
+pub(crate) async fn extract_text(path: &str, bytes: Vec<u8>) -> Result<String, String> {
+    let is_pdf = Path::new(path)
+        .extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| e.eq_ignore_ascii_case("pdf"));
+    if !is_pdf {
+        return Ok(String::from_utf8_lossy(&bytes).to_string());
+    }
+    // pdf-extract is synchronous
+    match tokio::task::spawn_blocking(move || pdf_extract::extract_text_from_mem(&bytes)).await {
+        Ok(Ok(text)) => Ok(text),
+        Ok(Err(e)) => Err(format!("Failed to parse PDF: {:?}", e)),
+        Err(e) => Err(format!("Task panicked: {}", e)),
+    }
+}
+
+/// Resolve symlinks and relative segments so spec paths and watcher paths
+/// compare equal. A path that no longer exists resolves through its parent.
+fn canonical_path(path: &Path) -> PathBuf {
+    if let Ok(resolved) = std::fs::canonicalize(path) {
+        return resolved;
+    }
+    match (
+        path.parent()
+            .and_then(|parent| std::fs::canonicalize(parent).ok()),
+        path.file_name(),
+    ) {
+        (Some(parent), Some(name)) => parent.join(name),
+        _ => path.to_path_buf(),
+    }
+}
+
+fn file_name(path: &str) -> String {
+    Path::new(path)
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or("knowledge-file")
+        .to_string()
+}
+
+fn knowledge_file(path: &str, indexed: &IndexedFile) -> KnowledgeFile {
+    KnowledgeFile {
+        id: indexed.id.clone(),
+        name: file_name(path),
+        path: path.to_string(),
+        size_bytes: indexed.size_bytes,
+        indexed_at: indexed.indexed_at,
+        chunk_count: indexed.chunk_count,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn concurrent_index_file_calls_leave_one_set_of_chunks() {
+        let dir = std::env::temp_dir().join(format!("knowledge-base-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let manager = Arc::new(MemoryManager::for_tests(dir.join("vault")).await);
+        let knowledge = KnowledgeBase::new(manager.clone(), &dir);
+        let path = dir.join("handbook.md").to_string_lossy().to_string();
+        std::fs::write(&path, "Deploys go out on Tuesdays.\n").unwrap();
+
+        let (first, second) = tokio::join!(
+            knowledge.index_file("agent-1", &path),
+            knowledge.index_file("agent-1", &path)
+        );
+        let (first, second) = (first.unwrap(), second.unwrap());
+        assert_ne!(first.reindexed, second.reindexed);
+
+        let source = format!("source:{}", path);
+        let chunks = manager
+            .query_workspace_memory(&knowledge_partition("agent-1"), "", 100)
+            .await
+            .unwrap()
+            .into_iter()
+            .filter(|entry| entry.tags.contains(&source))
+            .collect::<Vec<_>>();
+        assert_eq!(chunks.len(), first.file.chunk_count as usize);
+        let documents = chunks
+            .iter()
+            .filter_map(|entry| entry.tags.iter().find(|tag| tag.starts_with("doc:")))
+            .collect::<HashSet<_>>();
+        assert_eq!(documents.len(), 1);
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[tokio::test]
+    async fn sync_only_reads_files_added_through_index_file() {
+        let dir = std::env::temp_dir().join(format!("knowledge-base-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let manager = Arc::new(MemoryManager::for_tests(dir.join("vault")).await);
+        let knowledge = KnowledgeBase::new(manager.clone(), &dir);
+        let picked = dir.join("handbook.md").to_string_lossy().to_string();
+        let secret = dir.join("credentials").to_string_lossy().to_string();
+        std::fs::write(&picked, "Deploys go out on Tuesdays.\n").unwrap();
+        std::fs::write(&secret, "aws_secret_access_key = hunter2\n").unwrap();
+        knowledge.index_file("agent-1", &picked).await.unwrap();
+
+        std::fs::write(&picked, "Deploys go out on Thursdays.\n").unwrap();
+        let listed = |path: &str| KnowledgeFile {
+            id: String::new(),
+            name: file_name(path),
+            path: path.to_string(),
+            size_bytes: 0,
+            indexed_at: 0,
+            chunk_count: 0,
+        };
+        let config = KnowledgeConfig {
+            enabled: false,
+            indexed_files: vec![listed(&picked), listed(&secret)],
+        };
+        let files = knowledge.sync("agent-1", &config).await;
+        assert!(files[0].chunk_count > 0);
+        assert_eq!(files[1].chunk_count, 0);
+
+        let contents = manager
+            .query_workspace_memory(&knowledge_partition("agent-1"), "", 100)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|entry| entry.content)
+            .collect::<Vec<_>>();
+        assert!(contents.iter().any(|c| c.contains("Thursdays")));
+        assert!(!contents.iter().any(|c| c.contains("hunter2")));
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
@@ -18,6 +18,11 @@ impl ManagedResearchService {
         Self { provider_manager }
     }
 
+    /// Provider manager holding the keys, shared with other research backends
+    pub fn provider_manager(&self) -> Arc<AIProviderManager> {
+        self.provider_manager.clone()
+    }
+
     /// Perform web research and return SDK result directly
     pub async fn perform_research(
         &self,
@@ -33,7 +33,7 @@ fn stable_sort_value(value: &serde_json::Value) -> serde_json::Value {
 }
 
 /// Produce canonical JSON identical to ATM's `canonicalize()`.
-fn canonicalize(value: &serde_json::Value) -> String {
+pub(crate) fn canonicalize(value: &serde_json::Value) -> String {
     let sorted = stable_sort_value(value);
     serde_json::to_string(&sorted).unwrap_or_default()
 }
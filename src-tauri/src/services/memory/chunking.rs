@@ -0,0 +1,574 @@
+//! Structure-aware splitting of documents into retrieval chunks.
+//!
+//! Text is first cut into blocks along its structure (Markdown sections,
+//! fenced code, tables, top-level code items, paragraphs), blocks are broken
+//! into units no larger than a chunk, and units are packed greedily into
+//! chunks. Neighbouring chunks share up to `overlap_chars` of whole units.
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+const MARKDOWN_EXTENSIONS: &[&str] = &["md", "markdown", "mdx"];
+const CODE_EXTENSIONS: &[&str] = &[
+    "rs", "py", "js", "jsx", "ts", "tsx", "go", "java", "kt", "swift", "c", "h", "cc", "cpp",
+    "hpp", "cs", "rb", "php", "scala", "sh", "lua",
+];
+const MAX_HEADING_CHARS: usize = 120;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ChunkStrategy {
+    /// Pick from the file extension.
+    #[default]
+    Auto,
+    /// Split at headings; fenced code and tables stay whole.
+    Markdown,
+    /// Split at top-level items (functions, types, impl blocks).
+    Code,
+    Paragraph,
+    Sentence,
+}
+
+impl ChunkStrategy {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Auto => "auto",
+            Self::Markdown => "markdown",
+            Self::Code => "code",
+            Self::Paragraph => "paragraph",
+            Self::Sentence => "sentence",
+        }
+    }
+
+    /// `Auto` resolved against a source path.
+    pub fn resolve(self, source_path: &str) -> Self {
+        if self != Self::Auto {
+            return self;
+        }
+        let extension = std::path::Path::new(source_path)
+            .extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or("")
+            .to_lowercase();
+        if MARKDOWN_EXTENSIONS.contains(&extension.as_str()) {
+            Self::Markdown
+        } else if CODE_EXTENSIONS.contains(&extension.as_str()) {
+            Self::Code
+        } else {
+            Self::Paragraph
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ChunkOptions {
+    pub strategy: ChunkStrategy,
+    pub max_chars: usize,
+    pub overlap_chars: usize,
+}
+
+impl Default for ChunkOptions {
+    fn default() -> Self {
+        Self {
+            strategy: ChunkStrategy::Auto,
+            max_chars: 1500,
+            overlap_chars: 200,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Chunk {
+    pub content: String,
+    /// 1-based, inclusive.
+    pub start_line: usize,
+    pub end_line: usize,
+    /// Enclosing Markdown headings, or the code item the chunk starts in.
+    pub heading_path: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BlockKind {
+    Prose,
+    /// Code, tables and headings: split only at line boundaries.
+    Verbatim,
+}
+
+struct Block<'a> {
+    lines: Vec<(usize, &'a str)>,
+    kind: BlockKind,
+    section: usize,
+}
+
+struct Unit {
+    text: String,
+    start_line: usize,
+    end_line: usize,
+    section: usize,
+}
+
+/// Split `text` into chunks. The strategy is resolved against `source_path`.
+pub fn chunk_text(text: &str, source_path: &str, options: &ChunkOptions) -> Vec<Chunk> {
+    let strategy = options.strategy.resolve(source_path);
+    let max_chars = options.max_chars.max(1);
+    let overlap_chars = options.overlap_chars.min(max_chars / 2);
+
+    let lines = text
+        .lines()
+        .enumerate()
+        .map(|(idx, line)| (idx + 1, line))
+        .collect::<Vec<_>>();
+    let (blocks, sections) = match strategy {
+        ChunkStrategy::Markdown => markdown_blocks(&lines),
+        ChunkStrategy::Code => code_blocks(&lines),
+        _ => (paragraph_blocks(&lines), vec![Vec::new()]),
+    };
+
+    let sentence_units = strategy == ChunkStrategy::Sentence;
+    let mut units = Vec::new();
+    for block in &blocks {
+        block_units(block, max_chars, sentence_units, &mut units);
+    }
+
+    // Markdown chunks never span sections, so each chunk has one heading path.
+    pack_units(
+        units,
+        &sections,
+        max_chars,
+        overlap_chars,
+        strategy == ChunkStrategy::Markdown,
+    )
+}
+
+fn markdown_blocks<'a>(lines: &[(usize, &'a str)]) -> (Vec<Block<'a>>, Vec<Vec<String>>) {
+    let mut sections = vec![Vec::new()];
+    let mut headings: Vec<(usize, String)> = Vec::new();
+    let mut blocks = Vec::new();
+    let mut current: Option<Block<'a>> = None;
+    let mut fence: Option<&str> = None;
+
+    for &(number, line) in lines {
+        let trimmed = line.trim_start();
+
+        if let Some(marker) = fence {
+            if let Some(block) = current.as_mut() {
+                block.lines.push((number, line));
+            }
+            if trimmed.starts_with(marker) {
+                fence = None;
+                blocks.extend(current.take());
+            }
+            continue;
+        }
+
+        if let Some(marker) = ["```", "~~~"].into_iter().find(|m| trimmed.starts_with(m)) {
+            blocks.extend(current.take());
+            fence = Some(marker);
+            current = Some(Block {
+                lines: vec![(number, line)],
+                kind: BlockKind::Verbatim,
+                section: sections.len() - 1,
+            });
+            continue;
+        }
+
+        if let Some((level, title)) = markdown_heading(trimmed) {
+            blocks.extend(current.take());
+            headings.retain(|(l, _)| *l < level);
+            headings.push((level, truncate_chars(title, MAX_HEADING_CHARS)));
+            sections.push(headings.iter().map(|(_, t)| t.clone()).collect());
+            blocks.push(Block {
+                lines: vec![(number, line)],
+                kind: BlockKind::Verbatim,
+                section: sections.len() - 1,
+            });
+            continue;
+        }
+
+        if trimmed.is_empty() {
+            blocks.extend(current.take());
+            continue;
+        }
+
+        let kind = if trimmed.starts_with('|') {
+            BlockKind::Verbatim
+        } else {
+            BlockKind::Prose
+        };
+        match current.as_mut() {
+            Some(block) if block.kind == kind => block.lines.push((number, line)),
+            _ => {
+                blocks.extend(current.take());
+                current = Some(Block {
+                    lines: vec![(number, line)],
+                    kind,
+                    section: sections.len() - 1,
+                });
+            }
+        }
+    }
+    blocks.extend(current);
+    (blocks, sections)
+}
+
+fn markdown_heading(line: &str) -> Option<(usize, &str)> {
+    let level = line.chars().take_while(|c| *c == '#').count();
+    if !(1..=6).contains(&level) {
+        return None;
+    }
+    let rest = &line[level..];
+    if !rest.is_empty() && !rest.starts_with(' ') {
+        return None;
+    }
+    let title = rest.trim().trim_end_matches('#').trim();
+    (!title.is_empty()).then_some((level, title))
+}
+
+/// One block per top-level item. An item starts at an unindented line after
+/// a blank line or after the previous item's closing line; leading comments
+/// and attributes stay with the item they precede.
+fn code_blocks<'a>(lines: &[(usize, &'a str)]) -> (Vec<Block<'a>>, Vec<Vec<String>>) {
+    let mut sections = vec![Vec::new()];
+    let mut blocks: Vec<Block<'a>> = Vec::new();
+    let mut previous: Option<&str> = None;
+
+    for &(number, line) in lines {
+        let starts_item = is_top_level(line)
+            && previous.is_none_or(|p| p.trim().is_empty() || is_closing_line(p));
+        if starts_item || blocks.is_empty() {
+            sections.push(Vec::new());
+            blocks.push(Block {
+                lines: Vec::new(),
+                kind: BlockKind::Verbatim,
+                section: sections.len() - 1,
+            });
+        }
+        let block = blocks.last_mut().expect("block pushed above");
+        block.lines.push((number, line));
+        if sections[block.section].is_empty() && is_signature(line) {
+            sections[block.section] = vec![truncate_chars(line.trim(), MAX_HEADING_CHARS)];
+        }
+        previous = Some(line);
+    }
+
+    for block in &mut blocks {
+        while block.lines.last().is_some_and(|(_, l)| l.trim().is_empty()) {
+            block.lines.pop();
+        }
+    }
+    blocks.retain(|block| !block.lines.is_empty());
+    (blocks, sections)
+}
+
+fn is_top_level(line: &str) -> bool {
+    !line.is_empty() && !line.starts_with(char::is_whitespace) && !is_closing_line(line)
+}
+
+fn is_closing_line(line: &str) -> bool {
+    let trimmed = line.trim_end();
+    !trimmed.starts_with(char::is_whitespace)
+        && (trimmed.starts_with(['}', ')', ']']) || trimmed == "end")
+}
+
+fn is_signature(line: &str) -> bool {
+    let trimmed = line.trim();
+    !trimmed.is_empty()
+        && !["//", "#", "/*", "*", "@", "--"]
+            .iter()
+            .any(|prefix| trimmed.starts_with(prefix))
+}
+
+fn paragraph_blocks<'a>(lines: &[(usize, &'a str)]) -> Vec<Block<'a>> {
+    let mut blocks = Vec::new();
+    let mut current: Vec<(usize, &'a str)> = Vec::new();
+    for &(number, line) in lines {
+        if line.trim().is_empty() {
+            if !current.is_empty() {
+                blocks.push(Block {
+                    lines: std::mem::take(&mut current),
+                    kind: BlockKind::Prose,
+                    section: 0,
+                });
+            }
+        } else {
+            current.push((number, line));
+        }
+    }
+    if !current.is_empty() {
+        blocks.push(Block {
+            lines: current,
+            kind: BlockKind::Prose,
+            section: 0,
+        });
+    }
+    blocks
+}
+
+fn block_units(block: &Block<'_>, max_chars: usize, sentences: bool, out: &mut Vec<Unit>) {
+    let text = block
+        .lines
+        .iter()
+        .map(|(_, line)| *line)
+        .collect::<Vec<_>>()
+        .join("\n");
+    let first_line = block.lines.first().map_or(1, |(n, _)| *n);
+    let last_line = block.lines.last().map_or(first_line, |(n, _)| *n);
+
+    let fits = text.chars().count() <= max_chars;
+    if fits && !(sentences && block.kind == BlockKind::Prose) {
+        out.push(Unit {
+            text,
+            start_line: first_line,
+            end_line: last_line,
+            section: block.section,
+        });
+        return;
+    }
+
+    let pieces = match block.kind {
+        BlockKind::Prose => split_sentences(&text, first_line),
+        BlockKind::Verbatim => block
+            .lines
+            .iter()
+            .map(|(number, line)| (line.to_string(), *number, *number))
+            .collect(),
+    };
+    for (piece, start_line, end_line) in pieces {
+        for window in char_windows(&piece, max_chars) {
+            out.push(Unit {
+                text: window,
+                start_line,
+                end_line,
+                section: block.section,
+            });
+        }
+    }
+}
+
+/// Sentences of `text` with their line ranges; `first_line` is the line
+/// number of the first line of `text`.
+fn split_sentences(text: &str, first_line: usize) -> Vec<(String, usize, usize)> {
+    let mut sentences = Vec::new();
+    let mut current = String::new();
+    let mut start_line = first_line;
+    let mut line = first_line;
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if current.is_empty() {
+            if c.is_whitespace() {
+                if c == '\n' {
+                    line += 1;
+                }
+                continue;
+            }
+            start_line = line;
+        }
+        current.push(if c == '\n' { ' ' } else { c });
+        let ends_sentence =
+            matches!(c, '.' | '!' | '?') && chars.peek().is_none_or(|n| n.is_whitespace());
+        if ends_sentence {
+            sentences.push((current.trim_end().to_string(), start_line, line));
+            current.clear();
+        }
+        if c == '\n' {
+            line += 1;
+        }
+    }
+    if !current.trim().is_empty() {
+        let end_line = if text.ends_with('\n') { line - 1 } else { line };
+        sentences.push((current.trim_end().to_string(), start_line, end_line));
+    }
+    sentences
+}
+
+fn char_windows(text: &str, max_chars: usize) -> Vec<String> {
+    let chars = text.chars().collect::<Vec<_>>();
+    if chars.len() <= max_chars {
+        return vec![text.to_string()];
+    }
+    chars
+        .chunks(max_chars)
+        .map(|window| window.iter().collect())
+        .collect()
+}
+
+fn pack_units(
+    units: Vec<Unit>,
+    sections: &[Vec<String>],
+    max_chars: usize,
+    overlap_chars: usize,
+    split_sections: bool,
+) -> Vec<Chunk> {
+    let mut chunks = Vec::new();
+    let mut current: Vec<Unit> = Vec::new();
+    let mut current_chars = 0usize;
+    // Units added since the last flush; overlap alone never makes a chunk.
+    let mut fresh = 0usize;
+
+    for unit in units {
+        let unit_chars = unit.text.chars().count();
+        let section_changed = split_sections
+            && current
+                .first()
+                .is_some_and(|first| first.section != unit.section);
+        let overflows = !current.is_empty() && current_chars + 1 + unit_chars > max_chars;
+
+        if section_changed || overflows {
+            if fresh > 0 {
+                chunks.push(build_chunk(&current, sections));
+            }
+            current = if section_changed {
+                Vec::new()
+            } else {
+                overlap_tail(
+                    current,
+                    overlap_chars,
+                    max_chars.saturating_sub(unit_chars + 1),
+                )
+            };
+            current_chars = joined_len(&current);
+            fresh = 0;
+        }
+
+        current_chars += unit_chars + usize::from(!current.is_empty());
+        current.push(unit);
+        fresh += 1;
+    }
+    if fresh > 0 {
+        chunks.push(build_chunk(&current, sections));
+    }
+    chunks
+}
+
+/// Trailing units of a flushed chunk that fit in both the overlap budget and
+/// the room left beside the next unit.
+fn overlap_tail(mut units: Vec<Unit>, overlap_chars: usize, room: usize) -> Vec<Unit> {
+    let budget = overlap_chars.min(room);
+    let mut kept = 0usize;
+    let mut total = 0usize;
+    for unit in units.iter().rev() {
+        let len = unit.text.chars().count() + usize::from(kept > 0);
+        if total + len > budget {
+            break;
+        }
+        total += len;
+        kept += 1;
+    }
+    units.split_off(units.len() - kept)
+}
+
+fn joined_len(units: &[Unit]) -> usize {
+    units
+        .iter()
+        .map(|unit| unit.text.chars().count())
+        .sum::<usize>()
+        + units.len().saturating_sub(1)
+}
+
+fn build_chunk(units: &[Unit], sections: &[Vec<String>]) -> Chunk {
+    let mut content = String::new();
+    let mut previous_end: Option<usize> = None;
+    for unit in units {
+        match previous_end {
+            None => {}
+            Some(end) if unit.start_line > end + 1 => content.push_str("\n\n"),
+            Some(end) if unit.start_line > end => content.push('\n'),
+            Some(_) => content.push(' '),
+        }
+        content.push_str(&unit.text);
+        previous_end = Some(unit.end_line);
+    }
+    Chunk {
+        content,
+        start_line: units.first().map_or(1, |u| u.start_line),
+        end_line: units.iter().map(|u| u.end_line).max().unwrap_or(1),
+        heading_path: units
+            .first()
+            .and_then(|u| sections.get(u.section))
+            .cloned()
+            .unwrap_or_default(),
+    }
+}
+
+fn truncate_chars(text: &str, max_chars: usize) -> String {
+    text.chars().take(max_chars).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options(strategy: ChunkStrategy, max_chars: usize, overlap_chars: usize) -> ChunkOptions {
+        ChunkOptions {
+            strategy,
+            max_chars,
+            overlap_chars,
+        }
+    }
+
+    #[test]
+    fn test_markdown_chunks_follow_headings_and_keep_fences_whole() {
+        let doc = "# Guide\n\nIntro text.\n\n## Install\n\nRun the installer.\n\n```sh\n# not a heading\ncargo build\n```\n\n| a | b |\n|---|---|\n| 1 | 2 |\n\n## Usage\n\nCall it.\n";
+        let chunks = chunk_text(doc, "guide.md", &ChunkOptions::default());
+
+        assert_eq!(chunks.len(), 3);
+        assert_eq!(chunks[0].heading_path, vec!["Guide"]);
+        assert_eq!((chunks[0].start_line, chunks[0].end_line), (1, 3));
+        assert_eq!(chunks[1].heading_path, vec!["Guide", "Install"]);
+        assert!(chunks[1]
+            .content
+            .contains("```sh\n# not a heading\ncargo build\n```"));
+        assert!(chunks[1]
+            .content
+            .contains("| a | b |\n|---|---|\n| 1 | 2 |"));
+        assert_eq!((chunks[1].start_line, chunks[1].end_line), (5, 16));
+        assert_eq!(chunks[2].heading_path, vec!["Guide", "Usage"]);
+    }
+
+    #[test]
+    fn test_code_chunks_split_at_top_level_items() {
+        let source = "use std::fmt;\n\n/// Adds.\nfn add(a: i32, b: i32) -> i32 {\n    a + b\n}\n\nfn sub(a: i32, b: i32) -> i32 {\n\n    a - b\n}\n";
+        let chunks = chunk_text(source, "lib.rs", &options(ChunkStrategy::Auto, 60, 0));
+
+        assert_eq!(chunks.len(), 3);
+        assert_eq!(
+            chunks[1].heading_path,
+            vec!["fn add(a: i32, b: i32) -> i32 {"]
+        );
+        assert_eq!((chunks[1].start_line, chunks[1].end_line), (3, 6));
+        assert!(chunks[2].content.starts_with("fn sub"));
+        assert!(chunks[2].content.ends_with("a - b\n}"));
+    }
+
+    #[test]
+    fn test_sentence_chunks_overlap_by_whole_sentences() {
+        let text = "One is first. Two is second.\nThree is third. Four is fourth.";
+        let chunks = chunk_text(text, "notes.txt", &options(ChunkStrategy::Sentence, 32, 15));
+
+        assert_eq!(
+            chunks
+                .iter()
+                .map(|c| c.content.as_str())
+                .collect::<Vec<_>>(),
+            vec![
+                "One is first. Two is second.",
+                "Two is second.\nThree is third.",
+                "Three is third. Four is fourth."
+            ]
+        );
+        assert_eq!((chunks[1].start_line, chunks[1].end_line), (1, 2));
+    }
+
+    #[test]
+    fn test_long_documents_are_not_truncated() {
+        let paragraph = "word ".repeat(50);
+        let text = vec![paragraph.trim(); 3000].join("\n\n");
+        let chunks = chunk_text(&text, "big.txt", &options(ChunkStrategy::Paragraph, 500, 0));
+
+        assert_eq!(chunks.len(), 1500);
+        assert_eq!(chunks.last().unwrap().end_line, 5999);
+        assert!(chunks.iter().all(|c| c.content.chars().count() <= 500));
+    }
+}
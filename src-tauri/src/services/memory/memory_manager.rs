@@ -1,16 +1,40 @@
+use crate::services::memory::chunking::{self, ChunkOptions};
 use crate::services::memory::{
-    IngestionResult, MemoryEntry, MemoryError, MemoryStats, SemanticRetrievalMode,
-    SemanticSearchResult,
+    IngestionResult, MemoryEntry, MemoryError, MemoryStats, RetrievalStrategy, SearchOptions,
+    SemanticRetrievalMode, SemanticSearchResult,
+};
+use crate::services::embedder::{
+    embedder_from_settings, embedding_profile_from_settings, Embedder, EmbeddingTaskType,
+};
+use crate::services::memory_vault::lexical;
+use crate::services::memory_vault::lifecycle::recency_decay;
+use crate::services::memory_vault::{
+    EmbeddingProfile, MaintenanceReport, MemorySensitivity, MemoryVaultService, RetentionPolicy,
+    StoreMemoryInput,
 };
-use crate::services::embedder::{EmbedderService, EmbeddingTaskType};
-use crate::services::memory_vault::{MemorySensitivity, MemoryVaultService, StoreMemoryInput};
-use crate::services::memory_vault::{EMBEDDING_MODEL, EMBEDDING_PROVIDER};
 use std::collections::{HashMap, VecDeque};
 use std::path::PathBuf;
-use std::sync::{Arc, OnceLock};
+use std::sync::Arc;
 use tokio::sync::RwLock;
 
 const SEMANTIC_SEARCH_TIMEOUT_MS: u64 = 4000;
+const MAINTENANCE_INTERVAL_SECS: i64 = 6 * 60 * 60;
+const MAINTENANCE_TICK_SECS: u64 = 15 * 60;
+
+/// A workspace's retention policy and when maintenance last ran for it.
+#[derive(Debug, Clone)]
+struct WorkspaceRetention {
+    policy: RetentionPolicy,
+    last_maintenance: i64,
+}
+
+/// The embedder selected in settings, or why none is available.
+#[derive(Debug, Clone, Default)]
+struct EmbedderState {
+    embedder: Option<Arc<dyn Embedder>>,
+    profile: EmbeddingProfile,
+    unavailable_reason: Option<String>,
+}
 
 #[derive(Debug, Clone)]
 pub struct MemoryManager {
@@ -18,12 +42,12 @@ pub struct MemoryManager {
     short_term_capacity: usize,
     vault_dir: PathBuf,
     vault: Arc<RwLock<Option<Arc<MemoryVaultService>>>>,
-    embedder_cache: Arc<OnceLock<Option<Arc<EmbedderService>>>>,
+    embedder: Arc<RwLock<EmbedderState>>,
+    retention: Arc<RwLock<HashMap<String, WorkspaceRetention>>>,
 }
 
 impl MemoryManager {
-    const MAX_INGEST_CHUNKS: usize = 2048;
-    const DEFAULT_CHUNK_CHARS: usize = 1500;
+    const INGEST_BATCH_CHUNKS: usize = 64;
 
     pub fn new(short_term_size: usize, vault_dir: PathBuf) -> Self {
         Self {
@@ -31,16 +55,183 @@ impl MemoryManager {
             short_term_capacity: short_term_size.max(1),
             vault_dir,
             vault: Arc::new(RwLock::new(None)),
-            embedder_cache: Arc::new(OnceLock::new()),
+            embedder: Arc::new(RwLock::new(EmbedderState::default())),
+            retention: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    /// A manager over a fresh vault with a fixed key and no embedder.
+    #[cfg(test)]
+    pub(crate) async fn for_tests(vault_dir: PathBuf) -> Self {
+        struct TestKeyProvider;
+
+        impl crate::services::memory_vault::key_provider::VaultKeyProvider for TestKeyProvider {
+            fn get_or_create_master_key(&self) -> Result<Vec<u8>, String> {
+                Ok(vec![7u8; 32])
+            }
         }
+
+        let manager = Self::new(16, vault_dir.clone());
+        let vault = MemoryVaultService::new_with_provider(vault_dir, Arc::new(TestKeyProvider))
+            .await
+            .expect("test vault");
+        *manager.vault.write().await = Some(Arc::new(vault));
+        manager
     }
 
     pub async fn init(&self) {
-        // Pre-warm embedder cache so the first search doesn't pay Keychain init cost.
-        let _ = self.resolve_gemini_embedder();
+        // Resolve the embedder up front so the first search doesn't pay Keychain init cost.
+        self.reload_embedder().await;
         if let Ok(vault) = self.ensure_vault().await {
-            vault.spawn_reembed_backfill();
+            vault.spawn_term_index_backfill();
         }
+        self.spawn_maintenance_loop();
+    }
+
+    /// Rebuild the embedder from settings. Entries without a vector for the
+    /// new profile are re-embedded in the background.
+    pub async fn reload_embedder(&self) {
+        let settings = crate::services::settings::SettingsManager::new();
+        let state = match embedder_from_settings(&settings) {
+            Ok(Some(embedder)) => EmbedderState {
+                profile: embedder.profile().clone(),
+                embedder: Some(embedder),
+                unavailable_reason: None,
+            },
+            Ok(None) => {
+                let profile = embedding_profile_from_settings(&settings).unwrap_or_default();
+                EmbedderState {
+                    unavailable_reason: Some(format!(
+                        "Missing {} embedding API key",
+                        profile.provider
+                    )),
+                    profile,
+                    embedder: None,
+                }
+            }
+            Err(reason) => EmbedderState {
+                unavailable_reason: Some(reason),
+                ..Default::default()
+            },
+        };
+
+        if let Some(embedder) = state.embedder.clone() {
+            if let Ok(vault) = self.ensure_vault().await {
+                vault.spawn_reembed_backfill(embedder);
+            }
+        }
+        *self.embedder.write().await = state;
+    }
+
+    /// Profile new memories are embedded with.
+    pub async fn embedding_profile(&self) -> EmbeddingProfile {
+        self.embedder.read().await.profile.clone()
+    }
+
+    /// Apply an agent spec's retention policy to a workspace. Maintenance runs
+    /// in the background if the workspace hasn't been maintained recently.
+    pub async fn set_retention_policy(&self, workspace_id: &str, policy: RetentionPolicy) {
+        let now = chrono::Utc::now().timestamp();
+        let due = {
+            let mut retention = self.retention.write().await;
+            let entry = retention
+                .entry(workspace_id.to_string())
+                .or_insert_with(|| WorkspaceRetention {
+                    policy: policy.clone(),
+                    last_maintenance: 0,
+                });
+            entry.policy = policy;
+            entry.policy.maintenance_enabled
+                && now - entry.last_maintenance >= MAINTENANCE_INTERVAL_SECS
+        };
+        if due {
+            let manager = self.clone();
+            let workspace_id = workspace_id.to_string();
+            tokio::spawn(async move {
+                manager.run_maintenance_logged(&workspace_id).await;
+            });
+        }
+    }
+
+    async fn retention_policy(&self, workspace_id: &str) -> RetentionPolicy {
+        self.retention
+            .read()
+            .await
+            .get(workspace_id)
+            .map(|r| r.policy.clone())
+            .unwrap_or_default()
+    }
+
+    /// Expire, rescore and consolidate one workspace's memories now.
+    pub async fn run_maintenance(
+        &self,
+        workspace_id: &str,
+    ) -> Result<MaintenanceReport, MemoryError> {
+        let policy = self.retention_policy(workspace_id).await;
+        let vault = self.ensure_vault().await?;
+        // Stamp before running so a slow pass isn't started twice.
+        {
+            let mut retention = self.retention.write().await;
+            retention
+                .entry(workspace_id.to_string())
+                .or_insert_with(|| WorkspaceRetention {
+                    policy: policy.clone(),
+                    last_maintenance: 0,
+                })
+                .last_maintenance = chrono::Utc::now().timestamp();
+        }
+        vault
+            .run_maintenance(workspace_id, &policy)
+            .await
+            .map_err(MemoryError::Other)
+    }
+
+    async fn run_maintenance_logged(&self, workspace_id: &str) {
+        match self.run_maintenance(workspace_id).await {
+            Ok(report) => tracing::info!(
+                "Memory maintenance for {}: {} expired, {} merged, {} rescored",
+                workspace_id,
+                report.expired,
+                report.merged,
+                report.rescored
+            ),
+            Err(e) => tracing::warn!("Memory maintenance failed for {}: {}", workspace_id, e),
+        }
+    }
+
+    fn spawn_maintenance_loop(&self) {
+        let manager = self.clone();
+        tokio::spawn(async move {
+            let mut ticker =
+                tokio::time::interval(std::time::Duration::from_secs(MAINTENANCE_TICK_SECS));
+            loop {
+                ticker.tick().await;
+                let now = chrono::Utc::now().timestamp();
+                let due = manager
+                    .retention
+                    .read()
+                    .await
+                    .iter()
+                    .filter(|(_, r)| {
+                        r.policy.maintenance_enabled
+                            && now - r.last_maintenance >= MAINTENANCE_INTERVAL_SECS
+                    })
+                    .map(|(workspace_id, _)| workspace_id.clone())
+                    .collect::<Vec<_>>();
+                for workspace_id in due {
+                    manager.run_maintenance_logged(&workspace_id).await;
+                }
+            }
+        });
+    }
+
+    /// Returns false when no vault entry has this id.
+    pub async fn set_pinned(&self, id: &str, pinned: bool) -> Result<bool, MemoryError> {
+        let vault = self.ensure_vault().await?;
+        vault
+            .set_pinned(id, pinned)
+            .await
+            .map_err(MemoryError::Other)
     }
 
     async fn ensure_vault(&self) -> Result<Arc<MemoryVaultService>, MemoryError> {
@@ -101,6 +292,7 @@ impl MemoryManager {
                 timestamp: chrono::DateTime::from_timestamp(created_at, 0)
                     .unwrap_or_else(chrono::Utc::now),
                 tags: tags.clone(),
+                importance: 0.5,
             });
             while stm.len() > self.short_term_capacity {
                 let _ = stm.pop_front();
@@ -108,6 +300,9 @@ impl MemoryManager {
         }
 
         let vault = self.ensure_vault().await?;
+        let EmbedderState {
+            embedder, profile, ..
+        } = self.embedder.read().await.clone();
 
         vault
             .put(StoreMemoryInput {
@@ -120,9 +315,9 @@ impl MemoryManager {
                 metadata: metadata.clone(),
                 created_at,
                 embedding: None,
-                embedding_model: Some(EMBEDDING_MODEL.to_string()),
-                embedding_provider: Some(EMBEDDING_PROVIDER.to_string()),
-                embedding_dim: Some(crate::services::memory_vault::EMBEDDING_DIM),
+                embedding_model: Some(profile.model.clone()),
+                embedding_provider: Some(profile.provider.clone()),
+                embedding_dim: Some(profile.dim),
                 additional_embeddings: Vec::new(),
             })
             .await
@@ -130,12 +325,12 @@ impl MemoryManager {
 
         // Embed in the background so the write path is non-blocking. The upsert in put()
         // uses INSERT OR REPLACE so the second call simply attaches the embedding.
-        if let Ok(Some(embedder)) = self.resolve_gemini_embedder() {
+        if let Some(embedder) = embedder {
             let vault_bg = vault.clone();
             let workspace_id = workspace_id.to_string();
             tokio::spawn(async move {
                 if let Ok(vec) = embedder
-                    .embed_text_with_task(&content, EmbeddingTaskType::RetrievalDocument)
+                    .embed(&content, EmbeddingTaskType::RetrievalDocument)
                     .await
                 {
                     let _ = vault_bg
@@ -149,9 +344,9 @@ impl MemoryManager {
                             metadata,
                             created_at,
                             embedding: Some(vec),
-                            embedding_model: Some(EMBEDDING_MODEL.to_string()),
-                            embedding_provider: Some(EMBEDDING_PROVIDER.to_string()),
-                            embedding_dim: Some(crate::services::memory_vault::EMBEDDING_DIM),
+                            embedding_model: Some(profile.model),
+                            embedding_provider: Some(profile.provider),
+                            embedding_dim: Some(profile.dim),
                             additional_embeddings: Vec::new(),
                         })
                         .await;
@@ -210,6 +405,7 @@ impl MemoryManager {
             timestamp: chrono::DateTime::from_timestamp(entry.created_at, 0)
                 .unwrap_or_else(chrono::Utc::now),
             tags: entry.tags,
+            importance: entry.importance,
         }))
     }
 
@@ -226,6 +422,20 @@ impl MemoryManager {
             .map_err(MemoryError::Other)
     }
 
+    /// Remove everything ingested from `source` in a workspace, e.g. before
+    /// re-ingesting a changed document.
+    pub async fn delete_source(
+        &self,
+        workspace_id: &str,
+        source: &str,
+    ) -> Result<usize, MemoryError> {
+        let vault = self.ensure_vault().await?;
+        vault
+            .delete_source(workspace_id, source)
+            .await
+            .map_err(MemoryError::Other)
+    }
+
     pub async fn short_term_size(&self) -> usize {
         let stm = self.short_term.read().await;
         stm.len()
@@ -246,6 +456,8 @@ impl MemoryManager {
             .search_workspace(workspace_id, query, limit.max(1))
             .await
             .map_err(MemoryError::Other)?;
+        let ids: Vec<String> = rows.iter().map(|entry| entry.id.clone()).collect();
+        vault.touch_access(&ids).await;
 
         Ok(rows
             .into_iter()
@@ -256,6 +468,7 @@ impl MemoryManager {
                 timestamp: chrono::DateTime::from_timestamp(entry.created_at, 0)
                     .unwrap_or_else(chrono::Utc::now),
                 tags: entry.tags,
+                importance: entry.importance,
             })
             .collect())
     }
@@ -265,34 +478,77 @@ impl MemoryManager {
         query: &str,
         limit: usize,
     ) -> Result<SemanticSearchResult, MemoryError> {
-        let embedder = match self.resolve_gemini_embedder() {
-            Ok(Some(embedder)) => embedder,
-            Ok(None) => {
-                let entries = self
-                    .query_workspace_memory(workspace_id, query, limit)
-                    .await?;
-                return Ok(SemanticSearchResult {
-                    entries,
-                    mode: SemanticRetrievalMode::LexicalFallback,
-                    reason: Some("Missing Gemini embedding API key".to_string()),
-                    confidential_entry_ids: Vec::new(),
-                });
+        self.search_with_options(workspace_id, query, limit, &SearchOptions::default())
+            .await
+    }
+
+    /// Long-term memory retrieval. Hybrid fuses BM25 and vector rankings with
+    /// reciprocal rank fusion; vector and hybrid fall back to keyword search
+    /// when no embedder is available.
+    pub async fn search_with_options(
+        &self,
+        workspace_id: &str,
+        query: &str,
+        limit: usize,
+        options: &SearchOptions,
+    ) -> Result<SemanticSearchResult, MemoryError> {
+        let limit = limit.max(1);
+        let candidate_limit = limit.saturating_mul(3).max(20);
+        let vault = self.ensure_vault().await?;
+
+        if options.strategy == RetrievalStrategy::Lexical {
+            let mut rows = vault
+                .search_workspace_lexical(workspace_id, query, candidate_limit)
+                .await
+                .map_err(MemoryError::Other)?;
+            if rows.is_empty() {
+                rows = vault
+                    .search_workspace(workspace_id, query, candidate_limit)
+                    .await
+                    .map_err(MemoryError::Other)?
+                    .into_iter()
+                    .map(|entry| (entry, 0.0))
+                    .collect();
             }
-            Err(reason) => {
+            let best = rows.iter().map(|(_, score)| *score).fold(0.0, f64::max);
+            let scored = rows
+                .into_iter()
+                .map(|(entry, score)| {
+                    let normalized = if best > 0.0 { score / best } else { 0.0 };
+                    let relevance = 0.85 * normalized + 0.15 * access_score(entry.access_count);
+                    (relevance, entry)
+                })
+                .collect();
+            return Ok(self
+                .finish_ranking(
+                    workspace_id,
+                    query,
+                    scored,
+                    limit,
+                    options,
+                    SemanticRetrievalMode::Lexical,
+                )
+                .await);
+        }
+
+        let state = self.embedder.read().await.clone();
+        let embedder = match state.embedder {
+            Some(embedder) => embedder,
+            None => {
                 let entries = self
                     .query_workspace_memory(workspace_id, query, limit)
                     .await?;
                 return Ok(SemanticSearchResult {
                     entries,
                     mode: SemanticRetrievalMode::LexicalFallback,
-                    reason: Some(reason),
+                    reason: state.unavailable_reason,
                     confidential_entry_ids: Vec::new(),
                 });
             }
         };
 
-        let embed_future = embedder.embed_text_with_task(query, EmbeddingTaskType::RetrievalQuery);
-        let query_embedding = match tokio::time::timeout(
+        let embed_future = embed_query(embedder, query);
+        let (query_embedding, profile) = match tokio::time::timeout(
             std::time::Duration::from_millis(SEMANTIC_SEARCH_TIMEOUT_MS),
             embed_future,
         )
@@ -306,7 +562,7 @@ impl MemoryManager {
                 return Ok(SemanticSearchResult {
                     entries,
                     mode: SemanticRetrievalMode::LexicalFallback,
-                    reason: Some(format!("Gemini embedding request failed: {}", e)),
+                    reason: Some(format!("Embedding request failed: {}", e)),
                     confidential_entry_ids: Vec::new(),
                 });
             }
@@ -323,47 +579,115 @@ impl MemoryManager {
             }
         };
 
-        let vault = self.ensure_vault().await?;
-        let (rows, mode) = vault
-            .search_workspace_vector_with_mode(workspace_id, &query_embedding, limit.max(1))
+        let (vector_rows, vector_mode) = vault
+            .search_workspace_vector_with_mode(
+                workspace_id,
+                &query_embedding,
+                candidate_limit,
+                &profile,
+            )
             .await
             .map_err(MemoryError::Other)?;
 
+        if options.strategy == RetrievalStrategy::Vector {
+            let query_tokens = normalize_query_tokens(query);
+            let scored = vector_rows
+                .into_iter()
+                .map(|(entry, distance)| {
+                    let lexical = lexical_overlap_score(&query_tokens, &entry.content);
+                    let access = access_score(entry.access_count);
+                    let relevance =
+                        0.75 * semantic_score(distance) + 0.20 * lexical + 0.05 * access;
+                    (relevance, entry)
+                })
+                .collect();
+            let mode = match vector_mode {
+                crate::services::memory_vault::service::VectorSearchMode::Ann => {
+                    SemanticRetrievalMode::Ann
+                }
+                crate::services::memory_vault::service::VectorSearchMode::Exact => {
+                    SemanticRetrievalMode::Exact
+                }
+            };
+            return Ok(self
+                .finish_ranking(workspace_id, query, scored, limit, options, mode)
+                .await);
+        }
+
         let lexical_rows = vault
-            .search_workspace(workspace_id, query, limit.saturating_mul(3).max(20))
+            .search_workspace_lexical(workspace_id, query, candidate_limit)
             .await
             .map_err(MemoryError::Other)?;
 
-        let mode = match mode {
-            crate::services::memory_vault::service::VectorSearchMode::Ann => {
-                SemanticRetrievalMode::Ann
-            }
-            crate::services::memory_vault::service::VectorSearchMode::Exact => {
-                SemanticRetrievalMode::Exact
-            }
-        };
+        let rankings = vec![
+            vector_rows
+                .iter()
+                .map(|(entry, _)| entry.id.clone())
+                .collect::<Vec<_>>(),
+            lexical_rows
+                .iter()
+                .map(|(entry, _)| entry.id.clone())
+                .collect::<Vec<_>>(),
+        ];
+        let mut entries = HashMap::new();
+        let candidates = vector_rows
+            .into_iter()
+            .map(|(entry, _)| entry)
+            .chain(lexical_rows.into_iter().map(|(entry, _)| entry));
+        for entry in candidates {
+            entries.entry(entry.id.clone()).or_insert(entry);
+        }
+        // Normalize so an entry ranked first by both lists scores 1.0.
+        let best_possible = rankings.len() as f64 / (lexical::RRF_K + 1.0);
+        let scored = lexical::reciprocal_rank_fusion(&rankings, lexical::RRF_K)
+            .into_iter()
+            .filter_map(|(id, fused)| Some((fused / best_possible, entries.remove(&id)?)))
+            .collect();
+
+        Ok(self
+            .finish_ranking(
+                workspace_id,
+                query,
+                scored,
+                limit,
+                options,
+                SemanticRetrievalMode::Hybrid,
+            )
+            .await)
+    }
 
-        let query_tokens = normalize_query_tokens(query);
+    /// Optionally rerank, blend with importance and recency, and keep the top
+    /// `limit` entries.
+    async fn finish_ranking(
+        &self,
+        workspace_id: &str,
+        query: &str,
+        scored: Vec<(
+            f64,
+            crate::services::memory_vault::types::DecryptedMemoryEntry,
+        )>,
+        limit: usize,
+        options: &SearchOptions,
+        mode: SemanticRetrievalMode,
+    ) -> SemanticSearchResult {
+        let query_terms = lexical::query_terms(query);
         let now = chrono::Utc::now().timestamp();
+        let policy = self.retention_policy(workspace_id).await;
         let mut merged: HashMap<
             String,
-            (f64, crate::services::memory_vault::types::DecryptedMemoryEntry),
+            (
+                f64,
+                crate::services::memory_vault::types::DecryptedMemoryEntry,
+            ),
         > = HashMap::new();
 
-        for (entry, distance) in rows {
-            let lexical = lexical_overlap_score(&query_tokens, &entry.content);
-            let recency = recency_score(entry.created_at, now);
-            let access = access_score(entry.access_count);
-            let semantic = semantic_score(distance);
-            let score = 0.65 * semantic + 0.15 * lexical + 0.15 * recency + 0.05 * access;
-            upsert_ranked_entry(&mut merged, entry, score);
-        }
-
-        for entry in lexical_rows {
-            let lexical = lexical_overlap_score(&query_tokens, &entry.content);
-            let recency = recency_score(entry.created_at, now);
-            let access = access_score(entry.access_count);
-            let score = 0.70 * lexical + 0.20 * recency + 0.10 * access;
+        for (relevance, entry) in scored {
+            let relevance = if options.rerank {
+                0.6 * relevance + 0.4 * lexical::rerank_score(&query_terms, &entry.content)
+            } else {
+                relevance
+            };
+            let score = blended_score(relevance, &entry, now, &policy);
             upsert_ranked_entry(&mut merged, entry, score);
         }
 
@@ -374,17 +698,22 @@ impl MemoryManager {
                 .unwrap_or(std::cmp::Ordering::Equal)
         });
 
+        ranked.truncate(limit);
         let mut confidential_entry_ids = Vec::new();
-        for (_score, entry) in ranked.iter().take(limit.max(1)) {
+        for (_score, entry) in &ranked {
             if matches!(entry.sensitivity, MemorySensitivity::Confidential) {
                 confidential_entry_ids.push(entry.id.clone());
             }
         }
+        // Only the entries handed back count as accessed, not every candidate.
+        if let Ok(vault) = self.ensure_vault().await {
+            let ids: Vec<String> = ranked.iter().map(|(_, entry)| entry.id.clone()).collect();
+            vault.touch_access(&ids).await;
+        }
 
-        Ok(SemanticSearchResult {
+        SemanticSearchResult {
             entries: ranked
                 .into_iter()
-                .take(limit.max(1))
                 .map(|(_score, entry)| MemoryEntry {
                     id: entry.id,
                     content: entry.content,
@@ -392,12 +721,13 @@ impl MemoryManager {
                     timestamp: chrono::DateTime::from_timestamp(entry.created_at, 0)
                         .unwrap_or_else(chrono::Utc::now),
                     tags: entry.tags,
+                    importance: entry.importance,
                 })
                 .collect(),
             mode,
             reason: None,
             confidential_entry_ids,
-        })
+        }
     }
 
     pub async fn ingest_text_detailed(
@@ -406,32 +736,26 @@ impl MemoryManager {
         source_path: &str,
         text: &str,
         mut raw_tags: Option<Vec<String>>,
+        options: &ChunkOptions,
     ) -> Result<IngestionResult, MemoryError> {
         let vault = self.ensure_vault().await?;
 
-        let embedder = self.resolve_gemini_embedder().map_err(MemoryError::Other)?;
+        let EmbedderState {
+            embedder,
+            profile,
+            unavailable_reason,
+        } = self.embedder.read().await.clone();
         let mut warnings = Vec::new();
         if embedder.is_none() {
-            warnings.push("Gemini embedding API key unavailable; storing chunks without embeddings".to_string());
-        }
-
-        let chunks: Vec<String> = text
-            .chars()
-            .collect::<Vec<char>>()
-            .chunks(Self::DEFAULT_CHUNK_CHARS)
-            .map(|c| c.into_iter().collect())
-            .filter(|c: &String| !c.trim().is_empty())
-            .take(Self::MAX_INGEST_CHUNKS)
-            .collect();
-
-        let total_possible_chunks = text.chars().count().div_ceil(Self::DEFAULT_CHUNK_CHARS);
-        if total_possible_chunks > Self::MAX_INGEST_CHUNKS {
             warnings.push(format!(
-                "Document exceeded max chunk limit ({}); ingestion truncated",
-                Self::MAX_INGEST_CHUNKS
+                "{}; storing chunks without embeddings",
+                unavailable_reason.unwrap_or_else(|| "No embedder available".to_string())
             ));
         }
 
+        let strategy = options.strategy.resolve(source_path);
+        let chunks = chunking::chunk_text(text, source_path, options);
+
         let mut ingested_count = 0;
         let mut embedded_count = 0;
         let doc_id = uuid::Uuid::new_v4().to_string();
@@ -448,177 +772,179 @@ impl MemoryManager {
         }
 
         let chunk_count = chunks.len();
-        let active_model = crate::services::memory_vault::profiles::ACTIVE_EMBEDDING_PROFILE.model;
-        let fallback_model = crate::services::memory_vault::profiles::FALLBACK_EMBEDDING_PROFILE.model;
-        let mut primary_embeddings: Vec<Option<Vec<f32>>> = vec![None; chunk_count];
-        let mut primary_models: Vec<String> = vec![active_model.to_string(); chunk_count];
-        let mut fallback_embeddings: Vec<Option<Vec<f32>>> = vec![None; chunk_count];
-
-        if let Some(ref e) = embedder {
-            match e
-                .embed_texts_for_model_with_task(
-                    &chunks,
-                    active_model,
-                    EmbeddingTaskType::RetrievalDocument,
-                )
-                .await
-            {
-                Ok(vecs) if vecs.len() == chunk_count => {
-                    for (idx, v) in vecs.into_iter().enumerate() {
-                        primary_embeddings[idx] = Some(v);
-                    }
-                    if fallback_model != active_model {
-                        match e
-                            .embed_texts_for_model_with_task(
-                                &chunks,
-                                fallback_model,
-                                EmbeddingTaskType::RetrievalDocument,
-                            )
-                            .await
-                        {
-                            Ok(extra_vecs) if extra_vecs.len() == chunk_count => {
-                                for (idx, v) in extra_vecs.into_iter().enumerate() {
-                                    fallback_embeddings[idx] = Some(v);
+        let fallback = embedder.as_ref().and_then(|e| e.fallback());
+        let fallback_profile = fallback.as_ref().map(|f| f.profile().clone());
+        let mut embedding_mode = "none".to_string();
+
+        // Embed and store in batches so large documents never sit fully
+        // embedded in memory and are ingested in full.
+        for (batch_idx, batch) in chunks.chunks(Self::INGEST_BATCH_CHUNKS).enumerate() {
+            let texts = batch
+                .iter()
+                .map(|chunk| chunk.content.clone())
+                .collect::<Vec<_>>();
+            let mut primary_embeddings: Vec<Option<Vec<f32>>> = vec![None; texts.len()];
+            let mut primary_profile = profile.clone();
+            let mut fallback_embeddings: Vec<Option<Vec<f32>>> = vec![None; texts.len()];
+
+            if let Some(ref e) = embedder {
+                match e
+                    .embed_batch(&texts, EmbeddingTaskType::RetrievalDocument)
+                    .await
+                {
+                    Ok(vecs) => {
+                        for (idx, v) in vecs.into_iter().enumerate() {
+                            primary_embeddings[idx] = Some(v);
+                        }
+                        if let Some(ref f) = fallback {
+                            match f
+                                .embed_batch(&texts, EmbeddingTaskType::RetrievalDocument)
+                                .await
+                            {
+                                Ok(extra_vecs) => {
+                                    for (idx, v) in extra_vecs.into_iter().enumerate() {
+                                        fallback_embeddings[idx] = Some(v);
+                                    }
                                 }
+                                Err(err) => warnings.push(format!(
+                                    "Fallback batch embeddings failed during ingestion: {}",
+                                    err
+                                )),
                             }
-                            Ok(_) => warnings.push(
-                                "Fallback batch embeddings size mismatch during ingestion"
-                                    .to_string(),
-                            ),
-                            Err(err) => warnings.push(format!(
-                                "Fallback batch embeddings failed during ingestion: {}",
-                                err
-                            )),
                         }
                     }
-                }
-                Ok(_) => warnings.push(
-                    "Primary batch embeddings size mismatch during ingestion; using fallback path"
-                        .to_string(),
-                ),
-                Err(err) => {
-                    if fallback_model != active_model {
-                        match e
-                            .embed_texts_for_model_with_task(
-                                &chunks,
-                                fallback_model,
-                                EmbeddingTaskType::RetrievalDocument,
-                            )
-                            .await
-                        {
-                            Ok(vecs) if vecs.len() == chunk_count => {
-                                for (idx, v) in vecs.into_iter().enumerate() {
-                                    primary_embeddings[idx] = Some(v);
-                                    primary_models[idx] = fallback_model.to_string();
+                    Err(err) => {
+                        if let Some(ref f) = fallback {
+                            match f
+                                .embed_batch(&texts, EmbeddingTaskType::RetrievalDocument)
+                                .await
+                            {
+                                Ok(vecs) => {
+                                    for (idx, v) in vecs.into_iter().enumerate() {
+                                        primary_embeddings[idx] = Some(v);
+                                    }
+                                    primary_profile = f.profile().clone();
+                                    warnings.push(format!(
+                                        "Primary batch embedding failed; used fallback model '{}': {}",
+                                        primary_profile.model, err
+                                    ));
                                 }
-                                warnings.push(format!(
-                                    "Primary batch embedding failed; used fallback model '{}': {}",
-                                    fallback_model, err
-                                ));
+                                Err(fallback_err) => warnings.push(format!(
+                                    "Batch embeddings unavailable; storing without embeddings: {} | {}",
+                                    err, fallback_err
+                                )),
                             }
-                            Ok(_) => warnings.push(
-                                "Fallback batch embeddings size mismatch during ingestion"
-                                    .to_string(),
-                            ),
-                            Err(fallback_err) => warnings.push(format!(
-                                "Batch embeddings unavailable; storing without embeddings: {} | {}",
-                                err, fallback_err
-                            )),
+                        } else {
+                            warnings.push(format!(
+                                "Batch embeddings unavailable; storing without embeddings: {}",
+                                err
+                            ));
                         }
-                    } else {
-                        warnings.push(format!(
-                            "Batch embeddings unavailable; storing without embeddings: {}",
-                            err
-                        ));
                     }
                 }
             }
-        }
 
-        for (idx, chunk) in chunks.iter().enumerate() {
-            let embedding = primary_embeddings[idx].clone();
-            let embedding_model = primary_models[idx].clone();
-            let mut additional_embeddings = Vec::new();
-
-            if let Some(extra) = fallback_embeddings[idx].clone() {
-                additional_embeddings.push(crate::services::memory_vault::AdditionalEmbeddingInput {
-                    embedding: extra,
-                    embedding_model: fallback_model.to_string(),
-                    embedding_provider: EMBEDDING_PROVIDER.to_string(),
-                    embedding_dim: crate::services::memory_vault::EMBEDDING_DIM,
-                });
-            }
+            for (offset, chunk) in batch.iter().enumerate() {
+                let idx = batch_idx * Self::INGEST_BATCH_CHUNKS + offset;
+                let embedding = primary_embeddings[offset].take();
+                let mut additional_embeddings = Vec::new();
 
-            if embedding.is_some() {
-                embedded_count += 1;
-            }
+                if let (Some(extra), Some(extra_profile)) = (
+                    fallback_embeddings[offset].take(),
+                    fallback_profile.as_ref(),
+                ) {
+                    additional_embeddings.push(
+                        crate::services::memory_vault::AdditionalEmbeddingInput {
+                            embedding: extra,
+                            embedding_model: extra_profile.model.clone(),
+                            embedding_provider: extra_profile.provider.clone(),
+                            embedding_dim: extra_profile.dim,
+                        },
+                    );
+                }
 
-            let id = uuid::Uuid::new_v4().to_string();
-            let now = chrono::Utc::now().timestamp();
-            let mut metadata = HashMap::new();
-            metadata.insert("doc_id".to_string(), doc_id.clone());
-            metadata.insert("source_path".to_string(), source_path.to_string());
-            metadata.insert("chunk_index".to_string(), idx.to_string());
-            metadata.insert("chunk_count".to_string(), chunk_count.to_string());
-
-            vault
-                .put(StoreMemoryInput {
-                    id: id.clone(),
-                    workspace_id: workspace_id.to_string(),
-                    content: chunk.clone(),
-                    tags: tags_out.clone(),
-                    source: source_path.to_string(),
-                    sensitivity: MemorySensitivity::Internal,
-                    metadata,
-                    created_at: now,
-                    embedding,
-                    embedding_model: Some(embedding_model),
-                    embedding_provider: Some(EMBEDDING_PROVIDER.to_string()),
-                    embedding_dim: Some(crate::services::memory_vault::EMBEDDING_DIM),
-                    additional_embeddings,
-                })
-                .await
-                .map_err(MemoryError::Other)?;
+                if embedding.is_some() {
+                    embedded_count += 1;
+                    embedding_mode = primary_profile.label();
+                }
+
+                // Search results carry tags but not metadata, so the line
+                // range is a tag too for citing the chunk back to its source.
+                let mut tags = tags_out.clone();
+                tags.push(format!("lines:{}-{}", chunk.start_line, chunk.end_line));
 
-            ingested_count += 1;
+                let id = uuid::Uuid::new_v4().to_string();
+                let now = chrono::Utc::now().timestamp();
+                let mut metadata = HashMap::new();
+                metadata.insert("doc_id".to_string(), doc_id.clone());
+                metadata.insert("source_path".to_string(), source_path.to_string());
+                metadata.insert("chunk_index".to_string(), idx.to_string());
+                metadata.insert("chunk_count".to_string(), chunk_count.to_string());
+                metadata.insert("chunk_strategy".to_string(), strategy.as_str().to_string());
+                metadata.insert("start_line".to_string(), chunk.start_line.to_string());
+                metadata.insert("end_line".to_string(), chunk.end_line.to_string());
+                if !chunk.heading_path.is_empty() {
+                    metadata.insert("heading_path".to_string(), chunk.heading_path.join(" > "));
+                }
+
+                vault
+                    .put(StoreMemoryInput {
+                        id: id.clone(),
+                        workspace_id: workspace_id.to_string(),
+                        content: chunk.content.clone(),
+                        tags,
+                        source: source_path.to_string(),
+                        sensitivity: MemorySensitivity::Internal,
+                        metadata,
+                        created_at: now,
+                        embedding,
+                        embedding_model: Some(primary_profile.model.clone()),
+                        embedding_provider: Some(primary_profile.provider.clone()),
+                        embedding_dim: Some(primary_profile.dim),
+                        additional_embeddings,
+                    })
+                    .await
+                    .map_err(MemoryError::Other)?;
+
+                ingested_count += 1;
+            }
         }
 
         Ok(IngestionResult {
             chunks_ingested: ingested_count,
             chunks_embedded: embedded_count,
-            embedding_mode: if embedded_count > 0 {
-                format!("{}:{}", EMBEDDING_PROVIDER, EMBEDDING_MODEL)
-            } else {
-                "none".to_string()
-            },
+            embedding_mode,
             warnings,
         })
     }
+}
 
-    fn resolve_gemini_embedder(&self) -> Result<Option<Arc<EmbedderService>>, String> {
-        let cached = self.embedder_cache.get_or_init(|| {
-            let settings = crate::services::settings::SettingsManager::new();
-            let provider_raw = settings.get_embedder_provider().to_string();
-            let provider = match provider_raw.trim().to_lowercase().as_str() {
-                "g" | "google" | "gemini" => EMBEDDING_PROVIDER.to_string(),
-                _ => return None,
+/// Embed a search query, retrying with the embedder's fallback profile.
+/// Returns the profile the vector belongs to.
+async fn embed_query(
+    embedder: Arc<dyn Embedder>,
+    query: &str,
+) -> Result<(Vec<f32>, EmbeddingProfile), String> {
+    match embedder.embed(query, EmbeddingTaskType::RetrievalQuery).await {
+        Ok(v) => Ok((v, embedder.profile().clone())),
+        Err(primary_error) => {
+            let Some(fallback) = embedder.fallback() else {
+                return Err(primary_error);
             };
-            let keychain = crate::ai::keychain::KeychainManager::new();
-            let api_key = keychain
-                .get_key(EMBEDDING_PROVIDER)
-                .or_else(|_| keychain.get_key(&provider_raw))
-                .unwrap_or_default()
-                .unwrap_or_default();
-            if api_key.trim().is_empty() {
-                return None;
-            }
-            Some(Arc::new(EmbedderService::new(
-                provider,
-                api_key,
-                Some(EMBEDDING_MODEL.to_string()),
-            )))
-        });
-        Ok(cached.clone())
+            fallback
+                .embed(query, EmbeddingTaskType::RetrievalQuery)
+                .await
+                .map(|v| (v, fallback.profile().clone()))
+                .map_err(|fallback_error| {
+                    format!(
+                        "Embedding failed for '{}' and fallback '{}': {} | {}",
+                        embedder.profile().model,
+                        fallback.profile().model,
+                        primary_error,
+                        fallback_error
+                    )
+                })
+        }
     }
 }
 
@@ -663,10 +989,21 @@ fn lexical_overlap_score(tokens: &[String], content: &str) -> f64 {
     hits as f64 / tokens.len() as f64
 }
 
-fn recency_score(created_at: i64, now: i64) -> f64 {
-    let age_seconds = now.saturating_sub(created_at).max(0);
-    let age_hours = age_seconds as f64 / 3600.0;
-    (-age_hours / 168.0).exp().clamp(0.0, 1.0)
+/// Query relevance blended with stored importance and recency per the
+/// workspace's retention policy.
+fn blended_score(
+    relevance: f64,
+    entry: &crate::services::memory_vault::types::DecryptedMemoryEntry,
+    now: i64,
+    policy: &RetentionPolicy,
+) -> f64 {
+    let importance_weight = f64::from(policy.importance_weight);
+    let recency_weight = f64::from(policy.recency_weight);
+    let relevance_weight = (1.0 - importance_weight - recency_weight).max(0.0);
+    let recency = recency_decay(entry.created_at, now, policy.recency_half_life_days);
+    relevance_weight * relevance
+        + importance_weight * f64::from(entry.importance)
+        + recency_weight * f64::from(recency)
 }
 
 fn access_score(access_count: i64) -> f64 {
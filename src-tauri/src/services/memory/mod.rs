@@ -3,12 +3,15 @@
 //! Thin compatibility layer used by Tauri commands and the skill executor.
 //! Long-term storage is backed by `memory_vault` (encrypted at rest).
 
+pub mod chunking;
 pub mod memory_manager;
 pub mod types;
 
+pub use chunking::{ChunkOptions, ChunkStrategy};
 pub use memory_manager::MemoryManager;
 pub use types::{
-    IngestionResult, MemoryEntry, SemanticRetrievalMode, SemanticSearchResult,
+    IngestionResult, MemoryEntry, RetrievalStrategy, SearchOptions, SemanticRetrievalMode,
+    SemanticSearchResult,
 };
 
 #[derive(Debug, thiserror::Error)]
@@ -14,6 +14,9 @@ pub struct MemoryEntry {
     pub timestamp: DateTime<Utc>,
     /// Tags for categorization and retrieval
     pub tags: Vec<String>,
+    /// Vault importance score (0-1); 0.5 until maintenance has scored it
+    #[serde(default)]
+    pub importance: f32,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -21,9 +24,41 @@ pub struct MemoryEntry {
 pub enum SemanticRetrievalMode {
     Ann,
     Exact,
+    /// BM25 and vector rankings fused.
+    Hybrid,
+    /// BM25 only, by configuration.
+    Lexical,
     LexicalFallback,
 }
 
+/// How long-term memory is searched; parsed from `MemoryConfig.strategy`.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum RetrievalStrategy {
+    #[default]
+    Hybrid,
+    Vector,
+    Lexical,
+}
+
+impl RetrievalStrategy {
+    /// `simple_buffer` specs never embed, so they search keywords only.
+    pub fn from_spec(strategy: &str) -> Self {
+        match strategy.trim().to_lowercase().as_str() {
+            "vector" => Self::Vector,
+            "simple_buffer" | "lexical" | "keyword" => Self::Lexical,
+            _ => Self::Hybrid,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SearchOptions {
+    pub strategy: RetrievalStrategy,
+    /// Re-score fused candidates on term coverage and adjacency in the full text.
+    pub rerank: bool,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SemanticSearchResult {
     pub entries: Vec<MemoryEntry>,
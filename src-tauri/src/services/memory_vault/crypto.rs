@@ -1,8 +1,13 @@
 use aes_gcm::aead::{Aead, KeyInit};
 use aes_gcm::{Aes256Gcm, Key, Nonce};
+use hmac::{Hmac, Mac};
 use rand::RngCore;
 use sha2::{Digest, Sha256};
 
+type HmacSha256 = Hmac<Sha256>;
+
+const TERM_INDEX_KEY_CONTEXT: &[u8] = b"memory-vault-term-index-v1";
+
 #[derive(Debug, Clone)]
 pub struct EncryptedPayload {
     pub ciphertext: Vec<u8>,
@@ -63,6 +68,30 @@ pub fn decrypt_bytes(
         .map_err(|e| format!("Vault decryption failed: {}", e))
 }
 
+/// Key for hashing index terms; separate from the content keys so the
+/// lexical index can't be used to recover them.
+pub fn derive_term_key(master_key: &[u8]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(master_key);
+    hasher.update(TERM_INDEX_KEY_CONTEXT);
+    let digest = hasher.finalize();
+    let mut key = [0u8; 32];
+    key.copy_from_slice(&digest[..32]);
+    key
+}
+
+/// Opaque, workspace-scoped token for a search term. The lexical index stores
+/// these instead of plaintext words; equal terms in one workspace collide on
+/// purpose so they can be looked up.
+pub fn term_hash(term_key: &[u8; 32], workspace_id: &str, term: &str) -> Vec<u8> {
+    let mut mac =
+        <HmacSha256 as Mac>::new_from_slice(term_key).expect("HMAC accepts any key length");
+    mac.update(workspace_id.as_bytes());
+    mac.update(&[0]);
+    mac.update(term.as_bytes());
+    mac.finalize().into_bytes()[..16].to_vec()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -145,4 +174,17 @@ mod tests {
         );
         assert!(res3.is_err());
     }
+
+    #[test]
+    fn test_term_hash_is_keyed_and_workspace_scoped() {
+        let key = derive_term_key(b"0123456789abcdef0123456789abcdef");
+        let other_key = derive_term_key(b"abcdef0123456789abcdef0123456789");
+
+        let hash = term_hash(&key, "ws-a", "revenue");
+        assert_eq!(hash.len(), 16);
+        assert_eq!(hash, term_hash(&key, "ws-a", "revenue"));
+        assert_ne!(hash, term_hash(&key, "ws-b", "revenue"));
+        assert_ne!(hash, term_hash(&other_key, "ws-a", "revenue"));
+        assert_ne!(hash, term_hash(&key, "ws-a", "revenues"));
+    }
 }
@@ -0,0 +1,182 @@
+//! Tokenization, BM25 scoring and rank fusion for keyword retrieval over the
+//! vault. The index itself stores only keyed term hashes (see
+//! `crypto::term_hash`); everything here works on plaintext in memory.
+
+use std::collections::{HashMap, HashSet};
+
+/// Constant `k` of reciprocal rank fusion; damps the head of each list.
+pub const RRF_K: f64 = 60.0;
+
+const BM25_K1: f64 = 1.2;
+const BM25_B: f64 = 0.75;
+const MAX_TOKEN_CHARS: usize = 48;
+
+const STOP_WORDS: &[&str] = &[
+    "an", "and", "are", "as", "at", "be", "but", "by", "do", "for", "from", "has", "have", "if",
+    "in", "into", "is", "it", "its", "of", "on", "or", "so", "than", "that", "the", "then",
+    "there", "these", "this", "those", "to", "was", "we", "were", "what", "when", "where", "which",
+    "who", "will", "with",
+];
+
+/// Lowercased alphanumeric words, without stop words and single characters.
+pub fn tokenize(text: &str) -> Vec<String> {
+    text.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| {
+            let len = word.chars().count();
+            (2..=MAX_TOKEN_CHARS).contains(&len) && !STOP_WORDS.contains(word)
+        })
+        .map(str::to_string)
+        .collect()
+}
+
+/// Distinct query terms in first-seen order.
+pub fn query_terms(query: &str) -> Vec<String> {
+    let mut seen = HashSet::new();
+    tokenize(query)
+        .into_iter()
+        .filter(|term| seen.insert(term.clone()))
+        .collect()
+}
+
+/// Term frequencies and token count of a document.
+pub fn term_frequencies(text: &str) -> (HashMap<String, u32>, u32) {
+    let mut frequencies = HashMap::new();
+    let mut length = 0u32;
+    for token in tokenize(text) {
+        *frequencies.entry(token).or_insert(0) += 1;
+        length += 1;
+    }
+    (frequencies, length)
+}
+
+/// Okapi BM25 contribution of one term to one document.
+pub fn bm25_term_score(
+    term_frequency: u32,
+    document_frequency: usize,
+    document_count: usize,
+    document_length: u32,
+    average_length: f64,
+) -> f64 {
+    if term_frequency == 0 || document_count == 0 {
+        return 0.0;
+    }
+    let n = document_count as f64;
+    let df = document_frequency.min(document_count) as f64;
+    let idf = (1.0 + (n - df + 0.5) / (df + 0.5)).ln();
+    let tf = f64::from(term_frequency);
+    let length_ratio = if average_length > 0.0 {
+        f64::from(document_length) / average_length
+    } else {
+        1.0
+    };
+    idf * tf * (BM25_K1 + 1.0) / (tf + BM25_K1 * (1.0 - BM25_B + BM25_B * length_ratio))
+}
+
+/// Fuse ranked id lists: each list contributes `1 / (k + rank)` per id.
+/// Highest fused score first; ties keep first-seen order.
+pub fn reciprocal_rank_fusion(rankings: &[Vec<String>], k: f64) -> Vec<(String, f64)> {
+    let mut order = Vec::new();
+    let mut scores: HashMap<String, f64> = HashMap::new();
+    for ranking in rankings {
+        for (rank, id) in ranking.iter().enumerate() {
+            let score = scores.entry(id.clone()).or_insert_with(|| {
+                order.push(id.clone());
+                0.0
+            });
+            *score += 1.0 / (k + rank as f64 + 1.0);
+        }
+    }
+
+    let mut fused = order
+        .into_iter()
+        .map(|id| {
+            let score = scores[&id];
+            (id, score)
+        })
+        .collect::<Vec<_>>();
+    fused.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+    fused
+}
+
+/// Second-pass relevance in [0, 1] from the full text: how many query terms
+/// the content covers, and how many adjacent query-term pairs it keeps
+/// adjacent.
+pub fn rerank_score(query_terms: &[String], content: &str) -> f64 {
+    if query_terms.is_empty() {
+        return 0.0;
+    }
+    let tokens = tokenize(content);
+    let present = tokens.iter().map(String::as_str).collect::<HashSet<_>>();
+    let covered = query_terms
+        .iter()
+        .filter(|term| present.contains(term.as_str()))
+        .count();
+    let coverage = covered as f64 / query_terms.len() as f64;
+
+    if query_terms.len() < 2 {
+        return coverage;
+    }
+    let bigrams = tokens
+        .windows(2)
+        .map(|pair| (pair[0].as_str(), pair[1].as_str()))
+        .collect::<HashSet<_>>();
+    let adjacent = query_terms
+        .windows(2)
+        .filter(|pair| bigrams.contains(&(pair[0].as_str(), pair[1].as_str())))
+        .count();
+    let proximity = adjacent as f64 / (query_terms.len() - 1) as f64;
+
+    0.7 * coverage + 0.3 * proximity
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tokenize_drops_stop_words_and_short_tokens() {
+        assert_eq!(
+            tokenize("The Q3 revenue-report is in a PDF!"),
+            vec!["q3", "revenue", "report", "pdf"]
+        );
+        assert_eq!(query_terms("report the report"), vec!["report"]);
+        let (frequencies, length) = term_frequencies("report on report drafts");
+        assert_eq!(frequencies["report"], 2);
+        assert_eq!(length, 3);
+    }
+
+    #[test]
+    fn test_bm25_prefers_rare_terms_and_short_documents() {
+        let rare = bm25_term_score(1, 1, 100, 50, 50.0);
+        let common = bm25_term_score(1, 80, 100, 50, 50.0);
+        assert!(rare > common);
+
+        let short = bm25_term_score(2, 5, 100, 20, 50.0);
+        let long = bm25_term_score(2, 5, 100, 200, 50.0);
+        assert!(short > long);
+        assert_eq!(bm25_term_score(0, 5, 100, 20, 50.0), 0.0);
+    }
+
+    #[test]
+    fn test_rank_fusion_rewards_agreement() {
+        let lexical = vec!["a".to_string(), "b".to_string(), "c".to_string()];
+        let vector = vec!["b".to_string(), "c".to_string(), "d".to_string()];
+        let fused = reciprocal_rank_fusion(&[lexical, vector], RRF_K);
+        let ids = fused.iter().map(|(id, _)| id.as_str()).collect::<Vec<_>>();
+        assert_eq!(ids[0], "b");
+        assert_eq!(ids.len(), 4);
+        assert_eq!(ids[3], "d");
+    }
+
+    #[test]
+    fn test_rerank_rewards_coverage_and_adjacency() {
+        let terms = query_terms("quarterly revenue report");
+        let exact = rerank_score(&terms, "Quarterly revenue report for Acme");
+        let scattered = rerank_score(&terms, "Report: revenue was up this quarterly cycle");
+        let partial = rerank_score(&terms, "Revenue grew");
+        assert!((exact - 1.0).abs() < 1e-9);
+        assert!(exact > scattered);
+        assert!(scattered > partial);
+    }
+}
@@ -0,0 +1,165 @@
+//! Importance scoring, recency decay and near-duplicate detection for vault
+//! entries. Pure functions; `MemoryVaultService::run_maintenance` applies them.
+
+use super::types::RetentionPolicy;
+
+const SECONDS_PER_DAY: f64 = 86_400.0;
+
+/// An embedded entry considered for consolidation.
+#[derive(Debug, Clone)]
+pub struct DedupCandidate {
+    pub id: String,
+    pub importance: f32,
+    pub sensitivity: String,
+    pub model: String,
+    pub embedding: Vec<f32>,
+}
+
+/// How much an entry's origin counts toward its importance. User notes and
+/// indexed documents outrank conversation turns, which outrank raw tool and
+/// web output.
+pub fn source_weight(source: &str) -> f32 {
+    if source.starts_with("tool:") {
+        0.3
+    } else if source.starts_with("web:") {
+        0.35
+    } else if source == "agent_conversation" {
+        0.5
+    } else {
+        0.7
+    }
+}
+
+/// 1.0 for an entry touched at `now`, halving every `half_life_days`.
+pub fn recency_decay(timestamp: i64, now: i64, half_life_days: u32) -> f32 {
+    let age_days = now.saturating_sub(timestamp).max(0) as f64 / SECONDS_PER_DAY;
+    0.5_f64.powf(age_days / f64::from(half_life_days.max(1))) as f32
+}
+
+/// Importance in [0, 1] from origin, access count and last access. Pinned
+/// entries always score 1.0.
+pub fn score_importance(
+    source: &str,
+    access_count: i64,
+    last_accessed: i64,
+    pinned: bool,
+    now: i64,
+    policy: &RetentionPolicy,
+) -> f32 {
+    if pinned {
+        return 1.0;
+    }
+    let usage = 1.0 - (-(access_count.max(0) as f32) / 5.0).exp();
+    let freshness = recency_decay(last_accessed, now, policy.recency_half_life_days);
+    (0.35 * source_weight(source) + 0.45 * usage + 0.2 * freshness).clamp(0.0, 1.0)
+}
+
+pub fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() || a.is_empty() {
+        return 0.0;
+    }
+    let (mut dot, mut norm_a, mut norm_b) = (0.0f32, 0.0f32, 0.0f32);
+    for (x, y) in a.iter().zip(b) {
+        dot += x * y;
+        norm_a += x * x;
+        norm_b += y * y;
+    }
+    if norm_a == 0.0 || norm_b == 0.0 {
+        return 0.0;
+    }
+    dot / (norm_a.sqrt() * norm_b.sqrt())
+}
+
+/// `(keep_id, drop_id)` pairs for entries at or above `threshold` cosine
+/// similarity. Candidates are visited by descending importance, so the more
+/// important of two near-duplicates is kept. Entries of different sensitivity
+/// or embedding model are never merged.
+pub fn find_near_duplicates(
+    candidates: &[DedupCandidate],
+    threshold: f32,
+) -> Vec<(String, String)> {
+    let mut order = (0..candidates.len()).collect::<Vec<_>>();
+    order.sort_by(|a, b| {
+        candidates[*b]
+            .importance
+            .partial_cmp(&candidates[*a].importance)
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+
+    let mut kept: Vec<usize> = Vec::new();
+    let mut pairs = Vec::new();
+    for idx in order {
+        let candidate = &candidates[idx];
+        let duplicate_of = kept.iter().copied().find(|k| {
+            let keeper = &candidates[*k];
+            keeper.sensitivity == candidate.sensitivity
+                && keeper.model == candidate.model
+                && cosine_similarity(&keeper.embedding, &candidate.embedding) >= threshold
+        });
+        match duplicate_of {
+            Some(k) => pairs.push((candidates[k].id.clone(), candidate.id.clone())),
+            None => kept.push(idx),
+        }
+    }
+    pairs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY: i64 = 86_400;
+
+    #[test]
+    fn test_importance_reflects_usage_source_and_pins() {
+        let policy = RetentionPolicy::default();
+        let now = 100 * DAY;
+
+        let unused_note = score_importance("user", 0, now - 40 * DAY, false, now, &policy);
+        let used_note = score_importance("user", 20, now - 40 * DAY, false, now, &policy);
+        let used_tool =
+            score_importance("tool:web_search", 20, now - 40 * DAY, false, now, &policy);
+        let fresh_note = score_importance("user", 0, now, false, now, &policy);
+
+        assert!(used_note > unused_note);
+        assert!(used_note > used_tool);
+        assert!(fresh_note > unused_note);
+        assert!(used_note >= policy.keep_importance_above);
+        assert!(unused_note < policy.keep_importance_above);
+        assert_eq!(
+            score_importance("tool:read_file", 0, 0, true, now, &policy),
+            1.0
+        );
+    }
+
+    #[test]
+    fn test_recency_decay_halves_per_half_life() {
+        let now = 30 * DAY;
+        assert!((recency_decay(now, now, 7) - 1.0).abs() < 1e-6);
+        assert!((recency_decay(now - 7 * DAY, now, 7) - 0.5).abs() < 1e-6);
+        assert!((recency_decay(now - 14 * DAY, now, 7) - 0.25).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_near_duplicates_keep_the_more_important_entry() {
+        let candidate =
+            |id: &str, importance: f32, sensitivity: &str, model: &str, embedding| DedupCandidate {
+                id: id.to_string(),
+                importance,
+                sensitivity: sensitivity.to_string(),
+                model: model.to_string(),
+                embedding,
+            };
+        let candidates = vec![
+            candidate("low", 0.2, "internal", "a", vec![1.0, 0.0, 0.01]),
+            candidate("high", 0.8, "internal", "a", vec![1.0, 0.0, 0.0]),
+            candidate("secret", 0.1, "confidential", "a", vec![1.0, 0.0, 0.0]),
+            candidate("local", 0.1, "internal", "b", vec![1.0, 0.0, 0.0]),
+            candidate("other", 0.5, "internal", "a", vec![0.0, 1.0, 0.0]),
+        ];
+
+        let pairs = find_near_duplicates(&candidates, 0.95);
+        assert_eq!(pairs, vec![("high".to_string(), "low".to_string())]);
+        assert!(find_near_duplicates(&candidates, 1.01).is_empty());
+    }
+}
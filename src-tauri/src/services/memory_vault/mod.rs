@@ -1,5 +1,7 @@
 pub mod crypto;
 pub mod key_provider;
+pub mod lexical;
+pub mod lifecycle;
 pub mod profiles;
 pub mod repository;
 pub mod service;
@@ -8,9 +10,8 @@ pub mod types;
 pub use service::MemoryVaultService;
 #[allow(unused_imports)]
 pub use service::VectorSearchMode;
+pub use profiles::EmbeddingProfile;
 pub use types::{
-    AdditionalEmbeddingInput, MemorySensitivity, StoreMemoryInput, EMBEDDING_MODEL,
-    EMBEDDING_PROVIDER,
+    AdditionalEmbeddingInput, MaintenanceReport, MemorySensitivity, RetentionPolicy,
+    StoreMemoryInput,
 };
-#[allow(unused_imports)]
-pub use types::EMBEDDING_DIM;
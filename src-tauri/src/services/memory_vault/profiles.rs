@@ -1,18 +1,64 @@
-#[derive(Debug, Clone, Copy)]
+use serde::{Deserialize, Serialize};
+
+/// Width of the vault's `F32_BLOB` vector columns. Shorter embeddings are
+/// zero-padded on write, which leaves cosine distance unchanged.
+pub const VECTOR_COLUMN_DIM: usize = 3072;
+
+pub const GEMINI_PROVIDER: &str = "gemini";
+pub const OPENAI_COMPATIBLE_PROVIDER: &str = "openai";
+pub const LOCAL_PROVIDER: &str = "local";
+
+pub const GEMINI_EMBEDDING_2_PREVIEW: &str = "gemini-embedding-2-preview";
+pub const GEMINI_EMBEDDING_001: &str = "gemini-embedding-001";
+pub const LOCAL_HASH_EMBEDDING: &str = "local-hash-v1";
+
+/// Provider, model and vector size that vault entries are embedded with.
+/// Vectors from different profiles are never compared.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct EmbeddingProfile {
-    pub model: &'static str,
+    pub provider: String,
+    pub model: String,
     pub dim: usize,
 }
 
-pub const GEMINI_EMBEDDING_2_PREVIEW: EmbeddingProfile = EmbeddingProfile {
-    model: "gemini-embedding-2-preview",
-    dim: 3072,
-};
+impl EmbeddingProfile {
+    pub fn new(provider: &str, model: &str, dim: usize) -> Self {
+        Self {
+            provider: provider.to_string(),
+            model: model.to_string(),
+            dim,
+        }
+    }
+
+    pub fn gemini() -> Self {
+        Self::new(GEMINI_PROVIDER, GEMINI_EMBEDDING_2_PREVIEW, 3072)
+    }
 
-pub const GEMINI_EMBEDDING_001: EmbeddingProfile = EmbeddingProfile {
-    model: "gemini-embedding-001",
-    dim: 3072,
-};
+    /// Older Gemini model kept as a second vector per entry during ingestion.
+    pub fn gemini_fallback() -> Self {
+        Self::new(GEMINI_PROVIDER, GEMINI_EMBEDDING_001, 3072)
+    }
 
-pub const ACTIVE_EMBEDDING_PROFILE: EmbeddingProfile = GEMINI_EMBEDDING_2_PREVIEW;
-pub const FALLBACK_EMBEDDING_PROFILE: EmbeddingProfile = GEMINI_EMBEDDING_001;
+    pub fn local() -> Self {
+        Self::new(LOCAL_PROVIDER, LOCAL_HASH_EMBEDDING, 1024)
+    }
+
+    /// Migration marker recording that existing entries were re-embedded
+    /// with this profile.
+    pub fn backfill_key(&self) -> String {
+        format!(
+            "migrate_memory_reembed_{}_{}_{}",
+            self.provider, self.model, self.dim
+        )
+    }
+
+    pub fn label(&self) -> String {
+        format!("{}:{}", self.provider, self.model)
+    }
+}
+
+impl Default for EmbeddingProfile {
+    fn default() -> Self {
+        Self::gemini()
+    }
+}
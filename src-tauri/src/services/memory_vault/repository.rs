@@ -1,3 +1,4 @@
+use super::profiles::VECTOR_COLUMN_DIM;
 use libsql::{params, Builder, Connection};
 use std::path::PathBuf;
 
@@ -23,6 +24,33 @@ pub struct VaultRow {
     pub embedding_model: Option<String>,
     pub embedding_provider: Option<String>,
     pub embedding_dim: Option<usize>,
+    pub importance: f32,
+    pub pinned: bool,
+}
+
+/// The columns maintenance needs to rescore and expire an entry.
+#[derive(Debug, Clone)]
+pub struct LifecycleRow {
+    pub id: String,
+    pub source: String,
+    pub last_accessed: i64,
+    pub access_count: i64,
+    pub pinned: bool,
+}
+
+/// Keyed term hashes and frequencies for one entry's lexical index.
+#[derive(Debug, Clone, Default)]
+pub struct TermIndex {
+    pub doc_length: u32,
+    pub postings: Vec<(Vec<u8>, u32)>,
+}
+
+/// One entry's occurrences of a searched term.
+#[derive(Debug, Clone)]
+pub struct TermPosting {
+    pub entry_id: String,
+    pub term_frequency: u32,
+    pub doc_length: u32,
 }
 
 #[derive(Debug, Clone)]
@@ -120,6 +148,20 @@ impl MemoryVaultRepository {
         .await
         .map_err(|e| format!("Failed to create vault table: {}", e))?;
 
+        // Lifecycle columns postdate the table; ADD COLUMN fails harmlessly once they exist.
+        let _ = conn
+            .execute(
+                "ALTER TABLE memory_vault_entries ADD COLUMN importance REAL NOT NULL DEFAULT 0.5",
+                (),
+            )
+            .await;
+        let _ = conn
+            .execute(
+                "ALTER TABLE memory_vault_entries ADD COLUMN pinned INTEGER NOT NULL DEFAULT 0",
+                (),
+            )
+            .await;
+
         conn.execute(
             "CREATE INDEX IF NOT EXISTS idx_memory_vault_workspace_time
              ON memory_vault_entries(workspace_id, created_at DESC)",
@@ -184,6 +226,47 @@ impl MemoryVaultRepository {
             )
             .await;
 
+        // Lexical index: postings keyed by HMAC term hashes, never plaintext words.
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS memory_vault_term_postings (
+                term_hash BLOB NOT NULL,
+                entry_id TEXT NOT NULL,
+                workspace_id TEXT NOT NULL,
+                term_frequency INTEGER NOT NULL,
+                PRIMARY KEY(term_hash, entry_id)
+            )",
+            (),
+        )
+        .await
+        .map_err(|e| format!("Failed to create term postings table: {}", e))?;
+
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_memory_vault_term_postings_entry
+             ON memory_vault_term_postings(entry_id)",
+            (),
+        )
+        .await
+        .map_err(|e| format!("Failed to create term postings index: {}", e))?;
+
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS memory_vault_term_docs (
+                entry_id TEXT PRIMARY KEY,
+                workspace_id TEXT NOT NULL,
+                doc_length INTEGER NOT NULL
+            )",
+            (),
+        )
+        .await
+        .map_err(|e| format!("Failed to create term docs table: {}", e))?;
+
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_memory_vault_term_docs_workspace
+             ON memory_vault_term_docs(workspace_id)",
+            (),
+        )
+        .await
+        .map_err(|e| format!("Failed to create term docs index: {}", e))?;
+
         Ok(Self { conn })
     }
 
@@ -206,8 +289,8 @@ impl MemoryVaultRepository {
                source = excluded.source,
                sensitivity = excluded.sensitivity,
                created_at = excluded.created_at,
-               last_accessed = excluded.last_accessed,
-               access_count = excluded.access_count,
+               last_accessed = MAX(last_accessed, excluded.last_accessed),
+               access_count = MAX(access_count, excluded.access_count),
                content_ciphertext = excluded.content_ciphertext,
                content_nonce = excluded.content_nonce,
                tags_ciphertext = excluded.tags_ciphertext,
@@ -282,11 +365,181 @@ impl MemoryVaultRepository {
         Ok(())
     }
 
+    async fn write_term_index(
+        &self,
+        entry_id: &str,
+        workspace_id: &str,
+        index: &TermIndex,
+    ) -> Result<(), String> {
+        self.conn
+            .execute(
+                "DELETE FROM memory_vault_term_postings WHERE entry_id = ?1",
+                params![entry_id.to_string()],
+            )
+            .await
+            .map_err(|e| format!("Failed to clear term postings: {}", e))?;
+        for (term_hash, term_frequency) in &index.postings {
+            self.conn
+                .execute(
+                    "INSERT INTO memory_vault_term_postings
+                     (term_hash, entry_id, workspace_id, term_frequency)
+                     VALUES (?1, ?2, ?3, ?4)",
+                    params![
+                        term_hash.clone(),
+                        entry_id.to_string(),
+                        workspace_id.to_string(),
+                        i64::from(*term_frequency)
+                    ],
+                )
+                .await
+                .map_err(|e| format!("Failed to insert term posting: {}", e))?;
+        }
+        self.conn
+            .execute(
+                "INSERT INTO memory_vault_term_docs (entry_id, workspace_id, doc_length)
+                 VALUES (?1, ?2, ?3)
+                 ON CONFLICT(entry_id) DO UPDATE SET
+                    workspace_id = excluded.workspace_id,
+                    doc_length = excluded.doc_length",
+                params![
+                    entry_id.to_string(),
+                    workspace_id.to_string(),
+                    i64::from(index.doc_length)
+                ],
+            )
+            .await
+            .map_err(|e| format!("Failed to upsert term doc: {}", e))?;
+        Ok(())
+    }
+
+    /// Remove lexical index rows whose entry no longer exists.
+    async fn delete_orphaned_terms(&self, workspace_id: &str) -> Result<(), String> {
+        self.conn
+            .execute(
+                "DELETE FROM memory_vault_term_postings
+                 WHERE workspace_id = ?1
+                   AND entry_id NOT IN (SELECT id FROM memory_vault_entries WHERE workspace_id = ?1)",
+                params![workspace_id.to_string()],
+            )
+            .await
+            .map_err(|e| format!("Failed to delete orphaned term postings: {}", e))?;
+        self.conn
+            .execute(
+                "DELETE FROM memory_vault_term_docs
+                 WHERE workspace_id = ?1
+                   AND entry_id NOT IN (SELECT id FROM memory_vault_entries WHERE workspace_id = ?1)",
+                params![workspace_id.to_string()],
+            )
+            .await
+            .map_err(|e| format!("Failed to delete orphaned term docs: {}", e))?;
+        Ok(())
+    }
+
+    /// Replace one entry's lexical index.
+    pub async fn replace_term_index(
+        &self,
+        entry_id: &str,
+        workspace_id: &str,
+        index: &TermIndex,
+    ) -> Result<(), String> {
+        self.conn
+            .execute("BEGIN IMMEDIATE TRANSACTION", ())
+            .await
+            .map_err(|e| format!("Failed to begin term index transaction: {}", e))?;
+
+        match self.write_term_index(entry_id, workspace_id, index).await {
+            Ok(()) => {
+                self.conn
+                    .execute("COMMIT", ())
+                    .await
+                    .map_err(|e| format!("Failed to commit term index: {}", e))?;
+                Ok(())
+            }
+            Err(err) => {
+                let _ = self.conn.execute("ROLLBACK", ()).await;
+                Err(err)
+            }
+        }
+    }
+
+    /// Entries without a lexical index yet, oldest first.
+    pub async fn rows_missing_term_index(&self, limit: usize) -> Result<Vec<VaultRow>, String> {
+        let mut rows = self.conn.query(
+            "SELECT e.id, e.workspace_id, e.source, e.sensitivity, e.created_at, e.last_accessed, e.access_count,
+                    e.content_ciphertext, e.content_nonce, e.tags_ciphertext, e.tags_nonce, e.metadata_ciphertext, e.metadata_nonce, e.embedding, e.embedding_model, e.embedding_provider, e.embedding_dim, e.importance, e.pinned
+             FROM memory_vault_entries e
+             LEFT JOIN memory_vault_term_docs d ON d.entry_id = e.id
+             WHERE d.entry_id IS NULL
+             ORDER BY e.created_at ASC
+             LIMIT ?1",
+            params![limit as i64]
+        )
+        .await
+        .map_err(|e| format!("Failed to query rows missing term index: {}", e))?;
+
+        let mut results = Vec::new();
+        while let Some(row) = rows.next().await.map_err(|e| e.to_string())? {
+            results.push(row_to_vault(&row)?);
+        }
+        Ok(results)
+    }
+
+    /// Indexed document count and mean document length for a workspace.
+    pub async fn term_corpus_stats(&self, workspace_id: &str) -> Result<(usize, f64), String> {
+        let mut rows = self
+            .conn
+            .query(
+                "SELECT COUNT(*), COALESCE(AVG(doc_length), 0.0)
+                 FROM memory_vault_term_docs WHERE workspace_id = ?1",
+                params![workspace_id.to_string()],
+            )
+            .await
+            .map_err(|e| format!("Failed to read term corpus stats: {}", e))?;
+
+        match rows.next().await.map_err(|e| e.to_string())? {
+            Some(row) => Ok((
+                row.get::<i64>(0).unwrap_or(0).max(0) as usize,
+                row.get::<f64>(1).unwrap_or(0.0),
+            )),
+            None => Ok((0, 0.0)),
+        }
+    }
+
+    /// Every posting of `term_hash` in a workspace, with its document length.
+    pub async fn term_postings(
+        &self,
+        workspace_id: &str,
+        term_hash: &[u8],
+    ) -> Result<Vec<TermPosting>, String> {
+        let mut rows = self
+            .conn
+            .query(
+                "SELECT p.entry_id, p.term_frequency, d.doc_length
+                 FROM memory_vault_term_postings p
+                 JOIN memory_vault_term_docs d ON d.entry_id = p.entry_id
+                 WHERE p.term_hash = ?1 AND p.workspace_id = ?2",
+                params![term_hash.to_vec(), workspace_id.to_string()],
+            )
+            .await
+            .map_err(|e| format!("Failed to read term postings: {}", e))?;
+
+        let mut postings = Vec::new();
+        while let Some(row) = rows.next().await.map_err(|e| e.to_string())? {
+            postings.push(TermPosting {
+                entry_id: row.get::<String>(0).map_err(|e| e.to_string())?,
+                term_frequency: row.get::<i64>(1).unwrap_or(0).max(0) as u32,
+                doc_length: row.get::<i64>(2).unwrap_or(0).max(0) as u32,
+            });
+        }
+        Ok(postings)
+    }
+
     pub async fn upsert_encrypted_atomic(
         &self,
         row: &VaultRow,
         key_version: i64,
         embedding_rows: Vec<(String, String, usize, Vec<u8>)>,
+        term_index: Option<&TermIndex>,
     ) -> Result<(), String> {
         self.conn
             .execute("BEGIN IMMEDIATE TRANSACTION", ())
@@ -309,6 +562,11 @@ impl MemoryVaultRepository {
                 .await?;
             }
 
+            if let Some(index) = term_index {
+                self.write_term_index(&row.id, &row.workspace_id, index)
+                    .await?;
+            }
+
             Ok::<(), String>(())
         }
         .await;
@@ -335,7 +593,7 @@ impl MemoryVaultRepository {
     ) -> Result<Vec<VaultRow>, String> {
         let mut rows = self.conn.query(
             "SELECT id, workspace_id, source, sensitivity, created_at, last_accessed, access_count,
-                    content_ciphertext, content_nonce, tags_ciphertext, tags_nonce, metadata_ciphertext, metadata_nonce, embedding, embedding_model, embedding_provider, embedding_dim
+                    content_ciphertext, content_nonce, tags_ciphertext, tags_nonce, metadata_ciphertext, metadata_nonce, embedding, embedding_model, embedding_provider, embedding_dim, importance, pinned
              FROM memory_vault_entries
              WHERE workspace_id = ?1
              ORDER BY created_at DESC
@@ -356,7 +614,7 @@ impl MemoryVaultRepository {
     pub async fn get_by_id(&self, id: &str) -> Result<Option<VaultRow>, String> {
         let mut rows = self.conn.query(
             "SELECT id, workspace_id, source, sensitivity, created_at, last_accessed, access_count,
-                    content_ciphertext, content_nonce, tags_ciphertext, tags_nonce, metadata_ciphertext, metadata_nonce, embedding, embedding_model, embedding_provider, embedding_dim
+                    content_ciphertext, content_nonce, tags_ciphertext, tags_nonce, metadata_ciphertext, metadata_nonce, embedding, embedding_model, embedding_provider, embedding_dim, importance, pinned
              FROM memory_vault_entries WHERE id = ?1",
             params![id.to_string()]
         )
@@ -385,9 +643,73 @@ impl MemoryVaultRepository {
                 params![id.to_string()],
             )
             .await;
+        let _ = self
+            .conn
+            .execute(
+                "DELETE FROM memory_vault_term_postings WHERE entry_id = ?1",
+                params![id.to_string()],
+            )
+            .await;
+        let _ = self
+            .conn
+            .execute(
+                "DELETE FROM memory_vault_term_docs WHERE entry_id = ?1",
+                params![id.to_string()],
+            )
+            .await;
         Ok(())
     }
 
+    /// Delete every entry a workspace holds from one source. Returns how many
+    /// entries were removed.
+    pub async fn delete_source(&self, workspace_id: &str, source: &str) -> Result<usize, String> {
+        self.conn
+            .execute("BEGIN IMMEDIATE TRANSACTION", ())
+            .await
+            .map_err(|e| format!("Failed to begin source delete transaction: {}", e))?;
+
+        let result = async {
+            self.conn
+                .execute(
+                    "DELETE FROM memory_vault_embedding_vectors
+                     WHERE entry_id IN (
+                        SELECT id FROM memory_vault_entries WHERE workspace_id = ?1 AND source = ?2
+                     )",
+                    params![workspace_id.to_string(), source.to_string()],
+                )
+                .await
+                .map_err(|e| format!("Failed to delete vault vectors by source: {}", e))?;
+
+            let deleted = self
+                .conn
+                .execute(
+                    "DELETE FROM memory_vault_entries WHERE workspace_id = ?1 AND source = ?2",
+                    params![workspace_id.to_string(), source.to_string()],
+                )
+                .await
+                .map_err(|e| format!("Failed to delete vault entries by source: {}", e))?;
+
+            self.delete_orphaned_terms(workspace_id).await?;
+
+            Ok::<usize, String>(deleted as usize)
+        }
+        .await;
+
+        match result {
+            Ok(deleted) => {
+                self.conn
+                    .execute("COMMIT", ())
+                    .await
+                    .map_err(|e| format!("Failed to commit source delete transaction: {}", e))?;
+                Ok(deleted)
+            }
+            Err(err) => {
+                let _ = self.conn.execute("ROLLBACK", ()).await;
+                Err(err)
+            }
+        }
+    }
+
     pub async fn delete_workspace(&self, workspace_id: &str) -> Result<(), String> {
         self.conn
             .execute("BEGIN IMMEDIATE TRANSACTION", ())
@@ -411,6 +733,8 @@ impl MemoryVaultRepository {
                 .await
                 .map_err(|e| format!("Failed to delete vault vectors by workspace: {}", e))?;
 
+            self.delete_orphaned_terms(workspace_id).await?;
+
             Ok::<(), String>(())
         }
         .await;
@@ -438,17 +762,14 @@ impl MemoryVaultRepository {
         model: &str,
         dim: usize,
     ) -> Result<Vec<(VaultRow, f32)>, String> {
-        let mut bytes = Vec::with_capacity(query_embedding.len() * 4);
-        for f in query_embedding {
-            bytes.extend_from_slice(&f.to_le_bytes());
-        }
+        let bytes = vector_column_bytes(query_embedding);
 
         let mut rows = self
             .conn
             .query(
                 &format!(
                     "SELECT m.id, m.workspace_id, m.source, m.sensitivity, m.created_at, m.last_accessed, m.access_count,
-                            m.content_ciphertext, m.content_nonce, m.tags_ciphertext, m.tags_nonce, m.metadata_ciphertext, m.metadata_nonce, m.embedding, m.embedding_model, m.embedding_provider, m.embedding_dim,
+                            m.content_ciphertext, m.content_nonce, m.tags_ciphertext, m.tags_nonce, m.metadata_ciphertext, m.metadata_nonce, m.embedding, m.embedding_model, m.embedding_provider, m.embedding_dim, m.importance, m.pinned,
                             vector_distance_cos(v.embedding, ?1) as distance
                      FROM vector_top_k('{}', ?1, ?3) nn
                      JOIN memory_vault_embedding_vectors v ON v.rowid = nn.id
@@ -466,7 +787,7 @@ impl MemoryVaultRepository {
 
         let mut results = Vec::new();
         while let Some(row) = rows.next().await.map_err(|e| e.to_string())? {
-            let distance: f64 = row.get(17).unwrap_or(0.0);
+            let distance: f64 = row.get(19).unwrap_or(0.0);
             results.push((row_to_vault(&row)?, distance as f32));
         }
         Ok(results)
@@ -480,16 +801,13 @@ impl MemoryVaultRepository {
         model: &str,
         dim: usize,
     ) -> Result<Vec<(VaultRow, f32)>, String> {
-        let mut bytes = Vec::with_capacity(query_embedding.len() * 4);
-        for f in query_embedding {
-            bytes.extend_from_slice(&f.to_le_bytes());
-        }
+        let bytes = vector_column_bytes(query_embedding);
 
         let mut rows = self
             .conn
             .query(
                 "SELECT m.id, m.workspace_id, m.source, m.sensitivity, m.created_at, m.last_accessed, m.access_count,
-                        m.content_ciphertext, m.content_nonce, m.tags_ciphertext, m.tags_nonce, m.metadata_ciphertext, m.metadata_nonce, m.embedding, m.embedding_model, m.embedding_provider, m.embedding_dim,
+                        m.content_ciphertext, m.content_nonce, m.tags_ciphertext, m.tags_nonce, m.metadata_ciphertext, m.metadata_nonce, m.embedding, m.embedding_model, m.embedding_provider, m.embedding_dim, m.importance, m.pinned,
                         vector_distance_cos(v.embedding, ?1) as distance
                  FROM memory_vault_embedding_vectors v
                  JOIN memory_vault_entries m ON m.id = v.entry_id
@@ -505,7 +823,7 @@ impl MemoryVaultRepository {
 
         let mut results = Vec::new();
         while let Some(row) = rows.next().await.map_err(|e| e.to_string())? {
-            let distance: f64 = row.get(17).unwrap_or(0.0);
+            let distance: f64 = row.get(19).unwrap_or(0.0);
             results.push((row_to_vault(&row)?, distance as f32));
         }
         Ok(results)
@@ -552,6 +870,223 @@ impl MemoryVaultRepository {
         }
     }
 
+    pub async fn list_lifecycle_rows(
+        &self,
+        workspace_id: &str,
+    ) -> Result<Vec<LifecycleRow>, String> {
+        let mut rows = self
+            .conn
+            .query(
+                "SELECT id, source, last_accessed, access_count, pinned
+                 FROM memory_vault_entries
+                 WHERE workspace_id = ?1",
+                params![workspace_id.to_string()],
+            )
+            .await
+            .map_err(|e| format!("Failed to query lifecycle rows: {}", e))?;
+
+        let mut results = Vec::new();
+        while let Some(row) = rows.next().await.map_err(|e| e.to_string())? {
+            results.push(LifecycleRow {
+                id: row.get::<String>(0).map_err(|e| e.to_string())?,
+                source: row.get::<String>(1).map_err(|e| e.to_string())?,
+                last_accessed: row.get::<i64>(2).map_err(|e| e.to_string())?,
+                access_count: row.get::<i64>(3).map_err(|e| e.to_string())?,
+                pinned: row.get::<i64>(4).unwrap_or(0) != 0,
+            });
+        }
+        Ok(results)
+    }
+
+    pub async fn update_importance_batch(&self, scores: &[(String, f32)]) -> Result<(), String> {
+        if scores.is_empty() {
+            return Ok(());
+        }
+        self.conn
+            .execute("BEGIN IMMEDIATE TRANSACTION", ())
+            .await
+            .map_err(|e| format!("Failed to begin importance transaction: {}", e))?;
+
+        let result: Result<(), String> = async {
+            for (id, importance) in scores {
+                self.conn
+                    .execute(
+                        "UPDATE memory_vault_entries SET importance = ?1 WHERE id = ?2",
+                        params![f64::from(*importance), id.clone()],
+                    )
+                    .await
+                    .map_err(|e| format!("Failed to update importance for {}: {}", id, e))?;
+            }
+            Ok(())
+        }
+        .await;
+
+        match result {
+            Ok(()) => {
+                self.conn
+                    .execute("COMMIT", ())
+                    .await
+                    .map_err(|e| format!("Failed to commit importance update: {}", e))?;
+                Ok(())
+            }
+            Err(err) => {
+                let _ = self.conn.execute("ROLLBACK", ()).await;
+                Err(err)
+            }
+        }
+    }
+
+    /// Returns false when no entry has this id.
+    pub async fn set_pinned(&self, id: &str, pinned: bool) -> Result<bool, String> {
+        let changed = self
+            .conn
+            .execute(
+                "UPDATE memory_vault_entries
+                 SET pinned = ?1, importance = CASE WHEN ?1 = 1 THEN 1.0 ELSE importance END
+                 WHERE id = ?2",
+                params![i64::from(pinned), id.to_string()],
+            )
+            .await
+            .map_err(|e| format!("Failed to pin vault entry: {}", e))?;
+        Ok(changed > 0)
+    }
+
+    /// Delete unpinned entries not accessed since `cutoff` whose importance is
+    /// below `keep_importance_above`. Returns the number of entries removed.
+    pub async fn expire_inactive(
+        &self,
+        workspace_id: &str,
+        cutoff: i64,
+        keep_importance_above: f32,
+    ) -> Result<usize, String> {
+        self.conn
+            .execute("BEGIN IMMEDIATE TRANSACTION", ())
+            .await
+            .map_err(|e| format!("Failed to begin expiry transaction: {}", e))?;
+
+        let result = async {
+            let expired = self
+                .conn
+                .execute(
+                    "DELETE FROM memory_vault_entries
+                     WHERE workspace_id = ?1
+                       AND pinned = 0
+                       AND last_accessed < ?2
+                       AND importance < ?3",
+                    params![
+                        workspace_id.to_string(),
+                        cutoff,
+                        f64::from(keep_importance_above)
+                    ],
+                )
+                .await
+                .map_err(|e| format!("Failed to expire vault entries: {}", e))?;
+
+            self.conn
+                .execute(
+                    "DELETE FROM memory_vault_embedding_vectors
+                     WHERE workspace_id = ?1
+                       AND entry_id NOT IN (SELECT id FROM memory_vault_entries WHERE workspace_id = ?1)",
+                    params![workspace_id.to_string()],
+                )
+                .await
+                .map_err(|e| format!("Failed to expire vault vectors: {}", e))?;
+
+            self.delete_orphaned_terms(workspace_id).await?;
+
+            Ok::<usize, String>(expired as usize)
+        }
+        .await;
+
+        match result {
+            Ok(expired) => {
+                self.conn
+                    .execute("COMMIT", ())
+                    .await
+                    .map_err(|e| format!("Failed to commit expiry transaction: {}", e))?;
+                Ok(expired)
+            }
+            Err(err) => {
+                let _ = self.conn.execute("ROLLBACK", ()).await;
+                Err(err)
+            }
+        }
+    }
+
+    /// Fold `drop_id`'s usage and pin into `keep_id`, then delete `drop_id`.
+    pub async fn merge_duplicate(&self, keep_id: &str, drop_id: &str) -> Result<(), String> {
+        self.conn
+            .execute("BEGIN IMMEDIATE TRANSACTION", ())
+            .await
+            .map_err(|e| format!("Failed to begin merge transaction: {}", e))?;
+
+        let result = async {
+            self.conn
+                .execute(
+                    "UPDATE memory_vault_entries
+                     SET access_count = access_count
+                            + COALESCE((SELECT access_count FROM memory_vault_entries WHERE id = ?2), 0),
+                         last_accessed = MAX(last_accessed,
+                            COALESCE((SELECT last_accessed FROM memory_vault_entries WHERE id = ?2), 0)),
+                         pinned = MAX(pinned,
+                            COALESCE((SELECT pinned FROM memory_vault_entries WHERE id = ?2), 0))
+                     WHERE id = ?1",
+                    params![keep_id.to_string(), drop_id.to_string()],
+                )
+                .await
+                .map_err(|e| format!("Failed to merge vault entry {}: {}", drop_id, e))?;
+
+            self.conn
+                .execute(
+                    "DELETE FROM memory_vault_entries WHERE id = ?1",
+                    params![drop_id.to_string()],
+                )
+                .await
+                .map_err(|e| format!("Failed to delete merged vault entry: {}", e))?;
+
+            self.conn
+                .execute(
+                    "DELETE FROM memory_vault_embedding_vectors WHERE entry_id = ?1",
+                    params![drop_id.to_string()],
+                )
+                .await
+                .map_err(|e| format!("Failed to delete merged vault vectors: {}", e))?;
+
+            self.conn
+                .execute(
+                    "DELETE FROM memory_vault_term_postings WHERE entry_id = ?1",
+                    params![drop_id.to_string()],
+                )
+                .await
+                .map_err(|e| format!("Failed to delete merged term postings: {}", e))?;
+
+            self.conn
+                .execute(
+                    "DELETE FROM memory_vault_term_docs WHERE entry_id = ?1",
+                    params![drop_id.to_string()],
+                )
+                .await
+                .map_err(|e| format!("Failed to delete merged term docs: {}", e))?;
+
+            Ok::<(), String>(())
+        }
+        .await;
+
+        match result {
+            Ok(()) => {
+                self.conn
+                    .execute("COMMIT", ())
+                    .await
+                    .map_err(|e| format!("Failed to commit merge transaction: {}", e))?;
+                Ok(())
+            }
+            Err(err) => {
+                let _ = self.conn.execute("ROLLBACK", ()).await;
+                Err(err)
+            }
+        }
+    }
+
     pub async fn counts(&self, workspace_id: Option<&str>) -> Result<(usize, usize), String> {
         let mut total_rows = self
             .conn
@@ -610,6 +1145,16 @@ impl MemoryVaultRepository {
     }
 }
 
+/// Little-endian f32 bytes, zero-padded to the vector column width.
+pub fn vector_column_bytes(vector: &[f32]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(VECTOR_COLUMN_DIM.max(vector.len()) * 4);
+    for f in vector {
+        bytes.extend_from_slice(&f.to_le_bytes());
+    }
+    bytes.resize(VECTOR_COLUMN_DIM.max(vector.len()) * 4, 0);
+    bytes
+}
+
 fn row_to_vault(row: &libsql::Row) -> Result<VaultRow, String> {
     Ok(VaultRow {
         id: row.get::<String>(0).map_err(|e| e.to_string())?,
@@ -632,6 +1177,8 @@ fn row_to_vault(row: &libsql::Row) -> Result<VaultRow, String> {
             .get::<Option<i64>>(16)
             .unwrap_or(None)
             .map(|v| v as usize),
+        importance: row.get::<f64>(17).unwrap_or(0.5) as f32,
+        pinned: row.get::<i64>(18).unwrap_or(0) != 0,
     })
 }
 
@@ -670,10 +1217,12 @@ mod tests {
             embedding_model: None,
             embedding_provider: None,
             embedding_dim: None,
+            importance: 0.5,
+            pinned: false,
         };
 
         // Test insertion
-        repo.upsert_encrypted_atomic(&row, 1, Vec::new())
+        repo.upsert_encrypted_atomic(&row, 1, Vec::new(), None)
             .await
             .expect("Failed to upsert row");
 
@@ -1,13 +1,22 @@
-use super::crypto::{decrypt_bytes, encrypt_bytes};
+use super::crypto::{decrypt_bytes, derive_term_key, encrypt_bytes, term_hash};
 use super::key_provider::{MacOSKeychainVaultKeyProvider, VaultKeyProvider};
-use super::repository::{MemoryVaultRepository, VaultRow};
-use super::types::{DecryptedMemoryEntry, MemorySensitivity, MemoryVaultStats, StoreMemoryInput};
-use crate::services::embedder::EmbeddingTaskType;
+use super::lexical::{bm25_term_score, query_terms, term_frequencies};
+use super::lifecycle::{find_near_duplicates, score_importance, DedupCandidate};
+use super::profiles::{EmbeddingProfile, VECTOR_COLUMN_DIM};
+use super::repository::{vector_column_bytes, MemoryVaultRepository, TermIndex, VaultRow};
+use super::types::{
+    DecryptedMemoryEntry, MaintenanceReport, MemorySensitivity, MemoryVaultStats, RetentionPolicy,
+    StoreMemoryInput,
+};
+use crate::services::embedder::{Embedder, EmbeddingTaskType};
 use std::collections::HashMap;
 use std::path::PathBuf;
 use std::sync::Arc;
 
 const MIGRATION_PLAINTEXT_DB: &str = "migrate_plaintext_memory_entries_v1";
+const MIGRATION_TERM_INDEX: &str = "migrate_memory_term_index_v1";
+/// Most recent embedded entries compared pairwise during consolidation.
+const MAX_CONSOLIDATION_ROWS: usize = 500;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum VectorSearchMode {
@@ -19,6 +28,7 @@ pub enum VectorSearchMode {
 pub struct MemoryVaultService {
     repository: Arc<MemoryVaultRepository>,
     master_key: Arc<Vec<u8>>,
+    term_key: Arc<[u8; 32]>,
 }
 
 impl MemoryVaultService {
@@ -36,9 +46,11 @@ impl MemoryVaultService {
     ) -> Result<Self, String> {
         let repository = Arc::new(MemoryVaultRepository::new(app_data_dir).await?);
         let master_key = Arc::new(provider.get_or_create_master_key()?);
+        let term_key = Arc::new(derive_term_key(master_key.as_slice()));
         let service = Self {
             repository,
             master_key,
+            term_key,
         };
         if !cfg!(test) {
             service.run_plaintext_migration().await?;
@@ -71,39 +83,59 @@ impl MemoryVaultService {
             &metadata_json,
         )?;
 
-        let embedding_dim = input.embedding_dim.unwrap_or(super::types::EMBEDDING_DIM);
-        let embedding_model = input
-            .embedding_model
-            .clone()
-            .unwrap_or_else(|| super::types::EMBEDDING_MODEL.to_string());
-        let embedding_provider = input
-            .embedding_provider
-            .clone()
-            .unwrap_or_else(|| super::types::EMBEDDING_PROVIDER.to_string());
-
-        let valid_embedding = if let Some(emb) = input.embedding {
-            if emb.len() != embedding_dim {
-                println!(
-                    "Warning: Invalid embedding dimension {} (expected {}) for vault entry {}. Storing without embedding.",
+        let embedding_dim = input
+            .embedding_dim
+            .or_else(|| input.embedding.as_ref().map(Vec::len));
+        let valid_embedding = match (
+            input.embedding,
+            &input.embedding_model,
+            &input.embedding_provider,
+        ) {
+            (Some(emb), Some(_), Some(_))
+                if Some(emb.len()) == embedding_dim && emb.len() <= VECTOR_COLUMN_DIM =>
+            {
+                Some(emb)
+            }
+            (Some(emb), _, _) => {
+                tracing::warn!(
+                    "Invalid {}-dimensional embedding (expected {:?}, model {:?}) for vault entry {}. Storing without embedding.",
                     emb.len(),
                     embedding_dim,
+                    input.embedding_model,
                     input.id
                 );
                 None
-            } else {
-                Some(emb)
             }
-        } else {
-            None
+            (None, _, _) => None,
         };
 
-        let embedding_bytes = valid_embedding.as_ref().map(|v| {
-            let mut bytes = Vec::with_capacity(v.len() * 4);
-            for f in v {
-                bytes.extend_from_slice(&f.to_le_bytes());
+        let mut vector_rows = Vec::new();
+        if let (Some(emb), Some(model), Some(provider), Some(dim)) = (
+            valid_embedding.as_ref(),
+            input.embedding_model.as_ref(),
+            input.embedding_provider.as_ref(),
+            embedding_dim,
+        ) {
+            vector_rows.push((
+                model.clone(),
+                provider.clone(),
+                dim,
+                vector_column_bytes(emb),
+            ));
+        }
+        for extra in input.additional_embeddings {
+            if extra.embedding.len() != extra.embedding_dim
+                || extra.embedding_dim > VECTOR_COLUMN_DIM
+            {
+                continue;
             }
-            bytes
-        });
+            vector_rows.push((
+                extra.embedding_model,
+                extra.embedding_provider,
+                extra.embedding_dim,
+                vector_column_bytes(&extra.embedding),
+            ));
+        }
 
         let row = VaultRow {
             id: input.id,
@@ -119,69 +151,58 @@ impl MemoryVaultService {
             tags_nonce: tags.nonce,
             metadata_ciphertext: Some(metadata.ciphertext),
             metadata_nonce: Some(metadata.nonce),
-            embedding: embedding_bytes,
-            embedding_model: Some(embedding_model),
-            embedding_provider: Some(embedding_provider),
-            embedding_dim: Some(embedding_dim),
+            embedding: valid_embedding.as_deref().map(vector_column_bytes),
+            embedding_model: input.embedding_model,
+            embedding_provider: input.embedding_provider,
+            embedding_dim,
+            importance: 0.5,
+            pinned: false,
         };
 
-        let mut vector_rows = Vec::new();
-
-        if let Some(primary_emb) = valid_embedding {
-            let mut bytes = Vec::with_capacity(primary_emb.len() * 4);
-            for f in primary_emb {
-                bytes.extend_from_slice(&f.to_le_bytes());
-            }
-            vector_rows.push((
-                row.embedding_model
-                    .clone()
-                    .unwrap_or_else(|| super::types::EMBEDDING_MODEL.to_string()),
-                row.embedding_provider
-                    .clone()
-                    .unwrap_or_else(|| super::types::EMBEDDING_PROVIDER.to_string()),
-                row.embedding_dim.unwrap_or(super::types::EMBEDDING_DIM),
-                bytes,
-            ));
-        }
-
-        for extra in input.additional_embeddings {
-            if extra.embedding.len() != extra.embedding_dim {
-                continue;
-            }
-            let mut bytes = Vec::with_capacity(extra.embedding.len() * 4);
-            for f in extra.embedding {
-                bytes.extend_from_slice(&f.to_le_bytes());
-            }
-            vector_rows.push((
-                extra.embedding_model,
-                extra.embedding_provider,
-                extra.embedding_dim,
-                bytes,
-            ));
-        }
-
+        let term_index = self.term_index(&row.workspace_id, &input.content);
         self.repository
-            .upsert_encrypted_atomic(&row, 1, vector_rows)
+            .upsert_encrypted_atomic(&row, 1, vector_rows, Some(&term_index))
             .await?;
 
         Ok(())
     }
 
-    pub fn spawn_reembed_backfill(&self) {
+    /// Re-embed entries that lack a vector for `embedder`'s profile. Runs
+    /// once per profile.
+    pub fn spawn_reembed_backfill(&self, embedder: Arc<dyn Embedder>) {
         let service = self.clone();
         tokio::spawn(async move {
-            if let Err(err) = service.run_reembed_backfill().await {
+            if let Err(err) = service.run_reembed_backfill(embedder).await {
                 tracing::warn!("Memory re-embedding backfill failed: {}", err);
             }
         });
     }
 
+    /// Index every entry written before the lexical index existed.
+    pub fn spawn_term_index_backfill(&self) {
+        let service = self.clone();
+        tokio::spawn(async move {
+            if let Err(err) = service.run_term_index_backfill().await {
+                tracing::warn!("Memory term index backfill failed: {}", err);
+            }
+        });
+    }
+
+    /// Keyword search: BM25 over the lexical index, falling back to a
+    /// substring scan of recent entries when nothing matches a whole term.
     pub async fn search_workspace(
         &self,
         workspace_id: &str,
         query: &str,
         limit: usize,
     ) -> Result<Vec<DecryptedMemoryEntry>, String> {
+        let ranked = self
+            .search_workspace_lexical(workspace_id, query, limit)
+            .await?;
+        if !ranked.is_empty() {
+            return Ok(ranked.into_iter().map(|(entry, _score)| entry).collect());
+        }
+
         let rows = self
             .repository
             .list_workspace_rows(workspace_id, limit.saturating_mul(10).max(50))
@@ -189,23 +210,76 @@ impl MemoryVaultService {
         let query_lc = query.to_lowercase();
         let mut results = Vec::new();
 
-        let now = chrono::Utc::now().timestamp();
         for row in rows {
             let entry = self.decrypt_row(&row)?;
             if query_lc.is_empty() || entry.content.to_lowercase().contains(&query_lc) {
-                results.push(DecryptedMemoryEntry {
-                    access_count: entry.access_count + 1,
-                    last_accessed: now,
-                    ..entry
-                });
+                results.push(entry);
             }
             if results.len() >= limit {
                 break;
             }
         }
 
-        let ids: Vec<String> = results.iter().map(|e| e.id.clone()).collect();
-        let _ = self.repository.touch_access_batch(&ids, now).await;
+        Ok(results)
+    }
+
+    /// Record that these entries were returned to a caller. Searches only
+    /// gather candidates, so the caller touches the entries it keeps.
+    pub async fn touch_access(&self, ids: &[String]) {
+        let now = chrono::Utc::now().timestamp();
+        if let Err(e) = self.repository.touch_access_batch(ids, now).await {
+            tracing::warn!("Failed to record memory access: {}", e);
+        }
+    }
+
+    /// BM25-ranked entries containing at least one query term, best first.
+    pub async fn search_workspace_lexical(
+        &self,
+        workspace_id: &str,
+        query: &str,
+        limit: usize,
+    ) -> Result<Vec<(DecryptedMemoryEntry, f64)>, String> {
+        let terms = query_terms(query);
+        if terms.is_empty() || limit == 0 {
+            return Ok(Vec::new());
+        }
+        let (doc_count, average_length) = self.repository.term_corpus_stats(workspace_id).await?;
+        if doc_count == 0 {
+            return Ok(Vec::new());
+        }
+
+        let mut scores: HashMap<String, f64> = HashMap::new();
+        for term in &terms {
+            let postings = self
+                .repository
+                .term_postings(workspace_id, &term_hash(&self.term_key, workspace_id, term))
+                .await?;
+            let document_frequency = postings.len();
+            for posting in postings {
+                *scores.entry(posting.entry_id).or_insert(0.0) += bm25_term_score(
+                    posting.term_frequency,
+                    document_frequency,
+                    doc_count,
+                    posting.doc_length,
+                    average_length,
+                );
+            }
+        }
+
+        let mut ranked = scores.into_iter().collect::<Vec<_>>();
+        ranked.sort_by(|a, b| {
+            b.1.partial_cmp(&a.1)
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then_with(|| a.0.cmp(&b.0))
+        });
+        ranked.truncate(limit);
+
+        let mut results = Vec::new();
+        for (id, score) in ranked {
+            if let Some(row) = self.repository.get_by_id(&id).await? {
+                results.push((self.decrypt_row(&row)?, score));
+            }
+        }
 
         Ok(results)
     }
@@ -216,23 +290,31 @@ impl MemoryVaultService {
         workspace_id: &str,
         query_embedding: &[f32],
         limit: usize,
+        profile: &EmbeddingProfile,
     ) -> Result<Vec<(DecryptedMemoryEntry, f32)>, String> {
         let (rows, _mode) = self
-            .search_workspace_vector_with_mode(workspace_id, query_embedding, limit)
+            .search_workspace_vector_with_mode(workspace_id, query_embedding, limit, profile)
             .await?;
         Ok(rows)
     }
 
+    /// Nearest entries embedded with `profile`. `query_embedding` must come
+    /// from the same profile.
     pub async fn search_workspace_vector_with_mode(
         &self,
         workspace_id: &str,
         query_embedding: &[f32],
         limit: usize,
+        profile: &EmbeddingProfile,
     ) -> Result<(Vec<(DecryptedMemoryEntry, f32)>, VectorSearchMode), String> {
-        let primary_model = crate::services::memory_vault::profiles::ACTIVE_EMBEDDING_PROFILE.model;
-        let fallback_model =
-            crate::services::memory_vault::profiles::FALLBACK_EMBEDDING_PROFILE.model;
-        let embedding_dim = super::types::EMBEDDING_DIM;
+        if query_embedding.len() != profile.dim {
+            return Err(format!(
+                "Query embedding has {} dimensions, {} expects {}",
+                query_embedding.len(),
+                profile.label(),
+                profile.dim
+            ));
+        }
 
         let (rows, mode) = match self
             .repository
@@ -240,69 +322,30 @@ impl MemoryVaultService {
                 workspace_id,
                 query_embedding,
                 limit,
-                primary_model,
-                embedding_dim,
+                &profile.model,
+                profile.dim,
             )
             .await
         {
             Ok(rows) if !rows.is_empty() => (rows, VectorSearchMode::Ann),
-            _ => match self
-                .repository
-                .search_workspace_vector_ann_for_model(
-                    workspace_id,
-                    query_embedding,
-                    limit,
-                    fallback_model,
-                    embedding_dim,
-                )
-                .await
-            {
-                Ok(rows) if !rows.is_empty() => (rows, VectorSearchMode::Ann),
-                _ => match self
-                    .repository
+            _ => (
+                self.repository
                     .search_workspace_vector_exact_for_model(
                         workspace_id,
                         query_embedding,
                         limit,
-                        primary_model,
-                        embedding_dim,
+                        &profile.model,
+                        profile.dim,
                     )
-                    .await
-                {
-                    Ok(rows) if !rows.is_empty() => (rows, VectorSearchMode::Exact),
-                    _ => (
-                        self.repository
-                            .search_workspace_vector_exact_for_model(
-                                workspace_id,
-                                query_embedding,
-                                limit,
-                                fallback_model,
-                                embedding_dim,
-                            )
-                            .await?,
-                        VectorSearchMode::Exact,
-                    ),
-                },
-            },
+                    .await?,
+                VectorSearchMode::Exact,
+            ),
         };
-        let now = chrono::Utc::now().timestamp();
         let mut results = Vec::new();
-
         for (row, distance) in rows {
-            let entry = self.decrypt_row(&row)?;
-            results.push((
-                DecryptedMemoryEntry {
-                    access_count: entry.access_count + 1,
-                    last_accessed: now,
-                    ..entry
-                },
-                distance,
-            ));
+            results.push((self.decrypt_row(&row)?, distance));
         }
 
-        let ids: Vec<String> = results.iter().map(|(e, _)| e.id.clone()).collect();
-        let _ = self.repository.touch_access_batch(&ids, now).await;
-
         Ok((results, mode))
     }
 
@@ -332,10 +375,91 @@ impl MemoryVaultService {
         self.repository.delete_by_id(id).await
     }
 
+    pub async fn delete_source(&self, workspace_id: &str, source: &str) -> Result<usize, String> {
+        self.repository.delete_source(workspace_id, source).await
+    }
+
     pub async fn delete_workspace(&self, workspace_id: &str) -> Result<(), String> {
         self.repository.delete_workspace(workspace_id).await
     }
 
+    /// Pinned entries score full importance and never expire. Returns false
+    /// when the entry does not exist.
+    pub async fn set_pinned(&self, id: &str, pinned: bool) -> Result<bool, String> {
+        self.repository.set_pinned(id, pinned).await
+    }
+
+    /// Rescore importance, expire entries past retention, then merge
+    /// near-duplicate embeddings in one workspace.
+    pub async fn run_maintenance(
+        &self,
+        workspace_id: &str,
+        policy: &RetentionPolicy,
+    ) -> Result<MaintenanceReport, String> {
+        let now = chrono::Utc::now().timestamp();
+        let scores = self
+            .repository
+            .list_lifecycle_rows(workspace_id)
+            .await?
+            .into_iter()
+            .map(|row| {
+                let importance = score_importance(
+                    &row.source,
+                    row.access_count,
+                    row.last_accessed,
+                    row.pinned,
+                    now,
+                    policy,
+                );
+                (row.id, importance)
+            })
+            .collect::<Vec<_>>();
+        self.repository.update_importance_batch(&scores).await?;
+
+        let mut report = MaintenanceReport {
+            rescored: scores.len(),
+            ..Default::default()
+        };
+
+        if policy.retention_days > 0 {
+            let cutoff = now - i64::from(policy.retention_days) * 86_400;
+            report.expired = self
+                .repository
+                .expire_inactive(workspace_id, cutoff, policy.keep_importance_above)
+                .await?;
+        }
+
+        if policy.dedup_similarity < 1.0 {
+            let candidates = self
+                .repository
+                .list_workspace_rows(workspace_id, MAX_CONSOLIDATION_ROWS)
+                .await?
+                .into_iter()
+                .filter_map(|row| {
+                    let embedding = row_embedding(&row)?;
+                    Some(DedupCandidate {
+                        id: row.id,
+                        importance: row.importance,
+                        sensitivity: row.sensitivity,
+                        model: row.embedding_model?,
+                        embedding,
+                    })
+                })
+                .collect::<Vec<_>>();
+            let threshold = policy.dedup_similarity;
+            let pairs =
+                tokio::task::spawn_blocking(move || find_near_duplicates(&candidates, threshold))
+                    .await
+                    .map_err(|e| format!("Near-duplicate scan failed: {}", e))?;
+            for (keep_id, drop_id) in &pairs {
+                self.repository.merge_duplicate(keep_id, drop_id).await?;
+            }
+            report.merged = pairs.len();
+        }
+
+        Ok(report)
+    }
+
     pub async fn stats(&self, workspace_id: Option<&str>) -> Result<MemoryVaultStats, String> {
         let (total_entries, workspace_entries) = self.repository.counts(workspace_id).await?;
         Ok(MemoryVaultStats {
@@ -344,6 +468,17 @@ impl MemoryVaultService {
         })
     }
 
+    fn term_index(&self, workspace_id: &str, content: &str) -> TermIndex {
+        let (frequencies, doc_length) = term_frequencies(content);
+        TermIndex {
+            doc_length,
+            postings: frequencies
+                .into_iter()
+                .map(|(term, tf)| (term_hash(&self.term_key, workspace_id, &term), tf))
+                .collect(),
+        }
+    }
+
     fn decrypt_row(&self, row: &VaultRow) -> Result<DecryptedMemoryEntry, String> {
         let content_bytes = decrypt_bytes(
             self.master_key.as_slice(),
@@ -378,14 +513,7 @@ impl MemoryVaultService {
         let metadata: HashMap<String, String> = serde_json::from_slice(&metadata_bytes)
             .map_err(|e| format!("Invalid decrypted metadata json: {}", e))?;
 
-        let embedding = row.embedding.as_ref().map(|bytes| {
-            let mut floats = Vec::with_capacity(bytes.len() / 4);
-            for chunk in bytes.chunks_exact(4) {
-                let f = f32::from_le_bytes(chunk.try_into().unwrap());
-                floats.push(f);
-            }
-            floats
-        });
+        let embedding = row_embedding(row);
 
         Ok(DecryptedMemoryEntry {
             id: row.id.clone(),
@@ -402,6 +530,8 @@ impl MemoryVaultService {
             embedding_model: row.embedding_model.clone(),
             embedding_provider: row.embedding_provider.clone(),
             embedding_dim: row.embedding_dim,
+            importance: row.importance,
+            pinned: row.pinned,
         })
     }
 
@@ -474,10 +604,47 @@ impl MemoryVaultService {
             .await
     }
 
-    async fn run_reembed_backfill(&self) -> Result<(), String> {
+    async fn run_term_index_backfill(&self) -> Result<(), String> {
+        const BACKFILL_BATCH_SIZE: usize = 200;
+        if self
+            .repository
+            .migration_completed(MIGRATION_TERM_INDEX)
+            .await?
+        {
+            return Ok(());
+        }
+
+        loop {
+            let rows = self
+                .repository
+                .rows_missing_term_index(BACKFILL_BATCH_SIZE)
+                .await?;
+            if rows.is_empty() {
+                break;
+            }
+            for row in rows {
+                // An entry that can't be decrypted gets an empty index so it
+                // isn't picked up again on the next batch.
+                let index = match self.decrypt_row(&row) {
+                    Ok(entry) => self.term_index(&row.workspace_id, &entry.content),
+                    Err(_) => TermIndex::default(),
+                };
+                self.repository
+                    .replace_term_index(&row.id, &row.workspace_id, &index)
+                    .await?;
+            }
+        }
+
+        self.repository
+            .mark_migration_completed(MIGRATION_TERM_INDEX)
+            .await
+    }
+
+    async fn run_reembed_backfill(&self, embedder: Arc<dyn Embedder>) -> Result<(), String> {
         const BACKFILL_BATCH_SIZE: usize = 16;
-        let migration_key = "migrate_memory_reembed_active_profile_v2";
-        if self.repository.migration_completed(migration_key).await? {
+        let profile = embedder.profile().clone();
+        let migration_key = profile.backfill_key();
+        if self.repository.migration_completed(&migration_key).await? {
             return Ok(());
         }
 
@@ -493,15 +660,15 @@ impl MemoryVaultService {
                     "SELECT e.id
                      FROM memory_vault_entries e
                      LEFT JOIN memory_vault_embedding_vectors v
-                       ON v.entry_id = e.id AND v.embedding_model = ?2
+                       ON v.entry_id = e.id AND v.embedding_model = ?2 AND v.embedding_dim = ?1
                      WHERE e.embedding IS NULL
                         OR e.embedding_dim != ?1
                         OR e.embedding_model != ?2
                         OR v.entry_id IS NULL
                      LIMIT ?3 OFFSET ?4",
                     (
-                        super::types::EMBEDDING_DIM as i64,
-                        super::types::EMBEDDING_MODEL.to_string(),
+                        profile.dim as i64,
+                        profile.model.clone(),
                         PAGE_SIZE as i64,
                         offset as i64,
                     ),
@@ -524,60 +691,23 @@ impl MemoryVaultService {
         if ids_to_reembed.is_empty() {
             return self
                 .repository
-                .mark_migration_completed(migration_key)
+                .mark_migration_completed(&migration_key)
                 .await;
         }
 
         println!(
             "Found {} rows needing '{}' re-embedding (dim {}).",
             ids_to_reembed.len(),
-            super::types::EMBEDDING_MODEL,
-            super::types::EMBEDDING_DIM
+            profile.label(),
+            profile.dim
         );
 
-        // In a real production system, this should be done in a background task queue.
-        // For Tauri startup, we will process them using the global EmbedderService.
-        // Since MemoryVaultService doesn't have an embedder attached natively, we will
-        // just set up a local embedder for the backfill using standard environment/keychain.
-
-        let settings = crate::services::settings::SettingsManager::new();
-        let provider_raw = settings.get_embedder_provider().to_string();
-        let provider = match provider_raw.trim().to_lowercase().as_str() {
-            "g" | "google" | "gemini" => super::types::EMBEDDING_PROVIDER.to_string(),
-            _ => {
-                println!(
-                    "Memory vault backfill forcing Gemini embedding provider; configured '{}' is unsupported for Step 3",
-                    provider_raw
-                );
-                super::types::EMBEDDING_PROVIDER.to_string()
-            }
-        };
-        let model = super::types::EMBEDDING_MODEL.to_string();
-
-        let keychain = crate::ai::keychain::KeychainManager::new();
-        let api_key = keychain
-            .get_key(&provider)
-            .or_else(|_| keychain.get_key(&provider_raw))
-            .unwrap_or_default()
-            .unwrap_or_default();
-
-        let embedder = crate::services::embedder::EmbedderService::new(
-            provider,
-            api_key.clone(),
-            Some(model),
-        );
-
-        if api_key.is_empty() {
-            println!("Skipping re-embedding backfill: No API key available.");
-            return Ok(());
-        }
-
         for id_batch in ids_to_reembed.chunks(BACKFILL_BATCH_SIZE) {
             let mut entries = Vec::new();
             for id in id_batch {
                 if let Ok(Some(entry)) = self.get_by_id(id).await {
-                    if entry.embedding_dim == Some(super::types::EMBEDDING_DIM)
-                        && entry.embedding_model.as_deref() == Some(super::types::EMBEDDING_MODEL)
+                    if entry.embedding_dim == Some(profile.dim)
+                        && entry.embedding_model.as_deref() == Some(profile.model.as_str())
                     {
                         continue;
                     }
@@ -595,21 +725,10 @@ impl MemoryVaultService {
                 .collect::<Vec<_>>();
 
             let embeddings = match embedder
-                .embed_texts_for_model_with_task(
-                    &texts,
-                    super::types::EMBEDDING_MODEL,
-                    EmbeddingTaskType::RetrievalDocument,
-                )
+                .embed_batch(&texts, EmbeddingTaskType::RetrievalDocument)
                 .await
             {
-                Ok(v) if v.len() == entries.len() => Some(v),
-                Ok(_) => {
-                    println!(
-                        "Batch re-embed size mismatch for {} entries; falling back to per-entry path",
-                        entries.len()
-                    );
-                    None
-                }
+                Ok(v) => Some(v),
                 Err(err) => {
                     println!(
                         "Batch re-embed failed for {} entries: {}. Falling back to per-entry path",
@@ -625,11 +744,7 @@ impl MemoryVaultService {
                     batched.get(idx).cloned()
                 } else {
                     embedder
-                        .embed_text_for_model_with_task_strict(
-                            &entry.content,
-                            super::types::EMBEDDING_MODEL,
-                            EmbeddingTaskType::RetrievalDocument,
-                        )
+                        .embed(&entry.content, EmbeddingTaskType::RetrievalDocument)
                         .await
                         .ok()
                 };
@@ -646,9 +761,9 @@ impl MemoryVaultService {
                             metadata: entry.metadata,
                             created_at: entry.created_at,
                             embedding: Some(vec),
-                            embedding_model: Some(super::types::EMBEDDING_MODEL.to_string()),
-                            embedding_provider: Some(super::types::EMBEDDING_PROVIDER.to_string()),
-                            embedding_dim: Some(super::types::EMBEDDING_DIM),
+                            embedding_model: Some(profile.model.clone()),
+                            embedding_provider: Some(profile.provider.clone()),
+                            embedding_dim: Some(profile.dim),
                             additional_embeddings: Vec::new(),
                         })
                         .await;
@@ -657,7 +772,259 @@ impl MemoryVaultService {
         }
 
         self.repository
-            .mark_migration_completed(migration_key)
+            .mark_migration_completed(&migration_key)
             .await
     }
 }
+
+fn embedding_from_bytes(bytes: &[u8]) -> Vec<f32> {
+    bytes
+        .chunks_exact(4)
+        .map(|chunk| f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+        .collect()
+}
+
+/// Stored vectors are zero-padded to the column width; trim back to the
+/// profile's own size.
+fn row_embedding(row: &VaultRow) -> Option<Vec<f32>> {
+    let mut embedding = embedding_from_bytes(row.embedding.as_deref()?);
+    if let Some(dim) = row.embedding_dim {
+        embedding.truncate(dim);
+    }
+    Some(embedding)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serial_test::serial;
+
+    struct StaticKeyProvider;
+
+    impl VaultKeyProvider for StaticKeyProvider {
+        fn get_or_create_master_key(&self) -> Result<Vec<u8>, String> {
+            Ok(vec![7u8; 32])
+        }
+    }
+
+    fn input(id: &str, source: &str, created_at: i64, embedding: Vec<f32>) -> StoreMemoryInput {
+        StoreMemoryInput {
+            id: id.to_string(),
+            workspace_id: "ws".to_string(),
+            content: format!("memory {}", id),
+            tags: Vec::new(),
+            source: source.to_string(),
+            sensitivity: MemorySensitivity::Internal,
+            metadata: HashMap::new(),
+            created_at,
+            embedding: Some(embedding),
+            embedding_model: Some(EmbeddingProfile::local().model),
+            embedding_provider: Some(EmbeddingProfile::local().provider),
+            embedding_dim: None,
+            additional_embeddings: Vec::new(),
+        }
+    }
+
+    fn axis(index: usize, wobble: f32) -> Vec<f32> {
+        let mut v = vec![0.0f32; EmbeddingProfile::local().dim];
+        v[index] = 1.0;
+        v[index + 1] = wobble;
+        v
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_maintenance_expires_rescores_and_merges() {
+        let temp_dir = std::env::temp_dir().join(uuid::Uuid::new_v4().to_string());
+        let vault = MemoryVaultService::new_with_provider(
+            temp_dir.clone(),
+            Arc::new(StaticKeyProvider) as Arc<dyn VaultKeyProvider>,
+        )
+        .await
+        .unwrap();
+
+        let now = chrono::Utc::now().timestamp();
+        let stale = now - 60 * 86_400;
+        for entry in [
+            input("stale", "tool:web_search", stale, axis(0, 0.0)),
+            input("pinned", "user", stale, axis(2, 0.0)),
+            input("dup-a", "user", now, axis(4, 0.0)),
+            input("dup-b", "agent_conversation", now, axis(4, 0.01)),
+        ] {
+            vault.put(entry).await.unwrap();
+        }
+        assert!(vault.set_pinned("pinned", true).await.unwrap());
+        assert!(!vault.set_pinned("missing", true).await.unwrap());
+
+        let report = vault
+            .run_maintenance("ws", &RetentionPolicy::default())
+            .await
+            .unwrap();
+        assert_eq!(report.rescored, 4);
+        assert_eq!(report.expired, 1);
+        assert_eq!(report.merged, 1);
+
+        assert!(vault.get_by_id("stale").await.unwrap().is_none());
+        let pinned = vault.get_by_id("pinned").await.unwrap().unwrap();
+        assert!(pinned.pinned);
+        assert_eq!(pinned.importance, 1.0);
+        // The user note outranks the conversation turn, so it survives the merge.
+        assert!(vault.get_by_id("dup-a").await.unwrap().is_some());
+        assert!(vault.get_by_id("dup-b").await.unwrap().is_none());
+
+        let _ = std::fs::remove_dir_all(temp_dir);
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_vector_search_is_scoped_to_profile() {
+        let temp_dir = std::env::temp_dir().join(uuid::Uuid::new_v4().to_string());
+        let vault = MemoryVaultService::new_with_provider(
+            temp_dir.clone(),
+            Arc::new(StaticKeyProvider) as Arc<dyn VaultKeyProvider>,
+        )
+        .await
+        .unwrap();
+
+        let local = EmbeddingProfile::local();
+        let now = chrono::Utc::now().timestamp();
+        vault
+            .put(input("near", "user", now, axis(0, 0.1)))
+            .await
+            .unwrap();
+        vault
+            .put(input("far", "user", now, axis(8, 0.0)))
+            .await
+            .unwrap();
+        let mut other = input("other-model", "user", now, vec![1.0; 16]);
+        other.embedding_model = Some("text-embedding-test".to_string());
+        other.embedding_provider = Some("openai".to_string());
+        vault.put(other).await.unwrap();
+
+        let (results, _mode) = vault
+            .search_workspace_vector_with_mode("ws", &axis(0, 0.0), 5, &local)
+            .await
+            .unwrap();
+        let ids = results
+            .iter()
+            .map(|(e, _)| e.id.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(ids, vec!["near", "far"]);
+        // Padding to the column width is stripped on read.
+        assert_eq!(results[0].0.embedding.as_ref().unwrap().len(), local.dim);
+        assert_eq!(results[0].0.embedding_dim, Some(local.dim));
+
+        let stored = vault.get_by_id("other-model").await.unwrap().unwrap();
+        assert_eq!(stored.embedding.unwrap().len(), 16);
+
+        let wrong_size = vault
+            .search_workspace_vector_with_mode("ws", &[1.0; 16], 5, &local)
+            .await;
+        assert!(wrong_size.is_err());
+
+        let _ = std::fs::remove_dir_all(temp_dir);
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_lexical_search_ranks_by_bm25_and_tracks_deletes() {
+        let temp_dir = std::env::temp_dir().join(uuid::Uuid::new_v4().to_string());
+        let vault = MemoryVaultService::new_with_provider(
+            temp_dir.clone(),
+            Arc::new(StaticKeyProvider) as Arc<dyn VaultKeyProvider>,
+        )
+        .await
+        .unwrap();
+
+        let now = chrono::Utc::now().timestamp();
+        for (id, content) in [
+            (
+                "focused",
+                "Acme revenue forecast: revenue up, revenue targets met",
+            ),
+            (
+                "mention",
+                "Weekly notes. Acme called about the offsite and catering",
+            ),
+            (
+                "unrelated",
+                "Bake the bread at 220 degrees for forty minutes",
+            ),
+        ] {
+            let mut entry = input(id, "user", now, axis(0, 0.0));
+            entry.content = content.to_string();
+            vault.put(entry).await.unwrap();
+        }
+
+        let ranked = vault
+            .search_workspace_lexical("ws", "acme revenue", 10)
+            .await
+            .unwrap();
+        let ids = ranked
+            .iter()
+            .map(|(e, _)| e.id.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(ids, vec!["focused", "mention"]);
+        assert!(ranked[0].1 > ranked[1].1);
+
+        // Partial words miss the index and fall back to a substring scan.
+        let partial = vault.search_workspace("ws", "cater", 10).await.unwrap();
+        assert_eq!(partial.len(), 1);
+        assert_eq!(partial[0].id, "mention");
+
+        vault.delete_by_id("focused").await.unwrap();
+        let after_delete = vault
+            .search_workspace_lexical("ws", "revenue", 10)
+            .await
+            .unwrap();
+        assert!(after_delete.is_empty());
+
+        let _ = std::fs::remove_dir_all(temp_dir);
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_delete_source_removes_only_that_source() {
+        let temp_dir = std::env::temp_dir().join(uuid::Uuid::new_v4().to_string());
+        let vault = MemoryVaultService::new_with_provider(
+            temp_dir.clone(),
+            Arc::new(StaticKeyProvider) as Arc<dyn VaultKeyProvider>,
+        )
+        .await
+        .unwrap();
+
+        let now = chrono::Utc::now().timestamp();
+        for (id, source, workspace) in [
+            ("guide-1", "/docs/guide.md", "ws"),
+            ("guide-2", "/docs/guide.md", "ws"),
+            ("notes", "/docs/notes.md", "ws"),
+            ("other-ws", "/docs/guide.md", "other"),
+        ] {
+            let mut entry = input(id, source, now, axis(0, 0.0));
+            entry.workspace_id = workspace.to_string();
+            entry.content = format!("shared launch checklist {}", id);
+            vault.put(entry).await.unwrap();
+        }
+
+        assert_eq!(
+            vault.delete_source("ws", "/docs/guide.md").await.unwrap(),
+            2
+        );
+        assert!(vault.get_by_id("guide-1").await.unwrap().is_none());
+        assert!(vault.get_by_id("guide-2").await.unwrap().is_none());
+        assert!(vault.get_by_id("notes").await.unwrap().is_some());
+        assert!(vault.get_by_id("other-ws").await.unwrap().is_some());
+
+        let ranked = vault
+            .search_workspace_lexical("ws", "launch checklist", 10)
+            .await
+            .unwrap();
+        let ids = ranked
+            .iter()
+            .map(|(e, _)| e.id.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(ids, vec!["notes"]);
+
+        let _ = std::fs::remove_dir_all(temp_dir);
+    }
+}
@@ -1,11 +1,6 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
-pub const EMBEDDING_PROVIDER: &str = "gemini";
-pub const EMBEDDING_MODEL: &str =
-    crate::services::memory_vault::profiles::ACTIVE_EMBEDDING_PROFILE.model;
-pub const EMBEDDING_DIM: usize = crate::services::memory_vault::profiles::ACTIVE_EMBEDDING_PROFILE.dim;
-
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
 pub enum MemorySensitivity {
@@ -73,6 +68,8 @@ pub struct DecryptedMemoryEntry {
     pub embedding_model: Option<String>,
     pub embedding_provider: Option<String>,
     pub embedding_dim: Option<usize>,
+    pub importance: f32,
+    pub pinned: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -80,3 +77,42 @@ pub struct MemoryVaultStats {
     pub total_entries: usize,
     pub workspace_entries: usize,
 }
+
+/// Per-workspace lifecycle settings applied by vault maintenance and search ranking.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RetentionPolicy {
+    /// Entries untouched for longer than this are expired. 0 keeps everything.
+    pub retention_days: u32,
+    /// Entries at or above this importance survive retention.
+    pub keep_importance_above: f32,
+    /// Cosine similarity at which two entries are merged. Values >= 1.0 disable merging.
+    pub dedup_similarity: f32,
+    /// Weight of stored importance in the search score.
+    pub importance_weight: f32,
+    /// Weight of recency in the search score.
+    pub recency_weight: f32,
+    /// Days for the recency score to halve.
+    pub recency_half_life_days: u32,
+    pub maintenance_enabled: bool,
+}
+
+impl Default for RetentionPolicy {
+    fn default() -> Self {
+        Self {
+            retention_days: 30,
+            keep_importance_above: 0.65,
+            dedup_similarity: 0.95,
+            importance_weight: 0.15,
+            recency_weight: 0.15,
+            recency_half_life_days: 7,
+            maintenance_enabled: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MaintenanceReport {
+    pub expired: usize,
+    pub rescored: usize,
+    pub merged: usize,
+}
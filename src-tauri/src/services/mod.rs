@@ -10,6 +10,8 @@ pub mod atm_client;
 pub mod audit_emitter;
 pub mod browser_controller;
 pub mod cloud_bridge;
+pub mod command_policy;
+pub mod command_sandbox;
 pub mod command_poller;
 pub mod document;
 pub mod embedder;
@@ -18,6 +20,7 @@ pub mod file_operations;
 pub mod fleet_control;
 pub mod folder_manager;
 pub mod image;
+pub mod knowledge_base;
 pub mod llm_client;
 pub mod managed_research; // Phase 3 AI Research
 pub mod manifest_signing;
@@ -27,6 +30,7 @@ pub mod memory;
 pub mod memory_vault;
 pub mod neural_service;
 pub mod persistent_scheduler;
+pub mod process_manager;
 pub mod security;
 pub mod settings;
 pub mod skill_executor;
@@ -35,10 +39,13 @@ pub mod socket_client;
 pub mod task_manager;
 pub mod third_party_skill_registry;
 pub mod tool_manifest;
+pub mod tool_output;
 pub mod tool_policy;
 pub mod wasm_sandbox;
+pub mod web_search;
 
 pub mod workspace;
+pub mod workspace_index;
 pub mod workflow_recorder;
 
 pub use airlock::AirlockService;
@@ -52,10 +59,12 @@ pub use file_manager::FileManager;
 pub use file_operations::FileOperationEngine;
 pub use folder_manager::FolderManager;
 pub use image::ImageService;
+pub use knowledge_base::KnowledgeBase;
 pub use managed_research::ManagedResearchService;
 pub use mcp_service::McpService;
 pub use memory::MemoryManager;
 pub use neural_service::NeuralService;
+pub use process_manager::ProcessManager;
 pub use security::NodeAuthenticator;
 pub use skill_executor::SkillExecutor;
 
@@ -71,4 +80,5 @@ pub use workspace::{
     ConfigFormat, PermissionOverride, Workspace, WorkspaceAnalytics, WorkspaceManager,
     WorkspacePermissions, WorkspaceTemplate,
 };
+pub use workspace_index::WorkspaceIndexer;
 pub use workflow_recorder::WorkflowRecorderService;
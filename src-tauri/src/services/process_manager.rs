@@ -0,0 +1,523 @@
+//! Long-running processes started by agents
+//!
+//! `execute_command` waits for its command to exit; processes started here
+//! keep running across agent steps (dev servers, watchers, REPLs). Combined
+//! stdout/stderr is kept in a bounded buffer that callers read incrementally
+//! by byte cursor. Every process belongs to a workspace and, when started by
+//! an agent run, to that run: it is stopped when the run ends or is killed.
+
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::process::ExitStatus;
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWriteExt};
+use tokio::process::{Child, ChildStdin, Command};
+use tokio::sync::{Mutex, Notify, RwLock};
+
+/// Running processes allowed per workspace.
+const MAX_RUNNING_PER_WORKSPACE: usize = 8;
+/// Finished processes kept so their final output can still be read.
+const MAX_FINISHED: usize = 32;
+/// Output retained per process; older output is dropped.
+const OUTPUT_BUFFER_BYTES: usize = 1024 * 1024;
+/// How long a stopped process gets to exit after SIGTERM before SIGKILL.
+const STOP_GRACE: Duration = Duration::from_secs(3);
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ProcessState {
+    Running,
+    Exited,
+    Stopped,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ProcessInfo {
+    pub id: String,
+    pub workspace_id: String,
+    pub run_id: Option<String>,
+    pub command: String,
+    pub args: Vec<String>,
+    pub pid: Option<u32>,
+    pub state: ProcessState,
+    pub exit_code: Option<i32>,
+    pub started_at: i64,
+    pub ended_at: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ProcessOutput {
+    pub output: String,
+    /// Pass as `cursor` on the next read to get only newer output.
+    pub next_cursor: u64,
+    /// Output between the requested cursor and the oldest retained byte.
+    pub dropped_bytes: u64,
+    pub state: ProcessState,
+    pub exit_code: Option<i32>,
+}
+
+#[derive(Default)]
+struct OutputBuffer {
+    /// Cursor of the first retained byte.
+    start: u64,
+    bytes: VecDeque<u8>,
+}
+
+impl OutputBuffer {
+    fn end(&self) -> u64 {
+        self.start + self.bytes.len() as u64
+    }
+
+    fn push(&mut self, chunk: &[u8]) {
+        self.bytes.extend(chunk);
+        let excess = self.bytes.len().saturating_sub(OUTPUT_BUFFER_BYTES);
+        if excess > 0 {
+            self.bytes.drain(..excess);
+            self.start += excess as u64;
+        }
+    }
+
+    /// Up to `max_bytes` from `cursor`, the cursor after them, and how many
+    /// bytes before the retained window the cursor pointed at.
+    fn read(&self, cursor: u64, max_bytes: usize) -> (Vec<u8>, u64, u64) {
+        let from = cursor.clamp(self.start, self.end());
+        let to = from.saturating_add(max_bytes as u64).min(self.end());
+        let chunk = self
+            .bytes
+            .range((from - self.start) as usize..(to - self.start) as usize)
+            .copied()
+            .collect();
+        (chunk, to, self.start.saturating_sub(cursor))
+    }
+}
+
+struct ManagedProcess {
+    info: std::sync::Mutex<ProcessInfo>,
+    output: std::sync::Mutex<OutputBuffer>,
+    /// Signalled on new output and on exit.
+    changed: Notify,
+    stdin: Mutex<Option<ChildStdin>>,
+    stop: Notify,
+}
+
+impl ManagedProcess {
+    fn info(&self) -> ProcessInfo {
+        self.info.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    fn is_running(&self) -> bool {
+        self.info().state == ProcessState::Running
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct ProcessManager {
+    processes: Arc<RwLock<HashMap<String, Arc<ManagedProcess>>>>,
+    app: Option<AppHandle>,
+}
+
+impl ProcessManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Emit `process:started` and `process:exited` to the UI.
+    pub fn with_events(mut self, app: AppHandle) -> Self {
+        self.app = Some(app);
+        self
+    }
+
+    /// Spawn `command` (already configured with cwd, env and any sandbox)
+    /// with piped stdio in its own process group.
+    pub async fn start(
+        &self,
+        workspace_id: &str,
+        run_id: Option<&str>,
+        name: &str,
+        args: &[String],
+        mut command: Command,
+    ) -> Result<ProcessInfo, String> {
+        let mut processes = self.processes.write().await;
+        let running = processes
+            .values()
+            .filter(|p| p.is_running() && p.info().workspace_id == workspace_id)
+            .count();
+        if running >= MAX_RUNNING_PER_WORKSPACE {
+            return Err(format!(
+                "This workspace already has {} running processes; stop one first",
+                running
+            ));
+        }
+        Self::prune_finished(&mut processes);
+
+        command
+            .stdin(std::process::Stdio::piped())
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::piped())
+            .kill_on_drop(true);
+        #[cfg(unix)]
+        command.process_group(0);
+        let mut child = command
+            .spawn()
+            .map_err(|e| format!("Failed to start process: {}", e))?;
+
+        let info = ProcessInfo {
+            id: format!("proc-{}", &uuid::Uuid::new_v4().simple().to_string()[..8]),
+            workspace_id: workspace_id.to_string(),
+            run_id: run_id.map(str::to_string),
+            command: name.to_string(),
+            args: args.to_vec(),
+            pid: child.id(),
+            state: ProcessState::Running,
+            exit_code: None,
+            started_at: chrono::Utc::now().timestamp_millis(),
+            ended_at: None,
+        };
+        let process = Arc::new(ManagedProcess {
+            info: std::sync::Mutex::new(info.clone()),
+            output: std::sync::Mutex::new(OutputBuffer::default()),
+            changed: Notify::new(),
+            stdin: Mutex::new(child.stdin.take()),
+            stop: Notify::new(),
+        });
+        if let Some(stdout) = child.stdout.take() {
+            tokio::spawn(pump(stdout, process.clone()));
+        }
+        if let Some(stderr) = child.stderr.take() {
+            tokio::spawn(pump(stderr, process.clone()));
+        }
+        tokio::spawn(self.clone().supervise(child, process.clone()));
+
+        processes.insert(info.id.clone(), process);
+        self.emit("process:started", &info);
+        Ok(info)
+    }
+
+    /// Processes in `workspace_id` (all workspaces when `None`), oldest first.
+    pub async fn list(&self, workspace_id: Option<&str>) -> Vec<ProcessInfo> {
+        let mut infos = self
+            .processes
+            .read()
+            .await
+            .values()
+            .map(|p| p.info())
+            .filter(|info| workspace_id.is_none_or(|ws| info.workspace_id == ws))
+            .collect::<Vec<_>>();
+        infos.sort_by_key(|info| info.started_at);
+        infos
+    }
+
+    /// Output written since `cursor`. With `wait`, blocks up to that long for
+    /// new output while the process is running and has none yet.
+    pub async fn read_output(
+        &self,
+        workspace_id: &str,
+        process_id: &str,
+        cursor: u64,
+        max_bytes: usize,
+        wait: Duration,
+    ) -> Result<ProcessOutput, String> {
+        let process = self.get(workspace_id, process_id).await?;
+        let deadline = tokio::time::Instant::now() + wait;
+        loop {
+            let changed = process.changed.notified();
+            tokio::pin!(changed);
+            changed.as_mut().enable();
+            let has_output = process
+                .output
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .end()
+                > cursor;
+            if has_output || !process.is_running() {
+                break;
+            }
+            if tokio::time::timeout_at(deadline, changed).await.is_err() {
+                break;
+            }
+        }
+
+        let (chunk, next_cursor, dropped_bytes) = process
+            .output
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .read(cursor, max_bytes);
+        let info = process.info();
+        Ok(ProcessOutput {
+            output: String::from_utf8_lossy(&chunk).to_string(),
+            next_cursor,
+            dropped_bytes,
+            state: info.state,
+            exit_code: info.exit_code,
+        })
+    }
+
+    /// Write `input` to the process's stdin; `close` then sends EOF.
+    pub async fn write_stdin(
+        &self,
+        workspace_id: &str,
+        process_id: &str,
+        input: &str,
+        close: bool,
+    ) -> Result<(), String> {
+        let process = self.get(workspace_id, process_id).await?;
+        if !process.is_running() {
+            return Err(format!("Process {} is not running", process_id));
+        }
+        let mut stdin = process.stdin.lock().await;
+        let pipe = stdin
+            .as_mut()
+            .ok_or_else(|| format!("Stdin of process {} is closed", process_id))?;
+        pipe.write_all(input.as_bytes())
+            .await
+            .map_err(|e| format!("Failed to write to process {}: {}", process_id, e))?;
+        pipe.flush()
+            .await
+            .map_err(|e| format!("Failed to write to process {}: {}", process_id, e))?;
+        if close {
+            *stdin = None;
+        }
+        Ok(())
+    }
+
+    /// Stop a process (SIGTERM to its group, SIGKILL after a grace period)
+    /// and wait for it to exit.
+    pub async fn stop(&self, workspace_id: &str, process_id: &str) -> Result<ProcessInfo, String> {
+        let process = self.get(workspace_id, process_id).await?;
+        Self::stop_process(&process).await;
+        Ok(process.info())
+    }
+
+    /// Stop every process started by `run_id`; called when the run ends.
+    pub async fn stop_run(&self, run_id: &str) {
+        let processes = self
+            .running(|info| info.run_id.as_deref() == Some(run_id))
+            .await;
+        futures::future::join_all(processes.iter().map(Self::stop_process)).await;
+    }
+
+    /// Stop every running process, e.g. when the fleet kill switch is armed.
+    pub async fn stop_all(&self) {
+        let processes = self.running(|_| true).await;
+        futures::future::join_all(processes.iter().map(Self::stop_process)).await;
+    }
+
+    async fn get(
+        &self,
+        workspace_id: &str,
+        process_id: &str,
+    ) -> Result<Arc<ManagedProcess>, String> {
+        self.processes
+            .read()
+            .await
+            .get(process_id)
+            .filter(|p| p.info().workspace_id == workspace_id)
+            .cloned()
+            .ok_or_else(|| format!("Unknown process: {}", process_id))
+    }
+
+    async fn running(&self, filter: impl Fn(&ProcessInfo) -> bool) -> Vec<Arc<ManagedProcess>> {
+        self.processes
+            .read()
+            .await
+            .values()
+            .filter(|p| {
+                let info = p.info();
+                info.state == ProcessState::Running && filter(&info)
+            })
+            .cloned()
+            .collect()
+    }
+
+    async fn stop_process(process: &Arc<ManagedProcess>) {
+        let exited = process.changed.notified();
+        tokio::pin!(exited);
+        loop {
+            exited.as_mut().enable();
+            if !process.is_running() {
+                return;
+            }
+            process.stop.notify_one();
+            exited.as_mut().await;
+            exited.set(process.changed.notified());
+        }
+    }
+
+    fn prune_finished(processes: &mut HashMap<String, Arc<ManagedProcess>>) {
+        let mut finished = processes
+            .values()
+            .map(|p| p.info())
+            .filter(|info| info.state != ProcessState::Running)
+            .collect::<Vec<_>>();
+        if finished.len() < MAX_FINISHED {
+            return;
+        }
+        finished.sort_by_key(|info| info.ended_at);
+        for info in &finished[..=finished.len() - MAX_FINISHED] {
+            processes.remove(&info.id);
+        }
+    }
+
+    /// Wait for the child to exit or be stopped, then record how it ended.
+    async fn supervise(self, mut child: Child, process: Arc<ManagedProcess>) {
+        let group = child.id();
+        let (status, state) = tokio::select! {
+            status = child.wait() => (status.ok(), ProcessState::Exited),
+            _ = process.stop.notified() => (terminate(&mut child).await, ProcessState::Stopped),
+        };
+        // Background jobs it started would otherwise outlive it unsupervised.
+        #[cfg(unix)]
+        if let (ProcessState::Exited, Some(pid)) = (&state, group) {
+            // SAFETY: signalling a process group has no memory-safety requirements.
+            unsafe { libc::killpg(pid as libc::pid_t, libc::SIGTERM) };
+        }
+        #[cfg(not(unix))]
+        let _ = group;
+        *process.stdin.lock().await = None;
+        let info = {
+            let mut info = process.info.lock().unwrap_or_else(|e| e.into_inner());
+            info.state = state;
+            info.exit_code = status.and_then(|s| s.code());
+            info.ended_at = Some(chrono::Utc::now().timestamp_millis());
+            info.clone()
+        };
+        process.changed.notify_waiters();
+        self.emit("process:exited", &info);
+    }
+
+    fn emit(&self, event: &str, info: &ProcessInfo) {
+        if let Some(app) = self.app.as_ref() {
+            let _ = app.emit(event, info);
+        }
+    }
+}
+
+async fn pump(mut reader: impl AsyncRead + Unpin, process: Arc<ManagedProcess>) {
+    let mut buf = vec![0u8; 8192];
+    while let Ok(n) = reader.read(&mut buf).await {
+        if n == 0 {
+            break;
+        }
+        process
+            .output
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(&buf[..n]);
+        process.changed.notify_waiters();
+    }
+}
+
+/// SIGTERM the child's process group so servers and their workers all get
+/// to shut down, then SIGKILL whatever is left after the grace period.
+async fn terminate(child: &mut Child) -> Option<ExitStatus> {
+    #[cfg(unix)]
+    if let Some(pid) = child.id() {
+        let group = pid as libc::pid_t;
+        // SAFETY: signalling a process group has no memory-safety requirements.
+        unsafe { libc::killpg(group, libc::SIGTERM) };
+        let status = tokio::time::timeout(STOP_GRACE, child.wait()).await;
+        unsafe { libc::killpg(group, libc::SIGKILL) };
+        if let Ok(status) = status {
+            return status.ok();
+        }
+    }
+    let _ = child.kill().await;
+    child.wait().await.ok()
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    fn shell(script: &str) -> Command {
+        let mut command = Command::new("sh");
+        command.args(["-c", script]);
+        command
+    }
+
+    #[test]
+    fn output_buffer_drops_oldest_bytes_and_reports_them() {
+        let mut buffer = OutputBuffer::default();
+        buffer.push(&vec![b'a'; OUTPUT_BUFFER_BYTES]);
+        buffer.push(b"tail");
+        let (chunk, next, dropped) = buffer.read(0, usize::MAX);
+        assert_eq!(dropped, 4);
+        assert_eq!(chunk.len(), OUTPUT_BUFFER_BYTES);
+        assert!(chunk.ends_with(b"tail"));
+        assert_eq!(next, OUTPUT_BUFFER_BYTES as u64 + 4);
+        assert!(buffer.read(next, 10).0.is_empty());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn natural_exit_terminates_background_jobs() {
+        let manager = ProcessManager::new();
+        let info = manager
+            .start("ws", None, "sh", &[], shell("sleep 30 & echo $!"))
+            .await
+            .unwrap();
+        let output = manager
+            .read_output("ws", &info.id, 0, 4096, Duration::from_secs(5))
+            .await
+            .unwrap();
+        let job: u32 = output.output.trim().parse().unwrap();
+
+        // Alive until the kernel reaps it; a zombie has already exited.
+        let alive = || {
+            std::fs::read_to_string(format!("/proc/{}/stat", job))
+                .is_ok_and(|stat| !stat.contains(") Z "))
+        };
+        let deadline = tokio::time::Instant::now() + Duration::from_secs(5);
+        while alive() && tokio::time::Instant::now() < deadline {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        assert!(!alive());
+    }
+
+    #[tokio::test]
+    async fn interactive_process_reads_stdin_and_stops_with_its_run() {
+        let manager = ProcessManager::new();
+        let info = manager
+            .start(
+                "ws",
+                Some("run-1"),
+                "sh",
+                &[],
+                shell("echo ready; while read line; do echo \"got $line\"; done"),
+            )
+            .await
+            .unwrap();
+        let wait = Duration::from_secs(5);
+
+        let first = manager
+            .read_output("ws", &info.id, 0, 4096, wait)
+            .await
+            .unwrap();
+        assert_eq!(first.output, "ready\n");
+        assert!(manager
+            .read_output("other", &info.id, 0, 4096, wait)
+            .await
+            .is_err());
+
+        manager
+            .write_stdin("ws", &info.id, "ping\n", false)
+            .await
+            .unwrap();
+        let second = manager
+            .read_output("ws", &info.id, first.next_cursor, 4096, wait)
+            .await
+            .unwrap();
+        assert_eq!(second.output, "got ping\n");
+
+        manager.stop_run("run-1").await;
+        let listed = manager.list(Some("ws")).await;
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].state, ProcessState::Stopped);
+        assert!(manager
+            .write_stdin("ws", &info.id, "late\n", false)
+            .await
+            .is_err());
+    }
+}
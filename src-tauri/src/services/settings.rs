@@ -5,13 +5,22 @@ use crate::ai::model_catalog::{
     ensure_supported_model_slug, find_catalog_model, ModelProvider,
 };
 use crate::ai::provider::AIProviderManager;
+use crate::ai::specs::security::{
+    enforce_spec_signature, SpecSignaturePolicy, SpecTrust, TrustedSigner,
+};
+use crate::ai::specs::AgentSpec;
 use crate::models::neural::ToolAccessPolicy;
+use crate::services::command_policy::CommandPolicy;
+use crate::services::command_sandbox::SandboxConfig;
 use crate::services::mcp_service::{McpPermissionMode, PersistedMcpServerConfig};
+use crate::services::web_search::WebSearchConfig;
 use rainy_sdk::models::{CapabilityFlag, ModelCatalogItem};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::sync::Mutex;
 
 /// Available AI model for selection
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -39,12 +48,30 @@ pub struct UserSettings {
     pub tool_policy_version_floor: HashMap<String, u64>,
     #[serde(default)]
     pub workspace_tool_access_policies: HashMap<String, WorkspaceToolPolicyState>,
+    /// `execute_command` rules per workspace; workspaces without one use the built-in allowlist
+    pub workspace_command_policies: HashMap<String, CommandPolicy>,
+    /// OS sandbox for `execute_command` per workspace; absent means unsandboxed
+    pub workspace_sandboxes: HashMap<String, SandboxConfig>,
+    /// Workspaces whose files are indexed (and embedded) for `semantic_search_workspace`
+    pub indexed_workspaces: HashSet<String>,
     pub embedder_provider: String,
     pub embedder_model: String,
+    /// Base URL of the OpenAI-compatible embeddings endpoint
+    pub embedder_base_url: String,
+    /// Vector size for embedding models whose size isn't known in advance
+    pub embedder_dimensions: Option<usize>,
     #[serde(default)]
     pub mcp_permission_mode: McpPermissionMode,
     #[serde(default)]
     pub mcp_servers: Vec<PersistedMcpServerConfig>,
+    /// Base URL of the local OpenAI-compatible server (Ollama, llama.cpp, LM Studio)
+    pub local_llm_base_url: String,
+    /// Backend the `web_search` tool queries; defaults to Rainy cloud research
+    pub web_search: WebSearchConfig,
+    /// How agent spec signatures are enforced when a spec is loaded or run
+    pub spec_signature_policy: SpecSignaturePolicy,
+    /// Public keys whose agent spec signatures are accepted
+    pub trusted_spec_signers: Vec<TrustedSigner>,
 }
 
 /// User profile metadata for desktop personalization and cloud identity sync
@@ -76,10 +103,20 @@ impl Default for UserSettings {
             auto_reconnect_cloud: true,
             tool_policy_version_floor: HashMap::new(),
             workspace_tool_access_policies: HashMap::new(),
+            workspace_command_policies: HashMap::new(),
+            workspace_sandboxes: HashMap::new(),
+            indexed_workspaces: HashSet::new(),
             embedder_provider: "gemini".to_string(),
-            embedder_model: crate::services::memory_vault::types::EMBEDDING_MODEL.to_string(),
+            embedder_model: crate::services::memory_vault::profiles::GEMINI_EMBEDDING_2_PREVIEW
+                .to_string(),
+            embedder_base_url: "https://api.openai.com/v1".to_string(),
+            embedder_dimensions: None,
             mcp_permission_mode: McpPermissionMode::Ask,
             mcp_servers: Vec::new(),
+            local_llm_base_url: crate::ai::providers::local::LOCAL_API_BASE.to_string(),
+            web_search: WebSearchConfig::default(),
+            spec_signature_policy: SpecSignaturePolicy::default(),
+            trusted_spec_signers: Vec::new(),
         }
     }
 }
@@ -116,6 +153,32 @@ impl SettingsManager {
         }
     }
 
+    /// Settings stored at `settings_path` instead of the app data directory.
+    #[cfg(test)]
+    pub(crate) fn at_path(settings_path: PathBuf) -> Self {
+        let settings = Self::load_from_disk(&settings_path);
+        Self {
+            settings_path,
+            settings,
+        }
+    }
+
+    /// Read from the app's managed settings when `shared` is set; otherwise
+    /// load them from disk on a blocking thread.
+    pub async fn read_shared<T, F>(shared: Option<&Arc<Mutex<SettingsManager>>>, read: F) -> T
+    where
+        F: FnOnce(&SettingsManager) -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        if let Some(settings) = shared {
+            return read(&*settings.lock().await);
+        }
+        match tokio::task::spawn_blocking(move || read(&SettingsManager::new())).await {
+            Ok(value) => value,
+            Err(e) => std::panic::resume_unwind(e.into_panic()),
+        }
+    }
+
     fn get_settings_path() -> PathBuf {
         let app_data = dirs::data_dir()
             .unwrap_or_else(|| PathBuf::from("."))
@@ -143,7 +206,7 @@ impl SettingsManager {
                             }
                             _ => {
                                 settings.embedder_model =
-                                    crate::services::memory_vault::types::EMBEDDING_MODEL
+                                    crate::services::memory_vault::profiles::GEMINI_EMBEDDING_2_PREVIEW
                                         .to_string();
                             }
                         }
@@ -207,9 +270,18 @@ impl SettingsManager {
         &self.settings.embedder_provider
     }
 
-    /// Set embedder provider and persist
+    /// Set embedder provider and persist. Switching providers also switches
+    /// to that provider's default model, which selects the embedding profile.
     pub fn set_embedder_provider(&mut self, provider: String) -> Result<(), String> {
-        self.settings.embedder_provider = provider;
+        use crate::services::embedder::{default_embedder_model, normalize_embedder_provider};
+
+        let normalized = normalize_embedder_provider(&provider)
+            .ok_or_else(|| format!("Unsupported embedding provider '{}'", provider))?;
+        if normalize_embedder_provider(&self.settings.embedder_provider) != Some(normalized) {
+            self.settings.embedder_model = default_embedder_model(normalized).to_string();
+            self.settings.embedder_dimensions = None;
+        }
+        self.settings.embedder_provider = normalized.to_string();
         self.save_to_disk()
     }
 
@@ -224,6 +296,80 @@ impl SettingsManager {
         self.save_to_disk()
     }
 
+    /// Get OpenAI-compatible embeddings base URL
+    pub fn get_embedder_base_url(&self) -> &str {
+        &self.settings.embedder_base_url
+    }
+
+    /// Set OpenAI-compatible embeddings base URL and persist
+    pub fn set_embedder_base_url(&mut self, base_url: String) -> Result<(), String> {
+        let base_url = base_url.trim().trim_end_matches('/').to_string();
+        let parsed = url::Url::parse(&base_url)
+            .map_err(|e| format!("Invalid embeddings URL '{}': {}", base_url, e))?;
+        if !matches!(parsed.scheme(), "http" | "https") {
+            return Err(format!(
+                "Embeddings URL must be http or https: {}",
+                base_url
+            ));
+        }
+        self.settings.embedder_base_url = base_url;
+        self.save_to_disk()
+    }
+
+    /// Get the configured embedding vector size, if any
+    pub fn get_embedder_dimensions(&self) -> Option<usize> {
+        self.settings.embedder_dimensions
+    }
+
+    /// Set the embedding vector size and persist. `None` uses the model's known size.
+    pub fn set_embedder_dimensions(&mut self, dimensions: Option<usize>) -> Result<(), String> {
+        let max = crate::services::memory_vault::profiles::VECTOR_COLUMN_DIM;
+        if let Some(dim) = dimensions.filter(|d| *d == 0 || *d > max) {
+            return Err(format!(
+                "Embedding dimensions must be 1..={}, got {}",
+                max, dim
+            ));
+        }
+        self.settings.embedder_dimensions = dimensions;
+        self.save_to_disk()
+    }
+
+    /// Get local model server base URL
+    pub fn get_local_llm_base_url(&self) -> &str {
+        &self.settings.local_llm_base_url
+    }
+
+    /// Set local model server base URL and persist
+    pub fn set_local_llm_base_url(&mut self, base_url: String) -> Result<(), String> {
+        let base_url = base_url.trim().trim_end_matches('/').to_string();
+        let parsed = url::Url::parse(&base_url)
+            .map_err(|e| format!("Invalid local model server URL '{}': {}", base_url, e))?;
+        if !matches!(parsed.scheme(), "http" | "https") {
+            return Err(format!(
+                "Unsupported local model server URL scheme '{}'. Use http or https.",
+                parsed.scheme()
+            ));
+        }
+        self.settings.local_llm_base_url = base_url;
+        self.save_to_disk()
+    }
+
+    /// Get the `web_search` backend configuration
+    pub fn get_web_search_config(&self) -> WebSearchConfig {
+        self.settings.web_search.clone()
+    }
+
+    /// Set the `web_search` backend configuration and persist
+    pub fn set_web_search_config(&mut self, mut config: WebSearchConfig) -> Result<(), String> {
+        config.validate()?;
+        config.base_url = config
+            .base_url
+            .map(|u| u.trim().trim_end_matches('/').to_string())
+            .filter(|u| !u.is_empty());
+        self.settings.web_search = config;
+        self.save_to_disk()
+    }
+
     /// Get the persisted minimum accepted tool policy version for a workspace.
     pub fn get_tool_policy_floor(&self, workspace_id: &str) -> u64 {
         self.settings
@@ -271,6 +417,77 @@ impl SettingsManager {
         self.save_to_disk()
     }
 
+    pub fn get_workspace_command_policy(&self, workspace_id: &str) -> Option<CommandPolicy> {
+        self.settings
+            .workspace_command_policies
+            .get(workspace_id)
+            .cloned()
+    }
+
+    /// Store the workspace's command policy; `None` restores the built-in allowlist.
+    pub fn set_workspace_command_policy(
+        &mut self,
+        workspace_id: &str,
+        policy: Option<CommandPolicy>,
+    ) -> Result<(), String> {
+        match policy {
+            Some(policy) => {
+                self.settings
+                    .workspace_command_policies
+                    .insert(workspace_id.to_string(), policy);
+            }
+            None => {
+                self.settings
+                    .workspace_command_policies
+                    .remove(workspace_id);
+            }
+        }
+        self.save_to_disk()
+    }
+
+    pub fn get_workspace_sandbox(&self, workspace_id: &str) -> Option<SandboxConfig> {
+        self.settings.workspace_sandboxes.get(workspace_id).cloned()
+    }
+
+    /// Store the workspace's sandbox; `None` runs its commands unsandboxed.
+    pub fn set_workspace_sandbox(
+        &mut self,
+        workspace_id: &str,
+        sandbox: Option<SandboxConfig>,
+    ) -> Result<(), String> {
+        match sandbox {
+            Some(sandbox) => {
+                self.settings
+                    .workspace_sandboxes
+                    .insert(workspace_id.to_string(), sandbox);
+            }
+            None => {
+                self.settings.workspace_sandboxes.remove(workspace_id);
+            }
+        }
+        self.save_to_disk()
+    }
+
+    pub fn is_workspace_indexed(&self, workspace_id: &str) -> bool {
+        self.settings.indexed_workspaces.contains(workspace_id)
+    }
+
+    /// Opt the workspace in or out of the background file index.
+    pub fn set_workspace_indexed(
+        &mut self,
+        workspace_id: &str,
+        enabled: bool,
+    ) -> Result<(), String> {
+        if enabled {
+            self.settings
+                .indexed_workspaces
+                .insert(workspace_id.to_string());
+        } else {
+            self.settings.indexed_workspaces.remove(workspace_id);
+        }
+        self.save_to_disk()
+    }
+
     pub fn get_mcp_permission_mode(&self) -> McpPermissionMode {
         self.settings.mcp_permission_mode.clone()
     }
@@ -280,6 +497,54 @@ impl SettingsManager {
         self.save_to_disk()
     }
 
+    pub fn get_spec_signature_policy(&self) -> SpecSignaturePolicy {
+        self.settings.spec_signature_policy
+    }
+
+    pub fn set_spec_signature_policy(&mut self, policy: SpecSignaturePolicy) -> Result<(), String> {
+        self.settings.spec_signature_policy = policy;
+        self.save_to_disk()
+    }
+
+    pub fn get_trusted_spec_signers(&self) -> &[TrustedSigner] {
+        &self.settings.trusted_spec_signers
+    }
+
+    pub fn upsert_trusted_spec_signer(&mut self, signer: TrustedSigner) -> Result<(), String> {
+        if let Some(existing) = self
+            .settings
+            .trusted_spec_signers
+            .iter_mut()
+            .find(|s| s.signer_id == signer.signer_id)
+        {
+            *existing = signer;
+        } else {
+            self.settings.trusted_spec_signers.push(signer);
+        }
+        self.save_to_disk()
+    }
+
+    pub fn remove_trusted_spec_signer(&mut self, signer_id: &str) -> Result<(), String> {
+        let before = self.settings.trusted_spec_signers.len();
+        self.settings
+            .trusted_spec_signers
+            .retain(|s| s.signer_id != signer_id);
+        if self.settings.trusted_spec_signers.len() == before {
+            return Err(format!("Trusted signer '{}' not found", signer_id));
+        }
+        self.save_to_disk()
+    }
+
+    /// Apply the configured signature policy and keyring to a spec about to
+    /// be loaded or run.
+    pub fn verify_agent_spec(&self, spec: &AgentSpec) -> Result<SpecTrust, String> {
+        enforce_spec_signature(
+            spec,
+            self.settings.spec_signature_policy,
+            &self.settings.trusted_spec_signers,
+        )
+    }
+
     pub fn get_mcp_servers(&mut self) -> Vec<PersistedMcpServerConfig> {
         self.settings.mcp_servers.clone()
     }
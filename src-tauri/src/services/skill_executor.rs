@@ -1,25 +1,35 @@
 mod args;
 mod browser;
+mod edit;
+mod entities;
 mod filesystem;
+mod process;
 mod registry;
 mod shell;
 mod web;
 
+use crate::ai::agent::entity_graph::EntityGraph;
 use crate::models::neural::{CommandResult, QueuedCommand, ToolAccessPolicy};
 use crate::services::browser_controller::BrowserController;
+use crate::services::command_policy::CommandPolicy;
+use crate::services::process_manager::ProcessManager;
 use crate::services::settings::SettingsManager;
 use crate::services::third_party_skill_registry::{
     InstalledThirdPartySkill, ThirdPartySkillRegistry,
 };
+use crate::services::tool_output::ToolOutputSink;
 use crate::services::wasm_sandbox::{WasmExecutionRequest, WasmSandboxService};
+use crate::services::web_search::WebSearchService;
 use crate::services::workspace::WorkspaceManager;
+use crate::services::workspace_index::WorkspaceIndexer;
+use crate::services::FileOperationEngine;
 use crate::services::ManagedResearchService;
 use crate::services::MemoryManager;
 use sha2::{Digest, Sha256};
 use std::net::IpAddr;
 use std::path::Path;
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use tokio::sync::{Mutex, RwLock};
 
 const MAX_TOOL_OUTPUT_BYTES: usize = 48 * 1024;
 
@@ -43,8 +53,14 @@ fn truncate_output(input: &str) -> String {
 pub struct SkillExecutor {
     workspace_manager: Arc<WorkspaceManager>,
     managed_research: Arc<ManagedResearchService>,
+    web_search: Arc<WebSearchService>,
     browser: Arc<BrowserController>,
     memory_manager: Arc<RwLock<Option<Arc<MemoryManager>>>>,
+    file_ops: Arc<RwLock<Option<Arc<FileOperationEngine>>>>,
+    entity_graph: Arc<RwLock<Option<EntityGraph>>>,
+    workspace_index: Arc<RwLock<Option<WorkspaceIndexer>>>,
+    process_manager: Arc<RwLock<Option<ProcessManager>>>,
+    settings: Arc<RwLock<Option<Arc<Mutex<SettingsManager>>>>>,
     third_party_registry: Arc<ThirdPartySkillRegistry>,
     wasm_sandbox: Arc<WasmSandboxService>,
     mcp_service: Arc<crate::services::mcp_service::McpService>,
@@ -55,13 +71,6 @@ impl SkillExecutor {
         registry::registered_tool_definitions()
     }
 
-    fn is_allowed_shell_command(command: &str) -> bool {
-        matches!(
-            command,
-            "npm" | "pnpm" | "bun" | "cargo" | "git" | "ls" | "grep" | "echo" | "cat"
-        )
-    }
-
     fn is_tool_allowed(method: &str, policy: Option<&ToolAccessPolicy>) -> bool {
         let Some(policy) = policy else {
             return true;
@@ -107,11 +116,18 @@ impl SkillExecutor {
     ) -> Self {
         let third_party_registry =
             Arc::new(ThirdPartySkillRegistry::new().expect("Failed to init third-party registry"));
+        let web_search = Arc::new(WebSearchService::new(managed_research.provider_manager()));
         Self {
             workspace_manager,
             managed_research,
+            web_search,
             browser,
             memory_manager: Arc::new(RwLock::new(None)),
+            file_ops: Arc::new(RwLock::new(None)),
+            entity_graph: Arc::new(RwLock::new(None)),
+            workspace_index: Arc::new(RwLock::new(None)),
+            process_manager: Arc::new(RwLock::new(None)),
+            settings: Arc::new(RwLock::new(None)),
             third_party_registry,
             wasm_sandbox: Arc::new(WasmSandboxService::new()),
             mcp_service,
@@ -123,10 +139,105 @@ impl SkillExecutor {
         *lock = Some(mm);
     }
 
+    pub async fn set_file_operation_engine(&self, engine: Arc<FileOperationEngine>) {
+        let mut lock = self.file_ops.write().await;
+        *lock = Some(engine);
+    }
+
+    pub async fn set_entity_graph(&self, graph: EntityGraph) {
+        let mut lock = self.entity_graph.write().await;
+        *lock = Some(graph);
+    }
+
+    pub async fn set_workspace_indexer(&self, indexer: WorkspaceIndexer) {
+        let mut lock = self.workspace_index.write().await;
+        *lock = Some(indexer);
+    }
+
+    pub async fn set_process_manager(&self, manager: ProcessManager) {
+        let mut lock = self.process_manager.write().await;
+        *lock = Some(manager);
+    }
+
+    pub async fn set_settings_manager(&self, settings: Arc<Mutex<SettingsManager>>) {
+        let mut lock = self.settings.write().await;
+        *lock = Some(settings);
+    }
+
+    /// Read user settings without re-reading settings.json when the app's
+    /// managed copy has been injected.
+    pub(super) async fn read_settings<T, F>(&self, read: F) -> T
+    where
+        F: FnOnce(&SettingsManager) -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        let shared = self.settings.read().await.clone();
+        SettingsManager::read_shared(shared.as_ref(), read).await
+    }
+
+    /// The workspace's `execute_command` policy, or the built-in one.
+    pub async fn workspace_command_policy(&self, workspace_id: &str) -> CommandPolicy {
+        let workspace_id = workspace_id.to_string();
+        self.read_settings(move |settings| CommandPolicy::for_workspace(settings, &workspace_id))
+            .await
+    }
+
+    /// Stop the processes a run started; called when the run ends.
+    pub async fn stop_run_processes(&self, run_id: &str) {
+        if let Some(manager) = self.process_manager.read().await.clone() {
+            manager.stop_run(run_id).await;
+        }
+    }
+
+    /// Stop every agent-started process (fleet kill switch).
+    pub async fn stop_all_processes(&self) {
+        if let Some(manager) = self.process_manager.read().await.clone() {
+            manager.stop_all().await;
+        }
+    }
+
+    /// Snapshot `path` into the run's checkpoint before its first mutation.
+    /// Fails closed: a change that could not be checkpointed is not made.
+    pub(super) async fn checkpoint_before_mutation(
+        &self,
+        run_id: Option<&str>,
+        path: &Path,
+    ) -> Result<(), String> {
+        let Some(run_id) = run_id else {
+            return Ok(());
+        };
+        let Some(engine) = self.file_ops.read().await.clone() else {
+            return Ok(());
+        };
+        engine.checkpoint_path(run_id, path).await.map_err(|e| {
+            format!(
+                "Failed to checkpoint '{}' before change: {}",
+                path.display(),
+                e
+            )
+        })
+    }
+
+    /// Close the run's file checkpoint so it can be previewed and reverted.
+    pub async fn finish_run_checkpoint(&self, run_id: &str) {
+        let Some(engine) = self.file_ops.read().await.clone() else {
+            return;
+        };
+        if let Err(e) = engine.finish_run_checkpoint(run_id).await {
+            tracing::warn!("Failed to close file checkpoint for run {}: {}", run_id, e);
+        }
+    }
+
+    /// Halt a browser navigation left running by an interrupted tool call.
+    pub async fn abort_browser_navigation(&self) {
+        self.browser.stop_loading().await;
+    }
+
     #[cfg(test)]
     pub fn mock() -> Self {
         let provider_manager = Arc::new(crate::ai::provider::AIProviderManager::new());
-        let research = Arc::new(ManagedResearchService::new(provider_manager));
+        let research = Arc::new(ManagedResearchService::new(provider_manager.clone()));
+        let web_search = Arc::new(WebSearchService::new(provider_manager));
         let browser = Arc::new(BrowserController::new());
         let wm = Arc::new(WorkspaceManager::new().unwrap_or_else(|_| {
             panic!("Failed to create mock WorkspaceManager for test");
@@ -135,8 +246,14 @@ impl SkillExecutor {
         Self {
             workspace_manager: wm,
             managed_research: research,
+            web_search,
             browser,
             memory_manager: Arc::new(RwLock::new(None)),
+            file_ops: Arc::new(RwLock::new(None)),
+            entity_graph: Arc::new(RwLock::new(None)),
+            workspace_index: Arc::new(RwLock::new(None)),
+            process_manager: Arc::new(RwLock::new(None)),
+            settings: Arc::new(RwLock::new(None)),
             third_party_registry: Arc::new(
                 ThirdPartySkillRegistry::new().expect("mock third-party registry"),
             ),
@@ -146,6 +263,16 @@ impl SkillExecutor {
     }
 
     pub async fn execute(&self, command: &QueuedCommand) -> CommandResult {
+        self.execute_streaming(command, None).await
+    }
+
+    /// Like [`execute`](Self::execute), passing the output of shell, git and
+    /// WASM tools to `on_output` while they run.
+    pub async fn execute_streaming(
+        &self,
+        command: &QueuedCommand,
+        on_output: Option<ToolOutputSink>,
+    ) -> CommandResult {
         let payload = &command.payload;
         let skill = payload.skill.as_deref().unwrap_or("unknown");
         let method = payload.method.as_deref().unwrap_or("unknown");
@@ -238,9 +365,13 @@ impl SkillExecutor {
                     &payload.params,
                     allowed_paths,
                     blocked_paths,
+                    payload.run_id.as_deref(),
                 )
                 .await
             }
+            "shell" if process::PROCESS_TOOLS.contains(&method) => {
+                self.execute_process(workspace_id, method, payload).await
+            }
             "shell" => {
                 self.execute_shell(
                     workspace_id,
@@ -248,6 +379,8 @@ impl SkillExecutor {
                     &payload.params,
                     allowed_paths,
                     blocked_paths,
+                    payload.command_policy.as_ref(),
+                    on_output.as_ref(),
                 )
                 .await
             }
@@ -272,6 +405,7 @@ impl SkillExecutor {
                     blocked_paths,
                     allowed_domains,
                     blocked_domains,
+                    on_output,
                 )
                 .await
                 .unwrap_or_else(|| CommandResult {
@@ -381,15 +515,21 @@ impl SkillExecutor {
         method: &str,
         params: &Option<serde_json::Value>,
     ) -> CommandResult {
+        let empty_params = serde_json::Value::Object(serde_json::Map::new());
+        let params = params.as_ref().unwrap_or(&empty_params);
+
+        if matches!(method, "remember_fact" | "recall_entity" | "list_related") {
+            return self
+                .execute_entity_graph(workspace_id, method, params)
+                .await;
+        }
+
         let lock = self.memory_manager.read().await;
         let mm = match lock.as_ref() {
             Some(m) => m,
             None => return self.error("MemoryManager not initialized"),
         };
 
-        let empty_params = serde_json::Value::Object(serde_json::Map::new());
-        let params = params.as_ref().unwrap_or(&empty_params);
-
         match method {
             "search_memory" => {
                 let query = params.get("query").and_then(|v| v.as_str()).unwrap_or("");
@@ -410,6 +550,7 @@ impl SkillExecutor {
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     async fn execute_third_party_skill(
         &self,
         command: &QueuedCommand,
@@ -420,6 +561,7 @@ impl SkillExecutor {
         blocked_paths: &[String],
         allowed_domains: &[String],
         blocked_domains: &[String],
+        on_output: Option<ToolOutputSink>,
     ) -> Option<CommandResult> {
         let resolved = match self.third_party_registry.resolve_method(skill, method) {
             Ok(Some(r)) => r,
@@ -454,11 +596,14 @@ impl SkillExecutor {
 
         let result = self
             .wasm_sandbox
-            .execute(WasmExecutionRequest {
-                skill: skill_def,
-                method: method_def,
-                params_json,
-            })
+            .execute(
+                WasmExecutionRequest {
+                    skill: skill_def,
+                    method: method_def,
+                    params_json,
+                },
+                on_output,
+            )
             .await
             .into_command_result();
         Some(result)
@@ -583,22 +728,6 @@ mod tests {
     use super::SkillExecutor;
     use std::path::{Path, PathBuf};
 
-    #[test]
-    fn shell_allowlist_matches_agents_policy() {
-        for cmd in [
-            "npm", "pnpm", "bun", "cargo", "git", "ls", "grep", "echo", "cat",
-        ] {
-            assert!(SkillExecutor::is_allowed_shell_command(cmd));
-        }
-    }
-
-    #[test]
-    fn shell_allowlist_blocks_dangerous_commands() {
-        for cmd in ["rm", "curl", "wget", "kill", "mv", "cp", "node"] {
-            assert!(!SkillExecutor::is_allowed_shell_command(cmd));
-        }
-    }
-
     #[test]
     fn normalize_absolute_path_collapses_dot_segments() {
         let normalized = SkillExecutor::normalize_absolute_path(Path::new(
@@ -1,6 +1,8 @@
+use crate::services::memory::ChunkStrategy;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::collections::HashMap;
 
 #[derive(JsonSchema, Serialize, Deserialize)]
 pub struct ReadFileArgs {
@@ -14,6 +16,12 @@ pub struct IngestDocumentArgs {
     pub path: String,
     /// Optional tags to attach to the ingested memory
     pub tags: Option<Vec<String>>,
+    /// How to split the document; picked from the file extension by default
+    pub strategy: Option<ChunkStrategy>,
+    /// Maximum characters per chunk (default 1500)
+    pub chunk_chars: Option<usize>,
+    /// Characters of trailing context repeated at the start of the next chunk (default 200)
+    pub overlap_chars: Option<usize>,
 }
 
 #[derive(JsonSchema, Serialize, Deserialize)]
@@ -30,6 +38,32 @@ pub struct WriteFileArgs {
     pub content: String,
 }
 
+#[derive(JsonSchema, Serialize, Deserialize)]
+pub struct EditFileArgs {
+    /// The path of the file to edit
+    pub path: String,
+    /// Search/replace blocks, applied in order. All must match or nothing is written.
+    pub edits: Vec<SearchReplaceEdit>,
+}
+
+#[derive(JsonSchema, Serialize, Deserialize)]
+pub struct SearchReplaceEdit {
+    /// Exact text to find, including indentation. Must match exactly once unless replace_all is set
+    pub search: String,
+    /// Text to put in its place
+    pub replace: String,
+    /// Replace every occurrence instead of requiring a unique match
+    pub replace_all: Option<bool>,
+}
+
+#[derive(JsonSchema, Serialize, Deserialize)]
+pub struct ApplyPatchArgs {
+    /// Unified diff (as produced by `diff -u` or `git diff`), possibly touching several files
+    pub patch: String,
+    /// Target file for a patch without ---/+++ headers
+    pub path: Option<String>,
+}
+
 #[derive(JsonSchema, Serialize, Deserialize)]
 pub struct ListFilesArgs {
     /// The directory path to list
@@ -92,6 +126,16 @@ pub struct SearchFilesArgs {
     pub max_files: Option<usize>,
 }
 
+#[derive(JsonSchema, Serialize, Deserialize)]
+pub struct SemanticSearchWorkspaceArgs {
+    /// Natural-language description of the code or text to find
+    pub query: String,
+    /// Only return results under this path (default: all allowed paths)
+    pub path: Option<String>,
+    /// Maximum number of results (default: 8, max: 25)
+    pub limit: Option<usize>,
+}
+
 #[derive(JsonSchema, Serialize, Deserialize)]
 pub struct ExecuteCommandArgs {
     /// The command to execute (e.g., npm, cargo, git)
@@ -100,6 +144,47 @@ pub struct ExecuteCommandArgs {
     pub args: Vec<String>,
     /// Optional timeout in milliseconds (default: 120000, max: 600000)
     pub timeout_ms: Option<u64>,
+    /// Environment variables to set; only names in the command policy's env allowlist are accepted
+    pub env: Option<HashMap<String, String>>,
+}
+
+#[derive(JsonSchema, Serialize, Deserialize)]
+pub struct StartProcessArgs {
+    /// The command to start (e.g., npm, cargo)
+    pub command: String,
+    /// Arguments for the command
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// Environment variables to set; only names in the command policy's env allowlist are accepted
+    pub env: Option<HashMap<String, String>>,
+}
+
+#[derive(JsonSchema, Serialize, Deserialize)]
+pub struct ReadProcessOutputArgs {
+    /// Process ID returned by start_process
+    pub process_id: String,
+    /// `next_cursor` from the previous read (default: 0, from the start)
+    pub cursor: Option<u64>,
+    /// Maximum bytes to return (default: 16384, max: 49152)
+    pub max_bytes: Option<usize>,
+    /// Wait up to this long for new output while the process runs (default: 0, max: 30000)
+    pub wait_ms: Option<u64>,
+}
+
+#[derive(JsonSchema, Serialize, Deserialize)]
+pub struct WriteProcessStdinArgs {
+    /// Process ID returned by start_process
+    pub process_id: String,
+    /// Text to write; include a trailing newline to submit a line
+    pub input: String,
+    /// Close stdin after writing (sends EOF)
+    pub close_stdin: Option<bool>,
+}
+
+#[derive(JsonSchema, Serialize, Deserialize)]
+pub struct StopProcessArgs {
+    /// Process ID returned by start_process
+    pub process_id: String,
 }
 
 #[derive(JsonSchema, Serialize, Deserialize)]
@@ -249,3 +334,37 @@ pub struct ListFilesDetailedArgs {
     /// Maximum number of entries to return (default 200, max 2000)
     pub limit: Option<usize>,
 }
+
+#[derive(JsonSchema, Serialize, Deserialize)]
+pub struct RememberFactArgs {
+    /// The entity the fact is about (a person, project, service, ...)
+    pub entity: String,
+    /// Kind of entity, e.g. "person" or "project"
+    pub entity_type: Option<String>,
+    /// Attribute name, e.g. "role" or "deploy_target". Omit to store a free-form note.
+    pub attribute: Option<String>,
+    /// Attribute value or note text
+    pub value: Option<String>,
+    /// Relation name to another entity, e.g. "maintains" or "depends_on"
+    pub relation: Option<String>,
+    /// The other entity of the relation
+    pub related_entity: Option<String>,
+    /// How sure you are, from 0.0 to 1.0 (default 0.7)
+    pub confidence: Option<f32>,
+}
+
+#[derive(JsonSchema, Serialize, Deserialize)]
+pub struct RecallEntityArgs {
+    /// The entity to look up
+    pub entity: String,
+}
+
+#[derive(JsonSchema, Serialize, Deserialize)]
+pub struct ListRelatedArgs {
+    /// The entity whose relations to list
+    pub entity: String,
+    /// Only list relations with this name
+    pub relation: Option<String>,
+    /// Maximum number of relations to return (default 20, max 100)
+    pub limit: Option<usize>,
+}
@@ -0,0 +1,465 @@
+//! Text transforms behind the `edit_file` and `apply_patch` tools.
+//!
+//! Nothing here touches the filesystem: callers read every target, run the
+//! transform, and only write once all of it succeeded, so a mismatch leaves
+//! the workspace untouched.
+
+use super::args::SearchReplaceEdit;
+
+/// Apply search/replace blocks in order. Fails on the first block that does
+/// not match exactly once (or at least once with `replace_all`).
+pub(super) fn apply_search_replace(
+    content: &str,
+    edits: &[SearchReplaceEdit],
+) -> Result<String, String> {
+    if edits.is_empty() {
+        return Err("edits cannot be empty".to_string());
+    }
+
+    let (mut text, crlf) = normalize_line_endings(content);
+    for (index, edit) in edits.iter().enumerate() {
+        let search = edit.search.replace("\r\n", "\n");
+        let replace = edit.replace.replace("\r\n", "\n");
+        if search.is_empty() {
+            return Err(format!("Edit {}: search text is empty", index + 1));
+        }
+
+        match text.matches(search.as_str()).count() {
+            0 => {
+                return Err(format!(
+                    "Edit {}: search text not found. {}",
+                    index + 1,
+                    closest_line_hint(&text, &search)
+                ))
+            }
+            1 => text = text.replacen(search.as_str(), &replace, 1),
+            _ if edit.replace_all.unwrap_or(false) => {
+                text = text.replace(search.as_str(), &replace)
+            }
+            count => {
+                return Err(format!(
+                    "Edit {}: search text matches {} locations. Include more surrounding lines to make it unique, or set replace_all.",
+                    index + 1,
+                    count
+                ))
+            }
+        }
+    }
+
+    Ok(restore_line_endings(text, crlf))
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(super) enum HunkLine {
+    Context(String),
+    Remove(String),
+    Add(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(super) struct Hunk {
+    pub header: String,
+    pub old_start: usize,
+    pub old_count: usize,
+    pub lines: Vec<HunkLine>,
+}
+
+/// One file's worth of a unified diff. A `None` path stands for `/dev/null`.
+#[derive(Debug, Clone, PartialEq)]
+pub(super) struct FilePatch {
+    pub old_path: Option<String>,
+    pub new_path: Option<String>,
+    pub hunks: Vec<Hunk>,
+}
+
+/// Parse a unified diff. Anything outside `---`/`+++` headers and hunks
+/// (`diff --git`, `index`, prose) is ignored. A patch made only of hunks is
+/// accepted as a single file with no paths; the caller supplies the target.
+pub(super) fn parse_unified_diff(patch: &str) -> Result<Vec<FilePatch>, String> {
+    let (patch, _) = normalize_line_endings(patch);
+    let lines: Vec<&str> = patch.lines().collect();
+    let mut files: Vec<FilePatch> = Vec::new();
+    let mut i = 0;
+
+    while i < lines.len() {
+        let line = lines[i];
+        if let Some(old) = line.strip_prefix("--- ") {
+            let new = lines
+                .get(i + 1)
+                .and_then(|next| next.strip_prefix("+++ "))
+                .ok_or_else(|| format!("Line {}: '---' header without a following '+++'", i + 1))?;
+            files.push(FilePatch {
+                old_path: parse_header_path(old),
+                new_path: parse_header_path(new),
+                hunks: Vec::new(),
+            });
+            i += 2;
+        } else if line.starts_with("@@") {
+            if files.is_empty() {
+                files.push(FilePatch {
+                    old_path: None,
+                    new_path: None,
+                    hunks: Vec::new(),
+                });
+            }
+            let (hunk, next) = parse_hunk(&lines, i)?;
+            if let Some(file) = files.last_mut() {
+                file.hunks.push(hunk);
+            }
+            i = next;
+        } else {
+            i += 1;
+        }
+    }
+
+    if files.is_empty() {
+        return Err("Patch contains no file headers or hunks".to_string());
+    }
+    if let Some(file) = files.iter().find(|file| file.hunks.is_empty()) {
+        return Err(format!(
+            "Patch for '{}' contains no hunks",
+            file.new_path
+                .as_deref()
+                .or(file.old_path.as_deref())
+                .unwrap_or("<unnamed>")
+        ));
+    }
+    Ok(files)
+}
+
+/// Apply hunks to `content` (`None` for a file being created). Each hunk is
+/// located near its stated line number; context lines must match, ignoring
+/// trailing whitespace.
+pub(super) fn apply_hunks(content: Option<&str>, hunks: &[Hunk]) -> Result<String, String> {
+    let (text, crlf) = normalize_line_endings(content.unwrap_or(""));
+    let had_trailing_newline = text.is_empty() || text.ends_with('\n');
+    let source: Vec<&str> = if text.is_empty() {
+        Vec::new()
+    } else {
+        text.strip_suffix('\n')
+            .unwrap_or(&text)
+            .split('\n')
+            .collect()
+    };
+
+    let mut output: Vec<String> = Vec::with_capacity(source.len());
+    let mut cursor = 0usize;
+
+    for (index, hunk) in hunks.iter().enumerate() {
+        let old_block: Vec<&str> = hunk
+            .lines
+            .iter()
+            .filter_map(|line| match line {
+                HunkLine::Context(text) | HunkLine::Remove(text) => Some(text.as_str()),
+                HunkLine::Add(_) => None,
+            })
+            .collect();
+
+        // `-l,0` inserts after line l; otherwise the block starts at line l.
+        let expected = if hunk.old_count == 0 {
+            hunk.old_start
+        } else {
+            hunk.old_start.saturating_sub(1)
+        };
+
+        let Some(position) = locate_block(&source, &old_block, expected, cursor) else {
+            return Err(hunk_mismatch_error(
+                index, hunk, &source, &old_block, expected,
+            ));
+        };
+
+        output.extend(source[cursor..position].iter().map(|line| line.to_string()));
+        let mut old_offset = 0usize;
+        for line in &hunk.lines {
+            match line {
+                HunkLine::Context(_) => {
+                    // Keep the file's own text in case only trailing whitespace differed.
+                    output.push(source[position + old_offset].to_string());
+                    old_offset += 1;
+                }
+                HunkLine::Remove(_) => old_offset += 1,
+                HunkLine::Add(text) => output.push(text.clone()),
+            }
+        }
+        cursor = position + old_block.len();
+    }
+    output.extend(source[cursor..].iter().map(|line| line.to_string()));
+
+    let mut patched = output.join("\n");
+    if !output.is_empty() && had_trailing_newline {
+        patched.push('\n');
+    }
+    Ok(restore_line_endings(patched, crlf))
+}
+
+fn parse_header_path(raw: &str) -> Option<String> {
+    // Drop trailing timestamps (`--- a/file\t2024-01-01 ...`).
+    let path = raw.split('\t').next().unwrap_or(raw).trim();
+    if path == "/dev/null" || path.is_empty() {
+        return None;
+    }
+    let path = path
+        .strip_prefix("a/")
+        .or_else(|| path.strip_prefix("b/"))
+        .unwrap_or(path);
+    Some(path.to_string())
+}
+
+fn parse_hunk(lines: &[&str], start: usize) -> Result<(Hunk, usize), String> {
+    let header = lines[start];
+    let (old_start, old_count, new_count) = parse_hunk_header(header)
+        .ok_or_else(|| format!("Line {}: malformed hunk header '{}'", start + 1, header))?;
+
+    let mut hunk_lines = Vec::new();
+    let (mut old_seen, mut new_seen) = (0usize, 0usize);
+    let mut i = start + 1;
+
+    while (old_seen < old_count || new_seen < new_count) && i < lines.len() {
+        let line = lines[i];
+        if let Some(text) = line.strip_prefix('+') {
+            hunk_lines.push(HunkLine::Add(text.to_string()));
+            new_seen += 1;
+        } else if let Some(text) = line.strip_prefix('-') {
+            hunk_lines.push(HunkLine::Remove(text.to_string()));
+            old_seen += 1;
+        } else if line.starts_with('\\') {
+            // "\ No newline at end of file"
+        } else {
+            // Editors often strip the leading space from blank context lines.
+            let text = line.strip_prefix(' ').unwrap_or(line);
+            hunk_lines.push(HunkLine::Context(text.to_string()));
+            old_seen += 1;
+            new_seen += 1;
+        }
+        i += 1;
+    }
+
+    if old_seen != old_count || new_seen != new_count {
+        return Err(format!(
+            "Hunk '{}' is truncated: expected {} old / {} new lines, found {} / {}",
+            header, old_count, new_count, old_seen, new_seen
+        ));
+    }
+    while lines.get(i).is_some_and(|line| line.starts_with('\\')) {
+        i += 1;
+    }
+
+    Ok((
+        Hunk {
+            header: header.to_string(),
+            old_start,
+            old_count,
+            lines: hunk_lines,
+        },
+        i,
+    ))
+}
+
+/// Parse `@@ -l[,s] +l[,s] @@`, returning (old_start, old_count, new_count).
+fn parse_hunk_header(header: &str) -> Option<(usize, usize, usize)> {
+    let body = header.strip_prefix("@@ ")?;
+    let body = &body[..body.find(" @@")?];
+    let mut parts = body.split_whitespace();
+    let (old_start, old_count) = parse_range(parts.next()?.strip_prefix('-')?)?;
+    let (_, new_count) = parse_range(parts.next()?.strip_prefix('+')?)?;
+    Some((old_start, old_count, new_count))
+}
+
+fn parse_range(range: &str) -> Option<(usize, usize)> {
+    match range.split_once(',') {
+        Some((start, count)) => Some((start.parse().ok()?, count.parse().ok()?)),
+        None => Some((range.parse().ok()?, 1)),
+    }
+}
+
+/// Find `block` in `source` at or after `min`, preferring the position closest
+/// to `expected`. Exact matches win over trailing-whitespace-insensitive ones.
+fn locate_block(source: &[&str], block: &[&str], expected: usize, min: usize) -> Option<usize> {
+    if block.is_empty() {
+        return Some(expected.clamp(min, source.len()));
+    }
+    if block.len() > source.len() {
+        return None;
+    }
+    let last = source.len() - block.len();
+    if min > last {
+        return None;
+    }
+
+    let candidates = || {
+        let expected = expected.clamp(min, last);
+        (0..=last.saturating_sub(min)).flat_map(move |distance| {
+            let below = expected.checked_sub(distance).filter(|p| *p >= min);
+            let above = Some(expected + distance).filter(|p| *p <= last && distance > 0);
+            below.into_iter().chain(above)
+        })
+    };
+
+    let exact = |p: usize| source[p..p + block.len()] == *block;
+    let loose = |p: usize| {
+        source[p..p + block.len()]
+            .iter()
+            .zip(block)
+            .all(|(a, b)| a.trim_end() == b.trim_end())
+    };
+
+    candidates()
+        .find(|p| exact(*p))
+        .or_else(|| candidates().find(|p| loose(*p)))
+}
+
+fn hunk_mismatch_error(
+    index: usize,
+    hunk: &Hunk,
+    source: &[&str],
+    old_block: &[&str],
+    expected: usize,
+) -> String {
+    let start = expected.min(source.len());
+    let end = (start + old_block.len().max(1)).min(source.len());
+    let actual = if start < end {
+        source[start..end].join("\n")
+    } else {
+        "<end of file>".to_string()
+    };
+    format!(
+        "Hunk {} ({}) does not apply: context not found in file.\nExpected:\n{}\nFound at line {}:\n{}\nRe-read the file and regenerate the patch.",
+        index + 1,
+        hunk.header,
+        old_block.join("\n"),
+        start + 1,
+        actual
+    )
+}
+
+fn closest_line_hint(text: &str, search: &str) -> String {
+    let Some(first) = search.lines().map(str::trim).find(|line| !line.is_empty()) else {
+        return "Re-read the file; it may have changed.".to_string();
+    };
+    match text
+        .lines()
+        .position(|line| line.trim() == first)
+    {
+        Some(line) => format!(
+            "The first search line appears at line {} with different whitespace or following lines; copy the text exactly from read_file.",
+            line + 1
+        ),
+        None => "Re-read the file; it may have changed.".to_string(),
+    }
+}
+
+fn normalize_line_endings(content: &str) -> (String, bool) {
+    if content.contains("\r\n") {
+        (content.replace("\r\n", "\n"), true)
+    } else {
+        (content.to_string(), false)
+    }
+}
+
+fn restore_line_endings(content: String, crlf: bool) -> String {
+    if crlf {
+        content.replace('\n', "\r\n")
+    } else {
+        content
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn edit(search: &str, replace: &str) -> SearchReplaceEdit {
+        SearchReplaceEdit {
+            search: search.to_string(),
+            replace: replace.to_string(),
+            replace_all: None,
+        }
+    }
+
+    #[test]
+    fn search_replace_applies_blocks_in_order() {
+        let content = "fn a() {}\nfn b() {}\n";
+        let result = apply_search_replace(
+            content,
+            &[edit("fn a()", "fn alpha()"), edit("alpha", "omega")],
+        )
+        .unwrap();
+        assert_eq!(result, "fn omega() {}\nfn b() {}\n");
+    }
+
+    #[test]
+    fn search_replace_rejects_missing_and_ambiguous_matches() {
+        let content = "let x = 1;\nlet x = 1;\n";
+
+        let ambiguous =
+            apply_search_replace(content, &[edit("let x = 1;", "let y = 2;")]).unwrap_err();
+        assert!(ambiguous.contains("matches 2 locations"));
+
+        let mut all = edit("let x = 1;", "let y = 2;");
+        all.replace_all = Some(true);
+        assert_eq!(
+            apply_search_replace(content, &[all]).unwrap(),
+            "let y = 2;\nlet y = 2;\n"
+        );
+
+        let missing = apply_search_replace(
+            content,
+            &[edit("let x = 1;\nlet x = 1;", "ok"), edit("nope", "never")],
+        )
+        .unwrap_err();
+        assert!(missing.starts_with("Edit 2: search text not found"));
+    }
+
+    #[test]
+    fn search_replace_preserves_crlf() {
+        let content = "one\r\ntwo\r\n";
+        let result = apply_search_replace(content, &[edit("one\ntwo", "uno\ndos")]).unwrap();
+        assert_eq!(result, "uno\r\ndos\r\n");
+    }
+
+    #[test]
+    fn parses_multi_file_git_diff() {
+        let patch = "diff --git a/src/a.rs b/src/a.rs\nindex 111..222 100644\n--- a/src/a.rs\n+++ b/src/a.rs\n@@ -1,2 +1,2 @@\n-old\n+new\n ctx\n--- /dev/null\n+++ b/src/new.rs\n@@ -0,0 +1 @@\n+created\n";
+        let files = parse_unified_diff(patch).unwrap();
+        assert_eq!(files.len(), 2);
+        assert_eq!(files[0].old_path.as_deref(), Some("src/a.rs"));
+        assert_eq!(files[0].hunks[0].lines.len(), 3);
+        assert_eq!(files[1].old_path, None);
+        assert_eq!(files[1].new_path.as_deref(), Some("src/new.rs"));
+        assert_eq!(
+            files[1].hunks[0].lines,
+            vec![HunkLine::Add("created".to_string())]
+        );
+    }
+
+    #[test]
+    fn applies_hunks_with_drifted_line_numbers() {
+        let content = "header\nextra\na\nb\nc\nd\n";
+        // The hunk claims line 2, but the block now starts at line 3.
+        let patch = "--- a/f\n+++ b/f\n@@ -2,3 +2,3 @@\n a\n-b\n+B\n c\n@@ -5,1 +5,2 @@\n d\n+e\n";
+        let files = parse_unified_diff(patch).unwrap();
+        let result = apply_hunks(Some(content), &files[0].hunks).unwrap();
+        assert_eq!(result, "header\nextra\na\nB\nc\nd\ne\n");
+    }
+
+    #[test]
+    fn creates_new_file_from_dev_null() {
+        let files =
+            parse_unified_diff("--- /dev/null\n+++ b/x\n@@ -0,0 +1,2 @@\n+one\n+two\n").unwrap();
+        assert_eq!(apply_hunks(None, &files[0].hunks).unwrap(), "one\ntwo\n");
+    }
+
+    #[test]
+    fn mismatched_hunk_reports_context() {
+        let files = parse_unified_diff("@@ -1,2 +1,2 @@\n alpha\n-beta\n+gamma\n").unwrap();
+        let err = apply_hunks(Some("alpha\ndelta\n"), &files[0].hunks).unwrap_err();
+        assert!(err.starts_with("Hunk 1 (@@ -1,2 +1,2 @@) does not apply"));
+        assert!(err.contains("Found at line 1:\nalpha\ndelta"));
+    }
+
+    #[test]
+    fn truncated_hunk_is_rejected() {
+        let err = parse_unified_diff("--- a/f\n+++ b/f\n@@ -1,3 +1,3 @@\n a\n-b\n").unwrap_err();
+        assert!(err.contains("truncated"));
+    }
+}
@@ -0,0 +1,121 @@
+use super::args::*;
+use super::SkillExecutor;
+use crate::ai::agent::entity_graph::FactInput;
+use crate::models::neural::CommandResult;
+use serde_json::Value;
+
+const DEFAULT_FACT_CONFIDENCE: f32 = 0.7;
+
+impl SkillExecutor {
+    /// Entity knowledge graph tools, scoped to the command's workspace.
+    pub(super) async fn execute_entity_graph(
+        &self,
+        workspace_id: &str,
+        method: &str,
+        params: &Value,
+    ) -> CommandResult {
+        let Some(graph) = self.entity_graph.read().await.clone() else {
+            return self.error("Entity graph not initialized");
+        };
+
+        match method {
+            "remember_fact" => {
+                let args: RememberFactArgs = match serde_json::from_value(params.clone()) {
+                    Ok(a) => a,
+                    Err(e) => return self.error(&format!("Invalid parameters: {}", e)),
+                };
+                let confidence = args
+                    .confidence
+                    .unwrap_or(DEFAULT_FACT_CONFIDENCE)
+                    .clamp(0.0, 1.0);
+                let relation = match (args.relation.as_deref(), args.related_entity.as_deref()) {
+                    (Some(relation), Some(object)) => Some((relation, object)),
+                    (None, None) => None,
+                    _ => {
+                        return self.error("'relation' and 'related_entity' must be given together")
+                    }
+                };
+                if args.value.is_none() && relation.is_none() {
+                    return self.error("Provide a 'value' or a 'relation' with 'related_entity'");
+                }
+
+                let mut output = serde_json::Map::new();
+                if let Some(value) = args.value {
+                    let fact = FactInput {
+                        entity: args.entity.clone(),
+                        attribute: args.attribute.unwrap_or_default(),
+                        value,
+                        entity_type: args.entity_type,
+                        confidence,
+                        source: Some("agent:remember_fact".to_string()),
+                    };
+                    match graph.remember_fact(workspace_id, fact).await {
+                        Ok(outcome) => {
+                            output.insert("fact".to_string(), serde_json::json!(outcome));
+                        }
+                        Err(e) => return self.error(&format!("Failed to remember fact: {}", e)),
+                    }
+                }
+                if let Some((relation, object)) = relation {
+                    match graph
+                        .relate(
+                            workspace_id,
+                            &args.entity,
+                            relation,
+                            object,
+                            confidence,
+                            Some("agent:remember_fact"),
+                        )
+                        .await
+                    {
+                        Ok(relation) => {
+                            output.insert("relation".to_string(), serde_json::json!(relation));
+                        }
+                        Err(e) => {
+                            return self.error(&format!("Failed to remember relation: {}", e))
+                        }
+                    }
+                }
+                self.json_result(&Value::Object(output))
+            }
+            "recall_entity" => {
+                let args: RecallEntityArgs = match serde_json::from_value(params.clone()) {
+                    Ok(a) => a,
+                    Err(e) => return self.error(&format!("Invalid parameters: {}", e)),
+                };
+                match graph.recall_entity(workspace_id, &args.entity).await {
+                    Ok(Some(profile)) => self.json_result(&serde_json::json!(profile)),
+                    Ok(None) => self.json_result(&serde_json::json!({
+                        "entity": args.entity,
+                        "known": false,
+                    })),
+                    Err(e) => self.error(&format!("Failed to recall entity: {}", e)),
+                }
+            }
+            "list_related" => {
+                let args: ListRelatedArgs = match serde_json::from_value(params.clone()) {
+                    Ok(a) => a,
+                    Err(e) => return self.error(&format!("Invalid parameters: {}", e)),
+                };
+                let limit = args.limit.unwrap_or(20).clamp(1, 100);
+                match graph
+                    .list_related(workspace_id, &args.entity, args.relation.as_deref(), limit)
+                    .await
+                {
+                    Ok(relations) => self.json_result(&serde_json::json!(relations)),
+                    Err(e) => self.error(&format!("Failed to list relations: {}", e)),
+                }
+            }
+            _ => self.error(&format!("Unknown entity graph method: {}", method)),
+        }
+    }
+
+    pub(super) fn json_result(&self, value: &Value) -> CommandResult {
+        CommandResult {
+            success: true,
+            output: Some(value.to_string()),
+            error: None,
+            exit_code: Some(0),
+        }
+    }
+}
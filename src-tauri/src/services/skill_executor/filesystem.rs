@@ -1,6 +1,8 @@
 use super::args::*;
-use super::SkillExecutor;
+use super::edit::{apply_hunks, apply_search_replace, parse_unified_diff};
+use super::{truncate_output, SkillExecutor};
 use crate::models::neural::CommandResult;
+use crate::services::memory::ChunkOptions;
 use base64::prelude::*;
 use serde_json::Value;
 use std::path::{Component, Path, PathBuf};
@@ -163,6 +165,7 @@ impl SkillExecutor {
         params: &Option<Value>,
         allowed_paths: &[String],
         blocked_paths: &[String],
+        run_id: Option<&str>,
     ) -> CommandResult {
         let params = match params {
             Some(p) => p,
@@ -198,28 +201,45 @@ impl SkillExecutor {
                 self.handle_search_files(workspace_id, params, allowed_paths, blocked_paths)
                     .await
             }
+            "semantic_search_workspace" => {
+                self.handle_semantic_search_workspace(
+                    workspace_id,
+                    params,
+                    allowed_paths,
+                    blocked_paths,
+                )
+                .await
+            }
             "read_file_chunk" => {
                 self.handle_read_file_chunk(workspace_id, params, allowed_paths, blocked_paths)
                     .await
             }
             "write_file" => {
-                self.handle_write_file(workspace_id, params, allowed_paths, blocked_paths)
+                self.handle_write_file(workspace_id, params, allowed_paths, blocked_paths, run_id)
                     .await
             }
             "append_file" => {
-                self.handle_append_file(workspace_id, params, allowed_paths, blocked_paths)
+                self.handle_append_file(workspace_id, params, allowed_paths, blocked_paths, run_id)
+                    .await
+            }
+            "edit_file" => {
+                self.handle_edit_file(workspace_id, params, allowed_paths, blocked_paths, run_id)
+                    .await
+            }
+            "apply_patch" => {
+                self.handle_apply_patch(workspace_id, params, allowed_paths, blocked_paths, run_id)
                     .await
             }
             "mkdir" => {
-                self.handle_make_dir(workspace_id, params, allowed_paths, blocked_paths)
+                self.handle_make_dir(workspace_id, params, allowed_paths, blocked_paths, run_id)
                     .await
             }
             "delete_file" => {
-                self.handle_delete_file(workspace_id, params, allowed_paths, blocked_paths)
+                self.handle_delete_file(workspace_id, params, allowed_paths, blocked_paths, run_id)
                     .await
             }
             "move_file" => {
-                self.handle_move_file(workspace_id, params, allowed_paths, blocked_paths)
+                self.handle_move_file(workspace_id, params, allowed_paths, blocked_paths, run_id)
                     .await
             }
             "ingest_document" => {
@@ -636,6 +656,175 @@ impl SkillExecutor {
         }
     }
 
+    /// Roots the workspace index covers: the workspace's allowed paths, or
+    /// the payload's when the workspace is not stored locally.
+    fn workspace_index_roots(&self, workspace_id: &str, allowed_paths: &[String]) -> Vec<PathBuf> {
+        let allowed = match self.workspace_manager.load_workspace(workspace_id) {
+            Ok(ws) => ws.allowed_paths,
+            Err(_) => allowed_paths.to_vec(),
+        };
+        allowed
+            .iter()
+            .filter_map(|p| Self::normalize_absolute_path(Path::new(p)).ok())
+            .collect()
+    }
+
+    /// Blocked paths as absolute paths, resolving relative entries against
+    /// each root the way [`is_path_blocked`](Self::is_path_blocked) does.
+    pub(crate) fn absolute_blocked_paths(
+        blocked_paths: &[String],
+        roots: &[PathBuf],
+    ) -> Vec<PathBuf> {
+        let mut absolute = Vec::new();
+        for blocked in blocked_paths {
+            let blocked_path = Path::new(blocked);
+            if blocked_path.is_absolute() {
+                absolute.extend(Self::normalize_absolute_path(blocked_path).ok());
+                continue;
+            }
+            for root in roots {
+                absolute.extend(Self::normalize_absolute_path(&root.join(blocked_path)).ok());
+            }
+        }
+        absolute
+    }
+
+    /// Whether the user opted this workspace into the background file index.
+    async fn is_workspace_indexed(&self, workspace_id: &str) -> bool {
+        let workspace_id = workspace_id.to_string();
+        self.read_settings(move |settings| settings.is_workspace_indexed(&workspace_id))
+            .await
+    }
+
+    /// Start indexing the workspace in the background so the first
+    /// `semantic_search_workspace` call of a run does not pay for the walk.
+    /// Does nothing unless indexing is enabled for the workspace.
+    pub async fn warm_workspace_index(
+        &self,
+        workspace_id: &str,
+        allowed_paths: &[String],
+        blocked_paths: &[String],
+    ) {
+        let Some(indexer) = self.workspace_index.read().await.clone() else {
+            return;
+        };
+        if !self.is_workspace_indexed(workspace_id).await {
+            return;
+        }
+        let roots = self.workspace_index_roots(workspace_id, allowed_paths);
+        indexer
+            .ensure_roots(&roots, &Self::absolute_blocked_paths(blocked_paths, &roots))
+            .await;
+    }
+
+    /// Stop indexing the workspace and delete its index; called when the
+    /// user opts it out.
+    pub async fn drop_workspace_index(&self, workspace_id: &str) {
+        let Some(indexer) = self.workspace_index.read().await.clone() else {
+            return;
+        };
+        let roots = self.workspace_index_roots(workspace_id, &[]);
+        indexer.remove_roots(&roots).await;
+    }
+
+    async fn handle_semantic_search_workspace(
+        &self,
+        workspace_id: String,
+        params: &Value,
+        allowed_paths: &[String],
+        blocked_paths: &[String],
+    ) -> CommandResult {
+        let args: SemanticSearchWorkspaceArgs = match serde_json::from_value(params.clone()) {
+            Ok(a) => a,
+            Err(e) => return self.error(&format!("Invalid parameters: {}", e)),
+        };
+        let Some(indexer) = self.workspace_index.read().await.clone() else {
+            return self.error("Workspace index not configured; use search_files instead");
+        };
+        if !self.is_workspace_indexed(&workspace_id).await {
+            return self
+                .error("Indexing is not enabled for this workspace; use search_files instead");
+        }
+
+        let roots = self.workspace_index_roots(&workspace_id, allowed_paths);
+        if roots.is_empty() {
+            return self.error(
+                "No allowed paths configured for this workspace. Configure allowed paths before searching.",
+            );
+        }
+        let scope = match args.path.as_deref() {
+            Some(path) => match self
+                .resolve_path(workspace_id, path, allowed_paths, blocked_paths)
+                .await
+            {
+                Ok(p) => Some(p),
+                Err(e) => return self.error(&e),
+            },
+            None => None,
+        };
+
+        indexer
+            .ensure_roots(&roots, &Self::absolute_blocked_paths(blocked_paths, &roots))
+            .await;
+        let limit = args.limit.unwrap_or(8).clamp(1, 25);
+        // Over-fetch so scope and Airlock filtering still leave enough hits.
+        let hits = match indexer.search(&roots, &args.query, limit * 3).await {
+            Ok(hits) => hits,
+            Err(e) => return self.error(&format!("Workspace search failed: {}", e)),
+        };
+        let root_strings = roots
+            .iter()
+            .map(|r| r.to_string_lossy().to_string())
+            .collect::<Vec<_>>();
+        let hits = hits
+            .into_iter()
+            .filter(|hit| {
+                let path = Path::new(&hit.path);
+                scope.as_ref().is_none_or(|s| path.starts_with(s))
+                    && !Self::is_path_blocked(path, blocked_paths, &root_strings)
+            })
+            .take(limit)
+            .collect::<Vec<_>>();
+
+        let mut output = if hits.is_empty() {
+            format!("No indexed content matched \"{}\".", args.query)
+        } else {
+            let mut out = format!(
+                "Found {} relevant locations for \"{}\":\n",
+                hits.len(),
+                args.query
+            );
+            for (idx, hit) in hits.iter().enumerate() {
+                out.push_str(&format!(
+                    "\n{}. {}:{}-{}\n{}\n",
+                    idx + 1,
+                    hit.path,
+                    hit.start_line,
+                    hit.end_line,
+                    hit.snippet.trim_end()
+                ));
+            }
+            out
+        };
+        for root in &roots {
+            if let Some(status) = indexer.status(root).await {
+                if status.scanning {
+                    output.push_str(&format!(
+                        "\nNote: {} is still being indexed ({} files so far); results may be incomplete.",
+                        status.root, status.indexed_files
+                    ));
+                }
+            }
+        }
+
+        CommandResult {
+            success: true,
+            output: Some(truncate_output(&output)),
+            error: None,
+            exit_code: Some(0),
+        }
+    }
+
     async fn handle_file_exists(
         &self,
         workspace_id: String,
@@ -825,6 +1014,7 @@ impl SkillExecutor {
         params: &Value,
         allowed_paths: &[String],
         blocked_paths: &[String],
+        run_id: Option<&str>,
     ) -> CommandResult {
         let args: WriteFileArgs = match serde_json::from_value(params.clone()) {
             Ok(a) => a,
@@ -839,6 +1029,10 @@ impl SkillExecutor {
             Err(e) => return self.error(&e),
         };
 
+        if let Err(e) = self.checkpoint_before_mutation(run_id, &path).await {
+            return self.error(&e);
+        }
+
         if let Some(parent) = path.parent() {
             if let Err(e) = fs::create_dir_all(parent).await {
                 return self.error(&format!("Failed to create parent directories: {}", e));
@@ -862,6 +1056,7 @@ impl SkillExecutor {
         params: &Value,
         allowed_paths: &[String],
         blocked_paths: &[String],
+        run_id: Option<&str>,
     ) -> CommandResult {
         let args: WriteFileArgs = match serde_json::from_value(params.clone()) {
             Ok(a) => a,
@@ -876,6 +1071,10 @@ impl SkillExecutor {
             Err(e) => return self.error(&e),
         };
 
+        if let Err(e) = self.checkpoint_before_mutation(run_id, &path).await {
+            return self.error(&e);
+        }
+
         let file_res = fs::OpenOptions::new()
             .write(true)
             .append(true)
@@ -897,12 +1096,202 @@ impl SkillExecutor {
         }
     }
 
+    async fn handle_edit_file(
+        &self,
+        workspace_id: String,
+        params: &Value,
+        allowed_paths: &[String],
+        blocked_paths: &[String],
+        run_id: Option<&str>,
+    ) -> CommandResult {
+        let args: EditFileArgs = match serde_json::from_value(params.clone()) {
+            Ok(a) => a,
+            Err(e) => return self.error(&format!("Invalid parameters: {}", e)),
+        };
+
+        let path = match self
+            .resolve_path(workspace_id, &args.path, allowed_paths, blocked_paths)
+            .await
+        {
+            Ok(p) => p,
+            Err(e) => return self.error(&e),
+        };
+
+        let original = match fs::read_to_string(&path).await {
+            Ok(content) => content,
+            Err(e) => return self.error(&format!("Failed to read file: {}", e)),
+        };
+
+        let updated = match apply_search_replace(&original, &args.edits) {
+            Ok(content) => content,
+            Err(e) => return self.error(&format!("No changes written to {}: {}", args.path, e)),
+        };
+
+        if let Err(e) = self.checkpoint_before_mutation(run_id, &path).await {
+            return self.error(&e);
+        }
+
+        match Self::write_atomically(&path, &updated).await {
+            Ok(_) => CommandResult {
+                success: true,
+                output: Some(format!(
+                    "Applied {} edit(s) to {}",
+                    args.edits.len(),
+                    args.path
+                )),
+                error: None,
+                exit_code: Some(0),
+            },
+            Err(e) => self.error(&e),
+        }
+    }
+
+    /// Apply a unified diff. Every hunk of every file is checked before
+    /// anything is written, so a mismatch leaves the workspace untouched.
+    async fn handle_apply_patch(
+        &self,
+        workspace_id: String,
+        params: &Value,
+        allowed_paths: &[String],
+        blocked_paths: &[String],
+        run_id: Option<&str>,
+    ) -> CommandResult {
+        let args: ApplyPatchArgs = match serde_json::from_value(params.clone()) {
+            Ok(a) => a,
+            Err(e) => return self.error(&format!("Invalid parameters: {}", e)),
+        };
+
+        let file_patches = match parse_unified_diff(&args.patch) {
+            Ok(files) => files,
+            Err(e) => return self.error(&format!("Invalid patch: {}", e)),
+        };
+
+        let mut staged: Vec<(PathBuf, String, String)> = Vec::new();
+        for file_patch in &file_patches {
+            let target = match (&file_patch.old_path, &file_patch.new_path) {
+                (Some(_), None) => {
+                    return self.error("apply_patch cannot delete files; use delete_file instead");
+                }
+                (Some(old), Some(new)) if old != new => {
+                    return self.error(&format!(
+                        "apply_patch cannot rename '{}' to '{}'; use move_file first",
+                        old, new
+                    ));
+                }
+                (_, Some(new)) => new.clone(),
+                (None, None) => match &args.path {
+                    Some(path) if file_patches.len() == 1 => path.clone(),
+                    _ => {
+                        return self.error(
+                            "Patch has no ---/+++ headers; pass `path` for a single-file patch",
+                        );
+                    }
+                },
+            };
+
+            let path = match self
+                .resolve_path(workspace_id.clone(), &target, allowed_paths, blocked_paths)
+                .await
+            {
+                Ok(p) => p,
+                Err(e) => return self.error(&e),
+            };
+
+            let creating = file_patch.old_path.is_none() && file_patch.new_path.is_some();
+            let original = if creating {
+                if fs::metadata(&path).await.is_ok() {
+                    return self
+                        .error(&format!("Patch creates '{}' but it already exists", target));
+                }
+                None
+            } else {
+                match fs::read_to_string(&path).await {
+                    Ok(content) => Some(content),
+                    Err(e) => {
+                        return self.error(&format!("Failed to read '{}': {}", target, e));
+                    }
+                }
+            };
+
+            match apply_hunks(original.as_deref(), &file_patch.hunks) {
+                Ok(updated) => staged.push((path, target, updated)),
+                Err(e) => {
+                    return self.error(&format!(
+                        "No changes written. Patch for '{}' failed: {}",
+                        target, e
+                    ));
+                }
+            }
+        }
+
+        for (path, _, _) in &staged {
+            if let Err(e) = self.checkpoint_before_mutation(run_id, path).await {
+                return self.error(&e);
+            }
+        }
+
+        let mut written = Vec::new();
+        for (path, target, updated) in staged {
+            if let Some(parent) = path.parent() {
+                if let Err(e) = fs::create_dir_all(parent).await {
+                    return self.error(&format!("Failed to create parent directories: {}", e));
+                }
+            }
+            if let Err(e) = Self::write_atomically(&path, &updated).await {
+                return self.error(&format!(
+                    "{} (already patched: {})",
+                    e,
+                    if written.is_empty() {
+                        "none".to_string()
+                    } else {
+                        written.join(", ")
+                    }
+                ));
+            }
+            written.push(target);
+        }
+
+        CommandResult {
+            success: true,
+            output: Some(format!(
+                "Patched {} file(s): {}",
+                written.len(),
+                written.join(", ")
+            )),
+            error: None,
+            exit_code: Some(0),
+        }
+    }
+
+    /// Write through a sibling temp file and rename, so readers never observe
+    /// a half-written file. Existing permissions are carried over.
+    async fn write_atomically(path: &Path, content: &str) -> Result<(), String> {
+        let file_name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .ok_or_else(|| format!("Invalid file path '{}'", path.display()))?;
+        let temp_path = path.with_file_name(format!(".{}.rainy-edit.tmp", file_name));
+
+        fs::write(&temp_path, content)
+            .await
+            .map_err(|e| format!("Failed to write file: {}", e))?;
+        if let Ok(metadata) = fs::metadata(path).await {
+            let _ = fs::set_permissions(&temp_path, metadata.permissions()).await;
+        }
+        if let Err(e) = fs::rename(&temp_path, path).await {
+            let _ = fs::remove_file(&temp_path).await;
+            return Err(format!("Failed to replace file: {}", e));
+        }
+        Ok(())
+    }
+
     async fn handle_make_dir(
         &self,
         workspace_id: String,
         params: &Value,
         allowed_paths: &[String],
         blocked_paths: &[String],
+        run_id: Option<&str>,
     ) -> CommandResult {
         let args: MakeDirArgs = match serde_json::from_value(params.clone()) {
             Ok(a) => a,
@@ -917,6 +1306,14 @@ impl SkillExecutor {
             Err(e) => return self.error(&e),
         };
 
+        // An existing directory is left untouched, so only a new one needs a
+        // checkpoint entry, recorded as created so reverting removes it.
+        if !path.exists() {
+            if let Err(e) = self.checkpoint_before_mutation(run_id, &path).await {
+                return self.error(&e);
+            }
+        }
+
         match fs::create_dir_all(&path).await {
             Ok(_) => CommandResult {
                 success: true,
@@ -934,6 +1331,7 @@ impl SkillExecutor {
         params: &Value,
         allowed_paths: &[String],
         blocked_paths: &[String],
+        run_id: Option<&str>,
     ) -> CommandResult {
         let args: DeleteFileArgs = match serde_json::from_value(params.clone()) {
             Ok(a) => a,
@@ -948,6 +1346,10 @@ impl SkillExecutor {
             Err(e) => return self.error(&e),
         };
 
+        if let Err(e) = self.checkpoint_before_mutation(run_id, &path).await {
+            return self.error(&e);
+        }
+
         if path.is_dir() {
             match fs::remove_dir_all(&path).await {
                 Ok(_) => CommandResult {
@@ -977,6 +1379,7 @@ impl SkillExecutor {
         params: &Value,
         allowed_paths: &[String],
         blocked_paths: &[String],
+        run_id: Option<&str>,
     ) -> CommandResult {
         let args: MoveFileArgs = match serde_json::from_value(params.clone()) {
             Ok(a) => a,
@@ -1009,6 +1412,12 @@ impl SkillExecutor {
             Err(e) => return self.error(&e),
         };
 
+        for path in [&source, &destination] {
+            if let Err(e) = self.checkpoint_before_mutation(run_id, path).await {
+                return self.error(&e);
+            }
+        }
+
         if let Some(parent) = destination.parent() {
             if let Err(e) = fs::create_dir_all(parent).await {
                 return self.error(&format!(
@@ -1108,11 +1517,24 @@ impl SkillExecutor {
             return self.error("File is empty or contained no extractable text");
         }
 
+        let defaults = ChunkOptions::default();
+        let options = ChunkOptions {
+            strategy: args.strategy.unwrap_or(defaults.strategy),
+            max_chars: args.chunk_chars.unwrap_or(defaults.max_chars),
+            overlap_chars: args.overlap_chars.unwrap_or(defaults.overlap_chars),
+        };
+
         // Send to MemoryManager to chunk, embed, and put in the vault
         let lock = self.memory_manager.read().await;
         if let Some(mm) = lock.as_ref() {
             let result = match mm
-                .ingest_text_detailed(&workspace_id, &path.to_string_lossy(), &content, args.tags)
+                .ingest_text_detailed(
+                    &workspace_id,
+                    &path.to_string_lossy(),
+                    &content,
+                    args.tags,
+                    &options,
+                )
                 .await
             {
                 Ok(r) => r,
@@ -1140,3 +1562,158 @@ impl SkillExecutor {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::memory::MemoryManager;
+    use crate::services::settings::SettingsManager;
+    use crate::services::workspace_index::WorkspaceIndexer;
+    use crate::services::FileOperationEngine;
+    use std::sync::Arc;
+    use std::time::Duration;
+    use tokio::sync::Mutex;
+
+    async fn search(
+        executor: &SkillExecutor,
+        workspace_id: &str,
+        allowed_paths: &[String],
+        blocked_paths: &[String],
+        path: Option<&str>,
+    ) -> CommandResult {
+        let params = serde_json::json!({ "query": "tokenizer unicode", "path": path });
+        executor
+            .handle_semantic_search_workspace(
+                workspace_id.to_string(),
+                &params,
+                allowed_paths,
+                blocked_paths,
+            )
+            .await
+    }
+
+    async fn make_dir(
+        executor: &SkillExecutor,
+        allowed_paths: &[String],
+        path: &Path,
+        run_id: &str,
+    ) -> CommandResult {
+        let params = serde_json::json!({ "path": path.to_string_lossy() });
+        executor
+            .handle_make_dir(
+                "make-dir-test".to_string(),
+                &params,
+                allowed_paths,
+                &[],
+                Some(run_id),
+            )
+            .await
+    }
+
+    #[tokio::test]
+    async fn semantic_search_workspace_is_opt_in_and_filters_by_scope_and_blocked_paths() {
+        let dir = std::env::temp_dir().join(format!("semantic-search-{}", uuid::Uuid::new_v4()));
+        let root = dir.join("project");
+        for (path, body) in [
+            ("src/lexer.rs", "// tokenizer for unicode identifiers"),
+            ("docs/lexer.md", "How the tokenizer treats unicode input."),
+            ("private/notes.md", "Tokenizer unicode secrets."),
+        ] {
+            let path = root.join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, body).unwrap();
+        }
+
+        let executor = SkillExecutor::mock();
+        let settings = Arc::new(Mutex::new(SettingsManager::at_path(
+            dir.join("settings.json"),
+        )));
+        executor.set_settings_manager(settings.clone()).await;
+        let manager = Arc::new(MemoryManager::for_tests(dir.join("vault")).await);
+        let indexer = WorkspaceIndexer::new(manager, &dir);
+        executor.set_workspace_indexer(indexer.clone()).await;
+
+        let workspace_id = format!("semantic-search-{}", uuid::Uuid::new_v4());
+        let allowed = vec![root.to_string_lossy().to_string()];
+        let blocked = vec!["private".to_string()];
+
+        // Nothing is indexed until the workspace opts in.
+        executor
+            .warm_workspace_index(&workspace_id, &allowed, &blocked)
+            .await;
+        let refused = search(&executor, &workspace_id, &allowed, &blocked, None).await;
+        assert!(!refused.success);
+        assert!(indexer.status(&root).await.is_none());
+
+        settings
+            .lock()
+            .await
+            .set_workspace_indexed(&workspace_id, true)
+            .unwrap();
+        executor
+            .warm_workspace_index(&workspace_id, &allowed, &blocked)
+            .await;
+        let status = tokio::time::timeout(Duration::from_secs(30), async {
+            loop {
+                let status = indexer.status(&root).await.unwrap();
+                if !status.scanning {
+                    return status;
+                }
+                tokio::time::sleep(Duration::from_millis(20)).await;
+            }
+        })
+        .await
+        .unwrap();
+        assert_eq!(status.indexed_files, 2);
+
+        let all = search(&executor, &workspace_id, &allowed, &blocked, None).await;
+        let output = all.output.unwrap();
+        assert!(output.contains("src/lexer.rs"));
+        assert!(output.contains("docs/lexer.md"));
+        assert!(!output.contains("private"));
+
+        let scoped = search(&executor, &workspace_id, &allowed, &blocked, Some("src")).await;
+        let output = scoped.output.unwrap();
+        assert!(output.contains("src/lexer.rs"));
+        assert!(!output.contains("docs/lexer.md"));
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[tokio::test]
+    async fn make_dir_checkpoints_only_directories_it_creates() {
+        let data = tempfile::tempdir().unwrap();
+        let workspace = tempfile::tempdir().unwrap();
+        let existing = workspace.path().join("existing");
+        std::fs::create_dir_all(&existing).unwrap();
+        std::fs::write(existing.join("notes.txt"), "keep\n").unwrap();
+
+        let executor = SkillExecutor::mock();
+        let engine = Arc::new(FileOperationEngine::with_base_dir(
+            data.path().to_path_buf(),
+        ));
+        executor.set_file_operation_engine(engine.clone()).await;
+        let allowed = vec![workspace.path().to_string_lossy().to_string()];
+
+        // Re-creating an existing directory leaves nothing to revert.
+        let result = make_dir(&executor, &allowed, &existing, "run-existing").await;
+        assert!(result.success);
+        executor.finish_run_checkpoint("run-existing").await;
+        assert!(engine.preview_run_revert("run-existing").await.is_err());
+
+        let created = workspace.path().join("new/nested");
+        let result = make_dir(&executor, &allowed, &created, "run-new").await;
+        assert!(result.success);
+        executor.finish_run_checkpoint("run-new").await;
+        let preview = engine.preview_run_revert("run-new").await.unwrap();
+        assert_eq!(preview.changes.len(), 1);
+        assert_eq!(
+            preview.changes[0].path,
+            workspace.path().join("new").to_string_lossy()
+        );
+
+        engine.revert_run("run-new").await.unwrap();
+        assert!(!workspace.path().join("new").exists());
+        assert!(existing.join("notes.txt").exists());
+    }
+}
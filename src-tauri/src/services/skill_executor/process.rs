@@ -0,0 +1,129 @@
+use super::args::*;
+use super::SkillExecutor;
+use crate::models::neural::{CommandResult, RainyPayload};
+use serde_json::Value;
+use std::time::Duration;
+
+/// Tools served by the process manager rather than one-shot shell execution.
+pub(super) const PROCESS_TOOLS: &[&str] = &[
+    "start_process",
+    "read_process_output",
+    "write_process_stdin",
+    "list_processes",
+    "stop_process",
+];
+
+const MAX_READ_BYTES: usize = 48 * 1024;
+const MAX_READ_WAIT_MS: u64 = 30_000;
+
+impl SkillExecutor {
+    pub(super) async fn execute_process(
+        &self,
+        workspace_id: String,
+        method: &str,
+        payload: &RainyPayload,
+    ) -> CommandResult {
+        let Some(manager) = self.process_manager.read().await.clone() else {
+            return self.error("Process manager not initialized");
+        };
+        let params = payload
+            .params
+            .clone()
+            .unwrap_or_else(|| Value::Object(serde_json::Map::new()));
+
+        match method {
+            "start_process" => {
+                let args: StartProcessArgs = match serde_json::from_value(params) {
+                    Ok(a) => a,
+                    Err(e) => return self.error(&format!("Invalid parameters: {}", e)),
+                };
+                let prepared = match self
+                    .prepare_command(
+                        &workspace_id,
+                        &args.command,
+                        &args.args,
+                        args.env.unwrap_or_default(),
+                        &payload.allowed_paths,
+                        &payload.blocked_paths,
+                        payload.command_policy.as_ref(),
+                    )
+                    .await
+                {
+                    Ok(prepared) => prepared,
+                    Err(e) => return self.error(&e),
+                };
+                let process = prepared.build(&args.command, &args.args);
+                match manager
+                    .start(
+                        &workspace_id,
+                        payload.run_id.as_deref(),
+                        &args.command,
+                        &args.args,
+                        process,
+                    )
+                    .await
+                {
+                    Ok(info) => self.json_result(&serde_json::json!(info)),
+                    Err(e) => self.error(&e),
+                }
+            }
+            "read_process_output" => {
+                let args: ReadProcessOutputArgs = match serde_json::from_value(params) {
+                    Ok(a) => a,
+                    Err(e) => return self.error(&format!("Invalid parameters: {}", e)),
+                };
+                let max_bytes = args.max_bytes.unwrap_or(16 * 1024).clamp(1, MAX_READ_BYTES);
+                let wait = Duration::from_millis(args.wait_ms.unwrap_or(0).min(MAX_READ_WAIT_MS));
+                match manager
+                    .read_output(
+                        &workspace_id,
+                        &args.process_id,
+                        args.cursor.unwrap_or(0),
+                        max_bytes,
+                        wait,
+                    )
+                    .await
+                {
+                    Ok(output) => self.json_result(&serde_json::json!(output)),
+                    Err(e) => self.error(&e),
+                }
+            }
+            "write_process_stdin" => {
+                let args: WriteProcessStdinArgs = match serde_json::from_value(params) {
+                    Ok(a) => a,
+                    Err(e) => return self.error(&format!("Invalid parameters: {}", e)),
+                };
+                match manager
+                    .write_stdin(
+                        &workspace_id,
+                        &args.process_id,
+                        &args.input,
+                        args.close_stdin.unwrap_or(false),
+                    )
+                    .await
+                {
+                    Ok(()) => self.json_result(&serde_json::json!({
+                        "process_id": args.process_id,
+                        "bytes_written": args.input.len(),
+                    })),
+                    Err(e) => self.error(&e),
+                }
+            }
+            "list_processes" => {
+                let processes = manager.list(Some(&workspace_id)).await;
+                self.json_result(&serde_json::json!(processes))
+            }
+            "stop_process" => {
+                let args: StopProcessArgs = match serde_json::from_value(params) {
+                    Ok(a) => a,
+                    Err(e) => return self.error(&format!("Invalid parameters: {}", e)),
+                };
+                match manager.stop(&workspace_id, &args.process_id).await {
+                    Ok(info) => self.json_result(&serde_json::json!(info)),
+                    Err(e) => self.error(&e),
+                }
+            }
+            _ => self.error(&format!("Unknown process method: {}", method)),
+        }
+    }
+}
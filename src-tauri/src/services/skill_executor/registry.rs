@@ -18,6 +18,16 @@ pub fn registered_tool_definitions() -> Vec<Tool> {
             "Append content to a file",
             schema_for!(WriteFileArgs),
         ),
+        tool(
+            "edit_file",
+            "Edit a file by exact search/replace blocks; fails without writing if any block does not match",
+            schema_for!(EditFileArgs),
+        ),
+        tool(
+            "apply_patch",
+            "Apply a unified diff to one or more files; fails without writing if any hunk does not apply",
+            schema_for!(ApplyPatchArgs),
+        ),
         tool("list_files", "List files in a directory", schema_for!(ListFilesArgs)),
         tool(
             "list_files_detailed",
@@ -39,6 +49,11 @@ pub fn registered_tool_definitions() -> Vec<Tool> {
             "Search files by regex in names and (by default) text content",
             schema_for!(SearchFilesArgs),
         ),
+        tool(
+            "semantic_search_workspace",
+            "Find code or text in the workspace by meaning, using a continuously updated index; returns file paths with line ranges",
+            schema_for!(SemanticSearchWorkspaceArgs),
+        ),
         tool(
             "read_file_chunk",
             "Read a chunk of a text file by byte offset for large-file processing",
@@ -46,9 +61,34 @@ pub fn registered_tool_definitions() -> Vec<Tool> {
         ),
         tool(
             "execute_command",
-            "Execute a shell command (npm, cargo, git, ls, grep)",
+            "Execute a command allowed by the workspace command policy (by default npm, cargo, git, ls, grep)",
             schema_for!(ExecuteCommandArgs),
         ),
+        tool(
+            "start_process",
+            "Start a long-running command (dev server, watcher, REPL) allowed by the workspace command policy; returns a process ID without waiting for it to exit",
+            schema_for!(StartProcessArgs),
+        ),
+        tool(
+            "read_process_output",
+            "Read a started process's combined stdout/stderr since a cursor, optionally waiting for new output",
+            schema_for!(ReadProcessOutputArgs),
+        ),
+        tool(
+            "write_process_stdin",
+            "Write text to a started process's stdin",
+            schema_for!(WriteProcessStdinArgs),
+        ),
+        tool(
+            "list_processes",
+            "List processes started in this workspace with their state and exit code",
+            serde_json::json!({ "type": "object", "properties": {} }),
+        ),
+        tool(
+            "stop_process",
+            "Stop a started process and its child processes",
+            schema_for!(StopProcessArgs),
+        ),
         tool(
             "git_status",
             "Get git status with stable wrapper options",
@@ -146,6 +186,21 @@ pub fn registered_tool_definitions() -> Vec<Tool> {
             "Extract clickable links from the current browser page (href + text)",
             schema_for!(ExtractLinksArgs),
         ),
+        tool(
+            "remember_fact",
+            "Remember a stable fact about a person, project or other entity, or a relation between two entities",
+            schema_for!(RememberFactArgs),
+        ),
+        tool(
+            "recall_entity",
+            "Recall everything remembered about an entity, including contested values",
+            schema_for!(RecallEntityArgs),
+        ),
+        tool(
+            "list_related",
+            "List remembered relations between an entity and other entities",
+            schema_for!(ListRelatedArgs),
+        ),
         tool("mkdir", "Create a new directory", schema_for!(MakeDirArgs)),
         tool(
             "delete_file",
@@ -1,10 +1,54 @@
 use super::args::*;
-use super::{truncate_output, SkillExecutor};
+use super::SkillExecutor;
 use crate::models::neural::CommandResult;
+use crate::services::command_policy::{evaluate_command, CommandDecision, CommandPolicy};
+use crate::services::command_sandbox::CommandSandbox;
+use crate::services::tool_output::{OutputCapture, ToolOutputSink, ToolOutputStream, Utf8Chunks};
 use serde_json::Value;
-use std::path::{Path, PathBuf};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::sync::Mutex;
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+/// Environment of a spawned command.
+#[derive(Default)]
+struct CommandEnv {
+    /// Variables inherited from the app; `None` inherits everything.
+    inherit: Option<Vec<String>>,
+    set: HashMap<String, String>,
+}
+
+/// Where and how a command runs once it has passed the command policy.
+pub(super) struct PreparedCommand {
+    cwd: PathBuf,
+    env: CommandEnv,
+    sandbox: Option<CommandSandbox>,
+}
+
+impl PreparedCommand {
+    /// A process for `command`, not yet spawned.
+    pub(super) fn build(self, command: &str, args: &[String]) -> tokio::process::Command {
+        let mut process = tokio::process::Command::new(command);
+        process.args(args).current_dir(&self.cwd).kill_on_drop(true);
+        if let Some(inherit) = self.env.inherit.as_ref() {
+            process.env_clear();
+            for name in inherit {
+                if let Ok(value) = std::env::var(name) {
+                    process.env(name, value);
+                }
+            }
+        }
+        process.envs(&self.env.set);
+        if let Some(sandbox) = self.sandbox {
+            sandbox.apply(&mut process);
+        }
+        process
+    }
+}
 
 impl SkillExecutor {
+    #[allow(clippy::too_many_arguments)]
     pub(super) async fn execute_shell(
         &self,
         workspace_id: String,
@@ -12,6 +56,8 @@ impl SkillExecutor {
         params: &Option<Value>,
         allowed_paths: &[String],
         blocked_paths: &[String],
+        command_policy: Option<&CommandPolicy>,
+        on_output: Option<&ToolOutputSink>,
     ) -> CommandResult {
         let params = match params {
             Some(p) => p,
@@ -25,23 +71,37 @@ impl SkillExecutor {
                     Err(e) => return self.error(&format!("Invalid parameters: {}", e)),
                 };
 
-                let root_path = match self
-                    .resolve_path(workspace_id, ".", allowed_paths, blocked_paths)
+                let prepared = match self
+                    .prepare_command(
+                        &workspace_id,
+                        &args.command,
+                        &args.args,
+                        args.env.unwrap_or_default(),
+                        allowed_paths,
+                        blocked_paths,
+                        command_policy,
+                    )
                     .await
                 {
-                    Ok(p) => p,
+                    Ok(prepared) => prepared,
                     Err(e) => return self.error(&e),
                 };
 
-                self.execute_command(&args.command, args.args, args.timeout_ms, &root_path)
-                    .await
+                self.spawn_command(
+                    &args.command,
+                    args.args,
+                    args.timeout_ms,
+                    prepared,
+                    on_output,
+                )
+                .await
             }
             "git_status" => {
                 let args: GitStatusArgs = match serde_json::from_value(params.clone()) {
                     Ok(a) => a,
                     Err(e) => return self.error(&format!("Invalid parameters: {}", e)),
                 };
-                self.handle_git_status(workspace_id, args, allowed_paths, blocked_paths)
+                self.handle_git_status(workspace_id, args, allowed_paths, blocked_paths, on_output)
                     .await
             }
             "git_diff" => {
@@ -49,7 +109,7 @@ impl SkillExecutor {
                     Ok(a) => a,
                     Err(e) => return self.error(&format!("Invalid parameters: {}", e)),
                 };
-                self.handle_git_diff(workspace_id, args, allowed_paths, blocked_paths)
+                self.handle_git_diff(workspace_id, args, allowed_paths, blocked_paths, on_output)
                     .await
             }
             "git_log" => {
@@ -57,7 +117,7 @@ impl SkillExecutor {
                     Ok(a) => a,
                     Err(e) => return self.error(&format!("Invalid parameters: {}", e)),
                 };
-                self.handle_git_log(workspace_id, args, allowed_paths, blocked_paths)
+                self.handle_git_log(workspace_id, args, allowed_paths, blocked_paths, on_output)
                     .await
             }
             "git_show" => {
@@ -65,7 +125,7 @@ impl SkillExecutor {
                     Ok(a) => a,
                     Err(e) => return self.error(&format!("Invalid parameters: {}", e)),
                 };
-                self.handle_git_show(workspace_id, args, allowed_paths, blocked_paths)
+                self.handle_git_show(workspace_id, args, allowed_paths, blocked_paths, on_output)
                     .await
             }
             "git_branch_list" => {
@@ -73,8 +133,14 @@ impl SkillExecutor {
                     Ok(a) => a,
                     Err(e) => return self.error(&format!("Invalid parameters: {}", e)),
                 };
-                self.handle_git_branch_list(workspace_id, args, allowed_paths, blocked_paths)
-                    .await
+                self.handle_git_branch_list(
+                    workspace_id,
+                    args,
+                    allowed_paths,
+                    blocked_paths,
+                    on_output,
+                )
+                .await
             }
             _ => CommandResult {
                 success: false,
@@ -91,6 +157,7 @@ impl SkillExecutor {
         args: GitStatusArgs,
         allowed_paths: &[String],
         blocked_paths: &[String],
+        on_output: Option<&ToolOutputSink>,
     ) -> CommandResult {
         let cwd = match self
             .resolve_git_working_dir(workspace_id, args.path, allowed_paths, blocked_paths)
@@ -104,7 +171,8 @@ impl SkillExecutor {
         if args.short.unwrap_or(true) {
             git_args.push("--short".to_string());
         }
-        self.execute_command("git", git_args, None, &cwd).await
+        self.execute_command("git", git_args, None, &cwd, on_output)
+            .await
     }
 
     async fn handle_git_diff(
@@ -113,6 +181,7 @@ impl SkillExecutor {
         args: GitDiffArgs,
         allowed_paths: &[String],
         blocked_paths: &[String],
+        on_output: Option<&ToolOutputSink>,
     ) -> CommandResult {
         let cwd = match self
             .resolve_git_working_dir(workspace_id, args.path, allowed_paths, blocked_paths)
@@ -126,7 +195,8 @@ impl SkillExecutor {
         if args.staged.unwrap_or(false) {
             git_args.push("--staged".to_string());
         }
-        self.execute_command("git", git_args, None, &cwd).await
+        self.execute_command("git", git_args, None, &cwd, on_output)
+            .await
     }
 
     async fn handle_git_log(
@@ -135,6 +205,7 @@ impl SkillExecutor {
         args: GitLogArgs,
         allowed_paths: &[String],
         blocked_paths: &[String],
+        on_output: Option<&ToolOutputSink>,
     ) -> CommandResult {
         let cwd = match self
             .resolve_git_working_dir(workspace_id, args.path, allowed_paths, blocked_paths)
@@ -151,7 +222,8 @@ impl SkillExecutor {
             "--oneline".to_string(),
             format!("-{}", max_count),
         ];
-        self.execute_command("git", git_args, None, &cwd).await
+        self.execute_command("git", git_args, None, &cwd, on_output)
+            .await
     }
 
     async fn handle_git_show(
@@ -160,6 +232,7 @@ impl SkillExecutor {
         args: GitShowArgs,
         allowed_paths: &[String],
         blocked_paths: &[String],
+        on_output: Option<&ToolOutputSink>,
     ) -> CommandResult {
         let cwd = match self
             .resolve_git_working_dir(workspace_id, args.path, allowed_paths, blocked_paths)
@@ -181,7 +254,9 @@ impl SkillExecutor {
             "1".to_string(),
             target,
         ];
-        let result = self.execute_command("git", git_args, None, &cwd).await;
+        let result = self
+            .execute_command("git", git_args, None, &cwd, on_output)
+            .await;
         if !result.success {
             return result;
         }
@@ -213,6 +288,7 @@ impl SkillExecutor {
         args: GitBranchListArgs,
         allowed_paths: &[String],
         blocked_paths: &[String],
+        on_output: Option<&ToolOutputSink>,
     ) -> CommandResult {
         let cwd = match self
             .resolve_git_working_dir(workspace_id, args.path, allowed_paths, blocked_paths)
@@ -232,7 +308,8 @@ impl SkillExecutor {
             git_args.push("--all".to_string());
         }
 
-        self.execute_command("git", git_args, None, &cwd).await
+        self.execute_command("git", git_args, None, &cwd, on_output)
+            .await
     }
 
     pub(super) async fn resolve_git_working_dir(
@@ -256,55 +333,182 @@ impl SkillExecutor {
         }
     }
 
-    /// Execute a shell command
+    /// Check `command` against the workspace and agent command policies and
+    /// resolve its working directory, environment and sandbox.
+    #[allow(clippy::too_many_arguments)]
+    pub(super) async fn prepare_command(
+        &self,
+        workspace_id: &str,
+        command: &str,
+        args: &[String],
+        env: HashMap<String, String>,
+        allowed_paths: &[String],
+        blocked_paths: &[String],
+        command_policy: Option<&CommandPolicy>,
+    ) -> Result<PreparedCommand, String> {
+        let workspace_policy = self.workspace_command_policy(workspace_id).await;
+        let matched = match evaluate_command(&workspace_policy, command_policy, command, args) {
+            CommandDecision::Allow(matched) => matched,
+            CommandDecision::Deny { reason } => return Err(reason),
+        };
+        if let Some(name) = env.keys().find(|name| !matched.allows_env(name)) {
+            return Err(format!(
+                "Environment variable '{}' is not in the command policy's env allowlist",
+                name
+            ));
+        }
+
+        let cwd = self
+            .resolve_path(workspace_id.to_string(), ".", allowed_paths, blocked_paths)
+            .await?;
+        let writable_roots = if allowed_paths.is_empty() {
+            vec![cwd.clone()]
+        } else {
+            allowed_paths.iter().map(PathBuf::from).collect()
+        };
+        let sandbox_workspace = workspace_id.to_string();
+        let sandbox_config = self
+            .read_settings(move |settings| settings.get_workspace_sandbox(&sandbox_workspace))
+            .await
+            .unwrap_or_default();
+        let sandbox = CommandSandbox::prepare(&sandbox_config, &writable_roots, blocked_paths)
+            .map_err(|e| format!("Sandbox unavailable: {}", e))?;
+
+        Ok(PreparedCommand {
+            cwd,
+            env: CommandEnv {
+                inherit: matched.inherited_env(),
+                set: env,
+            },
+            sandbox,
+        })
+    }
+
+    /// Execute a fixed command on behalf of a built-in tool (the git tools).
     pub(super) async fn execute_command(
         &self,
         command: &str,
         args: Vec<String>,
         timeout_ms: Option<u64>,
         cwd: &PathBuf,
+        on_output: Option<&ToolOutputSink>,
     ) -> CommandResult {
-        if !Self::is_allowed_shell_command(command) {
-            return self.error(&format!("Command '{}' is not allowed", command));
-        }
+        let prepared = PreparedCommand {
+            cwd: cwd.clone(),
+            env: CommandEnv::default(),
+            sandbox: None,
+        };
+        self.spawn_command(command, args, timeout_ms, prepared, on_output)
+            .await
+    }
 
+    /// Run a command to completion, passing its output to `on_output` as it
+    /// arrives. The result holds a head-and-tail summary of stdout and stderr
+    /// interleaved in arrival order.
+    async fn spawn_command(
+        &self,
+        command: &str,
+        args: Vec<String>,
+        timeout_ms: Option<u64>,
+        prepared: PreparedCommand,
+        on_output: Option<&ToolOutputSink>,
+    ) -> CommandResult {
+        let sandboxed = prepared.sandbox.is_some();
         let timeout = timeout_ms.unwrap_or(120_000).clamp(500, 600_000);
-        let command_future = tokio::process::Command::new(command)
-            .args(&args)
-            .current_dir(Path::new(cwd))
-            .kill_on_drop(true)
-            .output();
-        let output = tokio::time::timeout(
-            tokio::time::Duration::from_millis(timeout),
-            command_future,
-        )
-        .await;
+        let mut process = prepared.build(command, &args);
+        process
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
 
-        match output {
-            Ok(Ok(out)) => {
-                let stdout = String::from_utf8_lossy(&out.stdout).to_string();
-                let stderr = String::from_utf8_lossy(&out.stderr).to_string();
-                let exit_code = out.status.code().unwrap_or(1);
-                let combined = format!("{}\n{}", stdout, stderr).trim().to_string();
+        let mut child = match process.spawn() {
+            Ok(child) => child,
+            Err(e) if sandboxed => {
+                return self.error(&format!("Failed to start sandboxed command: {}", e))
+            }
+            Err(e) => return self.error(&format!("Failed to execute command: {}", e)),
+        };
+
+        let combined = Mutex::new(OutputCapture::new());
+        let stdout = child.stdout.take();
+        let stderr = child.stderr.take();
+        let run = async {
+            let (_, stderr) = tokio::join!(
+                pump_output(stdout, ToolOutputStream::Stdout, &combined, on_output),
+                pump_output(stderr, ToolOutputStream::Stderr, &combined, on_output),
+            );
+            (child.wait().await, stderr)
+        };
+        let outcome = tokio::time::timeout(tokio::time::Duration::from_millis(timeout), run).await;
+        let combined = combined
+            .into_inner()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
 
+        match outcome {
+            Ok((Ok(status), stderr)) => CommandResult {
+                success: status.success(),
+                output: Some(combined.summary()),
+                error: if !status.success() {
+                    Some(stderr.summary())
+                } else {
+                    None
+                },
+                exit_code: Some(status.code().unwrap_or(1)),
+            },
+            Ok((Err(e), _)) => self.error(&format!("Failed to execute command: {}", e)),
+            Err(_) => {
+                let _ = child.start_kill();
                 CommandResult {
-                    success: out.status.success(),
-                    output: Some(truncate_output(&combined)),
-                    error: if !out.status.success() {
-                        Some(truncate_output(&stderr))
-                    } else {
-                        None
-                    },
-                    exit_code: Some(exit_code),
+                    success: false,
+                    output: (!combined.is_empty()).then(|| combined.summary()),
+                    error: Some(format!(
+                        "Command timed out after {}ms: {} {}",
+                        timeout,
+                        command,
+                        args.join(" ")
+                    )),
+                    exit_code: Some(1),
                 }
             }
-            Ok(Err(e)) => self.error(&format!("Failed to execute command: {}", e)),
-            Err(_) => self.error(&format!(
-                "Command timed out after {}ms: {} {}",
-                timeout,
-                command,
-                args.join(" ")
-            )),
         }
     }
 }
+
+/// Copy one of a child's pipes into `combined` and `on_output` until it
+/// closes, returning what came through this pipe alone.
+async fn pump_output(
+    pipe: Option<impl AsyncRead + Unpin>,
+    stream: ToolOutputStream,
+    combined: &Mutex<OutputCapture>,
+    on_output: Option<&ToolOutputSink>,
+) -> OutputCapture {
+    let mut own = OutputCapture::new();
+    let Some(mut pipe) = pipe else {
+        return own;
+    };
+    let mut text = Utf8Chunks::default();
+    let mut buf = [0u8; 8192];
+    loop {
+        let read = match pipe.read(&mut buf).await {
+            Ok(0) | Err(_) => break,
+            Ok(read) => read,
+        };
+        own.push(&buf[..read]);
+        if let Ok(mut combined) = combined.lock() {
+            combined.push(&buf[..read]);
+        }
+        if let Some(sink) = on_output {
+            let chunk = text.push(&buf[..read]);
+            if !chunk.is_empty() {
+                sink(stream, chunk);
+            }
+        }
+    }
+    if let Some(sink) = on_output {
+        let rest = text.finish();
+        if !rest.is_empty() {
+            sink(stream, rest);
+        }
+    }
+    own
+}
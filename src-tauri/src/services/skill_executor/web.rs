@@ -1,9 +1,13 @@
 use super::args::*;
 use super::{truncate_output, SkillExecutor};
 use crate::models::neural::CommandResult;
+use crate::services::web_search::{WebSearchConfig, WebSearchProvider};
 use reqwest::header::CONTENT_TYPE;
 use serde_json::Value;
 
+/// Results requested from a self-hosted or BYOK search backend per query.
+const WEB_SEARCH_RESULTS: usize = 8;
+
 impl SkillExecutor {
     pub(super) async fn execute_web(
         &self,
@@ -23,7 +27,8 @@ impl SkillExecutor {
                     Ok(a) => a,
                     Err(e) => return self.error(&format!("Invalid parameters: {}", e)),
                 };
-                self.handle_web_search(&args.query).await
+                self.handle_web_search(&args.query, allowed_domains, blocked_domains)
+                    .await
             }
             "read_web_page" => {
                 let args: ReadWebPageArgs = match serde_json::from_value(params.clone()) {
@@ -66,7 +71,21 @@ impl SkillExecutor {
         }
     }
 
-    async fn handle_web_search(&self, query: &str) -> CommandResult {
+    async fn handle_web_search(
+        &self,
+        query: &str,
+        allowed_domains: &[String],
+        blocked_domains: &[String],
+    ) -> CommandResult {
+        let config = self
+            .read_settings(|settings| settings.get_web_search_config())
+            .await;
+        if config.provider != WebSearchProvider::Rainy {
+            return self
+                .handle_backend_web_search(&config, query, allowed_domains, blocked_domains)
+                .await;
+        }
+
         match self
             .managed_research
             .perform_research(query.to_string(), None)
@@ -82,6 +101,61 @@ impl SkillExecutor {
         }
     }
 
+    /// Search the user's own backend, keeping only results the run's Airlock
+    /// domain scopes allow.
+    async fn handle_backend_web_search(
+        &self,
+        config: &WebSearchConfig,
+        query: &str,
+        allowed_domains: &[String],
+        blocked_domains: &[String],
+    ) -> CommandResult {
+        let (backend, results) = match self
+            .web_search
+            .search(config, query, WEB_SEARCH_RESULTS)
+            .await
+        {
+            Ok(found) => found,
+            Err(e) => return self.error(&format!("Web search failed: {}", e)),
+        };
+        let total = results.len();
+        let results: Vec<_> = results
+            .into_iter()
+            .filter(|r| {
+                Self::enforce_domain_scope(&r.url, allowed_domains, blocked_domains).is_ok()
+            })
+            .collect();
+
+        let mut output = format!("Search results for '{}' ({}):\n", query, backend);
+        if results.is_empty() {
+            output.push_str("No results found.\n");
+        }
+        for (i, result) in results.iter().enumerate() {
+            output.push_str(&format!(
+                "\n{}. {}\n   {}\n",
+                i + 1,
+                result.title,
+                result.url
+            ));
+            if !result.snippet.is_empty() {
+                output.push_str(&format!("   {}\n", result.snippet));
+            }
+        }
+        if results.len() < total {
+            output.push_str(&format!(
+                "\n[{} result(s) outside the allowed domains were omitted]\n",
+                total - results.len()
+            ));
+        }
+
+        CommandResult {
+            success: true,
+            output: Some(output),
+            error: None,
+            exit_code: Some(0),
+        }
+    }
+
     async fn handle_read_web_page(
         &self,
         url: &str,
@@ -0,0 +1,163 @@
+//! Incremental output of long-running tools
+//!
+//! Shell, git and WASM tools hand their output to a [`ToolOutputSink`] while
+//! they run, so a run can show a build's progress before it exits. The result
+//! returned to the model is an [`OutputCapture`] summary: the beginning and the
+//! end of the output, where commands report what they are doing and how it
+//! ended, with the middle elided.
+
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::sync::Arc;
+
+/// Bytes kept from the start of a tool's output.
+const HEAD_BYTES: usize = 16 * 1024;
+/// Bytes kept from the end of a tool's output.
+const TAIL_BYTES: usize = 32 * 1024;
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ToolOutputStream {
+    Stdout,
+    Stderr,
+}
+
+/// Receives a tool's output as it is produced. Called from the task running
+/// the tool, or from a blocking thread for WASM skills, so it must not block.
+pub type ToolOutputSink = Arc<dyn Fn(ToolOutputStream, String) + Send + Sync>;
+
+/// Bounded record of a tool's output: the first and last bytes written, and
+/// how many were dropped in between.
+#[derive(Debug, Default)]
+pub struct OutputCapture {
+    head: Vec<u8>,
+    tail: VecDeque<u8>,
+    total: usize,
+}
+
+impl OutputCapture {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, mut bytes: &[u8]) {
+        self.total += bytes.len();
+        if self.head.len() < HEAD_BYTES {
+            let take = bytes.len().min(HEAD_BYTES - self.head.len());
+            self.head.extend_from_slice(&bytes[..take]);
+            bytes = &bytes[take..];
+        }
+        if bytes.len() >= TAIL_BYTES {
+            self.tail.clear();
+            bytes = &bytes[bytes.len() - TAIL_BYTES..];
+        }
+        let overflow = (self.tail.len() + bytes.len()).saturating_sub(TAIL_BYTES);
+        self.tail.drain(..overflow);
+        self.tail.extend(bytes);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.total == 0
+    }
+
+    /// The output as text. When the middle was dropped, the kept head and tail
+    /// are trimmed to whole lines and joined by a marker naming the gap.
+    pub fn summary(&self) -> String {
+        let (tail_front, tail_back) = self.tail.as_slices();
+        let tail = [tail_front, tail_back].concat();
+        let omitted = self.total - self.head.len() - tail.len();
+        if omitted == 0 {
+            return String::from_utf8_lossy(&[self.head.as_slice(), &tail].concat())
+                .trim()
+                .to_string();
+        }
+
+        let head_end = self.head[HEAD_BYTES / 2..]
+            .iter()
+            .rposition(|&b| b == b'\n')
+            .map_or(self.head.len(), |pos| HEAD_BYTES / 2 + pos);
+        let tail_start = tail[..TAIL_BYTES / 2]
+            .iter()
+            .position(|&b| b == b'\n')
+            .map_or(0, |pos| pos + 1);
+        let omitted = omitted + (self.head.len() - head_end) + tail_start;
+
+        format!(
+            "{}\n\n[... {} bytes of output omitted ...]\n\n{}",
+            String::from_utf8_lossy(&self.head[..head_end]).trim_start(),
+            omitted,
+            String::from_utf8_lossy(&tail[tail_start..]).trim_end()
+        )
+    }
+}
+
+/// Decodes a byte stream read in arbitrary pieces, holding back a multi-byte
+/// character split across reads until the rest of it arrives.
+#[derive(Debug, Default)]
+pub struct Utf8Chunks {
+    pending: Vec<u8>,
+}
+
+impl Utf8Chunks {
+    pub fn push(&mut self, bytes: &[u8]) -> String {
+        self.pending.extend_from_slice(bytes);
+        let valid = match std::str::from_utf8(&self.pending) {
+            Ok(_) => self.pending.len(),
+            // An incomplete character at the end: keep it for the next read.
+            Err(e) if e.error_len().is_none() => e.valid_up_to(),
+            Err(_) => self.pending.len(),
+        };
+        let rest = self.pending.split_off(valid);
+        let text = String::from_utf8_lossy(&self.pending).into_owned();
+        self.pending = rest;
+        text
+    }
+
+    /// Whatever is still held back, once the stream has ended.
+    pub fn finish(&mut self) -> String {
+        let text = String::from_utf8_lossy(&self.pending).into_owned();
+        self.pending.clear();
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn capture_keeps_head_and_tail_lines() {
+        let mut capture = OutputCapture::new();
+        for i in 0..20_000 {
+            capture.push(format!("line {}\n", i).as_bytes());
+        }
+
+        let summary = capture.summary();
+        assert!(summary.starts_with("line 0\nline 1\n"));
+        assert!(summary.ends_with("line 19998\nline 19999"));
+        assert!(summary.contains("bytes of output omitted"));
+        assert!(summary.len() <= HEAD_BYTES + TAIL_BYTES + 64);
+        // Only whole lines on either side of the marker.
+        let (head, tail) = summary.split_once("\n\n[... ").unwrap();
+        assert!(head.lines().last().unwrap().starts_with("line "));
+        let tail = tail.split_once("...]\n\n").unwrap().1;
+        assert!(tail.lines().next().unwrap().starts_with("line "));
+
+        let mut short = OutputCapture::new();
+        short.push(b"  short output\n");
+        assert_eq!(short.summary(), "short output");
+    }
+
+    #[test]
+    fn utf8_chunks_do_not_split_characters() {
+        let bytes = "héllo wörld".as_bytes();
+        let mut chunks = Utf8Chunks::default();
+        let mut text = String::new();
+        for piece in bytes.chunks(2) {
+            text.push_str(&chunks.push(piece));
+        }
+        text.push_str(&chunks.finish());
+        assert_eq!(text, "héllo wörld");
+        assert!(!text.contains('\u{FFFD}'));
+    }
+}
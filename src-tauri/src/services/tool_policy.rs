@@ -6,6 +6,7 @@ pub enum ToolSkill {
     Browser,
     Shell,
     Web,
+    Memory,
 }
 
 impl ToolSkill {
@@ -15,6 +16,7 @@ impl ToolSkill {
             Self::Browser => "browser",
             Self::Shell => "shell",
             Self::Web => "web",
+            Self::Memory => "memory",
         }
     }
 }
@@ -36,6 +38,7 @@ pub fn get_tool_policy(function_name: &str) -> Option<ToolPolicy> {
         | "list_files"
         | "list_files_detailed"
         | "search_files"
+        | "semantic_search_workspace"
         | "file_exists"
         | "get_file_info"
         | "read_file_chunk"
@@ -44,6 +47,8 @@ pub fn get_tool_policy(function_name: &str) -> Option<ToolPolicy> {
         | "git_log"
         | "git_show"
         | "git_branch_list"
+        | "read_process_output"
+        | "list_processes"
         | "web_search"
         | "read_web_page"
         | "http_get_json"
@@ -64,16 +69,20 @@ pub fn get_tool_policy(function_name: &str) -> Option<ToolPolicy> {
                 | "extract_links" => {
                     ToolSkill::Browser
                 }
-                "git_status" | "git_diff" | "git_log" | "git_show" | "git_branch_list" => {
-                    ToolSkill::Shell
-                }
+                "git_status" | "git_diff" | "git_log" | "git_show" | "git_branch_list"
+                | "read_process_output" | "list_processes" => ToolSkill::Shell,
                 _ => ToolSkill::Filesystem,
             },
             airlock_level: AirlockLevel::Safe,
         },
+        "recall_entity" | "list_related" => ToolPolicy {
+            skill: ToolSkill::Memory,
+            airlock_level: AirlockLevel::Safe,
+        },
 
         // Level 1: state-changing but non-destructive
-        "write_file" | "append_file" | "mkdir" | "create_file" => ToolPolicy {
+        "write_file" | "append_file" | "edit_file" | "apply_patch" | "mkdir"
+        | "create_file" => ToolPolicy {
             skill: ToolSkill::Filesystem,
             airlock_level: AirlockLevel::Sensitive,
         },
@@ -82,9 +91,17 @@ pub fn get_tool_policy(function_name: &str) -> Option<ToolPolicy> {
             skill: ToolSkill::Browser,
             airlock_level: AirlockLevel::Sensitive,
         },
+        "remember_fact" => ToolPolicy {
+            skill: ToolSkill::Memory,
+            airlock_level: AirlockLevel::Sensitive,
+        },
+        "stop_process" => ToolPolicy {
+            skill: ToolSkill::Shell,
+            airlock_level: AirlockLevel::Sensitive,
+        },
 
         // Level 2: destructive or external command execution
-        "execute_command" => ToolPolicy {
+        "execute_command" | "start_process" | "write_process_stdin" => ToolPolicy {
             skill: ToolSkill::Shell,
             airlock_level: AirlockLevel::Dangerous,
         },
@@ -124,6 +141,12 @@ mod tests {
         let web = get_tool_policy("web_search").expect("web_search should have policy");
         assert_eq!(web.skill, ToolSkill::Web);
         assert_eq!(web.airlock_level, AirlockLevel::Safe);
+
+        for name in ["edit_file", "apply_patch"] {
+            let edit = get_tool_policy(name).expect("edit tools should have policy");
+            assert_eq!(edit.skill, ToolSkill::Filesystem);
+            assert_eq!(edit.airlock_level, AirlockLevel::Sensitive);
+        }
     }
 
     #[test]
@@ -1,20 +1,24 @@
+mod stdio;
 mod types;
 
 pub use types::{WasmExecutionRequest, WasmExecutionResult};
 
+use crate::services::tool_output::{ToolOutputSink, ToolOutputStream};
 use dashmap::DashMap;
 use sha2::{Digest, Sha256};
 use std::fs;
 use std::net::IpAddr;
 use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use stdio::StreamingOutputPipe;
 use tokio::sync::Semaphore;
 use wasmtime::Config;
-use wasmtime::{Engine, Linker, Module, ResourceLimiter, Store};
+use wasmtime::{Engine, Linker, Module, ResourceLimiter, Store, UpdateDeadline};
 use wasmtime_wasi::{DirPerms, FilePerms};
 use wasmtime_wasi::p1;
 use wasmtime_wasi::p1::WasiP1Ctx;
-use wasmtime_wasi::p2::pipe::{MemoryInputPipe, MemoryOutputPipe};
+use wasmtime_wasi::p2::pipe::MemoryInputPipe;
 use wasmtime_wasi::WasiCtxBuilder;
 
 struct SandboxCtx {
@@ -59,6 +63,25 @@ pub struct WasmSandboxService {
     exec_timeout_ms: u64,
 }
 
+/// Interrupts a running module when dropped, unless disarmed first. Covers both
+/// the execution timeout and the caller dropping `execute` (run cancellation):
+/// `spawn_blocking` work cannot be aborted, so the guest is stopped through an
+/// epoch tick that its deadline callback turns into a trap.
+struct AbortOnDrop {
+    engine: Engine,
+    abort: Arc<AtomicBool>,
+    armed: bool,
+}
+
+impl Drop for AbortOnDrop {
+    fn drop(&mut self) {
+        if self.armed {
+            self.abort.store(true, Ordering::SeqCst);
+            self.engine.increment_epoch();
+        }
+    }
+}
+
 impl Default for WasmSandboxService {
     fn default() -> Self {
         Self::new()
@@ -70,6 +93,7 @@ impl WasmSandboxService {
         let mut config = Config::new();
         config.async_support(false);
         config.consume_fuel(true);
+        config.epoch_interruption(true);
         config.max_wasm_stack(512 * 1024);
         let engine = Engine::new(&config).expect("Failed to initialize Wasmtime engine");
 
@@ -106,7 +130,13 @@ impl WasmSandboxService {
         Ok(bytes)
     }
 
-    pub async fn execute(&self, req: WasmExecutionRequest) -> WasmExecutionResult {
+    /// Run a skill method; the guest's stdout and stderr also go to `on_output`
+    /// as it writes them.
+    pub async fn execute(
+        &self,
+        req: WasmExecutionRequest,
+        on_output: Option<ToolOutputSink>,
+    ) -> WasmExecutionResult {
         let _permit = match self.concurrency.acquire().await {
             Ok(p) => p,
             Err(_) => {
@@ -160,6 +190,12 @@ impl WasmSandboxService {
         let module_sha = WasmSandboxService::sha256_hex(&bytes_for_exec);
         let fs_perms = req.skill.permissions.filesystem.clone();
         let envelope_for_exec = envelope;
+        let abort = Arc::new(AtomicBool::new(false));
+        let mut abort_guard = AbortOnDrop {
+            engine: self.engine.clone(),
+            abort: abort.clone(),
+            armed: true,
+        };
 
         let task = tokio::task::spawn_blocking(move || {
             Self::execute_wasi_module_static(
@@ -172,12 +208,19 @@ impl WasmSandboxService {
                 max_stdio_bytes,
                 max_memory_bytes,
                 fuel_limit,
+                abort,
+                on_output,
             )
         });
 
-        match tokio::time::timeout(std::time::Duration::from_millis(self.exec_timeout_ms), task)
-            .await
-        {
+        let outcome =
+            tokio::time::timeout(std::time::Duration::from_millis(self.exec_timeout_ms), task)
+                .await;
+        // Only a timeout leaves the guest running; completed runs need no interrupt.
+        abort_guard.armed = outcome.is_err();
+        drop(abort_guard);
+
+        match outcome {
             Ok(Ok(Ok(res))) => res,
             Ok(Ok(Err(e))) => WasmExecutionResult {
                 stdout: String::new(),
@@ -210,6 +253,8 @@ impl WasmSandboxService {
         max_stdio_bytes: usize,
         max_memory_bytes: usize,
         fuel_limit: u64,
+        abort: Arc<AtomicBool>,
+        on_output: Option<ToolOutputSink>,
     ) -> Result<WasmExecutionResult, String> {
         let module = if let Some(existing) = module_cache.get(module_sha) {
             existing.clone()
@@ -221,8 +266,10 @@ impl WasmSandboxService {
         };
 
         let stdin_pipe = MemoryInputPipe::new(params_json.as_bytes().to_vec());
-        let stdout_pipe = MemoryOutputPipe::new(max_stdio_bytes);
-        let stderr_pipe = MemoryOutputPipe::new(max_stdio_bytes);
+        let stdout_pipe =
+            StreamingOutputPipe::new(max_stdio_bytes, ToolOutputStream::Stdout, on_output.clone());
+        let stderr_pipe =
+            StreamingOutputPipe::new(max_stdio_bytes, ToolOutputStream::Stderr, on_output);
 
         let mut builder = WasiCtxBuilder::new();
         builder.stdin(stdin_pipe.clone());
@@ -267,6 +314,14 @@ impl WasmSandboxService {
         store
             .set_fuel(fuel_limit)
             .map_err(|e| format!("Failed to set fuel limit: {}", e))?;
+        store.set_epoch_deadline(1);
+        store.epoch_deadline_callback(move |_| {
+            if abort.load(Ordering::SeqCst) {
+                Ok(UpdateDeadline::Interrupt)
+            } else {
+                Ok(UpdateDeadline::Continue(1))
+            }
+        });
 
         let instance = linker
             .instantiate(&mut store, &module)
@@ -333,7 +388,9 @@ impl WasmSandboxService {
             );
         }
         if requests.len() > 4 {
-            return Err("WASM sandbox supports at most 4 networkRequests per execution".to_string());
+            return Err(
+                "WASM sandbox supports at most 4 networkRequests per execution".to_string(),
+            );
         }
 
         let client = reqwest::Client::builder()
@@ -384,7 +441,9 @@ impl WasmSandboxService {
                         .map_err(|e| format!("Invalid JSON from '{}': {}", url, e))?;
                     parsed
                 }
-                "text" => serde_json::Value::String(String::from_utf8_lossy(body_slice).to_string()),
+                "text" => {
+                    serde_json::Value::String(String::from_utf8_lossy(body_slice).to_string())
+                }
                 other => {
                     return Err(format!(
                         "Unsupported networkRequests[{}].responseType '{}' (expected 'text' or 'json')",
@@ -486,8 +545,8 @@ mod tests {
 
     #[test]
     fn map_fs_mode_rejects_invalid_mode() {
-        let err = WasmSandboxService::map_fs_mode("write_only")
-            .expect_err("invalid mode should fail");
+        let err =
+            WasmSandboxService::map_fs_mode("write_only").expect_err("invalid mode should fail");
         assert!(err.contains("Unsupported filesystem permission mode"));
     }
 
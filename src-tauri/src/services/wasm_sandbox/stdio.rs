@@ -0,0 +1,76 @@
+use crate::services::tool_output::{ToolOutputSink, ToolOutputStream};
+use bytes::Bytes;
+use tokio::io::AsyncWrite;
+use wasmtime_wasi::cli::{IsTerminal, StdoutStream};
+use wasmtime_wasi::p2::pipe::MemoryOutputPipe;
+use wasmtime_wasi::p2::{OutputStream, Pollable, StreamResult};
+
+/// Guest stdout/stderr: collected in memory like [`MemoryOutputPipe`], and
+/// also handed to the tool output sink as the guest writes.
+#[derive(Clone)]
+pub(super) struct StreamingOutputPipe {
+    buffer: MemoryOutputPipe,
+    stream: ToolOutputStream,
+    on_output: Option<ToolOutputSink>,
+}
+
+impl StreamingOutputPipe {
+    pub(super) fn new(
+        capacity: usize,
+        stream: ToolOutputStream,
+        on_output: Option<ToolOutputSink>,
+    ) -> Self {
+        Self {
+            buffer: MemoryOutputPipe::new(capacity),
+            stream,
+            on_output,
+        }
+    }
+
+    pub(super) fn contents(&self) -> Bytes {
+        self.buffer.contents()
+    }
+}
+
+impl IsTerminal for StreamingOutputPipe {
+    fn is_terminal(&self) -> bool {
+        false
+    }
+}
+
+impl StdoutStream for StreamingOutputPipe {
+    fn p2_stream(&self) -> Box<dyn OutputStream> {
+        Box::new(self.clone())
+    }
+
+    /// Only WASIp3 guests write through this; they are collected, not streamed.
+    fn async_stream(&self) -> Box<dyn AsyncWrite + Send + Sync> {
+        Box::new(self.buffer.clone())
+    }
+}
+
+#[async_trait::async_trait]
+impl OutputStream for StreamingOutputPipe {
+    fn write(&mut self, bytes: Bytes) -> StreamResult<()> {
+        self.buffer.write(bytes.clone())?;
+        if let Some(sink) = self.on_output.as_ref() {
+            // Guests write whole formatted prints, so a write rarely splits a
+            // character; if one does, the halves decode lossily.
+            sink(self.stream, String::from_utf8_lossy(&bytes).into_owned());
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self) -> StreamResult<()> {
+        self.buffer.flush()
+    }
+
+    fn check_write(&mut self) -> StreamResult<usize> {
+        self.buffer.check_write()
+    }
+}
+
+#[async_trait::async_trait]
+impl Pollable for StreamingOutputPipe {
+    async fn ready(&mut self) {}
+}
@@ -0,0 +1,215 @@
+use super::{fetch, SearchBackend, SearchResult};
+use serde::Deserialize;
+
+const BRAVE_API_BASE: &str = "https://api.search.brave.com/res/v1";
+const TAVILY_API_BASE: &str = "https://api.tavily.com";
+/// Largest page either keyed API returns per request.
+const MAX_API_RESULTS: usize = 20;
+
+/// A SearXNG instance's JSON API. The instance must list `json` under
+/// `search.formats` in its settings.yml.
+pub(super) struct SearxngBackend {
+    client: reqwest::Client,
+    base_url: String,
+}
+
+impl SearxngBackend {
+    pub(super) fn new(client: reqwest::Client, base_url: String) -> Self {
+        Self { client, base_url }
+    }
+}
+
+#[derive(Deserialize)]
+struct SearxngResponse {
+    #[serde(default)]
+    results: Vec<SearxngResult>,
+}
+
+#[derive(Deserialize)]
+struct SearxngResult {
+    #[serde(default)]
+    title: String,
+    #[serde(default)]
+    url: String,
+    #[serde(default)]
+    content: String,
+}
+
+#[async_trait::async_trait]
+impl SearchBackend for SearxngBackend {
+    fn name(&self) -> &'static str {
+        "SearXNG"
+    }
+
+    async fn search(&self, query: &str, limit: usize) -> Result<Vec<SearchResult>, String> {
+        let mut url = reqwest::Url::parse(&format!("{}/search", self.base_url))
+            .map_err(|e| format!("Invalid SearXNG URL: {}", e))?;
+        url.query_pairs_mut()
+            .append_pair("q", query)
+            .append_pair("format", "json");
+        let body = fetch(self.client.get(url).header("accept", "application/json"))
+            .await
+            .map_err(|e| {
+                if e.contains("status 403") {
+                    format!(
+                        "{} (is the json format enabled on the SearXNG instance?)",
+                        e
+                    )
+                } else {
+                    e
+                }
+            })?;
+        let response: SearxngResponse = serde_json::from_slice(&body)
+            .map_err(|e| format!("Unexpected SearXNG response: {}", e))?;
+        Ok(response
+            .results
+            .into_iter()
+            .take(limit)
+            .map(|r| SearchResult {
+                title: r.title,
+                url: r.url,
+                snippet: r.content,
+            })
+            .collect())
+    }
+}
+
+/// Brave Search web results with the user's subscription token.
+pub(super) struct BraveBackend {
+    client: reqwest::Client,
+    base_url: String,
+    api_key: String,
+}
+
+impl BraveBackend {
+    pub(super) fn new(client: reqwest::Client, base_url: Option<String>, api_key: String) -> Self {
+        Self {
+            client,
+            base_url: base_url.unwrap_or_else(|| BRAVE_API_BASE.to_string()),
+            api_key,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct BraveResponse {
+    web: Option<BraveWebResults>,
+}
+
+#[derive(Deserialize)]
+struct BraveWebResults {
+    #[serde(default)]
+    results: Vec<BraveResult>,
+}
+
+#[derive(Deserialize)]
+struct BraveResult {
+    #[serde(default)]
+    title: String,
+    #[serde(default)]
+    url: String,
+    #[serde(default)]
+    description: String,
+}
+
+#[async_trait::async_trait]
+impl SearchBackend for BraveBackend {
+    fn name(&self) -> &'static str {
+        "Brave Search"
+    }
+
+    async fn search(&self, query: &str, limit: usize) -> Result<Vec<SearchResult>, String> {
+        let mut url = reqwest::Url::parse(&format!("{}/web/search", self.base_url))
+            .map_err(|e| format!("Invalid Brave Search URL: {}", e))?;
+        url.query_pairs_mut()
+            .append_pair("q", query)
+            .append_pair("count", &limit.clamp(1, MAX_API_RESULTS).to_string());
+        let body = fetch(
+            self.client
+                .get(url)
+                .header("accept", "application/json")
+                .header("x-subscription-token", &self.api_key),
+        )
+        .await?;
+        let response: BraveResponse = serde_json::from_slice(&body)
+            .map_err(|e| format!("Unexpected Brave Search response: {}", e))?;
+        Ok(response
+            .web
+            .map(|web| web.results)
+            .unwrap_or_default()
+            .into_iter()
+            .take(limit)
+            .map(|r| SearchResult {
+                title: r.title,
+                url: r.url,
+                snippet: r.description,
+            })
+            .collect())
+    }
+}
+
+/// Tavily search with the user's API key.
+pub(super) struct TavilyBackend {
+    client: reqwest::Client,
+    base_url: String,
+    api_key: String,
+}
+
+impl TavilyBackend {
+    pub(super) fn new(client: reqwest::Client, base_url: Option<String>, api_key: String) -> Self {
+        Self {
+            client,
+            base_url: base_url.unwrap_or_else(|| TAVILY_API_BASE.to_string()),
+            api_key,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct TavilyResponse {
+    #[serde(default)]
+    results: Vec<TavilyResult>,
+}
+
+#[derive(Deserialize)]
+struct TavilyResult {
+    #[serde(default)]
+    title: String,
+    #[serde(default)]
+    url: String,
+    #[serde(default)]
+    content: String,
+}
+
+#[async_trait::async_trait]
+impl SearchBackend for TavilyBackend {
+    fn name(&self) -> &'static str {
+        "Tavily"
+    }
+
+    async fn search(&self, query: &str, limit: usize) -> Result<Vec<SearchResult>, String> {
+        let body = fetch(
+            self.client
+                .post(format!("{}/search", self.base_url))
+                .bearer_auth(&self.api_key)
+                .header("accept", "application/json")
+                .json(&serde_json::json!({
+                    "query": query,
+                    "max_results": limit.clamp(1, MAX_API_RESULTS),
+                })),
+        )
+        .await?;
+        let response: TavilyResponse = serde_json::from_slice(&body)
+            .map_err(|e| format!("Unexpected Tavily response: {}", e))?;
+        Ok(response
+            .results
+            .into_iter()
+            .take(limit)
+            .map(|r| SearchResult {
+                title: r.title,
+                url: r.url,
+                snippet: r.content,
+            })
+            .collect())
+    }
+}
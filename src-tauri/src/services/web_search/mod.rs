@@ -0,0 +1,649 @@
+//! Web search backends for the `web_search` tool
+//!
+//! By default `web_search` goes through the Rainy cloud research service. The
+//! backends here let it run against a search service the user picks instead:
+//! a self-hosted SearXNG instance, Brave or Tavily with the user's own key, or
+//! any engine publishing an OpenSearch description. Every backend returns the
+//! same normalized [`SearchResult`]s, and recent queries are answered from a
+//! short-lived cache.
+
+mod backends;
+mod opensearch;
+
+use crate::ai::provider::AIProviderManager;
+use backends::{BraveBackend, SearxngBackend, TavilyBackend};
+use opensearch::OpenSearchBackend;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+
+/// Keychain entry holding the user's Brave Search API key.
+pub const BRAVE_KEY_PROVIDER: &str = "brave_search";
+/// Keychain entry holding the user's Tavily API key.
+pub const TAVILY_KEY_PROVIDER: &str = "tavily";
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(15);
+const MAX_RESPONSE_BYTES: usize = 2 * 1024 * 1024;
+const MAX_SNIPPET_CHARS: usize = 400;
+const CACHE_TTL: Duration = Duration::from_secs(10 * 60);
+const MAX_CACHED_QUERIES: usize = 128;
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum WebSearchProvider {
+    /// Rainy cloud research (requires a Rainy API key)
+    #[default]
+    Rainy,
+    Searxng,
+    Brave,
+    Tavily,
+    OpenSearch,
+}
+
+/// Which backend `web_search` uses, persisted in user settings
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+#[serde(default)]
+pub struct WebSearchConfig {
+    pub provider: WebSearchProvider,
+    /// SearXNG instance URL or OpenSearch description URL. For Brave and
+    /// Tavily, overrides the API endpoint (e.g. a proxy); empty uses the
+    /// public API.
+    pub base_url: Option<String>,
+}
+
+impl WebSearchConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        let base_url = self
+            .base_url
+            .as_deref()
+            .map(str::trim)
+            .filter(|u| !u.is_empty());
+        match (self.provider, base_url) {
+            (WebSearchProvider::Searxng, None) => {
+                return Err("SearXNG search needs the instance URL".to_string())
+            }
+            (WebSearchProvider::OpenSearch, None) => {
+                return Err("OpenSearch search needs the description document URL".to_string())
+            }
+            _ => {}
+        }
+        if let Some(base_url) = base_url {
+            let parsed = url::Url::parse(base_url)
+                .map_err(|e| format!("Invalid search URL '{}': {}", base_url, e))?;
+            if !matches!(parsed.scheme(), "http" | "https") {
+                return Err(format!("Search URL must be http or https: {}", base_url));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// One search hit, the same shape whichever backend produced it
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct SearchResult {
+    pub title: String,
+    pub url: String,
+    pub snippet: String,
+}
+
+#[async_trait::async_trait]
+pub trait SearchBackend: Send + Sync {
+    /// Human-readable backend name, shown alongside results
+    fn name(&self) -> &'static str;
+
+    /// Run `query`, returning at most `limit` results as the backend reports
+    /// them; [`normalize_results`] cleans them up afterwards.
+    async fn search(&self, query: &str, limit: usize) -> Result<Vec<SearchResult>, String>;
+}
+
+/// Runs `web_search` queries against the configured backend.
+pub struct WebSearchService {
+    provider_manager: Arc<AIProviderManager>,
+    client: reqwest::Client,
+    /// Backend for the last configuration used, kept so an OpenSearch
+    /// description is fetched once rather than on every query.
+    backend: Mutex<Option<(BackendKey, Arc<dyn SearchBackend>)>>,
+    cache: Mutex<SearchCache>,
+}
+
+/// What a backend was built from; a change in either rebuilds it.
+#[derive(PartialEq, Eq)]
+struct BackendKey {
+    config: WebSearchConfig,
+    api_key: Option<String>,
+}
+
+impl WebSearchService {
+    pub fn new(provider_manager: Arc<AIProviderManager>) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .user_agent("rainy-cowork-agent/1.0")
+            .build()
+            .unwrap_or_default();
+        Self {
+            provider_manager,
+            client,
+            backend: Mutex::new(None),
+            cache: Mutex::new(SearchCache::default()),
+        }
+    }
+
+    /// Search with the backend `config` selects. Returns the backend's name
+    /// and up to `limit` normalized results.
+    pub async fn search(
+        &self,
+        config: &WebSearchConfig,
+        query: &str,
+        limit: usize,
+    ) -> Result<(&'static str, Vec<SearchResult>), String> {
+        let query = query.trim();
+        if query.is_empty() {
+            return Err("Search query is empty".to_string());
+        }
+        let backend = self.backend_for(config).await?;
+
+        let cache_key = (config.clone(), query.to_lowercase(), limit);
+        if let Some(results) = self.cache.lock().await.get(&cache_key) {
+            return Ok((backend.name(), results));
+        }
+
+        let results = normalize_results(backend.search(query, limit).await?, limit);
+        self.cache.lock().await.insert(cache_key, results.clone());
+        Ok((backend.name(), results))
+    }
+
+    async fn backend_for(
+        &self,
+        config: &WebSearchConfig,
+    ) -> Result<Arc<dyn SearchBackend>, String> {
+        config.validate()?;
+        let api_key = match config.provider {
+            WebSearchProvider::Brave => Some(
+                self.require_api_key(BRAVE_KEY_PROVIDER, "Brave Search")
+                    .await?,
+            ),
+            WebSearchProvider::Tavily => {
+                Some(self.require_api_key(TAVILY_KEY_PROVIDER, "Tavily").await?)
+            }
+            _ => None,
+        };
+        let key = BackendKey {
+            config: config.clone(),
+            api_key,
+        };
+
+        let mut current = self.backend.lock().await;
+        if let Some((built_from, backend)) = current.as_ref() {
+            if *built_from == key {
+                return Ok(backend.clone());
+            }
+        }
+        let backend = build_backend(self.client.clone(), &key.config, key.api_key.clone())?;
+        *current = Some((key, backend.clone()));
+        Ok(backend)
+    }
+
+    async fn require_api_key(&self, provider: &str, label: &str) -> Result<String, String> {
+        match self.provider_manager.get_api_key(provider).await {
+            Ok(Some(key)) if !key.trim().is_empty() => Ok(key),
+            Ok(_) => Err(format!(
+                "No {} API key configured. Add it in settings to use {} search.",
+                label, label
+            )),
+            Err(e) => Err(format!("Failed to retrieve {} API key: {}", label, e)),
+        }
+    }
+}
+
+fn build_backend(
+    client: reqwest::Client,
+    config: &WebSearchConfig,
+    api_key: Option<String>,
+) -> Result<Arc<dyn SearchBackend>, String> {
+    let base_url = config
+        .base_url
+        .as_deref()
+        .map(|u| u.trim().trim_end_matches('/'))
+        .filter(|u| !u.is_empty())
+        .map(str::to_string);
+    let backend: Arc<dyn SearchBackend> = match config.provider {
+        WebSearchProvider::Rainy => {
+            return Err("Rainy cloud research is not a search backend".to_string())
+        }
+        WebSearchProvider::Searxng => {
+            Arc::new(SearxngBackend::new(client, base_url.unwrap_or_default()))
+        }
+        WebSearchProvider::Brave => Arc::new(BraveBackend::new(
+            client,
+            base_url,
+            api_key.unwrap_or_default(),
+        )),
+        WebSearchProvider::Tavily => Arc::new(TavilyBackend::new(
+            client,
+            base_url,
+            api_key.unwrap_or_default(),
+        )),
+        WebSearchProvider::OpenSearch => {
+            Arc::new(OpenSearchBackend::new(client, base_url.unwrap_or_default()))
+        }
+    };
+    Ok(backend)
+}
+
+/// Send a request, retrying connection failures, 5xx and 429 like the other
+/// web tools, and return the body of a successful response.
+async fn fetch(request: reqwest::RequestBuilder) -> Result<Vec<u8>, String> {
+    let mut last_error: Option<String> = None;
+    for attempt in 0..=2u64 {
+        let Some(attempt_request) = request.try_clone() else {
+            return Err("Search request cannot be retried".to_string());
+        };
+        match attempt_request.send().await {
+            Ok(resp) => {
+                let status = resp.status();
+                if (status.is_server_error() || status.as_u16() == 429) && attempt < 2 {
+                    last_error = Some(format!("status {}", status));
+                    tokio::time::sleep(Duration::from_millis(250 * (attempt + 1))).await;
+                    continue;
+                }
+                if !status.is_success() {
+                    let url = resp.url().clone();
+                    return Err(format!(
+                        "Search request failed with status {} for {}{}",
+                        status,
+                        url.origin().ascii_serialization(),
+                        url.path()
+                    ));
+                }
+                let bytes = resp
+                    .bytes()
+                    .await
+                    .map_err(|e| format!("Failed to read search response: {}", e))?;
+                if bytes.len() > MAX_RESPONSE_BYTES {
+                    return Err(format!(
+                        "Search response size {} exceeds {} bytes",
+                        bytes.len(),
+                        MAX_RESPONSE_BYTES
+                    ));
+                }
+                return Ok(bytes.to_vec());
+            }
+            Err(e) => {
+                last_error = Some(e.to_string());
+                if attempt < 2 {
+                    tokio::time::sleep(Duration::from_millis(250 * (attempt + 1))).await;
+                }
+            }
+        }
+    }
+    Err(format!(
+        "Search request failed after retries: {}",
+        last_error.unwrap_or_else(|| "unknown error".to_string())
+    ))
+}
+
+/// Clean up raw backend results: strip markup and collapse whitespace in
+/// titles and snippets, drop anything that isn't an http(s) link, remove
+/// duplicate URLs and keep at most `limit`.
+pub fn normalize_results(raw: Vec<SearchResult>, limit: usize) -> Vec<SearchResult> {
+    let mut seen = HashSet::new();
+    raw.into_iter()
+        .filter_map(|result| {
+            let url = url::Url::parse(result.url.trim()).ok()?;
+            if !matches!(url.scheme(), "http" | "https") || url.host_str().is_none() {
+                return None;
+            }
+            let url = url.to_string();
+            if !seen.insert(url.clone()) {
+                return None;
+            }
+            let title = clean_text(&result.title);
+            let mut snippet = clean_text(&result.snippet);
+            if let Some((cut, _)) = snippet.char_indices().nth(MAX_SNIPPET_CHARS) {
+                snippet.truncate(cut);
+                snippet.push('…');
+            }
+            Some(SearchResult {
+                title: if title.is_empty() { url.clone() } else { title },
+                url,
+                snippet,
+            })
+        })
+        .take(limit)
+        .collect()
+}
+
+/// Plain text from a title or snippet that may carry HTML highlighting.
+fn clean_text(text: &str) -> String {
+    static TAGS: OnceLock<Regex> = OnceLock::new();
+    let tags = TAGS.get_or_init(|| Regex::new(r"<[^>]*>").expect("valid tag pattern"));
+    let text = tags.replace_all(text, "");
+    let text = text
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&#x27;", "'")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&nbsp;", " ")
+        .replace("&amp;", "&");
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+type CacheKey = (WebSearchConfig, String, usize);
+
+/// Results of recent queries, so an agent repeating a search doesn't pay for
+/// it again. Entries expire after [`CACHE_TTL`]; the oldest is evicted when
+/// full.
+#[derive(Default)]
+struct SearchCache {
+    entries: HashMap<CacheKey, (Instant, Vec<SearchResult>)>,
+}
+
+impl SearchCache {
+    fn get(&mut self, key: &CacheKey) -> Option<Vec<SearchResult>> {
+        match self.entries.get(key) {
+            Some((at, results)) if at.elapsed() < CACHE_TTL => Some(results.clone()),
+            Some(_) => {
+                self.entries.remove(key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    fn insert(&mut self, key: CacheKey, results: Vec<SearchResult>) {
+        self.entries.retain(|_, (at, _)| at.elapsed() < CACHE_TTL);
+        if self.entries.len() >= MAX_CACHED_QUERIES {
+            if let Some(oldest) = self
+                .entries
+                .iter()
+                .min_by_key(|(_, (at, _))| *at)
+                .map(|(k, _)| k.clone())
+            {
+                self.entries.remove(&oldest);
+            }
+        }
+        self.entries.insert(key, (Instant::now(), results));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex as StdMutex;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    /// Stand-in search server: answers each request with the route whose path
+    /// prefix matches, and records the request head and body.
+    struct StandIn {
+        base_url: String,
+        requests: Arc<StdMutex<Vec<String>>>,
+    }
+
+    async fn stand_in(routes: Vec<(&'static str, &'static str, String)>) -> StandIn {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(StdMutex::new(Vec::new()));
+        let log = requests.clone();
+        tokio::spawn(async move {
+            loop {
+                let Ok((mut socket, _)) = listener.accept().await else {
+                    return;
+                };
+                let mut raw = Vec::new();
+                let mut buf = [0u8; 4096];
+                let head_end = loop {
+                    let n = socket.read(&mut buf).await.unwrap_or(0);
+                    if n == 0 {
+                        break None;
+                    }
+                    raw.extend_from_slice(&buf[..n]);
+                    if let Some(pos) = raw.windows(4).position(|w| w == b"\r\n\r\n") {
+                        break Some(pos + 4);
+                    }
+                };
+                let Some(head_end) = head_end else { continue };
+                let head = String::from_utf8_lossy(&raw[..head_end]).to_string();
+                let body_len = head
+                    .lines()
+                    .find_map(|l| {
+                        let (name, value) = l.split_once(':')?;
+                        name.eq_ignore_ascii_case("content-length")
+                            .then(|| value.trim().parse::<usize>().ok())?
+                    })
+                    .unwrap_or(0);
+                while raw.len() < head_end + body_len {
+                    let n = socket.read(&mut buf).await.unwrap_or(0);
+                    if n == 0 {
+                        break;
+                    }
+                    raw.extend_from_slice(&buf[..n]);
+                }
+                log.lock()
+                    .unwrap()
+                    .push(String::from_utf8_lossy(&raw).to_string());
+
+                let path = head.split_whitespace().nth(1).unwrap_or("/");
+                let response = match routes.iter().find(|(prefix, _, _)| path.starts_with(prefix)) {
+                    Some((_, content_type, body)) => format!(
+                        "HTTP/1.1 200 OK\r\ncontent-type: {}\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+                        content_type,
+                        body.len(),
+                        body
+                    ),
+                    None => "HTTP/1.1 404 Not Found\r\ncontent-length: 0\r\nconnection: close\r\n\r\n"
+                        .to_string(),
+                };
+                let _ = socket.write_all(response.as_bytes()).await;
+                let _ = socket.shutdown().await;
+            }
+        });
+        StandIn { base_url, requests }
+    }
+
+    fn config(provider: WebSearchProvider, base_url: &str) -> WebSearchConfig {
+        WebSearchConfig {
+            provider,
+            base_url: Some(base_url.to_string()),
+        }
+    }
+
+    fn service() -> WebSearchService {
+        WebSearchService::new(Arc::new(AIProviderManager::new()))
+    }
+
+    #[tokio::test]
+    async fn searxng_results_are_normalized() {
+        let body = serde_json::json!({
+            "query": "rust async",
+            "results": [
+                {
+                    "title": "  The <b>Rust</b> Async   Book ",
+                    "url": "https://rust-lang.github.io/async-book/",
+                    "content": "Asynchronous <em>programming</em> in Rust &amp; more"
+                },
+                {
+                    "title": "Duplicate",
+                    "url": "https://rust-lang.github.io/async-book/",
+                    "content": "same page again"
+                },
+                { "title": "Not a web page", "url": "ftp://example.com/file", "content": "" },
+                { "title": "", "url": "https://tokio.rs/", "content": "x".repeat(1000) }
+            ]
+        })
+        .to_string();
+        let server = stand_in(vec![("/search", "application/json", body)]).await;
+
+        let (name, results) = service()
+            .search(
+                &config(WebSearchProvider::Searxng, &server.base_url),
+                "rust async",
+                8,
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(name, "SearXNG");
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].title, "The Rust Async Book");
+        assert_eq!(
+            results[0].snippet,
+            "Asynchronous programming in Rust & more"
+        );
+        assert_eq!(results[1].title, "https://tokio.rs/");
+        assert_eq!(results[1].snippet.chars().count(), MAX_SNIPPET_CHARS + 1);
+        let request = server.requests.lock().unwrap()[0].clone();
+        assert!(request.starts_with("GET /search?q=rust+async&format=json"));
+    }
+
+    #[tokio::test]
+    async fn keyed_backends_send_the_users_key() {
+        let brave = serde_json::json!({
+            "web": { "results": [
+                { "title": "Brave hit", "url": "https://brave.example/a", "description": "from <strong>Brave</strong>" }
+            ]}
+        })
+        .to_string();
+        let tavily = serde_json::json!({
+            "results": [
+                { "title": "Tavily hit", "url": "https://tavily.example/b", "content": "from Tavily" }
+            ]
+        })
+        .to_string();
+        let server = stand_in(vec![
+            ("/web/search", "application/json", brave),
+            ("/search", "application/json", tavily),
+        ])
+        .await;
+        let client = reqwest::Client::new();
+
+        let backend = build_backend(
+            client.clone(),
+            &config(WebSearchProvider::Brave, &server.base_url),
+            Some("brave-key".to_string()),
+        )
+        .unwrap();
+        let results = normalize_results(backend.search("llamas", 5).await.unwrap(), 5);
+        assert_eq!(results[0].snippet, "from Brave");
+
+        let backend = build_backend(
+            client,
+            &config(WebSearchProvider::Tavily, &server.base_url),
+            Some("tavily-key".to_string()),
+        )
+        .unwrap();
+        let results = normalize_results(backend.search("llamas", 5).await.unwrap(), 5);
+        assert_eq!(results[0].url, "https://tavily.example/b");
+
+        let requests = server.requests.lock().unwrap().clone();
+        let brave_request = requests[0].to_ascii_lowercase();
+        assert!(brave_request.starts_with("get /web/search?q=llamas&count=5"));
+        assert!(brave_request.contains("x-subscription-token: brave-key"));
+        let tavily_request = requests[1].to_ascii_lowercase();
+        assert!(tavily_request.starts_with("post /search"));
+        assert!(tavily_request.contains("authorization: bearer tavily-key"));
+        assert!(tavily_request.contains("\"max_results\":5"));
+    }
+
+    #[tokio::test]
+    async fn opensearch_description_is_followed() {
+        // The description names the results server's address, so the two are
+        // served separately.
+        let results_server = stand_in(vec![(
+            "/rss",
+            "application/rss+xml",
+            r#"<?xml version="1.0"?>
+<rss version="2.0" xmlns:opensearch="http://a9.com/-/spec/opensearch/1.1/">
+  <channel>
+    <title>Results</title>
+    <item>
+      <title>Tom &amp; Jerry</title>
+      <link>https://cartoons.example/tom-and-jerry</link>
+      <description><![CDATA[A <b>cat</b> and a mouse]]></description>
+    </item>
+    <item>
+      <title>Second</title>
+      <link>https://cartoons.example/second</link>
+      <description>Another result</description>
+    </item>
+  </channel>
+</rss>"#
+                .to_string(),
+        )])
+        .await;
+        let description = format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<OpenSearchDescription xmlns="http://a9.com/-/spec/opensearch/1.1/">
+  <ShortName>Stand-in</ShortName>
+  <Url type="text/html" template="{base}/html?q={{searchTerms}}"/>
+  <Url type="application/rss+xml" template="{base}/rss?q={{searchTerms}}&amp;n={{count?}}&amp;lang={{language?}}"/>
+</OpenSearchDescription>"#,
+            base = results_server.base_url
+        );
+        let description_server = stand_in(vec![(
+            "/opensearch.xml",
+            "application/opensearchdescription+xml",
+            description,
+        )])
+        .await;
+
+        let service = service();
+        let config = config(
+            WebSearchProvider::OpenSearch,
+            &format!("{}/opensearch.xml", description_server.base_url),
+        );
+        let (_, results) = service.search(&config, "cat & mouse", 8).await.unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].title, "Tom & Jerry");
+        assert_eq!(results[0].url, "https://cartoons.example/tom-and-jerry");
+        assert_eq!(results[0].snippet, "A cat and a mouse");
+
+        service.search(&config, "second query", 8).await.unwrap();
+        assert_eq!(description_server.requests.lock().unwrap().len(), 1);
+        let requests = results_server.requests.lock().unwrap().clone();
+        assert!(requests[0].starts_with("GET /rss?q=cat+%26+mouse&n=8&lang= "));
+    }
+
+    #[tokio::test]
+    async fn repeated_queries_are_served_from_cache() {
+        let body = serde_json::json!({
+            "results": [{ "title": "Hit", "url": "https://example.com/", "content": "" }]
+        })
+        .to_string();
+        let server = stand_in(vec![("/search", "application/json", body)]).await;
+        let service = service();
+        let config = config(WebSearchProvider::Searxng, &server.base_url);
+
+        service.search(&config, "weather", 8).await.unwrap();
+        service.search(&config, "  Weather ", 8).await.unwrap();
+        assert_eq!(server.requests.lock().unwrap().len(), 1);
+
+        service.search(&config, "forecast", 8).await.unwrap();
+        assert_eq!(server.requests.lock().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn config_requires_an_endpoint_for_self_hosted_backends() {
+        assert!(WebSearchConfig::default().validate().is_ok());
+        assert!(WebSearchConfig {
+            provider: WebSearchProvider::Searxng,
+            base_url: None,
+        }
+        .validate()
+        .is_err());
+        assert!(config(WebSearchProvider::OpenSearch, "file:///etc/passwd")
+            .validate()
+            .is_err());
+        assert!(WebSearchConfig {
+            provider: WebSearchProvider::Brave,
+            base_url: None,
+        }
+        .validate()
+        .is_ok());
+    }
+}
@@ -0,0 +1,304 @@
+use super::{fetch, SearchBackend, SearchResult};
+use quick_xml::escape::resolve_predefined_entity;
+use quick_xml::events::{BytesRef, BytesStart, Event};
+use quick_xml::Reader;
+use regex::{Captures, Regex};
+use std::sync::OnceLock;
+use tokio::sync::OnceCell;
+
+/// Result formats this backend can read, most preferred first.
+const FEED_TYPES: &[&str] = &["application/rss+xml", "application/atom+xml"];
+
+/// Any engine that publishes an OpenSearch description document with an RSS
+/// or Atom results template. The description is fetched on first use.
+pub(super) struct OpenSearchBackend {
+    client: reqwest::Client,
+    description_url: String,
+    template: OnceCell<UrlTemplate>,
+}
+
+/// A `<Url>` element of the description: its template and the offsets the
+/// engine counts pages and results from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct UrlTemplate {
+    template: String,
+    index_offset: u32,
+    page_offset: u32,
+}
+
+impl OpenSearchBackend {
+    pub(super) fn new(client: reqwest::Client, description_url: String) -> Self {
+        Self {
+            client,
+            description_url,
+            template: OnceCell::new(),
+        }
+    }
+
+    async fn template(&self) -> Result<&UrlTemplate, String> {
+        self.template
+            .get_or_try_init(|| async {
+                let body = fetch(self.client.get(&self.description_url).header(
+                    "accept",
+                    "application/opensearchdescription+xml, application/xml;q=0.9",
+                ))
+                .await?;
+                parse_description(&String::from_utf8_lossy(&body))
+            })
+            .await
+    }
+}
+
+#[async_trait::async_trait]
+impl SearchBackend for OpenSearchBackend {
+    fn name(&self) -> &'static str {
+        "OpenSearch"
+    }
+
+    async fn search(&self, query: &str, limit: usize) -> Result<Vec<SearchResult>, String> {
+        let url = fill_template(self.template().await?, query, limit)?;
+        let url = reqwest::Url::parse(&url)
+            .map_err(|e| format!("Invalid OpenSearch results URL: {}", e))?;
+        let body = fetch(
+            self.client
+                .get(url)
+                .header("accept", "application/rss+xml, application/atom+xml;q=0.9"),
+        )
+        .await?;
+        let mut results = parse_feed(&String::from_utf8_lossy(&body))?;
+        results.truncate(limit);
+        Ok(results)
+    }
+}
+
+/// Pick the results template from an OpenSearch description document.
+fn parse_description(xml: &str) -> Result<UrlTemplate, String> {
+    let mut reader = Reader::from_str(xml);
+    let mut templates: Vec<(String, UrlTemplate)> = Vec::new();
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(e)) | Ok(Event::Empty(e)) if e.local_name().as_ref() == b"Url" => {
+                let rel = attribute(&e, b"rel").unwrap_or_else(|| "results".to_string());
+                if rel != "results" {
+                    continue;
+                }
+                let (Some(kind), Some(template)) =
+                    (attribute(&e, b"type"), attribute(&e, b"template"))
+                else {
+                    continue;
+                };
+                let offset = |name: &[u8]| {
+                    attribute(&e, name)
+                        .and_then(|v| v.parse().ok())
+                        .unwrap_or(1)
+                };
+                templates.push((
+                    kind,
+                    UrlTemplate {
+                        template,
+                        index_offset: offset(b"indexOffset"),
+                        page_offset: offset(b"pageOffset"),
+                    },
+                ));
+            }
+            Ok(Event::Eof) => break,
+            Ok(_) => {}
+            Err(e) => return Err(format!("Invalid OpenSearch description: {}", e)),
+        }
+    }
+
+    FEED_TYPES
+        .iter()
+        .find_map(|feed| {
+            templates
+                .iter()
+                .find(|(kind, _)| kind.split(';').next().map(str::trim) == Some(*feed))
+                .map(|(_, template)| template.clone())
+        })
+        .ok_or_else(|| "OpenSearch description has no RSS or Atom results template".to_string())
+}
+
+/// Substitute the query into a template, filling the standard parameters and
+/// leaving unknown optional ones (`{name?}`) empty.
+fn fill_template(template: &UrlTemplate, query: &str, limit: usize) -> Result<String, String> {
+    static PARAM: OnceLock<Regex> = OnceLock::new();
+    let param =
+        PARAM.get_or_init(|| Regex::new(r"\{([^}?]+)(\??)\}").expect("valid param pattern"));
+
+    let mut missing = None;
+    let url = param.replace_all(&template.template, |caps: &Captures| match &caps[1] {
+        "searchTerms" => url::form_urlencoded::byte_serialize(query.as_bytes()).collect(),
+        "count" => limit.to_string(),
+        "startIndex" => template.index_offset.to_string(),
+        "startPage" => template.page_offset.to_string(),
+        _ if &caps[2] == "?" => String::new(),
+        "language" => "*".to_string(),
+        "inputEncoding" | "outputEncoding" => "UTF-8".to_string(),
+        name => {
+            missing.get_or_insert_with(|| name.to_string());
+            String::new()
+        }
+    });
+    match missing {
+        Some(name) => Err(format!(
+            "OpenSearch template needs unsupported parameter '{}'",
+            name
+        )),
+        None => Ok(url.into_owned()),
+    }
+}
+
+/// Read results from an RSS 2.0 or Atom feed.
+fn parse_feed(xml: &str) -> Result<Vec<SearchResult>, String> {
+    let mut reader = Reader::from_str(xml);
+    let mut results = Vec::new();
+    let mut current: Option<SearchResult> = None;
+    // Element whose text is being collected, and the field it goes to.
+    let mut field: Option<(Vec<u8>, Field)> = None;
+
+    loop {
+        let event = reader
+            .read_event()
+            .map_err(|e| format!("Invalid OpenSearch results feed: {}", e))?;
+        match event {
+            Event::Start(e) => {
+                let name = e.local_name().as_ref().to_vec();
+                if matches!(name.as_slice(), b"item" | b"entry") {
+                    current = Some(SearchResult {
+                        title: String::new(),
+                        url: String::new(),
+                        snippet: String::new(),
+                    });
+                    continue;
+                }
+                let Some(result) = current.as_mut() else {
+                    continue;
+                };
+                if field.is_some() {
+                    continue;
+                }
+                field = match name.as_slice() {
+                    b"title" => Some((name, Field::Title)),
+                    b"link" if attribute(&e, b"href").is_some() => {
+                        take_atom_link(&e, result);
+                        None
+                    }
+                    b"link" => Some((name, Field::Url)),
+                    b"description" | b"summary" | b"content" if result.snippet.is_empty() => {
+                        Some((name, Field::Snippet))
+                    }
+                    _ => None,
+                };
+            }
+            Event::Empty(e) if e.local_name().as_ref() == b"link" => {
+                if let Some(result) = current.as_mut() {
+                    take_atom_link(&e, result);
+                }
+            }
+            Event::Text(e) => push_text(&mut current, &field, &e.decode().unwrap_or_default()),
+            Event::CData(e) => push_text(&mut current, &field, &e.decode().unwrap_or_default()),
+            Event::GeneralRef(e) => push_text(&mut current, &field, &resolve_reference(&e)),
+            Event::End(e) => {
+                let name = e.local_name();
+                if matches!(name.as_ref(), b"item" | b"entry") {
+                    results.extend(current.take());
+                } else if field
+                    .as_ref()
+                    .is_some_and(|(open, _)| open == name.as_ref())
+                {
+                    field = None;
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    Ok(results)
+}
+
+#[derive(Clone, Copy)]
+enum Field {
+    Title,
+    Url,
+    Snippet,
+}
+
+fn push_text(current: &mut Option<SearchResult>, field: &Option<(Vec<u8>, Field)>, text: &str) {
+    let (Some(result), Some((_, field))) = (current.as_mut(), field) else {
+        return;
+    };
+    match field {
+        Field::Title => result.title.push_str(text),
+        Field::Url => result.url.push_str(text),
+        Field::Snippet => result.snippet.push_str(text),
+    }
+}
+
+/// Atom links carry the address in `href`; take the page link, not related ones.
+fn take_atom_link(e: &BytesStart, result: &mut SearchResult) {
+    let rel = attribute(e, b"rel").unwrap_or_else(|| "alternate".to_string());
+    if rel != "alternate" || !result.url.is_empty() {
+        return;
+    }
+    if let Some(href) = attribute(e, b"href") {
+        result.url = href;
+    }
+}
+
+fn resolve_reference(e: &BytesRef) -> String {
+    if let Ok(Some(ch)) = e.resolve_char_ref() {
+        return ch.to_string();
+    }
+    let name = e.decode().unwrap_or_default();
+    resolve_predefined_entity(&name)
+        .map(str::to_string)
+        .unwrap_or_else(|| format!("&{};", name))
+}
+
+fn attribute(e: &BytesStart, name: &[u8]) -> Option<String> {
+    e.try_get_attribute(name)
+        .ok()
+        .flatten()
+        .and_then(|a| a.unescape_value().ok())
+        .map(|v| v.into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn atom_feeds_and_template_parameters() {
+        let feed = r#"<feed xmlns="http://www.w3.org/2005/Atom">
+  <title>Results</title>
+  <entry>
+    <title type="html">Atom &lt;b&gt;entry&lt;/b&gt;</title>
+    <link rel="self" href="https://engine.example/api/1"/>
+    <link href="https://pages.example/one"/>
+    <summary>First summary</summary>
+    <content>Full content that should not replace the summary</content>
+  </entry>
+</feed>"#;
+        let results = parse_feed(feed).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].title, "Atom <b>entry</b>");
+        assert_eq!(results[0].url, "https://pages.example/one");
+        assert_eq!(results[0].snippet, "First summary");
+
+        let template = UrlTemplate {
+            template: "https://engine.example/s?q={searchTerms}&p={startPage?}&f={custom:filter?}"
+                .to_string(),
+            index_offset: 1,
+            page_offset: 0,
+        };
+        assert_eq!(
+            fill_template(&template, "a b", 5).unwrap(),
+            "https://engine.example/s?q=a+b&p=0&f="
+        );
+        let template = UrlTemplate {
+            template: "https://engine.example/s?q={searchTerms}&k={apiKey}".to_string(),
+            ..template
+        };
+        assert!(fill_template(&template, "a b", 5).is_err());
+    }
+}
@@ -0,0 +1,222 @@
+//! Ignore-file matching for the workspace index.
+//!
+//! Supports the common gitignore subset: `*`, `?`, `**`, character classes,
+//! `!` negation, a leading `/` (or any inner `/`) anchoring the pattern to the
+//! ignore file's directory, and a trailing `/` matching directories only.
+//! Patterns from deeper directories override shallower ones; within a file
+//! the last matching pattern wins.
+
+use regex::Regex;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Ignore files read from every directory, in precedence order.
+const IGNORE_FILES: &[&str] = &[".gitignore", ".ignore", ".rainyignore"];
+/// Directories never indexed, whatever the ignore files say.
+const ALWAYS_SKIPPED_DIRS: &[&str] = &["node_modules", "target", "dist", "build", "__pycache__"];
+
+#[derive(Debug, Clone)]
+struct Pattern {
+    regex: Regex,
+    negated: bool,
+    dir_only: bool,
+}
+
+#[derive(Debug, Default)]
+pub struct IgnoreRules {
+    root: PathBuf,
+    /// Directory -> patterns from its ignore files, relative to that directory.
+    by_dir: HashMap<PathBuf, Vec<Pattern>>,
+}
+
+impl IgnoreRules {
+    pub fn new(root: &Path) -> Self {
+        let mut rules = Self {
+            root: root.to_path_buf(),
+            by_dir: HashMap::new(),
+        };
+        rules.load_dir(root);
+        rules
+    }
+
+    /// (Re)read the ignore files in `dir`.
+    pub fn load_dir(&mut self, dir: &Path) {
+        let mut patterns = Vec::new();
+        for name in IGNORE_FILES {
+            if let Ok(body) = std::fs::read_to_string(dir.join(name)) {
+                patterns.extend(body.lines().filter_map(parse_pattern));
+            }
+        }
+        self.by_dir.insert(dir.to_path_buf(), patterns);
+    }
+
+    /// If `path` is an ignore file, re-read its directory and return true.
+    pub fn reload_if_ignore_file(&mut self, path: &Path) -> bool {
+        let is_ignore_file = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .is_some_and(|name| IGNORE_FILES.contains(&name));
+        if !is_ignore_file {
+            return false;
+        }
+        if let Some(dir) = path.parent() {
+            self.load_dir(dir);
+        }
+        true
+    }
+
+    /// Whether `path` (inside the root) should be skipped. Ignore files of
+    /// its ancestors are loaded on demand, and an ignored ancestor directory
+    /// ignores everything below it.
+    pub fn is_ignored(&mut self, path: &Path, is_dir: bool) -> bool {
+        let Ok(relative) = path.strip_prefix(&self.root) else {
+            return true;
+        };
+        let components = relative.components().collect::<Vec<_>>();
+        let mut current = self.root.clone();
+        for (idx, component) in components.iter().enumerate() {
+            let component_is_dir = is_dir || idx + 1 < components.len();
+            current.push(component);
+            if self.matches(&current, component_is_dir) {
+                return true;
+            }
+            if component_is_dir && !self.by_dir.contains_key(&current) {
+                self.load_dir(&current);
+            }
+        }
+        false
+    }
+
+    fn matches(&self, path: &Path, is_dir: bool) -> bool {
+        let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+        if is_dir && (name.starts_with('.') || ALWAYS_SKIPPED_DIRS.contains(&name)) {
+            return true;
+        }
+
+        let mut ignored = false;
+        // Shallowest directory first so deeper ignore files take precedence.
+        let mut dirs = path
+            .ancestors()
+            .skip(1)
+            .take_while(|a| a.starts_with(&self.root))
+            .collect::<Vec<_>>();
+        dirs.reverse();
+        for dir in dirs {
+            let Some(patterns) = self.by_dir.get(dir) else {
+                continue;
+            };
+            let Ok(relative) = path.strip_prefix(dir) else {
+                continue;
+            };
+            let relative = relative.to_string_lossy().replace('\\', "/");
+            for pattern in patterns {
+                if pattern.dir_only && !is_dir {
+                    continue;
+                }
+                if pattern.regex.is_match(&relative) {
+                    ignored = !pattern.negated;
+                }
+            }
+        }
+        ignored
+    }
+}
+
+fn parse_pattern(line: &str) -> Option<Pattern> {
+    let line = line.trim_end();
+    if line.is_empty() || line.starts_with('#') {
+        return None;
+    }
+    let (negated, line) = match line.strip_prefix('!') {
+        Some(rest) => (true, rest),
+        None => (false, line.strip_prefix('\\').unwrap_or(line)),
+    };
+    let (dir_only, line) = match line.strip_suffix('/') {
+        Some(rest) => (true, rest),
+        None => (false, line),
+    };
+    let anchored = line.contains('/');
+    let glob = line.trim_start_matches('/');
+    if glob.is_empty() {
+        return None;
+    }
+
+    let prefix = if anchored { "^" } else { "^(?:.*/)?" };
+    let regex = Regex::new(&format!("{}{}$", prefix, glob_to_regex(glob))).ok()?;
+    Some(Pattern {
+        regex,
+        negated,
+        dir_only,
+    })
+}
+
+fn glob_to_regex(glob: &str) -> String {
+    let chars = glob.chars().collect::<Vec<_>>();
+    let mut out = String::new();
+    let mut i = 0;
+    while i < chars.len() {
+        match chars[i] {
+            '*' if chars.get(i + 1) == Some(&'*') => {
+                if chars.get(i + 2) == Some(&'/') {
+                    out.push_str("(?:.*/)?");
+                    i += 3;
+                } else {
+                    out.push_str(".*");
+                    i += 2;
+                }
+                continue;
+            }
+            '*' => out.push_str("[^/]*"),
+            '?' => out.push_str("[^/]"),
+            '[' => match chars[i + 1..].iter().position(|c| *c == ']') {
+                Some(len) => {
+                    let class = chars[i + 1..i + 1 + len].iter().collect::<String>();
+                    let class = match class.strip_prefix('!') {
+                        Some(rest) => format!("^{}", rest),
+                        None => class.clone(),
+                    };
+                    out.push('[');
+                    out.push_str(&class.replace('\\', "\\\\"));
+                    out.push(']');
+                    i += len + 2;
+                    continue;
+                }
+                None => out.push_str("\\["),
+            },
+            c => out.push_str(&regex::escape(&c.to_string())),
+        }
+        i += 1;
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gitignore_patterns_anchor_negate_and_nest() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let root = temp.path();
+        std::fs::create_dir_all(root.join("src/generated")).unwrap();
+        std::fs::write(
+            root.join(".gitignore"),
+            "# build output\n*.log\n!keep.log\n/secrets.txt\ncoverage/\ndocs/**/*.tmp\n",
+        )
+        .unwrap();
+        std::fs::write(root.join("src/.gitignore"), "generated/\n").unwrap();
+
+        let mut rules = IgnoreRules::new(root);
+        assert!(rules.is_ignored(&root.join("debug.log"), false));
+        assert!(rules.is_ignored(&root.join("src/nested/trace.log"), false));
+        assert!(!rules.is_ignored(&root.join("keep.log"), false));
+        assert!(rules.is_ignored(&root.join("secrets.txt"), false));
+        assert!(!rules.is_ignored(&root.join("src/secrets.txt"), false));
+        assert!(rules.is_ignored(&root.join("coverage/index.html"), false));
+        assert!(rules.is_ignored(&root.join("docs/a/b/draft.tmp"), false));
+        assert!(rules.is_ignored(&root.join("src/generated/api.rs"), false));
+        assert!(!rules.is_ignored(&root.join("src/lib.rs"), false));
+        assert!(rules.is_ignored(&root.join("node_modules/pkg/index.js"), false));
+        assert!(rules.is_ignored(&root.join(".git/config"), false));
+    }
+}
@@ -0,0 +1,803 @@
+//! Incremental workspace index
+//!
+//! Keeps one searchable index per workspace root (an entry of a workspace's
+//! `allowed_paths`). A root is walked once when first used, then a `notify`
+//! watcher re-indexes files as they change. Files are tracked by content hash
+//! so unchanged files are never re-chunked or re-embedded; chunks live in the
+//! memory vault under one partition per root.
+
+mod ignore;
+
+pub use ignore::IgnoreRules;
+
+use crate::services::knowledge_base::extract_text;
+use crate::services::memory::{
+    ChunkOptions, MemoryEntry, MemoryManager, RetrievalStrategy, SearchOptions,
+};
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, UNIX_EPOCH};
+use tokio::sync::{mpsc, Mutex, RwLock};
+use walkdir::WalkDir;
+
+const STATE_DIR: &str = "workspace_index";
+const MAX_INDEXED_FILE_BYTES: u64 = 2 * 1024 * 1024;
+const MAX_INDEXED_PDF_BYTES: u64 = 25 * 1024 * 1024;
+const WATCH_DEBOUNCE_MS: u64 = 1000;
+/// Persist progress periodically during the first walk of a large root.
+const SAVE_EVERY_CHANGES: usize = 200;
+const SNIPPET_CHARS: usize = 600;
+const INDEXED_EXTENSIONS: &[&str] = &[
+    "txt", "md", "mdx", "rst", "rs", "ts", "tsx", "js", "jsx", "mjs", "cjs", "json", "toml", "yml",
+    "yaml", "css", "scss", "html", "vue", "svelte", "sh", "py", "go", "java", "kt", "swift", "c",
+    "cc", "cpp", "h", "hpp", "cs", "rb", "php", "scala", "lua", "sql", "graphql", "proto", "pdf",
+];
+const INDEXED_FILE_NAMES: &[&str] = &["Dockerfile", "Makefile", "justfile", "Procfile"];
+
+/// Vault partition holding one root's index.
+pub fn index_partition(root: &Path) -> String {
+    format!("workspace_index:{}", root.display())
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct IndexedFile {
+    content_hash: String,
+    modified: i64,
+    size_bytes: u64,
+    chunk_count: u32,
+}
+
+/// Indexed files under one root, keyed by absolute path.
+type RootState = HashMap<String, IndexedFile>;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct IndexStatus {
+    pub root: String,
+    pub indexed_files: usize,
+    pub indexed_chunks: usize,
+    /// True until the first full walk of the root finishes.
+    pub scanning: bool,
+    pub last_error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkspaceHit {
+    pub path: String,
+    /// 1-based, inclusive.
+    pub start_line: usize,
+    pub end_line: usize,
+    pub snippet: String,
+}
+
+struct RootHandle {
+    status: Arc<RwLock<IndexStatus>>,
+    /// Paths under the root that must not be indexed.
+    blocked: Arc<RwLock<Vec<PathBuf>>>,
+    /// Re-checks paths whose blocked state changed. Weak so that dropping
+    /// the watcher still closes the event channel and ends the root's task.
+    events: mpsc::WeakUnboundedSender<PathBuf>,
+    /// Held while the root's task indexes; false once the root is removed.
+    active: Arc<Mutex<bool>>,
+    _watcher: Option<RecommendedWatcher>,
+}
+
+#[derive(Clone)]
+pub struct WorkspaceIndexer {
+    manager: Arc<MemoryManager>,
+    state_dir: PathBuf,
+    roots: Arc<Mutex<HashMap<PathBuf, RootHandle>>>,
+}
+
+impl WorkspaceIndexer {
+    pub fn new(manager: Arc<MemoryManager>, app_data_dir: &Path) -> Self {
+        Self {
+            manager,
+            state_dir: app_data_dir.join(STATE_DIR),
+            roots: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Start indexing and watching any of `roots` not indexed yet. Returns
+    /// immediately; the first walk runs in the background. Nothing under
+    /// `blocked_paths` (absolute) is indexed, and files under newly blocked
+    /// paths are dropped from roots already being watched.
+    pub async fn ensure_roots(&self, roots: &[PathBuf], blocked_paths: &[PathBuf]) {
+        let mut handles = self.roots.lock().await;
+        for root in roots {
+            let mut blocked = blocked_paths
+                .iter()
+                .filter(|b| b.starts_with(root) || root.starts_with(b))
+                .cloned()
+                .collect::<Vec<_>>();
+            blocked.sort();
+            blocked.dedup();
+
+            if let Some(handle) = handles.get(root) {
+                let mut current = handle.blocked.write().await;
+                if *current != blocked {
+                    let changed = current
+                        .iter()
+                        .filter(|p| !blocked.contains(p))
+                        .chain(blocked.iter().filter(|p| !current.contains(p)))
+                        .cloned()
+                        .collect::<Vec<_>>();
+                    *current = blocked;
+                    if let Some(events) = handle.events.upgrade() {
+                        for path in changed {
+                            let _ = events.send(path);
+                        }
+                    }
+                }
+                continue;
+            }
+            if !root.is_dir() {
+                continue;
+            }
+
+            let status = Arc::new(RwLock::new(IndexStatus {
+                root: root.display().to_string(),
+                scanning: true,
+                ..Default::default()
+            }));
+            let blocked = Arc::new(RwLock::new(blocked));
+            let active = Arc::new(Mutex::new(true));
+            let (tx, rx) = mpsc::unbounded_channel::<PathBuf>();
+            let events = tx.downgrade();
+            let watcher = notify::recommended_watcher(move |res: notify::Result<notify::Event>| {
+                if let Ok(event) = res {
+                    if event.kind.is_create() || event.kind.is_modify() || event.kind.is_remove() {
+                        for path in event.paths {
+                            let _ = tx.send(path);
+                        }
+                    }
+                }
+            })
+            .and_then(|mut watcher| {
+                watcher.watch(root, RecursiveMode::Recursive)?;
+                Ok(watcher)
+            })
+            .map_err(|e| tracing::warn!("Failed to watch {}: {}", root.display(), e))
+            .ok();
+
+            handles.insert(
+                root.clone(),
+                RootHandle {
+                    status: status.clone(),
+                    blocked: blocked.clone(),
+                    events,
+                    active: active.clone(),
+                    _watcher: watcher,
+                },
+            );
+            let indexer = self.clone();
+            let root = root.clone();
+            tokio::spawn(async move {
+                indexer.run_root(root, status, blocked, active, rx).await;
+            });
+        }
+    }
+
+    /// Stop watching `roots` and delete their index, both the vault
+    /// partition and the saved state, e.g. when a workspace opts out.
+    pub async fn remove_roots(&self, roots: &[PathBuf]) {
+        for root in roots {
+            let Some(handle) = self.roots.lock().await.remove(root) else {
+                continue;
+            };
+            // Wait out any file being indexed; the task stops at its next step.
+            *handle.active.lock().await = false;
+            drop(handle);
+
+            if let Err(e) = self
+                .manager
+                .clear_workspace_memory(&index_partition(root))
+                .await
+            {
+                tracing::warn!("Failed to clear the index of {}: {}", root.display(), e);
+            }
+            let state_path = self.state_path(root);
+            if let Err(e) = tokio::fs::remove_file(&state_path).await {
+                if e.kind() != std::io::ErrorKind::NotFound {
+                    tracing::warn!("Failed to delete {}: {}", state_path.display(), e);
+                }
+            }
+        }
+    }
+
+    pub async fn status(&self, root: &Path) -> Option<IndexStatus> {
+        let status = self.roots.lock().await.get(root)?.status.clone();
+        let snapshot = status.read().await.clone();
+        Some(snapshot)
+    }
+
+    /// Search the given roots. Hits are interleaved by rank across roots.
+    pub async fn search(
+        &self,
+        roots: &[PathBuf],
+        query: &str,
+        limit: usize,
+    ) -> Result<Vec<WorkspaceHit>, String> {
+        let options = SearchOptions {
+            strategy: RetrievalStrategy::Hybrid,
+            rerank: true,
+        };
+        let mut per_root = Vec::with_capacity(roots.len());
+        for root in roots {
+            let result = self
+                .manager
+                .search_with_options(&index_partition(root), query, limit, &options)
+                .await
+                .map_err(|e| e.to_string())?;
+            per_root.push(
+                result
+                    .entries
+                    .into_iter()
+                    .filter_map(hit_from_entry)
+                    .collect::<Vec<_>>(),
+            );
+        }
+
+        let mut hits = Vec::new();
+        let mut rank = 0;
+        while hits.len() < limit && per_root.iter().any(|r| rank < r.len()) {
+            for root_hits in &per_root {
+                if let Some(hit) = root_hits.get(rank) {
+                    hits.push(hit.clone());
+                }
+            }
+            rank += 1;
+        }
+        hits.truncate(limit);
+        Ok(hits)
+    }
+
+    /// First walk of a root, then re-index on watcher events until the
+    /// watcher is dropped or the root is removed.
+    async fn run_root(
+        &self,
+        root: PathBuf,
+        status: Arc<RwLock<IndexStatus>>,
+        blocked: Arc<RwLock<Vec<PathBuf>>>,
+        active: Arc<Mutex<bool>>,
+        mut rx: mpsc::UnboundedReceiver<PathBuf>,
+    ) {
+        let state_path = self.state_path(&root);
+        let mut state: RootState = tokio::fs::read_to_string(&state_path)
+            .await
+            .ok()
+            .and_then(|body| serde_json::from_str(&body).ok())
+            .unwrap_or_default();
+
+        let walk_root = root.clone();
+        let walk_blocked = blocked.read().await.clone();
+        let (files, mut rules) = match tokio::task::spawn_blocking(move || {
+            let rules = IgnoreRules::new(&walk_root);
+            walk(&walk_root, rules, &walk_blocked)
+        })
+        .await
+        {
+            Ok(walked) => walked,
+            Err(e) => {
+                status.write().await.last_error = Some(format!("Walk failed: {}", e));
+                (Vec::new(), IgnoreRules::new(&root))
+            }
+        };
+
+        let seen = files
+            .iter()
+            .map(|p| p.to_string_lossy().to_string())
+            .collect::<HashSet<_>>();
+        let mut changes = 0usize;
+        for stale in state
+            .keys()
+            .filter(|path| !seen.contains(*path))
+            .cloned()
+            .collect::<Vec<_>>()
+        {
+            let running = active.lock().await;
+            if !*running {
+                return;
+            }
+            self.apply(&root, &mut state, &stale, false, &status).await;
+            changes += 1;
+        }
+        for file in &files {
+            let running = active.lock().await;
+            if !*running {
+                return;
+            }
+            if self
+                .apply(&root, &mut state, &file.to_string_lossy(), true, &status)
+                .await
+            {
+                changes += 1;
+                if changes % SAVE_EVERY_CHANGES == 0 {
+                    self.save_state(&state_path, &state).await;
+                    update_counts(&status, &state).await;
+                }
+            }
+        }
+        {
+            let running = active.lock().await;
+            if !*running {
+                return;
+            }
+            self.save_state(&state_path, &state).await;
+        }
+        update_counts(&status, &state).await;
+        status.write().await.scanning = false;
+
+        while let Some(first) = rx.recv().await {
+            let mut changed = HashSet::from([first]);
+            tokio::time::sleep(Duration::from_millis(WATCH_DEBOUNCE_MS)).await;
+            while let Ok(path) = rx.try_recv() {
+                changed.insert(path);
+            }
+
+            let running = active.lock().await;
+            if !*running {
+                return;
+            }
+            let blocked = blocked.read().await.clone();
+            let mut dirty = false;
+            for path in changed {
+                dirty |= self
+                    .handle_change(&root, &mut state, &mut rules, &blocked, &path, &status)
+                    .await;
+            }
+
+            if dirty {
+                self.save_state(&state_path, &state).await;
+                update_counts(&status, &state).await;
+            }
+        }
+    }
+
+    /// Bring the index up to date with one watcher event. Returns whether the
+    /// state changed.
+    async fn handle_change(
+        &self,
+        root: &Path,
+        state: &mut RootState,
+        rules: &mut IgnoreRules,
+        blocked: &[PathBuf],
+        path: &Path,
+        status: &RwLock<IndexStatus>,
+    ) -> bool {
+        if rules.reload_if_ignore_file(path) {
+            return false;
+        }
+        let key = path.to_string_lossy().to_string();
+        if is_blocked(path, blocked) || !path.exists() {
+            // Removed or blocked file, or every file under such a directory.
+            let prefix = format!("{}/", key.trim_end_matches('/'));
+            let removed = state
+                .keys()
+                .filter(|k| **k == key || k.starts_with(&prefix))
+                .cloned()
+                .collect::<Vec<_>>();
+            let mut dirty = false;
+            for removed_key in removed {
+                dirty |= self.apply(root, state, &removed_key, false, status).await;
+            }
+            return dirty;
+        }
+        if path.is_dir() {
+            if rules.is_ignored(path, true) {
+                return false;
+            }
+            // A directory moved, copied in or unblocked: index what is under it.
+            let dir = path.to_path_buf();
+            let taken = std::mem::replace(rules, IgnoreRules::new(root));
+            let walk_blocked = blocked.to_vec();
+            let walked =
+                tokio::task::spawn_blocking(move || walk(&dir, taken, &walk_blocked)).await;
+            let Ok((files, returned)) = walked else {
+                return false;
+            };
+            *rules = returned;
+            let mut dirty = false;
+            for file in files {
+                dirty |= self
+                    .apply(root, state, &file.to_string_lossy(), true, status)
+                    .await;
+            }
+            return dirty;
+        }
+        let keep = !rules.is_ignored(path, false);
+        self.apply(root, state, &key, keep, status).await
+    }
+
+    /// Index (`keep`) or drop one file, recording failures on the status.
+    /// Returns whether the state changed.
+    async fn apply(
+        &self,
+        root: &Path,
+        state: &mut RootState,
+        path: &str,
+        keep: bool,
+        status: &RwLock<IndexStatus>,
+    ) -> bool {
+        let result = if keep {
+            self.index_file(root, state, path).await
+        } else {
+            self.remove_file(root, state, path).await
+        };
+        match result {
+            Ok(changed) => changed,
+            Err(e) => {
+                tracing::warn!("Workspace index: {}", e);
+                status.write().await.last_error = Some(e);
+                false
+            }
+        }
+    }
+
+    async fn index_file(
+        &self,
+        root: &Path,
+        state: &mut RootState,
+        path: &str,
+    ) -> Result<bool, String> {
+        let metadata = match tokio::fs::metadata(path).await {
+            Ok(metadata) if metadata.is_file() => metadata,
+            _ => return self.remove_file(root, state, path).await,
+        };
+        if !is_indexable(Path::new(path), metadata.len()) {
+            return self.remove_file(root, state, path).await;
+        }
+        let modified = metadata
+            .modified()
+            .ok()
+            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+            .map_or(0, |d| d.as_secs() as i64);
+        if let Some(previous) = state.get(path) {
+            if previous.modified == modified && previous.size_bytes == metadata.len() {
+                return Ok(false);
+            }
+        }
+
+        let bytes = tokio::fs::read(path)
+            .await
+            .map_err(|e| format!("Failed to read {}: {}", path, e))?;
+        let content_hash = hex::encode(Sha256::digest(&bytes));
+        if let Some(previous) = state.get_mut(path) {
+            if previous.content_hash == content_hash {
+                // Touched but unchanged: nothing to re-embed.
+                previous.modified = modified;
+                previous.size_bytes = metadata.len();
+                return Ok(true);
+            }
+        }
+        if !has_extension(Path::new(path), "pdf") && bytes.contains(&0) {
+            return self.remove_file(root, state, path).await;
+        }
+
+        let size_bytes = bytes.len() as u64;
+        let text = extract_text(path, bytes).await?;
+        let partition = index_partition(root);
+        self.manager
+            .delete_source(&partition, path)
+            .await
+            .map_err(|e| e.to_string())?;
+        let chunk_count = if text.trim().is_empty() {
+            0
+        } else {
+            let relative = Path::new(path)
+                .strip_prefix(root)
+                .unwrap_or(Path::new(path))
+                .to_string_lossy()
+                .to_string();
+            self.manager
+                .ingest_text_detailed(
+                    &partition,
+                    path,
+                    &text,
+                    Some(vec![
+                        "workspace_index".to_string(),
+                        format!("path:{}", relative),
+                    ]),
+                    &ChunkOptions::default(),
+                )
+                .await
+                .map_err(|e| format!("Failed to index {}: {}", path, e))?
+                .chunks_ingested as u32
+        };
+
+        state.insert(
+            path.to_string(),
+            IndexedFile {
+                content_hash,
+                modified,
+                size_bytes,
+                chunk_count,
+            },
+        );
+        Ok(true)
+    }
+
+    async fn remove_file(
+        &self,
+        root: &Path,
+        state: &mut RootState,
+        path: &str,
+    ) -> Result<bool, String> {
+        if state.remove(path).is_none() {
+            return Ok(false);
+        }
+        self.manager
+            .delete_source(&index_partition(root), path)
+            .await
+            .map_err(|e| format!("Failed to drop {} from the index: {}", path, e))?;
+        Ok(true)
+    }
+
+    fn state_path(&self, root: &Path) -> PathBuf {
+        let key = hex::encode(Sha256::digest(root.to_string_lossy().as_bytes()));
+        self.state_dir.join(format!("{}.json", &key[..16]))
+    }
+
+    async fn save_state(&self, state_path: &Path, state: &RootState) {
+        let body = match serde_json::to_string(state) {
+            Ok(body) => body,
+            Err(e) => {
+                tracing::warn!("Failed to serialize workspace index state: {}", e);
+                return;
+            }
+        };
+        let _ = tokio::fs::create_dir_all(&self.state_dir).await;
+        if let Err(e) = tokio::fs::write(state_path, body).await {
+            tracing::warn!("Failed to persist workspace index state: {}", e);
+        }
+    }
+}
+
+/// Indexable files under `dir`, skipping ignored and blocked directories
+/// entirely.
+fn walk(dir: &Path, mut rules: IgnoreRules, blocked: &[PathBuf]) -> (Vec<PathBuf>, IgnoreRules) {
+    let mut files = Vec::new();
+    let mut entries = WalkDir::new(dir).follow_links(false).into_iter();
+    while let Some(entry) = entries.next() {
+        let Ok(entry) = entry else {
+            continue;
+        };
+        let is_dir = entry.file_type().is_dir();
+        if is_blocked(entry.path(), blocked)
+            || (entry.depth() > 0 && rules.is_ignored(entry.path(), is_dir))
+        {
+            if is_dir {
+                entries.skip_current_dir();
+            }
+            continue;
+        }
+        if entry.file_type().is_file() {
+            let len = entry.metadata().map(|m| m.len()).unwrap_or(u64::MAX);
+            if is_indexable(entry.path(), len) {
+                files.push(entry.into_path());
+            }
+        }
+    }
+    (files, rules)
+}
+
+fn is_blocked(path: &Path, blocked: &[PathBuf]) -> bool {
+    blocked.iter().any(|b| path.starts_with(b))
+}
+
+fn is_indexable(path: &Path, len: u64) -> bool {
+    let max_bytes = if has_extension(path, "pdf") {
+        MAX_INDEXED_PDF_BYTES
+    } else {
+        MAX_INDEXED_FILE_BYTES
+    };
+    if len > max_bytes {
+        return false;
+    }
+    let extension = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_lowercase());
+    match extension {
+        Some(extension) => INDEXED_EXTENSIONS.contains(&extension.as_str()),
+        None => path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .is_some_and(|name| INDEXED_FILE_NAMES.contains(&name)),
+    }
+}
+
+fn has_extension(path: &Path, extension: &str) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| e.eq_ignore_ascii_case(extension))
+}
+
+async fn update_counts(status: &RwLock<IndexStatus>, state: &RootState) {
+    let mut status = status.write().await;
+    status.indexed_files = state.values().filter(|f| f.chunk_count > 0).count();
+    status.indexed_chunks = state.values().map(|f| f.chunk_count as usize).sum();
+}
+
+fn hit_from_entry(entry: MemoryEntry) -> Option<WorkspaceHit> {
+    let tag = |key: &str| {
+        entry
+            .tags
+            .iter()
+            .find_map(|t| t.strip_prefix(key).map(str::to_string))
+    };
+    let path = tag("source:")?;
+    let (start_line, end_line) = tag("lines:")
+        .and_then(|lines| {
+            let (start, end) = lines.split_once('-')?;
+            Some((start.parse().ok()?, end.parse().ok()?))
+        })
+        .unwrap_or((1, 1));
+    Some(WorkspaceHit {
+        path,
+        start_line,
+        end_line,
+        snippet: entry.content.chars().take(SNIPPET_CHARS).collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::SystemTime;
+
+    async fn test_indexer() -> (WorkspaceIndexer, PathBuf) {
+        let dir = std::env::temp_dir().join(format!("workspace-index-{}", uuid::Uuid::new_v4()));
+        let root = dir.join("root");
+        std::fs::create_dir_all(&root).unwrap();
+        let manager = Arc::new(MemoryManager::for_tests(dir.join("vault")).await);
+        (WorkspaceIndexer::new(manager, &dir), root)
+    }
+
+    fn write(path: &Path, body: &str) {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, body).unwrap();
+    }
+
+    /// Ids of the chunks indexed from `path`.
+    async fn chunk_ids(indexer: &WorkspaceIndexer, root: &Path, path: &Path) -> Vec<String> {
+        let source = format!("source:{}", path.display());
+        let mut ids = indexer
+            .manager
+            .query_workspace_memory(&index_partition(root), "", 100)
+            .await
+            .unwrap()
+            .into_iter()
+            .filter(|entry| entry.tags.contains(&source))
+            .map(|entry| entry.id)
+            .collect::<Vec<_>>();
+        ids.sort();
+        ids
+    }
+
+    #[tokio::test]
+    async fn unchanged_files_are_skipped_and_modified_files_reindexed() {
+        let (indexer, root) = test_indexer().await;
+        let mut state = RootState::new();
+        let file = root.join("notes.md");
+        let key = file.to_string_lossy().to_string();
+        write(&file, "alpha release notes\n");
+
+        assert!(indexer.index_file(&root, &mut state, &key).await.unwrap());
+        let first = chunk_ids(&indexer, &root, &file).await;
+        assert!(!first.is_empty());
+
+        // Same mtime and size: the file is not even read.
+        assert!(!indexer.index_file(&root, &mut state, &key).await.unwrap());
+
+        // Touched but identical: the state takes the new mtime, the chunks stay.
+        let touched = SystemTime::now() - Duration::from_secs(3600);
+        std::fs::File::options()
+            .write(true)
+            .open(&file)
+            .unwrap()
+            .set_modified(touched)
+            .unwrap();
+        assert!(indexer.index_file(&root, &mut state, &key).await.unwrap());
+        assert_eq!(chunk_ids(&indexer, &root, &file).await, first);
+
+        write(&file, "beta release notes, rewritten\n");
+        assert!(indexer.index_file(&root, &mut state, &key).await.unwrap());
+        let second = chunk_ids(&indexer, &root, &file).await;
+        assert!(!second.is_empty());
+        assert!(second.iter().all(|id| !first.contains(id)));
+        assert_eq!(
+            state[&key].content_hash,
+            hex::encode(Sha256::digest(b"beta release notes, rewritten\n"))
+        );
+
+        let _ = std::fs::remove_dir_all(root.parent().unwrap());
+    }
+
+    #[tokio::test]
+    async fn deleted_and_blocked_paths_leave_the_index() {
+        let (indexer, root) = test_indexer().await;
+        let status = RwLock::new(IndexStatus::default());
+        let mut state = RootState::new();
+        let readme = root.join("readme.md");
+        let old_docs = root.join("docs/old");
+        let secrets = root.join("secrets");
+        write(&readme, "project readme");
+        write(&old_docs.join("a.md"), "old page a");
+        write(&old_docs.join("b.md"), "old page b");
+        write(&secrets.join("keys.md"), "deploy keys");
+
+        let blocked = vec![secrets.clone()];
+        let (files, mut rules) = walk(&root, IgnoreRules::new(&root), &blocked);
+        assert_eq!(files.len(), 3);
+        assert!(files.iter().all(|file| !file.starts_with(&secrets)));
+        for file in &files {
+            let key = file.to_string_lossy();
+            assert!(indexer.index_file(&root, &mut state, &key).await.unwrap());
+        }
+
+        std::fs::remove_dir_all(&old_docs).unwrap();
+        std::fs::remove_file(&readme).unwrap();
+        for removed in [&old_docs, &readme] {
+            let changed = indexer
+                .handle_change(&root, &mut state, &mut rules, &blocked, removed, &status)
+                .await;
+            assert!(changed);
+        }
+        assert!(state.is_empty());
+        let orphaned = chunk_ids(&indexer, &root, &old_docs.join("a.md")).await;
+        assert!(orphaned.is_empty());
+
+        // Unblocking indexes the directory; blocking it again drops it.
+        let changed = indexer
+            .handle_change(&root, &mut state, &mut rules, &[], &secrets, &status)
+            .await;
+        assert!(changed);
+        assert_eq!(state.len(), 1);
+        let changed = indexer
+            .handle_change(&root, &mut state, &mut rules, &blocked, &secrets, &status)
+            .await;
+        assert!(changed);
+        assert!(state.is_empty());
+        let orphaned = chunk_ids(&indexer, &root, &secrets.join("keys.md")).await;
+        assert!(orphaned.is_empty());
+
+        let _ = std::fs::remove_dir_all(root.parent().unwrap());
+    }
+
+    #[tokio::test]
+    async fn removed_roots_are_purged_and_no_longer_watched() {
+        let (indexer, root) = test_indexer().await;
+        let readme = root.join("readme.md");
+        write(&readme, "project readme");
+
+        indexer.ensure_roots(&[root.clone()], &[]).await;
+        for _ in 0..100 {
+            if indexer.status(&root).await.is_some_and(|s| !s.scanning) {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        assert!(!chunk_ids(&indexer, &root, &readme).await.is_empty());
+        let state_path = indexer.state_path(&root);
+        assert!(state_path.exists());
+
+        indexer.remove_roots(&[root.clone()]).await;
+        assert!(indexer.status(&root).await.is_none());
+        assert!(!state_path.exists());
+        assert!(chunk_ids(&indexer, &root, &readme).await.is_empty());
+
+        let notes = root.join("notes.md");
+        write(&notes, "written after opting out");
+        write(&readme, "project readme, edited after opting out");
+        tokio::time::sleep(Duration::from_millis(WATCH_DEBOUNCE_MS * 2)).await;
+        assert!(chunk_ids(&indexer, &root, &notes).await.is_empty());
+        assert!(chunk_ids(&indexer, &root, &readme).await.is_empty());
+        assert!(!state_path.exists());
+
+        let _ = std::fs::remove_dir_all(root.parent().unwrap());
+    }
+}
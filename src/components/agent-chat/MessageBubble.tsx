@@ -171,10 +171,17 @@ export function MessageBubble({
             {message.content ? (
               <MarkdownRenderer content={message.content} />
             ) : neuralState !== "idle" ? (
-              <NeuralStatus
-                state={neuralState}
-                toolName={message.activeToolName}
-              />
+              <>
+                <NeuralStatus
+                  state={neuralState}
+                  toolName={message.activeToolName}
+                />
+                {message.liveToolOutput && (
+                  <pre className="mt-2 max-h-48 overflow-auto rounded-lg bg-black/5 dark:bg-white/5 p-2 text-[11px] leading-snug font-mono whitespace-pre-wrap break-all text-muted-foreground">
+                    {message.liveToolOutput}
+                  </pre>
+                )}
+              </>
             ) : null}
           </div>
         </div>
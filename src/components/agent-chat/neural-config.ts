@@ -36,6 +36,8 @@ export const TOOL_STATE_MAP: Record<string, NeuralState> = {
   // Creating (Filesystem Write)
   write_file: "creating",
   append_file: "creating",
+  edit_file: "creating",
+  apply_patch: "creating",
   mkdir: "creating",
   move_file: "creating",
 
@@ -47,6 +49,7 @@ export const TOOL_STATE_MAP: Record<string, NeuralState> = {
   read_many_files: "observing",
   list_files: "observing",
   search_files: "observing",
+  semantic_search_workspace: "observing",
   file_exists: "observing",
   get_file_info: "observing",
   ingest_document: "observing",
@@ -70,6 +73,11 @@ export const TOOL_STATE_MAP: Record<string, NeuralState> = {
 
   // Executing (Shell/System)
   execute_command: "executing",
+  start_process: "executing",
+  write_process_stdin: "executing",
+  stop_process: "executing",
+  read_process_output: "observing",
+  list_processes: "observing",
   git_status: "executing",
   git_diff: "executing",
   git_log: "executing",
@@ -86,9 +94,12 @@ const TOOL_DISPLAY_NAMES: Record<string, string> = {
   read_many_files: "Reading Files",
   write_file: "Writing File",
   append_file: "Appending to File",
+  edit_file: "Editing File",
+  apply_patch: "Applying Patch",
   delete_file: "Deleting File",
   list_files: "Listing Files",
   search_files: "Searching Files",
+  semantic_search_workspace: "Searching Workspace",
   file_exists: "Checking File",
   get_file_info: "Inspecting File",
   ingest_document: "Ingesting Document",
@@ -101,6 +112,11 @@ const TOOL_DISPLAY_NAMES: Record<string, string> = {
   read_url: "Reading URL",
   browse_url: "Browsing URL",
   execute_command: "Running Command",
+  start_process: "Starting Process",
+  read_process_output: "Reading Process Output",
+  write_process_stdin: "Writing to Process",
+  list_processes: "Listing Processes",
+  stop_process: "Stopping Process",
   git_status: "Checking Git Status",
   git_diff: "Reading Git Diff",
   git_log: "Reading Git Log",
@@ -4,6 +4,7 @@ import { KNOWN_TOOL_NAMES, getToolAirlockLevel } from "../../../constants/toolPo
 import { PolicySection } from "./airlock/PolicySection";
 import { RateLimitsSection } from "./airlock/RateLimitsSection";
 import { ScopesSection } from "./airlock/ScopesSection";
+import { Switch } from "@heroui/react";
 import { Shield } from "lucide-react";
 
 interface AirlockPanelProps {
@@ -198,6 +199,26 @@ export function AirlockPanel({ airlock, onChange }: AirlockPanelProps) {
           }
         />
       </div>
+
+      <div className="rounded-2xl border border-border/20 bg-card/35 backdrop-blur-md p-5">
+        <Switch
+          isSelected={airlock.escalate_after_untrusted_content ?? false}
+          onChange={(escalate_after_untrusted_content) =>
+            onChange({
+              ...airlock,
+              escalate_after_untrusted_content,
+            })
+          }
+        >
+          <Switch.Control>
+            <Switch.Thumb />
+          </Switch.Control>
+          <span className="text-xs text-muted-foreground">
+            Require explicit approval for write tools after the agent reads web, browser, MCP or
+            third-party skill output
+          </span>
+        </Switch>
+      </div>
     </div>
   );
 }
@@ -69,6 +69,16 @@ export function MemoryPanel({ agentId, memoryConfig, onChange }: MemoryPanelProp
     });
   };
 
+  const updateLifecycle = (updates: Partial<MemoryConfig["lifecycle"]>) => {
+    onChange({
+      ...memoryConfig,
+      lifecycle: {
+        ...memoryConfig.lifecycle,
+        ...updates,
+      },
+    });
+  };
+
   const handleIndexFile = async () => {
     try {
       const selected = await open({
@@ -77,7 +87,22 @@ export function MemoryPanel({ agentId, memoryConfig, onChange }: MemoryPanelProp
         filters: [
           {
             name: "Knowledge Files",
-            extensions: ["md", "txt", "json", "csv", "yaml", "yml", "log"],
+            extensions: [
+              "md",
+              "txt",
+              "pdf",
+              "json",
+              "csv",
+              "yaml",
+              "yml",
+              "log",
+              "rs",
+              "py",
+              "ts",
+              "tsx",
+              "js",
+              "go",
+            ],
           },
         ],
       });
@@ -181,6 +206,15 @@ export function MemoryPanel({ agentId, memoryConfig, onChange }: MemoryPanelProp
               </ListBox>
             </Select.Popover>
           </Select>
+          <Switch
+            isSelected={memoryConfig.retrieval.rerank ?? false}
+            onChange={(rerank) => updateRetrieval({ rerank })}
+          >
+            <Switch.Control>
+              <Switch.Thumb />
+            </Switch.Control>
+            Rerank results
+          </Switch>
         </div>
 
         <div className="space-y-6">
@@ -302,6 +336,61 @@ export function MemoryPanel({ agentId, memoryConfig, onChange }: MemoryPanelProp
         </div>
       </section>
 
+      <section className="space-y-4 rounded-2xl border border-border/20 bg-card/35 backdrop-blur-md p-5">
+        <h4 className={sectionTitleClass}>Lifecycle</h4>
+        <div className="grid grid-cols-1 md:grid-cols-2 gap-4">
+          <Switch
+            isSelected={memoryConfig.lifecycle.maintenance_enabled}
+            onChange={(maintenance_enabled) =>
+              updateLifecycle({ maintenance_enabled })
+            }
+          >
+            <Switch.Control>
+              <Switch.Thumb />
+            </Switch.Control>
+            Expire and rescore in background
+          </Switch>
+          <Switch
+            isSelected={memoryConfig.lifecycle.dedup_similarity < 1}
+            onChange={(merge) =>
+              updateLifecycle({ dedup_similarity: merge ? 0.95 : 1 })
+            }
+          >
+            <Switch.Control>
+              <Switch.Thumb />
+            </Switch.Control>
+            Merge near-duplicates
+          </Switch>
+        </div>
+        <div className="space-y-2">
+          <div className="flex items-center justify-between">
+            <label className={sectionTitleClass}>Importance in ranking</label>
+            <span className="font-mono text-xs text-foreground">
+              {Math.round(memoryConfig.lifecycle.importance_weight * 100)}%
+            </span>
+          </div>
+          <Slider
+            minValue={0}
+            maxValue={50}
+            step={5}
+            value={Math.round(memoryConfig.lifecycle.importance_weight * 100)}
+            onChange={(value) =>
+              updateLifecycle({
+                importance_weight:
+                  (Array.isArray(value) ? Number(value[0] ?? 0) : Number(value)) /
+                  100,
+              })
+            }
+            className="max-w-full"
+          >
+            <Slider.Track className="h-1.5 bg-default-200 dark:bg-white/10 rounded-full">
+              <Slider.Fill className="bg-primary h-full rounded-full" />
+              <Slider.Thumb className="size-4 bg-background border-2 border-primary rounded-full shadow-md" />
+            </Slider.Track>
+          </Slider>
+        </div>
+      </section>
+
       <section className="space-y-4 rounded-2xl border border-border/20 bg-card/35 backdrop-blur-md p-5">
         <div className="flex items-center justify-between">
           <h4 className={sectionTitleClass}>Knowledge Files</h4>
@@ -1,5 +1,5 @@
 import type { AirlockConfig, AirlockRateLimits } from "../../../../types/airlock";
-import { Input } from "@heroui/react";
+import { Input, Switch } from "@heroui/react";
 import { inputClass, sectionTitleClass } from "./constants";
 
 interface RateLimitsSectionProps {
@@ -19,9 +19,9 @@ function RateLimitField({ title, value, onChange }: RateLimitFieldProps) {
       <h4 className={sectionTitleClass}>{title}</h4>
       <Input
         type="number"
-        min={1}
+        min={0}
         value={value}
-        onChange={(e) => onChange(Math.max(1, Number.parseInt(e.target.value || "1", 10)))}
+        onChange={(e) => onChange(Math.max(0, Number.parseInt(e.target.value || "0", 10)))}
         className={inputClass}
       />
     </div>
@@ -32,7 +32,7 @@ export function RateLimitsSection({ airlock, onRateLimitsChange }: RateLimitsSec
   return (
     <section className="grid grid-cols-1 md:grid-cols-2 gap-4">
       <RateLimitField
-        title="Requests / Minute"
+        title="Requests / Minute (0 = unlimited)"
         value={airlock.rate_limits.max_requests_per_minute}
         onChange={(max_requests_per_minute) =>
           onRateLimitsChange({
@@ -42,7 +42,7 @@ export function RateLimitsSection({ airlock, onRateLimitsChange }: RateLimitsSec
         }
       />
       <RateLimitField
-        title="Tokens / Day"
+        title="Tokens / Day (0 = unlimited)"
         value={airlock.rate_limits.max_tokens_per_day}
         onChange={(max_tokens_per_day) =>
           onRateLimitsChange({
@@ -51,6 +51,24 @@ export function RateLimitsSection({ airlock, onRateLimitsChange }: RateLimitsSec
           })
         }
       />
+      <div className="md:col-span-2">
+        <Switch
+          isSelected={airlock.rate_limits.on_limit === "fail"}
+          onChange={(fail) =>
+            onRateLimitsChange({
+              ...airlock.rate_limits,
+              on_limit: fail ? "fail" : "wait",
+            })
+          }
+        >
+          <Switch.Control>
+            <Switch.Thumb />
+          </Switch.Control>
+          <span className="text-xs text-muted-foreground">
+            Fail the run instead of waiting when the per-minute limit is reached
+          </span>
+        </Switch>
+      </div>
     </section>
   );
 }
@@ -41,13 +41,16 @@ export function createDefaultAgentSpec(id: string = crypto.randomUUID()): AgentS
       rate_limits: {
         max_requests_per_minute: 0,
         max_tokens_per_day: 0,
+        on_limit: "wait",
       },
+      escalate_after_untrusted_content: false,
     },
     memory_config: {
       strategy: "hybrid",
       retrieval: {
         retention_days: 0,
         max_tokens: 0,
+        rerank: false,
       },
       persistence: {
         cross_session: false,
@@ -58,6 +61,14 @@ export function createDefaultAgentSpec(id: string = crypto.randomUUID()): AgentS
         enabled: false,
         indexed_files: [],
       },
+      lifecycle: {
+        maintenance_enabled: true,
+        keep_importance_above: 0.65,
+        dedup_similarity: 0.95,
+        importance_weight: 0.15,
+        recency_weight: 0.15,
+        recency_half_life_days: 7,
+      },
     },
   };
 }
@@ -139,6 +150,9 @@ export function normalizeAgentSpec(raw: any): AgentSpec {
         ...defaults.airlock.rate_limits,
         ...(source.airlock?.rate_limits ?? {}),
       },
+      escalate_after_untrusted_content:
+        source.airlock?.escalate_after_untrusted_content ??
+        defaults.airlock.escalate_after_untrusted_content,
     },
     memory_config: {
       ...defaults.memory_config,
@@ -168,6 +182,10 @@ export function normalizeAgentSpec(raw: any): AgentSpec {
           ? sourceKnowledge.indexed_files
           : defaults.memory_config.knowledge.indexed_files,
       },
+      lifecycle: {
+        ...defaults.memory_config.lifecycle,
+        ...(sourceMemory.lifecycle ?? {}),
+      },
     },
     model:
       typeof source.model === "string" && source.model.trim().length > 0
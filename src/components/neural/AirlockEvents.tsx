@@ -85,6 +85,17 @@ export function AirlockEvents() {
               action.
             </p>
 
+            {request.commandRule && (
+              <div className="flex items-center gap-2 px-3 py-2 rounded-xl border border-white/5 bg-white/5">
+                <span className="text-[10px] font-medium text-white/40 uppercase tracking-wider shrink-0">
+                  Matched Rule
+                </span>
+                <span className="text-xs font-mono text-white/80 truncate">
+                  {request.commandRule}
+                </span>
+              </div>
+            )}
+
             <div className="rounded-2xl border border-white/5 bg-black/40 overflow-hidden">
               <div className="flex items-center gap-2 px-4 py-2 border-b border-white/5 bg-white/5">
                 <Terminal className="size-3 text-white/30" />
@@ -2,7 +2,7 @@ import { useState, useEffect } from "react";
 import { Zap, Bot, Database } from "lucide-react";
 import * as tauri from "../../../services/tauri";
 import { useAIProvider } from "../../../hooks";
-import { Select, ListBox, Card, Skeleton } from "@heroui/react";
+import { Select, ListBox, Card, Skeleton, Input } from "@heroui/react";
 
 const ModelCard = ({
   name,
@@ -37,29 +37,39 @@ export function ModelsTab() {
   const [geminiModels, setGeminiModels] = useState<string[]>([]);
   const [embedderProvider, setEmbedderProvider] = useState<string>("gemini");
   const [embedderModel, setEmbedderModel] = useState<string>(
-    "gemini-embedding-001",
+    "gemini-embedding-2-preview",
   );
+  const [embedderBaseUrl, setEmbedderBaseUrl] = useState<string>("");
+  const [embedderDimensions, setEmbedderDimensions] = useState<string>("");
+  const [embeddingProfile, setEmbeddingProfile] =
+    useState<tauri.EmbeddingProfile | null>(null);
   const { hasApiKey } = useAIProvider();
 
+  const refreshEmbeddingProfile = () =>
+    tauri
+      .getEmbeddingProfile()
+      .then(setEmbeddingProfile)
+      .catch(() => setEmbeddingProfile(null));
+
   useEffect(() => {
     async function loadData() {
       try {
-        const [rainyModels, geminiModelsList, eProvider, eModel] =
-          await Promise.all([
-            tauri.getProviderModels("rainy_api").catch(() => []),
-            tauri.getProviderModels("gemini").catch(() => []),
-            tauri.getEmbedderProvider().catch(() => "gemini"),
-            tauri.getEmbedderModel().catch(() => "gemini-embedding-001"),
-          ]);
+        const [rainyModels, geminiModelsList, settings] = await Promise.all([
+          tauri.getProviderModels("rainy_api").catch(() => []),
+          tauri.getProviderModels("gemini").catch(() => []),
+          tauri.getUserSettings().catch(() => null),
+        ]);
         setRainyApiModels(rainyModels || []);
         setGeminiModels(geminiModelsList || []);
-        setEmbedderProvider("gemini");
-        setEmbedderModel("gemini-embedding-001");
-
-        if (eProvider !== "gemini")
-          tauri.setEmbedderProvider("gemini").catch(console.error);
-        if (eModel !== "gemini-embedding-001")
-          tauri.setEmbedderModel("gemini-embedding-001").catch(console.error);
+        if (settings) {
+          setEmbedderProvider(settings.embedderProvider);
+          setEmbedderModel(settings.embedderModel);
+          setEmbedderBaseUrl(settings.embedderBaseUrl ?? "");
+          setEmbedderDimensions(
+            settings.embedderDimensions ? String(settings.embedderDimensions) : "",
+          );
+        }
+        await refreshEmbeddingProfile();
       } catch (error) {
         console.error("Failed to load settings:", error);
       } finally {
@@ -74,9 +84,13 @@ export function ModelsTab() {
     setEmbedderProvider(provider);
     try {
       await tauri.setEmbedderProvider(provider);
+      // The backend resets the model and vector size when the provider changes.
+      setEmbedderModel(await tauri.getEmbedderModel());
+      setEmbedderDimensions("");
     } catch (error) {
       console.error("Failed to save embedder provider:", error);
     }
+    await refreshEmbeddingProfile();
   };
 
   const handleEmbedderModelChange = async (model: string | null) => {
@@ -87,6 +101,27 @@ export function ModelsTab() {
     } catch (error) {
       console.error("Failed to save embedder model:", error);
     }
+    await refreshEmbeddingProfile();
+  };
+
+  const handleEmbedderBaseUrlCommit = async () => {
+    if (!embedderBaseUrl.trim()) return;
+    try {
+      await tauri.setEmbedderBaseUrl(embedderBaseUrl.trim());
+    } catch (error) {
+      console.error("Failed to save embedder base URL:", error);
+    }
+    await refreshEmbeddingProfile();
+  };
+
+  const handleEmbedderDimensionsCommit = async () => {
+    const parsed = Number.parseInt(embedderDimensions, 10);
+    try {
+      await tauri.setEmbedderDimensions(Number.isFinite(parsed) ? parsed : null);
+    } catch (error) {
+      console.error("Failed to save embedder dimensions:", error);
+    }
+    await refreshEmbeddingProfile();
   };
 
   if (isLoading) {
@@ -132,7 +167,6 @@ export function ModelsTab() {
                   if (!value) return;
                   handleEmbedderProviderChange(value);
                 }}
-                isDisabled
               >
                 <Select.Trigger className="h-10 px-4 bg-success/5 border border-success/10 rounded-xl hover:bg-success/10 text-foreground">
                   <Select.Value />
@@ -140,37 +174,90 @@ export function ModelsTab() {
                 </Select.Trigger>
                 <Select.Popover className="bg-background/95 dark:bg-background/35 border border-default-200/70 dark:border-white/15 backdrop-blur-xl">
                   <ListBox className="bg-transparent">
-                    <ListBox.Item id="gemini" textValue="Gemini (Active)">Gemini (Active)</ListBox.Item>
-                    <ListBox.Item id="openai" textValue="OpenAI (Coming Soon)">OpenAI (Coming Soon)</ListBox.Item>
+                    <ListBox.Item id="gemini" textValue="Gemini">Gemini</ListBox.Item>
+                    <ListBox.Item id="openai" textValue="OpenAI-compatible">OpenAI-compatible</ListBox.Item>
+                    <ListBox.Item id="local" textValue="Local (offline)">Local (offline)</ListBox.Item>
                   </ListBox>
                 </Select.Popover>
               </Select>
             </div>
 
-            <div className="space-y-1">
-              <label className="text-xs font-medium text-muted-foreground ml-1">Model</label>
-              <Select 
-                className="w-full"
-                selectedKey={embedderModel}
-                placeholder="Select model"
-                onSelectionChange={(selection) => {
-                  const value = selectionToValue(selection);
-                  if (!value) return;
-                  handleEmbedderModelChange(value);
-                }}
-                isDisabled
-              >
-                <Select.Trigger className="h-10 px-4 bg-success/5 border border-success/10 rounded-xl hover:bg-success/10 text-foreground">
-                  <Select.Value />
-                  <Select.Indicator />
-                </Select.Trigger>
-                <Select.Popover className="bg-background/95 dark:bg-background/35 border border-default-200/70 dark:border-white/15 backdrop-blur-xl">
-                  <ListBox className="bg-transparent">
-                    <ListBox.Item id="gemini-embedding-001" textValue="gemini-embedding-001 (3072d)">gemini-embedding-001 (3072d)</ListBox.Item>
-                  </ListBox>
-                </Select.Popover>
-              </Select>
-            </div>
+            {embedderProvider === "gemini" && (
+              <div className="space-y-1">
+                <label className="text-xs font-medium text-muted-foreground ml-1">Model</label>
+                <Select
+                  className="w-full"
+                  selectedKey={embedderModel}
+                  placeholder="Select model"
+                  onSelectionChange={(selection) => {
+                    const value = selectionToValue(selection);
+                    if (!value) return;
+                    handleEmbedderModelChange(value);
+                  }}
+                >
+                  <Select.Trigger className="h-10 px-4 bg-success/5 border border-success/10 rounded-xl hover:bg-success/10 text-foreground">
+                    <Select.Value />
+                    <Select.Indicator />
+                  </Select.Trigger>
+                  <Select.Popover className="bg-background/95 dark:bg-background/35 border border-default-200/70 dark:border-white/15 backdrop-blur-xl">
+                    <ListBox className="bg-transparent">
+                      <ListBox.Item id="gemini-embedding-2-preview" textValue="gemini-embedding-2-preview (3072d)">gemini-embedding-2-preview (3072d)</ListBox.Item>
+                      <ListBox.Item id="gemini-embedding-001" textValue="gemini-embedding-001 (3072d)">gemini-embedding-001 (3072d)</ListBox.Item>
+                    </ListBox>
+                  </Select.Popover>
+                </Select>
+              </div>
+            )}
+
+            {embedderProvider === "openai" && (
+              <>
+                <div className="space-y-1">
+                  <label className="text-xs font-medium text-muted-foreground ml-1">Base URL</label>
+                  <Input
+                    className="h-10 px-4 bg-success/5 border border-success/10 rounded-xl w-full"
+                    placeholder="http://localhost:11434/v1"
+                    value={embedderBaseUrl}
+                    onChange={(e) => setEmbedderBaseUrl(e.target.value)}
+                    onBlur={handleEmbedderBaseUrlCommit}
+                  />
+                </div>
+                <div className="space-y-1">
+                  <label className="text-xs font-medium text-muted-foreground ml-1">Model</label>
+                  <Input
+                    className="h-10 px-4 bg-success/5 border border-success/10 rounded-xl w-full"
+                    placeholder="text-embedding-3-small"
+                    value={embedderModel}
+                    onChange={(e) => setEmbedderModel(e.target.value)}
+                    onBlur={() => handleEmbedderModelChange(embedderModel.trim())}
+                  />
+                </div>
+                <div className="space-y-1">
+                  <label className="text-xs font-medium text-muted-foreground ml-1">Dimensions</label>
+                  <Input
+                    className="h-10 px-4 bg-success/5 border border-success/10 rounded-xl w-full"
+                    placeholder="Model default"
+                    inputMode="numeric"
+                    value={embedderDimensions}
+                    onChange={(e) => setEmbedderDimensions(e.target.value.replace(/\D/g, ""))}
+                    onBlur={handleEmbedderDimensionsCommit}
+                  />
+                </div>
+              </>
+            )}
+
+            {embedderProvider === "local" && (
+              <p className="text-xs text-muted-foreground ml-1 leading-relaxed">
+                Hashed word and character features computed on this device. No network
+                or API key needed; matches wording rather than meaning.
+              </p>
+            )}
+
+            {embeddingProfile && (
+              <p className="text-[10px] text-muted-foreground ml-1 italic opacity-60">
+                * Active: {embeddingProfile.provider}:{embeddingProfile.model} ({embeddingProfile.dim}d).
+                Existing memories are re-embedded in the background after a change.
+              </p>
+            )}
           </div>
         </section>
 
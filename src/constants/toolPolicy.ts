@@ -6,6 +6,7 @@ export type ToolSkillName =
   | "shell"
   | "web"
   | "browser"
+  | "memory"
   | "skills";
 
 type ToolPolicy = {
@@ -28,6 +29,10 @@ const TOOL_POLICY_MAP: Record<string, ToolPolicy> = {
     airlockLevel: AirlockLevels.Safe,
   },
   search_files: { skill: "filesystem", airlockLevel: AirlockLevels.Safe },
+  semantic_search_workspace: {
+    skill: "filesystem",
+    airlockLevel: AirlockLevels.Safe,
+  },
   file_exists: { skill: "filesystem", airlockLevel: AirlockLevels.Safe },
   get_file_info: { skill: "filesystem", airlockLevel: AirlockLevels.Safe },
   read_file_chunk: { skill: "filesystem", airlockLevel: AirlockLevels.Safe },
@@ -37,6 +42,8 @@ const TOOL_POLICY_MAP: Record<string, ToolPolicy> = {
   git_log: { skill: "shell", airlockLevel: AirlockLevels.Safe },
   git_show: { skill: "shell", airlockLevel: AirlockLevels.Safe },
   git_branch_list: { skill: "shell", airlockLevel: AirlockLevels.Safe },
+  read_process_output: { skill: "shell", airlockLevel: AirlockLevels.Safe },
+  list_processes: { skill: "shell", airlockLevel: AirlockLevels.Safe },
   web_search: { skill: "web", airlockLevel: AirlockLevels.Safe },
   read_web_page: { skill: "web", airlockLevel: AirlockLevels.Safe },
   http_get_json: { skill: "web", airlockLevel: AirlockLevels.Safe },
@@ -46,10 +53,14 @@ const TOOL_POLICY_MAP: Record<string, ToolPolicy> = {
   get_page_snapshot: { skill: "browser", airlockLevel: AirlockLevels.Safe },
   wait_for_selector: { skill: "browser", airlockLevel: AirlockLevels.Safe },
   extract_links: { skill: "browser", airlockLevel: AirlockLevels.Safe },
+  recall_entity: { skill: "memory", airlockLevel: AirlockLevels.Safe },
+  list_related: { skill: "memory", airlockLevel: AirlockLevels.Safe },
 
   // Level 1: state-changing but non-destructive
   write_file: { skill: "filesystem", airlockLevel: AirlockLevels.Sensitive },
   append_file: { skill: "filesystem", airlockLevel: AirlockLevels.Sensitive },
+  edit_file: { skill: "filesystem", airlockLevel: AirlockLevels.Sensitive },
+  apply_patch: { skill: "filesystem", airlockLevel: AirlockLevels.Sensitive },
   mkdir: { skill: "filesystem", airlockLevel: AirlockLevels.Sensitive },
   browse_url: { skill: "browser", airlockLevel: AirlockLevels.Sensitive },
   open_new_tab: { skill: "browser", airlockLevel: AirlockLevels.Sensitive },
@@ -57,9 +68,13 @@ const TOOL_POLICY_MAP: Record<string, ToolPolicy> = {
   navigate: { skill: "browser", airlockLevel: AirlockLevels.Sensitive },
   go_back: { skill: "browser", airlockLevel: AirlockLevels.Sensitive },
   type_text: { skill: "browser", airlockLevel: AirlockLevels.Sensitive },
+  remember_fact: { skill: "memory", airlockLevel: AirlockLevels.Sensitive },
+  stop_process: { skill: "shell", airlockLevel: AirlockLevels.Sensitive },
 
   // Level 2: destructive or command execution
   execute_command: { skill: "shell", airlockLevel: AirlockLevels.Dangerous },
+  start_process: { skill: "shell", airlockLevel: AirlockLevels.Dangerous },
+  write_process_stdin: { skill: "shell", airlockLevel: AirlockLevels.Dangerous },
   http_post_json: { skill: "web", airlockLevel: AirlockLevels.Dangerous },
   submit_form: { skill: "browser", airlockLevel: AirlockLevels.Dangerous },
   delete_file: { skill: "filesystem", airlockLevel: AirlockLevels.Dangerous },
@@ -16,5 +16,6 @@ export { useIntelligentRouter } from "./useIntelligentRouter";
 export { useNeuralService } from "./useNeuralService";
 export { useAirlock } from "./useAirlock";
 export { useMcpApprovals } from "./useMcpApprovals";
+export { useAgentProcesses } from "./useAgentProcesses";
 export { useUserProfile } from "./useUserProfile";
 export { useCloudBridgeStatus } from "./useCloudBridgeStatus";
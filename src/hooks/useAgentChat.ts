@@ -14,6 +14,16 @@ import {
   getToolDisplayName,
 } from "../components/agent-chat/neural-config";
 
+// How much of a running tool's output the chat keeps on screen.
+const LIVE_TOOL_OUTPUT_CHARS = 4000;
+
+// Tool calls and tool results are kept in history for the model; the chat
+// only shows the user's prompts and the agent's answers.
+function isVisiblePersistedMessage(msg: tauri.PersistedChatMessage): boolean {
+  if (msg.role === "tool") return false;
+  return !(msg.role === "assistant" && msg.tool_calls?.length);
+}
+
 export function useAgentChat() {
   const [messages, setMessages] = useState<AgentMessage[]>([]);
   const [isPlanning, setIsPlanning] = useState(false);
@@ -85,7 +95,7 @@ export function useAgentChat() {
   }, []);
 
   const mapPersistedRoleToUiType = useCallback(
-    (role: "user" | "assistant" | "system"): AgentMessage["type"] => {
+    (role: "user" | "assistant" | "system" | "tool"): AgentMessage["type"] => {
       if (role === "assistant") return "agent";
       if (role === "system") return "system";
       return "user";
@@ -136,16 +146,18 @@ export function useAgentChat() {
       const scope = await ensureChatScope();
       await refreshChatSession(scope);
       const window = await tauri.getChatHistoryWindow(scope, undefined, 100);
-      const hydratedMessages: AgentMessage[] = window.messages.map((msg) => ({
-        id: msg.id,
-        type: mapPersistedRoleToUiType(msg.role),
-        content: msg.content,
-        timestamp: new Date(msg.created_at),
-        ragTelemetry:
-          msg.role === "assistant"
-            ? { ...defaultRagTelemetry }
-            : undefined,
-      }));
+      const hydratedMessages: AgentMessage[] = window.messages
+        .filter(isVisiblePersistedMessage)
+        .map((msg) => ({
+          id: msg.id,
+          type: mapPersistedRoleToUiType(msg.role),
+          content: msg.content,
+          timestamp: new Date(msg.created_at),
+          ragTelemetry:
+            msg.role === "assistant"
+              ? { ...defaultRagTelemetry }
+              : undefined,
+        }));
       const runtimeTelemetry = await tauri.getChatRuntimeTelemetry(scope);
       if (runtimeTelemetry) {
         for (let i = hydratedMessages.length - 1; i >= 0; i -= 1) {
@@ -192,16 +204,18 @@ export function useAgentChat() {
         historyCursorRowid,
         100,
       );
-      const olderMessages: AgentMessage[] = window.messages.map((msg) => ({
-        id: msg.id,
-        type: mapPersistedRoleToUiType(msg.role),
-        content: msg.content,
-        timestamp: new Date(msg.created_at),
-        ragTelemetry:
-          msg.role === "assistant"
-            ? { ...defaultRagTelemetry }
-            : undefined,
-      }));
+      const olderMessages: AgentMessage[] = window.messages
+        .filter(isVisiblePersistedMessage)
+        .map((msg) => ({
+          id: msg.id,
+          type: mapPersistedRoleToUiType(msg.role),
+          content: msg.content,
+          timestamp: new Date(msg.created_at),
+          ragTelemetry:
+            msg.role === "assistant"
+              ? { ...defaultRagTelemetry }
+              : undefined,
+        }));
       setMessages((prev) => [...olderMessages, ...prev]);
       setHistoryCursorRowid(window.next_cursor_rowid ?? null);
       setHasMoreHistory(window.has_more);
@@ -935,6 +949,14 @@ export function useAgentChat() {
                       ),
                     ],
                     activeToolName: getToolDisplayName(functionName),
+                    liveToolOutput: undefined,
+                  };
+                }
+                case "tool_output_chunk": {
+                  const output = (m.liveToolOutput || "") + (payload.data?.text || "");
+                  return {
+                    ...m,
+                    liveToolOutput: output.slice(-LIVE_TOOL_OUTPUT_CHARS),
                   };
                 }
                 case "tool_result": {
@@ -965,6 +987,7 @@ export function useAgentChat() {
                       ),
                     ],
                     activeToolName: undefined,
+                    liveToolOutput: undefined,
                   };
                 }
                 case "thought":
@@ -982,6 +1005,37 @@ export function useAgentChat() {
                     activeToolName: undefined,
                   };
                 }
+                case "run_cancelled": {
+                  const interrupted: string[] = payload.data?.interrupted || [];
+                  const label =
+                    interrupted.length > 0
+                      ? `Run cancelled, interrupted: ${interrupted.join(", ")}`
+                      : "Run cancelled";
+                  return {
+                    ...m,
+                    runState: "cancelled",
+                    trace: [
+                      ...(m.trace || []),
+                      createTraceEntry("cancelled", label, undefined, payload.timestampMs),
+                    ],
+                    activeToolName: undefined,
+                  };
+                }
+                case "prompt_injection_detected": {
+                  const flags: string[] = payload.data?.flags || [];
+                  return {
+                    ...m,
+                    trace: [
+                      ...(m.trace || []),
+                      createTraceEntry(
+                        "error",
+                        `Possible prompt injection in ${payload.data?.tool || "tool"} output`,
+                        { preview: flags.join(", ") },
+                        payload.timestampMs,
+                      ),
+                    ],
+                  };
+                }
                 case "status": {
                   const statusText = String(payload.data || "");
                   const lower = statusText.toLowerCase();
@@ -0,0 +1,48 @@
+import { useCallback, useEffect, useState } from "react";
+import { listen } from "@tauri-apps/api/event";
+import {
+  listAgentProcesses,
+  stopAgentProcess,
+  type AgentProcessInfo,
+} from "../services/tauri";
+
+export function useAgentProcesses(workspaceId?: string) {
+  const [processes, setProcesses] = useState<AgentProcessInfo[]>([]);
+
+  useEffect(() => {
+    const upsert = (info: AgentProcessInfo) => {
+      if (workspaceId && info.workspace_id !== workspaceId) return;
+      setProcesses((prev) => [
+        ...prev.filter((item) => item.id !== info.id),
+        info,
+      ]);
+    };
+
+    const unlistenStarted = listen<AgentProcessInfo>("process:started", (event) =>
+      upsert(event.payload),
+    );
+    const unlistenExited = listen<AgentProcessInfo>("process:exited", (event) =>
+      upsert(event.payload),
+    );
+
+    listAgentProcesses(workspaceId)
+      .then((items) => items.forEach(upsert))
+      .catch((error) => {
+        console.error("Failed to load agent processes:", error);
+      });
+
+    return () => {
+      unlistenStarted.then((fn) => fn());
+      unlistenExited.then((fn) => fn());
+    };
+  }, [workspaceId]);
+
+  const stop = useCallback(async (info: AgentProcessInfo) => {
+    const stopped = await stopAgentProcess(info.workspace_id, info.id);
+    setProcesses((prev) =>
+      prev.map((item) => (item.id === stopped.id ? stopped : item)),
+    );
+  }, []);
+
+  return { processes, stop };
+}
@@ -28,6 +28,12 @@ export type AgentEvent =
       type: "tool_result";
       data: { id: string; result: string };
     }
+  | {
+      runId?: string;
+      timestampMs?: number;
+      type: "tool_output_chunk";
+      data: { id: string; stream: "stdout" | "stderr"; text: string };
+    }
   | {
       runId?: string;
       timestampMs?: number;
@@ -75,6 +81,18 @@ export type AgentEvent =
       timestampMs?: number;
       type: "supervisor_summary";
       data: any;
+    }
+  | {
+      runId?: string;
+      timestampMs?: number;
+      type: "run_cancelled";
+      data: { interrupted: string[] };
+    }
+  | {
+      runId?: string;
+      timestampMs?: number;
+      type: "prompt_injection_detected";
+      data: { tool_call_id: string; tool: string; flags: string[] };
     };
 
 export type AgentStatus = "idle" | "running" | "error" | "completed";
@@ -2,6 +2,7 @@
 // Typed wrappers for Tauri command invocation
 
 import { invoke, Channel } from "@tauri-apps/api/core";
+import type { CommandPolicy, SandboxConfig } from "../types/airlock";
 
 // ============ Types ============
 
@@ -441,8 +442,41 @@ export interface UserSettings {
   profile: UserProfile;
   embedderProvider: string;
   embedderModel: string;
+  embedderBaseUrl?: string;
+  embedderDimensions?: number | null;
+  specSignaturePolicy?: SpecSignaturePolicy;
+  trustedSpecSigners?: TrustedSigner[];
+  webSearch?: WebSearchConfig;
 }
 
+export type SpecSignaturePolicy = "require" | "warn" | "ignore";
+
+/** `rainy` uses Rainy cloud research; the others need no Rainy API key. */
+export type WebSearchProvider = "rainy" | "searxng" | "brave" | "tavily" | "open_search";
+
+export interface WebSearchConfig {
+  provider: WebSearchProvider;
+  /**
+   * SearXNG instance URL or OpenSearch description URL; for Brave and Tavily,
+   * an optional API endpoint override. Keys are stored with `storeApiKey`
+   * under `brave_search` and `tavily`.
+   */
+  baseUrl?: string | null;
+}
+
+export interface TrustedSigner {
+  signerId: string;
+  publicKeyHex: string;
+  label: string;
+}
+
+export type SpecTrust =
+  | { status: "verified"; signer_id: string }
+  | { status: "unsigned" }
+  | { status: "untrusted_signer"; signer_id: string }
+  | { status: "invalid"; reason: string }
+  | { status: "skipped" };
+
 export interface UserProfile {
   displayName: string;
   email: string;
@@ -500,6 +534,94 @@ export async function setEmbedderModel(model: string): Promise<void> {
   return invoke<void>("set_embedder_model", { model });
 }
 
+export interface EmbeddingProfile {
+  provider: string;
+  model: string;
+  dim: number;
+}
+
+export async function getEmbedderBaseUrl(): Promise<string> {
+  return invoke<string>("get_embedder_base_url");
+}
+
+export async function setEmbedderBaseUrl(baseUrl: string): Promise<void> {
+  return invoke<void>("set_embedder_base_url", { baseUrl });
+}
+
+export async function setEmbedderDimensions(dimensions: number | null): Promise<void> {
+  return invoke<void>("set_embedder_dimensions", { dimensions });
+}
+
+export async function getEmbeddingProfile(): Promise<EmbeddingProfile> {
+  return invoke<EmbeddingProfile>("get_embedding_profile");
+}
+
+export async function getWebSearchConfig(): Promise<WebSearchConfig> {
+  return invoke<WebSearchConfig>("get_web_search_config");
+}
+
+export async function setWebSearchConfig(config: WebSearchConfig): Promise<void> {
+  return invoke<void>("set_web_search_config", { config });
+}
+
+export async function getSpecSignaturePolicy(): Promise<SpecSignaturePolicy> {
+  return invoke<SpecSignaturePolicy>("get_spec_signature_policy");
+}
+
+export async function setSpecSignaturePolicy(policy: SpecSignaturePolicy): Promise<void> {
+  return invoke<void>("set_spec_signature_policy", { policy });
+}
+
+export async function getWorkspaceCommandPolicy(
+  workspaceId: string,
+): Promise<CommandPolicy | null> {
+  return invoke<CommandPolicy | null>("get_workspace_command_policy", { workspaceId });
+}
+
+/** `null` restores the built-in command allowlist. */
+export async function setWorkspaceCommandPolicy(
+  workspaceId: string,
+  policy: CommandPolicy | null,
+): Promise<void> {
+  return invoke<void>("set_workspace_command_policy", { workspaceId, policy });
+}
+
+export async function getWorkspaceSandbox(workspaceId: string): Promise<SandboxConfig | null> {
+  return invoke<SandboxConfig | null>("get_workspace_sandbox", { workspaceId });
+}
+
+/** `null` runs the workspace's commands unsandboxed. Rejects when the kernel lacks support. */
+export async function setWorkspaceSandbox(
+  workspaceId: string,
+  sandbox: SandboxConfig | null,
+): Promise<void> {
+  return invoke<void>("set_workspace_sandbox", { workspaceId, sandbox });
+}
+
+export async function getWorkspaceIndexed(workspaceId: string): Promise<boolean> {
+  return invoke<boolean>("get_workspace_indexed", { workspaceId });
+}
+
+/** Indexing sends the workspace's file contents to the configured embedder. */
+export async function setWorkspaceIndexed(workspaceId: string, enabled: boolean): Promise<void> {
+  return invoke<void>("set_workspace_indexed", { workspaceId, enabled });
+}
+
+export async function listTrustedSpecSigners(): Promise<TrustedSigner[]> {
+  return invoke<TrustedSigner[]>("list_trusted_spec_signers");
+}
+
+export async function addTrustedSpecSigner(
+  publicKeyHex: string,
+  label?: string,
+): Promise<TrustedSigner> {
+  return invoke<TrustedSigner>("add_trusted_spec_signer", { publicKeyHex, label });
+}
+
+export async function removeTrustedSpecSigner(signerId: string): Promise<void> {
+  return invoke<void>("remove_trusted_spec_signer", { signerId });
+}
+
 // ============ Workspace Types ============
 
 export interface AdvancedWorkspace {
@@ -882,6 +1004,7 @@ export interface Transaction {
   endTime?: string;
   operations: FileOpChange[];
   snapshots: FileVersion[];
+  origin?: OperationOrigin;
 }
 
 // ============ File Versioning Commands ============
@@ -910,8 +1033,9 @@ export async function restoreFileVersion(
 
 export async function beginFileTransaction(
   description: string,
+  taskId?: string,
 ): Promise<string> {
-  return invoke<string>("begin_file_transaction", { description });
+  return invoke<string>("begin_file_transaction", { description, taskId });
 }
 
 export async function commitFileTransaction(
@@ -932,6 +1056,33 @@ export async function getFileTransaction(
   return invoke<Transaction | null>("get_file_transaction", { transactionId });
 }
 
+// ============ Agent Run Checkpoints ============
+
+export type RunFileStatus = "created" | "modified" | "deleted" | "unchanged";
+
+export interface RunFileChange {
+  path: string;
+  status: RunFileStatus;
+  diff?: string;
+}
+
+export interface RunRevertPreview {
+  runId: string;
+  transactionId: string;
+  state: TransactionState;
+  changes: RunFileChange[];
+}
+
+export async function previewAgentRunRevert(
+  runId: string,
+): Promise<RunRevertPreview> {
+  return invoke<RunRevertPreview>("preview_agent_run_revert", { runId });
+}
+
+export async function revertAgentRun(runId: string): Promise<FileOpChange[]> {
+  return invoke<FileOpChange[]>("revert_agent_run", { runId });
+}
+
 // ============ Enhanced Undo/Redo Commands ============
 
 export async function undoFileOperationEnhanced(
@@ -954,6 +1105,58 @@ export async function listEnhancedFileOperations(): Promise<
   );
 }
 
+// ============ File Operation History ============
+
+export interface OperationOrigin {
+  workspaceId?: string;
+  taskId?: string;
+  runId?: string;
+}
+
+export type OperationStatus = "applied" | "undone";
+
+export interface FileOperationQuery {
+  workspaceId?: string;
+  taskId?: string;
+  runId?: string;
+  since?: string;
+  until?: string;
+  limit?: number;
+}
+
+export interface FileOperationEntry {
+  id: string;
+  description: string;
+  timestamp: string;
+  origin: OperationOrigin;
+  transactionId?: string;
+  status: OperationStatus;
+  undoable: boolean;
+  changes: FileOpChange[];
+}
+
+export interface HistoryGcReport {
+  operationsRemoved: number;
+  transactionsRemoved: number;
+  versionsRemoved: number;
+  trashEntriesRemoved: number;
+  bytesFreed: number;
+}
+
+export async function queryFileOperations(
+  query: FileOperationQuery = {},
+): Promise<FileOperationEntry[]> {
+  return invoke<FileOperationEntry[]>("query_file_operations", { query });
+}
+
+export async function collectFileHistoryGarbage(
+  maxAgeDays?: number,
+): Promise<HistoryGcReport> {
+  return invoke<HistoryGcReport>("collect_file_history_garbage", {
+    maxAgeDays,
+  });
+}
+
 export async function setFileOpsWorkspace(workspaceId: string): Promise<void> {
   return invoke<void>("set_file_ops_workspace", { workspaceId });
 }
@@ -1252,6 +1455,8 @@ export interface ApprovalRequest {
   payloadSummary: string;
   airlockLevel: AirlockLevel;
   timestamp: number;
+  /** Command policy rule(s) that allowed an `execute_command` call. */
+  commandRule?: string;
 }
 
 export interface ParameterSchema {
@@ -2065,6 +2270,14 @@ export async function deployAgentSpec(spec: any): Promise<any> {
   return invoke("deploy_agent_spec", { spec });
 }
 
+export async function signAgentSpec(spec: any): Promise<any> {
+  return invoke("sign_agent_spec", { spec });
+}
+
+export async function verifyAgentSpecSignature(spec: any): Promise<SpecTrust> {
+  return invoke<SpecTrust>("verify_agent_spec_signature", { spec });
+}
+
 export interface AgentKnowledgeFile {
   id: string;
   name: string;
@@ -2098,6 +2311,16 @@ export async function indexKnowledgeFile(
   });
 }
 
+export async function syncAgentKnowledge(
+  agentId: string,
+  knowledge: { enabled: boolean; indexed_files: AgentKnowledgeFile[] },
+): Promise<AgentKnowledgeFile[]> {
+  return invoke<AgentKnowledgeFile[]>("sync_agent_knowledge", {
+    agentId,
+    knowledge,
+  });
+}
+
 export async function queryAgentMemory(
   agentId: string,
   query: string,
@@ -2112,6 +2335,24 @@ export async function queryAgentMemory(
   });
 }
 
+export interface MemoryMaintenanceReport {
+  expired: number;
+  rescored: number;
+  merged: number;
+}
+
+export async function pinMemory(id: string, pinned: boolean): Promise<void> {
+  return invoke("pin_memory", { id, pinned });
+}
+
+export async function runMemoryMaintenance(
+  workspaceId: string,
+): Promise<MemoryMaintenanceReport> {
+  return invoke<MemoryMaintenanceReport>("run_memory_maintenance", {
+    workspaceId,
+  });
+}
+
 export async function resetNeuralWorkspace(
   masterKey: string,
   userApiKey: string,
@@ -2223,13 +2464,24 @@ export async function clearChatHistory(chatId: string): Promise<void> {
   return invoke<void>("clear_chat_history", { chatId });
 }
 
+export interface PersistedToolCall {
+  id: string;
+  type: string;
+  function: {
+    name: string;
+    arguments: string;
+  };
+}
+
 export interface PersistedChatMessage {
   id: string;
   chat_scope_id: string;
-  role: "user" | "assistant" | "system";
+  role: "user" | "assistant" | "system" | "tool";
   content: string;
   created_at: string;
   cursor_rowid: number;
+  tool_calls?: PersistedToolCall[] | null;
+  tool_call_id?: string | null;
 }
 
 export interface ChatHistoryWindow {
@@ -2265,6 +2517,19 @@ export interface ChatSession {
   updated_at: string;
   message_count: number;
   last_message_at: string | null;
+  parent_chat_id: string | null;
+  forked_from_message_id: string | null;
+}
+
+export interface ArchivedChatMessage {
+  id: string;
+  chat_scope_id: string;
+  summary_message_id: string;
+  generation: number;
+  role: "user" | "assistant" | "system" | "tool";
+  content: string;
+  created_at: string | null;
+  archived_at: string;
 }
 
 export interface EnsureChatTitleResult {
@@ -2321,6 +2586,38 @@ export async function ensureChatTitle(
   return invoke<EnsureChatTitleResult>("ensure_chat_title", input);
 }
 
+export async function getCompactedMessages(
+  chatScopeId: string,
+  summaryMessageId: string,
+): Promise<ArchivedChatMessage[]> {
+  return invoke<ArchivedChatMessage[]>("get_compacted_messages", {
+    chatScopeId,
+    summaryMessageId,
+  });
+}
+
+export async function expandChatCompaction(
+  chatScopeId: string,
+  summaryMessageId: string,
+): Promise<number> {
+  return invoke<number>("expand_chat_compaction", {
+    chatScopeId,
+    summaryMessageId,
+  });
+}
+
+export async function forkChatSession(
+  chatScopeId: string,
+  messageId: string,
+  title?: string,
+): Promise<ChatSession> {
+  return invoke<ChatSession>("fork_chat_session", {
+    chatScopeId,
+    messageId,
+    title,
+  });
+}
+
 export async function getChatCompactionState(
   chatScopeId: string,
 ): Promise<ChatCompactionState | null> {
@@ -2380,3 +2677,34 @@ export async function cancelAgentRun(
 ): Promise<CancelAgentRunResponse> {
   return invoke<CancelAgentRunResponse>("cancel_agent_run", { runId });
 }
+
+// ============ Agent Process Commands ============
+
+export type AgentProcessState = "running" | "exited" | "stopped";
+
+/** A process an agent started with `start_process`; fields are snake_case. */
+export interface AgentProcessInfo {
+  id: string;
+  workspace_id: string;
+  run_id: string | null;
+  command: string;
+  args: string[];
+  pid: number | null;
+  state: AgentProcessState;
+  exit_code: number | null;
+  started_at: number;
+  ended_at: number | null;
+}
+
+export async function listAgentProcesses(
+  workspaceId?: string,
+): Promise<AgentProcessInfo[]> {
+  return invoke<AgentProcessInfo[]>("list_agent_processes", { workspaceId });
+}
+
+export async function stopAgentProcess(
+  workspaceId: string,
+  processId: string,
+): Promise<AgentProcessInfo> {
+  return invoke<AgentProcessInfo>("stop_agent_process", { workspaceId, processId });
+}
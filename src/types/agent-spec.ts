@@ -77,4 +77,5 @@ export interface RuntimeConfig {
   mode?: "single" | "supervisor";
   max_specialists?: number;
   verification_required?: boolean;
+  max_parallel_tools?: number;
 }
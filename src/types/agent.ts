@@ -148,6 +148,8 @@ export interface AgentMessage {
   neuralState?: string;
   /** Name of the tool currently being executed by the agent */
   activeToolName?: string;
+  /** Latest output of the running tool, trimmed to its last few KB */
+  liveToolOutput?: string;
   supervisorPlan?: {
     summary: string;
     steps: string[];
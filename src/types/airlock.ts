@@ -13,9 +13,51 @@ export interface AirlockScopes {
   blocked_domains: string[];
 }
 
+export type RateLimitAction = "wait" | "fail";
+
+/** `0` means unlimited. */
 export interface AirlockRateLimits {
   max_requests_per_minute: number;
   max_tokens_per_day: number;
+  on_limit: RateLimitAction;
+}
+
+export type CommandRuleAction = "allow" | "deny";
+
+/** A rule for `execute_command`. Globs support `*` and `?`. */
+export interface CommandRule {
+  id?: string;
+  binary: string;
+  /** Globs the leading arguments must match, in order. */
+  args?: string[];
+  /** Globs that must each match some argument anywhere. */
+  args_contain?: string[];
+  action: CommandRuleAction;
+  /** Defaults to Dangerous. */
+  airlock_level?: AirlockLevel | null;
+}
+
+export interface CommandPolicy {
+  rules: CommandRule[];
+  /** `null` inherits the app environment and accepts no overrides. */
+  env_allowlist?: string[] | null;
+}
+
+export type SandboxBackend = "none" | "linux";
+
+/** OS-level confinement for `execute_command`, chosen per workspace. */
+export interface SandboxConfig {
+  backend: SandboxBackend;
+  /** Otherwise commands run in an empty network namespace. */
+  allow_network: boolean;
+  /** `~/` is expanded; missing paths are skipped. */
+  read_paths: string[];
+  /** Writable besides the workspace, e.g. package manager caches. */
+  write_paths: string[];
+  /** `0` means unlimited. */
+  max_cpu_seconds: number;
+  max_memory_mb: number;
+  max_processes: number;
 }
 
 export interface AirlockConfig {
@@ -23,4 +65,7 @@ export interface AirlockConfig {
   tool_levels: Record<string, AirlockLevel>;
   scopes: AirlockScopes;
   rate_limits: AirlockRateLimits;
+  escalate_after_untrusted_content?: boolean;
+  /** Narrows the workspace's command policy. */
+  command_policy?: CommandPolicy | null;
 }
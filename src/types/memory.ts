@@ -12,6 +12,8 @@ export interface MemoryConfig {
   retrieval: {
     retention_days: number;
     max_tokens: number;
+    rerank?: boolean;
+    chars_per_token?: number;
   };
   persistence: {
     cross_session: boolean;
@@ -22,4 +24,12 @@ export interface MemoryConfig {
     enabled: boolean;
     indexed_files: KnowledgeFile[];
   };
+  lifecycle: {
+    maintenance_enabled: boolean;
+    keep_importance_above: number;
+    dedup_similarity: number;
+    importance_weight: number;
+    recency_weight: number;
+    recency_half_life_days: number;
+  };
 }
@@ -11,4 +11,6 @@ export interface ApprovalRequest {
   payloadSummary: string;
   airlockLevel: AirlockLevel;
   timestamp: number;
+  /** Command policy rule(s) that allowed an `execute_command` call. */
+  commandRule?: string;
 }